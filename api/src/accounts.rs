@@ -6,11 +6,12 @@ use crate::{
     failpoint::fail_point,
     metrics::metrics,
     param::{AddressParam, LedgerVersionParam, MoveIdentifierParam, MoveStructTagParam},
+    precondition,
     version::Version,
 };
 
 use aptos_api_types::{
-    AccountData, Address, AsConverter, Error, LedgerInfo, MoveModuleBytecode, Response,
+    AccountData, Address, AsConverter, Error, ErrorCode, LedgerInfo, MoveModuleBytecode, Response,
     TransactionId,
 };
 use aptos_types::{
@@ -34,16 +35,29 @@ use warp::{filters::BoxedFilter, Filter, Rejection, Reply};
 pub fn get_account(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("accounts" / AddressParam)
         .and(warp::get())
+        .and(precondition::ledger_version_precondition(context.clone()))
         .and(context.filter())
         .and_then(handle_get_account)
         .with(metrics("get_account"))
         .boxed()
 }
 
+// HEAD /accounts/<address>
+pub fn head_account(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("accounts" / AddressParam)
+        .and(warp::head())
+        .and(precondition::ledger_version_precondition(context.clone()))
+        .and(context.filter())
+        .and_then(handle_head_account)
+        .with(metrics("head_account"))
+        .boxed()
+}
+
 // GET /accounts/<address>/resources
 pub fn get_account_resources(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("accounts" / AddressParam / "resources")
         .and(warp::get())
+        .and(precondition::ledger_version_precondition(context.clone()))
         .and(context.filter())
         .and(warp::query::<Version>())
         .map(|address, ctx, version: Version| (version.version, address, ctx))
@@ -57,6 +71,7 @@ pub fn get_account_resources(context: Context) -> BoxedFilter<(impl Reply,)> {
 pub fn get_account_modules(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("accounts" / AddressParam / "modules")
         .and(warp::get())
+        .and(precondition::ledger_version_precondition(context.clone()))
         .and(context.filter())
         .and(warp::query::<Version>())
         .map(|address, ctx, version: Version| (version.version, address, ctx))
@@ -74,6 +89,14 @@ async fn handle_get_account(
     Ok(Account::new(None, address, context)?.account()?)
 }
 
+async fn handle_head_account(
+    address: AddressParam,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_head_account")?;
+    Ok(Account::new(None, address, context)?.account_exists()?)
+}
+
 async fn handle_get_account_resources(
     ledger_version: Option<LedgerVersionParam>,
     address: AddressParam,
@@ -112,11 +135,22 @@ impl Account {
 
         if ledger_version > latest_ledger_info.version() {
             return Err(Error::not_found(
+                ErrorCode::VersionNotFound,
                 "ledger",
                 TransactionId::Version(ledger_version),
                 latest_ledger_info.version(),
             ));
         }
+        let oldest_state_version =
+            context.get_oldest_state_version(latest_ledger_info.version())?;
+        if ledger_version < oldest_state_version {
+            return Err(Error::version_pruned(
+                "state",
+                TransactionId::Version(ledger_version),
+                oldest_state_version,
+                latest_ledger_info.version(),
+            ));
+        }
 
         Ok(Self {
             ledger_version,
@@ -127,24 +161,29 @@ impl Account {
     }
 
     pub fn account(self) -> Result<impl Reply, Error> {
+        let account_resource: AccountResource =
+            bcs::from_bytes(&self.account_resource_bytes()?).map_err(anyhow::Error::from)?;
+
+        let account: AccountData = account_resource.into();
+
+        Response::new(self.latest_ledger_info, &account)
+    }
+
+    /// Answers like `account`, without the account data
+    pub fn account_exists(self) -> Result<impl Reply, Error> {
+        self.account_resource_bytes()?;
+        Ok(Response::empty(self.latest_ledger_info))
+    }
+
+    fn account_resource_bytes(&self) -> Result<Vec<u8>, Error> {
         let state_key = StateKey::AccessPath(AccessPath::resource_access_path(ResourceKey::new(
             self.address.into(),
             AccountResource::struct_tag(),
         )));
 
-        let state_value = self
-            .context
-            .get_state_value(&state_key, self.ledger_version)?;
-
-        let account_resource: AccountResource = state_value
-            .map(|bytes| bcs::from_bytes(&bytes))
-            .transpose()
-            .map_err(anyhow::Error::from)?
-            .ok_or_else(|| self.resource_not_found(&AccountResource::struct_tag()))?;
-
-        let account: AccountData = account_resource.into();
-
-        Response::new(self.latest_ledger_info, &account)
+        self.context
+            .get_state_value(&state_key, self.ledger_version)?
+            .ok_or_else(|| self.resource_not_found(&AccountResource::struct_tag()))
     }
 
     pub fn resources(self) -> Result<impl Reply, Error> {
@@ -219,6 +258,7 @@ impl Account {
 
     fn account_not_found(&self) -> Error {
         Error::not_found(
+            ErrorCode::AccountNotFound,
             "account",
             format!(
                 "address({}) and ledger version({})",
@@ -230,6 +270,7 @@ impl Account {
 
     fn resource_not_found(&self, struct_tag: &StructTag) -> Error {
         Error::not_found(
+            ErrorCode::ResourceNotFound,
             "resource",
             format!(
                 "address({}), struct tag({}) and ledger version({})",
@@ -241,6 +282,7 @@ impl Account {
 
     fn field_not_found(&self, struct_tag: &StructTag, field_name: &Identifier) -> Error {
         Error::not_found(
+            ErrorCode::ResourceNotFound,
             "resource",
             format!(
                 "address({}), struct tag({}), field name({}) and ledger version({})",
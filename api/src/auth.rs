@@ -0,0 +1,163 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{context::Context, metrics::API_KEY_REQUESTS};
+use anyhow::{ensure, format_err, Result};
+use aptos_api_types::Error;
+use aptos_config::config::{ApiEndpointClass, ApiKeyConfig};
+use aptos_infallible::Mutex;
+use aptos_rate_limiter::rate_limit::{Bucket, SharedBucket};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Instant,
+};
+use warp::{
+    filters::path::FullPath,
+    http::{Method, StatusCode},
+    Filter, Rejection,
+};
+
+/// The header clients pass their API key in
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Load balancers probe the node without a key, so the health check stays open.
+const HEALTH_CHECK_PATH: &str = "/-/healthy";
+
+/// The API keys accepted by the node. The API is open to everyone when there are none.
+#[derive(Clone, Default)]
+pub struct ApiKeys {
+    keys: Arc<HashMap<String, ApiKeyEntry>>,
+}
+
+struct ApiKeyEntry {
+    name: String,
+    endpoint_classes: HashSet<ApiEndpointClass>,
+    rate_limit: Option<SharedBucket>,
+}
+
+impl ApiKeys {
+    /// Reads the configured keys, failing if any of them can't be read.
+    pub fn new(configs: &[ApiKeyConfig]) -> Result<Self> {
+        let mut keys = HashMap::new();
+        for config in configs {
+            let key = config
+                .key
+                .read_key()
+                .map_err(|e| format_err!("failed to read API key {}: {}", config.name, e))?;
+            ensure!(!key.is_empty(), "API key {} is empty", config.name);
+
+            let rate_limit = match config.max_requests_per_sec {
+                Some(rate) => {
+                    ensure!(rate > 0, "API key {} has a rate limit of 0", config.name);
+                    let rate = rate as usize;
+                    Some(Arc::new(Mutex::new(Bucket::new(
+                        "api_key".to_owned(),
+                        String::new(),
+                        config.name.clone(),
+                        rate,
+                        rate,
+                        rate,
+                        None,
+                    ))))
+                }
+                None => None,
+            };
+            let entry = ApiKeyEntry {
+                name: config.name.clone(),
+                endpoint_classes: config.endpoint_classes.iter().copied().collect(),
+                rate_limit,
+            };
+            ensure!(
+                keys.insert(key, entry).is_none(),
+                "API key {} is configured more than once",
+                config.name
+            );
+        }
+        Ok(Self {
+            keys: Arc::new(keys),
+        })
+    }
+
+    /// The name of the key, none if it isn't one of the configured keys
+    pub fn key_name(&self, key: &str) -> Option<&str> {
+        self.keys.get(key).map(|entry| entry.name.as_str())
+    }
+
+    /// Checks the key is known, allowed on the endpoint class and within its rate limit.
+    pub fn authorize(&self, key: Option<&str>, class: ApiEndpointClass) -> Result<(), Error> {
+        if self.keys.is_empty() {
+            return Ok(());
+        }
+
+        let entry = key.and_then(|key| self.keys.get(key)).ok_or_else(|| {
+            Error::new(
+                StatusCode::UNAUTHORIZED,
+                format!("missing or invalid {} header", API_KEY_HEADER),
+            )
+        })?;
+        let record = |result: &str| {
+            API_KEY_REQUESTS
+                .with_label_values(&[entry.name.as_str(), class.as_str(), result])
+                .inc()
+        };
+
+        if !entry.endpoint_classes.contains(&class) {
+            record("forbidden");
+            return Err(Error::new(
+                StatusCode::FORBIDDEN,
+                format!(
+                    "API key {} is not allowed to call {} endpoints",
+                    entry.name,
+                    class.as_str()
+                ),
+            ));
+        }
+        if let Some(rate_limit) = &entry.rate_limit {
+            if let Err(ready_at) = rate_limit.lock().acquire_all_tokens(1) {
+                record("throttled");
+                let retry_after_secs = ready_at
+                    .map(|ready_at| ready_at.saturating_duration_since(Instant::now()))
+                    .map_or(1, |wait| wait.as_secs() + 1);
+                return Err(Error::too_many_requests(
+                    format!("API key {} is over its rate limit", entry.name),
+                    retry_after_secs,
+                ));
+            }
+        }
+        record("allowed");
+        Ok(())
+    }
+}
+
+/// Returns the class of the endpoint a request is for
+pub fn endpoint_class(method: &Method, path: &str) -> ApiEndpointClass {
+    if path.starts_with("/admin/") {
+        ApiEndpointClass::Admin
+    } else if method == Method::POST && path == "/transactions" {
+        ApiEndpointClass::Submit
+    } else {
+        ApiEndpointClass::Read
+    }
+}
+
+/// Lets the request through only if it carries an API key allowed on the endpoint it calls.
+/// Every request is let through when no keys are configured.
+pub fn api_key_auth(context: Context) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::method()
+        .and(warp::path::full())
+        .and(warp::header::optional::<String>(API_KEY_HEADER))
+        .and(context.filter())
+        .and_then(
+            |method: Method, path: FullPath, key: Option<String>, context: Context| async move {
+                if path.as_str() == HEALTH_CHECK_PATH {
+                    return Ok(());
+                }
+                context
+                    .api_keys()
+                    .authorize(key.as_deref(), endpoint_class(&method, path.as_str()))
+                    .map_err(warp::reject::custom)
+            },
+        )
+        .untuple_one()
+}
@@ -0,0 +1,130 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    context::Context,
+    failpoint::fail_point,
+    metrics::metrics,
+    param::{BlockHeightParam, Param},
+    precondition,
+};
+use aptos_api_types::{AsConverter, BlockTransactions, Error, ErrorCode, Response, Transaction};
+use serde::Deserialize;
+use warp::{filters::BoxedFilter, Filter, Rejection, Reply};
+
+/// Query flags of the block transactions endpoint. The events of the transactions are left out
+/// unless `with_events` is true.
+#[derive(Clone, Debug, Deserialize)]
+struct BlockTransactionsQuery {
+    with_events: Option<Param<bool>>,
+}
+
+// GET /blocks/{height}/transactions?with_events={bool}
+pub fn get_block_transactions(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("blocks" / BlockHeightParam / "transactions")
+        .and(warp::get())
+        .and(warp::query::<BlockTransactionsQuery>())
+        .and(precondition::ledger_version_precondition(context.clone()))
+        .and(context.filter())
+        .and_then(handle_get_block_transactions)
+        .with(metrics("get_block_transactions"))
+        .boxed()
+}
+
+async fn handle_get_block_transactions(
+    height: BlockHeightParam,
+    query: BlockTransactionsQuery,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_get_block_transactions")?;
+    let with_events = query
+        .with_events
+        .map(|v| v.parse("with_events"))
+        .unwrap_or(Ok(false))?;
+    Ok(block_transactions(
+        height.parse("block height")?,
+        with_events,
+        context,
+    )?)
+}
+
+/// Returns all the transactions of the block, with the proof of their inclusion against the
+/// ledger info of the response, so that indexers can ingest blocks in parallel. The boundaries of
+/// the block come from the `NewBlockEvent`s and aren't covered by the proof, so the block is
+/// checked to start with a block metadata transaction at least.
+fn block_transactions(
+    height: u64,
+    with_events: bool,
+    context: Context,
+) -> Result<impl Reply, Error> {
+    let ledger_info = context.get_latest_ledger_info()?;
+    let ledger_version = ledger_info.version();
+    let (first_version, last_version) = context
+        .get_block_versions(height, ledger_version)?
+        .ok_or_else(|| {
+            Error::not_found(
+                ErrorCode::BlockNotFound,
+                "block",
+                format!("height({})", height),
+                ledger_version,
+            )
+        })?;
+    if first_version < ledger_info.oldest_version() {
+        return Err(Error::version_pruned(
+            "block",
+            format!("height({})", height),
+            ledger_info.oldest_version(),
+            ledger_version,
+        ));
+    }
+
+    let (data, proof) = context.get_transactions_with_proof(
+        first_version,
+        last_version - first_version + 1,
+        ledger_version,
+    )?;
+    let starts_block = data.first().map_or(false, |txn| match txn.transaction {
+        aptos_types::transaction::Transaction::GenesisTransaction(_) => height == 0,
+        aptos_types::transaction::Transaction::BlockMetadata(_) => height > 0,
+        _ => false,
+    });
+    if !starts_block {
+        return Err(Error::internal(anyhow::format_err!(
+            "Block at height {} doesn't start with its block metadata transaction at version {}",
+            height,
+            first_version
+        )));
+    }
+    let mut timestamp = context.get_block_timestamp(first_version)?;
+    let resolver = context.move_resolver()?;
+    let converter = resolver.as_converter();
+    let transactions = data
+        .into_iter()
+        .map(|mut txn| {
+            if !with_events {
+                txn.events = vec![];
+            }
+            // The write set of the genesis holds the whole framework, so it's summarized as by
+            // default on the transaction endpoints
+            let txn = if matches!(
+                txn.transaction,
+                aptos_types::transaction::Transaction::GenesisTransaction(_)
+            ) {
+                converter.try_into_summarized_onchain_transaction(timestamp, txn)?
+            } else {
+                converter.try_into_onchain_transaction(timestamp, txn)?
+            };
+            timestamp = txn.timestamp();
+            Ok(txn)
+        })
+        .collect::<anyhow::Result<Vec<Transaction>>>()?;
+
+    let block = BlockTransactions {
+        height: height.into(),
+        first_version: first_version.into(),
+        last_version: last_version.into(),
+        transactions,
+        proof: bcs::to_bytes(&proof).map_err(anyhow::Error::from)?.into(),
+    };
+    Response::new(ledger_info, &block)
+}
@@ -1,17 +1,24 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use aptos_api_types::{Error, LedgerInfo, TransactionOnChainData};
+use crate::auth::ApiKeys;
+use aptos_api_types::{Error, LedgerInfo, Transaction, TransactionOnChainData};
 use aptos_config::config::ApiConfig;
 use aptos_crypto::HashValue;
-use aptos_mempool::{MempoolClientRequest, MempoolClientSender, SubmissionStatus};
+use aptos_infallible::Mutex;
+use aptos_mempool::{
+    MempoolClientRequest, MempoolClientSender, MempoolQueueStatus, ParkedAccount, SubmissionStatus,
+};
 use aptos_types::{
     account_address::AccountAddress,
     account_state::AccountState,
+    block_metadata::new_block_event_key,
     chain_id::ChainId,
     contract_event::ContractEvent,
     event::EventKey,
     ledger_info::LedgerInfoWithSignatures,
+    proof::{TransactionAccumulatorSummary, TransactionInfoListWithProof},
+    state_proof::StateProof,
     transaction::{SignedTransaction, TransactionWithProof},
 };
 use storage_interface::{DbReader, Order};
@@ -24,7 +31,7 @@ use aptos_types::{
 };
 use aptos_vm::data_cache::{IntoMoveResolver, RemoteStorageOwned};
 use futures::{channel::oneshot, SinkExt};
-use std::{convert::Infallible, sync::Arc};
+use std::{collections::HashMap, convert::Infallible, sync::Arc};
 use storage_interface::state_view::{DbStateView, DbStateViewAtVersion, LatestDbStateView};
 use warp::{filters::BoxedFilter, Filter, Reply};
 
@@ -35,6 +42,10 @@ pub struct Context {
     db: Arc<dyn DbReader>,
     mp_sender: MempoolClientSender,
     api_config: ApiConfig,
+    api_keys: ApiKeys,
+    /// Genesis transactions rendered with their whole write set, by version, as decoding the
+    /// write set of a genesis is expensive
+    full_genesis_transactions: Arc<Mutex<HashMap<Version, Transaction>>>,
 }
 
 impl Context {
@@ -43,12 +54,15 @@ impl Context {
         db: Arc<dyn DbReader>,
         mp_sender: MempoolClientSender,
         api_config: ApiConfig,
+        api_keys: ApiKeys,
     ) -> Self {
         Self {
             chain_id,
             db,
             mp_sender,
             api_config,
+            api_keys,
+            full_genesis_transactions: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -70,6 +84,42 @@ impl Context {
         self.api_config.content_length_limit()
     }
 
+    pub fn failpoints_admin_token(&self) -> Option<&str> {
+        self.api_config.failpoints_admin_token.as_deref()
+    }
+
+    pub fn mempool_backpressure_threshold_pct(&self) -> u64 {
+        self.api_config.mempool_backpressure_threshold_pct
+    }
+
+    pub fn mempool_backpressure_retry_after_secs(&self) -> u64 {
+        self.api_config.mempool_backpressure_retry_after_secs
+    }
+
+    pub fn ledger_version_precondition_max_wait_ms(&self) -> u64 {
+        self.api_config.ledger_version_precondition_max_wait_ms
+    }
+
+    pub fn api_keys(&self) -> &ApiKeys {
+        &self.api_keys
+    }
+
+    /// Returns the genesis transaction at `version` rendered with its whole write set, rendering it
+    /// with `render` the first time only
+    pub fn full_genesis_transaction(
+        &self,
+        version: Version,
+        render: impl FnOnce() -> Result<Transaction>,
+    ) -> Result<Transaction> {
+        let mut transactions = self.full_genesis_transactions.lock();
+        if let Some(txn) = transactions.get(&version) {
+            return Ok(txn.clone());
+        }
+        let txn = render()?;
+        transactions.insert(version, txn.clone());
+        Ok(txn)
+    }
+
     pub fn filter(self) -> impl Filter<Extract = (Context,), Error = Infallible> + Clone {
         warp::any().map(move || self.clone())
     }
@@ -84,13 +134,56 @@ impl Context {
         callback.await?
     }
 
+    pub async fn get_mempool_queue_status(&self) -> Result<MempoolQueueStatus> {
+        let (req_sender, callback) = oneshot::channel();
+        self.mp_sender
+            .clone()
+            .send(MempoolClientRequest::GetQueueStatus(req_sender))
+            .await?;
+
+        Ok(callback.await?)
+    }
+
+    pub async fn get_mempool_parked_accounts(&self) -> Result<Vec<ParkedAccount>> {
+        let (req_sender, callback) = oneshot::channel();
+        self.mp_sender
+            .clone()
+            .send(MempoolClientRequest::GetParkedAccounts(req_sender))
+            .await?;
+
+        Ok(callback.await?)
+    }
+
     pub fn get_latest_ledger_info(&self) -> Result<LedgerInfo, Error> {
         Ok(LedgerInfo::new(
             &self.chain_id(),
             &self.get_latest_ledger_info_with_signatures()?,
+            self.get_latest_state_checkpoint_version()?,
+            Some(self.get_oldest_ledger_version()?),
         ))
     }
 
+    pub fn get_latest_state_checkpoint_version(&self) -> Result<Option<u64>> {
+        self.db.get_latest_state_checkpoint_version()
+    }
+
+    /// The oldest version whose transactions the node still has, the older ones being pruned
+    pub fn get_oldest_ledger_version(&self) -> Result<u64> {
+        Ok(self.db.get_first_txn_version()?.unwrap_or(0))
+    }
+
+    /// The oldest version whose state the node still has, for a latest `ledger_version`. The
+    /// state store is pruned on a window of its own, usually shorter than the one of the
+    /// transactions, so the state at a version can be gone while its transaction is still there.
+    pub fn get_oldest_state_version(&self, ledger_version: u64) -> Result<u64> {
+        let oldest_state_version = match self.db.get_state_prune_window()? {
+            Some(window) => ledger_version.saturating_sub(window as u64),
+            None => 0,
+        };
+        // Nor is there state before the first transaction, e.g. on a node restored from a backup
+        Ok(oldest_state_version.max(self.get_oldest_ledger_version()?))
+    }
+
     pub fn get_latest_ledger_info_with_signatures(&self) -> Result<LedgerInfoWithSignatures> {
         self.db.get_latest_ledger_info()
     }
@@ -123,9 +216,21 @@ impl Context {
         limit: u16,
         ledger_version: u64,
     ) -> Result<Vec<TransactionOnChainData>> {
+        self.get_transactions_with_proof(start_version, limit as u64, ledger_version)
+            .map(|(txns, _)| txns)
+    }
+
+    /// The transactions from `start_version`, with the proof of their transaction infos against
+    /// the ledger info at `ledger_version`
+    pub fn get_transactions_with_proof(
+        &self,
+        start_version: u64,
+        limit: u64,
+        ledger_version: u64,
+    ) -> Result<(Vec<TransactionOnChainData>, TransactionInfoListWithProof)> {
         let data = self
             .db
-            .get_transaction_outputs(start_version, limit as u64, ledger_version)?;
+            .get_transaction_outputs(start_version, limit, ledger_version)?;
 
         let txn_start_version = data
             .first_transaction_output_version
@@ -137,19 +242,19 @@ impl Context {
             start_version
         );
 
-        let infos = data.proof.transaction_infos;
+        let proof = data.proof;
         let transactions_and_outputs = data.transactions_and_outputs;
 
         ensure!(
-            transactions_and_outputs.len() == infos.len(),
+            transactions_and_outputs.len() == proof.transaction_infos.len(),
             "invalid data size from database: {}, {}",
             transactions_and_outputs.len(),
-            infos.len(),
+            proof.transaction_infos.len(),
         );
 
-        transactions_and_outputs
+        let txns = transactions_and_outputs
             .into_iter()
-            .zip(infos.into_iter())
+            .zip(proof.transaction_infos.iter().cloned())
             .enumerate()
             .map(|(i, ((txn, txn_output), info))| {
                 let version = start_version + i as u64;
@@ -157,7 +262,43 @@ impl Context {
                 self.get_accumulator_root_hash(version)
                     .map(|h| (version, txn, info, events, h, write_set).into())
             })
-            .collect()
+            .collect::<Result<_>>()?;
+        Ok((txns, proof))
+    }
+
+    /// The first and the last version of the block at `height`, or `None` if it isn't committed
+    /// at `ledger_version`. A block starts with the block metadata transaction moving the chain
+    /// to its height, which emits the `NewBlockEvent` of sequence number `height - 1`, and ends
+    /// right before the next one. The genesis is the block at height 0. The last block committed
+    /// ends at `ledger_version`, as ledger infos are only committed at the end of blocks.
+    pub fn get_block_versions(
+        &self,
+        height: u64,
+        ledger_version: u64,
+    ) -> Result<Option<(u64, u64)>> {
+        let mut versions = self
+            .db
+            .get_events(
+                &new_block_event_key(),
+                height.saturating_sub(1),
+                Order::Ascending,
+                2,
+            )?
+            .into_iter()
+            .map(|(version, _)| version)
+            .filter(|version| *version <= ledger_version);
+        let first_version = if height == 0 {
+            0
+        } else {
+            match versions.next() {
+                Some(version) => version,
+                None => return Ok(None),
+            }
+        };
+        let last_version = versions
+            .next()
+            .map_or(ledger_version, |next_block_version| next_block_version - 1);
+        Ok(Some((first_version, last_version)))
     }
 
     pub fn get_account_transactions(
@@ -222,6 +363,19 @@ impl Context {
         self.db.get_accumulator_root_hash(version)
     }
 
+    pub fn get_state_proof(
+        &self,
+        known_version: u64,
+        ledger_info: LedgerInfoWithSignatures,
+    ) -> Result<StateProof> {
+        self.db
+            .get_state_proof_with_ledger_info(known_version, ledger_info)
+    }
+
+    pub fn get_accumulator_summary(&self, version: u64) -> Result<TransactionAccumulatorSummary> {
+        self.db.get_accumulator_summary(version)
+    }
+
     fn convert_into_transaction_on_chain_data(
         &self,
         txn: TransactionWithProof,
@@ -8,6 +8,7 @@ use crate::{
     metrics::metrics,
     page::Page,
     param::{AddressParam, EventKeyParam, MoveIdentifierParam, MoveStructTagParam},
+    precondition,
 };
 
 use aptos_api_types::{AsConverter, Error, LedgerInfo, Response};
@@ -21,6 +22,7 @@ pub fn get_events_by_event_key(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("events" / EventKeyParam)
         .and(warp::get())
         .and(warp::query::<Page>())
+        .and(precondition::ledger_version_precondition(context.clone()))
         .and(context.filter())
         .and_then(handle_get_events_by_event_key)
         .with(metrics("get_events_by_event_key"))
@@ -32,6 +34,7 @@ pub fn get_events_by_event_handle(context: Context) -> BoxedFilter<(impl Reply,)
     warp::path!("accounts" / AddressParam / "events" / MoveStructTagParam / MoveIdentifierParam)
         .and(warp::get())
         .and(warp::query::<Page>())
+        .and(precondition::ledger_version_precondition(context.clone()))
         .and(context.filter())
         .and_then(handle_get_events_by_event_handle)
         .with(metrics("get_events_by_event_handle"))
@@ -1,9 +1,17 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::{context::Context, metrics::metrics};
 #[allow(unused_imports)]
 use anyhow::{format_err, Result};
 use aptos_api_types::Error;
+use aptos_logger::prelude::*;
+use serde::{Deserialize, Serialize};
+use warp::{
+    filters::BoxedFilter,
+    http::{header::AUTHORIZATION, StatusCode},
+    reply, Filter, Rejection, Reply,
+};
 
 #[allow(unused_variables)]
 #[inline]
@@ -12,3 +20,107 @@ pub fn fail_point(name: &str) -> Result<(), Error> {
         Err(format_err!("unexpected internal error for {}", name).into())
     }))
 }
+
+/// A failpoint of the node and its actions, e.g. `api::endpoint_index` and `return`. See
+/// https://docs.rs/fail for the actions syntax.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Failpoint {
+    pub name: String,
+    pub actions: String,
+}
+
+// GET /admin/failpoints
+pub fn get_failpoints(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("admin" / "failpoints")
+        .and(warp::get())
+        .and(admin_auth(context))
+        .and_then(handle_get_failpoints)
+        .with(metrics("get_failpoints"))
+        .boxed()
+}
+
+// POST /admin/failpoints
+pub fn set_failpoint(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("admin" / "failpoints")
+        .and(warp::post())
+        .and(admin_auth(context))
+        .and(warp::body::json::<Failpoint>())
+        .and_then(handle_set_failpoint)
+        .with(metrics("set_failpoint"))
+        .boxed()
+}
+
+// DELETE /admin/failpoints/:name
+pub fn remove_failpoint(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("admin" / "failpoints" / String)
+        .and(warp::delete())
+        .and(admin_auth(context))
+        .and_then(handle_remove_failpoint)
+        .with(metrics("remove_failpoint"))
+        .boxed()
+}
+
+/// Lets the request through only if the admin routes are enabled and it carries the admin token.
+/// The routes don't exist at all when no token is configured.
+pub(crate) fn admin_auth(context: Context) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::header::optional::<String>(AUTHORIZATION.as_str())
+        .and(context.filter())
+        .and_then(
+            |authorization: Option<String>, context: Context| async move {
+                match context.failpoints_admin_token() {
+                    None => Err(warp::reject::not_found()),
+                    Some(token)
+                        if authorization.as_deref()
+                            == Some(format!("Bearer {}", token).as_str()) =>
+                    {
+                        Ok(())
+                    }
+                    Some(_) => Err(warp::reject::custom(Error::new(
+                        StatusCode::UNAUTHORIZED,
+                        "invalid admin token".to_owned(),
+                    ))),
+                }
+            },
+        )
+        .untuple_one()
+}
+
+fn ensure_failpoints_enabled() -> Result<(), Error> {
+    if fail::has_failpoints() {
+        Ok(())
+    } else {
+        Err(Error::bad_request(
+            "failpoints are not enabled in this binary",
+        ))
+    }
+}
+
+fn list_failpoints() -> Vec<Failpoint> {
+    fail::list()
+        .into_iter()
+        .map(|(name, actions)| Failpoint { name, actions })
+        .collect()
+}
+
+async fn handle_get_failpoints() -> Result<impl Reply, Rejection> {
+    ensure_failpoints_enabled()?;
+    Ok(reply::json(&list_failpoints()))
+}
+
+async fn handle_set_failpoint(failpoint: Failpoint) -> Result<impl Reply, Rejection> {
+    ensure_failpoints_enabled()?;
+    fail::cfg(&failpoint.name, &failpoint.actions).map_err(Error::bad_request)?;
+    warn!(
+        name = failpoint.name,
+        actions = failpoint.actions,
+        "Failpoint set through the admin API"
+    );
+    Ok(reply::json(&list_failpoints()))
+}
+
+async fn handle_remove_failpoint(name: String) -> Result<impl Reply, Rejection> {
+    ensure_failpoints_enabled()?;
+    fail::remove(&name);
+    warn!(name = name, "Failpoint removed through the admin API");
+    Ok(reply::json(&list_failpoints()))
+}
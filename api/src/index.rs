@@ -2,22 +2,24 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    accounts,
+    accounts, auth, blocks,
     context::Context,
     events,
-    failpoint::fail_point,
-    log,
+    failpoint::{self, fail_point},
+    log, mempool,
     metrics::{metrics, status_metrics},
-    state, transactions,
+    precondition, state, state_proof, transactions,
+};
+use aptos_api_types::{
+    Error, ErrorCode, Response, X_APTOS_LEDGER_VERSION, X_APTOS_OLDEST_LEDGER_VERSION,
 };
-use aptos_api_types::{Error, Response};
 
 use std::convert::Infallible;
 use warp::{
     body::BodyDeserializeError,
     cors::CorsForbidden,
     filters::BoxedFilter,
-    http::{header, HeaderValue, StatusCode},
+    http::{header, HeaderName, HeaderValue, StatusCode},
     reject::{LengthRequired, MethodNotAllowed, PayloadTooLarge, UnsupportedMediaType},
     reply, Filter, Rejection, Reply,
 };
@@ -26,32 +28,48 @@ const OPEN_API_HTML: &str = include_str!("../doc/spec.html");
 const OPEN_API_SPEC: &str = include_str!("../doc/openapi.yaml");
 
 pub fn routes(context: Context) -> impl Filter<Extract = impl Reply, Error = Infallible> + Clone {
-    index(context.clone())
-        .or(openapi_spec())
-        .or(accounts::get_account(context.clone()))
-        .or(accounts::get_account_resources(context.clone()))
-        .or(accounts::get_account_modules(context.clone()))
-        .or(transactions::get_transaction(context.clone()))
-        .or(transactions::get_transactions(context.clone()))
-        .or(transactions::get_account_transactions(context.clone()))
-        .or(transactions::submit_bcs_transactions(context.clone()))
-        .or(transactions::submit_json_transactions(context.clone()))
-        .or(transactions::create_signing_message(context.clone()))
-        .or(events::get_events_by_event_key(context.clone()))
-        .or(events::get_events_by_event_handle(context.clone()))
-        .or(state::get_account_resource(context.clone()))
-        .or(state::get_account_module(context.clone()))
-        .or(state::get_table_item(context.clone()))
-        .or(context.health_check_route().with(metrics("health_check")))
+    let routes = auth::api_key_auth(context.clone())
+        .and(
+            index(context.clone())
+                .or(openapi_spec())
+                .or(accounts::get_account(context.clone()))
+                .or(accounts::head_account(context.clone()))
+                .or(accounts::get_account_resources(context.clone()))
+                .or(accounts::get_account_modules(context.clone()))
+                .or(transactions::get_transaction(context.clone()))
+                .or(transactions::get_transactions(context.clone()))
+                .or(transactions::get_account_transactions(context.clone()))
+                .or(transactions::submit_bcs_transactions(context.clone()))
+                .or(transactions::submit_json_transactions(context.clone()))
+                .or(transactions::create_signing_message(context.clone()))
+                .or(blocks::get_block_transactions(context.clone()))
+                .or(events::get_events_by_event_key(context.clone()))
+                .or(events::get_events_by_event_handle(context.clone()))
+                .or(state::get_account_resource(context.clone()))
+                .or(state::get_account_module(context.clone()))
+                .or(state::head_account_resource(context.clone()))
+                .or(state::head_account_module(context.clone()))
+                .or(state::get_table_item(context.clone()))
+                .or(state_proof::get_state_proof(context.clone()))
+                .or(context.health_check_route().with(metrics("health_check")))
+                .or(failpoint::get_failpoints(context.clone()))
+                .or(failpoint::set_failpoint(context.clone()))
+                .or(failpoint::remove_failpoint(context.clone()))
+                .or(mempool::get_parked_accounts(context.clone())),
+        )
         .with(
             warp::cors()
                 .allow_any_origin()
-                .allow_methods(vec!["POST", "GET"])
-                .allow_headers(vec![header::CONTENT_TYPE]),
+                .allow_methods(vec!["POST", "GET", "HEAD"])
+                .allow_headers(vec![
+                    header::CONTENT_TYPE,
+                    HeaderName::from_static(auth::API_KEY_HEADER),
+                    HeaderName::from_static("x-request-id"),
+                    HeaderName::from_static("x-aptos-if-ledger-version-at-least"),
+                ]),
         )
-        .recover(handle_rejection)
-        .with(log::logger())
-        .with(status_metrics())
+        .recover(handle_rejection);
+    log::logger(routes, context.api_keys().clone()).with(status_metrics())
 }
 
 // GET /openapi.yaml
@@ -74,6 +92,7 @@ pub fn openapi_spec() -> BoxedFilter<(impl Reply,)> {
 pub fn index(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path::end()
         .and(warp::get())
+        .and(precondition::ledger_version_precondition(context.clone()))
         .and(context.filter())
         .and_then(handle_index)
         .with(metrics("get_ledger_info"))
@@ -101,7 +120,9 @@ async fn handle_rejection(err: Rejection) -> Result<impl Reply, Infallible> {
         body = reply::json(&Error::new(code, cause.to_string()));
     } else if let Some(cause) = err.find::<BodyDeserializeError>() {
         code = StatusCode::BAD_REQUEST;
-        body = reply::json(&Error::new(code, cause.to_string()));
+        body = reply::json(
+            &Error::new(code, cause.to_string()).error_code(ErrorCode::InvalidRequestBody),
+        );
     } else if let Some(cause) = err.find::<LengthRequired>() {
         code = StatusCode::LENGTH_REQUIRED;
         body = reply::json(&Error::new(code, cause.to_string()));
@@ -121,6 +142,20 @@ async fn handle_rejection(err: Rejection) -> Result<impl Reply, Infallible> {
     let mut rep = reply::with_status(body, code).into_response();
     rep.headers_mut()
         .insert("access-control-allow-origin", HeaderValue::from_static("*"));
+    if let Some(ledger_version) = err.find::<Error>().and_then(|e| e.aptos_ledger_version) {
+        rep.headers_mut()
+            .insert(X_APTOS_LEDGER_VERSION, HeaderValue::from(ledger_version.0));
+    }
+    if let Some(oldest_version) = err.find::<Error>().and_then(|e| e.oldest_ledger_version) {
+        rep.headers_mut().insert(
+            X_APTOS_OLDEST_LEDGER_VERSION,
+            HeaderValue::from(oldest_version.0),
+        );
+    }
+    if let Some(retry_after_secs) = err.find::<Error>().and_then(|e| e.retry_after_secs) {
+        rep.headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(retry_after_secs.0));
+    }
     Ok(rep)
 }
 
@@ -2,16 +2,21 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod accounts;
+mod auth;
+mod blocks;
 mod context;
 mod events;
 mod health_check;
 mod index;
 pub(crate) mod log;
+mod mempool;
 mod metrics;
 mod page;
 pub(crate) mod param;
+mod precondition;
 pub mod runtime;
 mod state;
+mod state_proof;
 mod transactions;
 pub(crate) mod version;
 
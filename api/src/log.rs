@@ -1,46 +1,142 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use std::time::Duration;
+use std::{
+    convert::Infallible,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
 
+use crate::auth::{ApiKeys, API_KEY_HEADER};
+use aptos_api_types::{Error, X_REQUEST_ID};
 use aptos_logger::{
     debug, error,
     prelude::{sample, SampleRate},
     sample::Sampling,
     Schema,
 };
+use hyper::Body;
 use warp::{
-    http::header,
-    log::{custom, Info, Log},
+    filters::path::FullPath,
+    http::{header, HeaderMap, HeaderValue, Method},
+    reply::Response,
+    Filter, Reply,
 };
 
-pub fn logger() -> Log<impl Fn(Info) + Copy> {
-    let func = move |info: Info| {
-        let status = info.status().as_u16();
-        let log = HttpRequestLog {
-            remote_addr: info.remote_addr(),
-            method: info.method().to_string(),
-            path: info.path().to_string(),
-            status,
-            referer: info.referer(),
-            user_agent: info.user_agent(),
-            elapsed: info.elapsed(),
-            forwarded: info
-                .request_headers()
-                .get(header::FORWARDED)
-                .and_then(|v| v.to_str().ok()),
-        };
-        if status >= 500 {
-            sample!(SampleRate::Duration(Duration::from_secs(1)), error!(log));
-        } else {
-            debug!(log);
+/// Longest request id taken from a client, longer ones are replaced by a generated id
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+/// Logs every request of `routes` with an id, taken from its `X-Request-Id` header or else
+/// generated. The response carries the id in the same header, and errors in their body too, so
+/// that the failures clients run into can be found in the logs of the node. Requests made with
+/// one of `api_keys` are logged with the name of the key.
+pub fn logger<F, R>(
+    routes: F,
+    api_keys: ApiKeys,
+) -> impl Filter<Extract = (Response,), Error = Infallible> + Clone
+where
+    F: Filter<Extract = (R,), Error = Infallible> + Clone + Send + Sync + 'static,
+    R: Reply,
+{
+    warp::any()
+        .map(Instant::now)
+        .and(warp::addr::remote())
+        .and(warp::method())
+        .and(warp::path::full())
+        .and(warp::header::headers_cloned())
+        .and(routes)
+        .then(move |start, remote_addr, method, path, headers, reply: R| {
+            log_request(
+                start,
+                remote_addr,
+                method,
+                path,
+                headers,
+                reply.into_response(),
+                api_keys.clone(),
+            )
+        })
+}
+
+async fn log_request(
+    start: Instant,
+    remote_addr: Option<SocketAddr>,
+    method: Method,
+    path: FullPath,
+    headers: HeaderMap,
+    response: Response,
+    api_keys: ApiKeys,
+) -> Response {
+    let request_id = headers
+        .get(X_REQUEST_ID)
+        .and_then(|id| id.to_str().ok())
+        .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LENGTH)
+        .map(str::to_owned)
+        .unwrap_or_else(new_request_id);
+    let elapsed = start.elapsed();
+
+    let mut response = if response.status().is_success() {
+        response
+    } else {
+        with_request_id_in_error(response, &request_id).await
+    };
+    // The id is either a header value already or generated from hex digits
+    response.headers_mut().insert(
+        X_REQUEST_ID,
+        HeaderValue::from_str(&request_id).expect("Invalid request id"),
+    );
+
+    let request_header = |name| headers.get(name).and_then(|v| v.to_str().ok());
+    let status = response.status().as_u16();
+    let log = HttpRequestLog {
+        request_id: &request_id,
+        remote_addr,
+        method: method.to_string(),
+        path: path.as_str().to_string(),
+        status,
+        referer: request_header(header::REFERER),
+        user_agent: request_header(header::USER_AGENT),
+        elapsed,
+        forwarded: request_header(header::FORWARDED),
+        api_key: headers
+            .get(API_KEY_HEADER)
+            .and_then(|key| key.to_str().ok())
+            .and_then(|key| api_keys.key_name(key)),
+    };
+    if status >= 500 {
+        sample!(SampleRate::Duration(Duration::from_secs(1)), error!(log));
+    } else {
+        debug!(log);
+    }
+    response
+}
+
+fn new_request_id() -> String {
+    format!("{:032x}", rand::random::<u128>())
+}
+
+/// Adds the request id to the error in the body of `response`. Bodies which aren't an error, e.g.
+/// those of HEAD requests, are left as is.
+async fn with_request_id_in_error(response: Response, request_id: &str) -> Response {
+    let (mut parts, body) = response.into_parts();
+    let bytes = match hyper::body::to_bytes(body).await {
+        Ok(bytes) => bytes,
+        Err(_) => return Response::from_parts(parts, Body::empty()),
+    };
+    let body = match serde_json::from_slice::<Error>(&bytes) {
+        Ok(mut error) => {
+            error.request_id = Some(request_id.to_owned());
+            parts.headers.remove(header::CONTENT_LENGTH);
+            serde_json::to_vec(&error).map_or(bytes, Into::into)
         }
+        Err(_) => bytes,
     };
-    custom(func)
+    Response::from_parts(parts, body.into())
 }
 
 #[derive(Schema)]
 struct HttpRequestLog<'a> {
+    request_id: &'a str,
     #[schema(display)]
     remote_addr: Option<std::net::SocketAddr>,
     method: String,
@@ -51,4 +147,6 @@ struct HttpRequestLog<'a> {
     #[schema(debug)]
     elapsed: std::time::Duration,
     forwarded: Option<&'a str>,
+    /// Name of the API key the request was made with
+    api_key: Option<&'a str>,
 }
@@ -0,0 +1,27 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{context::Context, failpoint::admin_auth, metrics::metrics};
+use aptos_api_types::Error;
+use warp::{filters::BoxedFilter, reply, Filter, Rejection, Reply};
+
+// GET /admin/mempool/parked
+pub fn get_parked_accounts(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("admin" / "mempool" / "parked")
+        .and(warp::get())
+        .and(admin_auth(context.clone()))
+        .and(context.filter())
+        .and_then(handle_get_parked_accounts)
+        .with(metrics("get_parked_accounts"))
+        .boxed()
+}
+
+/// Lists the accounts whose transactions are held back in mempool by a missing sequence number,
+/// to tell a stuck transaction apart from one that's just waiting for consensus.
+async fn handle_get_parked_accounts(context: Context) -> Result<impl Reply, Rejection> {
+    let parked_accounts = context
+        .get_mempool_parked_accounts()
+        .await
+        .map_err(Error::internal)?;
+    Ok(reply::json(&parked_accounts))
+}
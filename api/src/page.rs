@@ -3,7 +3,7 @@
 
 use crate::param::{Param, TransactionVersionParam};
 
-use aptos_api_types::{Error, TransactionId};
+use aptos_api_types::{Error, ErrorCode, TransactionId};
 
 use anyhow::Result;
 use serde::Deserialize;
@@ -27,6 +27,7 @@ impl Page {
             .unwrap_or_else(|| Ok(default))?;
         if version > max {
             return Err(Error::not_found(
+                ErrorCode::VersionNotFound,
                 "transaction",
                 TransactionId::Version(version),
                 max,
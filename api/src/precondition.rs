@@ -0,0 +1,57 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::context::Context;
+use aptos_api_types::{Error, X_APTOS_IF_LEDGER_VERSION_AT_LEAST};
+use std::{
+    cmp::min,
+    time::{Duration, Instant},
+};
+use warp::{Filter, Rejection};
+
+/// How often the ledger version is checked while a request waits for it
+const LEDGER_VERSION_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Holds requests with the `X-Aptos-If-Ledger-Version-At-Least` header back until the node has
+/// reached the version, for clients to read their writes after submitting a transaction through
+/// another node. Requests still waiting after the configured max wait are rejected with 412.
+///
+/// Routes apply this once their path and method match, so that requests to unknown routes are
+/// rejected right away.
+pub fn ledger_version_precondition(
+    context: Context,
+) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::header::optional::<String>(X_APTOS_IF_LEDGER_VERSION_AT_LEAST)
+        .and(context.filter())
+        .and_then(|version: Option<String>, context: Context| async move {
+            match version {
+                Some(version) => wait_for_ledger_version(&context, &version)
+                    .await
+                    .map_err(warp::reject::custom),
+                None => Ok(()),
+            }
+        })
+        .untuple_one()
+}
+
+async fn wait_for_ledger_version(context: &Context, version: &str) -> Result<(), Error> {
+    let required_version = version
+        .parse::<u64>()
+        .map_err(|_| Error::invalid_param(X_APTOS_IF_LEDGER_VERSION_AT_LEAST, version))?;
+    let deadline =
+        Instant::now() + Duration::from_millis(context.ledger_version_precondition_max_wait_ms());
+    loop {
+        let ledger_version = context.get_latest_ledger_info()?.ledger_version.0;
+        if ledger_version >= required_version {
+            return Ok(());
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(Error::ledger_version_not_reached(
+                required_version,
+                ledger_version,
+            ));
+        }
+        tokio::time::sleep(min(LEDGER_VERSION_POLL_INTERVAL, deadline - now)).await;
+    }
+}
@@ -1,7 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{context::Context, index};
+use crate::{auth::ApiKeys, context::Context, index};
 
 use aptos_config::config::{ApiConfig, NodeConfig};
 use aptos_mempool::MempoolClientSender;
@@ -31,9 +31,10 @@ pub fn bootstrap(
 
     let api_config = config.api.clone();
     let api = WebServer::from(api_config.clone());
+    let api_keys = ApiKeys::new(&api_config.api_keys)?;
 
     runtime.spawn(async move {
-        let context = Context::new(chain_id, db, mp_sender, api_config);
+        let context = Context::new(chain_id, db, mp_sender, api_config, api_keys);
         let routes = index::routes(context);
         api.serve(routes).await;
     });
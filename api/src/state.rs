@@ -8,11 +8,13 @@ use crate::{
     param::{
         AddressParam, LedgerVersionParam, MoveIdentifierParam, MoveStructTagParam, TableHandleParam,
     },
+    precondition,
     version::Version,
 };
 use anyhow::anyhow;
 use aptos_api_types::{
-    AsConverter, Error, LedgerInfo, MoveModuleBytecode, Response, TableItemRequest, TransactionId,
+    AsConverter, Error, ErrorCode, LedgerInfo, MoveModuleBytecode, Response, TableItemRequest,
+    TransactionId,
 };
 use aptos_state_view::StateView;
 use aptos_types::{access_path::AccessPath, state_store::state_key::StateKey};
@@ -30,6 +32,7 @@ use warp::{filters::BoxedFilter, Filter, Rejection, Reply};
 pub fn get_account_resource(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("accounts" / AddressParam / "resource" / MoveStructTagParam)
         .and(warp::get())
+        .and(precondition::ledger_version_precondition(context.clone()))
         .and(context.filter())
         .and(warp::query::<Version>())
         .map(|address, struct_tag, ctx, version: Version| {
@@ -45,6 +48,7 @@ pub fn get_account_resource(context: Context) -> BoxedFilter<(impl Reply,)> {
 pub fn get_account_module(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("accounts" / AddressParam / "module" / MoveIdentifierParam)
         .and(warp::get())
+        .and(precondition::ledger_version_precondition(context.clone()))
         .and(context.filter())
         .and(warp::query::<Version>())
         .map(|address, name, ctx, version: Version| (version.version, address, name, ctx))
@@ -54,6 +58,36 @@ pub fn get_account_module(context: Context) -> BoxedFilter<(impl Reply,)> {
         .boxed()
 }
 
+// HEAD /accounts/<address>/resource/<resource_type>
+pub fn head_account_resource(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("accounts" / AddressParam / "resource" / MoveStructTagParam)
+        .and(warp::head())
+        .and(precondition::ledger_version_precondition(context.clone()))
+        .and(context.filter())
+        .and(warp::query::<Version>())
+        .map(|address, struct_tag, ctx, version: Version| {
+            (version.version, address, struct_tag, ctx)
+        })
+        .untuple_one()
+        .and_then(handle_head_account_resource)
+        .with(metrics("head_account_resource"))
+        .boxed()
+}
+
+// HEAD /accounts/<address>/module/<module_name>
+pub fn head_account_module(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("accounts" / AddressParam / "module" / MoveIdentifierParam)
+        .and(warp::head())
+        .and(precondition::ledger_version_precondition(context.clone()))
+        .and(context.filter())
+        .and(warp::query::<Version>())
+        .map(|address, name, ctx, version: Version| (version.version, address, name, ctx))
+        .untuple_one()
+        .and_then(handle_head_account_module)
+        .with(metrics("head_account_module"))
+        .boxed()
+}
+
 // GET /tables/<table_handle>/item
 pub fn get_table_item(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("tables" / TableHandleParam / "item")
@@ -62,6 +96,7 @@ pub fn get_table_item(context: Context) -> BoxedFilter<(impl Reply,)> {
             context.content_length_limit(),
         ))
         .and(warp::body::json::<TableItemRequest>())
+        .and(precondition::ledger_version_precondition(context.clone()))
         .and(context.filter())
         .and(warp::query::<Version>())
         .map(|handle, body, ctx, version: Version| (version.version, handle, body, ctx))
@@ -101,6 +136,36 @@ async fn handle_get_account_module(
     )?)
 }
 
+async fn handle_head_account_resource(
+    ledger_version: Option<LedgerVersionParam>,
+    address: AddressParam,
+    struct_tag: MoveStructTagParam,
+    context: Context,
+) -> anyhow::Result<impl Reply, Rejection> {
+    fail_point("endpoint_head_account_resource")?;
+    let struct_tag = struct_tag.parse("struct tag")?;
+    Ok(State::new(ledger_version, context)?.resource_exists(
+        address.parse("account address")?.into(),
+        struct_tag
+            .clone()
+            .try_into()
+            .map_err(|_| Error::invalid_param("resource_type", struct_tag))?,
+    )?)
+}
+
+async fn handle_head_account_module(
+    ledger_version: Option<LedgerVersionParam>,
+    address: AddressParam,
+    name: MoveIdentifierParam,
+    context: Context,
+) -> anyhow::Result<impl Reply, Rejection> {
+    fail_point("endpoint_head_account_module")?;
+    Ok(State::new(ledger_version, context)?.module_exists(
+        address.parse("account address")?.into(),
+        name.parse("module name")?,
+    )?)
+}
+
 async fn handle_get_table_item(
     ledger_version: Option<LedgerVersionParam>,
     handle: TableHandleParam,
@@ -129,11 +194,22 @@ impl State {
 
         if ledger_version > latest_ledger_info.version() {
             return Err(Error::not_found(
+                ErrorCode::VersionNotFound,
                 "ledger",
                 TransactionId::Version(ledger_version),
                 latest_ledger_info.version(),
             ));
         }
+        let oldest_state_version =
+            context.get_oldest_state_version(latest_ledger_info.version())?;
+        if ledger_version < oldest_state_version {
+            return Err(Error::version_pruned(
+                "state",
+                TransactionId::Version(ledger_version),
+                oldest_state_version,
+                latest_ledger_info.version(),
+            ));
+        }
 
         let state_view = context.state_view_at_version(ledger_version)?;
 
@@ -149,13 +225,7 @@ impl State {
         address: AccountAddress,
         struct_tag: StructTag,
     ) -> Result<impl Reply, Error> {
-        let resource_key = ResourceKey::new(address, struct_tag.clone());
-        let access_path = AccessPath::resource_access_path(resource_key.clone());
-        let state_key = StateKey::AccessPath(access_path);
-        let bytes = self
-            .state_view
-            .get_state_value(&state_key)?
-            .ok_or_else(|| Error::not_found("Resource", resource_key, self.ledger_version))?;
+        let bytes = self.resource_bytes(address, struct_tag.clone())?;
 
         let resource = self
             .state_view
@@ -166,13 +236,7 @@ impl State {
     }
 
     pub fn module(self, address: AccountAddress, name: Identifier) -> Result<impl Reply, Error> {
-        let module_id = ModuleId::new(address, name);
-        let access_path = AccessPath::code_access_path(module_id.clone());
-        let state_key = StateKey::AccessPath(access_path);
-        let bytes = self
-            .state_view
-            .get_state_value(&state_key)?
-            .ok_or_else(|| Error::not_found("Module", module_id, self.ledger_version))?;
+        let bytes = self.module_bytes(address, name)?;
 
         let module = MoveModuleBytecode::new(bytes)
             .try_parse_abi()
@@ -180,6 +244,58 @@ impl State {
         Response::new(self.latest_ledger_info, &module)
     }
 
+    /// Answers like `resource`, without the resource
+    pub fn resource_exists(
+        self,
+        address: AccountAddress,
+        struct_tag: StructTag,
+    ) -> Result<impl Reply, Error> {
+        self.resource_bytes(address, struct_tag)?;
+        Ok(Response::empty(self.latest_ledger_info))
+    }
+
+    /// Answers like `module`, without the module
+    pub fn module_exists(
+        self,
+        address: AccountAddress,
+        name: Identifier,
+    ) -> Result<impl Reply, Error> {
+        self.module_bytes(address, name)?;
+        Ok(Response::empty(self.latest_ledger_info))
+    }
+
+    fn resource_bytes(
+        &self,
+        address: AccountAddress,
+        struct_tag: StructTag,
+    ) -> Result<Vec<u8>, Error> {
+        let resource_key = ResourceKey::new(address, struct_tag);
+        let access_path = AccessPath::resource_access_path(resource_key.clone());
+        let state_key = StateKey::AccessPath(access_path);
+        self.state_view.get_state_value(&state_key)?.ok_or_else(|| {
+            Error::not_found(
+                ErrorCode::ResourceNotFound,
+                "Resource",
+                resource_key,
+                self.ledger_version,
+            )
+        })
+    }
+
+    fn module_bytes(&self, address: AccountAddress, name: Identifier) -> Result<Vec<u8>, Error> {
+        let module_id = ModuleId::new(address, name);
+        let access_path = AccessPath::code_access_path(module_id.clone());
+        let state_key = StateKey::AccessPath(access_path);
+        self.state_view.get_state_value(&state_key)?.ok_or_else(|| {
+            Error::not_found(
+                ErrorCode::ModuleNotFound,
+                "Module",
+                module_id,
+                self.ledger_version,
+            )
+        })
+    }
+
     pub fn table_item(self, handle: u128, body: TableItemRequest) -> Result<impl Reply, Error> {
         let TableItemRequest {
             key_type,
@@ -204,7 +320,14 @@ impl State {
         let bytes = self
             .state_view
             .get_state_value(&state_key)?
-            .ok_or_else(|| Error::not_found("table handle or item", key, self.ledger_version))?;
+            .ok_or_else(|| {
+                Error::not_found(
+                    ErrorCode::TableItemNotFound,
+                    "table handle or item",
+                    key,
+                    self.ledger_version,
+                )
+            })?;
 
         let move_value = converter.try_into_move_value(&value_type, &bytes)?;
         Response::new(self.latest_ledger_info, &move_value)
@@ -0,0 +1,76 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    context::Context, failpoint::fail_point, metrics::metrics, param::LedgerVersionParam,
+    precondition,
+};
+use aptos_api_types::{Error, ErrorCode, LedgerInfo, Response, StateProofData, TransactionId};
+use serde::Deserialize;
+use warp::{filters::BoxedFilter, Filter, Rejection, Reply};
+
+#[derive(Clone, Debug, Deserialize)]
+struct StateProofQuery {
+    known_version: Option<LedgerVersionParam>,
+    #[serde(default)]
+    with_accumulator: bool,
+}
+
+// GET /state_proof?known_version=<version>&with_accumulator=<bool>
+pub fn get_state_proof(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("state_proof")
+        .and(warp::get())
+        .and(warp::query::<StateProofQuery>())
+        .and(precondition::ledger_version_precondition(context.clone()))
+        .and(context.filter())
+        .and_then(handle_get_state_proof)
+        .with(metrics("get_state_proof"))
+        .boxed()
+}
+
+async fn handle_get_state_proof(
+    query: StateProofQuery,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_get_state_proof")?;
+    Ok(state_proof(query, context)?)
+}
+
+fn state_proof(query: StateProofQuery, context: Context) -> Result<impl Reply, Error> {
+    // The proof and the response headers must describe the same ledger info, otherwise a client
+    // could not tie the headers to what it has verified.
+    let ledger_info_with_sigs = context.get_latest_ledger_info_with_signatures()?;
+    let ledger_info = LedgerInfo::new(
+        &context.chain_id(),
+        &ledger_info_with_sigs,
+        context.get_latest_state_checkpoint_version()?,
+        Some(context.get_oldest_ledger_version()?),
+    );
+    let known_version = query
+        .known_version
+        .map(|v| v.parse("known_version"))
+        .unwrap_or(Ok(0))?;
+    if known_version > ledger_info.version() {
+        return Err(Error::not_found(
+            ErrorCode::VersionNotFound,
+            "ledger",
+            TransactionId::Version(known_version),
+            ledger_info.version(),
+        ));
+    }
+
+    let accumulator_summary = if query.with_accumulator {
+        let summary = context.get_accumulator_summary(known_version)?;
+        Some(bcs::to_bytes(&summary).map_err(anyhow::Error::from)?.into())
+    } else {
+        None
+    };
+    let state_proof = context.get_state_proof(known_version, ledger_info_with_sigs)?;
+    let data = StateProofData {
+        state_proof: bcs::to_bytes(&state_proof)
+            .map_err(anyhow::Error::from)?
+            .into(),
+        accumulator_summary,
+    };
+    Response::new(ledger_info, &data)
+}
@@ -3,8 +3,9 @@
 
 use crate::{
     current_function_name,
-    tests::{find_value, new_test_context},
+    tests::{find_value, new_test_context, new_test_context_with_pruner},
 };
+use aptos_config::config::StoragePrunerConfig;
 use serde_json::json;
 
 #[tokio::test]
@@ -147,6 +148,53 @@ async fn test_get_account_resources_by_ledger_version_is_too_large() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test]
+async fn test_get_account_resources_by_pruned_ledger_version() {
+    let mut context = new_test_context_with_pruner(
+        current_function_name!(),
+        StoragePrunerConfig {
+            state_store_prune_window: Some(2),
+            ledger_prune_window: Some(1_000_000),
+            pruning_batch_size: 1,
+        },
+    );
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    context.commit_block(&[txn]).await;
+    context.commit_block(&[]).await;
+    let ledger_version = context.get_latest_ledger_info().version();
+    let oldest_state_version = ledger_version - 2;
+    let root_address = context.root_account().address().to_hex_literal();
+
+    // the state out of the state prune window is gone
+    let resp = context
+        .expect_status_code(410)
+        .get(&account_resources_with_ledger_version(
+            &root_address,
+            (oldest_state_version - 1).into(),
+        ))
+        .await;
+    assert_eq!(resp["error_code"], "version_pruned");
+    assert_eq!(
+        resp["oldest_ledger_version"],
+        oldest_state_version.to_string()
+    );
+
+    // while the state in it is still there
+    context
+        .get(&account_resources_with_ledger_version(
+            &root_address,
+            oldest_state_version.into(),
+        ))
+        .await;
+
+    // as is the transaction out of the state prune window, in the ledger prune window
+    let txn = context
+        .get(&format!("/transactions/{}", oldest_state_version - 1))
+        .await;
+    assert_eq!(txn["version"], (oldest_state_version - 1).to_string());
+}
+
 #[tokio::test]
 async fn test_get_account_resources_by_invalid_ledger_version() {
     let mut context = new_test_context(current_function_name!());
@@ -204,6 +252,25 @@ async fn test_get_core_account_data_not_found() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test]
+async fn test_head_core_account_data() {
+    let context = new_test_context(current_function_name!());
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("HEAD")
+                .path("/accounts/0xA550C18"),
+        )
+        .await;
+    assert_eq!(resp.status(), 200);
+    assert!(resp.body().is_empty());
+
+    let resp = context
+        .reply(warp::test::request().method("HEAD").path("/accounts/0xf"))
+        .await;
+    assert_eq!(resp.status(), 404);
+}
+
 fn account_resources(address: &str) -> String {
     format!("/accounts/{}/resources", address)
 }
@@ -0,0 +1,179 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    auth::{ApiKeys, API_KEY_HEADER},
+    context::Context,
+    current_function_name,
+    tests::{new_test_context, TestContext},
+};
+use aptos_api_types::mime_types;
+use aptos_config::config::{ApiConfig, ApiEndpointClass, ApiKey, ApiKeyConfig};
+use aptos_types::chain_id::ChainId;
+use warp::http::header::{CONTENT_TYPE, RETRY_AFTER};
+
+const READER_KEY: &str = "reader-key";
+const SUBMITTER_KEY: &str = "submitter-key";
+
+fn new_api_keys_test_context(test_name: &'static str) -> TestContext {
+    let mut context = new_test_context(test_name);
+    let api_keys = ApiKeys::new(&[
+        ApiKeyConfig {
+            name: "reader".to_owned(),
+            key: ApiKey::FromConfig(READER_KEY.to_owned()),
+            max_requests_per_sec: Some(2),
+            endpoint_classes: vec![ApiEndpointClass::Read],
+        },
+        ApiKeyConfig {
+            name: "submitter".to_owned(),
+            key: ApiKey::FromConfig(SUBMITTER_KEY.to_owned()),
+            max_requests_per_sec: None,
+            endpoint_classes: ApiEndpointClass::all(),
+        },
+    ])
+    .unwrap();
+    context.context = Context::new(
+        ChainId::test(),
+        context.db.clone(),
+        context.mempool.ac_client.clone(),
+        ApiConfig::default(),
+        api_keys,
+    );
+    context
+}
+
+#[tokio::test]
+async fn test_api_key_required() {
+    let context = new_api_keys_test_context(current_function_name!());
+    let resp = context
+        .reply(warp::test::request().method("GET").path("/"))
+        .await;
+    assert_eq!(resp.status(), 401);
+
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .path("/")
+                .header(API_KEY_HEADER, "unknown-key"),
+        )
+        .await;
+    assert_eq!(resp.status(), 401);
+
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .path("/")
+                .header(API_KEY_HEADER, SUBMITTER_KEY),
+        )
+        .await;
+    assert_eq!(resp.status(), 200);
+
+    // The health check stays open for load balancers
+    let resp = context
+        .reply(warp::test::request().method("GET").path("/-/healthy"))
+        .await;
+    assert_eq!(resp.status(), 200);
+}
+
+#[tokio::test]
+async fn test_api_key_endpoint_classes() {
+    let mut context = new_api_keys_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("POST")
+                .path("/transactions")
+                .header(API_KEY_HEADER, READER_KEY)
+                .header(CONTENT_TYPE, mime_types::BCS_SIGNED_TRANSACTION)
+                .body(bcs::to_bytes(&txn).unwrap()),
+        )
+        .await;
+    assert_eq!(resp.status(), 403);
+    assert!(context.mempool.get_txns(1).is_empty());
+
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("POST")
+                .path("/transactions")
+                .header(API_KEY_HEADER, SUBMITTER_KEY)
+                .header(CONTENT_TYPE, mime_types::BCS_SIGNED_TRANSACTION)
+                .body(bcs::to_bytes(&txn).unwrap()),
+        )
+        .await;
+    assert_eq!(resp.status(), 202);
+}
+
+#[tokio::test]
+async fn test_api_key_rate_limit() {
+    let context = new_api_keys_test_context(current_function_name!());
+    let request = || {
+        warp::test::request()
+            .method("GET")
+            .path("/")
+            .header(API_KEY_HEADER, READER_KEY)
+    };
+
+    assert_eq!(context.reply(request()).await.status(), 200);
+    assert_eq!(context.reply(request()).await.status(), 200);
+    let resp = context.reply(request()).await;
+    assert_eq!(resp.status(), 429);
+    assert!(resp.headers().contains_key(RETRY_AFTER));
+
+    // Other keys are limited on their own
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .path("/")
+                .header(API_KEY_HEADER, SUBMITTER_KEY),
+        )
+        .await;
+    assert_eq!(resp.status(), 200);
+}
+
+#[test]
+fn test_api_key_default_endpoint_classes() {
+    let key_config: ApiKeyConfig = serde_json::from_value(serde_json::json!({
+        "name": "default",
+        "key": { "from_config": "default-key" },
+    }))
+    .unwrap();
+    let api_keys = ApiKeys::new(&[key_config]).unwrap();
+    assert_eq!(api_keys.key_name("default-key"), Some("default"));
+    assert_eq!(api_keys.key_name("unknown-key"), None);
+
+    assert!(api_keys
+        .authorize(Some("default-key"), ApiEndpointClass::Read)
+        .is_ok());
+    assert!(api_keys
+        .authorize(Some("default-key"), ApiEndpointClass::Submit)
+        .is_ok());
+    // admin endpoints have to be allowed explicitly
+    let err = api_keys
+        .authorize(Some("default-key"), ApiEndpointClass::Admin)
+        .unwrap_err();
+    assert_eq!(err.status_code(), 403);
+}
+
+#[test]
+fn test_api_keys_config_errors() {
+    let key_config = ApiKeyConfig {
+        name: "test".to_owned(),
+        key: ApiKey::FromEnv("APTOS_API_TEST_KEY_NOT_SET".to_owned()),
+        max_requests_per_sec: None,
+        endpoint_classes: ApiEndpointClass::all(),
+    };
+    assert!(ApiKeys::new(&[key_config.clone()]).is_err());
+
+    let key_config = ApiKeyConfig {
+        key: ApiKey::FromConfig("duplicate".to_owned()),
+        ..key_config
+    };
+    assert!(ApiKeys::new(&[key_config.clone(), key_config]).is_err());
+}
@@ -0,0 +1,139 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{current_function_name, tests::new_test_context};
+use aptos_api_types::{BlockTransactions, HashValue, Transaction, X_APTOS_LEDGER_VERSION};
+use aptos_types::proof::TransactionInfoListWithProof;
+
+#[tokio::test]
+async fn test_get_block_transactions() {
+    let mut context = new_test_context(current_function_name!());
+    let mut root_account = context.root_account();
+    for num_txns in [1, 2] {
+        let mut txns = vec![];
+        for _ in 0..num_txns {
+            let account = context.gen_account();
+            txns.push(context.create_user_account_by(&mut root_account, &account));
+        }
+        context.commit_block(&txns).await;
+    }
+
+    // The genesis is the block at height 0, then each block starts with its block metadata
+    let resp = context.get("/blocks/2/transactions").await;
+    let block: BlockTransactions = serde_json::from_value(resp).unwrap();
+    assert_eq!(block.height.0, 2);
+    assert_eq!(block.first_version.0, 3);
+    assert_eq!(block.last_version.0, 5);
+    assert_eq!(block.transactions.len(), 3);
+    assert!(matches!(
+        block.transactions[0],
+        Transaction::BlockMetadataTransaction(_)
+    ));
+
+    let proof: TransactionInfoListWithProof = bcs::from_bytes(block.proof.inner()).unwrap();
+    let ledger_info = context
+        .context
+        .get_latest_ledger_info_with_signatures()
+        .unwrap();
+    proof
+        .verify(ledger_info.ledger_info(), Some(block.first_version.0))
+        .unwrap();
+    for (txn, info) in block.transactions.iter().zip(&proof.transaction_infos) {
+        assert_eq!(
+            txn.transaction_info().unwrap().hash,
+            HashValue::from(info.transaction_hash())
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_get_block_transactions_with_events() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    context.commit_block(&[txn]).await;
+
+    let resp = context.get("/blocks/1/transactions").await;
+    assert_eq!(resp["transactions"][0]["events"], serde_json::json!([]));
+
+    let resp = context.get("/blocks/1/transactions?with_events=true").await;
+    assert_eq!(resp["first_version"], "1");
+    assert_eq!(resp["last_version"], "2");
+    assert_eq!(
+        resp["transactions"][0]["events"][0]["type"],
+        "0x1::Block::NewBlockEvent"
+    );
+}
+
+#[tokio::test]
+async fn test_get_genesis_block_transactions() {
+    let context = new_test_context(current_function_name!());
+    let resp = context.get("/blocks/0/transactions").await;
+    assert_eq!(resp["first_version"], "0");
+    assert_eq!(resp["last_version"], "0");
+    assert_eq!(resp["transactions"][0]["type"], "genesis_transaction");
+    assert_eq!(
+        resp["transactions"][0]["payload"]["type"],
+        "write_set_summary"
+    );
+}
+
+#[tokio::test]
+async fn test_get_block_transactions_not_found() {
+    let context = new_test_context(current_function_name!());
+    let resp = context
+        .expect_status_code(404)
+        .get("/blocks/1/transactions")
+        .await;
+    assert_eq!(resp["error_code"], "block_not_found");
+    assert_eq!(resp["retriable"], true);
+}
+
+#[tokio::test]
+async fn test_block_transactions_proof_against_response_ledger_info() {
+    let mut context = new_test_context(current_function_name!());
+    let mut root_account = context.root_account();
+    for _ in 0..3 {
+        let account = context.gen_account();
+        let txn = context.create_user_account_by(&mut root_account, &account);
+        context.commit_block(&[txn]).await;
+    }
+
+    // An older block, proven against the ledger info of the response rather than its own
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .path("/blocks/1/transactions"),
+        )
+        .await;
+    assert_eq!(resp.status(), 200);
+    let ledger_version: u64 = resp.headers()[X_APTOS_LEDGER_VERSION]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    let ledger_info = context
+        .context
+        .get_latest_ledger_info_with_signatures()
+        .unwrap();
+    assert_eq!(ledger_info.ledger_info().version(), ledger_version);
+
+    let block: BlockTransactions = serde_json::from_slice(resp.body()).unwrap();
+    assert!(block.last_version.0 < ledger_version);
+    let proof: TransactionInfoListWithProof = bcs::from_bytes(block.proof.inner()).unwrap();
+    assert_eq!(proof.transaction_infos.len(), block.transactions.len());
+    proof
+        .verify(ledger_info.ledger_info(), Some(block.first_version.0))
+        .unwrap();
+
+    // The proof doesn't hold for other versions, nor once a transaction info is tampered with
+    assert!(proof
+        .verify(ledger_info.ledger_info(), Some(block.first_version.0 + 1))
+        .is_err());
+    let mut tampered = proof.clone();
+    tampered.transaction_infos.swap(0, 1);
+    assert!(tampered
+        .verify(ledger_info.ledger_info(), Some(block.first_version.0))
+        .is_err());
+}
@@ -0,0 +1,108 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    auth::ApiKeys,
+    context::Context,
+    current_function_name,
+    failpoint::{fail_point, Failpoint},
+    tests::{new_test_context, TestContext},
+};
+use aptos_config::config::ApiConfig;
+use aptos_types::chain_id::ChainId;
+use serde_json::json;
+
+pub(crate) const ADMIN_TOKEN: &str = "test-admin-token";
+
+/// A test context with the admin routes enabled
+pub(crate) fn new_admin_test_context(test_name: &'static str) -> TestContext {
+    let mut context = new_test_context(test_name);
+    let api_config = ApiConfig {
+        failpoints_admin_token: Some(ADMIN_TOKEN.to_owned()),
+        ..ApiConfig::default()
+    };
+    context.context = Context::new(
+        ChainId::test(),
+        context.db.clone(),
+        context.mempool.ac_client.clone(),
+        api_config,
+        ApiKeys::default(),
+    );
+    context
+}
+
+#[tokio::test]
+async fn test_failpoints_not_found_without_admin_token_config() {
+    let context = new_test_context(current_function_name!());
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .path("/admin/failpoints")
+                .header("authorization", format!("Bearer {}", ADMIN_TOKEN)),
+        )
+        .await;
+    assert_eq!(resp.status(), 404);
+}
+
+#[tokio::test]
+async fn test_failpoints_require_admin_token() {
+    let context = new_admin_test_context(current_function_name!());
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .path("/admin/failpoints"),
+        )
+        .await;
+    assert_eq!(resp.status(), 401);
+
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("POST")
+                .path("/admin/failpoints")
+                .header("authorization", "Bearer wrong-token")
+                .json(&json!({"name": "api::endpoint_index", "actions": "return"})),
+        )
+        .await;
+    assert_eq!(resp.status(), 401);
+}
+
+#[tokio::test]
+async fn test_set_and_remove_failpoint() {
+    let context = new_admin_test_context(current_function_name!());
+    // Failpoints are global to the process, so use one no other test goes through.
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("POST")
+                .path("/admin/failpoints")
+                .header("authorization", format!("Bearer {}", ADMIN_TOKEN))
+                .json(&json!({"name": "api::test_admin_failpoint", "actions": "return"})),
+        )
+        .await;
+    if !fail::has_failpoints() {
+        // Nothing to inject failures into unless built with failpoints
+        assert_eq!(resp.status(), 400);
+        return;
+    }
+    assert_eq!(resp.status(), 200);
+    let failpoints: Vec<Failpoint> = serde_json::from_slice(resp.body()).unwrap();
+    assert!(failpoints.contains(&Failpoint {
+        name: "api::test_admin_failpoint".to_owned(),
+        actions: "return".to_owned(),
+    }));
+    assert!(fail_point("test_admin_failpoint").is_err());
+
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("DELETE")
+                .path("/admin/failpoints/api::test_admin_failpoint")
+                .header("authorization", format!("Bearer {}", ADMIN_TOKEN)),
+        )
+        .await;
+    assert_eq!(resp.status(), 200);
+    assert!(fail_point("test_admin_failpoint").is_ok());
+}
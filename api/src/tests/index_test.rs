@@ -2,7 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{current_function_name, tests::new_test_context};
-use serde_json::json;
+use aptos_api_types::X_REQUEST_ID;
+use serde_json::{json, Value};
 
 #[tokio::test]
 async fn test_get_ledger_info() {
@@ -28,6 +29,32 @@ async fn test_return_bad_request_if_method_not_allowed() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test]
+async fn test_request_id() {
+    let context = new_test_context(current_function_name!());
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .path("/invalid_path")
+                .header(X_REQUEST_ID, "test-request"),
+        )
+        .await;
+    assert_eq!(resp.status(), 404);
+    assert_eq!(resp.headers()[X_REQUEST_ID], "test-request");
+    let body: Value = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body["request_id"], "test-request");
+
+    // Requests without an id get one, which successful responses carry in their headers only
+    let resp = context
+        .reply(warp::test::request().method("GET").path("/"))
+        .await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers()[X_REQUEST_ID].len(), 32);
+    let body: Value = serde_json::from_slice(resp.body()).unwrap();
+    assert!(body.get("request_id").is_none());
+}
+
 #[tokio::test]
 async fn test_health_check() {
     let context = new_test_context(current_function_name!());
@@ -0,0 +1,71 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    current_function_name,
+    tests::{
+        failpoint_test::{new_admin_test_context, ADMIN_TOKEN},
+        new_test_context,
+    },
+};
+use aptos_mempool::ParkedAccount;
+
+#[tokio::test]
+async fn test_parked_accounts_not_found_without_admin_token_config() {
+    let context = new_test_context(current_function_name!());
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .path("/admin/mempool/parked")
+                .header("authorization", format!("Bearer {}", ADMIN_TOKEN)),
+        )
+        .await;
+    assert_eq!(resp.status(), 404);
+}
+
+#[tokio::test]
+async fn test_get_parked_accounts() {
+    let mut context = new_admin_test_context(current_function_name!());
+    let mut root = context.root_account();
+    let account = context.gen_account();
+    let first_txn = context.create_user_account_by(&mut root, &account);
+    let second_txn = context.create_user_account_by(&mut root, &account);
+    // Mempool gets the second transaction of the root account but not the first one
+    context.mempool.add_txns(vec![second_txn]).unwrap();
+
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .path("/admin/mempool/parked")
+                .header("authorization", format!("Bearer {}", ADMIN_TOKEN)),
+        )
+        .await;
+    assert_eq!(resp.status(), 200);
+    let parked_accounts: Vec<ParkedAccount> = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(
+        parked_accounts,
+        vec![ParkedAccount {
+            address: root.address(),
+            account_sequence_number: 0,
+            num_ready: 0,
+            first_missing_sequence_number: 0,
+            lowest_parked_sequence_number: 1,
+            gap: 1,
+            num_parked: 1,
+        }]
+    );
+
+    context.mempool.add_txns(vec![first_txn]).unwrap();
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .path("/admin/mempool/parked")
+                .header("authorization", format!("Bearer {}", ADMIN_TOKEN)),
+        )
+        .await;
+    let parked_accounts: Vec<ParkedAccount> = serde_json::from_slice(resp.body()).unwrap();
+    assert!(parked_accounts.is_empty());
+}
@@ -2,18 +2,26 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod accounts_test;
+mod auth_test;
+mod blocks_test;
 mod converter_test;
 mod events_test;
+mod failpoint_test;
 mod golden_output;
 mod index_test;
 mod invalid_post_request_test;
+mod mempool_test;
+mod precondition_test;
+mod replay;
+mod replay_test;
+mod state_proof_test;
 mod state_test;
 mod string_resource_test;
 mod test_context;
 mod transactions_test;
 
 use serde_json::Value;
-pub use test_context::{new_test_context, TestContext};
+pub use test_context::{new_test_context, new_test_context_with_pruner, TestContext};
 
 pub fn find_value(val: &Value, filter: for<'r> fn(&'r &Value) -> bool) -> Value {
     let resources = val
@@ -0,0 +1,104 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    auth::ApiKeys,
+    context::Context,
+    current_function_name,
+    tests::{new_test_context, TestContext},
+};
+use aptos_api_types::{X_APTOS_IF_LEDGER_VERSION_AT_LEAST, X_APTOS_LEDGER_VERSION};
+use aptos_config::config::ApiConfig;
+use aptos_types::chain_id::ChainId;
+use serde_json::Value;
+use std::time::{Duration, Instant};
+
+const MAX_WAIT_MS: u64 = 500;
+
+fn new_precondition_test_context(test_name: &'static str) -> TestContext {
+    let mut context = new_test_context(test_name);
+    let api_config = ApiConfig {
+        ledger_version_precondition_max_wait_ms: MAX_WAIT_MS,
+        ..ApiConfig::default()
+    };
+    context.context = Context::new(
+        ChainId::test(),
+        context.db.clone(),
+        context.mempool.ac_client.clone(),
+        api_config,
+        ApiKeys::default(),
+    );
+    context
+}
+
+fn get_at_least(version: impl ToString) -> warp::test::RequestBuilder {
+    warp::test::request()
+        .method("GET")
+        .path("/")
+        .header(X_APTOS_IF_LEDGER_VERSION_AT_LEAST, version.to_string())
+}
+
+#[tokio::test]
+async fn test_ledger_version_reached() {
+    let context = new_precondition_test_context(current_function_name!());
+    let ledger_version = context.get_latest_ledger_info().version();
+    for version in [0, ledger_version] {
+        let resp = context.reply(get_at_least(version)).await;
+        assert_eq!(resp.status(), 200);
+    }
+}
+
+#[tokio::test]
+async fn test_ledger_version_not_reached() {
+    let context = new_precondition_test_context(current_function_name!());
+    let ledger_version = context.get_latest_ledger_info().version();
+
+    let start = Instant::now();
+    let resp = context.reply(get_at_least(ledger_version + 1000)).await;
+    assert!(start.elapsed() >= Duration::from_millis(MAX_WAIT_MS));
+    assert_eq!(resp.status(), 412);
+    assert_eq!(
+        resp.headers()[X_APTOS_LEDGER_VERSION],
+        ledger_version.to_string()
+    );
+    let body: Value = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body["error_code"], "ledger_version_not_reached");
+    assert_eq!(body["retriable"], true);
+}
+
+#[tokio::test]
+async fn test_ledger_version_reached_while_waiting() {
+    let mut context = new_precondition_test_context(current_function_name!());
+    let ledger_version = context.get_latest_ledger_info().version();
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+
+    let waiting = context.clone();
+    let (resp, ()) = tokio::join!(waiting.reply(get_at_least(ledger_version + 1)), async {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        context.commit_block(&[txn]).await;
+    });
+    assert_eq!(resp.status(), 200);
+}
+
+#[tokio::test]
+async fn test_invalid_ledger_version_precondition() {
+    let context = new_precondition_test_context(current_function_name!());
+    let resp = context.reply(get_at_least("latest")).await;
+    assert_eq!(resp.status(), 400);
+    let body: Value = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body["error_code"], "invalid_parameter");
+}
+
+#[tokio::test]
+async fn test_unknown_route_not_held_back() {
+    let context = new_precondition_test_context(current_function_name!());
+    let ledger_version = context.get_latest_ledger_info().version();
+
+    let start = Instant::now();
+    let resp = context
+        .reply(get_at_least(ledger_version + 1000).path("/unknown"))
+        .await;
+    assert!(start.elapsed() < Duration::from_millis(MAX_WAIT_MS));
+    assert_eq!(resp.status(), 404);
+}
@@ -0,0 +1,76 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Replays of the REST API traffic recorded by forge tests, see `forge::start_api_recording`,
+//! against the API of a test context, to reproduce the API issues found by network tests without
+//! a network
+
+use crate::tests::TestContext;
+use aptos_api_types::ApiRecord;
+use std::{
+    fmt,
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+use warp::http::header::CONTENT_TYPE;
+
+/// Loads the records of a recording, only those made while `test` ran if given
+pub fn load_recording(path: &Path, test: Option<&str>) -> Vec<ApiRecord> {
+    BufReader::new(File::open(path).expect("Failed to open the recording"))
+        .lines()
+        .map(|line| serde_json::from_str(&line.unwrap()).expect("Invalid record"))
+        .filter(|record: &ApiRecord| {
+            test.map_or(true, |test| record.tests.iter().any(|t| t == test))
+        })
+        .collect()
+}
+
+/// A replayed request which got another status than the recorded one
+#[derive(Debug)]
+pub struct ReplayMismatch {
+    pub record: ApiRecord,
+    pub status: u16,
+    pub body: String,
+}
+
+impl fmt::Display for ReplayMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}: recorded {}, replayed {}: {}",
+            self.record.method, self.record.path, self.record.status, self.status, self.body
+        )
+    }
+}
+
+/// Replays the requests of `records` in order, returning those which got another status than
+/// when they were recorded. The bodies aren't compared, as they depend on the state of the
+/// chain, so the requests which only succeed against the recorded chain, e.g. for its accounts,
+/// are mismatches too and are best left out of the records to replay.
+pub async fn replay(context: &TestContext, records: &[ApiRecord]) -> Vec<ReplayMismatch> {
+    let mut mismatches = vec![];
+    for record in records {
+        let mut request = warp::test::request()
+            .method(&record.method)
+            .path(&record.path)
+            .body(
+                record
+                    .request_body
+                    .to_bytes()
+                    .expect("Invalid request body"),
+            );
+        if let Some(content_type) = &record.content_type {
+            request = request.header(CONTENT_TYPE, content_type.as_str());
+        }
+        let response = context.reply(request).await;
+        if response.status() != record.status {
+            mismatches.push(ReplayMismatch {
+                record: record.clone(),
+                status: response.status().as_u16(),
+                body: String::from_utf8_lossy(response.body()).into_owned(),
+            });
+        }
+    }
+    mismatches
+}
@@ -0,0 +1,109 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    current_function_name,
+    tests::{
+        new_test_context,
+        replay::{load_recording, replay},
+    },
+};
+use aptos_api_types::{mime_types, ApiRecord, RecordedBody};
+use aptos_temppath::TempPath;
+use std::{env, fs, path::PathBuf};
+
+fn record(
+    test: &str,
+    method: &str,
+    path: &str,
+    body: Option<(&str, &[u8])>,
+    status: u16,
+) -> ApiRecord {
+    ApiRecord {
+        tests: vec![test.to_owned()],
+        endpoint: "http://127.0.0.1:8080/".to_owned(),
+        method: method.to_owned(),
+        path: path.to_owned(),
+        content_type: body.map(|(content_type, _)| content_type.to_owned()),
+        request_body: RecordedBody::new(body.map_or(&[][..], |(_, body)| body)),
+        status,
+        request_id: None,
+        response_body: RecordedBody::new(&[]),
+        elapsed_ms: 0,
+    }
+}
+
+#[tokio::test]
+async fn test_replay_recording() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = bcs::to_bytes(&context.create_user_account(&account)).unwrap();
+    let records = vec![
+        record("index", "GET", "/", None, 200),
+        record("accounts", "GET", "/accounts/0xf", None, 404),
+        record(
+            "transactions",
+            "GET",
+            "/transactions?start=hello",
+            None,
+            400,
+        ),
+        record(
+            "transactions",
+            "POST",
+            "/transactions",
+            Some((mime_types::BCS_SIGNED_TRANSACTION, &txn)),
+            202,
+        ),
+    ];
+    let recording = TempPath::new();
+    let lines: Vec<_> = records
+        .iter()
+        .map(|record| serde_json::to_string(record).unwrap())
+        .collect();
+    fs::write(recording.path(), lines.join("\n")).unwrap();
+
+    let loaded = load_recording(recording.path(), None);
+    assert_eq!(loaded, records);
+    assert_eq!(
+        load_recording(recording.path(), Some("transactions")),
+        records[2..].to_vec()
+    );
+
+    let mismatches = replay(&context, &loaded).await;
+    assert!(mismatches.is_empty(), "{:?}", mismatches);
+}
+
+#[tokio::test]
+async fn test_replay_mismatch() {
+    let context = new_test_context(current_function_name!());
+    let records = vec![
+        record("index", "GET", "/", None, 200),
+        record("accounts", "GET", "/accounts/0xf", None, 200),
+    ];
+    let mismatches = replay(&context, &records).await;
+    assert_eq!(mismatches.len(), 1);
+    assert_eq!(mismatches[0].record, records[1]);
+    assert_eq!(mismatches[0].status, 404);
+}
+
+/// Replays the recording of a forge run, e.g. with
+/// `API_RECORDING=api.jsonl API_RECORDING_TEST=<test> cargo test -p aptos-api test_replay_forge_recording -- --ignored`
+#[ignore]
+#[tokio::test]
+async fn test_replay_forge_recording() {
+    let path = PathBuf::from(env::var("API_RECORDING").expect("API_RECORDING isn't set"));
+    let test = env::var("API_RECORDING_TEST").ok();
+    let records = load_recording(&path, test.as_deref());
+    let context = new_test_context(current_function_name!());
+    let mismatches = replay(&context, &records).await;
+    for mismatch in &mismatches {
+        println!("{}", mismatch);
+    }
+    assert!(
+        mismatches.is_empty(),
+        "{} of {} requests got another status",
+        mismatches.len(),
+        records.len()
+    );
+}
@@ -0,0 +1,57 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{current_function_name, tests::new_test_context};
+use aptos_api_types::{HexEncodedBytes, StateProofData};
+use aptos_types::{
+    proof::TransactionAccumulatorSummary, state_proof::StateProof, trusted_state::TrustedState,
+    waypoint::Waypoint,
+};
+
+#[tokio::test]
+async fn test_get_state_proof_from_genesis_waypoint() {
+    let context = new_test_context(current_function_name!());
+    let genesis_li = context
+        .context
+        .get_latest_ledger_info_with_signatures()
+        .unwrap();
+    let waypoint = Waypoint::new_epoch_boundary(genesis_li.ledger_info()).unwrap();
+
+    let resp = context
+        .get("/state_proof?known_version=0&with_accumulator=true")
+        .await;
+    let data: StateProofData = serde_json::from_value(resp).unwrap();
+    let state_proof: StateProof = bcs::from_bytes(data.state_proof.inner()).unwrap();
+    let accumulator: TransactionAccumulatorSummary = bcs::from_bytes(
+        data.accumulator_summary
+            .as_ref()
+            .map(HexEncodedBytes::inner)
+            .unwrap(),
+    )
+    .unwrap();
+
+    let trusted_state = TrustedState::from_epoch_waypoint(waypoint);
+    let change = trusted_state
+        .verify_and_ratchet(&state_proof, Some(&accumulator))
+        .unwrap();
+    assert!(change.is_epoch_change());
+    assert_eq!(change.new_state().unwrap().version(), 0);
+}
+
+#[tokio::test]
+async fn test_get_state_proof_without_accumulator() {
+    let context = new_test_context(current_function_name!());
+    let resp = context.get("/state_proof?known_version=0").await;
+    let data: StateProofData = serde_json::from_value(resp).unwrap();
+    assert!(data.accumulator_summary.is_none());
+}
+
+#[tokio::test]
+async fn test_get_state_proof_known_version_too_large() {
+    let mut context = new_test_context(current_function_name!());
+    let resp = context
+        .expect_status_code(404)
+        .get("/state_proof?known_version=1000")
+        .await;
+    assert_eq!(resp["code"], 404);
+}
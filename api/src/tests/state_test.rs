@@ -5,6 +5,7 @@ use crate::{
     current_function_name,
     tests::{new_test_context, TestContext},
 };
+use aptos_api_types::X_APTOS_LEDGER_VERSION;
 use aptos_sdk::types::LocalAccount;
 use move_deps::{move_core_types::account_address::AccountAddress, move_package::BuildConfig};
 use serde::Serialize;
@@ -93,6 +94,54 @@ async fn test_get_account_module_not_found() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test]
+async fn test_head_account_resource() {
+    let context = new_test_context(current_function_name!());
+    let resp = context
+        .reply(head(&get_account_resource(
+            "0xA550C18",
+            "0x1::GUID::Generator",
+        )))
+        .await;
+    assert_eq!(resp.status(), 200);
+    assert!(resp.body().is_empty());
+    assert_eq!(
+        resp.headers()[X_APTOS_LEDGER_VERSION],
+        context.get_latest_ledger_info().version().to_string()
+    );
+}
+
+#[tokio::test]
+async fn test_head_account_resource_not_found() {
+    let context = new_test_context(current_function_name!());
+    let resp = context
+        .reply(head(&get_account_resource(
+            "0xA550C18",
+            "0x1::GUID::GeneratorX",
+        )))
+        .await;
+    assert_eq!(resp.status(), 404);
+    assert_eq!(
+        resp.headers()[X_APTOS_LEDGER_VERSION],
+        context.get_latest_ledger_info().version().to_string()
+    );
+}
+
+#[tokio::test]
+async fn test_head_account_module() {
+    let context = new_test_context(current_function_name!());
+    let resp = context
+        .reply(head(&get_account_module("0x1", "GUID")))
+        .await;
+    assert_eq!(resp.status(), 200);
+    assert!(resp.body().is_empty());
+
+    let resp = context
+        .reply(head(&get_account_module("0x1", "NoNoNo")))
+        .await;
+    assert_eq!(resp.status(), 404);
+}
+
 #[tokio::test]
 async fn test_get_table_item() {
     let mut context = new_test_context(current_function_name!());
@@ -176,6 +225,10 @@ async fn test_get_table_item() {
     assert_table_item(ctx, &nested_table, "u8", "u8", 2, 3).await;
 }
 
+fn head(path: &str) -> warp::test::RequestBuilder {
+    warp::test::request().method("HEAD").path(path)
+}
+
 fn get_account_resource(address: &str, struct_tag: &str) -> String {
     format!("/accounts/{}/resource/{}", address, struct_tag)
 }
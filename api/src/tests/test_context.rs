@@ -1,12 +1,14 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{context::Context, index, tests::pretty};
+use crate::{auth::ApiKeys, context::Context, index, tests::pretty};
 use aptos_api_types::{
     mime_types, HexEncodedBytes, TransactionOnChainData, X_APTOS_CHAIN_ID,
-    X_APTOS_LEDGER_TIMESTAMP, X_APTOS_LEDGER_VERSION,
+    X_APTOS_LEDGER_TIMESTAMP, X_APTOS_LEDGER_VERSION, X_REQUEST_ID,
+};
+use aptos_config::config::{
+    ApiConfig, RocksdbConfig, StoragePrunerConfig, NO_OP_STORAGE_PRUNER_CONFIG,
 };
-use aptos_config::config::ApiConfig;
 use aptos_crypto::{hash::HashValue, SigningKey};
 use aptos_genesis_tool::validator_builder::{RootKeys, ValidatorBuilder};
 use aptos_global_constants::OWNER_ACCOUNT;
@@ -47,6 +49,14 @@ use vm_validator::vm_validator::VMValidator;
 use warp::http::header::CONTENT_TYPE;
 
 pub fn new_test_context(test_name: &'static str) -> TestContext {
+    new_test_context_with_pruner(test_name, NO_OP_STORAGE_PRUNER_CONFIG)
+}
+
+/// A test context whose DB prunes the data out of the windows of `pruner_config`
+pub fn new_test_context_with_pruner(
+    test_name: &'static str,
+    pruner_config: StoragePrunerConfig,
+) -> TestContext {
     let tmp_dir = TempPath::new();
     tmp_dir.create_as_dir().unwrap();
 
@@ -59,7 +69,10 @@ pub fn new_test_context(test_name: &'static str) -> TestContext {
     let (root_keys, genesis, genesis_waypoint, validators) = builder.build(&mut rng).unwrap();
     let validator_owner = validators[0].storage().get(OWNER_ACCOUNT).unwrap().value;
 
-    let (db, db_rw) = DbReaderWriter::wrap(AptosDB::new_for_test(&tmp_dir));
+    let (db, db_rw) = DbReaderWriter::wrap(
+        AptosDB::open(&tmp_dir, false, pruner_config, RocksdbConfig::default())
+            .expect("Unable to open AptosDB"),
+    );
     let ret =
         db_bootstrapper::maybe_bootstrap::<AptosVM>(&db_rw, &genesis, genesis_waypoint).unwrap();
     assert!(ret);
@@ -72,6 +85,7 @@ pub fn new_test_context(test_name: &'static str) -> TestContext {
             db.clone(),
             mempool.ac_client.clone(),
             ApiConfig::default(),
+            ApiKeys::default(),
         ),
         rng,
         root_keys,
@@ -365,7 +379,14 @@ impl TestContext {
         let headers = resp.headers();
         assert_eq!(headers[CONTENT_TYPE], mime_types::JSON);
 
-        let body = serde_json::from_slice(resp.body()).expect("response body is JSON");
+        let mut body: Value = serde_json::from_slice(resp.body()).expect("response body is JSON");
+        // The request id is random, so it's left out of the body once checked against the header
+        if let Some(request_id) = body
+            .as_object_mut()
+            .and_then(|body| body.remove("request_id"))
+        {
+            assert_eq!(request_id, headers[X_REQUEST_ID].to_str().unwrap());
+        }
         assert_eq!(
             self.expect_status_code,
             resp.status(),
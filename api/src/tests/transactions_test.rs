@@ -2,11 +2,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    auth::ApiKeys,
+    context::Context,
     current_function_name,
     tests::{assert_json, new_test_context, pretty, TestContext},
 };
 
-use aptos_api_types::HexEncodedBytes;
+use aptos_api_types::{mime_types, HexEncodedBytes};
+use aptos_config::config::ApiConfig;
 use aptos_crypto::{
     multi_ed25519::{MultiEd25519PrivateKey, MultiEd25519PublicKey},
     PrivateKey, SigningKey, Uniform,
@@ -15,6 +18,7 @@ use aptos_sdk::types::LocalAccount;
 use aptos_types::{
     access_path::{AccessPath, Path},
     account_address::AccountAddress,
+    chain_id::ChainId,
     transaction::{
         authenticator::{AuthenticationKey, TransactionAuthenticator},
         ChangeSet, Script, ScriptFunction, SignedTransaction,
@@ -29,7 +33,8 @@ use move_deps::move_core_types::{
     language_storage::{ModuleId, StructTag, TypeTag},
 };
 use rand::{distributions::Alphanumeric, thread_rng, Rng};
-use serde_json::json;
+use serde_json::{json, Value};
+use warp::http::header::{CONTENT_TYPE, RETRY_AFTER};
 
 #[tokio::test]
 async fn test_deserialize_genesis_transaction() {
@@ -43,10 +48,48 @@ async fn test_deserialize_genesis_transaction() {
 #[tokio::test]
 async fn test_get_transactions_output_genesis_transaction() {
     let mut context = new_test_context(current_function_name!());
-    let resp = context.get("/transactions").await;
+    let resp = context.get("/transactions?full=true").await;
     context.check_golden_output(resp);
 }
 
+#[tokio::test]
+async fn test_get_genesis_transaction_summary() {
+    let context = new_test_context(current_function_name!());
+    let summary = context.get("/transactions/0").await;
+    assert_eq!(summary["type"], "genesis_transaction");
+    assert_eq!(summary["payload"]["type"], "write_set_summary");
+    assert_eq!(summary["changes"], json!([]));
+    assert!(!summary["events"].as_array().unwrap().is_empty());
+
+    let full = context.get("/transactions/0?full=true").await;
+    assert_eq!(full["payload"]["type"], "write_set_payload");
+    assert_eq!(full["hash"], summary["hash"]);
+    assert_eq!(full["events"], summary["events"]);
+    let changes = full["changes"].as_array().unwrap();
+    assert_eq!(
+        summary["payload"]["num_changes"],
+        json!(changes.len().to_string())
+    );
+    let modules = changes
+        .iter()
+        .filter(|change| change["type"] == "write_module")
+        .count();
+    assert!(modules > 0);
+    assert_eq!(
+        summary["payload"]["modules"].as_array().unwrap().len(),
+        modules
+    );
+    assert_eq!(
+        summary["payload"]["num_modules"],
+        json!(modules.to_string())
+    );
+
+    // The full genesis transaction is cached, which doesn't change it
+    assert_eq!(context.get("/transactions/0?full=true").await, full);
+    let listed = context.get("/transactions?start=0&limit=1").await;
+    assert_eq!(listed[0], summary);
+}
+
 #[tokio::test]
 async fn test_get_transactions_returns_last_page_when_start_version_is_not_specified() {
     let mut context = new_test_context(current_function_name!());
@@ -282,6 +325,42 @@ async fn test_post_transaction_rejected_by_mempool() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test]
+async fn test_post_transaction_rejected_by_mempool_backpressure() {
+    let mut context = new_test_context(current_function_name!());
+    // Any mempool occupancy is above the threshold
+    let api_config = ApiConfig {
+        mempool_backpressure_threshold_pct: 0,
+        mempool_backpressure_retry_after_secs: 7,
+        ..ApiConfig::default()
+    };
+    context.context = Context::new(
+        ChainId::test(),
+        context.db.clone(),
+        context.mempool.ac_client.clone(),
+        api_config,
+        ApiKeys::default(),
+    );
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("POST")
+                .path("/transactions")
+                .header(CONTENT_TYPE, mime_types::BCS_SIGNED_TRANSACTION)
+                .body(bcs::to_bytes(&txn).unwrap()),
+        )
+        .await;
+    assert_eq!(resp.status(), 429);
+    assert_eq!(resp.headers()[RETRY_AFTER], "7");
+    let body: Value = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body["retry_after_secs"], "7");
+    assert_eq!(body["mempool_queue"]["size"], "0");
+    assert!(context.mempool.get_txns(1).is_empty());
+}
+
 #[ignore]
 #[tokio::test]
 async fn test_multi_agent_signed_transaction() {
@@ -475,6 +554,55 @@ async fn test_get_transaction_by_version() {
     assert_json(resp, txns[0].clone())
 }
 
+#[tokio::test]
+async fn test_get_transactions_without_events_and_changes() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    context.commit_block(&vec![txn.clone()]).await;
+
+    // The genesis transaction has both events and changes
+    let genesis = context.get("/transactions?start=0&limit=1&full=true").await;
+    assert!(!genesis[0]["events"].as_array().unwrap().is_empty());
+    assert!(!genesis[0]["changes"].as_array().unwrap().is_empty());
+
+    let without_events = context
+        .get("/transactions?start=0&limit=1&include_events=false&full=true")
+        .await;
+    assert_eq!(without_events[0]["events"], json!([]));
+    assert_eq!(without_events[0]["changes"], genesis[0]["changes"]);
+
+    let headers = context
+        .get("/transactions/0?include_events=false&include_changes=false&full=true")
+        .await;
+    assert_eq!(headers["events"], json!([]));
+    assert_eq!(headers["changes"], json!([]));
+    assert_eq!(headers["hash"], genesis[0]["hash"]);
+
+    let root_address = context.root_account().address();
+    let account_txns = context
+        .get(&format!("/accounts/{}/transactions", root_address))
+        .await;
+    assert!(!account_txns[0]["changes"].as_array().unwrap().is_empty());
+    let without_changes = context
+        .get(&format!(
+            "/accounts/{}/transactions?include_changes=false",
+            root_address
+        ))
+        .await;
+    assert_eq!(without_changes[0]["changes"], json!([]));
+    assert_eq!(without_changes[0]["events"], account_txns[0]["events"]);
+
+    let resp = context
+        .expect_status_code(400)
+        .get("/transactions?include_events=hello")
+        .await;
+    assert_eq!(
+        resp["message"],
+        json!("invalid parameter include_events: hello")
+    );
+}
+
 #[tokio::test]
 async fn test_get_pending_transaction_by_hash() {
     let mut context = new_test_context(current_function_name!());
@@ -6,52 +6,125 @@ use crate::{
     failpoint::fail_point,
     metrics::metrics,
     page::Page,
-    param::{AddressParam, TransactionIdParam},
+    param::{AddressParam, Param, TransactionIdParam},
+    precondition,
 };
 
 use aptos_api_types::{
-    mime_types::BCS_SIGNED_TRANSACTION, AsConverter, Error, LedgerInfo, Response, Transaction,
-    TransactionData, TransactionId, TransactionOnChainData, TransactionSigningMessage,
-    UserTransactionRequest,
+    mime_types::BCS_SIGNED_TRANSACTION, AsConverter, Error, ErrorCode, LedgerInfo, MoveConverter,
+    Response, Transaction, TransactionData, TransactionId, TransactionOnChainData,
+    TransactionSigningMessage, UserTransactionRequest,
 };
+use aptos_mempool::MempoolQueueStatus;
 use aptos_types::{
     mempool_status::MempoolStatusCode,
     transaction::{RawTransaction, SignedTransaction},
 };
+use aptos_vm::move_vm_ext::MoveResolverExt;
 
 use anyhow::Result;
+use serde::Deserialize;
+use std::fmt::Display;
 use warp::{
     filters::BoxedFilter,
     http::{header::CONTENT_TYPE, StatusCode},
     reply, Filter, Rejection, Reply,
 };
 
-// GET /transactions/{txn-hash / version}
+/// Query flags of the transaction endpoints, which leave the events or the write set changes out
+/// of the returned transactions when false, e.g. for indexers only needing the transactions.
+/// Both are included by default. Pending transactions have neither, whatever the flags.
+///
+/// Genesis transactions come with a summary of their write set unless `full` is true, as their
+/// write set holds the bytecode of every module of the framework.
+#[derive(Clone, Debug, Deserialize)]
+struct ContentQuery {
+    include_events: Option<Param<bool>>,
+    include_changes: Option<Param<bool>>,
+    full: Option<Param<bool>>,
+}
+
+impl ContentQuery {
+    fn parse(self) -> Result<Content, Error> {
+        let include = |flag: Option<Param<bool>>, name| {
+            flag.map(|v| v.parse(name)).unwrap_or_else(|| Ok(true))
+        };
+        Ok(Content {
+            events: include(self.include_events, "include_events")?,
+            changes: include(self.include_changes, "include_changes")?,
+            full: self
+                .full
+                .map(|v| v.parse("full"))
+                .unwrap_or_else(|| Ok(false))?,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Content {
+    events: bool,
+    changes: bool,
+    full: bool,
+}
+
+impl Content {
+    /// Drops what isn't included from the data of a transaction, before it's converted
+    fn strip(self, mut data: TransactionOnChainData) -> TransactionOnChainData {
+        if !self.events {
+            data.events = vec![];
+        }
+        if !self.changes {
+            data.changes = Default::default();
+        }
+        data
+    }
+
+    /// Drops what isn't included from a rendered genesis transaction
+    fn strip_genesis(self, mut txn: Transaction) -> Transaction {
+        if let Transaction::GenesisTransaction(genesis) = &mut txn {
+            if !self.events {
+                genesis.events = vec![];
+            }
+            if !self.changes {
+                genesis.info.changes = vec![];
+            }
+        }
+        txn
+    }
+}
+
+// GET /transactions/{txn-hash / version}?include_events={bool}&include_changes={bool}&full={bool}
 pub fn get_transaction(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("transactions" / TransactionIdParam)
         .and(warp::get())
+        .and(warp::query::<ContentQuery>())
+        .and(precondition::ledger_version_precondition(context.clone()))
         .and(context.filter())
         .and_then(handle_get_transaction)
         .with(metrics("get_transaction"))
         .boxed()
 }
 
-// GET /transactions?start={u64}&limit={u16}
+// GET /transactions?start={u64}&limit={u16}&include_events={bool}&include_changes={bool}&full={bool}
 pub fn get_transactions(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("transactions")
         .and(warp::get())
         .and(warp::query::<Page>())
+        .and(warp::query::<ContentQuery>())
+        .and(precondition::ledger_version_precondition(context.clone()))
         .and(context.filter())
         .and_then(handle_get_transactions)
         .with(metrics("get_transactions"))
         .boxed()
 }
 
-// GET /accounts/{address}/transactions?start={u64}&limit={u16}
+// GET /accounts/{address}/transactions?start={u64}&limit={u16}&include_events={bool}&include_changes={bool}&full={bool}
 pub fn get_account_transactions(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("accounts" / AddressParam / "transactions")
         .and(warp::get())
         .and(warp::query::<Page>())
+        .and(warp::query::<ContentQuery>())
+        .and(precondition::ledger_version_precondition(context.clone()))
         .and(context.filter())
         .and_then(handle_get_account_transactions)
         .with(metrics("get_account_transactions"))
@@ -66,6 +139,7 @@ pub fn submit_json_transactions(context: Context) -> BoxedFilter<(impl Reply,)>
             context.content_length_limit(),
         ))
         .and(warp::body::json::<UserTransactionRequest>())
+        .and(precondition::ledger_version_precondition(context.clone()))
         .and(context.filter())
         .and_then(handle_submit_json_transactions)
         .with(metrics("submit_json_transactions"))
@@ -89,6 +163,7 @@ pub fn submit_bcs_transactions(context: Context) -> BoxedFilter<(impl Reply,)> {
             BCS_SIGNED_TRANSACTION,
         ))
         .and(warp::body::bytes())
+        .and(precondition::ledger_version_precondition(context.clone()))
         .and(context.filter())
         .and_then(handle_submit_bcs_transactions)
         .with(metrics("submit_bcs_transactions"))
@@ -103,6 +178,7 @@ pub fn create_signing_message(context: Context) -> BoxedFilter<(impl Reply,)> {
             context.content_length_limit(),
         ))
         .and(warp::body::json::<UserTransactionRequest>())
+        .and(precondition::ledger_version_precondition(context.clone()))
         .and(context.filter())
         .and_then(handle_create_signing_message)
         .with(metrics("create_signing_message"))
@@ -111,26 +187,32 @@ pub fn create_signing_message(context: Context) -> BoxedFilter<(impl Reply,)> {
 
 async fn handle_get_transaction(
     id: TransactionIdParam,
+    content: ContentQuery,
     context: Context,
 ) -> Result<impl Reply, Rejection> {
     fail_point("endpoint_get_transaction")?;
     Ok(Transactions::new(context)?
-        .get_transaction(id.parse("transaction hash or version")?)
+        .get_transaction(id.parse("transaction hash or version")?, content.parse()?)
         .await?)
 }
 
-async fn handle_get_transactions(page: Page, context: Context) -> Result<impl Reply, Rejection> {
+async fn handle_get_transactions(
+    page: Page,
+    content: ContentQuery,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
     fail_point("endpoint_get_transactions")?;
-    Ok(Transactions::new(context)?.list(page)?)
+    Ok(Transactions::new(context)?.list(page, content.parse()?)?)
 }
 
 async fn handle_get_account_transactions(
     address: AddressParam,
     page: Page,
+    content: ContentQuery,
     context: Context,
 ) -> Result<impl Reply, Rejection> {
     fail_point("endpoint_get_account_transactions")?;
-    Ok(Transactions::new(context)?.list_by_account(address, page)?)
+    Ok(Transactions::new(context)?.list_by_account(address, page, content.parse()?)?)
 }
 
 async fn handle_submit_json_transactions(
@@ -194,6 +276,15 @@ impl Transactions {
     }
 
     pub async fn create(self, txn: SignedTransaction) -> Result<impl Reply, Error> {
+        // Push back before mempool is full, so clients retry later instead of having their
+        // transactions dropped
+        let queue_status = self.context.get_mempool_queue_status().await?;
+        if queue_status.size as u64 * 100
+            >= queue_status.capacity as u64 * self.context.mempool_backpressure_threshold_pct()
+        {
+            return Err(self.backpressure_error("mempool is near capacity", queue_status));
+        }
+
         let (mempool_status, vm_status_opt) = self.context.submit_transaction(txn.clone()).await?;
         match mempool_status.code {
             MempoolStatusCode::Accepted => {
@@ -202,20 +293,35 @@ impl Transactions {
                 let resp = Response::new(self.ledger_info, &pending_txn)?;
                 Ok(reply::with_status(resp, StatusCode::ACCEPTED))
             }
+            MempoolStatusCode::MempoolIsFull | MempoolStatusCode::TooManyTransactions => {
+                let queue_status = self.context.get_mempool_queue_status().await?;
+                Err(self.backpressure_error(
+                    format!("transaction is rejected: {}", mempool_status),
+                    queue_status,
+                ))
+            }
             MempoolStatusCode::VmError => Err(Error::bad_request(format!(
                 "invalid transaction: {}",
                 vm_status_opt
                     .map(|s| format!("{:?}", s))
                     .unwrap_or_else(|| "UNKNOWN".to_owned())
-            ))),
-            _ => Err(Error::bad_request(format!(
-                "transaction is rejected: {}",
-                mempool_status,
-            ))),
+            ))
+            .error_code(ErrorCode::InvalidTransaction)),
+            _ => Err(
+                Error::bad_request(format!("transaction is rejected: {}", mempool_status,))
+                    .error_code(ErrorCode::TransactionRejected),
+            ),
         }
     }
 
-    pub fn list(self, page: Page) -> Result<impl Reply, Error> {
+    fn backpressure_error<S: Display>(&self, msg: S, queue_status: MempoolQueueStatus) -> Error {
+        Error::too_many_requests(msg, self.context.mempool_backpressure_retry_after_secs())
+            .error_code(ErrorCode::MempoolIsFull)
+            .mempool_queue(queue_status.size as u64, queue_status.capacity as u64)
+            .aptos_ledger_version(self.ledger_info.version())
+    }
+
+    fn list(self, page: Page, content: Content) -> Result<impl Reply, Error> {
         let ledger_version = self.ledger_info.version();
         let limit = page.limit()?;
         let last_page_start = if ledger_version > (limit as u64) {
@@ -224,25 +330,37 @@ impl Transactions {
             0
         };
         let start_version = page.start(last_page_start, ledger_version)?;
+        if start_version < self.ledger_info.oldest_version() {
+            return Err(self.transaction_pruned(TransactionId::Version(start_version)));
+        }
 
         let data = self
             .context
             .get_transactions(start_version, limit, ledger_version)?;
 
-        self.render_transactions(data)
+        self.render_transactions(data, content)
     }
 
-    pub fn list_by_account(self, address: AddressParam, page: Page) -> Result<impl Reply, Error> {
+    fn list_by_account(
+        self,
+        address: AddressParam,
+        page: Page,
+        content: Content,
+    ) -> Result<impl Reply, Error> {
         let data = self.context.get_account_transactions(
             address.parse("account address")?.into(),
             page.start(0, u64::MAX)?,
             page.limit()?,
             self.ledger_info.version(),
         )?;
-        self.render_transactions(data)
+        self.render_transactions(data, content)
     }
 
-    fn render_transactions(self, data: Vec<TransactionOnChainData>) -> Result<impl Reply, Error> {
+    fn render_transactions(
+        self,
+        data: Vec<TransactionOnChainData>,
+        content: Content,
+    ) -> Result<impl Reply, Error> {
         if data.is_empty() {
             let txns: Vec<Transaction> = vec![];
             return Response::new(self.ledger_info, &txns);
@@ -254,7 +372,7 @@ impl Transactions {
         let txns: Vec<Transaction> = data
             .into_iter()
             .map(|t| {
-                let txn = converter.try_into_onchain_transaction(timestamp, t)?;
+                let txn = self.render_transaction(&converter, timestamp, t, content)?;
                 // update timestamp, when txn is metadata block transaction
                 // new timestamp is used for the following transactions
                 timestamp = txn.timestamp();
@@ -264,7 +382,11 @@ impl Transactions {
         Response::new(self.ledger_info, &txns)
     }
 
-    pub async fn get_transaction(self, id: TransactionId) -> Result<impl Reply, Error> {
+    async fn get_transaction(
+        self,
+        id: TransactionId,
+        content: Content,
+    ) -> Result<impl Reply, Error> {
         let txn_data = match id.clone() {
             TransactionId::Hash(hash) => self.get_by_hash(hash.into()).await?,
             TransactionId::Version(version) => self.get_by_version(version)?,
@@ -275,9 +397,7 @@ impl Transactions {
         let txn = match txn_data {
             TransactionData::OnChain(txn) => {
                 let timestamp = self.context.get_block_timestamp(txn.version)?;
-                resolver
-                    .as_converter()
-                    .try_into_onchain_transaction(timestamp, txn)?
+                self.render_transaction(&resolver.as_converter(), timestamp, txn, content)?
             }
             TransactionData::Pending(txn) => {
                 resolver.as_converter().try_into_pending_transaction(*txn)?
@@ -287,6 +407,29 @@ impl Transactions {
         Response::new(self.ledger_info, &txn)
     }
 
+    fn render_transaction<R: MoveResolverExt>(
+        &self,
+        converter: &MoveConverter<R>,
+        timestamp: u64,
+        data: TransactionOnChainData,
+        content: Content,
+    ) -> Result<Transaction> {
+        if !matches!(
+            data.transaction,
+            aptos_types::transaction::Transaction::GenesisTransaction(_)
+        ) {
+            return converter.try_into_onchain_transaction(timestamp, content.strip(data));
+        }
+        if content.full {
+            let txn = self.context.full_genesis_transaction(data.version, || {
+                converter.try_into_onchain_transaction(timestamp, data)
+            })?;
+            Ok(content.strip_genesis(txn))
+        } else {
+            converter.try_into_summarized_onchain_transaction(timestamp, content.strip(data))
+        }
+    }
+
     pub fn signing_message(self, txn: UserTransactionRequest) -> Result<impl Reply, Error> {
         let resolver = self.context.move_resolver()?;
         let raw_txn: RawTransaction = resolver
@@ -303,13 +446,30 @@ impl Transactions {
     }
 
     fn transaction_not_found(&self, id: TransactionId) -> Error {
-        Error::not_found("transaction", id, self.ledger_info.version())
+        Error::not_found(
+            ErrorCode::TransactionNotFound,
+            "transaction",
+            id,
+            self.ledger_info.version(),
+        )
+    }
+
+    fn transaction_pruned(&self, id: TransactionId) -> Error {
+        Error::version_pruned(
+            "transaction",
+            id,
+            self.ledger_info.oldest_version(),
+            self.ledger_info.version(),
+        )
     }
 
-    fn get_by_version(&self, version: u64) -> Result<Option<TransactionData>> {
+    fn get_by_version(&self, version: u64) -> Result<Option<TransactionData>, Error> {
         if version > self.ledger_info.version() {
             return Ok(None);
         }
+        if version < self.ledger_info.oldest_version() {
+            return Err(self.transaction_pruned(TransactionId::Version(version)));
+        }
         Ok(Some(
             self.context
                 .get_transaction_by_version(version, self.ledger_info.version())?
@@ -0,0 +1,23 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{HexEncodedBytes, Transaction, U64};
+
+use serde::{Deserialize, Serialize};
+
+/// All the transactions of a block, from the block metadata transaction starting it to the last
+/// one before the next block, with the proof of their inclusion in the ledger. The proof only
+/// covers the transactions: the boundaries of the block come from the events indexed by the node
+/// and aren't proven.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct BlockTransactions {
+    /// Height of the block, which its block metadata transaction moves the chain to. The genesis
+    /// is the block at height 0.
+    pub height: U64,
+    pub first_version: U64,
+    pub last_version: U64,
+    pub transactions: Vec<Transaction>,
+    /// BCS-encoded `TransactionInfoListWithProof` of the transactions from `first_version`,
+    /// against the ledger info returned in the response headers.
+    pub proof: HexEncodedBytes,
+}
@@ -4,9 +4,10 @@
 use crate::{
     transaction::{ModuleBundlePayload, StateCheckpointTransaction},
     Bytecode, DirectWriteSet, Event, HexEncodedBytes, MoveFunction, MoveModuleBytecode,
-    MoveResource, MoveScriptBytecode, MoveValue, ScriptFunctionId, ScriptFunctionPayload,
+    MoveResource, MoveScriptBytecode, MoveType, MoveValue, ScriptFunctionId, ScriptFunctionPayload,
     ScriptPayload, ScriptWriteSet, Transaction, TransactionInfo, TransactionOnChainData,
     TransactionPayload, UserTransactionRequest, WriteSet, WriteSetChange, WriteSetPayload,
+    WriteSetSummary,
 };
 use anyhow::{bail, ensure, format_err, Result};
 use aptos_crypto::{hash::CryptoHash, HashValue};
@@ -108,6 +109,70 @@ impl<'a, R: MoveResolverExt + ?Sized> MoveConverter<'a, R> {
         })
     }
 
+    /// Converts the transaction like `try_into_onchain_transaction`, except for a genesis
+    /// transaction writing its write set directly, whose write set is summarized instead of
+    /// decoded: the genesis write set holds the bytecode of every module of the framework
+    pub fn try_into_summarized_onchain_transaction(
+        &self,
+        timestamp: u64,
+        data: TransactionOnChainData,
+    ) -> Result<Transaction> {
+        use aptos_types::transaction::{Transaction::GenesisTransaction, WriteSetPayload::Direct};
+        let change_set = match data.transaction {
+            GenesisTransaction(Direct(change_set)) => change_set,
+            transaction => {
+                return self.try_into_onchain_transaction(
+                    timestamp,
+                    TransactionOnChainData {
+                        transaction,
+                        ..data
+                    },
+                )
+            }
+        };
+        let info = self.into_transaction_info(
+            data.version,
+            &data.info,
+            data.accumulator_root_hash,
+            aptos_types::write_set::WriteSet::default(),
+        );
+        let events = self.try_into_events(&data.events)?;
+        let (write_set, write_set_events) = change_set.into_inner();
+        Ok((
+            info,
+            self.summarize_write_set(&write_set, &write_set_events),
+            events,
+        )
+            .into())
+    }
+
+    fn summarize_write_set(
+        &self,
+        write_set: &aptos_types::write_set::WriteSet,
+        events: &[ContractEvent],
+    ) -> WriteSetSummary {
+        let mut modules = Vec::new();
+        let (mut num_resources, mut num_table_items) = (0u64, 0u64);
+        for (state_key, _) in write_set {
+            match state_key {
+                StateKey::AccessPath(access_path) => match access_path.get_path() {
+                    Path::Code(module_id) => modules.push(module_id.into()),
+                    Path::Resource(_) => num_resources += 1,
+                },
+                StateKey::TableItem { .. } => num_table_items += 1,
+                StateKey::Raw(_) => {}
+            }
+        }
+        WriteSetSummary {
+            num_changes: (write_set.iter().len() as u64).into(),
+            num_modules: (modules.len() as u64).into(),
+            num_resources: num_resources.into(),
+            num_table_items: num_table_items.into(),
+            num_events: (events.len() as u64).into(),
+            modules,
+        }
+    }
+
     pub fn into_transaction_info(
         &self,
         version: u64,
@@ -151,19 +216,25 @@ impl<'a, R: MoveResolverExt + ?Sized> MoveConverter<'a, R> {
                 let func_args = self
                     .inner
                     .view_function_arguments(&module, &function, &args);
-                let json_args = match func_args {
-                    Ok(values) => values
-                        .into_iter()
-                        .map(|v| MoveValue::try_from(v)?.json())
-                        .collect::<Result<_>>()?,
-                    Err(_e) => args
-                        .into_iter()
-                        .map(|arg| HexEncodedBytes::from(arg).json())
-                        .collect::<Result<_>>()?,
+                let (json_args, argument_types) = match func_args {
+                    Ok(values) => (
+                        values
+                            .into_iter()
+                            .map(|v| MoveValue::try_from(v)?.json())
+                            .collect::<Result<_>>()?,
+                        self.script_function_argument_types(&module, &function),
+                    ),
+                    Err(_e) => (
+                        args.into_iter()
+                            .map(|arg| HexEncodedBytes::from(arg).json())
+                            .collect::<Result<_>>()?,
+                        None,
+                    ),
                 };
 
                 TransactionPayload::ScriptFunctionPayload(ScriptFunctionPayload {
                     arguments: json_args,
+                    argument_types,
                     function: ScriptFunctionId {
                         module: module.into(),
                         name: function,
@@ -175,6 +246,18 @@ impl<'a, R: MoveResolverExt + ?Sized> MoveConverter<'a, R> {
         Ok(ret)
     }
 
+    /// Returns the declared types of the non-signer parameters of a script function, which are
+    /// the ones passed as payload arguments
+    fn script_function_argument_types(
+        &self,
+        module: &ModuleId,
+        function: &Identifier,
+    ) -> Option<Vec<MoveType>> {
+        let code = self.inner.get_module(module).ok()? as Rc<dyn Bytecode>;
+        let func = code.find_script_function(function.as_ident_str())?;
+        Some(func.params.into_iter().filter(|p| !p.is_signer()).collect())
+    }
+
     pub fn try_into_write_set_payload(
         &self,
         payload: aptos_types::transaction::WriteSetPayload,
@@ -348,6 +431,7 @@ impl<'a, R: MoveResolverExt + ?Sized> MoveConverter<'a, R> {
                     function,
                     type_arguments,
                     arguments,
+                    argument_types: _,
                 } = script_func_payload;
 
                 let module = function.module.clone();
@@ -10,21 +10,119 @@ use warp::{http::StatusCode, reject::Reject};
 
 use crate::U64;
 
+/// Machine readable code of an API error, for clients to tell errors apart without parsing the
+/// messages. Several codes can share an HTTP status code.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// A path or query parameter is malformed or out of range
+    InvalidParameter,
+    /// The request body can't be deserialized or converted
+    InvalidRequestBody,
+    /// A submitted transaction failed validation, e.g. it has an invalid signature
+    InvalidTransaction,
+    /// Mempool rejected a submitted transaction, e.g. it's already in mempool
+    TransactionRejected,
+    /// Any other invalid request
+    BadRequest,
+    /// The request lacks valid credentials
+    Unauthorized,
+    /// The credentials of the request don't allow it
+    Forbidden,
+    AccountNotFound,
+    ResourceNotFound,
+    ModuleNotFound,
+    TableItemNotFound,
+    TransactionNotFound,
+    /// The requested block isn't committed yet
+    BlockNotFound,
+    /// The requested ledger version is newer than the latest one of the node
+    VersionNotFound,
+    /// The requested ledger version is older than the oldest one the node serves, the data at it
+    /// being pruned
+    VersionPruned,
+    /// The node hasn't reached the ledger version the request requires with the
+    /// `X-Aptos-If-Ledger-Version-At-Least` header yet
+    LedgerVersionNotReached,
+    /// No endpoint matches the request
+    NotFound,
+    MethodNotAllowed,
+    LengthRequired,
+    PayloadTooLarge,
+    UnsupportedMediaType,
+    /// The request is over a rate limit
+    RateLimited,
+    /// Mempool is too full to take the submitted transaction
+    MempoolIsFull,
+    InternalError,
+    /// A code this version of the types doesn't know about, from a newer node
+    #[serde(other)]
+    Unknown,
+}
+
+impl ErrorCode {
+    /// The code of errors that don't have a more specific one
+    fn from_status_code(code: StatusCode) -> Self {
+        match code {
+            StatusCode::BAD_REQUEST => ErrorCode::BadRequest,
+            StatusCode::UNAUTHORIZED => ErrorCode::Unauthorized,
+            StatusCode::FORBIDDEN => ErrorCode::Forbidden,
+            StatusCode::NOT_FOUND => ErrorCode::NotFound,
+            StatusCode::GONE => ErrorCode::VersionPruned,
+            StatusCode::PRECONDITION_FAILED => ErrorCode::LedgerVersionNotReached,
+            StatusCode::METHOD_NOT_ALLOWED => ErrorCode::MethodNotAllowed,
+            StatusCode::LENGTH_REQUIRED => ErrorCode::LengthRequired,
+            StatusCode::PAYLOAD_TOO_LARGE => ErrorCode::PayloadTooLarge,
+            StatusCode::UNSUPPORTED_MEDIA_TYPE => ErrorCode::UnsupportedMediaType,
+            StatusCode::TOO_MANY_REQUESTS => ErrorCode::RateLimited,
+            _ => ErrorCode::InternalError,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct Error {
     pub code: u16,
+    pub error_code: ErrorCode,
     pub message: String,
     /// Diem blockchain latest onchain ledger version.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub aptos_ledger_version: Option<U64>,
+    /// Oldest ledger version the node serves, set when the requested version is pruned.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oldest_ledger_version: Option<U64>,
+    /// Seconds to wait before retrying, set when the node is overloaded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after_secs: Option<U64>,
+    /// Occupancy of mempool, set when a transaction is rejected for lack of mempool capacity.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mempool_queue: Option<MempoolQueue>,
+    /// Whether the same request may succeed later, e.g. once the node caught up or is less busy.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub retriable: bool,
+    /// Id of the request, under which the node logged it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct MempoolQueue {
+    pub size: U64,
+    pub capacity: U64,
 }
 
 impl Error {
     pub fn new(code: StatusCode, message: String) -> Self {
         Self {
             code: code.as_u16(),
+            error_code: ErrorCode::from_status_code(code),
             message,
             aptos_ledger_version: None,
+            oldest_ledger_version: None,
+            retry_after_secs: None,
+            mempool_queue: None,
+            retriable: false,
+            request_id: None,
         }
     }
 
@@ -36,20 +134,77 @@ impl Error {
         Self::new(StatusCode::BAD_REQUEST, msg.to_string())
     }
 
-    pub fn not_found<S: Display>(resource: &str, identifier: S, ledger_version: u64) -> Self {
-        Self::new(
+    pub fn not_found<S: Display>(
+        error_code: ErrorCode,
+        resource: &str,
+        identifier: S,
+        ledger_version: u64,
+    ) -> Self {
+        let error = Self::new(
             StatusCode::NOT_FOUND,
             format!("{} not found by {}", resource, identifier),
         )
+        .error_code(error_code)
+        .aptos_ledger_version(ledger_version);
+        // A version or a block the node doesn't have yet exists once it catches up
+        if matches!(
+            error_code,
+            ErrorCode::VersionNotFound | ErrorCode::BlockNotFound
+        ) {
+            error.retriable()
+        } else {
+            error
+        }
+    }
+
+    /// The error for a request at a version the node pruned, which no retry can fix, unlike a
+    /// version the node doesn't have yet
+    pub fn version_pruned<S: Display>(
+        resource: &str,
+        identifier: S,
+        oldest_ledger_version: u64,
+        ledger_version: u64,
+    ) -> Self {
+        let mut error = Self::new(
+            StatusCode::GONE,
+            format!(
+                "{} pruned at {}, the oldest version available is {}",
+                resource, identifier, oldest_ledger_version
+            ),
+        )
+        .aptos_ledger_version(ledger_version);
+        error.oldest_ledger_version = Some(oldest_ledger_version.into());
+        error
+    }
+
+    /// The error for a request requiring a ledger version the node hasn't reached yet, which it
+    /// may have once it catches up
+    pub fn ledger_version_not_reached(required_version: u64, ledger_version: u64) -> Self {
+        Self::new(
+            StatusCode::PRECONDITION_FAILED,
+            format!(
+                "ledger version {} not reached, the latest version is {}",
+                required_version, ledger_version
+            ),
+        )
         .aptos_ledger_version(ledger_version)
+        .retriable()
     }
 
     pub fn invalid_param<S: Display>(name: &str, value: S) -> Self {
         Self::bad_request(format!("invalid parameter {}: {}", name, value))
+            .error_code(ErrorCode::InvalidParameter)
     }
 
     pub fn invalid_request_body<S: Display>(msg: S) -> Self {
         Self::bad_request(format!("invalid request body: {}", msg))
+            .error_code(ErrorCode::InvalidRequestBody)
+    }
+
+    pub fn too_many_requests<S: Display>(msg: S, retry_after_secs: u64) -> Self {
+        let mut error = Self::new(StatusCode::TOO_MANY_REQUESTS, msg.to_string()).retriable();
+        error.retry_after_secs = Some(retry_after_secs.into());
+        error
     }
 
     pub fn internal(err: anyhow::Error) -> Self {
@@ -60,10 +215,28 @@ impl Error {
         StatusCode::from_u16(self.code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
     }
 
+    pub fn error_code(mut self, error_code: ErrorCode) -> Self {
+        self.error_code = error_code;
+        self
+    }
+
+    pub fn retriable(mut self) -> Self {
+        self.retriable = true;
+        self
+    }
+
     pub fn aptos_ledger_version(mut self, ledger_version: u64) -> Self {
         self.aptos_ledger_version = Some(ledger_version.into());
         self
     }
+
+    pub fn mempool_queue(mut self, size: u64, capacity: u64) -> Self {
+        self.mempool_queue = Some(MempoolQueue {
+            size: size.into(),
+            capacity: capacity.into(),
+        });
+        self
+    }
 }
 
 impl fmt::Display for Error {
@@ -72,6 +245,9 @@ impl fmt::Display for Error {
         if let Some(val) = &self.aptos_ledger_version {
             write!(f, "\ndiem ledger version: {}", val)?;
         }
+        if let Some(request_id) = &self.request_id {
+            write!(f, "\nrequest id: {}", request_id)?;
+        }
         Ok(())
     }
 }
@@ -92,7 +268,7 @@ impl From<serde_json::error::Error> for Error {
 
 #[cfg(test)]
 mod tests {
-    use crate::error::Error;
+    use crate::error::{Error, ErrorCode};
     use warp::http::StatusCode;
 
     #[test]
@@ -117,6 +293,32 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_error_codes() {
+        let err = Error::invalid_param("limit", 0);
+        assert_eq!(err.error_code, ErrorCode::InvalidParameter);
+        assert!(!err.retriable);
+
+        let err = Error::not_found(ErrorCode::VersionNotFound, "ledger", "version(10)", 5);
+        assert_eq!(err.status_code(), StatusCode::NOT_FOUND);
+        assert!(err.retriable);
+
+        let err = Error::version_pruned("ledger", "version(10)", 100, 200);
+        assert_eq!(err.status_code(), StatusCode::GONE);
+        assert_eq!(err.error_code, ErrorCode::VersionPruned);
+        assert_eq!(err.oldest_ledger_version, Some(100.into()));
+        assert!(!err.retriable);
+
+        let err = Error::new(StatusCode::METHOD_NOT_ALLOWED, "not allowed".to_owned());
+        assert_eq!(err.error_code, ErrorCode::MethodNotAllowed);
+
+        let json = serde_json::to_value(&Error::too_many_requests("slow down", 1)).unwrap();
+        assert_eq!(json["error_code"], "rate_limited");
+        assert_eq!(json["retriable"], true);
+        let code: ErrorCode = serde_json::from_str("\"some_future_code\"").unwrap();
+        assert_eq!(code, ErrorCode::Unknown);
+    }
+
     #[test]
     fn test_internal_error() {
         let err = Error::internal(anyhow::format_err!("hello"));
@@ -3,7 +3,7 @@
 
 use crate::U64;
 
-use aptos_types::{chain_id::ChainId, ledger_info::LedgerInfoWithSignatures};
+use aptos_types::{chain_id::ChainId, ledger_info::LedgerInfoWithSignatures, transaction::Version};
 
 use serde::{Deserialize, Serialize};
 
@@ -13,16 +13,31 @@ pub struct LedgerInfo {
     pub epoch: u64,
     pub ledger_version: U64,
     pub ledger_timestamp: U64,
+    /// Latest version with a state checkpoint covered by the ledger info. Data up to this version
+    /// is final, while data past it may still be replayed, e.g. after a restore.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest_state_checkpoint_version: Option<U64>,
+    /// Oldest version the node still serves, older versions being pruned. Clients and indexers
+    /// need another node for the data before it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oldest_ledger_version: Option<U64>,
 }
 
 impl LedgerInfo {
-    pub fn new(chain_id: &ChainId, info: &LedgerInfoWithSignatures) -> Self {
+    pub fn new(
+        chain_id: &ChainId,
+        info: &LedgerInfoWithSignatures,
+        latest_state_checkpoint_version: Option<Version>,
+        oldest_ledger_version: Option<Version>,
+    ) -> Self {
         let ledger_info = info.ledger_info();
         Self {
             chain_id: chain_id.id(),
             epoch: ledger_info.epoch(),
             ledger_version: ledger_info.version().into(),
             ledger_timestamp: ledger_info.timestamp_usecs().into(),
+            latest_state_checkpoint_version: latest_state_checkpoint_version.map(U64::from),
+            oldest_ledger_version: oldest_ledger_version.map(U64::from),
         }
     }
 
@@ -33,4 +48,9 @@ impl LedgerInfo {
     pub fn timestamp(&self) -> u64 {
         self.ledger_timestamp.into()
     }
+
+    /// The oldest version the node serves, 0 if it doesn't report any
+    pub fn oldest_version(&self) -> u64 {
+        self.oldest_ledger_version.map(u64::from).unwrap_or(0)
+    }
 }
@@ -3,6 +3,7 @@
 
 mod account;
 mod address;
+mod block;
 mod bytecode;
 mod convert;
 mod error;
@@ -11,15 +12,18 @@ mod hash;
 mod ledger_info;
 pub mod mime_types;
 mod move_types;
+mod recording;
 mod response;
+mod state_proof;
 mod table;
 mod transaction;
 
 pub use account::AccountData;
 pub use address::Address;
+pub use block::BlockTransactions;
 pub use bytecode::Bytecode;
 pub use convert::{new_vm_ascii_string, AsConverter, MoveConverter};
-pub use error::Error;
+pub use error::{Error, ErrorCode, MempoolQueue};
 pub use event_key::EventKey;
 pub use hash::HashValue;
 pub use ledger_info::LedgerInfo;
@@ -28,14 +32,18 @@ pub use move_types::{
     MoveScriptBytecode, MoveStructTag, MoveStructValue, MoveType, MoveValue, ScriptFunctionId,
     U128, U64,
 };
+pub use recording::{ApiRecord, RecordedBody};
 pub use response::{
-    Response, X_APTOS_CHAIN_ID, X_APTOS_EPOCH, X_APTOS_LEDGER_TIMESTAMP, X_APTOS_LEDGER_VERSION,
+    Response, X_APTOS_CHAIN_ID, X_APTOS_EPOCH, X_APTOS_IF_LEDGER_VERSION_AT_LEAST,
+    X_APTOS_LATEST_STATE_CHECKPOINT_VERSION, X_APTOS_LEDGER_TIMESTAMP, X_APTOS_LEDGER_VERSION,
+    X_APTOS_OLDEST_LEDGER_VERSION, X_REQUEST_ID,
 };
+pub use state_proof::StateProofData;
 pub use table::TableItemRequest;
 pub use transaction::{
-    BlockMetadataTransaction, DirectWriteSet, Event, GenesisTransaction, PendingTransaction,
-    ScriptFunctionPayload, ScriptPayload, ScriptWriteSet, Transaction, TransactionData,
-    TransactionId, TransactionInfo, TransactionOnChainData, TransactionPayload,
+    BlockMetadataTransaction, DirectWriteSet, Event, GenesisPayload, GenesisTransaction,
+    PendingTransaction, ScriptFunctionPayload, ScriptPayload, ScriptWriteSet, Transaction,
+    TransactionData, TransactionId, TransactionInfo, TransactionOnChainData, TransactionPayload,
     TransactionSigningMessage, UserTransaction, UserTransactionRequest, WriteSet, WriteSetChange,
-    WriteSetPayload,
+    WriteSetPayload, WriteSetSummary,
 };
@@ -0,0 +1,51 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// A request made to the REST API of a node and the response to it, as recorded by the tests so
+/// that the request can be replayed against the API alone. Recordings hold one record per line.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ApiRecord {
+    /// The tests running when the request was made, more than one when tests run in parallel
+    pub tests: Vec<String>,
+    /// Url of the API the request was made to, e.g. `http://10.0.0.1:8080/`
+    pub endpoint: String,
+    pub method: String,
+    /// Path and query of the request, relative to the endpoint
+    pub path: String,
+    pub content_type: Option<String>,
+    pub request_body: RecordedBody,
+    pub status: u16,
+    /// Id the node logged the request under, from the `X-Request-Id` header of the response
+    pub request_id: Option<String>,
+    pub response_body: RecordedBody,
+    /// Milliseconds the node took to respond
+    pub elapsed_ms: u64,
+}
+
+/// Body of a recorded request or response, kept as text unless it's binary, e.g. a BCS encoded
+/// transaction
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordedBody {
+    Text(String),
+    Hex(String),
+}
+
+impl RecordedBody {
+    pub fn new(bytes: &[u8]) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => Self::Text(text.to_owned()),
+            Err(_) => Self::Hex(hex::encode(bytes)),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        Ok(match self {
+            Self::Text(text) => text.as_bytes().to_vec(),
+            Self::Hex(hex) => hex::decode(hex)?,
+        })
+    }
+}
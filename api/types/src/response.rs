@@ -11,6 +11,15 @@ pub const X_APTOS_CHAIN_ID: &str = "X-Aptos-Chain-Id";
 pub const X_APTOS_EPOCH: &str = "X-Aptos-Epoch";
 pub const X_APTOS_LEDGER_VERSION: &str = "X-Aptos-Ledger-Version";
 pub const X_APTOS_LEDGER_TIMESTAMP: &str = "X-Aptos-Ledger-TimestampUsec";
+pub const X_APTOS_LATEST_STATE_CHECKPOINT_VERSION: &str = "X-Aptos-Latest-State-Checkpoint-Version";
+pub const X_APTOS_OLDEST_LEDGER_VERSION: &str = "X-Aptos-Oldest-Ledger-Version";
+/// Ledger version a request requires the node to have reached, e.g. that of a transaction the
+/// client just submitted through another node. The node waits a little for it before answering
+/// with 412 Precondition Failed.
+pub const X_APTOS_IF_LEDGER_VERSION_AT_LEAST: &str = "X-Aptos-If-Ledger-Version-At-Least";
+/// Id of the request, given by the client or else generated by the node, under which the node
+/// logs it
+pub const X_REQUEST_ID: &str = "X-Request-Id";
 
 pub struct Response {
     pub ledger_info: LedgerInfo,
@@ -24,6 +33,14 @@ impl Response {
             body: serde_json::to_vec(body)?,
         })
     }
+
+    /// Response with the ledger info headers only, answering a HEAD request
+    pub fn empty(ledger_info: LedgerInfo) -> Self {
+        Self {
+            ledger_info,
+            body: vec![],
+        }
+    }
 }
 
 impl warp::Reply for Response {
@@ -42,6 +59,12 @@ impl warp::Reply for Response {
             self.ledger_info.ledger_timestamp.into(),
         );
         headers.insert(X_APTOS_EPOCH, self.ledger_info.epoch.into());
+        if let Some(version) = self.ledger_info.latest_state_checkpoint_version {
+            headers.insert(X_APTOS_LATEST_STATE_CHECKPOINT_VERSION, version.into());
+        }
+        if let Some(version) = self.ledger_info.oldest_ledger_version {
+            headers.insert(X_APTOS_OLDEST_LEDGER_VERSION, version.into());
+        }
 
         res
     }
@@ -0,0 +1,17 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::HexEncodedBytes;
+
+use serde::{Deserialize, Serialize};
+
+/// BCS-encoded proofs a light client needs to ratchet its trusted state up to the ledger info
+/// returned in the response headers.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct StateProofData {
+    /// BCS-encoded `StateProof` from the client's known version to the latest ledger info.
+    pub state_proof: HexEncodedBytes,
+    /// BCS-encoded `TransactionAccumulatorSummary` at the client's known version. Only returned
+    /// when requested, since clients starting from an epoch waypoint have no accumulator yet.
+    pub accumulator_summary: Option<HexEncodedBytes>,
+}
@@ -251,6 +251,16 @@ impl From<(TransactionInfo, WriteSetPayload, Vec<Event>)> for Transaction {
     }
 }
 
+impl From<(TransactionInfo, WriteSetSummary, Vec<Event>)> for Transaction {
+    fn from((info, summary, events): (TransactionInfo, WriteSetSummary, Vec<Event>)) -> Self {
+        Transaction::GenesisTransaction(GenesisTransaction {
+            info,
+            payload: GenesisPayload::WriteSetSummary(summary),
+            events,
+        })
+    }
+}
+
 impl From<(&BlockMetadata, TransactionInfo)> for Transaction {
     fn from((txn, info): (&BlockMetadata, TransactionInfo)) -> Self {
         Transaction::BlockMetadataTransaction(BlockMetadataTransaction {
@@ -374,6 +384,7 @@ impl From<(&ContractEvent, serde_json::Value)> for Event {
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum GenesisPayload {
     WriteSetPayload(WriteSetPayload),
+    WriteSetSummary(WriteSetSummary),
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -395,6 +406,11 @@ pub struct ScriptFunctionPayload {
     pub function: ScriptFunctionId,
     pub type_arguments: Vec<MoveType>,
     pub arguments: Vec<serde_json::Value>,
+    /// Declared types of the arguments, taken from the ABI of the published module. Only set
+    /// when the arguments could be decoded with that ABI. Compiled modules do not keep parameter
+    /// names, so arguments are matched with their types by position.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub argument_types: Option<Vec<MoveType>>,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -425,6 +441,19 @@ pub struct WriteSetPayload {
     pub write_set: WriteSet,
 }
 
+/// Summary of the write set of a genesis transaction, which the API returns in place of the write
+/// set unless asked for it, as the write set holds the bytecode of all the genesis modules
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WriteSetSummary {
+    pub num_changes: U64,
+    pub num_modules: U64,
+    pub num_resources: U64,
+    pub num_table_items: U64,
+    pub num_events: U64,
+    /// The modules the write set writes or deletes
+    pub modules: Vec<MoveModuleId>,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum WriteSet {
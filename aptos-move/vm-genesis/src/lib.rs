@@ -55,22 +55,17 @@ pub fn encode_genesis_transaction(
     aptos_root_key: Ed25519PublicKey,
     validators: &[Validator],
     stdlib_module_bytes: &[Vec<u8>],
+    initial_state: &InitialState,
+    genesis_configuration: &GenesisConfiguration,
     chain_id: ChainId,
     min_price_per_gas_unit: u64,
 ) -> Transaction {
-    let consensus_config = OnChainConsensusConfig::V2(ConsensusConfigV2 {
-        two_chain: true,
-        decoupled_execution: true,
-        back_pressure_limit: 10,
-        exclude_round: 20,
-    });
-
     Transaction::GenesisTransaction(WriteSetPayload::Direct(encode_genesis_change_set(
         &aptos_root_key,
         validators,
         stdlib_module_bytes,
-        VMPublishingOption::open(),
-        consensus_config,
+        initial_state,
+        genesis_configuration,
         chain_id,
         min_price_per_gas_unit,
     )))
@@ -80,8 +75,8 @@ pub fn encode_genesis_change_set(
     aptos_root_key: &Ed25519PublicKey,
     validators: &[Validator],
     stdlib_module_bytes: &[Vec<u8>],
-    vm_publishing_option: VMPublishingOption,
-    consensus_config: OnChainConsensusConfig,
+    initial_state: &InitialState,
+    genesis_configuration: &GenesisConfiguration,
     chain_id: ChainId,
     min_price_per_gas_unit: u64,
 ) -> ChangeSet {
@@ -102,13 +97,13 @@ pub fn encode_genesis_change_set(
     create_and_initialize_main_accounts(
         &mut session,
         aptos_root_key,
-        vm_publishing_option,
-        consensus_config,
+        genesis_configuration,
         chain_id,
         min_price_per_gas_unit,
     );
     // generate the genesis WriteSet
     create_and_initialize_validators(&mut session, validators);
+    create_initial_accounts(&mut session, &initial_state.accounts);
     reconfigure(&mut session);
 
     let mut session1_out = session.finish().unwrap();
@@ -123,6 +118,7 @@ pub fn encode_genesis_change_set(
     let mut session = move_vm.new_session(&data_cache, SessionId::genesis(id2));
 
     publish_stdlib(&mut session, Modules::new(stdlib_modules.iter()));
+    publish_packages(&mut session, &initial_state.packages);
     let session2_out = session.finish().unwrap();
 
     session1_out.squash(session2_out).unwrap();
@@ -168,8 +164,7 @@ fn exec_function(
 fn create_and_initialize_main_accounts(
     session: &mut SessionExt<impl MoveResolver>,
     aptos_root_key: &Ed25519PublicKey,
-    publishing_option: VMPublishingOption,
-    consensus_config: OnChainConsensusConfig,
+    genesis_configuration: &GenesisConfiguration,
     chain_id: ChainId,
     min_price_per_gas_unit: u64,
 ) {
@@ -177,10 +172,11 @@ fn create_and_initialize_main_accounts(
 
     let root_aptos_root_address = account_config::aptos_root_address();
 
+    let publishing_option = &genesis_configuration.vm_publishing_option;
     let initial_allow_list = MoveValue::Vector(
         publishing_option
             .script_allow_list
-            .into_iter()
+            .iter()
             .map(|hash| MoveValue::vector_u8(hash.to_vec().into_iter().collect()))
             .collect(),
     );
@@ -191,11 +187,11 @@ fn create_and_initialize_main_accounts(
     let native_gas_costs = bcs::to_bytes(&genesis_gas_schedule.native_table)
         .expect("Failure serializing genesis native gas costs");
 
-    let consensus_config_bytes =
-        bcs::to_bytes(&consensus_config).expect("Failure serializing genesis consensus config");
+    let consensus_config_bytes = bcs::to_bytes(&genesis_configuration.consensus_config)
+        .expect("Failure serializing genesis consensus config");
 
+    let epoch_interval = genesis_configuration.epoch_duration_secs * 1000000;
     // TODO: make these configurable
-    let epoch_interval = 86400 * 1000000;
     let minimum_stake = 0;
     let maximum_stake = 1000000;
 
@@ -262,6 +258,35 @@ fn create_and_initialize_validators(
     );
 }
 
+/// Creates the accounts of the initial state and mints their balances.
+fn create_initial_accounts(
+    session: &mut SessionExt<impl MoveResolver>,
+    accounts: &[(Ed25519PublicKey, u64)],
+) {
+    let aptos_root_address = account_config::aptos_root_address();
+    for (public_key, balance) in accounts {
+        let address = AuthenticationKey::ed25519(public_key).derived_address();
+        exec_function(
+            session,
+            "Account",
+            "create_account",
+            vec![],
+            serialize_values(&vec![MoveValue::Address(address)]),
+        );
+        exec_function(
+            session,
+            "TestCoin",
+            "mint",
+            vec![],
+            serialize_values(&vec![
+                MoveValue::Signer(aptos_root_address),
+                MoveValue::Address(address),
+                MoveValue::U64(*balance),
+            ]),
+        );
+    }
+}
+
 /// Publish the standard library.
 fn publish_stdlib(session: &mut SessionExt<impl MoveResolver>, stdlib: Modules) {
     let dep_graph = stdlib.compute_dependency_graph();
@@ -293,6 +318,22 @@ fn publish_stdlib(session: &mut SessionExt<impl MoveResolver>, stdlib: Modules)
         .unwrap_or_else(|e| panic!("Failure publishing modules {:?}", e));
 }
 
+/// Publish the packages of the initial state, each under the address of its modules.
+fn publish_packages(session: &mut SessionExt<impl MoveResolver>, packages: &[Vec<Vec<u8>>]) {
+    for package in packages {
+        let module = package
+            .first()
+            .expect("Packages must have at least one module");
+        let address = *CompiledModule::deserialize(module)
+            .unwrap_or_else(|e| panic!("Failure deserializing package module {:?}", e))
+            .self_id()
+            .address();
+        session
+            .publish_module_bundle(package.clone(), address, &mut GasStatus::new_unmetered())
+            .unwrap_or_else(|e| panic!("Failure publishing package {:?}", e));
+    }
+}
+
 /// Trigger a reconfiguration. This emits an event that will be passed along to the storage layer.
 fn reconfigure(session: &mut SessionExt<impl MoveResolver>) {
     exec_function(
@@ -350,6 +391,43 @@ pub fn test_genesis_change_set_and_validators(
     )
 }
 
+/// Accounts and packages created at genesis on top of the framework, for tests which need on-chain
+/// state from the start
+#[derive(Clone, Debug, Default)]
+pub struct InitialState {
+    /// Accounts to create, with their initial TestCoin balance
+    pub accounts: Vec<(Ed25519PublicKey, u64)>,
+    /// Module bundles to publish, each under the address of its modules
+    pub packages: Vec<Vec<Vec<u8>>>,
+}
+
+/// On-chain configuration the chain starts with, for tests which need it in a given state from
+/// the start rather than reconfiguring it
+#[derive(Clone, Debug, PartialEq)]
+pub struct GenesisConfiguration {
+    /// Includes the limits on the size of blocks from `ConsensusConfigV3` on
+    pub consensus_config: OnChainConsensusConfig,
+    /// Which scripts may run and whether modules may be published
+    pub vm_publishing_option: VMPublishingOption,
+    /// How long an epoch lasts before the validator set is reconfigured
+    pub epoch_duration_secs: u64,
+}
+
+impl Default for GenesisConfiguration {
+    fn default() -> Self {
+        Self {
+            consensus_config: OnChainConsensusConfig::V2(ConsensusConfigV2 {
+                two_chain: true,
+                decoupled_execution: true,
+                back_pressure_limit: 10,
+                exclude_round: 20,
+            }),
+            vm_publishing_option: VMPublishingOption::open(),
+            epoch_duration_secs: 86400,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Validator {
     /// The Aptos account address of the validator
@@ -422,8 +500,12 @@ pub fn generate_test_genesis(
         &GENESIS_KEYPAIR.1,
         validators,
         stdlib_modules,
-        vm_publishing_option,
-        OnChainConsensusConfig::V1(ConsensusConfigV1 { two_chain: true }),
+        &InitialState::default(),
+        &GenesisConfiguration {
+            consensus_config: OnChainConsensusConfig::V1(ConsensusConfigV1 { two_chain: true }),
+            vm_publishing_option,
+            ..GenesisConfiguration::default()
+        },
         ChainId::test(),
         0,
     );
@@ -33,6 +33,7 @@ use backup_service::start_backup_service;
 use consensus::consensus_provider::start_consensus;
 use consensus_notifications::ConsensusNotificationListener;
 use data_streaming_service::{
+    data_stream::ActiveDataStreams,
     streaming_client::{new_streaming_service_client_listener_pair, StreamingServiceClient},
     streaming_service::DataStreamingService,
 };
@@ -64,7 +65,8 @@ use storage_interface::{state_view::DbStateViewAtVersion, DbReaderWriter};
 use storage_service::start_storage_service_with_db;
 use storage_service_client::{StorageServiceClient, StorageServiceMultiSender};
 use storage_service_server::{
-    network::StorageServiceNetworkEvents, StorageReader, StorageServiceServer,
+    network::StorageServiceNetworkEvents, scheduler::RequestScheduler, StorageReader,
+    StorageServiceServer,
 };
 use tokio::runtime::{Builder, Runtime};
 use tokio_stream::wrappers::IntervalStream;
@@ -128,7 +130,7 @@ pub fn load_test_environment<R>(
     random_ports: bool,
     lazy: bool,
     genesis_modules: Vec<Vec<u8>>,
-    rng: R,
+    mut rng: R,
 ) where
     R: ::rand::RngCore + ::rand::CryptoRng,
 {
@@ -177,7 +179,11 @@ pub fn load_test_environment<R>(
         .template(template)
         .randomize_first_validator_ports(random_ports);
 
-        let (root_keys, _genesis, genesis_waypoint, validators) = builder.build(rng).unwrap();
+        let mut seed = [0u8; 32];
+        rng.fill_bytes(&mut seed);
+        let (root_keys, _genesis, genesis_waypoint, validators) =
+            aptos_genesis_tool::validator_builder::build_test_network_genesis(builder, seed)
+                .unwrap();
 
         let serialized_keys = bcs::to_bytes(&root_keys.root_key).unwrap();
         let mut key_file = std::fs::File::create(&aptos_root_key_path).unwrap();
@@ -234,7 +240,12 @@ fn fetch_chain_id(db: &DbReaderWriter) -> ChainId {
         .chain_id()
 }
 
-fn setup_debug_interface(config: &NodeConfig, logger: Option<Arc<Logger>>) -> NodeDebugService {
+fn setup_debug_interface(
+    config: &NodeConfig,
+    logger: Option<Arc<Logger>>,
+    peer_metadata_storage: Arc<PeerMetadataStorage>,
+    active_data_streams: ActiveDataStreams,
+) -> NodeDebugService {
     let addr = format!(
         "{}:{}",
         config.debug_interface.address, config.debug_interface.admission_control_node_debug_port,
@@ -244,12 +255,18 @@ fn setup_debug_interface(config: &NodeConfig, logger: Option<Arc<Logger>>) -> No
     .next()
     .unwrap();
 
-    NodeDebugService::new(addr, logger, config)
+    NodeDebugService::new(
+        addr,
+        logger,
+        config,
+        peer_metadata_storage,
+        active_data_streams,
+    )
 }
 
 fn create_state_sync_runtimes<M: MempoolNotificationSender + 'static>(
     node_config: &NodeConfig,
-    storage_service_server_network_handles: Vec<StorageServiceNetworkEvents>,
+    storage_service_server_network_handles: Vec<(NetworkId, StorageServiceNetworkEvents)>,
     storage_service_client_network_handles: HashMap<
         NetworkId,
         storage_service_client::StorageServiceNetworkSender,
@@ -261,6 +278,7 @@ fn create_state_sync_runtimes<M: MempoolNotificationSender + 'static>(
     waypoint: Waypoint,
     event_subscription_service: EventSubscriptionService,
     db_rw: DbReaderWriter,
+    active_data_streams: ActiveDataStreams,
 ) -> StateSyncRuntimes {
     // Start the state sync storage service
     let storage_service_runtime = setup_state_sync_storage_service(
@@ -281,6 +299,7 @@ fn create_state_sync_runtimes<M: MempoolNotificationSender + 'static>(
     let (streaming_service_client, streaming_service_runtime) = setup_data_streaming_service(
         node_config.state_sync.data_streaming_service,
         aptos_data_client.clone(),
+        active_data_streams,
     );
 
     // Create the chunk executor
@@ -314,12 +333,17 @@ fn create_state_sync_runtimes<M: MempoolNotificationSender + 'static>(
 fn setup_data_streaming_service(
     config: DataStreamingServiceConfig,
     aptos_data_client: AptosNetDataClient,
+    active_data_streams: ActiveDataStreams,
 ) -> (StreamingServiceClient, Runtime) {
     // Create the data streaming service
     let (streaming_service_client, streaming_service_listener) =
         new_streaming_service_client_listener_pair();
-    let data_streaming_service =
-        DataStreamingService::new(config, aptos_data_client, streaming_service_listener);
+    let data_streaming_service = DataStreamingService::new(
+        config,
+        aptos_data_client,
+        streaming_service_listener,
+        active_data_streams,
+    );
 
     // Start the data streaming service
     let streaming_service_runtime = Builder::new_multi_thread()
@@ -366,24 +390,31 @@ fn setup_aptos_data_client(
 
 fn setup_state_sync_storage_service(
     config: StorageServiceConfig,
-    network_handles: Vec<StorageServiceNetworkEvents>,
+    network_handles: Vec<(NetworkId, StorageServiceNetworkEvents)>,
     db_rw: &DbReaderWriter,
 ) -> Runtime {
-    // Create a new state sync storage service runtime
+    // Create a new state sync storage service runtime. The servers schedule
+    // their requests onto as many blocking threads as they have tasks, so that
+    // requests only ever wait in the scheduler, where priority peers go first.
     let storage_service_runtime = Builder::new_multi_thread()
         .thread_name("storage-service-server")
+        .max_blocking_threads(config.max_concurrent_requests as usize)
         .enable_all()
         .build()
         .expect("Failed to start the AptosNet storage-service runtime.");
 
-    // Spawn all state sync storage service servers on the same runtime
+    // Spawn all state sync storage service servers on the same runtime, and
+    // have them share their tasks
     let storage_reader = StorageReader::new(config, Arc::clone(&db_rw.reader));
-    for events in network_handles {
+    let request_scheduler = RequestScheduler::new(&config);
+    for (network_id, events) in network_handles {
         let service = StorageServiceServer::new(
             config,
             storage_service_runtime.handle().clone(),
             storage_reader.clone(),
             TimeService::real(),
+            network_id,
+            request_scheduler.clone(),
             events,
         );
         storage_service_runtime.spawn(service.start());
@@ -475,7 +506,36 @@ async fn periodic_state_dump(node_config: NodeConfig, db: DbReaderWriter) {
 }
 
 pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>) -> AptosHandle {
-    let debug_if = setup_debug_interface(node_config, logger);
+    // Gather all network configs into a single vector.
+    let mut network_configs: Vec<&NetworkConfig> = node_config.full_node_networks.iter().collect();
+    if let Some(network_config) = node_config.validator_network.as_ref() {
+        network_configs.push(network_config);
+    }
+
+    // Collect the network ids up front: the debug interface serves the peers of every network.
+    let mut network_ids = HashSet::new();
+    network_configs.iter().for_each(|config| {
+        let network_id = config.network_id;
+        // Guarantee there is only one of this network
+        if network_ids.contains(&network_id) {
+            panic!(
+                "Duplicate NetworkId: '{}'.  Can't start node with duplicate networks",
+                network_id
+            );
+        }
+        network_ids.insert(network_id);
+    });
+    let network_ids: Vec<_> = network_ids.into_iter().collect();
+
+    let peer_metadata_storage = PeerMetadataStorage::new(&network_ids);
+    let active_data_streams = ActiveDataStreams::new();
+    // Start the debug interface before opening the DB, so it is up while storage loads
+    let debug_if = setup_debug_interface(
+        node_config,
+        logger,
+        peer_metadata_storage.clone(),
+        active_data_streams.clone(),
+    );
 
     let metrics_port = node_config.debug_interface.metrics_server_port;
     let metric_host = node_config.debug_interface.address.clone();
@@ -488,11 +548,12 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
 
     let mut instant = Instant::now();
     let (aptos_db, db_rw) = DbReaderWriter::wrap(
-        AptosDB::open(
+        AptosDB::open_with_cold_storage(
             &node_config.storage.dir(),
             false, /* readonly */
             node_config.storage.storage_pruner_config,
             node_config.storage.rocksdb_config,
+            node_config.storage.cold_storage(),
         )
         .expect("DB should open."),
     );
@@ -545,28 +606,7 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
         None
     };
 
-    // Gather all network configs into a single vector.
-    let mut network_configs: Vec<&NetworkConfig> = node_config.full_node_networks.iter().collect();
-    if let Some(network_config) = node_config.validator_network.as_ref() {
-        network_configs.push(network_config);
-    }
-
     // Instantiate every network and collect the requisite endpoints for state_sync, mempool, and consensus.
-    let mut network_ids = HashSet::new();
-    network_configs.iter().for_each(|config| {
-        let network_id = config.network_id;
-        // Guarantee there is only one of this network
-        if network_ids.contains(&network_id) {
-            panic!(
-                "Duplicate NetworkId: '{}'.  Can't start node with duplicate networks",
-                network_id
-            );
-        }
-        network_ids.insert(network_id);
-    });
-    let network_ids: Vec<_> = network_ids.into_iter().collect();
-
-    let peer_metadata_storage = PeerMetadataStorage::new(&network_ids);
     for network_config in network_configs.into_iter() {
         debug!("Creating runtime for {}", network_config.network_id);
         let runtime = Builder::new_multi_thread()
@@ -603,7 +643,7 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
             network_builder.add_service(&storage_service_server::network::network_endpoint_config(
                 node_config.state_sync.storage_service,
             ));
-        storage_service_server_network_handles.push(storage_service_events);
+        storage_service_server_network_handles.push((network_id, storage_service_events));
 
         // Register the storage-service clients with Network
         let storage_service_sender =
@@ -660,6 +700,7 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
         genesis_waypoint,
         event_subscription_service,
         db_rw.clone(),
+        active_data_streams,
     );
 
     let (mp_client_sender, mp_client_events) = channel(AC_SMP_CHANNEL_BUFFER_SIZE);
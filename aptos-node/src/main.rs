@@ -47,6 +47,7 @@ struct Args {
     lazy: bool,
 }
 
+#[cfg(unix)]
 #[global_allocator]
 static ALLOC: jemallocator::Jemalloc = jemallocator::Jemalloc;
 
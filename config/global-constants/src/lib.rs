@@ -25,3 +25,8 @@ pub const WAYPOINT: &str = "waypoint";
 pub const GENESIS_WAYPOINT: &str = "genesis-waypoint";
 pub const MOVE_MODULES: &str = "move_modules";
 pub const MIN_PRICE_PER_GAS_UNIT: &str = "min_price_per_gas_unit";
+pub const INITIAL_ACCOUNTS: &str = "initial_accounts";
+pub const INITIAL_PACKAGES: &str = "initial_packages";
+pub const CONSENSUS_CONFIG: &str = "consensus_config";
+pub const VM_PUBLISHING_OPTION: &str = "vm_publishing_option";
+pub const EPOCH_DURATION_SECS: &str = "epoch_duration_secs";
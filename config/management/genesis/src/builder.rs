@@ -5,7 +5,8 @@ use crate::layout::Layout;
 use anyhow::Result;
 use aptos_crypto::ed25519::Ed25519PublicKey;
 use aptos_global_constants::{
-    APTOS_ROOT_KEY, MIN_PRICE_PER_GAS_UNIT, MOVE_MODULES, OPERATOR_KEY, OWNER_KEY,
+    APTOS_ROOT_KEY, CONSENSUS_CONFIG, EPOCH_DURATION_SECS, INITIAL_ACCOUNTS, INITIAL_PACKAGES,
+    MIN_PRICE_PER_GAS_UNIT, MOVE_MODULES, OPERATOR_KEY, OWNER_KEY, VM_PUBLISHING_OPTION,
 };
 use aptos_management::constants::{self, VALIDATOR_CONFIG, VALIDATOR_OPERATOR};
 use aptos_secure_storage::{KVStorage, Namespaced};
@@ -15,7 +16,7 @@ use aptos_types::{
         authenticator::AuthenticationKey, ScriptFunction, Transaction, TransactionPayload,
     },
 };
-use vm_genesis::Validator;
+use vm_genesis::{GenesisConfiguration, InitialState, Validator};
 
 pub struct GenesisBuilder<S> {
     storage: S,
@@ -203,16 +204,80 @@ impl<S: KVStorage> GenesisBuilder<S> {
             .map_err(Into::into)
     }
 
+    /// Sets the accounts created at genesis, with their initial balance
+    pub fn set_initial_accounts(&mut self, accounts: Vec<(Ed25519PublicKey, u64)>) -> Result<()> {
+        self.with_namespace_mut(constants::COMMON_NS)
+            .set(INITIAL_ACCOUNTS, accounts)
+            .map_err(Into::into)
+    }
+
+    pub fn initial_accounts(&self) -> Result<Vec<(Ed25519PublicKey, u64)>> {
+        self.with_namespace(constants::COMMON_NS)
+            .get(INITIAL_ACCOUNTS)
+            .map(|r| r.value)
+            .map_err(Into::into)
+    }
+
+    /// Sets the packages published at genesis, as the module blobs of each package
+    pub fn set_initial_packages(&mut self, packages: Vec<Vec<Vec<u8>>>) -> Result<()> {
+        self.with_namespace_mut(constants::COMMON_NS)
+            .set(INITIAL_PACKAGES, packages)
+            .map_err(Into::into)
+    }
+
+    pub fn initial_packages(&self) -> Result<Vec<Vec<Vec<u8>>>> {
+        self.with_namespace(constants::COMMON_NS)
+            .get(INITIAL_PACKAGES)
+            .map(|r| r.value)
+            .map_err(Into::into)
+    }
+
+    /// Sets the on-chain configuration the chain starts with
+    pub fn set_genesis_configuration(
+        &mut self,
+        configuration: &GenesisConfiguration,
+    ) -> Result<()> {
+        let mut storage = self.with_namespace_mut(constants::COMMON_NS);
+        storage.set(CONSENSUS_CONFIG, &configuration.consensus_config)?;
+        storage.set(VM_PUBLISHING_OPTION, &configuration.vm_publishing_option)?;
+        storage.set(EPOCH_DURATION_SECS, configuration.epoch_duration_secs)?;
+        Ok(())
+    }
+
+    /// The on-chain configuration the chain starts with, the default for what wasn't set
+    pub fn genesis_configuration(&self) -> GenesisConfiguration {
+        let storage = self.with_namespace(constants::COMMON_NS);
+        let default = GenesisConfiguration::default();
+        GenesisConfiguration {
+            consensus_config: storage
+                .get(CONSENSUS_CONFIG)
+                .map_or(default.consensus_config, |r| r.value),
+            vm_publishing_option: storage
+                .get(VM_PUBLISHING_OPTION)
+                .map_or(default.vm_publishing_option, |r| r.value),
+            epoch_duration_secs: storage
+                .get(EPOCH_DURATION_SECS)
+                .map_or(default.epoch_duration_secs, |r| r.value),
+        }
+    }
+
     pub fn build(&self, chain_id: ChainId) -> Result<Transaction> {
         let aptos_root_key = self.root_key()?;
         let validators = self.validators()?;
         let move_modules = self.move_modules()?;
         let min_price_per_gas_unit = self.min_price_per_gas_unit().unwrap_or(1);
+        let initial_state = InitialState {
+            accounts: self.initial_accounts().unwrap_or_default(),
+            packages: self.initial_packages().unwrap_or_default(),
+        };
+        let genesis_configuration = self.genesis_configuration();
 
         let genesis = vm_genesis::encode_genesis_transaction(
             aptos_root_key,
             &validators,
             &move_modules,
+            &initial_state,
+            &genesis_configuration,
             chain_id,
             min_price_per_gas_unit,
         );
@@ -393,6 +393,36 @@ pub mod tests {
         };
     }
 
+    #[test]
+    fn test_initialize_by_idx_derives_reproducible_keys() {
+        use aptos_secure_storage::CryptoStorage;
+
+        let helper = StorageHelper::new();
+        let other_helper = StorageHelper::new();
+        helper.initialize_by_idx("a".into(), 0);
+        helper.initialize_by_idx("b".into(), 1);
+        other_helper.initialize_by_idx("a".into(), 0);
+
+        let key = |helper: &StorageHelper, ns: &str, name| {
+            helper.storage(ns.into()).export_private_key(name).unwrap()
+        };
+        for name in [OWNER_KEY, OPERATOR_KEY] {
+            assert_eq!(key(&helper, "a", name), key(&other_helper, "a", name));
+            assert_ne!(key(&helper, "a", name), key(&helper, "b", name));
+        }
+        assert_ne!(
+            key(&helper, "a", OWNER_KEY),
+            key(&helper, "a", OPERATOR_KEY)
+        );
+        assert_eq!(
+            key(&helper, "a", OWNER_KEY),
+            helper
+                .storage("a".into())
+                .derive_ed25519_key("m/4'")
+                .unwrap()
+        );
+    }
+
     #[test]
     fn test_set_operator() {
         let storage_helper = StorageHelper::new();
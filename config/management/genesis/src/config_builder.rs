@@ -1,25 +1,20 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::validator_builder::ValidatorBuilder;
+use crate::validator_builder::{generate_test_network_genesis, test_root_keys};
 use aptos_config::config::NodeConfig;
 use aptos_crypto::ed25519::Ed25519PrivateKey;
 use aptos_secure_storage::{CryptoStorage, KVStorage, Storage};
 use aptos_temppath::TempPath;
-use rand::{rngs::StdRng, SeedableRng};
+use std::num::NonZeroUsize;
 
 pub fn test_config() -> (NodeConfig, Ed25519PrivateKey) {
     let path = TempPath::new();
     path.create_as_dir().unwrap();
-    let (root_keys, _genesis, _genesis_waypoint, validators) = ValidatorBuilder::new(
-        path.path(),
-        cached_framework_packages::module_blobs().to_vec(),
-    )
-    .template(NodeConfig::default_for_validator())
-    .build(StdRng::from_seed([0; 32]))
-    .unwrap();
-    let mut configs = validators.into_iter().map(|v| v.config).collect::<Vec<_>>();
-    let key = root_keys.root_key;
+    let seed = [0; 32];
+    let (_genesis, _genesis_waypoint, mut configs) =
+        generate_test_network_genesis(path.path(), NonZeroUsize::new(1).unwrap(), seed).unwrap();
+    let key = test_root_keys(seed).root_key;
 
     let mut config = configs.swap_remove(0);
     config.set_data_dir(path.path().to_path_buf());
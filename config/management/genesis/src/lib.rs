@@ -18,8 +18,8 @@ mod waypoint;
 
 #[cfg(any(test, feature = "testing"))]
 pub mod config_builder;
-#[cfg(test)]
-mod storage_helper;
+#[cfg(any(test, feature = "testing"))]
+pub mod storage_helper;
 
 #[cfg(any(test, feature = "testing"))]
 pub use crate::config_builder::test_config;
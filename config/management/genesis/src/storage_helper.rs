@@ -4,10 +4,7 @@
 // FIXME: (gnazario) storage helper doesn't belong in the genesis tool, but it's attached to it right now
 
 use crate::command::Command;
-use aptos_crypto::{
-    ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
-    Uniform,
-};
+use aptos_crypto::ed25519::Ed25519PublicKey;
 use aptos_global_constants::{
     APTOS_ROOT_KEY, CONSENSUS_KEY, EXECUTION_KEY, FULLNODE_NETWORK_KEY, OPERATOR_KEY, OWNER_KEY,
     SAFETY_DATA, VALIDATOR_NETWORK_KEY, WAYPOINT,
@@ -22,11 +19,23 @@ use consensus_types::safety_data::SafetyData;
 use std::{fs::File, path::Path};
 use structopt::StructOpt;
 
+/// The keys `initialize` derives, in the order of their index in their derivation path
+const KEYS: &[&str] = &[
+    APTOS_ROOT_KEY,
+    CONSENSUS_KEY,
+    EXECUTION_KEY,
+    FULLNODE_NETWORK_KEY,
+    OWNER_KEY,
+    OPERATOR_KEY,
+    VALIDATOR_NETWORK_KEY,
+];
+
 pub struct StorageHelper {
     temppath: aptos_temppath::TempPath,
 }
 
 impl StorageHelper {
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
         let temppath = aptos_temppath::TempPath::new();
         temppath.create_as_file().unwrap();
@@ -51,32 +60,19 @@ impl StorageHelper {
         self.initialize(namespace, seed);
     }
 
+    /// Initializes the keys of the namespace, each derived at `m/<index in KEYS>'` from `seed`,
+    /// which is kept in the namespace as its master seed
     pub fn initialize(&self, namespace: String, seed: [u8; 32]) {
-        let mut rng: rand::rngs::StdRng = rand::SeedableRng::from_seed(seed);
         let mut storage = self.storage(namespace);
 
         // Initialize all keys in storage
-        storage
-            .import_private_key(APTOS_ROOT_KEY, Ed25519PrivateKey::generate(&mut rng))
-            .unwrap();
-        storage
-            .import_private_key(CONSENSUS_KEY, Ed25519PrivateKey::generate(&mut rng))
-            .unwrap();
-        storage
-            .import_private_key(EXECUTION_KEY, Ed25519PrivateKey::generate(&mut rng))
-            .unwrap();
-        storage
-            .import_private_key(FULLNODE_NETWORK_KEY, Ed25519PrivateKey::generate(&mut rng))
-            .unwrap();
-        storage
-            .import_private_key(OWNER_KEY, Ed25519PrivateKey::generate(&mut rng))
-            .unwrap();
-        storage
-            .import_private_key(OPERATOR_KEY, Ed25519PrivateKey::generate(&mut rng))
-            .unwrap();
-        storage
-            .import_private_key(VALIDATOR_NETWORK_KEY, Ed25519PrivateKey::generate(&mut rng))
-            .unwrap();
+        storage.import_master_seed(seed).unwrap();
+        for (index, key) in KEYS.iter().enumerate() {
+            let private_key = storage
+                .derive_ed25519_key(&format!("m/{}'", index))
+                .unwrap();
+            storage.import_private_key(key, private_key).unwrap();
+        }
 
         // Initialize all other data in storage
         storage
@@ -222,7 +218,6 @@ impl StorageHelper {
         command.owner_key()
     }
 
-    #[cfg(test)]
     pub fn set_layout(&self, path: &str) -> Result<crate::layout::Layout, Error> {
         let args = format!(
             "
@@ -241,7 +236,6 @@ impl StorageHelper {
         command.set_layout()
     }
 
-    #[cfg(test)]
     pub fn set_move_modules(&self, dir: &str) -> Result<Vec<Vec<u8>>, Error> {
         println!("setting move modules with dir {}", dir);
         let args = format!(
@@ -319,7 +313,6 @@ impl StorageHelper {
         command.validator_config()
     }
 
-    #[cfg(test)]
     pub fn verify(&self, namespace: &str) -> Result<String, Error> {
         let args = format!(
             "
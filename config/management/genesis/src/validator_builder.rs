@@ -24,7 +24,9 @@ use aptos_global_constants::{
 use aptos_management::{
     storage::StorageWrapper, validator_config::build_validator_config_transaction,
 };
-use aptos_secure_storage::{CryptoStorage, KVStorage, OnDiskStorage, Storage};
+use aptos_secure_storage::{
+    derive_ed25519_key_from_seed, CryptoStorage, KVStorage, OnDiskStorage, Storage,
+};
 use aptos_types::{
     chain_id::ChainId,
     transaction::{authenticator::AuthenticationKey, Transaction},
@@ -38,10 +40,24 @@ use std::{
     num::NonZeroUsize,
     path::{Path, PathBuf},
 };
+pub use vm_genesis::{GenesisConfiguration, InitialState};
 
 const APTOS_ROOT_NS: &str = "aptos_root";
 const OPERATOR_NS: &str = "_operator";
 const OWNER_NS: &str = "_owner";
+/// Where the root key is derived from the master seed of the genesis storage: the last hardened
+/// index, which is never the index of a validator
+const ROOT_KEY_PATH: &str = "m/2147483647'";
+/// The keys of a validator, each derived at `m/<validator index>'/<index in this list>'` from the
+/// master seed of the genesis storage
+const VALIDATOR_KEYS: &[&str] = &[
+    OWNER_KEY,
+    OPERATOR_KEY,
+    CONSENSUS_KEY,
+    EXECUTION_KEY,
+    FULLNODE_NETWORK_KEY,
+    VALIDATOR_NETWORK_KEY,
+];
 
 pub struct ValidatorConfig {
     pub name: String,
@@ -137,6 +153,13 @@ impl RootKeys {
 
         Self { root_key }
     }
+
+    /// The root keys of the validators built from `master_seed`, derived at `ROOT_KEY_PATH`
+    pub fn derive(master_seed: [u8; 32]) -> Result<Self> {
+        Ok(Self {
+            root_key: derive_ed25519_key_from_seed(&master_seed, ROOT_KEY_PATH)?,
+        })
+    }
 }
 
 #[derive(Clone)]
@@ -148,6 +171,9 @@ pub struct ValidatorBuilder {
     randomize_first_validator_ports: bool,
     template: NodeConfig,
     min_price_per_gas_unit: u64,
+    initial_state: InitialState,
+    genesis_configuration: GenesisConfiguration,
+    num_ipv6_validators: usize,
 }
 
 impl ValidatorBuilder {
@@ -159,6 +185,9 @@ impl ValidatorBuilder {
             randomize_first_validator_ports: true,
             template: NodeConfig::default_for_validator(),
             min_price_per_gas_unit: 1,
+            initial_state: InitialState::default(),
+            genesis_configuration: GenesisConfiguration::default(),
+            num_ipv6_validators: 0,
         }
     }
 
@@ -182,33 +211,71 @@ impl ValidatorBuilder {
         self
     }
 
+    /// Accounts and packages to create at genesis, on top of the Move modules
+    pub fn initial_state(mut self, initial_state: InitialState) -> Self {
+        self.initial_state = initial_state;
+        self
+    }
+
+    /// On-chain configuration the chain starts with
+    pub fn genesis_configuration(mut self, genesis_configuration: GenesisConfiguration) -> Self {
+        self.genesis_configuration = genesis_configuration;
+        self
+    }
+
+    /// Number of validators, counting from the last one, whose validator network listens on the
+    /// IPv6 loopback instead of IPv4
+    pub fn ipv6_validators(mut self, num_ipv6_validators: usize) -> Self {
+        self.num_ipv6_validators = num_ipv6_validators;
+        self
+    }
+
+    /// Builds the validators and the genesis from a master seed drawn from `rng`, see
+    /// `build_from_seed`
     pub fn build<R>(
-        mut self,
+        self,
         mut rng: R,
     ) -> Result<(RootKeys, Transaction, Waypoint, Vec<ValidatorConfig>)>
     where
         R: ::rand::RngCore + ::rand::CryptoRng,
     {
+        let mut master_seed = [0u8; 32];
+        rng.fill_bytes(&mut master_seed);
+        self.build_from_seed(master_seed)
+    }
+
+    /// Builds the validators and the genesis. Every key is derived from `master_seed` at a path
+    /// of its own, kept in the genesis storage: the root key at `ROOT_KEY_PATH` and the keys of a
+    /// validator under its index, see `VALIDATOR_KEYS`. So each key only depends on the seed and on
+    /// its role, not on the order the keys are generated in or on the number of validators.
+    pub fn build_from_seed(
+        mut self,
+        master_seed: [u8; 32],
+    ) -> Result<(RootKeys, Transaction, Waypoint, Vec<ValidatorConfig>)> {
         // Canonicalize the config directory path
         self.config_directory = self.config_directory.canonicalize()?;
 
-        // Generate chain root keys
-        let root_keys = RootKeys::generate(&mut rng);
+        let mut genesis_storage =
+            OnDiskStorage::new(self.config_directory.join("genesis-storage.json"));
+        genesis_storage.import_master_seed(master_seed)?;
+
+        // Derive chain root keys
+        let root_keys = RootKeys::derive(master_seed)?;
 
         // Generate and initialize Validator configs
         let mut validators = (0..self.num_validators.get())
-            .map(|i| self.initialize_validator_config(i, &mut rng))
+            .map(|i| self.initialize_validator_config(i, &genesis_storage))
             .collect::<Result<Vec<_>>>()?;
 
         // Build genesis
-        let mut genesis_storage =
-            OnDiskStorage::new(self.config_directory.join("genesis-storage.json"));
         let (genesis, waypoint) = Self::genesis_ceremony(
             &mut genesis_storage,
             &root_keys,
             &validators,
             self.move_modules,
             self.min_price_per_gas_unit,
+            self.initial_state,
+            &self.genesis_configuration,
         )?;
 
         // Insert Genesis and Waypoint into each validator
@@ -237,14 +304,45 @@ impl ValidatorBuilder {
         Ok((root_keys, genesis, waypoint, validators))
     }
 
+    /// Builds the config of a validator outside of the genesis which `build` generated in the same
+    /// directory, to join the validator set on chain later on. Its keys are derived at `index`
+    /// from the master seed of the genesis storage, so `index` has to be past the validators of
+    /// the genesis. Unlike theirs, its owner account isn't created at genesis: it's the account of
+    /// its owner key, which has to be created before the validator registers.
+    pub fn build_standby_validator(
+        mut self,
+        index: usize,
+        genesis: &Transaction,
+        waypoint: &Waypoint,
+    ) -> Result<ValidatorConfig> {
+        self.config_directory = self.config_directory.canonicalize()?;
+        let genesis_storage_path = self.config_directory.join("genesis-storage.json");
+        anyhow::ensure!(
+            genesis_storage_path.exists(),
+            "No genesis storage in {:?} to derive the keys of a validator from",
+            self.config_directory
+        );
+        let genesis_storage = OnDiskStorage::new(genesis_storage_path);
+
+        let mut validator = self.initialize_validator_config(index, &genesis_storage)?;
+        let owner_address = AuthenticationKey::ed25519(&validator.owner_key()?).derived_address();
+        validator.storage().set(OWNER_ACCOUNT, owner_address)?;
+
+        validator.insert_genesis(genesis)?;
+        validator.insert_waypoint(waypoint)?;
+        validator.save_config()?;
+        Ok(validator)
+    }
+
     //
     // Build helpers
     //
 
-    fn initialize_validator_config<R>(&self, index: usize, rng: R) -> Result<ValidatorConfig>
-    where
-        R: ::rand::RngCore + ::rand::CryptoRng,
-    {
+    fn initialize_validator_config(
+        &self,
+        index: usize,
+        key_storage: &OnDiskStorage,
+    ) -> Result<ValidatorConfig> {
         let name = index.to_string();
         let directory = self.config_directory.join(&name);
         std::fs::create_dir_all(&directory)?;
@@ -253,7 +351,7 @@ impl ValidatorBuilder {
 
         let mut validator =
             ValidatorConfig::new(name, storage_config, directory, self.template.clone());
-        Self::initialize_validator_storage(&validator, rng)?;
+        Self::initialize_validator_storage(&validator, index, key_storage)?;
 
         validator.config.set_data_dir(validator.directory.clone());
         let mut config = &mut validator.config;
@@ -313,6 +411,13 @@ impl ValidatorBuilder {
         if index > 0 || self.randomize_first_validator_ports {
             config.randomize_ports();
         }
+        let num_validators = self.num_validators.get();
+        if (num_validators.saturating_sub(self.num_ipv6_validators)..num_validators)
+            .contains(&index)
+        {
+            config.validator_network.as_mut().unwrap().listen_address =
+                aptos_config::utils::get_available_port_in_multiaddr(false);
+        }
 
         Ok(validator)
     }
@@ -324,29 +429,26 @@ impl ValidatorBuilder {
         storage_config
     }
 
-    fn initialize_validator_storage<R>(validator: &ValidatorConfig, mut rng: R) -> Result<()>
-    where
-        R: ::rand::RngCore + ::rand::CryptoRng,
-    {
+    /// Imports the keys of the validator, derived at `m/<index>'/<key index>'` from the master
+    /// seed of `key_storage`, see `VALIDATOR_KEYS`
+    fn initialize_validator_storage(
+        validator: &ValidatorConfig,
+        index: usize,
+        key_storage: &OnDiskStorage,
+    ) -> Result<()> {
         let mut storage = validator.storage();
+        for (key_index, key) in VALIDATOR_KEYS.iter().enumerate() {
+            let path = format!("m/{}'/{}'", index, key_index);
+            storage.import_private_key(key, key_storage.derive_ed25519_key(&path)?)?;
+        }
 
-        // Set owner key and account address
-        storage.import_private_key(OWNER_KEY, Ed25519PrivateKey::generate(&mut rng))?;
+        // Set owner and operator account addresses
         let owner_address =
             aptos_config::utils::validator_owner_account_from_name(validator.owner().as_bytes());
         storage.set(OWNER_ACCOUNT, owner_address)?;
-
-        // Set operator key and account address
-        let operator_key = Ed25519PrivateKey::generate(&mut rng);
-        let operator_address =
-            AuthenticationKey::ed25519(&Ed25519PublicKey::from(&operator_key)).derived_address();
+        let operator_key = storage.get_public_key(OPERATOR_KEY)?.public_key;
+        let operator_address = AuthenticationKey::ed25519(&operator_key).derived_address();
         storage.set(OPERATOR_ACCOUNT, operator_address)?;
-        storage.import_private_key(OPERATOR_KEY, operator_key)?;
-
-        storage.import_private_key(CONSENSUS_KEY, Ed25519PrivateKey::generate(&mut rng))?;
-        storage.import_private_key(EXECUTION_KEY, Ed25519PrivateKey::generate(&mut rng))?;
-        storage.import_private_key(FULLNODE_NETWORK_KEY, Ed25519PrivateKey::generate(&mut rng))?;
-        storage.import_private_key(VALIDATOR_NETWORK_KEY, Ed25519PrivateKey::generate(&mut rng))?;
 
         // Initialize all other data in storage
         storage.set(SAFETY_DATA, SafetyData::new(0, 0, 0, 0, None))?;
@@ -361,6 +463,8 @@ impl ValidatorBuilder {
         validators: &[ValidatorConfig],
         move_modules: Vec<Vec<u8>>,
         min_price_per_gas_unit: u64,
+        initial_state: InitialState,
+        genesis_configuration: &GenesisConfiguration,
     ) -> Result<(Transaction, Waypoint)> {
         let mut genesis_builder = GenesisBuilder::new(genesis_storage);
 
@@ -405,6 +509,9 @@ impl ValidatorBuilder {
             genesis_builder.set_validator_config(&validator.operator(), &validator_config)?;
         }
         genesis_builder.set_min_price_per_gas_unit(min_price_per_gas_unit)?;
+        genesis_builder.set_initial_accounts(initial_state.accounts)?;
+        genesis_builder.set_initial_packages(initial_state.packages)?;
+        genesis_builder.set_genesis_configuration(genesis_configuration)?;
 
         // Create Genesis and Genesis Waypoint
         let genesis = genesis_builder.build(ChainId::test())?;
@@ -413,3 +520,102 @@ impl ValidatorBuilder {
         Ok((genesis, waypoint))
     }
 }
+
+/// Generates the genesis of a test network of `num_validators` validators, writing their configs
+/// and secure storage under `config_directory`.
+///
+/// All keys are derived from `seed`, used as the master seed of the genesis storage, so the same
+/// seed always yields the same validators and accounts, see `build_test_network_genesis`.
+/// Returns the BCS serialized genesis transaction, as written to each node's genesis file, the
+/// genesis waypoint and the config of each validator.
+pub fn generate_test_network_genesis<T: AsRef<Path>>(
+    config_directory: T,
+    num_validators: NonZeroUsize,
+    seed: [u8; 32],
+) -> Result<(Vec<u8>, Waypoint, Vec<NodeConfig>)> {
+    let builder = ValidatorBuilder::new(
+        config_directory,
+        cached_framework_packages::module_blobs().to_vec(),
+    )
+    .num_validators(num_validators);
+    let (_root_keys, genesis, waypoint, validators) = build_test_network_genesis(builder, seed)?;
+
+    Ok((
+        bcs::to_bytes(&genesis)?,
+        waypoint,
+        validators.into_iter().map(|v| v.config).collect(),
+    ))
+}
+
+/// Generates the genesis of a test network of the validators `builder` sets up, from `seed`, for
+/// the callers which run the network themselves and so need the root keys and the validators
+/// with their storage, like forge or the test mode of the node. The root keys are those of
+/// `test_root_keys` for the seed.
+pub fn build_test_network_genesis(
+    builder: ValidatorBuilder,
+    seed: [u8; 32],
+) -> Result<(RootKeys, Transaction, Waypoint, Vec<ValidatorConfig>)> {
+    builder.build_from_seed(seed)
+}
+
+/// The root keys of the test network generated by `generate_test_network_genesis` with this seed
+pub fn test_root_keys(seed: [u8; 32]) -> RootKeys {
+    RootKeys::derive(seed).expect("The root key path is valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_temppath::TempPath;
+
+    fn build(seed: [u8; 32], num_validators: usize) -> (RootKeys, Vec<ValidatorConfig>) {
+        let path = TempPath::new();
+        path.create_as_dir().unwrap();
+        let (root_keys, _genesis, _waypoint, validators) = ValidatorBuilder::new(
+            path.path(),
+            cached_framework_packages::module_blobs().to_vec(),
+        )
+        .num_validators(NonZeroUsize::new(num_validators).unwrap())
+        .build_from_seed(seed)
+        .unwrap();
+        (root_keys, validators)
+    }
+
+    fn validator_keys(validator: &ValidatorConfig) -> Vec<Ed25519PublicKey> {
+        let storage = validator.storage();
+        VALIDATOR_KEYS
+            .iter()
+            .map(|key| storage.get_public_key(key).unwrap().public_key)
+            .collect()
+    }
+
+    #[test]
+    fn test_keys_derived_by_role() {
+        let seed = [3u8; 32];
+        let (root_keys, validators) = build(seed, 2);
+        let (other_root_keys, other_validators) = build(seed, 1);
+
+        // the keys don't depend on the number of validators
+        assert_eq!(root_keys.root_key, other_root_keys.root_key);
+        assert_eq!(root_keys.root_key, test_root_keys(seed).root_key);
+        assert_eq!(
+            validator_keys(&validators[0]),
+            validator_keys(&other_validators[0])
+        );
+
+        // every role has a key of its own
+        let root_key = Ed25519PublicKey::from(&root_keys.root_key);
+        let mut keys = validator_keys(&validators[0]);
+        keys.extend(validator_keys(&validators[1]));
+        keys.push(root_key.clone());
+        let num_keys = keys.len();
+        keys.sort_by_key(|key| key.to_bytes());
+        keys.dedup();
+        assert_eq!(keys.len(), num_keys);
+
+        assert_ne!(
+            root_key,
+            Ed25519PublicKey::from(&test_root_keys([4u8; 32]).root_key)
+        );
+    }
+}
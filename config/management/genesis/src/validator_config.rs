@@ -1,9 +1,14 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, ValidCryptoMaterialStringExt};
 use aptos_global_constants::OWNER_ACCOUNT;
 use aptos_management::{constants, error::Error, secure_backend::SharedBackend};
-use aptos_types::{network_address::NetworkAddress, transaction::Transaction};
+use aptos_types::{
+    network_address::NetworkAddress,
+    transaction::{authenticator::AuthenticationKey, Transaction},
+};
+use std::{fs, path::PathBuf};
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
@@ -20,6 +25,11 @@ pub struct ValidatorConfig {
     shared_backend: SharedBackend,
     #[structopt(long, help = "Disables network address validation")]
     disable_address_validation: bool,
+    /// Hex encoded owner key, e.g. from `aptos-operational-tool generate-key`, whose account owns
+    /// the validator instead of the one named after the owner. For a validator joining after the
+    /// genesis, whose owner account the genesis doesn't create.
+    #[structopt(long)]
+    owner_key_file: Option<PathBuf>,
 }
 
 impl ValidatorConfig {
@@ -30,8 +40,19 @@ impl ValidatorConfig {
             .override_shared_backend(&self.shared_backend.shared_backend)?;
 
         // Retrieve and set owner account
-        let owner_account =
-            aptos_config::utils::validator_owner_account_from_name(self.owner_name.as_bytes());
+        let owner_account = match &self.owner_key_file {
+            Some(owner_key_file) => {
+                let owner_key = fs::read_to_string(owner_key_file).map_err(|e| {
+                    Error::UnableToReadFile(owner_key_file.display().to_string(), e.to_string())
+                })?;
+                let owner_key = Ed25519PrivateKey::from_encoded_string(owner_key.trim())
+                    .map_err(|e| Error::UnableToParse("owner key", e.to_string()))?;
+                AuthenticationKey::ed25519(&owner_key.public_key()).derived_address()
+            }
+            None => {
+                aptos_config::utils::validator_owner_account_from_name(self.owner_name.as_bytes())
+            }
+        };
         let mut validator_storage = config.validator_backend();
         validator_storage.set(OWNER_ACCOUNT, owner_account)?;
 
@@ -2,9 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    account_resource::SimplifiedAccountResource, validator_config::DecodedValidatorConfig,
-    validator_set::DecryptedValidatorInfo, validator_state::VerifyValidatorStateResult,
-    TransactionContext,
+    account_resource::SimplifiedAccountResource, diagnostics::DiagnosticsBundle,
+    validator_config::DecodedValidatorConfig, validator_set::DecryptedValidatorInfo,
+    validator_state::VerifyValidatorStateResult, TransactionContext,
 };
 use aptos_config::config::Peer;
 use aptos_crypto::{ed25519::Ed25519PublicKey, x25519};
@@ -25,6 +25,16 @@ pub enum Command {
     CheckEndpoint(crate::network_checker::CheckEndpoint),
     #[structopt(about = "Check all on-chain endpoints for a listening socket")]
     CheckValidatorSetEndpoints(crate::network_checker::CheckValidatorSetEndpoints),
+    #[structopt(about = "Report which validators each validator is connected to")]
+    CheckConnectivityMatrix(crate::network_checker::CheckConnectivityMatrix),
+    #[structopt(
+        about = "Check that a full node's public listener matches its advertised identity and auth"
+    )]
+    CheckFullNodeIdentity(crate::network_checker::CheckFullNodeIdentity),
+    #[structopt(
+        about = "Collect the config, logs, metrics, storage size and sync status of a node into an archive"
+    )]
+    CollectDiagnostics(crate::diagnostics::CollectDiagnostics),
     #[structopt(about = "Create a new validator account")]
     CreateValidator(crate::governance::CreateValidator),
     #[structopt(about = "Create a new validator operator account")]
@@ -83,6 +93,9 @@ pub enum CommandName {
     AddValidator,
     CheckEndpoint,
     CheckValidatorSetEndpoints,
+    CheckConnectivityMatrix,
+    CheckFullNodeIdentity,
+    CollectDiagnostics,
     CreateValidator,
     CreateValidatorOperator,
     ExtractPeerFromFile,
@@ -116,6 +129,9 @@ impl From<&Command> for CommandName {
             Command::AddValidator(_) => CommandName::AddValidator,
             Command::CheckEndpoint(_) => CommandName::CheckEndpoint,
             Command::CheckValidatorSetEndpoints(_) => CommandName::CheckValidatorSetEndpoints,
+            Command::CheckConnectivityMatrix(_) => CommandName::CheckConnectivityMatrix,
+            Command::CheckFullNodeIdentity(_) => CommandName::CheckFullNodeIdentity,
+            Command::CollectDiagnostics(_) => CommandName::CollectDiagnostics,
             Command::CreateValidator(_) => CommandName::CreateValidator,
             Command::CreateValidatorOperator(_) => CommandName::CreateValidatorOperator,
             Command::ExtractPrivateKey(_) => CommandName::ExtractPrivateKey,
@@ -151,6 +167,9 @@ impl std::fmt::Display for CommandName {
             CommandName::AddValidator => "add-validator",
             CommandName::CheckEndpoint => "check-endpoint",
             CommandName::CheckValidatorSetEndpoints => "check-validator-set-endpoints",
+            CommandName::CheckConnectivityMatrix => "check-connectivity-matrix",
+            CommandName::CheckFullNodeIdentity => "check-full-node-identity",
+            CommandName::CollectDiagnostics => "collect-diagnostics",
             CommandName::CreateValidator => "create-validator",
             CommandName::CreateValidatorOperator => "create-validator-operator",
             CommandName::ExtractPrivateKey => "extract-private-key",
@@ -187,6 +206,9 @@ impl Command {
             Command::AddValidator(cmd) => Self::print_transaction_context(cmd.execute().await),
             Command::CheckEndpoint(cmd) => Self::pretty_print(cmd.execute().await),
             Command::CheckValidatorSetEndpoints(cmd) => Self::pretty_print(cmd.execute().await),
+            Command::CheckConnectivityMatrix(cmd) => Self::pretty_print(cmd.execute().await),
+            Command::CheckFullNodeIdentity(cmd) => Self::pretty_print(cmd.execute().await),
+            Command::CollectDiagnostics(cmd) => Self::pretty_print(cmd.execute().await),
             Command::CreateValidator(cmd) => {
                 Self::print_transaction_context(cmd.execute().await.map(|(txn_ctx, _)| txn_ctx))
             }
@@ -298,6 +320,30 @@ impl Command {
         )
     }
 
+    pub async fn check_connectivity_matrix(self) -> Result<String, Error> {
+        execute_command_await!(
+            self,
+            Command::CheckConnectivityMatrix,
+            CommandName::CheckConnectivityMatrix
+        )
+    }
+
+    pub async fn check_full_node_identity(self) -> Result<String, Error> {
+        execute_command_await!(
+            self,
+            Command::CheckFullNodeIdentity,
+            CommandName::CheckFullNodeIdentity
+        )
+    }
+
+    pub async fn collect_diagnostics(self) -> Result<DiagnosticsBundle, Error> {
+        execute_command_await!(
+            self,
+            Command::CollectDiagnostics,
+            CommandName::CollectDiagnostics
+        )
+    }
+
     pub async fn create_validator(self) -> Result<(TransactionContext, AccountAddress), Error> {
         execute_command_await!(self, Command::CreateValidator, CommandName::CreateValidator)
     }
@@ -0,0 +1,387 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_config::config::NodeConfig;
+use aptos_management::error::Error;
+use debug_interface::{AsyncNodeDebugClient, StorageUsage};
+use serde::Serialize;
+use serde_yaml::Value;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fs::File,
+    future::Future,
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use structopt::StructOpt;
+use url::Url;
+
+/// How long the node gets to answer each query of its debug interface
+const DEBUG_INTERFACE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Fields of the node config which hold secrets: private keys, and the tokens of the secure
+/// backends and of the API
+const SECRET_FIELDS: &[&str] = &[
+    "account_key",
+    "consensus_key",
+    "execution_key",
+    "failpoints_admin_token",
+    "key",
+    "network_key",
+    "operator_key",
+    "owner_key",
+    "token",
+];
+const REDACTED: &str = "<redacted>";
+
+/// The metrics of the versions state sync has reached
+const SYNCED_VERSION_METRIC: &str = "aptos_state_sync_version";
+
+#[derive(Debug, StructOpt)]
+pub struct CollectDiagnostics {
+    /// Config of a node running on this machine. Its storage is measured on disk and the rest is
+    /// queried from its debug interface
+    #[structopt(long, required_unless = "debug-url")]
+    config: Option<PathBuf>,
+    /// Debug interface of the node (e.g. http://localhost:6191), to collect the diagnostics of a
+    /// remote node, or of a local node whose debug interface isn't at the address in its config
+    #[structopt(long)]
+    debug_url: Option<Url>,
+    /// Log file of the node, whose last lines are added to the bundle
+    #[structopt(long)]
+    log_file: Option<PathBuf>,
+    /// Number of lines at the end of the log file which are added to the bundle
+    #[structopt(long, default_value = "10000")]
+    log_lines: usize,
+    /// Path of the bundle, a tar archive
+    #[structopt(long)]
+    output: PathBuf,
+}
+
+/// What went into the diagnostics bundle
+#[derive(Debug, Serialize)]
+pub struct DiagnosticsBundle {
+    pub output: PathBuf,
+    pub files: Vec<String>,
+    /// The diagnostics which couldn't be collected, and why
+    pub errors: Vec<String>,
+}
+
+/// The state of state sync on the node
+#[derive(Debug, Serialize)]
+struct SyncStatus {
+    synced_versions: BTreeMap<String, String>,
+    data_streams: serde_json::Value,
+    connected_peers: serde_json::Value,
+}
+
+impl CollectDiagnostics {
+    /// Collects the config of the node with its secrets redacted, its metrics, the disk usage of
+    /// its storage, the status of state sync and the end of its logs into a tar archive.
+    ///
+    /// The bundle is collected on a best effort basis, as it's needed the most when the node is
+    /// unhealthy: the diagnostics which can't be collected are listed in `errors.txt` instead.
+    pub async fn execute(self) -> Result<DiagnosticsBundle, Error> {
+        let local_config = self
+            .config
+            .as_ref()
+            .map(|path| {
+                NodeConfig::load(path)
+                    .map_err(|e| Error::ConfigError(format!("{}: {}", path.display(), e)))
+            })
+            .transpose()?;
+        let debug_url = match (&self.debug_url, &local_config) {
+            (Some(url), _) => url.clone(),
+            (None, Some(config)) => local_debug_url(config)?,
+            (None, None) => {
+                return Err(Error::CommandArgumentError(
+                    "Either --config or --debug-url is required".to_string(),
+                ))
+            }
+        };
+        let client = AsyncNodeDebugClient::from_url(debug_url);
+
+        let mut files = Vec::new();
+        let mut errors = Vec::new();
+
+        let node_info = query("node info", client.get_node_info()).await;
+        let node_config = match (local_config.as_ref(), &node_info) {
+            (Some(config), _) => Ok(config.clone()),
+            (None, Ok(info)) => Ok(info.node_config.clone()),
+            (None, Err(e)) => Err(e.clone()),
+        };
+        match node_config.and_then(|config| redacted_config(&config)) {
+            Ok(config) => files.push(("node.yaml".to_string(), config.into_bytes())),
+            Err(e) => errors.push(format!("config: {}", e)),
+        }
+        match node_info {
+            Ok(info) => files.push((
+                "git_revision.txt".to_string(),
+                format!("{}\n", info.git_revision).into_bytes(),
+            )),
+            Err(e) => errors.push(format!("git revision: {}", e)),
+        }
+
+        let metrics = query("metrics", client.get_raw_node_metrics())
+            .await
+            .map(|metrics| metrics.into_iter().collect::<BTreeMap<_, _>>());
+        match &metrics {
+            Ok(metrics) => files.push(("metrics.json".to_string(), to_json(metrics))),
+            Err(e) => errors.push(format!("metrics: {}", e)),
+        }
+
+        let storage = match &local_config {
+            Some(config) => StorageUsage::of(config.data_dir())
+                .map_err(|e| format!("{}: {}", config.data_dir().display(), e)),
+            None => query("storage usage", client.get_storage_usage()).await,
+        };
+        match storage {
+            Ok(storage) => files.push(("storage.json".to_string(), to_json(&storage))),
+            Err(e) => errors.push(format!("storage: {}", e)),
+        }
+
+        let data_streams = query("data streams", client.get_data_streams()).await;
+        let connected_peers = query("peers", client.get_connected_peers()).await;
+        match (metrics, data_streams, connected_peers) {
+            (Ok(metrics), Ok(data_streams), Ok(connected_peers)) => {
+                let status = SyncStatus {
+                    synced_versions: metrics
+                        .into_iter()
+                        .filter(|(name, _)| name.starts_with(SYNCED_VERSION_METRIC))
+                        .collect(),
+                    data_streams: serde_json::to_value(data_streams).unwrap(),
+                    connected_peers: serde_json::to_value(connected_peers).unwrap(),
+                };
+                files.push(("sync_status.json".to_string(), to_json(&status)));
+            }
+            (metrics, data_streams, connected_peers) => {
+                let e = [metrics.err(), data_streams.err(), connected_peers.err()]
+                    .iter()
+                    .flatten()
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                errors.push(format!("sync status: {}", e));
+            }
+        }
+
+        if let Some(log_file) = &self.log_file {
+            match tail(log_file, self.log_lines) {
+                Ok(logs) => files.push(("logs.txt".to_string(), logs.into_bytes())),
+                Err(e) => errors.push(format!("logs: {}", e)),
+            }
+        }
+        if !errors.is_empty() {
+            let mut contents = errors.join("\n");
+            contents.push('\n');
+            files.push(("errors.txt".to_string(), contents.into_bytes()));
+        }
+
+        write_archive(&self.output, &files)?;
+        Ok(DiagnosticsBundle {
+            output: self.output,
+            files: files.into_iter().map(|(name, _)| name).collect(),
+            errors,
+        })
+    }
+}
+
+/// The debug interface of the node running on this machine with `config`
+fn local_debug_url(config: &NodeConfig) -> Result<Url, Error> {
+    let address = match config.debug_interface.address.as_str() {
+        "0.0.0.0" | "::" => "localhost",
+        address => address,
+    };
+    Url::parse(&format!(
+        "http://{}:{}",
+        address, config.debug_interface.admission_control_node_debug_port
+    ))
+    .map_err(|e| Error::UnableToParse("debug interface address", e.to_string()))
+}
+
+async fn query<T, F: Future<Output = anyhow::Result<T>>>(
+    name: &str,
+    future: F,
+) -> Result<T, String> {
+    match tokio::time::timeout(DEBUG_INTERFACE_TIMEOUT, future).await {
+        Ok(result) => result.map_err(|e| format!("failed to query {}: {}", name, e)),
+        Err(_) => Err(format!("timed out querying {}", name)),
+    }
+}
+
+/// Serializes the config as YAML, with the values of the secret fields replaced
+fn redacted_config(config: &NodeConfig) -> Result<String, String> {
+    let mut value = serde_yaml::to_value(config).map_err(|e| e.to_string())?;
+    redact(&mut value);
+    serde_yaml::to_string(&value).map_err(|e| e.to_string())
+}
+
+fn redact(value: &mut Value) {
+    match value {
+        Value::Mapping(mapping) => {
+            for (field, value) in mapping.iter_mut() {
+                let secret = field
+                    .as_str()
+                    .map_or(false, |field| SECRET_FIELDS.contains(&field));
+                if secret && !value.is_null() {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact(value);
+                }
+            }
+        }
+        Value::Sequence(values) => values.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> Vec<u8> {
+    let mut json = serde_json::to_vec_pretty(value).unwrap();
+    json.push(b'\n');
+    json
+}
+
+/// The last `num_lines` lines of the file, read without holding the whole file in memory as logs
+/// can be large
+fn tail(path: &Path, num_lines: usize) -> Result<String, String> {
+    let file = File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut lines = VecDeque::with_capacity(num_lines);
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| format!("{}: {}", path.display(), e))?;
+        if lines.len() == num_lines {
+            lines.pop_front();
+        }
+        if num_lines > 0 {
+            lines.push_back(line);
+        }
+    }
+    let mut logs = lines.into_iter().collect::<Vec<_>>().join("\n");
+    logs.push('\n');
+    Ok(logs)
+}
+
+fn write_archive(path: &Path, files: &[(String, Vec<u8>)]) -> Result<(), Error> {
+    let io_error = |e| Error::IO(path.display().to_string(), e);
+    let mtime = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let mut archive = tar::Builder::new(File::create(path).map_err(io_error)?);
+    for (name, contents) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(mtime);
+        archive
+            .append_data(&mut header, name, contents.as_slice())
+            .map_err(io_error)?;
+    }
+    archive.into_inner().map_err(io_error)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_config::config::{
+        Identity, SafetyRulesTestConfig, SecureBackend, Token, VaultConfig,
+    };
+    use aptos_crypto::{ed25519::Ed25519PrivateKey, x25519, Uniform};
+    use aptos_types::{account_address::from_identity_public_key, PeerId};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    const VAULT_TOKEN: &str = "vault-token-to-redact";
+    const ADMIN_TOKEN: &str = "admin-token-to-redact";
+
+    fn config_with_secrets() -> NodeConfig {
+        let mut rng = StdRng::from_seed([3u8; 32]);
+        let mut config = NodeConfig::default_for_validator();
+
+        let network_key = x25519::PrivateKey::generate(&mut rng);
+        let peer_id = from_identity_public_key(network_key.public_key());
+        config.validator_network.as_mut().unwrap().identity =
+            Identity::from_config(network_key, peer_id);
+
+        let mut test_config = SafetyRulesTestConfig::new(PeerId::random());
+        test_config.consensus_key(Ed25519PrivateKey::generate(&mut rng));
+        test_config.execution_key(Ed25519PrivateKey::generate(&mut rng));
+        config.consensus.safety_rules.test = Some(test_config);
+        config.consensus.safety_rules.backend = SecureBackend::Vault(VaultConfig {
+            ca_certificate: None,
+            namespace: None,
+            renew_ttl_secs: None,
+            server: "http://127.0.0.1:8200".to_string(),
+            token: Token::FromConfig(VAULT_TOKEN.to_string()),
+            disable_cas: None,
+            connection_timeout_ms: None,
+            response_timeout_ms: None,
+        });
+        config.api.failpoints_admin_token = Some(ADMIN_TOKEN.to_string());
+        config
+    }
+
+    #[test]
+    fn test_redacted_config() {
+        let config = config_with_secrets();
+        let unredacted = serde_yaml::to_value(&config).unwrap();
+        let redacted = redacted_config(&config).unwrap();
+
+        // The secrets are nowhere in the output
+        let network_key = unredacted["validator_network"]["identity"]["key"]
+            .as_str()
+            .unwrap();
+        let test_config = &unredacted["consensus"]["safety_rules"]["test"];
+        let consensus_key = test_config["consensus_key"]["key"].as_str().unwrap();
+        let execution_key = test_config["execution_key"]["key"].as_str().unwrap();
+        for secret in [
+            network_key,
+            consensus_key,
+            execution_key,
+            VAULT_TOKEN,
+            ADMIN_TOKEN,
+        ] {
+            assert!(!secret.is_empty());
+            assert!(
+                !redacted.contains(secret),
+                "{} is in the redacted config",
+                secret
+            );
+        }
+
+        // In their place is the redaction marker, and the rest of the config is kept
+        let redacted: Value = serde_yaml::from_str(&redacted).unwrap();
+        let identity = &redacted["validator_network"]["identity"];
+        assert_eq!(identity["key"].as_str(), Some(REDACTED));
+        assert_eq!(
+            identity["peer_id"],
+            unredacted["validator_network"]["identity"]["peer_id"]
+        );
+        let safety_rules = &redacted["consensus"]["safety_rules"];
+        assert_eq!(
+            safety_rules["test"]["consensus_key"].as_str(),
+            Some(REDACTED)
+        );
+        assert_eq!(
+            safety_rules["test"]["execution_key"].as_str(),
+            Some(REDACTED)
+        );
+        assert_eq!(safety_rules["backend"]["token"].as_str(), Some(REDACTED));
+        assert_eq!(
+            safety_rules["backend"]["server"].as_str(),
+            Some("http://127.0.0.1:8200")
+        );
+        assert_eq!(
+            redacted["api"]["failpoints_admin_token"].as_str(),
+            Some(REDACTED)
+        );
+    }
+
+    #[test]
+    fn test_redact_keeps_unset_secrets() {
+        let mut value: Value = serde_yaml::from_str("{ key: ~, nested: [{ token: ~ }] }").unwrap();
+        let expected = value.clone();
+        redact(&mut value);
+        assert_eq!(value, expected);
+    }
+}
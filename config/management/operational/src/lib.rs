@@ -6,6 +6,7 @@
 mod account_resource;
 mod auto_validate;
 pub mod command;
+pub mod diagnostics;
 mod governance;
 pub mod keys;
 mod owner;
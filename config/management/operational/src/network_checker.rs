@@ -3,25 +3,40 @@
 
 use crate::{
     rest_client::RestClient,
-    validator_set::{validator_set_full_node_addresses, validator_set_validator_addresses},
+    validator_set::{
+        decode_validator_set, validator_set_full_node_addresses, validator_set_validator_addresses,
+    },
 };
 use aptos_config::{
     config::{RoleType, HANDSHAKE_VERSION},
     network_id::{NetworkContext, NetworkId},
 };
-use aptos_crypto::{x25519, x25519::PRIVATE_KEY_SIZE, ValidCryptoMaterialStringExt};
+use aptos_crypto::{x25519, x25519::PRIVATE_KEY_SIZE, Uniform, ValidCryptoMaterialStringExt};
 use aptos_management::error::Error;
-use aptos_types::{account_address, chain_id::ChainId, network_address::NetworkAddress, PeerId};
-use futures::{AsyncReadExt, AsyncWriteExt};
+use aptos_types::{
+    account_address,
+    account_address::AccountAddress,
+    chain_id::ChainId,
+    network_address::{NetworkAddress, Protocol},
+    PeerId,
+};
+use debug_interface::AsyncNodeDebugClient;
+use futures::{future::join_all, AsyncReadExt, AsyncWriteExt};
 use netcore::transport::tcp::{resolve_and_connect, TcpSocket};
 use network::{
     noise::{HandshakeAuthMode, NoiseUpgrader},
     protocols::wire::handshake::v1::ProtocolIdSet,
     transport::{upgrade_outbound, UpgradeContext, SUPPORTED_MESSAGING_PROTOCOL},
 };
-use std::{collections::BTreeMap, sync::Arc};
+use rand::{rngs::StdRng, SeedableRng};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::Write,
+    sync::Arc,
+};
 use structopt::StructOpt;
 use tokio::time::Duration;
+use url::Url;
 
 const DEFAULT_TIMEOUT_SECONDS: u64 = 5;
 
@@ -151,6 +166,276 @@ impl CheckValidatorSetEndpoints {
     }
 }
 
+#[derive(Debug, StructOpt)]
+pub struct CheckConnectivityMatrix {
+    /// JSON-RPC Endpoint (e.g. http://localhost:8080)
+    #[structopt(long)]
+    json_server: String,
+    /// Port of the debug interface of the validators, reached at the host of their on-chain
+    /// validator network address
+    #[structopt(long, default_value = "6191")]
+    debug_port: u16,
+    /// Optional number of seconds to timeout querying each validator
+    #[structopt(long)]
+    timeout_seconds: Option<u64>,
+}
+
+impl CheckConnectivityMatrix {
+    /// Asks every validator in the validator set which validators it's connected to, and prints
+    /// the N×N matrix of the answers with the missing connections listed below it.
+    pub async fn execute(self) -> Result<String, Error> {
+        let client = RestClient::new(self.json_server);
+        let validators = decode_validator_set(client, None).await?;
+        let timeout = timeout_duration(self.timeout_seconds);
+
+        let reports = join_all(validators.iter().map(|validator| {
+            connected_validators(
+                &validator.validator_network_address,
+                self.debug_port,
+                timeout,
+            )
+        }))
+        .await;
+
+        let peer_ids: Vec<PeerId> = validators.iter().map(|v| v.account_address).collect();
+        let mut output = String::new();
+        for (index, peer_id) in peer_ids.iter().enumerate() {
+            writeln!(output, "{:>3}: {}", index, peer_id).unwrap();
+        }
+        write!(output, "\n    ").unwrap();
+        for index in 0..peer_ids.len() {
+            write!(output, "{:>4}", index).unwrap();
+        }
+        writeln!(output).unwrap();
+
+        let mut gaps = vec![];
+        let mut unreachable = vec![];
+        for (index, (peer_id, report)) in peer_ids.iter().zip(&reports).enumerate() {
+            write!(output, "{:>3} ", index).unwrap();
+            for other in &peer_ids {
+                let cell = match report {
+                    _ if other == peer_id => "-",
+                    Err(_) => "?",
+                    Ok(connected) if connected.contains(other) => "o",
+                    Ok(_) => {
+                        gaps.push((*peer_id, *other));
+                        "X"
+                    }
+                };
+                write!(output, "{:>4}", cell).unwrap();
+            }
+            writeln!(output).unwrap();
+            if let Err(err) = report {
+                unreachable.push((*peer_id, err));
+            }
+        }
+
+        writeln!(output, "\no: connected, X: missing, ?: no report").unwrap();
+        for (peer_id, other) in &gaps {
+            writeln!(output, "Missing: {} is not connected to {}", peer_id, other).unwrap();
+        }
+        for (peer_id, err) in &unreachable {
+            writeln!(output, "No report: {} -- {}", peer_id, err).unwrap();
+        }
+        println!("{}", output);
+
+        Ok(format!(
+            "{} missing connections, {} of {} validators didn't report",
+            gaps.len(),
+            unreachable.len(),
+            peer_ids.len()
+        ))
+    }
+}
+
+#[derive(Debug, StructOpt)]
+pub struct CheckFullNodeIdentity {
+    /// JSON-RPC Endpoint (e.g. http://localhost:8080)
+    #[structopt(long)]
+    json_server: String,
+    /// Account of the validator whose on-chain full node addresses to check
+    #[structopt(long)]
+    account_address: AccountAddress,
+    /// `ChainId` of remote server
+    #[structopt(long)]
+    chain_id: ChainId,
+    /// Optional address the full node is published at off-chain (e.g. in DNS), checked against
+    /// the on-chain addresses and the listener
+    #[structopt(long)]
+    address: Option<NetworkAddress>,
+    /// Private key of an identity known to the full node, e.g. one of its trusted peers
+    #[structopt(long, parse(try_from_str = parse_private_key_hex))]
+    private_key: Option<x25519::PrivateKey>,
+    /// Optional number of seconds to timeout attempting to connect to endpoint
+    #[structopt(long)]
+    timeout_seconds: Option<u64>,
+}
+
+impl CheckFullNodeIdentity {
+    /// Connects to every public address of the full node twice: once with a freshly generated
+    /// identity, which only a listener without mutual authentication accepts, and once with the
+    /// known identity. A listener accepting neither while still reachable doesn't own the
+    /// advertised noise key, typically after a key rotation reached only one of the node and the
+    /// chain (or DNS).
+    pub async fn execute(self) -> Result<String, Error> {
+        let client = RestClient::new(self.json_server);
+        let mut addresses: Vec<NetworkAddress> =
+            validator_set_full_node_addresses(client, Some(self.account_address))
+                .await?
+                .into_iter()
+                .flat_map(|(_, _, addrs)| addrs)
+                .collect();
+        let onchain_keys: HashSet<x25519::PublicKey> = addresses
+            .iter()
+            .filter_map(|address| address.find_noise_proto())
+            .collect();
+        let mut output = String::new();
+        if let Some(address) = self.address {
+            validate_address(&address)?;
+            let published_key = address.find_noise_proto().unwrap();
+            if !onchain_keys.contains(&published_key) {
+                writeln!(
+                    output,
+                    "Mismatch: {} advertises noise key {}, which isn't on-chain",
+                    address, published_key
+                )
+                .unwrap();
+            }
+            addresses.push(address);
+        }
+        if addresses.is_empty() {
+            return Err(Error::UnexpectedError(format!(
+                "No full node address to check for {}",
+                self.account_address
+            )));
+        }
+
+        let timeout = timeout_duration(self.timeout_seconds);
+        // A fresh identity is never a trusted peer, nor already connected to the node
+        let fresh_key = x25519::PrivateKey::generate(&mut StdRng::from_entropy());
+        let (fresh_peer_id, _) = private_key_to_public_info(&fresh_key);
+        let fresh_context =
+            build_upgrade_context(self.chain_id, NetworkId::Public, fresh_peer_id, fresh_key);
+        let known_context = self.private_key.map(|private_key| {
+            let (peer_id, _) = private_key_to_public_info(&private_key);
+            (
+                peer_id,
+                build_upgrade_context(self.chain_id, NetworkId::Public, peer_id, private_key),
+            )
+        });
+
+        let mut failures = 0;
+        for address in &addresses {
+            if validate_address(address).is_err() {
+                writeln!(output, "{}: skipped, not a full network address", address).unwrap();
+                continue;
+            }
+            writeln!(
+                output,
+                "{}: advertised noise key {}",
+                address,
+                address.find_noise_proto().unwrap()
+            )
+            .unwrap();
+
+            let fresh =
+                check_endpoint(fresh_context.clone(), address.clone(), timeout, false).await;
+            writeln!(
+                output,
+                "  fresh identity {}: {}",
+                fresh_peer_id,
+                outcome(&fresh)
+            )
+            .unwrap();
+            let known = match &known_context {
+                Some((peer_id, context)) => {
+                    let known =
+                        check_endpoint(context.clone(), address.clone(), timeout, false).await;
+                    writeln!(output, "  known identity {}: {}", peer_id, outcome(&known)).unwrap();
+                    Some(known)
+                }
+                None => None,
+            };
+
+            let verdict = match (&fresh, &known) {
+                (Ok(_), _) => "noise key matches the listener, mutual authentication disabled",
+                (Err(_), Some(Ok(_))) => {
+                    "noise key matches the listener, mutual authentication enabled"
+                }
+                (Err(_), _) => {
+                    failures += 1;
+                    if check_endpoint(fresh_context.clone(), address.clone(), timeout, true)
+                        .await
+                        .is_ok()
+                    {
+                        if known.is_some() {
+                            "listener is reachable but doesn't own the noise key"
+                        } else {
+                            "listener is reachable but rejects the handshake: it doesn't own the \
+                            noise key or requires mutual authentication, check with --private-key"
+                        }
+                    } else {
+                        "listener is unreachable"
+                    }
+                }
+            };
+            writeln!(output, "  {}", verdict).unwrap();
+        }
+        println!("{}", output);
+
+        Ok(format!(
+            "{} of {} addresses failed the identity check",
+            failures,
+            addresses.len()
+        ))
+    }
+}
+
+fn outcome(result: &Result<String, Error>) -> String {
+    match result {
+        Ok(_) => "connected".to_string(),
+        Err(err) => format!("failed -- {}", err),
+    }
+}
+
+/// Queries the debug interface of the validator for the validators it's connected to
+async fn connected_validators(
+    validator_network_address: &NetworkAddress,
+    debug_port: u16,
+    timeout: Duration,
+) -> Result<HashSet<PeerId>, Error> {
+    let host = validator_network_address
+        .as_slice()
+        .iter()
+        .find_map(|protocol| match protocol {
+            Protocol::Ip4(ip) => Some(ip.to_string()),
+            Protocol::Ip6(ip) => Some(format!("[{}]", ip)),
+            Protocol::Dns(name) | Protocol::Dns4(name) | Protocol::Dns6(name) => {
+                Some(name.to_string())
+            }
+            _ => None,
+        })
+        .ok_or_else(|| {
+            Error::UnexpectedError(format!("No host in address {}", validator_network_address))
+        })?;
+    let url = Url::parse(&format!("http://{}:{}", host, debug_port))
+        .map_err(|err| Error::UnexpectedError(err.to_string()))?;
+
+    let peers = tokio::time::timeout(
+        timeout,
+        AsyncNodeDebugClient::from_url(url).get_connected_peers(),
+    )
+    .await
+    .map_err(|_| Error::Timeout("CheckConnectivityMatrix", host))?
+    .map_err(|err| Error::UnexpectedError(err.to_string()))?;
+
+    Ok(peers
+        .into_iter()
+        .filter(|peer| peer.network_id == NetworkId::Validator)
+        .map(|peer| peer.peer_id)
+        .collect())
+}
+
 /// Builds a listener free noise connector
 fn build_upgrade_context(
     chain_id: ChainId,
@@ -4,6 +4,7 @@
 use crate::{
     account_resource::SimplifiedAccountResource,
     command::{Command, CommandName},
+    diagnostics::DiagnosticsBundle,
     keys::{EncodingType, KeyType},
     validator_config::DecodedValidatorConfig,
     validator_set::DecryptedValidatorInfo,
@@ -109,6 +110,28 @@ impl OperationalTool {
             .await
     }
 
+    pub async fn collect_diagnostics(
+        &self,
+        config_path: &Path,
+        log_file: Option<&Path>,
+        output: &Path,
+    ) -> Result<DiagnosticsBundle, Error> {
+        let args = format!(
+            "
+                {command}
+                --config {config_path}
+                {log_file}
+                --output {output}
+            ",
+            command = command(TOOL_NAME, CommandName::CollectDiagnostics),
+            config_path = config_path.to_str().unwrap(),
+            log_file = optional_arg("log-file", log_file.map(|path| path.to_str().unwrap())),
+            output = output.to_str().unwrap(),
+        );
+        let command = Command::from_iter(args.split_whitespace());
+        command.collect_diagnostics().await
+    }
+
     pub fn create_account(
         &self,
         name: &str,
@@ -22,7 +22,7 @@ use aptos_types::{
     transaction::{SignedTransaction, Transaction},
 };
 use core::str::FromStr;
-use std::net::{Ipv4Addr, ToSocketAddrs};
+use std::net::{Ipv4Addr, Ipv6Addr, ToSocketAddrs};
 use structopt::StructOpt;
 
 #[derive(Clone, Debug, StructOpt)]
@@ -178,10 +178,21 @@ pub fn validate_address(
                 }
                 has_addr = true
             }
+            Protocol::Ip6(_) => has_addr = true,
+            Protocol::Dns6(dns_name) => {
+                let dns_name = format!("{}", dns_name);
+                if Ipv6Addr::from_str(&dns_name).is_ok() {
+                    return Err(Error::CommandArgumentError(format!(
+                        "{}: Please use the /ip6/ protocol for IP addresses",
+                        address_name
+                    )));
+                }
+                has_addr = true
+            }
             Protocol::Tcp(_) => has_port = true,
-            Protocol::Dns(_) | Protocol::Ip6(_) | Protocol::Dns6(_) => {
+            Protocol::Dns(_) => {
                 return Err(Error::CommandArgumentError(format!(
-                    "{}: IPv6 is currently not supported.  Protocol: '{}'",
+                    "{}: Please use the /dns4/ or /dns6/ protocol to pick an IP version.  Protocol: '{}'",
                     address_name, protocol
                 )))
             }
@@ -245,8 +256,10 @@ mod tests {
     fn test_valid_inputs() {
         let ip = NetworkAddress::from_str("/ip4/127.0.0.1/tcp/1234").unwrap();
         let dns = NetworkAddress::from_str("/dns4/localhost/tcp/1234").unwrap();
+        let ipv6 = NetworkAddress::from_str("/ip6/::1/tcp/1234").unwrap();
 
         validate_address("ip", &ip).expect("IP failed to validate");
         validate_address("dns", &dns).expect("DNS failed to validate");
+        validate_address("ipv6", &ipv6).expect("IPv6 failed to validate");
     }
 }
@@ -1,9 +1,9 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::utils;
+use crate::{config::Error, utils};
 use serde::{Deserialize, Serialize};
-use std::net::SocketAddr;
+use std::{env, fs, net::SocketAddr, path::PathBuf};
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default, deny_unknown_fields)]
@@ -18,6 +18,24 @@ pub struct ApiConfig {
     // optional for compatible with old configuration
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub content_length_limit: Option<u64>,
+    /// Enables the `/admin` routes, which require an `Authorization: Bearer <token>` header with
+    /// this token: `/admin/failpoints` configures failpoints at runtime, for tests such as forge
+    /// fault injection, and only works in binaries built with failpoints. `/admin/mempool/parked`
+    /// lists the accounts whose transactions mempool holds back for a missing sequence number.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failpoints_admin_token: Option<String>,
+    /// Mempool occupancy, in percent of its capacity, from which transaction submissions are
+    /// rejected with 429 so clients back off, instead of being passed on to mempool
+    pub mempool_backpressure_threshold_pct: u64,
+    /// Seconds clients are asked to wait before retrying a submission rejected by backpressure
+    pub mempool_backpressure_retry_after_secs: u64,
+    /// Milliseconds a request with the `X-Aptos-If-Ledger-Version-At-Least` header waits for the
+    /// node to reach the version, before being rejected with 412
+    pub ledger_version_precondition_max_wait_ms: u64,
+    /// Requires requests to carry one of these keys in the `x-api-key` header, each with its own
+    /// rate limit and allowed endpoint classes. The API is open to everyone when there are none.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub api_keys: Vec<ApiKeyConfig>,
 }
 
 pub const DEFAULT_ADDRESS: &str = "127.0.0.1";
@@ -38,6 +56,11 @@ impl Default for ApiConfig {
             tls_cert_path: None,
             tls_key_path: None,
             content_length_limit: None,
+            failpoints_admin_token: None,
+            mempool_backpressure_threshold_pct: 90,
+            mempool_backpressure_retry_after_secs: 5,
+            ledger_version_precondition_max_wait_ms: 2000,
+            api_keys: vec![],
         }
     }
 }
@@ -54,3 +77,79 @@ impl ApiConfig {
         }
     }
 }
+
+/// A key clients of the API authenticate with. Requests made with the key are attributed to its
+/// name in the request logs and metrics.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiKeyConfig {
+    pub name: String,
+    pub key: ApiKey,
+    /// Max number of requests per second made with the key, unlimited if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_requests_per_sec: Option<u64>,
+    /// The classes of endpoints the key may call, reads and submissions if not set. Admin
+    /// endpoints are only allowed to keys which list them.
+    #[serde(default = "ApiEndpointClass::default_classes")]
+    pub endpoint_classes: Vec<ApiEndpointClass>,
+}
+
+/// Keys can either be directly within this config, stored somewhere on disk or passed through an
+/// environment variable.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiKey {
+    FromConfig(String),
+    /// This is an absolute path and not relative to data_dir
+    FromDisk(PathBuf),
+    /// The name of the environment variable holding the key
+    FromEnv(String),
+}
+
+impl ApiKey {
+    pub fn read_key(&self) -> Result<String, Error> {
+        match self {
+            ApiKey::FromConfig(key) => Ok(key.clone()),
+            ApiKey::FromDisk(path) => fs::read_to_string(path)
+                .map(|key| key.trim().to_owned())
+                .map_err(|e| Error::IO(path.display().to_string(), e)),
+            ApiKey::FromEnv(var) => env::var(var).map_err(|_| {
+                Error::InvariantViolation(format!("environment variable {} is not set", var))
+            }),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiEndpointClass {
+    /// Queries of the ledger, accounts, events and state
+    Read,
+    /// Transaction submissions
+    Submit,
+    /// The `/admin` routes
+    Admin,
+}
+
+impl ApiEndpointClass {
+    pub fn all() -> Vec<ApiEndpointClass> {
+        vec![
+            ApiEndpointClass::Read,
+            ApiEndpointClass::Submit,
+            ApiEndpointClass::Admin,
+        ]
+    }
+
+    /// The classes of the keys which don't list theirs, which leave out the admin endpoints
+    pub fn default_classes() -> Vec<ApiEndpointClass> {
+        vec![ApiEndpointClass::Read, ApiEndpointClass::Submit]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiEndpointClass::Read => "read",
+            ApiEndpointClass::Submit => "submit",
+            ApiEndpointClass::Admin => "admin",
+        }
+    }
+}
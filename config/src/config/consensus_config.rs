@@ -10,7 +10,11 @@ use std::{collections::HashMap, path::PathBuf};
 #[serde(default, deny_unknown_fields)]
 pub struct ConsensusConfig {
     pub contiguous_rounds: u32,
+    // Max number of txns in a proposed block, unless set in the on-chain consensus config
     pub max_block_size: u64,
+    // Max total size in bytes of the txns in a proposed block, unless set in the on-chain
+    // consensus config
+    pub max_block_bytes: u64,
     pub max_pruned_blocks_in_mem: usize,
     // Timeout for consensus to get an ack from mempool for executed transactions (in milliseconds)
     pub mempool_executed_txn_timeout_ms: u64,
@@ -23,9 +27,13 @@ pub struct ConsensusConfig {
     // validators coordinate on the latest version to apply a manual transaction.
     pub sync_only: bool,
     // Decides how long the leader waits before proposing empty block if there's no txns in mempool
-    // the period = (poll_count - 1) * 30ms
+    // the period = (poll_count - 1) * 30ms, unless set in the on-chain consensus config
     pub mempool_poll_count: u64,
     pub channel_size: usize,
+    // Max number of rounds a node can fall behind the ordered blocks of its peers and still catch
+    // up by retrieving the missing blocks from them, rather than by state sync. 0, the default,
+    // always syncs.
+    pub max_catch_up_rounds: u64,
 }
 
 impl Default for ConsensusConfig {
@@ -33,6 +41,7 @@ impl Default for ConsensusConfig {
         ConsensusConfig {
             contiguous_rounds: 2,
             max_block_size: 3000,
+            max_block_bytes: 5 * 1024 * 1024, // 5MB
             max_pruned_blocks_in_mem: 100,
             mempool_txn_pull_timeout_ms: 1000,
             mempool_executed_txn_timeout_ms: 1000,
@@ -45,6 +54,7 @@ impl Default for ConsensusConfig {
             sync_only: false,
             mempool_poll_count: 20,
             channel_size: 30, // hard-coded
+            max_catch_up_rounds: 0,
         }
     }
 }
@@ -15,6 +15,9 @@ pub struct MempoolConfig {
     pub shared_mempool_ack_timeout_ms: u64,
     pub shared_mempool_backoff_interval_ms: u64,
     pub shared_mempool_batch_size: usize,
+    // number of recently broadcast transactions remembered per upstream peer, so that they aren't
+    // broadcast to it again. 0 disables the suppression of duplicate broadcasts.
+    pub shared_mempool_broadcast_filter_capacity: usize,
     pub shared_mempool_max_concurrent_inbound_syncs: usize,
     pub shared_mempool_tick_interval_ms: u64,
     pub system_transaction_timeout_secs: u64,
@@ -27,6 +30,7 @@ impl Default for MempoolConfig {
             shared_mempool_tick_interval_ms: 50,
             shared_mempool_backoff_interval_ms: 30_000,
             shared_mempool_batch_size: 100,
+            shared_mempool_broadcast_filter_capacity: 20_000,
             shared_mempool_ack_timeout_ms: 2_000,
             shared_mempool_max_concurrent_inbound_syncs: 2,
             max_broadcasts_per_peer: 1,
@@ -97,6 +97,8 @@ pub struct NetworkConfig {
     pub inbound_rate_limit_config: Option<RateLimitConfig>,
     // Outbound rate limiting configuration, if not specified, no rate limiting
     pub outbound_rate_limit_config: Option<RateLimitConfig>,
+    // Scoring of misbehaving peers, which are temporarily banned past a threshold
+    pub peer_reputation: PeerReputationConfig,
 }
 
 impl Default for NetworkConfig {
@@ -130,6 +132,7 @@ impl NetworkConfig {
             max_inbound_connections: MAX_INBOUND_CONNECTIONS,
             inbound_rate_limit_config: None,
             outbound_rate_limit_config: None,
+            peer_reputation: PeerReputationConfig::default(),
         };
         config.prepare_identity();
         config
@@ -389,6 +392,48 @@ impl Default for RateLimitConfig {
     }
 }
 
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PeerReputationConfig {
+    /// Allow for disabling the banning of peers, misbehavior is still recorded. The peers of the
+    /// validator network, trusted validators, are never banned.
+    pub enabled: bool,
+    /// Penalty for a message which can't be deserialized
+    pub invalid_message_penalty: u64,
+    /// Penalty for data failing proof verification, as reported by state sync
+    pub invalid_proof_penalty: u64,
+    /// Penalty for connecting more than `max_connections_per_window` times in a window
+    pub connection_churn_penalty: u64,
+    /// Maximum number of connections of a peer within `connection_churn_window_secs`
+    pub max_connections_per_window: usize,
+    pub connection_churn_window_secs: u64,
+    /// Amount of penalty forgiven every second
+    pub penalty_decay_per_sec: u64,
+    /// A peer is banned once its penalty reaches this threshold
+    pub ban_threshold: u64,
+    /// How long a ban lasts before the peer is allowed to connect again
+    pub ban_duration_secs: u64,
+    /// Peers which are never banned, regardless of their behavior
+    pub ban_exempt_peers: HashSet<PeerId>,
+}
+
+impl Default for PeerReputationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            invalid_message_penalty: 10,
+            invalid_proof_penalty: 50,
+            connection_churn_penalty: 20,
+            max_connections_per_window: 10,
+            connection_churn_window_secs: 60,
+            penalty_decay_per_sec: 1,
+            ban_threshold: 100,
+            ban_duration_secs: 600, /* 10 minutes */
+            ban_exempt_peers: HashSet::new(),
+        }
+    }
+}
+
 pub type PeerSet = HashMap<PeerId, Peer>;
 
 // TODO: Combine with RoleType?
@@ -89,7 +89,8 @@ impl InitialSafetyRulesConfig {
 pub enum SafetyRulesService {
     /// This runs safety rules in the same thread as event processor
     Local,
-    /// This is the production, separate service approach
+    /// This is the production, separate service approach, e.g. the safety-rules binary in a
+    /// sidecar container of the validator
     Process(RemoteService),
     /// This runs safety rules in the same thread as event processor but data is passed through the
     /// light weight RPC (serializer)
@@ -102,6 +103,14 @@ pub enum SafetyRulesService {
 #[serde(deny_unknown_fields)]
 pub struct RemoteService {
     pub server_address: NetworkAddress,
+    /// Serves safety rules over this Unix domain socket instead of `server_address`, for a
+    /// service on the same host as the validator, e.g. in a volume shared with a sidecar
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unix_socket_path: Option<PathBuf>,
+    /// Where the service answers HTTP health checks once its storage is loaded, since the service
+    /// itself only takes one client at a time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health_check_address: Option<SocketAddr>,
 }
 
 impl RemoteService {
@@ -107,7 +107,8 @@ impl Default for StateSyncDriverConfig {
 #[serde(default, deny_unknown_fields)]
 pub struct StorageServiceConfig {
     pub max_account_states_chunk_sizes: u64, // Max num of accounts per chunk
-    pub max_concurrent_requests: u64,        // Max num of concurrent storage server tasks
+    pub max_concurrent_requests: u64,        // Max num of concurrent tasks, across all networks
+    pub max_concurrent_public_requests: u64, // Max num of those tasks serving public fullnodes
     pub max_epoch_chunk_size: u64,           // Max num of epoch ending ledger infos per chunk
     pub max_lru_cache_size: u64,             // Max num of items in the lru cache before eviction
     pub max_network_channel_size: u64,       // Max num of pending network messages
@@ -121,6 +122,7 @@ impl Default for StorageServiceConfig {
         Self {
             max_account_states_chunk_sizes: 1000,
             max_concurrent_requests: 4000,
+            max_concurrent_public_requests: 1000,
             max_epoch_chunk_size: 100,
             max_lru_cache_size: 100,
             max_network_channel_size: 4000,
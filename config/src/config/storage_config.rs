@@ -46,6 +46,31 @@ pub struct StorageConfig {
     pub timeout_ms: u64,
     /// Rocksdb-specific configurations
     pub rocksdb_config: RocksdbConfig,
+    /// Moves ancient transactions and write sets to a secondary DB, e.g. on cheaper disks. None
+    /// keeps the full history in the main DB.
+    pub cold_storage: Option<ColdStorageConfig>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColdStorageConfig {
+    /// Directory of the cold DB, relative to the data dir unless absolute
+    pub dir: PathBuf,
+    /// Number of latest versions kept in the main DB. Older transactions and write sets are moved
+    /// to the cold DB, from which they are still served transparently.
+    pub hot_window: u64,
+    /// Number of versions moved to the cold DB at once
+    pub archiving_batch_size: usize,
+}
+
+impl Default for ColdStorageConfig {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("cold_db"),
+            hot_window: 10_000_000,
+            archiving_batch_size: 10_000,
+        }
+    }
 }
 
 pub const NO_OP_STORAGE_PRUNER_CONFIG: StoragePrunerConfig = StoragePrunerConfig {
@@ -105,6 +130,7 @@ impl Default for StorageConfig {
             // Default read/write/connection timeout, in milliseconds
             timeout_ms: 30_000,
             rocksdb_config: RocksdbConfig::default(),
+            cold_storage: None,
         }
     }
 }
@@ -118,6 +144,18 @@ impl StorageConfig {
         }
     }
 
+    /// Returns the cold storage config, with its directory resolved against the data dir
+    pub fn cold_storage(&self) -> Option<ColdStorageConfig> {
+        self.cold_storage.as_ref().map(|config| ColdStorageConfig {
+            dir: if config.dir.is_relative() {
+                self.data_dir.join(&config.dir)
+            } else {
+                config.dir.clone()
+            },
+            ..config.clone()
+        })
+    }
+
     pub fn set_data_dir(&mut self, data_dir: PathBuf) {
         self.data_dir = data_dir;
     }
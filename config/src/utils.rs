@@ -8,7 +8,16 @@ use aptos_types::{
     transaction::{authenticator::AuthenticationKey, Transaction},
 };
 use get_if_addrs::get_if_addrs;
-use std::net::{TcpListener, TcpStream};
+use once_cell::sync::Lazy;
+use std::{
+    collections::HashSet,
+    net::{TcpListener, TcpStream},
+    sync::Mutex,
+};
+
+/// Ports returned by `get_available_port`, which never returns the same port twice. Only some OSes
+/// keep the port of a closed connection reserved, e.g. Windows may hand it out again right away.
+static RETURNED_PORTS: Lazy<Mutex<HashSet<u16>>> = Lazy::new(|| Mutex::new(HashSet::new()));
 
 pub fn default_validator_owner_auth_key_from_name(name: &[u8]) -> AuthenticationKey {
     let salt = "validator_owner::";
@@ -24,13 +33,16 @@ pub fn validator_owner_account_from_name(name: &[u8]) -> AccountAddress {
 
 /// Return an ephemeral, available port. On unix systems, the port returned will be in the
 /// TIME_WAIT state ensuring that the OS won't hand out this port for some grace period.
-/// Callers should be able to bind to this port given they use SO_REUSEADDR.
+/// Callers should be able to bind to this port given they use SO_REUSEADDR. Within a process, the
+/// same port is never returned twice.
 pub fn get_available_port() -> u16 {
     const MAX_PORT_RETRIES: u32 = 1000;
 
     for _ in 0..MAX_PORT_RETRIES {
         if let Ok(port) = get_ephemeral_port() {
-            return port;
+            if RETURNED_PORTS.lock().unwrap().insert(port) {
+                return port;
+            }
         }
     }
 
@@ -6,9 +6,32 @@ use crate::{
     remote_service::{self, RemoteService},
     safety_rules_manager,
 };
-use aptos_config::config::{SafetyRulesConfig, SafetyRulesService};
+use aptos_config::config::{
+    RemoteService as RemoteServiceConfig, SafetyRulesConfig, SafetyRulesService,
+};
+use aptos_logger::{info, warn};
+use aptos_secure_net::ServerAddress;
+use std::{
+    io::{Read, Write},
+    net::{SocketAddr, TcpListener},
+    thread,
+    time::Duration,
+};
+
+/// How long a health check gets to send its request
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(1);
 
-use std::net::SocketAddr;
+/// Where `service` listens and the validator connects: its Unix domain socket if it has one, its
+/// server address otherwise
+pub fn server_address(service: &RemoteServiceConfig) -> ServerAddress {
+    match &service.unix_socket_path {
+        #[cfg(unix)]
+        Some(path) => ServerAddress::Unix(path.clone()),
+        #[cfg(not(unix))]
+        Some(_) => panic!("Unix domain sockets are not supported on this platform"),
+        None => service.server_address().into(),
+    }
+}
 
 pub struct Process {
     data: Option<ProcessData>,
@@ -24,11 +47,13 @@ impl Process {
             SafetyRulesService::Process(service) => service,
             _ => panic!("Unexpected SafetyRules service: {:?}", config.service),
         };
-        let server_addr = service.server_address();
+        let server_addr = server_address(service);
+        let health_check_addr = service.health_check_address;
 
         Self {
             data: Some(ProcessData {
                 server_addr,
+                health_check_addr,
                 storage,
                 verify_vote_proposal_signature,
                 export_consensus_key,
@@ -39,6 +64,10 @@ impl Process {
 
     pub fn start(&mut self) {
         let data = self.data.take().expect("Unable to retrieve ProcessData");
+        // The storage is loaded by now, so the service is ready as soon as it answers
+        if let Some(health_check_addr) = data.health_check_addr {
+            serve_health_checks(health_check_addr);
+        }
         remote_service::execute(
             data.storage,
             data.server_addr,
@@ -50,7 +79,8 @@ impl Process {
 }
 
 struct ProcessData {
-    server_addr: SocketAddr,
+    server_addr: ServerAddress,
+    health_check_addr: Option<SocketAddr>,
     storage: PersistentSafetyStorage,
     verify_vote_proposal_signature: bool,
     export_consensus_key: bool,
@@ -58,13 +88,35 @@ struct ProcessData {
     network_timeout: u64,
 }
 
+/// Answers every HTTP request on `address` with 200 from a thread of its own, for as long as the
+/// process runs, e.g. for the probes of k8s
+fn serve_health_checks(address: SocketAddr) {
+    let listener = TcpListener::bind(address).expect("Unable to bind the health check address");
+    info!("Serving health checks on {}", address);
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let result = stream.and_then(|mut stream| {
+                // What is asked doesn't matter, the request is only read so it isn't reset
+                stream.set_read_timeout(Some(HEALTH_CHECK_TIMEOUT))?;
+                let _ = stream.read(&mut [0; 1024]);
+                stream.write_all(
+                    b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+                )
+            });
+            if let Err(e) = result {
+                warn!("Failed to answer a health check: {}", e);
+            }
+        }
+    });
+}
+
 pub struct ProcessService {
-    server_addr: SocketAddr,
+    server_addr: ServerAddress,
     network_timeout_ms: u64,
 }
 
 impl ProcessService {
-    pub fn new(server_addr: SocketAddr, network_timeout: u64) -> Self {
+    pub fn new(server_addr: ServerAddress, network_timeout: u64) -> Self {
         Self {
             server_addr,
             network_timeout_ms: network_timeout,
@@ -73,8 +125,8 @@ impl ProcessService {
 }
 
 impl RemoteService for ProcessService {
-    fn server_address(&self) -> SocketAddr {
-        self.server_addr
+    fn server_address(&self) -> ServerAddress {
+        self.server_addr.clone()
     }
 
     fn network_timeout_ms(&self) -> u64 {
@@ -7,8 +7,7 @@ use crate::{
     Error, SafetyRules, TSafetyRules,
 };
 use aptos_logger::warn;
-use aptos_secure_net::{NetworkClient, NetworkServer};
-use std::net::SocketAddr;
+use aptos_secure_net::{NetworkClient, NetworkServer, ServerAddress};
 
 pub trait RemoteService {
     fn client(&self) -> SerializerClient {
@@ -21,7 +20,7 @@ pub trait RemoteService {
         SerializerClient::new_client(service)
     }
 
-    fn server_address(&self) -> SocketAddr;
+    fn server_address(&self) -> ServerAddress;
 
     /// Network Timeout in milliseconds.
     fn network_timeout_ms(&self) -> u64;
@@ -29,7 +28,7 @@ pub trait RemoteService {
 
 pub fn execute(
     storage: PersistentSafetyStorage,
-    listen_addr: SocketAddr,
+    listen_addr: ServerAddress,
     verify_vote_proposal_signature: bool,
     export_consensus_key: bool,
     network_timeout_ms: u64,
@@ -4,7 +4,7 @@
 use crate::{
     local_client::LocalClient,
     persistent_safety_storage::PersistentSafetyStorage,
-    process::ProcessService,
+    process::{self, ProcessService},
     remote_service::RemoteService,
     serializer::{SerializerClient, SerializerService},
     thread::ThreadService,
@@ -12,8 +12,9 @@ use crate::{
 };
 use aptos_config::config::{InitialSafetyRulesConfig, SafetyRulesConfig, SafetyRulesService};
 use aptos_infallible::RwLock;
+use aptos_secure_net::ServerAddress;
 use aptos_secure_storage::{KVStorage, Storage};
-use std::{convert::TryInto, net::SocketAddr, sync::Arc};
+use std::{convert::TryInto, sync::Arc};
 
 pub fn storage(config: &SafetyRulesConfig) -> PersistentSafetyStorage {
     let backend = &config.backend;
@@ -96,7 +97,7 @@ pub struct SafetyRulesManager {
 impl SafetyRulesManager {
     pub fn new(config: &SafetyRulesConfig) -> Self {
         if let SafetyRulesService::Process(conf) = &config.service {
-            return Self::new_process(conf.server_address(), config.network_timeout_ms);
+            return Self::new_process(process::server_address(conf), config.network_timeout_ms);
         }
 
         let storage = storage(config);
@@ -138,7 +139,7 @@ impl SafetyRulesManager {
         }
     }
 
-    pub fn new_process(server_addr: SocketAddr, timeout_ms: u64) -> Self {
+    pub fn new_process(server_addr: ServerAddress, timeout_ms: u64) -> Self {
         let process_service = ProcessService::new(server_addr, timeout_ms);
         Self {
             internal_safety_rules: SafetyRulesWrapper::Process(process_service),
@@ -12,6 +12,7 @@ use crate::{
     remote_service::{self, RemoteService},
 };
 use aptos_config::utils;
+use aptos_secure_net::ServerAddress;
 use std::{
     net::{IpAddr, Ipv4Addr, SocketAddr},
     thread::{self, JoinHandle},
@@ -39,7 +40,7 @@ impl ThreadService {
         let child = thread::spawn(move || {
             remote_service::execute(
                 storage,
-                listen_addr,
+                listen_addr.into(),
                 verify_vote_proposal_signature,
                 export_consensus_key,
                 timeout,
@@ -55,8 +56,8 @@ impl ThreadService {
 }
 
 impl RemoteService for ThreadService {
-    fn server_address(&self) -> SocketAddr {
-        self.server_addr
+    fn server_address(&self) -> ServerAddress {
+        self.server_addr.into()
     }
     fn network_timeout_ms(&self) -> u64 {
         self.network_timeout
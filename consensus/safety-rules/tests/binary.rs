@@ -2,28 +2,37 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_config::{
-    config::{NodeConfig, PersistableConfig, RemoteService, SafetyRulesService},
+    config::{NodeConfig, PersistableConfig, RemoteService, SafetyRulesConfig, SafetyRulesService},
     utils,
 };
 use aptos_types::validator_signer::ValidatorSigner;
-use safety_rules::{test_utils, SafetyRulesManager};
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use safety_rules::{test_utils, ConsensusState, Error, SafetyRulesManager};
+use std::{
+    io::{self, Read, Write},
+    net::{IpAddr, Ipv4Addr, SocketAddr, TcpStream},
+    time::{Duration, Instant},
+};
 
 const BINARY: &str = env!("CARGO_BIN_EXE_safety-rules");
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(30);
 
-#[test]
-fn test_consensus_state() {
+fn test_config() -> SafetyRulesConfig {
     let mut config = NodeConfig::random().consensus.safety_rules;
     let test_config = config.test.as_mut().unwrap();
     let private_key = test_config.consensus_key.as_ref().unwrap().private_key();
     let signer = ValidatorSigner::new(test_config.author, private_key);
     let waypoint = test_utils::validator_signers_to_waypoint(&[&signer]);
     test_config.waypoint = Some(waypoint);
+    config
+}
 
-    let server_port = utils::get_available_port();
-    let server_address = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), server_port).into();
-    config.service = SafetyRulesService::Process(RemoteService { server_address });
+fn localhost_address() -> SocketAddr {
+    SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), utils::get_available_port())
+}
 
+/// Runs the binary with `config`, which must be a process service, and gets the consensus state
+/// from it
+fn consensus_state_from_binary(config: &SafetyRulesConfig) -> Result<ConsensusState, Error> {
     let config_path = aptos_temppath::TempPath::new();
     config_path.create_as_file().unwrap();
     config.save_config(config_path.path()).unwrap();
@@ -36,7 +45,7 @@ fn test_consensus_state() {
         .stderr(std::process::Stdio::inherit());
     let mut child = command.spawn().unwrap();
 
-    let safety_rules_manager = SafetyRulesManager::new(&config);
+    let safety_rules_manager = SafetyRulesManager::new(config);
     let mut safety_rules = safety_rules_manager.client();
     let consensus_state = safety_rules.consensus_state();
 
@@ -46,5 +55,89 @@ fn test_consensus_state() {
     child
         .wait()
         .expect("could not wait on safety-rules process");
-    consensus_state.unwrap();
+    consensus_state
+}
+
+#[test]
+fn test_consensus_state() {
+    let mut config = test_config();
+    config.service = SafetyRulesService::Process(RemoteService {
+        server_address: localhost_address().into(),
+        unix_socket_path: None,
+        health_check_address: None,
+    });
+    consensus_state_from_binary(&config).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_consensus_state_over_unix_socket() {
+    let socket_dir = aptos_temppath::TempPath::new();
+    socket_dir.create_as_dir().unwrap();
+    let mut config = test_config();
+    config.service = SafetyRulesService::Process(RemoteService {
+        server_address: localhost_address().into(),
+        unix_socket_path: Some(socket_dir.path().join("safety-rules.sock")),
+        health_check_address: None,
+    });
+    consensus_state_from_binary(&config).unwrap();
+}
+
+#[test]
+fn test_health_check() {
+    let health_check_address = localhost_address();
+    let mut config = test_config();
+    config.service = SafetyRulesService::Process(RemoteService {
+        server_address: localhost_address().into(),
+        unix_socket_path: None,
+        health_check_address: Some(health_check_address),
+    });
+
+    let config_path = aptos_temppath::TempPath::new();
+    config_path.create_as_file().unwrap();
+    config.save_config(config_path.path()).unwrap();
+    let mut child = std::process::Command::new(BINARY)
+        .arg(config_path.path())
+        .spawn()
+        .unwrap();
+
+    // The health checks are served once the storage is loaded
+    let deadline = Instant::now() + HEALTH_CHECK_TIMEOUT;
+    let response = (|| loop {
+        if let Some(status) = child.try_wait()? {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!(
+                    "safety-rules exited before serving health checks: {}",
+                    status
+                ),
+            ));
+        }
+        if let Ok(mut stream) = TcpStream::connect(health_check_address) {
+            stream.write_all(b"GET / HTTP/1.1\r\n\r\n")?;
+            let mut response = String::new();
+            stream.read_to_string(&mut response)?;
+            return Ok(response);
+        }
+        if Instant::now() >= deadline {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("health checks not served within {:?}", HEALTH_CHECK_TIMEOUT),
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    })();
+
+    // Killing an already exited process is an error, so only kill it while it still runs
+    if child
+        .try_wait()
+        .expect("could not poll safety-rules process")
+        .is_none()
+    {
+        child.kill().expect("could not kill safety-rules process");
+        child
+            .wait()
+            .expect("could not wait on safety-rules process");
+    }
+    assert!(response.unwrap().starts_with("HTTP/1.1 200 OK"));
 }
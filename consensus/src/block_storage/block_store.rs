@@ -101,6 +101,8 @@ pub struct BlockStore {
     time_service: Arc<dyn TimeService>,
     // consistent with round type
     back_pressure_limit: Round,
+    // Gaps up to this many rounds are caught up from peers instead of state synced
+    max_catch_up_rounds: Round,
 }
 
 impl BlockStore {
@@ -111,6 +113,7 @@ impl BlockStore {
         max_pruned_blocks_in_mem: usize,
         time_service: Arc<dyn TimeService>,
         back_pressure_limit: Round,
+        max_catch_up_rounds: Round,
     ) -> Self {
         let highest_tc = initial_data.highest_timeout_certificate();
         let highest_2chain_tc = initial_data.highest_2chain_timeout_certificate();
@@ -127,6 +130,7 @@ impl BlockStore {
             max_pruned_blocks_in_mem,
             time_service,
             back_pressure_limit,
+            max_catch_up_rounds,
         ));
         block_on(block_store.try_commit());
         block_store
@@ -165,6 +169,7 @@ impl BlockStore {
         max_pruned_blocks_in_mem: usize,
         time_service: Arc<dyn TimeService>,
         back_pressure_limit: Round,
+        max_catch_up_rounds: Round,
     ) -> Self {
         let RootInfo(root_block, root_qc, root_ordered_cert, root_commit_li) = root;
 
@@ -220,6 +225,7 @@ impl BlockStore {
             storage,
             time_service,
             back_pressure_limit,
+            max_catch_up_rounds,
         };
         for block in blocks {
             block_store
@@ -314,6 +320,7 @@ impl BlockStore {
             max_pruned_blocks_in_mem,
             Arc::clone(&self.time_service),
             self.back_pressure_limit,
+            self.max_catch_up_rounds,
         )
         .await;
 
@@ -5,12 +5,17 @@ use crate::{
     block_storage::{block_store::sync_manager::NeedFetchResult, BlockReader},
     pending_votes::{PendingVotes, VoteReceptionResult},
     test_utils::{
-        build_empty_tree, build_simple_tree, consensus_runtime, timed_block_on, TreeInserter,
+        build_empty_tree, build_empty_tree_with_catch_up, build_simple_tree, consensus_runtime,
+        timed_block_on, TreeInserter,
     },
 };
 use aptos_crypto::{HashValue, PrivateKey};
 use aptos_types::{
-    validator_signer::ValidatorSigner, validator_verifier::random_validator_verifier,
+    block_info::BlockInfo,
+    epoch_state::EpochState,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    validator_signer::ValidatorSigner,
+    validator_verifier::random_validator_verifier,
 };
 use consensus_types::{
     block::{
@@ -25,7 +30,10 @@ use consensus_types::{
     vote_data::VoteData,
 };
 use proptest::prelude::*;
-use std::{cmp::min, collections::HashSet};
+use std::{
+    cmp::min,
+    collections::{BTreeMap, HashSet},
+};
 
 #[tokio::test]
 async fn test_highest_block_and_quorum_cert() {
@@ -422,3 +430,59 @@ async fn test_need_fetch_for_qc() {
         NeedFetchResult::QCAlreadyExist,
     );
 }
+
+#[tokio::test]
+async fn test_can_catch_up_to_ordered_cert() {
+    let signer = ValidatorSigner::random(None);
+    let ordered_cert = |round: u64| {
+        placeholder_certificate_for_block(
+            vec![&signer],
+            HashValue::random(),
+            round,
+            HashValue::random(),
+            round - 1,
+        )
+    };
+    let ledger_info = |round: u64, next_epoch_state: Option<EpochState>| {
+        LedgerInfoWithSignatures::new(
+            LedgerInfo::new(
+                BlockInfo::new(
+                    1,
+                    round,
+                    HashValue::random(),
+                    HashValue::zero(),
+                    0,
+                    0,
+                    next_epoch_state,
+                ),
+                HashValue::zero(),
+            ),
+            BTreeMap::new(),
+        )
+    };
+
+    let block_store = build_empty_tree_with_catch_up(3);
+    let root_round = block_store.ordered_root().round();
+    // gaps up to the limit are caught up from peers
+    assert!(block_store.can_catch_up_to_ordered_cert(
+        &ordered_cert(root_round + 3),
+        &ledger_info(root_round + 2, None)
+    ));
+    // beyond it, the node falls back to state sync
+    assert!(!block_store.can_catch_up_to_ordered_cert(
+        &ordered_cert(root_round + 4),
+        &ledger_info(root_round + 2, None)
+    ));
+    // as it does for gaps ending an epoch
+    assert!(!block_store.can_catch_up_to_ordered_cert(
+        &ordered_cert(root_round + 3),
+        &ledger_info(root_round + 2, Some(EpochState::empty()))
+    ));
+
+    // with no rounds to catch up, as by default, the node always state syncs
+    let block_store = build_empty_tree();
+    assert!(!block_store.can_catch_up_to_ordered_cert(
+        &ordered_cert(root_round + 1),
+        &ledger_info(root_round + 1, None)
+    ));
+}
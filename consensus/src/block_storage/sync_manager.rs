@@ -3,6 +3,7 @@
 
 use crate::{
     block_storage::{BlockReader, BlockStore},
+    counters,
     logging::{LogEvent, LogSchema},
     network::NetworkSender,
     network_interface::ConsensusMsg,
@@ -44,6 +45,19 @@ impl BlockStore {
         self.ordered_root().round() + self.back_pressure_limit < li.commit_info().round()
     }
 
+    /// Check if the gap to this ordered cert is small enough to catch up by retrieving the missing
+    /// blocks from peers. Gaps ending an epoch are always state synced, as the peers may have
+    /// moved on to the next epoch already.
+    pub fn can_catch_up_to_ordered_cert(
+        &self,
+        highest_ordered_cert: &QuorumCert,
+        highest_ledger_info: &LedgerInfoWithSignatures,
+    ) -> bool {
+        !highest_ledger_info.ledger_info().ends_epoch()
+            && highest_ordered_cert.certified_block().round()
+                <= self.ordered_root().round() + self.max_catch_up_rounds
+    }
+
     /// Checks if quorum certificate can be inserted in block store without RPC
     /// Returns the enum to indicate the detailed status.
     pub fn need_fetch_for_quorum_cert(&self, qc: &QuorumCert) -> NeedFetchResult {
@@ -170,6 +184,33 @@ impl BlockStore {
         if !self.need_sync_for_ledger_info(&highest_ledger_info) {
             return Ok(());
         }
+        if self.can_catch_up_to_ordered_cert(&highest_ordered_cert, &highest_ledger_info) {
+            match self
+                .catch_up_to_ordered_cert(&highest_ordered_cert, &highest_ledger_info, retriever)
+                .await
+            {
+                Ok(()) => {
+                    counters::CATCH_UP_COUNT
+                        .with_label_values(&["caught_up"])
+                        .inc();
+                    return Ok(());
+                }
+                Err(e) => {
+                    counters::CATCH_UP_COUNT
+                        .with_label_values(&["fallback"])
+                        .inc();
+                    warn!(
+                        remote_peer = retriever.preferred_peer,
+                        error = ?e,
+                        "Failed to catch up from peers, falling back to state sync"
+                    );
+                }
+            }
+        } else {
+            counters::CATCH_UP_COUNT
+                .with_label_values(&["state_sync"])
+                .inc();
+        }
         let (root, root_metadata, blocks, quorum_certs) = Self::fast_forward_sync(
             &highest_ordered_cert,
             highest_ledger_info.clone(),
@@ -199,6 +240,48 @@ impl BlockStore {
         Ok(())
     }
 
+    /// Catch up to the highest ordered cert without state sync:
+    /// 1. request the blocks from the highest ordered cert back to our ordered root from peers
+    /// 2. execute and insert them along with their quorum certs, oldest first, as if they had
+    /// been proposed to us
+    /// 3. order them with the highest ordered cert, and fast forward the commit of the
+    /// decoupled-execution pipeline with the highest ledger info
+    /// Unlike state sync, the block tree is kept and the gap costs one round trip per
+    /// MAX_BLOCKS_PER_REQUEST blocks. If it fails midway, the blocks inserted so far are replaced
+    /// by the state sync that follows.
+    async fn catch_up_to_ordered_cert(
+        &self,
+        highest_ordered_cert: &QuorumCert,
+        highest_ledger_info: &LedgerInfoWithSignatures,
+        retriever: &mut BlockRetriever,
+    ) -> anyhow::Result<()> {
+        let ordered_root = self.ordered_root();
+        debug!(
+            LogSchema::new(LogEvent::CatchUp)
+                .remote_peer(retriever.preferred_peer)
+                .round(ordered_root.round()),
+            "Catch up with peer to block: {}",
+            highest_ordered_cert.certified_block(),
+        );
+
+        // rounds only increase along the chain, so there are at most as many blocks as rounds
+        let num_blocks = highest_ordered_cert.certified_block().round() - ordered_root.round() + 1;
+        let blocks = retriever
+            .retrieve_block_for_qc(highest_ordered_cert, num_blocks, ordered_root.id())
+            .await?;
+
+        // the last block is the ordered root
+        for block in blocks.into_iter().rev().skip(1) {
+            self.insert_single_quorum_cert(block.quorum_cert().clone())?;
+            self.execute_and_insert_block(block).await?;
+        }
+        self.insert_quorum_cert(highest_ordered_cert, retriever)
+            .await?;
+        self.sync_to_highest_commit_cert(highest_ledger_info, &retriever.network)
+            .await;
+        Ok(())
+    }
+
     pub async fn fast_forward_sync<'a>(
         highest_ordered_cert: &'a QuorumCert,
         highest_ledger_info: LedgerInfoWithSignatures,
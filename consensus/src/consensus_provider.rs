@@ -43,7 +43,6 @@ pub fn start_consensus(
     let storage = Arc::new(StorageWriteProxy::new(node_config, aptos_db.reader.clone()));
     let txn_manager = Arc::new(MempoolProxy::new(
         consensus_to_mempool_sender,
-        node_config.consensus.mempool_poll_count,
         node_config.consensus.mempool_txn_pull_timeout_ms,
         node_config.consensus.mempool_executed_txn_timeout_ms,
     ));
@@ -3,8 +3,8 @@
 
 use aptos_metrics::{
     register_histogram, register_histogram_vec, register_int_counter, register_int_counter_vec,
-    register_int_gauge, DurationHistogram, Histogram, HistogramVec, IntCounter, IntCounterVec,
-    IntGauge,
+    register_int_gauge, register_int_gauge_vec, DurationHistogram, Histogram, HistogramVec,
+    IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
 };
 use once_cell::sync::Lazy;
 
@@ -80,6 +80,16 @@ pub static VOTE_NIL_COUNT: Lazy<IntCounter> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Count the number of votes rejected since last restart because their author already voted for
+/// something else in the same round, which only a faulty or malicious validator does.
+pub static EQUIVOCATING_VOTES_COUNT: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_consensus_equivocating_votes_count",
+        "Count the number of votes rejected because their author voted for something else in the same round."
+    )
+    .unwrap()
+});
+
 /// Committed proposals from this validator when using LeaderReputation as the ProposerElection
 pub static COMMITTED_PROPOSALS_IN_WINDOW: Lazy<IntGauge> = Lazy::new(|| {
     register_int_gauge!(
@@ -166,6 +176,17 @@ pub static SYNC_INFO_MSGS_SENT_COUNT: Lazy<IntCounter> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Counts the times a node fell behind its peers by more than the back pressure limit, by whether
+/// it caught up with the blocks retrieved from them or fell back to state sync.
+pub static CATCH_UP_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_consensus_catch_up_count",
+        "Counts the times a node fell behind its peers, by how it caught up",
+        &["result"]
+    )
+    .unwrap()
+});
+
 //////////////////////
 // RECONFIGURATION COUNTERS
 //////////////////////
@@ -182,6 +203,29 @@ pub static CURRENT_EPOCH_VALIDATORS: Lazy<IntGauge> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Histogram of the duration of each phase of an epoch change, from syncing to the ledger info
+/// ending the previous epoch to starting the round manager of the new one.
+pub static EPOCH_CHANGE_PHASE_DURATION_S: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "aptos_consensus_epoch_change_phase_duration_s",
+        "Histogram of the duration of each phase of an epoch change",
+        &["phase"]
+    )
+    .unwrap()
+});
+
+/// Histogram of the time from the start of the round manager of a new epoch to the first proposal
+/// it processes.
+pub static EPOCH_FIRST_PROPOSAL_LATENCY_S: Lazy<DurationHistogram> = Lazy::new(|| {
+    DurationHistogram::new(
+        register_histogram!(
+            "aptos_consensus_epoch_first_proposal_latency_s",
+            "Histogram of the time from the start of a new epoch to its first proposal"
+        )
+        .unwrap(),
+    )
+});
+
 //////////////////////
 // BLOCK STORE COUNTERS
 //////////////////////
@@ -216,6 +260,29 @@ pub static NUM_TXNS_PER_BLOCK: Lazy<Histogram> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Histogram of how full proposed blocks are relative to the block budget, between 0 and 1, for
+/// the number of txns and their size in bytes.
+pub static PROPOSED_BLOCK_FILL_RATE: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "aptos_consensus_proposed_block_fill_rate",
+        "Histogram of how full proposed blocks are relative to the block budget",
+        &["budget"],
+        vec![0.0, 0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 0.95, 1.0]
+    )
+    .unwrap()
+});
+
+/// Block budget of the current epoch, taken from the on-chain consensus config or the local
+/// config.
+pub static BLOCK_BUDGET: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "aptos_consensus_block_budget",
+        "Block budget of the current epoch",
+        &["budget"]
+    )
+    .unwrap()
+});
+
 pub static BLOCK_TRACING: Lazy<HistogramVec> = Lazy::new(|| {
     register_histogram_vec!(
         "aptos_consensus_block_tracing",
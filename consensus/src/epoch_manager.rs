@@ -25,13 +25,14 @@ use crate::{
     persistent_liveness_storage::{LedgerRecoveryData, PersistentLivenessStorage, RecoveryData},
     round_manager::{RoundManager, UnverifiedEvent, VerifiedEvent},
     state_replication::{StateComputer, TxnManager},
+    txn_manager::NO_TXN_DELAY,
     util::time_service::TimeService,
 };
 use anyhow::{bail, ensure, Context};
 use aptos_config::config::{ConsensusConfig, ConsensusProposerType, NodeConfig};
 use aptos_infallible::{duration_since_epoch, Mutex};
 use aptos_logger::prelude::*;
-use aptos_metrics::monitor;
+use aptos_metrics::{monitor, HistogramTimer};
 use aptos_types::{
     account_address::AccountAddress,
     epoch_change::EpochChangeProof,
@@ -288,6 +289,7 @@ impl EpochManager {
         );
 
         // make sure storage is on this ledger_info too, it should be no-op if it's already committed
+        let sync_timer = epoch_change_phase_timer("sync_to");
         self.commit_state_computer
             .sync_to(ledger_info.clone())
             .await
@@ -295,6 +297,7 @@ impl EpochManager {
                 "[EpochManager] State sync to new epoch {}",
                 ledger_info
             ))?;
+        sync_timer.observe_duration();
 
         monitor!("reconfig", self.await_reconfig_notification().await);
         Ok(())
@@ -437,9 +440,41 @@ impl EpochManager {
             self.config.max_pruned_blocks_in_mem,
             Arc::clone(&self.time_service),
             onchain_config.back_pressure_limit(),
+            self.config.max_catch_up_rounds,
         ));
 
         info!(epoch = epoch, "Create ProposalGenerator");
+        // The block budget set on chain takes precedence, so it can be tuned without a release.
+        let max_block_size = onchain_config
+            .max_block_txns()
+            .unwrap_or(self.config.max_block_size);
+        let max_block_bytes = onchain_config
+            .max_block_bytes()
+            .unwrap_or(self.config.max_block_bytes);
+        let max_pull_latency = onchain_config.max_pull_latency().unwrap_or_else(|| {
+            Duration::from_millis(
+                self.config
+                    .mempool_poll_count
+                    .saturating_sub(1)
+                    .saturating_mul(NO_TXN_DELAY),
+            )
+        });
+        info!(
+            epoch = epoch,
+            max_block_size = max_block_size,
+            max_block_bytes = max_block_bytes,
+            max_pull_latency_ms = max_pull_latency.as_millis() as u64,
+            "Block budget"
+        );
+        counters::BLOCK_BUDGET
+            .with_label_values(&["txns"])
+            .set(max_block_size as i64);
+        counters::BLOCK_BUDGET
+            .with_label_values(&["bytes"])
+            .set(max_block_bytes as i64);
+        counters::BLOCK_BUDGET
+            .with_label_values(&["pull_latency_ms"])
+            .set(max_pull_latency.as_millis() as i64);
         // txn manager is required both by proposal generator (to pull the proposers)
         // and by event processor (to update their status).
         let proposal_generator = ProposalGenerator::new(
@@ -447,7 +482,9 @@ impl EpochManager {
             block_store.clone(),
             self.txn_manager.clone(),
             self.time_service.clone(),
-            self.config.max_block_size,
+            max_block_size,
+            max_block_bytes,
+            max_pull_latency,
         );
 
         let mut round_manager = RoundManager::new(
@@ -477,21 +514,31 @@ impl EpochManager {
         let validator_set: ValidatorSet = payload
             .get()
             .expect("failed to get ValidatorSet from payload");
+        let verifier_timer = epoch_change_phase_timer("validator_verifier");
         let epoch_state = EpochState {
             epoch: payload.epoch(),
             verifier: (&validator_set).into(),
         };
+        verifier_timer.observe_duration();
+
+        let shutdown_timer = epoch_change_phase_timer("shutdown");
         self.shutdown_current_processor().await;
+        shutdown_timer.observe_duration();
 
         let onchain_config: OnChainConsensusConfig = payload.get().unwrap_or_default();
         self.epoch_state = Some(epoch_state.clone());
 
+        let recovery_timer = epoch_change_phase_timer("recovery");
         let initial_data = self
             .storage
             .start()
             .expect_recovery_data("Consensusdb is corrupted, need to do a backup and restore");
+        recovery_timer.observe_duration();
+
+        let start_timer = epoch_change_phase_timer("start_round_manager");
         self.start_round_manager(initial_data, epoch_state, onchain_config)
             .await;
+        start_timer.observe_duration();
     }
 
     async fn process_message(
@@ -664,3 +711,10 @@ impl EpochManager {
         }
     }
 }
+
+/// Times a phase of the epoch change, observed when the timer is dropped or stopped
+fn epoch_change_phase_timer(phase: &str) -> HistogramTimer {
+    counters::EPOCH_CHANGE_PHASE_DURATION_S
+        .with_label_values(&[phase])
+        .start_timer()
+}
@@ -2,19 +2,20 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    block_storage::BlockReader, state_replication::TxnManager, util::time_service::TimeService,
+    block_storage::BlockReader, counters, state_replication::TxnManager,
+    util::time_service::TimeService,
 };
 use anyhow::{bail, ensure, format_err, Context};
 use consensus_types::{
     block::Block,
     block_data::BlockData,
-    common::{Author, Round},
+    common::{Author, Payload, Round},
     quorum_cert::QuorumCert,
 };
 
 use aptos_infallible::Mutex;
 use futures::future::BoxFuture;
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 #[cfg(test)]
 #[path = "proposal_generator_test.rs"]
@@ -41,6 +42,10 @@ pub struct ProposalGenerator {
     time_service: Arc<dyn TimeService>,
     // Max number of transactions to be added to a proposed block.
     max_block_size: u64,
+    // Max total size in bytes of the transactions added to a proposed block.
+    max_block_bytes: u64,
+    // Max time to wait for transactions from mempool before proposing an empty block.
+    max_pull_latency: Duration,
     // Last round that a proposal was generated
     last_round_generated: Mutex<Round>,
 }
@@ -52,6 +57,8 @@ impl ProposalGenerator {
         txn_manager: Arc<dyn TxnManager>,
         time_service: Arc<dyn TimeService>,
         max_block_size: u64,
+        max_block_bytes: u64,
+        max_pull_latency: Duration,
     ) -> Self {
         Self {
             author,
@@ -59,6 +66,8 @@ impl ProposalGenerator {
             txn_manager,
             time_service,
             max_block_size,
+            max_block_bytes,
+            max_pull_latency,
             last_round_generated: Mutex::new(0),
         }
     }
@@ -137,6 +146,7 @@ impl ProposalGenerator {
                 .txn_manager
                 .pull_txns(
                     self.max_block_size,
+                    self.max_pull_latency,
                     exclude_payload,
                     wait_callback,
                     pending_ordering,
@@ -144,7 +154,7 @@ impl ProposalGenerator {
                 .await
                 .context("Fail to retrieve txn")?;
 
-            (payload, timestamp.as_micros() as u64)
+            (self.fit_to_budget(payload), timestamp.as_micros() as u64)
         };
 
         // create block proposal
@@ -157,6 +167,31 @@ impl ProposalGenerator {
         ))
     }
 
+    /// Drops the transactions past the size budget of the block, and records how full the block
+    /// is. The first transaction is always kept, so an oversized transaction can't stall
+    /// proposals.
+    fn fit_to_budget(&self, mut payload: Payload) -> Payload {
+        let mut num_bytes = 0;
+        let mut num_txns = 0;
+        for txn in &payload {
+            let txn_bytes = txn.raw_txn_bytes_len() as u64;
+            if num_txns > 0 && num_bytes + txn_bytes > self.max_block_bytes {
+                break;
+            }
+            num_bytes += txn_bytes;
+            num_txns += 1;
+        }
+        payload.truncate(num_txns);
+
+        counters::PROPOSED_BLOCK_FILL_RATE
+            .with_label_values(&["txns"])
+            .observe(num_txns as f64 / self.max_block_size.max(1) as f64);
+        counters::PROPOSED_BLOCK_FILL_RATE
+            .with_label_values(&["bytes"])
+            .observe(num_bytes as f64 / self.max_block_bytes.max(1) as f64);
+        payload
+    }
+
     fn ensure_highest_quorum_cert(&self, round: Round) -> anyhow::Result<Arc<QuorumCert>> {
         let hqc = self.block_store.highest_quorum_cert();
         ensure!(
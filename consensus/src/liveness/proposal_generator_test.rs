@@ -10,7 +10,7 @@ use crate::{
 use aptos_types::validator_signer::ValidatorSigner;
 use consensus_types::block::{block_test_utils::certificate_for_genesis, Block};
 use futures::{future::BoxFuture, FutureExt};
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 fn empty_callback() -> BoxFuture<'static, ()> {
     async move {}.boxed()
@@ -26,6 +26,8 @@ async fn test_proposal_generation_empty_tree() {
         Arc::new(MockTransactionManager::new(None)),
         Arc::new(SimulatedTimeService::new()),
         1,
+        u64::MAX,
+        Duration::ZERO,
     );
     let genesis = block_store.ordered_root();
 
@@ -57,6 +59,8 @@ async fn test_proposal_generation_parent() {
         Arc::new(MockTransactionManager::new(None)),
         Arc::new(SimulatedTimeService::new()),
         1,
+        u64::MAX,
+        Duration::ZERO,
     );
     let genesis = block_store.ordered_root();
     let a1 = inserter
@@ -108,6 +112,8 @@ async fn test_old_proposal_generation() {
         Arc::new(MockTransactionManager::new(None)),
         Arc::new(SimulatedTimeService::new()),
         1,
+        u64::MAX,
+        Duration::ZERO,
     );
     let genesis = block_store.ordered_root();
     let a1 = inserter
@@ -121,3 +127,25 @@ async fn test_old_proposal_generation() {
         .err();
     assert!(proposal_err.is_some());
 }
+
+#[tokio::test]
+async fn test_proposal_generation_bytes_budget() {
+    let signer = ValidatorSigner::random(None);
+    let block_store = build_empty_tree();
+    let mut proposal_generator = ProposalGenerator::new(
+        signer.author(),
+        block_store,
+        Arc::new(MockTransactionManager::new(None)),
+        Arc::new(SimulatedTimeService::new()),
+        10,
+        1,
+        Duration::ZERO,
+    );
+
+    // The first txn is kept even if it alone exceeds the budget.
+    let proposal_data = proposal_generator
+        .generate_proposal(1, empty_callback())
+        .await
+        .unwrap();
+    assert_eq!(proposal_data.payload().unwrap().len(), 1);
+}
@@ -16,6 +16,7 @@ pub struct LogSchema {
 
 #[derive(Serialize)]
 pub enum LogEvent {
+    CatchUp,
     CommitViaBlock,
     CommitViaSync,
     HelpPeerSync,
@@ -7,6 +7,7 @@
 //! when enough votes (or timeout votes) have been observed.
 //! Votes are automatically dropped when the structure goes out of scope.
 
+use crate::counters;
 use aptos_crypto::{hash::CryptoHash, HashValue};
 use aptos_logger::prelude::*;
 use aptos_types::{
@@ -103,6 +104,7 @@ impl PendingVotes {
                     vote = vote,
                     previous_vote = previously_seen_vote
                 );
+                counters::EQUIVOCATING_VOTES_COUNT.inc();
 
                 return VoteReceptionResult::EquivocateVote;
             }
@@ -262,6 +264,7 @@ impl fmt::Display for PendingVotes {
 #[cfg(test)]
 mod tests {
     use super::{PendingVotes, VoteReceptionResult};
+    use crate::counters;
     use aptos_crypto::HashValue;
     use aptos_types::{
         block_info::BlockInfo, ledger_info::LedgerInfo,
@@ -453,4 +456,65 @@ mod tests {
             }
         };
     }
+
+    #[test]
+    /// Verify that the second vote of an author for the round is rejected as an equivocation,
+    /// counted, and left out of the certificates
+    fn test_equivocating_vote() {
+        ::aptos_logger::Logger::init_for_testing();
+
+        // set up 4 validators
+        let (signers, validator) = random_validator_verifier(4, Some(2), false);
+        let mut pending_votes = PendingVotes::new();
+
+        let li1 = random_ledger_info();
+        let vote_data_1 = random_vote_data();
+        let vote_1_author_0 = Vote::new(
+            vote_data_1.clone(),
+            signers[0].author(),
+            li1.clone(),
+            &signers[0],
+        );
+        assert_eq!(
+            pending_votes.insert_vote(&vote_1_author_0, &validator),
+            VoteReceptionResult::VoteAdded(1)
+        );
+
+        // the same author voting for another ledger info -> EquivocateVote, counted
+        let equivocations = counters::EQUIVOCATING_VOTES_COUNT.get();
+        let li2 = random_ledger_info();
+        let vote_data_2 = random_vote_data();
+        let vote_2_author_0 = Vote::new(
+            vote_data_2.clone(),
+            signers[0].author(),
+            li2.clone(),
+            &signers[0],
+        );
+        assert_eq!(
+            pending_votes.insert_vote(&vote_2_author_0, &validator),
+            VoteReceptionResult::EquivocateVote
+        );
+        // other tests may equivocate concurrently, the counter is global
+        assert!(counters::EQUIVOCATING_VOTES_COUNT.get() > equivocations);
+
+        // the equivocating vote doesn't count towards the other ledger info
+        let vote_2_author_1 = Vote::new(vote_data_2, signers[1].author(), li2, &signers[1]);
+        assert_eq!(
+            pending_votes.insert_vote(&vote_2_author_1, &validator),
+            VoteReceptionResult::VoteAdded(1)
+        );
+
+        // nor does it replace the first vote, which still forms a QC
+        let vote_1_author_2 = Vote::new(vote_data_1, signers[2].author(), li1, &signers[2]);
+        match pending_votes.insert_vote(&vote_1_author_2, &validator) {
+            VoteReceptionResult::NewQuorumCertificate(qc) => {
+                let signers = qc.ledger_info().signatures();
+                assert!(signers.contains_key(&vote_1_author_0.author()));
+                assert!(signers.contains_key(&vote_1_author_2.author()));
+            }
+            _ => {
+                panic!("No QC formed.");
+            }
+        };
+    }
 }
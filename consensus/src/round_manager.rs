@@ -48,7 +48,11 @@ use futures::{channel::oneshot, FutureExt, StreamExt};
 use safety_rules::ConsensusState;
 use safety_rules::TSafetyRules;
 use serde::Serialize;
-use std::{mem::Discriminant, sync::Arc, time::Duration};
+use std::{
+    mem::Discriminant,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use termion::color::*;
 
 #[derive(Serialize, Clone)]
@@ -146,6 +150,8 @@ pub struct RoundManager {
     storage: Arc<dyn PersistentLivenessStorage>,
     sync_only: bool,
     onchain_config: OnChainConsensusConfig,
+    /// When the round manager started, until it processes the first proposal of the epoch
+    epoch_start: Option<Instant>,
 }
 
 impl RoundManager {
@@ -183,6 +189,7 @@ impl RoundManager {
             storage,
             sync_only,
             onchain_config,
+            epoch_start: Some(Instant::now()),
         }
     }
 
@@ -553,6 +560,9 @@ impl RoundManager {
         );
 
         observe_block(proposal.timestamp_usecs(), BlockStage::SYNCED);
+        if let Some(epoch_start) = self.epoch_start.take() {
+            counters::EPOCH_FIRST_PROPOSAL_LATENCY_S.observe_duration(epoch_start.elapsed());
+        }
 
         let proposal_round = proposal.round();
         let vote = self
@@ -72,6 +72,7 @@ fn build_empty_store(
         10, // max pruned blocks in mem
         Arc::new(SimulatedTimeService::new()),
         10,
+        0,
     ))
 }
 
@@ -145,6 +146,8 @@ fn create_node_for_fuzzing() -> RoundManager {
         Arc::new(MockTransactionManager::new(None)),
         time_service,
         1,
+        u64::MAX,
+        Duration::ZERO,
     );
 
     //
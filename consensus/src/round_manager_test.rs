@@ -196,6 +196,7 @@ impl NodeSetup {
             10, // max pruned blocks in mem
             time_service.clone(),
             10,
+            0,
         ));
 
         let proposal_generator = ProposalGenerator::new(
@@ -204,6 +205,8 @@ impl NodeSetup {
             Arc::new(MockTransactionManager::new(None)),
             time_service.clone(),
             1,
+            u64::MAX,
+            Duration::ZERO,
         );
 
         let round_state = Self::create_round_state(time_service);
@@ -8,7 +8,7 @@ use aptos_types::{epoch_state::EpochState, ledger_info::LedgerInfoWithSignatures
 use consensus_types::{block::Block, common::Payload, executed_block::ExecutedBlock};
 use executor_types::{Error as ExecutionError, StateComputeResult};
 use futures::future::BoxFuture;
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 pub type StateComputerCommitCallBackType =
     Box<dyn FnOnce(&[Arc<ExecutedBlock>], LedgerInfoWithSignatures) + Send + Sync>;
@@ -22,9 +22,11 @@ pub trait TxnManager: Send + Sync {
     ///
     /// wait_callback is executed when there's no transactions available and it decides to wait.
     /// pending_ordering indicates if we should long poll mempool or propose empty blocks to help commit pending txns
+    /// max_poll_time bounds how long to long poll mempool for.
     async fn pull_txns(
         &self,
         max_size: u64,
+        max_poll_time: Duration,
         exclude: Vec<&Payload>,
         wait_callback: BoxFuture<'static, ()>,
         pending_ordering: bool,
@@ -15,6 +15,7 @@ use consensus_types::{
 use executor_types::StateComputeResult;
 use futures::{channel::mpsc, future::BoxFuture};
 use rand::Rng;
+use std::time::Duration;
 
 pub struct MockTransactionManager {
     // used non-mocked TxnManager to test interaction with shared mempool
@@ -23,7 +24,7 @@ pub struct MockTransactionManager {
 
 impl MockTransactionManager {
     pub fn new(consensus_to_mempool_sender: Option<mpsc::Sender<ConsensusRequest>>) -> Self {
-        let mempool_proxy = consensus_to_mempool_sender.map(|s| MempoolProxy::new(s, 1, 1, 1));
+        let mempool_proxy = consensus_to_mempool_sender.map(|s| MempoolProxy::new(s, 1, 1));
         Self { mempool_proxy }
     }
 }
@@ -48,6 +49,7 @@ impl TxnManager for MockTransactionManager {
     async fn pull_txns(
         &self,
         _max_size: u64,
+        _max_poll_time: Duration,
         _exclude_txns: Vec<&Payload>,
         _callback: BoxFuture<'static, ()>,
         _pending_ordering: bool,
@@ -66,6 +66,11 @@ pub async fn build_simple_tree() -> (Vec<Arc<ExecutedBlock>>, Arc<BlockStore>) {
 }
 
 pub fn build_empty_tree() -> Arc<BlockStore> {
+    build_empty_tree_with_catch_up(0)
+}
+
+/// An empty tree whose block store catches up gaps of up to `max_catch_up_rounds` from peers
+pub fn build_empty_tree_with_catch_up(max_catch_up_rounds: Round) -> Arc<BlockStore> {
     let (initial_data, storage) = EmptyStorage::start_for_testing();
     Arc::new(BlockStore::new(
         storage,
@@ -74,6 +79,7 @@ pub fn build_empty_tree() -> Arc<BlockStore> {
         10, // max pruned blocks in mem
         Arc::new(SimulatedTimeService::new()),
         10,
+        max_catch_up_rounds,
     ))
 }
 
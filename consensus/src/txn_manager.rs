@@ -18,13 +18,13 @@ use itertools::Itertools;
 use std::time::Duration;
 use tokio::time::{sleep, timeout};
 
-const NO_TXN_DELAY: u64 = 30;
+/// Delay between polls of mempool while it has no txns, in milliseconds
+pub(crate) const NO_TXN_DELAY: u64 = 30;
 
 /// Proxy interface to mempool
 #[derive(Clone)]
 pub struct MempoolProxy {
     consensus_to_mempool_sender: mpsc::Sender<ConsensusRequest>,
-    poll_count: u64,
     /// Timeout for consensus to get an ack from mempool for executed transactions (in milliseconds)
     mempool_executed_txn_timeout_ms: u64,
     /// Timeout for consensus to pull transactions from mempool and get a response (in milliseconds)
@@ -34,17 +34,11 @@ pub struct MempoolProxy {
 impl MempoolProxy {
     pub fn new(
         consensus_to_mempool_sender: mpsc::Sender<ConsensusRequest>,
-        poll_count: u64,
         mempool_txn_pull_timeout_ms: u64,
         mempool_executed_txn_timeout_ms: u64,
     ) -> Self {
-        assert!(
-            poll_count > 0,
-            "poll_count = 0 won't pull any txns from mempool"
-        );
         Self {
             consensus_to_mempool_sender,
-            poll_count,
             mempool_executed_txn_timeout_ms,
             mempool_txn_pull_timeout_ms,
         }
@@ -89,6 +83,7 @@ impl TxnManager for MempoolProxy {
     async fn pull_txns(
         &self,
         max_size: u64,
+        max_poll_time: Duration,
         exclude_payloads: Vec<&Payload>,
         wait_callback: BoxFuture<'static, ()>,
         pending_ordering: bool,
@@ -107,7 +102,8 @@ impl TxnManager for MempoolProxy {
         }
         let mut callback_wrapper = Some(wait_callback);
         // keep polling mempool until there's txn available or there's still pending txns
-        let mut count = self.poll_count;
+        let poll_count = max_poll_time.as_millis() as u64 / NO_TXN_DELAY + 1;
+        let mut count = poll_count;
         let txns = loop {
             count -= 1;
             let txns = self.pull_internal(max_size, exclude_txns.clone()).await?;
@@ -120,10 +116,7 @@ impl TxnManager for MempoolProxy {
             }
             break txns;
         };
-        debug!(
-            poll_count = self.poll_count - count,
-            "Pull txn from mempool"
-        );
+        debug!(poll_count = poll_count - count, "Pull txn from mempool");
         Ok(txns)
     }
 
@@ -313,6 +313,9 @@ mod tests {
             epoch: 1,
             ledger_version: 5.into(),
             ledger_timestamp: 5.into(),
+            // as a node's, whose ledger info always ends with a state checkpoint
+            latest_state_checkpoint_version: Some(5.into()),
+            oldest_ledger_version: None,
         };
         Response::new(li, body).unwrap().into_response()
     }
@@ -351,6 +351,28 @@ impl AptosData {
         self.filter.write().remote_filter = filter;
     }
 
+    /// Updates the local filter with a directives string, keeping the directives of the modules
+    /// it doesn't mention, until the node restarts
+    pub fn update_filter(&self, filters: &str) {
+        self.filter.write().local_filter.update(filters);
+    }
+
+    /// Updates the remote filter with a directives string, keeping the directives of the modules
+    /// it doesn't mention, until the node restarts
+    pub fn update_remote_filter(&self, filters: &str) {
+        self.filter.write().remote_filter.update(filters);
+    }
+
+    /// Returns the local filter as a directives string
+    pub fn filter(&self) -> String {
+        self.filter.read().local_filter.to_string()
+    }
+
+    /// Returns the remote filter as a directives string
+    pub fn remote_filter(&self) -> String {
+        self.filter.read().remote_filter.to_string()
+    }
+
     fn send_entry(&self, entry: LogEntry) {
         if let Some(printer) = &self.printer {
             let s = (self.formatter)(&entry).expect("Unable to format");
@@ -4,7 +4,7 @@
 //! Filtering definitions for controlling what modules and levels are logged
 
 use crate::{Level, Metadata};
-use std::{env, str::FromStr};
+use std::{env, fmt, str::FromStr};
 
 pub struct FilterParseError;
 
@@ -40,6 +40,20 @@ impl FromStr for LevelFilter {
     }
 }
 
+impl fmt::Display for LevelFilter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let level = match self {
+            LevelFilter::Off => "off",
+            LevelFilter::Error => "error",
+            LevelFilter::Warn => "warn",
+            LevelFilter::Info => "info",
+            LevelFilter::Debug => "debug",
+            LevelFilter::Trace => "trace",
+        };
+        f.pad(level)
+    }
+}
+
 impl From<Level> for LevelFilter {
     fn from(level: Level) -> Self {
         match level {
@@ -107,13 +121,7 @@ impl Builder {
             // Add the default filter if none exist
             self.filter_level(LevelFilter::Error);
         } else {
-            // Sort the directives by length of their name, this allows a
-            // little more efficient lookup at runtime.
-            self.directives.sort_by(|a, b| {
-                let alen = a.name.as_ref().map(|a| a.len()).unwrap_or(0);
-                let blen = b.name.as_ref().map(|b| b.len()).unwrap_or(0);
-                alen.cmp(&blen)
-            });
+            sort_directives(&mut self.directives);
         }
 
         Filter {
@@ -122,6 +130,16 @@ impl Builder {
     }
 }
 
+/// Sorts the directives by length of their name, this allows a little more efficient lookup at
+/// runtime.
+fn sort_directives(directives: &mut [Directive]) {
+    directives.sort_by(|a, b| {
+        let alen = a.name.as_ref().map(|a| a.len()).unwrap_or(0);
+        let blen = b.name.as_ref().map(|b| b.len()).unwrap_or(0);
+        alen.cmp(&blen)
+    });
+}
+
 /// A logging filter to determine which logs to keep or remove based on `Directive`s
 #[derive(Debug)]
 pub struct Filter {
@@ -133,6 +151,22 @@ impl Filter {
         Builder::new()
     }
 
+    /// Merges a directives string into the filter, e.g. `state_sync=debug`. Each directive
+    /// replaces the one of the filter for the same module (or the one without a module), the
+    /// directives for other modules are kept.
+    pub fn update(&mut self, filters: &str) {
+        let directives = filters
+            .split(',')
+            .map(Directive::from_str)
+            .filter_map(Result::ok);
+        for directive in directives {
+            self.directives
+                .retain(|existing| existing.name != directive.name);
+            self.directives.push(directive);
+        }
+        sort_directives(&mut self.directives);
+    }
+
     pub fn enabled(&self, metadata: &Metadata) -> bool {
         // Search for the longest match, the vector is assumed to be pre-sorted.
         for directive in self.directives.iter().rev() {
@@ -145,6 +179,22 @@ impl Filter {
     }
 }
 
+/// Formats the filter as a directives string, which parses back into the same filter
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, directive) in self.directives.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            match &directive.name {
+                Some(name) => write!(f, "{}={}", name, directive.level)?,
+                None => write!(f, "{}", directive.level)?,
+            }
+        }
+        Ok(())
+    }
+}
+
 /// A `Filter` directive for which logs to keep based on a module `name` based filter
 #[derive(Debug)]
 struct Directive {
@@ -339,6 +389,24 @@ mod tests {
         assert_eq!(dirs[0].level, LevelFilter::max());
     }
 
+    #[test]
+    fn update_replaces_module_directives() {
+        let mut logger = Builder::new().parse("info,crate1=warn,crate2=info").build();
+        logger.update("crate1=debug,crate3=trace");
+        assert!(logger.enabled(&make_metadata(Level::Debug, "crate1::mod1")));
+        assert!(!logger.enabled(&make_metadata(Level::Debug, "crate2")));
+        assert!(logger.enabled(&make_metadata(Level::Trace, "crate3")));
+        assert!(!logger.enabled(&make_metadata(Level::Debug, "crate4")));
+
+        logger.update("error");
+        assert!(!logger.enabled(&make_metadata(Level::Info, "crate4")));
+        assert!(logger.enabled(&make_metadata(Level::Debug, "crate1::mod1")));
+        assert_eq!(
+            logger.to_string(),
+            "error,crate2=info,crate1=debug,crate3=trace"
+        );
+    }
+
     #[test]
     fn parse_global() {
         // test parse with no crate
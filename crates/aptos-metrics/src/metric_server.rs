@@ -26,6 +26,7 @@ use std::{
 use tokio::runtime;
 
 fn encode_metrics(encoder: impl Encoder, whitelist: &'static [&'static str]) -> Vec<u8> {
+    refresh_system_metrics();
     let mut metric_families = gather_metrics();
     if !whitelist.is_empty() {
         metric_families = whitelist_metrics(metric_families, whitelist);
@@ -82,6 +83,7 @@ fn get_metrics(fams: Vec<MetricFamily>) -> HashMap<String, String> {
 }
 
 pub fn get_all_metrics() -> HashMap<String, String> {
+    refresh_system_metrics();
     let all_metric_families = gather_metrics();
     get_metrics(all_metric_families)
 }
@@ -3,7 +3,11 @@
 
 use aptos_metrics_core::{register_int_gauge_vec, IntGaugeVec};
 use once_cell::sync::Lazy;
-use sysinfo::{System, SystemExt};
+use std::sync::Mutex;
+use sysinfo::{get_current_pid, ProcessExt, System, SystemExt};
+
+/// Kept across refreshes, as the CPU usage of the process is measured between two refreshes
+static SYSTEM: Lazy<Mutex<System>> = Lazy::new(|| Mutex::new(System::new()));
 
 static TOTAL_MEMORY_GAUGE: Lazy<IntGaugeVec> = Lazy::new(|| {
     register_int_gauge_vec!("system_total_memory", "Total system memory", &[]).unwrap()
@@ -16,8 +20,26 @@ static PHYSICAL_CORE_COUNT_GAUGE: Lazy<IntGaugeVec> = Lazy::new(|| {
     register_int_gauge_vec!("system_physical_core_count", "Physical CPU cores", &[]).unwrap()
 });
 
+static PROCESS_MEMORY_GAUGE: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "system_process_memory",
+        "Memory used by the process, in KB",
+        &[]
+    )
+    .unwrap()
+});
+
+static PROCESS_CPU_USAGE_GAUGE: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "system_process_cpu_usage",
+        "CPU used by the process since the previous refresh, in percent of a core",
+        &[]
+    )
+    .unwrap()
+});
+
 pub fn refresh_system_metrics() {
-    let mut sys = System::new();
+    let mut sys = SYSTEM.lock().unwrap();
     sys.refresh_system();
 
     TOTAL_MEMORY_GAUGE
@@ -32,4 +54,17 @@ pub fn refresh_system_metrics() {
             .with_label_values(&[])
             .set(physical_core_count as i64);
     }
+
+    if let Ok(pid) = get_current_pid() {
+        if sys.refresh_process(pid) {
+            if let Some(process) = sys.process(pid) {
+                PROCESS_MEMORY_GAUGE
+                    .with_label_values(&[])
+                    .set(process.memory() as i64);
+                PROCESS_CPU_USAGE_GAUGE
+                    .with_label_values(&[])
+                    .set(process.cpu_usage() as i64);
+            }
+        }
+    }
 }
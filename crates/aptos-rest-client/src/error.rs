@@ -53,6 +53,11 @@ impl Error {
         matches!(self.inner.kind, Kind::NeedSync)
     }
 
+    /// Whether the node is of another chain than the client expects
+    pub fn is_chain_id_mismatch(&self) -> bool {
+        matches!(self.inner.kind, Kind::ChainId)
+    }
+
     //
     // Private Constructors
     //
@@ -1,19 +1,26 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::{anyhow, Result};
-use aptos_api_types::mime_types::BCS_SIGNED_TRANSACTION as BCS_CONTENT_TYPE;
+use anyhow::{anyhow, ensure, Context, Result};
 pub use aptos_api_types::{self, MoveModuleBytecode, PendingTransaction, Transaction};
+use aptos_api_types::{
+    mime_types::BCS_SIGNED_TRANSACTION as BCS_CONTENT_TYPE, BlockTransactions, StateProofData, U64,
+    X_REQUEST_ID,
+};
 use aptos_crypto::HashValue;
 use aptos_types::{
-    account_address::AccountAddress, account_config::aptos_root_address,
-    transaction::SignedTransaction,
+    account_address::AccountAddress, account_config::aptos_root_address, chain_id::ChainId,
+    proof::TransactionAccumulatorSummary, state_proof::StateProof, transaction::SignedTransaction,
+    trusted_state::TrustedState, waypoint::Waypoint,
 };
 use reqwest::{header::CONTENT_TYPE, Client as ReqwestClient, StatusCode};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::{json, Value};
 use state::State;
-use std::time::Duration;
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
 use url::Url;
 
 pub mod error;
@@ -33,6 +40,14 @@ const USER_AGENT: &str = concat!("aptos-client-sdk-rust / ", env!("CARGO_PKG_VER
 pub struct Client {
     inner: ReqwestClient,
     base_url: Url,
+    /// When set, the ledger info of every response is checked against this state, which is
+    /// ratcheted forward with verified state proofs. Shared between clones of the client.
+    trusted_state: Option<Arc<RwLock<TrustedState>>>,
+    /// The chain of the node, pinned by the first response unless set with
+    /// [`Client::with_chain_id`]. Responses from another chain are rejected with a
+    /// [`error::Error`] of [`error::Error::is_chain_id_mismatch`]. Shared between clones of the
+    /// client.
+    chain_id: Arc<RwLock<Option<u8>>>,
 }
 
 impl Client {
@@ -44,7 +59,51 @@ impl Client {
             .build()
             .unwrap();
 
-        Self { inner, base_url }
+        Self {
+            inner,
+            base_url,
+            trusted_state: None,
+            chain_id: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    pub fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
+    /// Only accepts responses from nodes of `chain_id`, rather than of the chain of the first
+    /// response, and only submits transactions signed for it
+    pub fn with_chain_id(mut self, chain_id: ChainId) -> Self {
+        self.chain_id = Arc::new(RwLock::new(Some(chain_id.id())));
+        self
+    }
+
+    /// Turns on response verification: ledger infos returned by the node are only accepted once
+    /// they are covered by a state proof verified starting from `waypoint`, instead of trusting
+    /// the node blindly.
+    pub fn with_waypoint(self, waypoint: Waypoint) -> Self {
+        self.with_trusted_state(TrustedState::from_epoch_waypoint(waypoint))
+    }
+
+    pub fn with_trusted_state(mut self, trusted_state: TrustedState) -> Self {
+        self.trusted_state = Some(Arc::new(RwLock::new(trusted_state)));
+        self
+    }
+
+    /// Returns the latest verified state, if response verification is turned on.
+    pub fn trusted_state(&self) -> Option<TrustedState> {
+        self.trusted_state
+            .as_ref()
+            .map(|trusted_state| trusted_state.read().unwrap().clone())
+    }
+
+    /// Returns the chain of the node, asking the node if no response pinned it yet
+    pub async fn get_chain_id(&self) -> Result<ChainId> {
+        if let Some(chain_id) = *self.chain_id.read().unwrap() {
+            return Ok(ChainId::new(chain_id));
+        }
+        let state = self.get_ledger_information().await?.into_inner();
+        Ok(ChainId::new(state.chain_id))
     }
 
     pub async fn get_aptos_version(&self) -> Result<Response<AptosVersion>> {
@@ -74,6 +133,10 @@ impl Client {
             ledger_version: u64,
             #[serde(deserialize_with = "types::deserialize_from_string")]
             ledger_timestamp: u64,
+            #[serde(default)]
+            latest_state_checkpoint_version: Option<U64>,
+            #[serde(default)]
+            oldest_ledger_version: Option<U64>,
         }
 
         let response = self.inner.get(self.base_url.clone()).send().await?;
@@ -83,12 +146,18 @@ impl Client {
             epoch: r.epoch,
             version: r.ledger_version,
             timestamp_usecs: r.ledger_timestamp,
+            latest_state_checkpoint_version: r.latest_state_checkpoint_version.map(u64::from),
+            oldest_ledger_version: r.oldest_ledger_version.map(u64::from),
         });
 
         Ok(response)
     }
 
     pub async fn submit(&self, txn: &SignedTransaction) -> Result<Response<PendingTransaction>> {
+        let chain_id = self.get_chain_id().await?;
+        if txn.chain_id() != chain_id {
+            return Err(error::Error::chain_id(chain_id.id(), txn.chain_id().id()).into());
+        }
         let txn_payload = bcs::to_bytes(txn)?;
         let url = self.base_url.join("transactions")?;
 
@@ -103,8 +172,37 @@ impl Client {
         self.json(response).await
     }
 
+    /// Submits the transaction, retrying after the delay asked by the node as long as it pushes
+    /// back on submissions, e.g. while its mempool is near capacity. Errors other than
+    /// backpressure are returned right away; use [`RestError::retry_after`] on the error to
+    /// handle backpressure with [`Client::submit`] instead.
+    pub async fn submit_with_backoff(
+        &self,
+        txn: &SignedTransaction,
+    ) -> Result<Response<PendingTransaction>> {
+        const MAX_ATTEMPTS: usize = 5;
+
+        let mut attempt = 1;
+        loop {
+            let error = match self.submit(txn).await {
+                Ok(response) => return Ok(response),
+                Err(error) => error,
+            };
+            let retry_after = error
+                .downcast_ref::<RestError>()
+                .and_then(RestError::retry_after);
+            match retry_after {
+                Some(delay) if attempt < MAX_ATTEMPTS => {
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                _ => return Err(error),
+            }
+        }
+    }
+
     pub async fn submit_and_wait(&self, txn: &SignedTransaction) -> Result<Response<Transaction>> {
-        self.submit(txn).await?;
+        self.submit_with_backoff(txn).await?;
         self.wait_for_signed_transaction(txn).await
     }
 
@@ -192,6 +290,29 @@ impl Client {
         self.json(response).await
     }
 
+    /// Like `get_transactions`, except that genesis transactions come with their whole write set
+    /// rather than a summary of it
+    pub async fn get_full_transactions(
+        &self,
+        start: Option<u64>,
+        limit: Option<u64>,
+    ) -> Result<Response<Vec<Transaction>>> {
+        let url = self.base_url.join("transactions")?;
+
+        let mut request = self.inner.get(url).query(&[("full", true)]);
+        if let Some(start) = start {
+            request = request.query(&[("start", start)])
+        }
+
+        if let Some(limit) = limit {
+            request = request.query(&[("limit", limit)])
+        }
+
+        let response = request.send().await?;
+
+        self.json(response).await
+    }
+
     pub async fn get_transaction(&self, hash: HashValue) -> Result<Response<Transaction>> {
         self.json(
             self.get_transaction_by_version_or_hash(hash.to_hex_literal())
@@ -208,6 +329,38 @@ impl Client {
         .await
     }
 
+    /// Like `get_transaction_by_version`, except that a genesis transaction comes with its whole
+    /// write set rather than a summary of it
+    pub async fn get_full_transaction_by_version(
+        &self,
+        version: u64,
+    ) -> Result<Response<Transaction>> {
+        let url = self.base_url.join(&format!("transactions/{}", version))?;
+        let response = self.inner.get(url).query(&[("full", true)]).send().await?;
+
+        self.json(response).await
+    }
+
+    /// All the transactions of the block at `height`, with the proof of their inclusion against
+    /// the ledger info of the response. The boundaries of the block aren't proven.
+    pub async fn get_block_transactions(
+        &self,
+        height: u64,
+        with_events: bool,
+    ) -> Result<Response<BlockTransactions>> {
+        let url = self
+            .base_url
+            .join(&format!("blocks/{}/transactions", height))?;
+        let response = self
+            .inner
+            .get(url)
+            .query(&[("with_events", with_events)])
+            .send()
+            .await?;
+
+        self.json(response).await
+    }
+
     async fn get_transaction_by_version_or_hash(
         &self,
         version_or_hash: String,
@@ -327,19 +480,105 @@ impl Client {
         self.json(response).await
     }
 
+    pub async fn get_state_proof(&self, known_version: u64) -> Result<Response<StateProof>> {
+        let (state_proof, _, state) = self.fetch_state_proof(known_version, false).await?;
+        Ok(Response::new(state_proof, state))
+    }
+
+    /// Fetches a state proof without verifying the response, as it is what verification is
+    /// built on.
+    async fn fetch_state_proof(
+        &self,
+        known_version: u64,
+        with_accumulator: bool,
+    ) -> Result<(StateProof, Option<TransactionAccumulatorSummary>, State)> {
+        let url = self.base_url.join("state_proof")?;
+        let response = self
+            .inner
+            .get(url)
+            .query(&[("known_version", known_version.to_string())])
+            .query(&[("with_accumulator", with_accumulator.to_string())])
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            let error_response = Self::rest_error(response).await?;
+            return Err(anyhow!("Request failed: {:?}", error_response));
+        }
+        let state = State::from_headers(response.headers())?;
+        let data: StateProofData = response.json().await?;
+
+        let state_proof: StateProof = bcs::from_bytes(data.state_proof.inner())?;
+        let accumulator_summary = data
+            .accumulator_summary
+            .map(|summary| bcs::from_bytes(summary.inner()))
+            .transpose()?;
+        Ok((state_proof, accumulator_summary, state))
+    }
+
+    /// Ratchets the trusted state forward until it covers `state`, failing if the node cannot
+    /// prove the ledger info it returned.
+    async fn verify_state(
+        &self,
+        trusted_state: &RwLock<TrustedState>,
+        state: &State,
+    ) -> Result<()> {
+        loop {
+            let current = trusted_state.read().unwrap().clone();
+            if state.version <= current.version() {
+                return Ok(());
+            }
+
+            let (state_proof, accumulator_summary, proof_state) = self
+                .fetch_state_proof(current.version(), current.need_accumulator())
+                .await?;
+            let new_state = ratchet_trusted_state(
+                &current,
+                state,
+                &state_proof,
+                accumulator_summary.as_ref(),
+                &proof_state,
+            )?;
+
+            // Another request may have ratcheted further in the meantime
+            let mut trusted = trusted_state.write().unwrap();
+            if new_state.version() > trusted.version() {
+                *trusted = new_state;
+            }
+        }
+    }
+
     async fn check_response(
         &self,
         response: reqwest::Response,
     ) -> Result<(reqwest::Response, State)> {
         if !response.status().is_success() {
-            let error_response = response.json::<RestError>().await?;
-            return Err(anyhow::anyhow!("Request failed: {:?}", error_response));
+            let error_response = Self::rest_error(response).await?;
+            return Err(error_response.into());
         }
         let state = State::from_headers(response.headers())?;
+        self.check_chain_id(state.chain_id)?;
+        if let Some(trusted_state) = &self.trusted_state {
+            self.verify_state(trusted_state, &state).await?;
+        }
 
         Ok((response, state))
     }
 
+    /// Pins the chain of the first response, then rejects responses from any other chain
+    fn check_chain_id(&self, chain_id: u8) -> Result<()> {
+        let mut pinned = self.chain_id.write().unwrap();
+        match *pinned {
+            Some(expected) if expected != chain_id => {
+                Err(error::Error::chain_id(expected, chain_id).into())
+            }
+            Some(_) => Ok(()),
+            None => {
+                *pinned = Some(chain_id);
+                Ok(())
+            }
+        }
+    }
+
     async fn json<T: serde::de::DeserializeOwned>(
         &self,
         response: reqwest::Response,
@@ -349,6 +588,62 @@ impl Client {
         Ok(Response::new(json, state))
     }
 
+    /// Configures a failpoint of the node, e.g. `api::endpoint_index` with `return`. Requires the
+    /// node to enable the admin routes with `failpoints_admin_token` in its API config.
+    pub async fn set_failpoint(&self, name: &str, actions: &str, admin_token: &str) -> Result<()> {
+        let url = self.base_url.join("admin/failpoints")?;
+        let response = self
+            .inner
+            .post(url)
+            .bearer_auth(admin_token)
+            .json(&json!({ "name": name, "actions": actions }))
+            .send()
+            .await?;
+        Self::check_admin_response(response).await
+    }
+
+    /// Removes a failpoint configured with [`Client::set_failpoint`].
+    pub async fn remove_failpoint(&self, name: &str, admin_token: &str) -> Result<()> {
+        let url = self.base_url.join(&format!("admin/failpoints/{}", name))?;
+        let response = self
+            .inner
+            .delete(url)
+            .bearer_auth(admin_token)
+            .send()
+            .await?;
+        Self::check_admin_response(response).await
+    }
+
+    /// The error of a failed request, with the id the node logged the request under, so that the
+    /// failure can be found in the logs of the node
+    async fn rest_error(response: reqwest::Response) -> Result<RestError> {
+        let request_id = response
+            .headers()
+            .get(X_REQUEST_ID)
+            .and_then(|id| id.to_str().ok())
+            .map(str::to_owned);
+        let status = response.status();
+        let error = response.json::<RestError>().await.with_context(|| {
+            format!(
+                "Failed to decode the error ({}) of request {}",
+                status,
+                request_id.as_deref().unwrap_or("without id")
+            )
+        })?;
+        Ok(RestError {
+            request_id: error.request_id.or(request_id),
+            ..error
+        })
+    }
+
+    async fn check_admin_response(response: reqwest::Response) -> Result<()> {
+        if !response.status().is_success() {
+            let error_response = Self::rest_error(response).await?;
+            return Err(anyhow!("Request failed: {:?}", error_response));
+        }
+        Ok(())
+    }
+
     pub async fn health_check(&self, seconds: u64) -> Result<()> {
         let url = self.base_url.join("-/healthy")?;
         let response = self
@@ -365,3 +660,326 @@ impl Client {
         Ok(())
     }
 }
+
+/// Verifies `state_proof` against `trusted_state` and returns the trusted state it ratchets to.
+/// `proof_state` are the headers the node returned along with the proof, and `state` those of the
+/// response being verified, which must agree with the ledger info the proof verifies once it
+/// covers their version.
+fn ratchet_trusted_state(
+    trusted_state: &TrustedState,
+    state: &State,
+    state_proof: &StateProof,
+    accumulator_summary: Option<&TransactionAccumulatorSummary>,
+    proof_state: &State,
+) -> Result<TrustedState> {
+    let latest_li = state_proof.latest_ledger_info();
+    ensure!(
+        proof_state.version == latest_li.version()
+            && proof_state.epoch == latest_li.epoch()
+            && proof_state.timestamp_usecs == latest_li.timestamp_usecs(),
+        "ledger info in state proof does not match response headers: {:?}",
+        proof_state
+    );
+    // Verifying from a waypoint needs the accumulator the node was asked for
+    ensure!(
+        !trusted_state.need_accumulator() || accumulator_summary.is_some(),
+        "state proof is missing the accumulator summary"
+    );
+    let change = trusted_state
+        .verify_and_ratchet(state_proof, accumulator_summary)
+        .map_err(|e| anyhow!("failed to verify state proof: {}", e))?;
+    let new_state = change.new_state().ok_or_else(|| {
+        anyhow!(
+            "ledger version {} in response is ahead of verified version {}",
+            state.version,
+            trusted_state.version()
+        )
+    })?;
+
+    // Past an epoch change, the proof may only verify up to the end of an older epoch, and the
+    // next proof picks up from there
+    if new_state.version() == latest_li.version() && state.version <= latest_li.version() {
+        ensure!(
+            state.epoch <= latest_li.epoch(),
+            "epoch {} in response is ahead of verified epoch {}",
+            state.epoch,
+            latest_li.epoch()
+        );
+        ensure!(
+            state.version < latest_li.version()
+                || (state.epoch == latest_li.epoch()
+                    && state.timestamp_usecs == latest_li.timestamp_usecs()),
+            "ledger info in response does not match the verified one: {:?}",
+            state
+        );
+    }
+    Ok(new_state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_crypto::{ed25519::Ed25519PrivateKey, hash::TransactionAccumulatorHasher};
+    use aptos_types::{
+        block_info::BlockInfo,
+        epoch_change::EpochChangeProof,
+        epoch_state::EpochState,
+        ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+        proof::{accumulator::InMemoryAccumulator, AccumulatorConsistencyProof},
+        validator_signer::ValidatorSigner,
+        validator_verifier::ValidatorVerifier,
+    };
+    use std::{collections::BTreeMap, convert::TryFrom};
+
+    /// A ledger of a single validator, with one transaction per version and the genesis ending
+    /// epoch 0
+    struct TestLedger {
+        signer: ValidatorSigner,
+        leaves: Vec<HashValue>,
+    }
+
+    impl TestLedger {
+        fn new() -> Self {
+            let signer = validator_signer(1);
+            let leaves = (0..3u8).map(|i| HashValue::sha3_256_of(&[i])).collect();
+            Self { signer, leaves }
+        }
+
+        fn accumulator(&self, version: u64) -> InMemoryAccumulator<TransactionAccumulatorHasher> {
+            InMemoryAccumulator::from_leaves(&self.leaves[..=version as usize])
+        }
+
+        fn genesis(&self) -> LedgerInfoWithSignatures {
+            let epoch_state = EpochState {
+                epoch: 1,
+                verifier: ValidatorVerifier::new_single(
+                    self.signer.author(),
+                    self.signer.public_key(),
+                ),
+            };
+            let block_info = BlockInfo::new(
+                0,
+                0,
+                HashValue::zero(),
+                self.accumulator(0).root_hash(),
+                0,
+                0,
+                Some(epoch_state),
+            );
+            LedgerInfoWithSignatures::new(
+                LedgerInfo::new(block_info, HashValue::zero()),
+                BTreeMap::new(),
+            )
+        }
+
+        /// The ledger info at `version` in epoch 1, signed by `signer`
+        fn ledger_info(&self, version: u64, signer: &ValidatorSigner) -> LedgerInfoWithSignatures {
+            let block_info = BlockInfo::new(
+                1,
+                version,
+                HashValue::zero(),
+                self.accumulator(version).root_hash(),
+                version,
+                version * 1_000,
+                None,
+            );
+            let ledger_info = LedgerInfo::new(block_info, HashValue::zero());
+            let mut signatures = BTreeMap::new();
+            signatures.insert(signer.author(), signer.sign(&ledger_info));
+            LedgerInfoWithSignatures::new(ledger_info, signatures)
+        }
+
+        fn waypoint(&self) -> TrustedState {
+            let waypoint = Waypoint::new_epoch_boundary(self.genesis().ledger_info()).unwrap();
+            TrustedState::from_epoch_waypoint(waypoint)
+        }
+
+        /// The proof of the genesis from the waypoint, with the accumulator it asks for
+        fn genesis_proof(&self) -> (StateProof, TransactionAccumulatorSummary) {
+            let state_proof = StateProof::new(
+                self.genesis(),
+                EpochChangeProof::new(vec![self.genesis()], false),
+                AccumulatorConsistencyProof::new(vec![]),
+            );
+            let accumulator = TransactionAccumulatorSummary::new(self.accumulator(0)).unwrap();
+            (state_proof, accumulator)
+        }
+
+        /// The proof of version 1 from the genesis
+        fn proof(&self, latest_li: LedgerInfoWithSignatures) -> StateProof {
+            StateProof::new(
+                latest_li,
+                EpochChangeProof::new(vec![], false),
+                AccumulatorConsistencyProof::new(vec![self.leaves[1]]),
+            )
+        }
+    }
+
+    fn validator_signer(seed: u8) -> ValidatorSigner {
+        let private_key = Ed25519PrivateKey::try_from(&[seed; 32][..]).unwrap();
+        ValidatorSigner::new(
+            AccountAddress::new([seed; AccountAddress::LENGTH]),
+            private_key,
+        )
+    }
+
+    fn state(ledger_info: &LedgerInfo) -> State {
+        State {
+            chain_id: 4,
+            epoch: ledger_info.epoch(),
+            version: ledger_info.version(),
+            timestamp_usecs: ledger_info.timestamp_usecs(),
+            latest_state_checkpoint_version: None,
+            oldest_ledger_version: None,
+        }
+    }
+
+    #[test]
+    fn test_ratchet_from_waypoint() {
+        let ledger = TestLedger::new();
+        let (genesis_proof, accumulator) = ledger.genesis_proof();
+        let genesis_state = state(genesis_proof.latest_ledger_info());
+        let trusted_state = ratchet_trusted_state(
+            &ledger.waypoint(),
+            &genesis_state,
+            &genesis_proof,
+            Some(&accumulator),
+            &genesis_state,
+        )
+        .unwrap();
+        assert_eq!(trusted_state.version(), 0);
+        assert!(!trusted_state.need_accumulator());
+
+        let latest_li = ledger.ledger_info(1, &ledger.signer);
+        let latest_state = state(latest_li.ledger_info());
+        let trusted_state = ratchet_trusted_state(
+            &trusted_state,
+            &latest_state,
+            &ledger.proof(latest_li),
+            None,
+            &latest_state,
+        )
+        .unwrap();
+        assert_eq!(trusted_state.version(), 1);
+    }
+
+    #[test]
+    fn test_tampered_proof_rejected() {
+        let ledger = TestLedger::new();
+
+        // the node leaves out the accumulator the waypoint needs
+        let (genesis_proof, accumulator) = ledger.genesis_proof();
+        let genesis_state = state(genesis_proof.latest_ledger_info());
+        assert!(ratchet_trusted_state(
+            &ledger.waypoint(),
+            &genesis_state,
+            &genesis_proof,
+            None,
+            &genesis_state,
+        )
+        .is_err());
+        let trusted_state = ratchet_trusted_state(
+            &ledger.waypoint(),
+            &genesis_state,
+            &genesis_proof,
+            Some(&accumulator),
+            &genesis_state,
+        )
+        .unwrap();
+
+        // a ledger info signed by a validator outside the validator set
+        let forged_li = ledger.ledger_info(1, &validator_signer(2));
+        let forged_state = state(forged_li.ledger_info());
+        let error = ratchet_trusted_state(
+            &trusted_state,
+            &forged_state,
+            &ledger.proof(forged_li),
+            None,
+            &forged_state,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("failed to verify state proof"));
+
+        // a ledger info changed after it was signed
+        let latest_li = ledger.ledger_info(1, &ledger.signer);
+        let signed = latest_li.ledger_info().commit_info();
+        let block_info = BlockInfo::new(
+            signed.epoch(),
+            signed.round(),
+            signed.id(),
+            signed.executed_state_id(),
+            signed.version(),
+            signed.timestamp_usecs() + 1,
+            None,
+        );
+        let tampered_li = LedgerInfoWithSignatures::new(
+            LedgerInfo::new(block_info, HashValue::zero()),
+            latest_li.signatures().clone(),
+        );
+        let tampered_state = state(tampered_li.ledger_info());
+        assert!(ratchet_trusted_state(
+            &trusted_state,
+            &tampered_state,
+            &ledger.proof(tampered_li),
+            None,
+            &tampered_state,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_tampered_headers_rejected() {
+        let ledger = TestLedger::new();
+        let (genesis_proof, accumulator) = ledger.genesis_proof();
+        let genesis_state = state(genesis_proof.latest_ledger_info());
+        let trusted_state = ratchet_trusted_state(
+            &ledger.waypoint(),
+            &genesis_state,
+            &genesis_proof,
+            Some(&accumulator),
+            &genesis_state,
+        )
+        .unwrap();
+        let latest_li = ledger.ledger_info(1, &ledger.signer);
+        let latest_state = state(latest_li.ledger_info());
+        let ratchet = |state: &State, proof_state: &State| {
+            ratchet_trusted_state(
+                &trusted_state,
+                state,
+                &ledger.proof(latest_li.clone()),
+                None,
+                proof_state,
+            )
+        };
+
+        // headers of the proof response which don't match its ledger info
+        let mut proof_state = latest_state.clone();
+        proof_state.timestamp_usecs += 1;
+        assert!(ratchet(&latest_state, &proof_state).is_err());
+
+        // a response claiming an epoch or a timestamp the proof doesn't cover
+        let mut state = latest_state.clone();
+        state.epoch = 2;
+        assert!(ratchet(&state, &latest_state).is_err());
+        let mut state = latest_state.clone();
+        state.timestamp_usecs -= 1;
+        assert!(ratchet(&state, &latest_state).is_err());
+
+        // an older response is covered by the proof
+        assert!(ratchet(&genesis_state, &latest_state).is_ok());
+        let trusted_state = ratchet(&latest_state, &latest_state).unwrap();
+
+        // a response claiming a version past the latest one the node proves
+        let mut state = latest_state.clone();
+        state.version = 2;
+        let error = ratchet_trusted_state(
+            &trusted_state,
+            &state,
+            &ledger.proof(latest_li.clone()),
+            None,
+            &latest_state,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("ahead of verified version"));
+    }
+}
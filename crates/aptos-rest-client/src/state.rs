@@ -2,7 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_api_types::{
-    X_APTOS_CHAIN_ID, X_APTOS_EPOCH, X_APTOS_LEDGER_TIMESTAMP, X_APTOS_LEDGER_VERSION,
+    X_APTOS_CHAIN_ID, X_APTOS_EPOCH, X_APTOS_LATEST_STATE_CHECKPOINT_VERSION,
+    X_APTOS_LEDGER_TIMESTAMP, X_APTOS_LEDGER_VERSION, X_APTOS_OLDEST_LEDGER_VERSION,
 };
 
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
@@ -11,6 +12,10 @@ pub struct State {
     pub epoch: u64,
     pub version: u64,
     pub timestamp_usecs: u64,
+    /// Latest version which is safe to finalize, if the server reports it
+    pub latest_state_checkpoint_version: Option<u64>,
+    /// Oldest version the server serves, older ones being pruned, if the server reports it
+    pub oldest_ledger_version: Option<u64>,
 }
 
 impl State {
@@ -31,6 +36,14 @@ impl State {
             .get(X_APTOS_EPOCH)
             .and_then(|h| h.to_str().ok())
             .and_then(|s| s.parse().ok());
+        let latest_state_checkpoint_version = headers
+            .get(X_APTOS_LATEST_STATE_CHECKPOINT_VERSION)
+            .and_then(|h| h.to_str().ok())
+            .and_then(|s| s.parse().ok());
+        let oldest_ledger_version = headers
+            .get(X_APTOS_OLDEST_LEDGER_VERSION)
+            .and_then(|h| h.to_str().ok())
+            .and_then(|s| s.parse().ok());
 
         let state = if let (Some(chain_id), Some(version), Some(timestamp_usecs), Some(epoch)) =
             (maybe_chain_id, maybe_version, maybe_timestamp, maybe_epoch)
@@ -40,6 +53,8 @@ impl State {
                 epoch,
                 version,
                 timestamp_usecs,
+                latest_state_checkpoint_version,
+                oldest_ledger_version,
             }
         } else {
             todo!()
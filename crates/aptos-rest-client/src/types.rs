@@ -1,19 +1,55 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use aptos_api_types::{Address, U64};
+use aptos_api_types::{Address, ErrorCode, MempoolQueue, U64};
 use aptos_types::transaction::authenticator::AuthenticationKey;
 use move_deps::move_core_types::{language_storage::StructTag, parser::parse_struct_tag};
 use serde::{Deserialize, Deserializer, Serialize};
-use std::str::FromStr;
+use std::{fmt, str::FromStr, time::Duration};
 
 #[derive(Clone, Debug, PartialEq, Deserialize)]
 pub struct RestError {
     pub code: u32,
+    /// Machine readable code of the error, missing from the errors of older nodes
+    #[serde(default)]
+    pub error_code: Option<ErrorCode>,
     pub message: String,
     pub aptos_ledger_version: Option<U64>,
+    pub retry_after_secs: Option<U64>,
+    pub mempool_queue: Option<MempoolQueue>,
+    #[serde(default)]
+    pub retriable: bool,
+    /// Id under which the node logged the request, missing from the errors of older nodes
+    #[serde(default)]
+    pub request_id: Option<String>,
 }
 
+impl RestError {
+    /// Whether the node pushed back on the request because it is overloaded, e.g. a transaction
+    /// submission while its mempool is near capacity
+    pub fn is_backpressure(&self) -> bool {
+        self.code == 429
+    }
+
+    /// How long the node asked to wait before retrying, if it pushed back on the request
+    pub fn retry_after(&self) -> Option<Duration> {
+        if self.is_backpressure() {
+            self.retry_after_secs
+                .map(|secs| Duration::from_secs(secs.0))
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for RestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Request failed: {:?}", self)
+    }
+}
+
+impl std::error::Error for RestError {}
+
 #[derive(Clone, Debug, PartialEq, Deserialize)]
 pub struct Resource {
     #[serde(rename = "type", deserialize_with = "deserialize_resource_type")]
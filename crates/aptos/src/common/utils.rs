@@ -7,7 +7,10 @@ use crate::{
 };
 use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey};
 use aptos_rest_client::{Client, Transaction};
-use aptos_sdk::{transaction_builder::TransactionFactory, types::LocalAccount};
+use aptos_sdk::{
+    transaction_builder::{transaction_summary, TransactionFactory},
+    types::LocalAccount,
+};
 use aptos_telemetry::constants::APTOS_CLI_PUSH_METRICS;
 use aptos_types::{
     chain_id::ChainId,
@@ -279,6 +282,9 @@ pub async fn submit_transaction(
     // Get sequence number for account
     let sequence_number = get_sequence_number(&client, sender_address).await?;
 
+    // Show what is about to be signed
+    eprint!("{}", transaction_summary::summarize(&payload));
+
     // Sign and submit transaction
     let transaction_factory = TransactionFactory::new(chain_id)
         .with_gas_unit_price(1)
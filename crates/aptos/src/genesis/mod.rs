@@ -72,6 +72,8 @@ impl GenerateGenesis {
             genesis_info.root_key.clone(),
             &genesis_info.validators,
             &genesis_info.modules,
+            &vm_genesis::InitialState::default(),
+            &vm_genesis::GenesisConfiguration::default(),
             genesis_info.chain_id,
             MIN_PRICE_PER_GAS_UNIT,
         ))
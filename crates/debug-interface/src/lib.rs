@@ -2,11 +2,97 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anyhow::Result;
+use aptos_config::{config::NodeConfig, network_id::NetworkId};
+use aptos_types::{network_address::NetworkAddress, PeerId};
+use data_streaming_service::data_stream::DataStreamSummary;
 use reqwest::{blocking, Url};
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs, io,
+    path::{Path, PathBuf},
+};
 
 pub mod node_debug_service;
 
+/// A peer the node is connected to, as reported by `GET /peers`
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ConnectedPeer {
+    pub network_id: NetworkId,
+    pub peer_id: PeerId,
+    pub address: NetworkAddress,
+    /// Whether the peer dialed the node, rather than the other way around
+    pub inbound: bool,
+}
+
+/// The logging filters of the node as directives strings, as reported by `GET /log`
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct LogFilters {
+    pub filter: String,
+    pub remote_filter: String,
+}
+
+/// The git revision the node was built at and the config it runs with, as reported by
+/// `GET /node-info`
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct NodeInfo {
+    #[serde(default)]
+    pub node_config: NodeConfig,
+    #[serde(default)]
+    pub git_revision: String,
+}
+
+/// Disk usage of the data directory of the node, as reported by `GET /storage`
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StorageUsage {
+    pub data_dir: PathBuf,
+    /// Size in bytes of each entry at the top of the data directory, e.g. `db` for AptosDB
+    pub entries: BTreeMap<String, u64>,
+    pub total_bytes: u64,
+}
+
+impl StorageUsage {
+    /// Measures the data directory, which is empty while the node hasn't created it yet
+    pub fn of(data_dir: &Path) -> io::Result<Self> {
+        let mut entries = BTreeMap::new();
+        if data_dir.exists() {
+            for entry in fs::read_dir(data_dir)? {
+                let entry = entry?;
+                let name = entry.file_name().to_string_lossy().into_owned();
+                entries.insert(name, size_of(&entry.path())?);
+            }
+        }
+        Ok(Self {
+            data_dir: data_dir.to_path_buf(),
+            total_bytes: entries.values().sum(),
+            entries,
+        })
+    }
+}
+
+/// Size of the file, or of all the files under the directory, at `path`. Files the node removes
+/// while they're measured, e.g. when the DB compacts, are skipped.
+fn size_of(path: &Path) -> io::Result<u64> {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+    if !metadata.is_dir() {
+        return Ok(metadata.len());
+    }
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+    let mut size = 0;
+    for entry in entries {
+        size += size_of(&entry?.path())?;
+    }
+    Ok(size)
+}
+
 /// Implement default utility client for NodeDebugInterface
 pub struct NodeDebugClient {
     client: blocking::Client,
@@ -78,6 +164,55 @@ impl NodeDebugClient {
             })
             .collect()
     }
+
+    /// Retrieves the peers the node is connected to, on all of its networks.
+    pub fn get_connected_peers(&self) -> Result<Vec<ConnectedPeer>> {
+        let mut url = self.url.clone();
+        url.set_path("peers");
+        let response = self.client.get(url).send()?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Error querying peers: {}", response.status());
+        }
+        Ok(response.json()?)
+    }
+
+    /// Retrieves the active data streams of state sync and their progress.
+    pub fn get_data_streams(&self) -> Result<Vec<DataStreamSummary>> {
+        let mut url = self.url.clone();
+        url.set_path("state-sync/streams");
+        let response = self.client.get(url).send()?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Error querying data streams: {}", response.status());
+        }
+        Ok(response.json()?)
+    }
+
+    /// Retrieves the local and remote logging filters of the node.
+    pub fn get_log_filters(&self) -> Result<LogFilters> {
+        let mut url = self.url.clone();
+        url.set_path("log");
+        let response = self.client.get(url).send()?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Error querying log filters: {}", response.status());
+        }
+        Ok(response.json()?)
+    }
+
+    /// Updates the local logging filter with directives, e.g. `state_sync=debug`, keeping the
+    /// directives of the other modules. The update lasts until the node restarts.
+    pub fn update_log_filter(&self, filters: &str) -> Result<()> {
+        let mut url = self.url.clone();
+        url.set_path("log/filter");
+        let response = self.client.patch(url).body(filters.to_owned()).send()?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Error updating log filter: {}", response.status());
+        }
+        Ok(())
+    }
 }
 
 /// Implement default utility client for AsyncNodeDebugInterface
@@ -153,4 +288,64 @@ impl AsyncNodeDebugClient {
             })
             .collect()
     }
+
+    /// Retrieves the peers the node is connected to, on all of its networks.
+    pub async fn get_connected_peers(&self) -> Result<Vec<ConnectedPeer>> {
+        let mut url = self.url.clone();
+        url.set_path("peers");
+        let response = self.client.get(url).send().await?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Error querying peers: {}", response.status());
+        }
+        Ok(response.json().await?)
+    }
+
+    /// Retrieves the active data streams of state sync and their progress.
+    pub async fn get_data_streams(&self) -> Result<Vec<DataStreamSummary>> {
+        let mut url = self.url.clone();
+        url.set_path("state-sync/streams");
+        let response = self.client.get(url).send().await?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Error querying data streams: {}", response.status());
+        }
+        Ok(response.json().await?)
+    }
+
+    /// Retrieves all node metrics, with their values as the node formats them.
+    pub async fn get_raw_node_metrics(&self) -> Result<HashMap<String, String>> {
+        let mut url = self.url.clone();
+        url.set_path("metrics");
+        let response = self.client.get(url).send().await?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Error querying metrics: {}", response.status());
+        }
+        Ok(response.json().await?)
+    }
+
+    /// Retrieves the git revision of the node and the config it runs with.
+    pub async fn get_node_info(&self) -> Result<NodeInfo> {
+        let mut url = self.url.clone();
+        url.set_path("node-info");
+        let response = self.client.get(url).send().await?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Error querying node info: {}", response.status());
+        }
+        Ok(response.json().await?)
+    }
+
+    /// Retrieves the disk usage of the data directory of the node.
+    pub async fn get_storage_usage(&self) -> Result<StorageUsage> {
+        let mut url = self.url.clone();
+        url.set_path("storage");
+        let response = self.client.get(url).send().await?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Error querying storage usage: {}", response.status());
+        }
+        Ok(response.json().await?)
+    }
 }
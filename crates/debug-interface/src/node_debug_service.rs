@@ -3,29 +3,30 @@
 
 //! Debug interface to access information in a specific node.
 
+use crate::{ConnectedPeer, LogFilters, NodeInfo, StorageUsage};
 use aptos_config::config::NodeConfig;
 use aptos_logger::{info, Filter, Logger};
 use aptos_metrics::json_metrics::get_git_rev;
-use serde::{Deserialize, Serialize};
+use data_streaming_service::data_stream::ActiveDataStreams;
+use netcore::transport::ConnectionOrigin;
+use network::application::{storage::PeerMetadataStorage, types::PeerState};
 use std::{net::SocketAddr, sync::Arc};
 use tokio::runtime::{Builder, Runtime};
-use warp::Filter as _;
+use warp::{http::StatusCode, Filter as _};
 
 #[derive(Debug)]
 pub struct NodeDebugService {
     runtime: Runtime,
 }
 
-#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
-struct NodeInfo {
-    #[serde(default)]
-    node_config: NodeConfig,
-    #[serde(default)]
-    git_revision: String,
-}
-
 impl NodeDebugService {
-    pub fn new(address: SocketAddr, logger: Option<Arc<Logger>>, node_config: &NodeConfig) -> Self {
+    pub fn new(
+        address: SocketAddr,
+        logger: Option<Arc<Logger>>,
+        node_config: &NodeConfig,
+        peer_metadata_storage: Arc<PeerMetadataStorage>,
+        active_data_streams: ActiveDataStreams,
+    ) -> Self {
         let runtime = Builder::new_multi_thread()
             .thread_name("nodedebug")
             .enable_all()
@@ -55,24 +56,80 @@ impl NodeDebugService {
         };
 
         // Post /log/remote-filter
-        let remote_filter = warp::path("remote-filter")
-            // 16kb should be long enough for a filter
-            .and(warp::body::content_length_limit(1024 * 16))
-            .and(warp::body::bytes())
-            .map(move |bytes: bytes::Bytes| {
-                if let (Some(logger), Ok(filter)) = (&logger, ::std::str::from_utf8(&bytes)) {
-                    info!(filter = filter, "Updating remote logging filter");
-                    logger.set_remote_filter(Filter::builder().parse(filter).build());
-                }
-
-                warp::reply::reply()
-            });
+        let remote_filter = {
+            let logger = logger.clone();
+
+            warp::path("remote-filter")
+                // 16kb should be long enough for a filter
+                .and(warp::body::content_length_limit(1024 * 16))
+                .and(warp::body::bytes())
+                .map(move |bytes: bytes::Bytes| {
+                    if let (Some(logger), Ok(filter)) = (&logger, ::std::str::from_utf8(&bytes)) {
+                        info!(filter = filter, "Updating remote logging filter");
+                        logger.set_remote_filter(Filter::builder().parse(filter).build());
+                    }
+
+                    warp::reply::reply()
+                })
+        };
 
         // Post /log
         let log = warp::post()
             .and(warp::path("log"))
             .and(local_filter.or(remote_filter));
 
+        // Patch /log/filter (only replaces the directives of the modules in the body, e.g.
+        // `state_sync=debug`, until the node restarts)
+        let update_local_filter = {
+            let logger = logger.clone();
+
+            warp::path("filter")
+                .and(warp::body::content_length_limit(1024 * 16))
+                .and(warp::body::bytes())
+                .map(move |bytes: bytes::Bytes| {
+                    if let (Some(logger), Ok(filter)) = (&logger, ::std::str::from_utf8(&bytes)) {
+                        info!(filter = filter, "Updating local logging filter directives");
+                        logger.update_filter(filter);
+                    }
+
+                    warp::reply::reply()
+                })
+        };
+
+        // Patch /log/remote-filter
+        let update_remote_filter = {
+            let logger = logger.clone();
+
+            warp::path("remote-filter")
+                .and(warp::body::content_length_limit(1024 * 16))
+                .and(warp::body::bytes())
+                .map(move |bytes: bytes::Bytes| {
+                    if let (Some(logger), Ok(filter)) = (&logger, ::std::str::from_utf8(&bytes)) {
+                        info!(filter = filter, "Updating remote logging filter directives");
+                        logger.update_remote_filter(filter);
+                    }
+
+                    warp::reply::reply()
+                })
+        };
+
+        // Patch /log
+        let update_log = warp::patch()
+            .and(warp::path("log"))
+            .and(update_local_filter.or(update_remote_filter));
+
+        // Get /log (the current logging filters)
+        let log_filters = warp::path!("log").map(move || {
+            let filters = logger
+                .as_ref()
+                .map(|logger| LogFilters {
+                    filter: logger.filter(),
+                    remote_filter: logger.remote_filter(),
+                })
+                .unwrap_or_default();
+            warp::reply::json(&filters)
+        });
+
         // Get /node-info (git revision the node was built at and the node config being used)
         let node_info = NodeInfo {
             git_revision: get_git_rev(),
@@ -80,7 +137,32 @@ impl NodeDebugService {
         };
         let node_info_route = warp::path("node-info").map(move || warp::reply::json(&node_info));
 
-        let routes = log.or(warp::get().and(metrics.or(node_info_route)));
+        // Get /peers (the peers the node is connected to, on all of its networks)
+        let peers = warp::path("peers")
+            .map(move || warp::reply::json(&connected_peers(&peer_metadata_storage)));
+
+        // Get /state-sync/streams (the active data streams of state sync and their progress)
+        let data_streams = warp::path!("state-sync" / "streams")
+            .map(move || warp::reply::json(&active_data_streams.summaries()));
+
+        // Get /storage (the disk usage of the data directory of the node)
+        let data_dir = node_config.data_dir().to_path_buf();
+        let storage = warp::path("storage").map(move || match StorageUsage::of(&data_dir) {
+            Ok(usage) => warp::reply::with_status(warp::reply::json(&usage), StatusCode::OK),
+            Err(e) => warp::reply::with_status(
+                warp::reply::json(&e.to_string()),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+        });
+
+        let routes = log.or(update_log).or(warp::get().and(
+            metrics
+                .or(node_info_route)
+                .or(peers)
+                .or(data_streams)
+                .or(storage)
+                .or(log_filters),
+        ));
 
         runtime
             .handle()
@@ -93,3 +175,17 @@ impl NodeDebugService {
         &self.runtime
     }
 }
+
+fn connected_peers(peer_metadata_storage: &PeerMetadataStorage) -> Vec<ConnectedPeer> {
+    peer_metadata_storage
+        .networks()
+        .flat_map(|network_id| peer_metadata_storage.read_all(network_id))
+        .filter(|(_, peer_info)| peer_info.status == PeerState::Connected)
+        .map(|(peer_network_id, peer_info)| ConnectedPeer {
+            network_id: peer_network_id.network_id(),
+            peer_id: peer_network_id.peer_id(),
+            address: peer_info.active_connection.addr,
+            inbound: peer_info.active_connection.origin == ConnectionOrigin::Inbound,
+        })
+        .collect()
+}
@@ -3,9 +3,10 @@
 
 #![forbid(unsafe_code)]
 
+use serde::{Deserialize, Serialize};
 use std::{
     fmt::Debug,
-    ops::Sub,
+    ops::{Add, Sub},
     sync::atomic::{AtomicU64, Ordering},
 };
 
@@ -63,7 +64,7 @@ impl AtomicHistogramAccumulator {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct AtomicHistogramSnapshot {
     capacity: usize,
     step_width: u64,
@@ -99,6 +100,30 @@ impl Sub for &AtomicHistogramSnapshot {
     }
 }
 
+impl Add for &AtomicHistogramSnapshot {
+    type Output = AtomicHistogramSnapshot;
+
+    fn add(self, other: &AtomicHistogramSnapshot) -> AtomicHistogramSnapshot {
+        assert_eq!(
+            self.buckets.len(),
+            other.buckets.len(),
+            "Histogram snapshots must have same size, left: {}, right: {}",
+            self.buckets.len(),
+            other.buckets.len()
+        );
+        AtomicHistogramSnapshot {
+            capacity: self.capacity,
+            step_width: self.step_width,
+            buckets: self
+                .buckets
+                .iter()
+                .zip(other.buckets.iter())
+                .map(|(a, b)| a + b)
+                .collect(),
+        }
+    }
+}
+
 impl AtomicHistogramSnapshot {
     pub fn percentile(&self, numerator: u64, denominator: u64) -> u64 {
         let committed: u64 = self.buckets.iter().sum();
@@ -117,7 +142,6 @@ impl AtomicHistogramSnapshot {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::TxnStats;
 
     #[test]
     pub fn test_default_atomic_histogram() {
@@ -167,14 +191,7 @@ mod test {
         for i in 1..11 {
             histogram.record_data_point(i as u64 * 100, 1);
         }
-        let stat = TxnStats {
-            submitted: 0,
-            committed: 10,
-            expired: 0,
-            latency: 0,
-            latency_buckets: histogram.snapshot(),
-        };
-        let res = stat.latency_buckets.percentile(9, 10);
+        let res = histogram.snapshot().percentile(9, 10);
         assert_eq!(res, 900);
     }
 }
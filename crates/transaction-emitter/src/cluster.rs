@@ -17,7 +17,7 @@ use aptos_sdk::{
     move_types::account_address::AccountAddress,
     types::{account_config::aptos_root_address, chain_id::ChainId, AccountKey, LocalAccount},
 };
-use rand::seq::SliceRandom;
+use rand::{seq::SliceRandom, Rng};
 use std::{convert::TryFrom, path::Path};
 
 pub struct Cluster {
@@ -103,10 +103,9 @@ impl Cluster {
             .await
     }
 
-    pub fn random_instance(&self) -> Instance {
-        let mut rnd = rand::thread_rng();
+    pub fn random_instance<R: Rng>(&self, rng: &mut R) -> Instance {
         self.instances
-            .choose(&mut rnd)
+            .choose(rng)
             .expect("random_validator_instance requires non-empty validator_instances")
             .clone()
     }
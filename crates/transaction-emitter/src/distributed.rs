@@ -0,0 +1,299 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Coordinates several emitter processes, e.g. one k8s Job per emitter, so that together they
+//! generate more load than a single machine can.
+//!
+//! The controller splits the target TPS between emitters and every emitter goes through:
+//! 1. `POST /register` to get its index and share of the load
+//! 2. wait for its turn to mint accounts, as all emitters mint from the same root account
+//! 3. `POST /ready/<index>` once its accounts are minted
+//! 4. wait on `GET /start` for the common start time, set once all emitters are ready
+//! 5. emit for the requested duration and `POST /stats/<index>`
+//!
+//! The controller aggregates the stats of all emitters once they have all reported.
+
+use crate::TxnStats;
+use anyhow::{bail, format_err, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::oneshot;
+use url::Url;
+use warp::Filter;
+
+/// Delay between the last emitter becoming ready and the start of the load, leaving time for
+/// every emitter to see the start time
+const START_DELAY: Duration = Duration::from_secs(5);
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Assignment {
+    pub index: usize,
+    pub num_emitters: usize,
+    /// This emitter's share of the target TPS, if the load is capped
+    pub target_tps: Option<u64>,
+    pub duration_secs: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Turn {
+    /// Index of the emitter currently allowed to mint accounts
+    minting: usize,
+    /// Unix time in milliseconds at which all emitters start, once all of them are ready
+    start_at_millis: Option<u64>,
+}
+
+struct ControllerState {
+    num_emitters: usize,
+    target_tps: Option<u64>,
+    duration: Duration,
+    registered: usize,
+    ready: Vec<bool>,
+    start_at_millis: Option<u64>,
+    stats: Vec<Option<TxnStats>>,
+}
+
+impl ControllerState {
+    fn register(&mut self) -> Result<Assignment> {
+        if self.registered >= self.num_emitters {
+            bail!("all {} emitters are already registered", self.num_emitters);
+        }
+        let index = self.registered;
+        self.registered += 1;
+        Ok(Assignment {
+            index,
+            num_emitters: self.num_emitters,
+            target_tps: self
+                .target_tps
+                .map(|tps| split_tps(tps, self.num_emitters, index)),
+            duration_secs: self.duration.as_secs(),
+        })
+    }
+
+    fn turn(&self) -> Turn {
+        Turn {
+            minting: self.ready.iter().take_while(|ready| **ready).count(),
+            start_at_millis: self.start_at_millis,
+        }
+    }
+
+    fn set_ready(&mut self, index: usize) -> Result<()> {
+        *self
+            .ready
+            .get_mut(index)
+            .ok_or_else(|| format_err!("unknown emitter {}", index))? = true;
+        if self.start_at_millis.is_none() && self.ready.iter().all(|ready| *ready) {
+            self.start_at_millis = Some(unix_millis() + START_DELAY.as_millis() as u64);
+        }
+        Ok(())
+    }
+
+    fn set_stats(&mut self, index: usize, stats: TxnStats) -> Result<()> {
+        *self
+            .stats
+            .get_mut(index)
+            .ok_or_else(|| format_err!("unknown emitter {}", index))? = Some(stats);
+        Ok(())
+    }
+}
+
+/// Splits `target_tps` between `num_emitters`, handing the remainder to the first emitters
+fn split_tps(target_tps: u64, num_emitters: usize, index: usize) -> u64 {
+    let num_emitters = num_emitters as u64;
+    let index = index as u64;
+    target_tps / num_emitters
+        + if index < target_tps % num_emitters {
+            1
+        } else {
+            0
+        }
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time is before the unix epoch")
+        .as_millis() as u64
+}
+
+/// Serves the controller on `address` until all `num_emitters` emitters reported their stats,
+/// returning the stats aggregated over all of them.
+pub async fn run_controller(
+    address: SocketAddr,
+    num_emitters: usize,
+    target_tps: Option<u64>,
+    duration: Duration,
+) -> Result<TxnStats> {
+    if num_emitters == 0 {
+        bail!("need at least one emitter");
+    }
+    if let Some(target_tps) = target_tps {
+        if target_tps < num_emitters as u64 {
+            bail!(
+                "target tps {} is too low to be split between {} emitters",
+                target_tps,
+                num_emitters
+            );
+        }
+    }
+    let state = Arc::new(Mutex::new(ControllerState {
+        num_emitters,
+        target_tps,
+        duration,
+        registered: 0,
+        ready: vec![false; num_emitters],
+        start_at_millis: None,
+        stats: (0..num_emitters).map(|_| None).collect(),
+    }));
+
+    let register = {
+        let state = state.clone();
+        warp::path!("register")
+            .and(warp::post())
+            .map(move || reply(state.lock().unwrap().register()))
+    };
+    let ready = {
+        let state = state.clone();
+        warp::path!("ready" / usize)
+            .and(warp::post())
+            .map(move |index| reply(state.lock().unwrap().set_ready(index)))
+    };
+    let turn = {
+        let state = state.clone();
+        warp::path!("start")
+            .and(warp::get())
+            .map(move || warp::reply::json(&state.lock().unwrap().turn()))
+    };
+    let stats = {
+        let state = state.clone();
+        warp::path!("stats" / usize)
+            .and(warp::post())
+            .and(warp::body::json::<TxnStats>())
+            .map(move |index, stats| reply(state.lock().unwrap().set_stats(index, stats)))
+    };
+
+    let (shutdown_sender, shutdown_receiver) = oneshot::channel::<()>();
+    let (address, server) = warp::serve(register.or(ready).or(turn).or(stats))
+        .try_bind_with_graceful_shutdown(address, async {
+            shutdown_receiver.await.ok();
+        })?;
+    let server = tokio::spawn(server);
+    println!(
+        "Controller listening on {}, waiting for {} emitters",
+        address, num_emitters
+    );
+
+    let mut total = TxnStats::default();
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+        let state = state.lock().unwrap();
+        if state.stats.iter().all(Option::is_some) {
+            for stats in state.stats.iter().flatten() {
+                total = &total + stats;
+            }
+            break;
+        }
+    }
+
+    let _ = shutdown_sender.send(());
+    server.await?;
+    Ok(total)
+}
+
+fn reply<T: Serialize>(result: Result<T>) -> warp::reply::WithStatus<warp::reply::Json> {
+    match result {
+        Ok(body) => warp::reply::with_status(warp::reply::json(&body), warp::http::StatusCode::OK),
+        Err(e) => warp::reply::with_status(
+            warp::reply::json(&e.to_string()),
+            warp::http::StatusCode::BAD_REQUEST,
+        ),
+    }
+}
+
+/// Client used by an emitter to follow the controller's schedule
+pub struct ControllerClient {
+    client: reqwest::Client,
+    base_url: Url,
+}
+
+impl ControllerClient {
+    pub fn new(base_url: Url) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url,
+        }
+    }
+
+    pub async fn register(&self) -> Result<Assignment> {
+        self.post("register", None::<&()>).await
+    }
+
+    /// Waits until the emitter with the given index may mint its accounts
+    pub async fn wait_for_minting_turn(&self, index: usize) -> Result<()> {
+        while self.turn().await?.minting < index {
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+        Ok(())
+    }
+
+    pub async fn ready(&self, index: usize) -> Result<()> {
+        self.post(&format!("ready/{}", index), None::<&()>).await
+    }
+
+    /// Waits until all emitters are ready and then until the common start time
+    pub async fn wait_for_start(&self) -> Result<()> {
+        let start_at_millis = loop {
+            if let Some(start_at_millis) = self.turn().await?.start_at_millis {
+                break start_at_millis;
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        };
+        let now = unix_millis();
+        if start_at_millis > now {
+            tokio::time::sleep(Duration::from_millis(start_at_millis - now)).await;
+        }
+        Ok(())
+    }
+
+    pub async fn report_stats(&self, index: usize, stats: &TxnStats) -> Result<()> {
+        self.post(&format!("stats/{}", index), Some(stats)).await
+    }
+
+    async fn turn(&self) -> Result<Turn> {
+        let response = self.client.get(self.base_url.join("start")?).send().await?;
+        Ok(response.error_for_status()?.json().await?)
+    }
+
+    async fn post<B: Serialize, T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+        body: Option<&B>,
+    ) -> Result<T> {
+        let mut request = self.client.post(self.base_url.join(path)?);
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            let error: String = response.json().await?;
+            bail!("controller rejected {}: {}", path, error);
+        }
+        Ok(response.json().await?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn test_split_tps() {
+        let shares = (0..3).map(|i| split_tps(100, 3, i)).collect::<Vec<_>>();
+        assert_eq!(shares, vec![34, 33, 33]);
+        assert_eq!(shares.iter().sum::<u64>(), 100);
+    }
+}
@@ -0,0 +1,170 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+use hdrhistogram::Histogram;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::ops::{Add, Sub};
+
+/// Latencies are recorded with 3 significant digits, i.e. within 0.1% of their value
+const SIGNIFICANT_DIGITS: u8 = 3;
+
+/// HDR histogram of transaction latencies in milliseconds. Unlike fixed width buckets, it keeps
+/// the same relative precision for any latency, so tail latencies are as accurate as the median.
+///
+/// It serializes as the recorded `(latency, count)` pairs, so that the stats of distributed
+/// emitters can be merged by the controller.
+#[derive(Clone, Debug)]
+pub struct LatencyHistogram(Histogram<u64>);
+
+impl Default for LatencyHistogram {
+    fn default() -> LatencyHistogram {
+        // An auto resizing histogram, which tracks latencies of any value
+        LatencyHistogram(Histogram::new(SIGNIFICANT_DIGITS).expect("Valid significant digits"))
+    }
+}
+
+impl LatencyHistogram {
+    pub fn record(&mut self, latency_ms: u64) {
+        self.0.saturating_record(latency_ms);
+    }
+
+    /// Number of recorded latencies
+    pub fn len(&self) -> u64 {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Latency at the `percentile`, between 0 and 100, or 0 if there are no latencies
+    pub fn percentile(&self, percentile: f64) -> u64 {
+        if self.is_empty() {
+            return 0;
+        }
+        self.0.value_at_percentile(percentile)
+    }
+
+    /// Highest latency, or 0 if there are no latencies
+    pub fn max(&self) -> u64 {
+        if self.is_empty() {
+            return 0;
+        }
+        self.0.max()
+    }
+}
+
+impl Add for &LatencyHistogram {
+    type Output = LatencyHistogram;
+
+    fn add(self, other: &LatencyHistogram) -> LatencyHistogram {
+        let mut histogram = self.clone();
+        histogram
+            .0
+            .add(&other.0)
+            .expect("Auto resizing histograms can always be added");
+        histogram
+    }
+}
+
+impl Sub for &LatencyHistogram {
+    type Output = LatencyHistogram;
+
+    fn sub(self, other: &LatencyHistogram) -> LatencyHistogram {
+        let mut histogram = self.clone();
+        histogram
+            .0
+            .subtract(&other.0)
+            .expect("Can only subtract a previous snapshot of the same histogram");
+        histogram
+    }
+}
+
+impl Serialize for LatencyHistogram {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0
+            .iter_recorded()
+            .map(|value| (value.value_iterated_to(), value.count_at_value()))
+            .collect::<Vec<_>>()
+            .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for LatencyHistogram {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut histogram = LatencyHistogram::default();
+        for (latency_ms, count) in Vec::<(u64, u64)>::deserialize(deserializer)? {
+            histogram.0.saturating_record_n(latency_ms, count);
+        }
+        Ok(histogram)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn histogram(latencies: impl IntoIterator<Item = u64>) -> LatencyHistogram {
+        let mut histogram = LatencyHistogram::default();
+        for latency in latencies {
+            histogram.record(latency);
+        }
+        histogram
+    }
+
+    #[test]
+    pub fn test_percentiles() {
+        // 1000 commits: 550 of 10ms, 400 of 20ms, 45 of 30ms and 5 of 40ms
+        let histogram = histogram(
+            (0..550)
+                .map(|_| 10)
+                .chain((0..400).map(|_| 20))
+                .chain((0..45).map(|_| 30))
+                .chain((0..5).map(|_| 40)),
+        );
+        assert_eq!(histogram.len(), 1000);
+        assert_eq!(histogram.percentile(50.0), 10);
+        assert_eq!(histogram.percentile(90.0), 20);
+        assert_eq!(histogram.percentile(99.0), 30);
+        assert_eq!(histogram.max(), 40);
+
+        let empty = LatencyHistogram::default();
+        assert_eq!(empty.percentile(99.0), 0);
+        assert_eq!(empty.max(), 0);
+    }
+
+    #[test]
+    pub fn test_tail_latency_precision() {
+        // A slow tail is as precise as the fast majority
+        let histogram = histogram((0..990).map(|_| 100).chain((0..10).map(|_| 54_321)));
+        assert_eq!(histogram.percentile(50.0), 100);
+        assert!((54_321..54_400).contains(&histogram.percentile(99.5)));
+        assert!((54_321..54_400).contains(&histogram.max()));
+    }
+
+    #[test]
+    pub fn test_add_sub() {
+        let prev = histogram(1..=10);
+        let cur = &prev + &histogram(11..=20);
+        assert_eq!(cur.len(), 20);
+        assert_eq!(cur.max(), 20);
+
+        let delta = &cur - &prev;
+        assert_eq!(delta.len(), 10);
+        assert_eq!(delta.max(), 20);
+        assert_eq!((&delta - &delta).max(), 0);
+    }
+
+    #[test]
+    pub fn test_serde() {
+        let histogram = histogram(vec![5, 5, 120, 3_000, 70_000]);
+        let json = serde_json::to_string(&histogram).unwrap();
+        let deserialized: LatencyHistogram = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.len(), 5);
+        assert_eq!(deserialized.percentile(30.0), 5);
+        assert_eq!(deserialized.percentile(50.0), 120);
+        assert_eq!(deserialized.max(), histogram.max());
+    }
+}
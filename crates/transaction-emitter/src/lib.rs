@@ -2,8 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use ::aptos_logger::*;
-use anyhow::{format_err, Context, Result};
-use aptos_rest_client::{Client as RestClient, PendingTransaction, Response};
+use anyhow::{ensure, format_err, Context, Result};
+use aptos_rest_client::{Client as RestClient, PendingTransaction, Response, RestError};
 use aptos_sdk::{
     move_types::account_address::AccountAddress,
     transaction_builder::TransactionFactory,
@@ -13,7 +13,10 @@ use aptos_sdk::{
         LocalAccount,
     },
 };
-use futures::future::{try_join_all, FutureExt};
+use futures::{
+    future::{try_join_all, FutureExt},
+    stream::{self, StreamExt},
+};
 use itertools::zip;
 use rand::{
     distributions::{Distribution, Standard},
@@ -21,15 +24,16 @@ use rand::{
     Rng, RngCore,
 };
 use rand_core::SeedableRng;
+use serde::{Deserialize, Serialize};
 use std::{
     cmp::{max, min},
-    collections::HashSet,
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
     fmt,
     num::NonZeroU64,
     path::Path,
     sync::{
         atomic::{AtomicBool, AtomicU64, Ordering},
-        Arc,
+        Arc, Mutex,
     },
     time::{Duration, Instant},
 };
@@ -37,7 +41,10 @@ use tokio::{runtime::Handle, task::JoinHandle, time};
 
 pub mod atomic_histogram;
 pub mod cluster;
+pub mod distributed;
 pub mod instance;
+pub mod latency_histogram;
+pub mod transaction_mix;
 
 use aptos::common::types::EncodingType;
 use aptos_crypto::ed25519::{Ed25519PrivateKey, Ed25519PublicKey};
@@ -45,15 +52,33 @@ use aptos_sdk::{
     transaction_builder::aptos_stdlib,
     types::{transaction::authenticator::AuthenticationKeyPreimage, AccountKey},
 };
-use atomic_histogram::*;
+use latency_histogram::LatencyHistogram;
+use move_deps::{
+    move_binary_format::file_format::empty_module, move_core_types::identifier::Identifier,
+};
 use rand::rngs::StdRng;
+use transaction_mix::{TransactionMix, TransactionType};
 
 /// Max transactions per account in mempool
 const MAX_TXN_BATCH_SIZE: usize = 100;
 const MAX_TXNS: u64 = 1_000_000;
 const SEND_AMOUNT: u64 = 1;
+// extra coins for secure to pay none zero gas price
+const COINS_PER_ACCOUNT: u64 = SEND_AMOUNT * MAX_TXNS * 10;
 const TXN_EXPIRATION_SECONDS: u64 = 180;
 const TXN_MAX_WAIT: Duration = Duration::from_secs(TXN_EXPIRATION_SECONDS as u64 + 30);
+/// Expiration of the transactions of a job unless set with
+/// `EmitJobRequest::txn_expiration_time_secs`, the default of `TransactionFactory`
+const DEFAULT_JOB_TXN_EXPIRATION_SECONDS: u64 = 30;
+/// How long past their expiration transactions are still waited for, as expiration goes by the
+/// ledger timestamp, which lags behind the clock of the emitter
+const TXN_EXPIRATION_GRACE: Duration = Duration::from_secs(5);
+/// How often workers which don't wait for their transactions to commit look for expired ones
+const TXN_SWEEP_INTERVAL: Duration = Duration::from_secs(5);
+/// Backoff of the submissions to a node after its mempool first rejects them in adaptive mode,
+/// doubling as long as it keeps rejecting them
+const MIN_MEMPOOL_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_MEMPOOL_BACKOFF: Duration = Duration::from_secs(10);
 const MAX_CHILD_VASP_NUM: usize = 65536;
 const MAX_VASP_ACCOUNT_NUM: usize = 16;
 
@@ -80,7 +105,13 @@ pub struct EmitJobRequest {
     thread_params: EmitThreadParams,
     gas_price: u64,
     invalid_transaction_ratio: usize,
+    transaction_mix: TransactionMix,
     vasp: bool,
+    hot_account: bool,
+    txn_expiration_time_secs: u64,
+    target_tps: Option<NonZeroU64>,
+    adaptive_backoff: bool,
+    endpoint_weights: Option<Vec<usize>>,
 }
 
 impl Default for EmitJobRequest {
@@ -92,7 +123,13 @@ impl Default for EmitJobRequest {
             thread_params: EmitThreadParams::default(),
             gas_price: 0,
             invalid_transaction_ratio: 0,
+            transaction_mix: TransactionMix::default(),
             vasp: false,
+            hot_account: false,
+            txn_expiration_time_secs: DEFAULT_JOB_TXN_EXPIRATION_SECONDS,
+            target_tps: None,
+            adaptive_backoff: false,
+            endpoint_weights: None,
         }
     }
 }
@@ -132,11 +169,25 @@ impl EmitJobRequest {
         self
     }
 
-    pub fn fixed_tps(self, target_tps: NonZeroU64) -> Self {
+    pub fn transaction_mix(mut self, transaction_mix: TransactionMix) -> Self {
+        self.transaction_mix = transaction_mix;
+        self
+    }
+
+    /// How long the transactions of the job stay valid after they're created. Workers give up on
+    /// transactions once they expire and resync the sequence numbers of their accounts, so a
+    /// shorter expiration recovers faster from transactions which are dropped.
+    pub fn txn_expiration_time_secs(mut self, txn_expiration_time_secs: u64) -> Self {
+        self.txn_expiration_time_secs = txn_expiration_time_secs;
+        self
+    }
+
+    pub fn fixed_tps(mut self, target_tps: NonZeroU64) -> Self {
         let clients_count = self.rest_clients.len() as u64;
         let num_workers = target_tps.get() / clients_count + 1;
         let wait_time = clients_count * num_workers * 1000 / target_tps.get();
 
+        self.target_tps = Some(target_tps);
         self.workers_per_endpoint(num_workers as usize)
             .thread_params(EmitThreadParams {
                 wait_millis: wait_time,
@@ -145,19 +196,111 @@ impl EmitJobRequest {
             .accounts_per_client(1)
     }
 
+    fn num_workers_per_endpoint(&self) -> usize {
+        match self.workers_per_endpoint {
+            Some(x) => x,
+            None => {
+                let target_threads = 300;
+                // Trying to create somewhere between target_threads/2..target_threads threads
+                // We want to have equal numbers of threads for each endpoint, so that they are equally loaded
+                // Otherwise things like flamegrap/perf going to show different numbers depending on which endpoint is chosen
+                // Also limiting number of threads as max 10 per endpoint for use cases with very small number of nodes or use --peers
+                min(10, max(1, target_threads / self.rest_clients.len()))
+            }
+        }
+    }
+
+    /// Workers of each endpoint, as many for each unless the job has endpoint weights
+    fn workers_of_endpoints(&self) -> Result<Vec<usize>> {
+        let workers_per_endpoint = self.num_workers_per_endpoint();
+        let weights = match &self.endpoint_weights {
+            Some(weights) => weights,
+            None => return Ok(vec![workers_per_endpoint; self.rest_clients.len()]),
+        };
+        ensure!(
+            weights.len() == self.rest_clients.len(),
+            "{} endpoint weights for {} endpoints",
+            weights.len(),
+            self.rest_clients.len()
+        );
+        ensure!(
+            weights.iter().any(|weight| *weight > 0),
+            "All the endpoint weights are zero"
+        );
+        Ok(split_workers(
+            workers_per_endpoint * self.rest_clients.len(),
+            weights,
+        ))
+    }
+
+    fn num_accounts(&self) -> usize {
+        self.accounts_per_client * self.rest_clients.len() * self.num_workers_per_endpoint()
+    }
+
     pub fn vasp(mut self) -> Self {
         self.vasp = true;
         self
     }
+
+    /// Sends all the transfers of the job to a single account, so that they all write its
+    /// balance and conflict with each other, rather than spreading them over the job's accounts
+    pub fn hot_account(mut self) -> Self {
+        self.hot_account = true;
+        self
+    }
+
+    /// Backs off the submissions to a node while its mempool is full, resubmitting the rejected
+    /// transactions once the backoff ends, rather than moving on and leaving the later
+    /// transactions of their accounts stuck until they expire. The workers submitting to the same
+    /// node back off together, for the delay the node asks for or else for an exponential delay.
+    pub fn adaptive_backoff(mut self) -> Self {
+        self.adaptive_backoff = true;
+        self
+    }
+
+    /// Spreads the load of the job unevenly over the endpoints, in proportion to `weights`, one
+    /// for each of the rest clients in the same order, e.g. 80% of it on the first of 3 endpoints
+    /// with `[80, 10, 10]`. The workers of the job are split between the endpoints by weight, as
+    /// closely as their number allows, so an endpoint slower to commit than the others takes a
+    /// smaller share of the load than its weight when the workers wait for their transactions.
+    pub fn endpoint_weights(mut self, weights: Vec<usize>) -> Self {
+        self.endpoint_weights = Some(weights);
+        self
+    }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Deserialize, Serialize)]
 pub struct TxnStats {
     pub submitted: u64,
     pub committed: u64,
     pub expired: u64,
     pub latency: u64,
-    pub latency_buckets: AtomicHistogramSnapshot,
+    /// Latencies of the committed transactions, from their submission to their commit
+    pub latency_histogram: LatencyHistogram,
+    /// Submissions the nodes rejected because their mempool was full, including the
+    /// resubmissions of the adaptive mode
+    #[serde(default)]
+    pub rejected: u64,
+    /// TPS the job was asked to emit at, if it was limited
+    #[serde(default)]
+    pub requested_tps: Option<u64>,
+    /// Stats of the submissions to each endpoint, by url
+    #[serde(default)]
+    pub endpoints: BTreeMap<String, EndpointTxnStats>,
+}
+
+/// Stats of the submissions of a job to one endpoint
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct EndpointTxnStats {
+    pub submitted: u64,
+    /// Submissions the node accepted into its mempool
+    pub accepted: u64,
+    /// Submissions the node rejected because its mempool was full
+    pub rejected: u64,
+    /// Transactions seen committed, only counted when the workers wait for them
+    pub committed: u64,
+    /// Latencies of the committed transactions, from their submission to their commit
+    pub latency_histogram: LatencyHistogram,
 }
 
 #[derive(Debug, Default)]
@@ -165,8 +308,13 @@ pub struct TxnStatsRate {
     pub submitted: u64,
     pub committed: u64,
     pub expired: u64,
+    pub rejected: u64,
+    pub requested_tps: Option<u64>,
     pub latency: u64,
+    pub p50_latency: u64,
+    pub p90_latency: u64,
     pub p99_latency: u64,
+    pub max_latency: u64,
 }
 
 #[derive(Default)]
@@ -175,7 +323,77 @@ struct StatsAccumulator {
     committed: AtomicU64,
     expired: AtomicU64,
     latency: AtomicU64,
-    latencies: Arc<AtomicHistogramAccumulator>,
+    latencies: Mutex<LatencyHistogram>,
+    rejected: AtomicU64,
+    requested_tps: Option<u64>,
+    /// The url and stats of each endpoint of the job
+    endpoints: Vec<(String, EndpointStatsAccumulator)>,
+}
+
+#[derive(Default)]
+struct EndpointStatsAccumulator {
+    submitted: AtomicU64,
+    accepted: AtomicU64,
+    rejected: AtomicU64,
+    committed: AtomicU64,
+    latencies: Mutex<LatencyHistogram>,
+}
+
+/// Backoff of the submissions of the workers to a node whose mempool is full, shared by the
+/// workers submitting to the node
+#[derive(Debug)]
+struct MempoolBackoff {
+    state: Mutex<BackoffState>,
+}
+
+#[derive(Debug)]
+struct BackoffState {
+    /// The delay of the last backoff, zero once the node accepts transactions again
+    delay: Duration,
+    /// When the submissions resume
+    resume_at: Instant,
+}
+
+impl MempoolBackoff {
+    fn new() -> Self {
+        Self {
+            state: Mutex::new(BackoffState {
+                delay: Duration::ZERO,
+                resume_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Waits until the backoff, if any, ends
+    async fn wait(&self) {
+        let resume_at = self.state.lock().unwrap().resume_at;
+        let now = Instant::now();
+        if resume_at > now {
+            time::sleep(resume_at - now).await;
+        }
+    }
+
+    /// Backs off after a rejection, for `retry_after` if the node asked for it and it's longer
+    /// than the exponential delay
+    fn on_rejected(&self, retry_after: Option<Duration>) {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        // The rejections of the submissions made before the current backoff started don't
+        // extend it
+        if state.resume_at > now {
+            return;
+        }
+        state.delay = if state.delay.is_zero() {
+            MIN_MEMPOOL_BACKOFF
+        } else {
+            min(state.delay * 2, MAX_MEMPOOL_BACKOFF)
+        };
+        state.resume_at = now + max(state.delay, retry_after.unwrap_or_default());
+    }
+
+    fn on_accepted(&self) {
+        self.state.lock().unwrap().delay = Duration::ZERO;
+    }
 }
 
 struct Worker {
@@ -197,70 +415,88 @@ struct SubmissionWorker {
     stats: Arc<StatsAccumulator>,
     txn_factory: TransactionFactory,
     invalid_transaction_ratio: usize,
+    transaction_mix: TransactionMix,
     rng: ::rand::rngs::StdRng,
+    txn_expiration: Duration,
+    /// Sequence numbers and submission times of the transactions of each account which weren't
+    /// seen committed yet, oldest first. Only tracked when not waiting for batches to commit.
+    in_flight: HashMap<AccountAddress, VecDeque<(u64, Instant)>>,
+    /// Backoff of the submissions to the node of `client`, in adaptive mode
+    backoff: Option<Arc<MempoolBackoff>>,
+    /// Index of the endpoint of `client` in the stats
+    endpoint: usize,
 }
 
 impl SubmissionWorker {
     #[allow(clippy::collapsible_if)]
     async fn run(mut self, gas_price: u64) -> Vec<LocalAccount> {
         let wait_duration = Duration::from_millis(self.params.wait_millis);
+        let mut last_sweep = Instant::now();
         while !self.stop.load(Ordering::Relaxed) {
             let requests = self.gen_requests(gas_price);
             let num_requests = requests.len();
             let start_time = Instant::now();
             let wait_until = start_time + wait_duration;
-            let mut txn_offset_time = 0u64;
+            let mut submit_times = HashMap::with_capacity(num_requests);
             for request in requests {
-                let cur_time = Instant::now();
-                txn_offset_time += (cur_time - start_time).as_millis() as u64;
-                self.stats.submitted.fetch_add(1, Ordering::Relaxed);
-                let resp = self.client.submit(&request).await;
-                if let Err(e) = resp {
-                    warn!("[{:?}] Failed to submit request: {:?}", self.client, e);
+                // A duplicated transaction is only committed once, from its first submission
+                let submit_time = Instant::now();
+                submit_times.entry(request.sender()).or_insert(submit_time);
+                if !self.params.wait_committed {
+                    self.in_flight
+                        .entry(request.sender())
+                        .or_default()
+                        .push_back((request.sequence_number(), submit_time));
                 }
+                self.stats.submitted.fetch_add(1, Ordering::Relaxed);
+                self.endpoint_stats()
+                    .submitted
+                    .fetch_add(1, Ordering::Relaxed);
+                self.submit(&request, submit_time).await;
             }
             if self.params.wait_committed {
-                if let Err(uncommitted) =
-                    wait_for_accounts_sequence(&self.client, &mut self.accounts).await
-                {
-                    let num_committed = (num_requests - uncommitted.len()) as u64;
-                    // To avoid negative result caused by uncommitted tx occur
-                    // Simplified from:
-                    // end_time * num_committed - (txn_offset_time/num_requests) * num_committed
-                    // to
-                    // (end_time - txn_offset_time / num_requests) * num_committed
-                    let latency = (Instant::now() - start_time).as_millis() as u64
-                        - txn_offset_time / num_requests as u64;
-                    let committed_latency = latency * num_committed as u64;
-                    self.stats
-                        .committed
-                        .fetch_add(num_committed, Ordering::Relaxed);
-                    self.stats
-                        .expired
-                        .fetch_add(uncommitted.len() as u64, Ordering::Relaxed);
-                    self.stats
-                        .latency
-                        .fetch_add(committed_latency, Ordering::Relaxed);
-                    self.stats
-                        .latencies
-                        .record_data_point(latency, num_committed);
+                let (commit_times, uncommitted) = wait_for_accounts_sequence(
+                    &self.client,
+                    &mut self.accounts,
+                    start_time + self.txn_expiration + TXN_EXPIRATION_GRACE,
+                )
+                .await;
+                let num_committed = (num_requests - uncommitted.len()) as u64;
+                // Each account sends at most one transaction per batch, so the transaction of an
+                // account is committed once the account reaches its sequence number.
+                let latencies: Vec<u64> = commit_times
+                    .iter()
+                    .filter_map(|(address, commit_time)| {
+                        let submit_time = submit_times.get(address)?;
+                        Some(
+                            commit_time
+                                .saturating_duration_since(*submit_time)
+                                .as_millis() as u64,
+                        )
+                    })
+                    .collect();
+                self.stats
+                    .committed
+                    .fetch_add(num_committed, Ordering::Relaxed);
+                self.stats
+                    .expired
+                    .fetch_add(uncommitted.len() as u64, Ordering::Relaxed);
+                self.stats.record_latencies(&latencies);
+                let endpoint_stats = self.endpoint_stats();
+                endpoint_stats
+                    .committed
+                    .fetch_add(num_committed, Ordering::Relaxed);
+                endpoint_stats.record_latencies(&latencies);
+                if !uncommitted.is_empty() {
                     info!(
                         "[{:?}] Transactions were not committed before expiration: {:?}",
                         self.client, uncommitted
                     );
-                } else {
-                    let latency = (Instant::now() - start_time).as_millis() as u64
-                        - txn_offset_time / num_requests as u64;
-                    self.stats
-                        .committed
-                        .fetch_add(num_requests as u64, Ordering::Relaxed);
-                    self.stats
-                        .latency
-                        .fetch_add(latency * num_requests as u64, Ordering::Relaxed);
-                    self.stats
-                        .latencies
-                        .record_data_point(latency, num_requests as u64);
+                    resync_sequence_numbers(&self.client, &mut self.accounts, &uncommitted).await;
                 }
+            } else if last_sweep.elapsed() >= TXN_SWEEP_INTERVAL {
+                self.sweep_expired().await;
+                last_sweep = Instant::now();
             }
             let now = Instant::now();
             if wait_until > now {
@@ -270,6 +506,112 @@ impl SubmissionWorker {
         self.accounts
     }
 
+    /// Submits the transaction, and in adaptive mode resubmits it after backing off as long as the
+    /// mempool of the node is full, until the transaction expires or the job stops
+    async fn submit(&self, request: &SignedTransaction, submit_time: Instant) {
+        loop {
+            if let Some(backoff) = &self.backoff {
+                backoff.wait().await;
+            }
+            let error = match self.client.submit(request).await {
+                Ok(_) => {
+                    self.endpoint_stats()
+                        .accepted
+                        .fetch_add(1, Ordering::Relaxed);
+                    if let Some(backoff) = &self.backoff {
+                        backoff.on_accepted();
+                    }
+                    return;
+                }
+                Err(error) => error,
+            };
+            let rejection = error
+                .downcast_ref::<RestError>()
+                .filter(|error| error.is_backpressure());
+            if let Some(rejection) = rejection {
+                self.stats.rejected.fetch_add(1, Ordering::Relaxed);
+                self.endpoint_stats()
+                    .rejected
+                    .fetch_add(1, Ordering::Relaxed);
+                if let Some(backoff) = &self.backoff {
+                    if submit_time.elapsed() < self.txn_expiration
+                        && !self.stop.load(Ordering::Relaxed)
+                    {
+                        backoff.on_rejected(rejection.retry_after());
+                        continue;
+                    }
+                }
+            }
+            warn!("[{:?}] Failed to submit request: {:?}", self.client, error);
+            return;
+        }
+    }
+
+    fn endpoint_stats(&self) -> &EndpointStatsAccumulator {
+        &self.stats.endpoints[self.endpoint].1
+    }
+
+    /// Resets the sequence numbers of the accounts whose oldest transaction not seen committed has
+    /// expired to those on chain. Otherwise, all the later transactions of such an account would
+    /// be stuck behind the missing one until the end of the job.
+    async fn sweep_expired(&mut self) {
+        let addresses: Vec<_> = self.in_flight.keys().copied().collect();
+        if addresses.is_empty() {
+            return;
+        }
+        let sequence_numbers = match query_sequence_numbers(&self.client, &addresses).await {
+            Ok(sequence_numbers) => sequence_numbers,
+            Err(e) => {
+                warn!(
+                    "[{:?}] Failed to query the sequence numbers of {:?}: {:?}",
+                    self.client, addresses, e
+                );
+                return;
+            }
+        };
+
+        let mut expired = HashMap::new();
+        for (address, sequence_number) in zip(addresses, sequence_numbers) {
+            let txns = self.in_flight.get_mut(&address).unwrap();
+            // the transactions below the sequence number on chain were committed
+            while let Some((txn_sequence_number, _)) = txns.front() {
+                if *txn_sequence_number >= sequence_number {
+                    break;
+                }
+                txns.pop_front();
+            }
+            match txns.front() {
+                None => {
+                    self.in_flight.remove(&address);
+                }
+                Some((_, submit_time))
+                    if submit_time.elapsed() > self.txn_expiration + TXN_EXPIRATION_GRACE =>
+                {
+                    expired.insert(address, sequence_number);
+                }
+                Some(_) => {}
+            }
+        }
+        if expired.is_empty() {
+            return;
+        }
+
+        let mut num_expired = 0;
+        for account in self.accounts.iter_mut() {
+            if let Some(sequence_number) = expired.get(&account.address()) {
+                num_expired += account.sequence_number().saturating_sub(*sequence_number);
+                *account.sequence_number_mut() = *sequence_number;
+                self.in_flight.remove(&account.address());
+            }
+        }
+        self.stats.expired.fetch_add(num_expired, Ordering::Relaxed);
+        info!(
+            "[{:?}] Transactions expired, resynced the sequence numbers of {:?}",
+            self.client,
+            expired.keys().collect::<Vec<_>>()
+        );
+    }
+
     fn gen_requests(&mut self, gas_price: u64) -> Vec<SignedTransaction> {
         let batch_size = max(MAX_TXN_BATCH_SIZE, self.accounts.len());
         let accounts = self
@@ -291,12 +633,13 @@ impl SubmissionWorker {
                 .expect("all_addresses can't be empty");
             let request = if num_valid_tx > 0 {
                 num_valid_tx -= 1;
-                gen_transfer_txn_request(
+                gen_mixed_txn_request(
+                    self.transaction_mix.sample(&mut self.rng),
                     sender,
                     receiver,
-                    SEND_AMOUNT,
                     &self.txn_factory,
                     gas_price,
+                    &mut self.rng,
                 )
             } else {
                 generate_invalid_transaction(
@@ -348,6 +691,45 @@ impl<'t> TxnEmitter<'t> {
         self.accounts.clear();
     }
 
+    /// The accounts minted or reused so far, which jobs hand back once they stop
+    pub fn into_accounts(self) -> Vec<LocalAccount> {
+        self.accounts
+    }
+
+    /// Adds accounts handed back by an earlier emitter on the same chain, whose sequence numbers
+    /// are up to date, for jobs to use before minting new ones. As with reused accounts, those
+    /// which spent more than half of their coins are left out. Returns the number of added accounts.
+    pub async fn add_accounts(&mut self, accounts: Vec<LocalAccount>) -> usize {
+        let client = self.client.clone();
+        let balances: Vec<_> = stream::iter(accounts.iter().map(|account| account.address()))
+            .map(|address| account_balance(&client, address))
+            .buffered(MAX_TXN_BATCH_SIZE)
+            .collect()
+            .await;
+        let mut accounts = funded_accounts(accounts, balances, COINS_PER_ACCOUNT / 2);
+        let added = accounts.len();
+        self.accounts.append(&mut accounts);
+        info!("Adding {} pooled accounts", added);
+        added
+    }
+
+    /// Reuses the accounts of `keys`, minted by an earlier emitter on the same chain, so that
+    /// jobs only mint the accounts still missing. Accounts which don't exist or spent more than
+    /// half of their coins are left out. Returns the number of reused accounts.
+    pub async fn reuse_accounts(&mut self, keys: Vec<Ed25519PrivateKey>) -> usize {
+        let client = self.client.clone();
+        let mut accounts: Vec<_> = stream::iter(keys)
+            .map(|key| load_funded_account(&client, key, COINS_PER_ACCOUNT / 2))
+            .buffered(MAX_TXN_BATCH_SIZE)
+            .filter_map(|account| async move { account })
+            .collect()
+            .await;
+        let reused = accounts.len();
+        self.accounts.append(&mut accounts);
+        info!("Reusing {} accounts", reused);
+        reused
+    }
+
     pub fn rng(&mut self) -> &mut ::rand::rngs::StdRng {
         &mut self.rng
     }
@@ -462,7 +844,7 @@ impl<'t> TxnEmitter<'t> {
                 req.rest_clients.len()
             };
         let num_accounts = total_requested_accounts - self.accounts.len(); // Only minting extra accounts
-        let coins_per_account = SEND_AMOUNT * MAX_TXNS * 10; // extra coins for secure to pay none zero gas price
+        let coins_per_account = COINS_PER_ACCOUNT;
         let coins_total = coins_per_account * num_accounts as u64;
         let txn_factory = self.txn_factory.clone();
         let client = self.pick_mint_client(&req.rest_clients);
@@ -541,24 +923,28 @@ impl<'t> TxnEmitter<'t> {
         Ok(())
     }
 
+    /// Mints the accounts `req` needs ahead of `start_job`, so that minting does not count
+    /// towards the time spent emitting
+    pub async fn prepare_job(&mut self, req: &EmitJobRequest) -> Result<()> {
+        self.mint_accounts(req, req.num_accounts()).await
+    }
+
     pub async fn start_job(&mut self, req: EmitJobRequest) -> Result<EmitJob> {
-        let workers_per_endpoint = match req.workers_per_endpoint {
-            Some(x) => x,
-            None => {
-                let target_threads = 300;
-                // Trying to create somewhere between target_threads/2..target_threads threads
-                // We want to have equal numbers of threads for each endpoint, so that they are equally loaded
-                // Otherwise things like flamegrap/perf going to show different numbers depending on which endpoint is chosen
-                // Also limiting number of threads as max 10 per endpoint for use cases with very small number of nodes or use --peers
-                min(10, max(1, target_threads / req.rest_clients.len()))
-            }
-        };
-        let num_clients = req.rest_clients.len() * workers_per_endpoint;
-        println!(
-            "Will use {} workers per endpoint with total {} endpoint clients",
-            workers_per_endpoint, num_clients
-        );
-        let num_accounts = req.accounts_per_client * num_clients;
+        let workers_of_endpoints = req.workers_of_endpoints()?;
+        let num_clients: usize = workers_of_endpoints.iter().sum();
+        if req.endpoint_weights.is_some() {
+            println!(
+                "Will use {:?} workers for the endpoints with total {} endpoint clients",
+                workers_of_endpoints, num_clients
+            );
+        } else {
+            println!(
+                "Will use {} workers per endpoint with total {} endpoint clients",
+                req.num_workers_per_endpoint(),
+                num_clients
+            );
+        }
+        let num_accounts = req.num_accounts();
         println!(
             "Will create {} accounts_per_client with total {} accounts",
             req.accounts_per_client, num_accounts
@@ -566,14 +952,31 @@ impl<'t> TxnEmitter<'t> {
         self.mint_accounts(&req, num_accounts).await?;
         let all_accounts = self.accounts.split_off(self.accounts.len() - num_accounts);
         let mut workers = vec![];
-        let all_addresses: Vec<_> = all_accounts.iter().map(|d| d.address()).collect();
+        let all_addresses: Vec<_> = if req.hot_account {
+            all_accounts.iter().take(1).map(|d| d.address()).collect()
+        } else {
+            all_accounts.iter().map(|d| d.address()).collect()
+        };
         let all_addresses = Arc::new(all_addresses);
         let mut all_accounts = all_accounts.into_iter();
         let stop = Arc::new(AtomicBool::new(false));
-        let stats = Arc::new(StatsAccumulator::default());
+        let stats = Arc::new(StatsAccumulator {
+            requested_tps: req.target_tps.map(NonZeroU64::get),
+            endpoints: req
+                .rest_clients
+                .iter()
+                .map(|client| (client.base_url().to_string(), Default::default()))
+                .collect(),
+            ..StatsAccumulator::default()
+        });
         let tokio_handle = Handle::current();
-        for client in req.rest_clients {
-            for _ in 0..workers_per_endpoint {
+        for (endpoint, (client, num_workers)) in
+            zip(req.rest_clients, workers_of_endpoints).enumerate()
+        {
+            let backoff = req
+                .adaptive_backoff
+                .then(|| Arc::new(MempoolBackoff::new()));
+            for _ in 0..num_workers {
                 let accounts = (&mut all_accounts).take(req.accounts_per_client).collect();
                 let all_addresses = all_addresses.clone();
                 let stop = stop.clone();
@@ -586,9 +989,17 @@ impl<'t> TxnEmitter<'t> {
                     stop,
                     params,
                     stats,
-                    txn_factory: self.txn_factory.clone(),
+                    txn_factory: self
+                        .txn_factory
+                        .clone()
+                        .with_transaction_expiration_time(req.txn_expiration_time_secs),
                     invalid_transaction_ratio: req.invalid_transaction_ratio,
+                    transaction_mix: req.transaction_mix.clone(),
                     rng: self.from_rng(),
+                    txn_expiration: Duration::from_secs(req.txn_expiration_time_secs),
+                    in_flight: HashMap::new(),
+                    backoff: backoff.clone(),
+                    endpoint,
                 };
                 let join_handle = tokio_handle.spawn(worker.run(req.gas_price).boxed());
                 workers.push(Worker { join_handle });
@@ -714,25 +1125,32 @@ pub async fn execute_and_wait_transactions(
     Ok(())
 }
 
+/// Waits for the accounts to reach their local sequence numbers. Returns when each account was
+/// seen at its sequence number, and the accounts that didn't reach it before `deadline`, by which
+/// their transactions expired.
 async fn wait_for_accounts_sequence(
     client: &RestClient,
     accounts: &mut [LocalAccount],
-) -> Result<(), Vec<AccountAddress>> {
-    let deadline = Instant::now() + Duration::from_secs(TXN_EXPIRATION_SECONDS); //TXN_MAX_WAIT;
+    deadline: Instant,
+) -> (HashMap<AccountAddress, Instant>, Vec<AccountAddress>) {
     let addresses: Vec<_> = accounts.iter().map(|d| d.address()).collect();
     let mut uncommitted = addresses.clone().into_iter().collect::<HashSet<_>>();
+    let mut commit_times = HashMap::with_capacity(addresses.len());
 
     while Instant::now() < deadline {
         match query_sequence_numbers(client, &addresses).await {
             Ok(sequence_numbers) => {
+                let now = Instant::now();
                 for (account, sequence_number) in zip(accounts.iter(), &sequence_numbers) {
-                    if account.sequence_number() == *sequence_number {
-                        uncommitted.remove(&account.address());
+                    if account.sequence_number() == *sequence_number
+                        && uncommitted.remove(&account.address())
+                    {
+                        commit_times.insert(account.address(), now);
                     }
                 }
 
                 if uncommitted.is_empty() {
-                    return Ok(());
+                    return (commit_times, vec![]);
                 }
             }
             Err(e) => {
@@ -746,7 +1164,32 @@ async fn wait_for_accounts_sequence(
         time::sleep(Duration::from_millis(500)).await;
     }
 
-    Err(uncommitted.into_iter().collect())
+    (commit_times, uncommitted.into_iter().collect())
+}
+
+/// Resets the sequence numbers of the accounts at `addresses` to those on chain, e.g. once their
+/// transactions expired, so that their next transactions aren't stuck behind the missing ones
+async fn resync_sequence_numbers(
+    client: &RestClient,
+    accounts: &mut [LocalAccount],
+    addresses: &[AccountAddress],
+) {
+    let stale: Vec<_> = accounts
+        .iter_mut()
+        .filter(|account| addresses.contains(&account.address()))
+        .collect();
+    let stale_addresses: Vec<_> = stale.iter().map(|account| account.address()).collect();
+    match query_sequence_numbers(client, &stale_addresses).await {
+        Ok(sequence_numbers) => {
+            for (account, sequence_number) in zip(stale, sequence_numbers) {
+                *account.sequence_number_mut() = sequence_number;
+            }
+        }
+        Err(e) => warn!(
+            "[{:?}] Failed to resync the sequence numbers of {:?}: {:?}",
+            client, stale_addresses, e
+        ),
+    }
 }
 
 pub async fn query_sequence_numbers(
@@ -763,6 +1206,54 @@ pub async fn query_sequence_numbers(
     )
 }
 
+/// The account of `key` with its current sequence number, if it exists and has at least
+/// `min_coins`
+async fn load_funded_account(
+    client: &RestClient,
+    key: Ed25519PrivateKey,
+    min_coins: u64,
+) -> Option<LocalAccount> {
+    let account_key = AccountKey::from_private_key(key);
+    let address = account_key.authentication_key().derived_address();
+    if account_balance(client, address).await? < min_coins {
+        return None;
+    }
+    let sequence_number = client
+        .get_account(address)
+        .await
+        .ok()?
+        .into_inner()
+        .sequence_number;
+    Some(LocalAccount::new(address, account_key, sequence_number))
+}
+
+/// The balance of the account, none if it can't be fetched
+async fn account_balance(client: &RestClient, address: AccountAddress) -> Option<u64> {
+    Some(
+        client
+            .get_account_balance(address)
+            .await
+            .ok()?
+            .into_inner()
+            .get(),
+    )
+}
+
+/// The accounts which have at least `min_coins`, given their balances, leaving out those whose
+/// balance is unknown
+fn funded_accounts(
+    accounts: Vec<LocalAccount>,
+    balances: Vec<Option<u64>>,
+    min_coins: u64,
+) -> Vec<LocalAccount> {
+    accounts
+        .into_iter()
+        .zip(balances)
+        .filter(|(_, balance)| balance.map_or(false, |balance| balance >= min_coins))
+        .map(|(account, _)| account)
+        .collect()
+}
+
 /// Create `num_new_accounts` by transferring coins from `source_account`. Return Vec of created
 /// accounts
 async fn create_new_accounts<R>(
@@ -896,6 +1387,56 @@ pub fn gen_transfer_txn_request(
     )
 }
 
+/// Generates a valid transaction of `txn_type` from `sender`, transfers going to `receiver`
+fn gen_mixed_txn_request<R>(
+    txn_type: TransactionType,
+    sender: &mut LocalAccount,
+    receiver: &AccountAddress,
+    txn_factory: &TransactionFactory,
+    gas_price: u64,
+    rng: &mut R,
+) -> SignedTransaction
+where
+    R: ::rand_core::RngCore + ::rand_core::CryptoRng,
+{
+    let payload = match txn_type {
+        TransactionType::Transfer => txn_factory.payload(aptos_stdlib::encode_test_coin_transfer(
+            *receiver,
+            SEND_AMOUNT,
+        )),
+        TransactionType::AccountCreation => {
+            let new_account = LocalAccount::generate(rng);
+            txn_factory.payload(aptos_stdlib::encode_account_create_account(
+                new_account.address(),
+            ))
+        }
+        TransactionType::ModulePublish => {
+            txn_factory.module(gen_module_code(sender.address(), sender.sequence_number()))
+        }
+        // Rotating the authentication key to itself leaves the account usable
+        TransactionType::ScriptFunction => {
+            txn_factory.payload(aptos_stdlib::encode_account_rotate_authentication_key(
+                sender.authentication_key().to_vec(),
+            ))
+        }
+    };
+    sender.sign_with_transaction_builder(payload.gas_unit_price(gas_price))
+}
+
+/// Code of an empty module under `address`, named after `sequence_number` as a module can't be
+/// published twice
+fn gen_module_code(address: AccountAddress, sequence_number: u64) -> Vec<u8> {
+    let mut module = empty_module();
+    module.address_identifiers[0] = address;
+    module.identifiers[0] =
+        Identifier::new(format!("Emitter{}", sequence_number)).expect("Valid module name");
+    let mut code = vec![];
+    module
+        .serialize(&mut code)
+        .expect("Empty modules always serialize");
+    code
+}
+
 fn generate_invalid_transaction<R>(
     sender: &mut LocalAccount,
     receiver: &AccountAddress,
@@ -944,13 +1485,50 @@ where
 }
 
 impl StatsAccumulator {
+    fn record_latencies(&self, latencies: &[u64]) {
+        self.latency
+            .fetch_add(latencies.iter().sum(), Ordering::Relaxed);
+        let mut histogram = self.latencies.lock().unwrap();
+        for latency in latencies {
+            histogram.record(*latency);
+        }
+    }
+
     pub fn accumulate(&self) -> TxnStats {
+        let mut endpoints = BTreeMap::<_, EndpointTxnStats>::new();
+        // The same endpoint may be given more than once
+        for (url, endpoint) in &self.endpoints {
+            let stats = endpoints.entry(url.clone()).or_default();
+            *stats = &*stats + &endpoint.accumulate();
+        }
         TxnStats {
             submitted: self.submitted.load(Ordering::Relaxed),
             committed: self.committed.load(Ordering::Relaxed),
             expired: self.expired.load(Ordering::Relaxed),
             latency: self.latency.load(Ordering::Relaxed),
-            latency_buckets: self.latencies.snapshot(),
+            latency_histogram: self.latencies.lock().unwrap().clone(),
+            rejected: self.rejected.load(Ordering::Relaxed),
+            requested_tps: self.requested_tps,
+            endpoints,
+        }
+    }
+}
+
+impl EndpointStatsAccumulator {
+    fn record_latencies(&self, latencies: &[u64]) {
+        let mut histogram = self.latencies.lock().unwrap();
+        for latency in latencies {
+            histogram.record(*latency);
+        }
+    }
+
+    fn accumulate(&self) -> EndpointTxnStats {
+        EndpointTxnStats {
+            submitted: self.submitted.load(Ordering::Relaxed),
+            accepted: self.accepted.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+            committed: self.committed.load(Ordering::Relaxed),
+            latency_histogram: self.latencies.lock().unwrap().clone(),
         }
     }
 }
@@ -961,12 +1539,17 @@ impl TxnStats {
             submitted: self.submitted / window.as_secs(),
             committed: self.committed / window.as_secs(),
             expired: self.expired / window.as_secs(),
+            rejected: self.rejected / window.as_secs(),
+            requested_tps: self.requested_tps,
             latency: if self.committed == 0 {
                 0u64
             } else {
                 self.latency / self.committed
             },
-            p99_latency: self.latency_buckets.percentile(99, 100),
+            p50_latency: self.latency_histogram.percentile(50.0),
+            p90_latency: self.latency_histogram.percentile(90.0),
+            p99_latency: self.latency_histogram.percentile(99.0),
+            max_latency: self.latency_histogram.max(),
         }
     }
 }
@@ -980,7 +1563,75 @@ impl std::ops::Sub for &TxnStats {
             committed: self.committed - other.committed,
             expired: self.expired - other.expired,
             latency: self.latency - other.latency,
-            latency_buckets: &self.latency_buckets - &other.latency_buckets,
+            latency_histogram: &self.latency_histogram - &other.latency_histogram,
+            rejected: self.rejected - other.rejected,
+            // The requested TPS is a rate rather than a count, so it's that of the whole job
+            requested_tps: self.requested_tps,
+            endpoints: self
+                .endpoints
+                .iter()
+                .map(|(url, stats)| {
+                    let other_stats = other.endpoints.get(url).cloned().unwrap_or_default();
+                    (url.clone(), stats - &other_stats)
+                })
+                .collect(),
+        }
+    }
+}
+
+impl std::ops::Add for &TxnStats {
+    type Output = TxnStats;
+
+    fn add(self, other: &TxnStats) -> TxnStats {
+        TxnStats {
+            submitted: self.submitted + other.submitted,
+            committed: self.committed + other.committed,
+            expired: self.expired + other.expired,
+            latency: self.latency + other.latency,
+            latency_histogram: &self.latency_histogram + &other.latency_histogram,
+            rejected: self.rejected + other.rejected,
+            // The stats of concurrent jobs add up, e.g. those of the emitters of a distributed
+            // run
+            requested_tps: match (self.requested_tps, other.requested_tps) {
+                (Some(tps), Some(other_tps)) => Some(tps + other_tps),
+                (tps, other_tps) => tps.or(other_tps),
+            },
+            endpoints: {
+                let mut endpoints = self.endpoints.clone();
+                for (url, other_stats) in &other.endpoints {
+                    let stats = endpoints.entry(url.clone()).or_default();
+                    *stats = &*stats + other_stats;
+                }
+                endpoints
+            },
+        }
+    }
+}
+
+impl std::ops::Sub for &EndpointTxnStats {
+    type Output = EndpointTxnStats;
+
+    fn sub(self, other: &EndpointTxnStats) -> EndpointTxnStats {
+        EndpointTxnStats {
+            submitted: self.submitted - other.submitted,
+            accepted: self.accepted - other.accepted,
+            rejected: self.rejected - other.rejected,
+            committed: self.committed - other.committed,
+            latency_histogram: &self.latency_histogram - &other.latency_histogram,
+        }
+    }
+}
+
+impl std::ops::Add for &EndpointTxnStats {
+    type Output = EndpointTxnStats;
+
+    fn add(self, other: &EndpointTxnStats) -> EndpointTxnStats {
+        EndpointTxnStats {
+            submitted: self.submitted + other.submitted,
+            accepted: self.accepted + other.accepted,
+            rejected: self.rejected + other.rejected,
+            committed: self.committed + other.committed,
+            latency_histogram: &self.latency_histogram + &other.latency_histogram,
         }
     }
 }
@@ -989,22 +1640,58 @@ impl fmt::Display for TxnStats {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "submitted: {}, committed: {}, expired: {}",
-            self.submitted, self.committed, self.expired,
+            "submitted: {}, committed: {}, expired: {}, rejected: {}",
+            self.submitted, self.committed, self.expired, self.rejected,
         )
     }
 }
 
-impl fmt::Display for TxnStatsRate {
+impl fmt::Display for EndpointTxnStats {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "submitted: {} txn/s, committed: {} txn/s, expired: {} txn/s, latency: {} ms, p99 latency: {} ms",
-            self.submitted, self.committed, self.expired, self.latency, self.p99_latency,
+            "submitted: {}, accepted: {}, rejected: {}, committed: {}, \
+            p50/p90/p99/max latency: {}/{}/{}/{} ms",
+            self.submitted,
+            self.accepted,
+            self.rejected,
+            self.committed,
+            self.latency_histogram.percentile(50.0),
+            self.latency_histogram.percentile(90.0),
+            self.latency_histogram.percentile(99.0),
+            self.latency_histogram.max(),
         )
     }
 }
 
+impl fmt::Display for TxnStatsRate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "submitted: {} txn/s, committed: {} txn/s, expired: {} txn/s, rejected: {} txn/s, \
+            latency: {} ms, p50/p90/p99/max latency: {}/{}/{}/{} ms",
+            self.submitted,
+            self.committed,
+            self.expired,
+            self.rejected,
+            self.latency,
+            self.p50_latency,
+            self.p90_latency,
+            self.p99_latency,
+            self.max_latency,
+        )?;
+        if let Some(requested_tps) = self.requested_tps {
+            write!(
+                f,
+                ", committed {:.1}% of the requested {} txn/s",
+                self.committed as f64 * 100.0 / requested_tps as f64,
+                requested_tps
+            )?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 enum InvalidTransactionType {
     /// invalid tx with wrong chain id
@@ -1028,6 +1715,23 @@ impl Distribution<InvalidTransactionType> for Standard {
     }
 }
 
+/// Splits `total` workers between endpoints in proportion to their `weights`, handing the workers
+/// left over from rounding down to the endpoints with the largest remainders
+fn split_workers(total: usize, weights: &[usize]) -> Vec<usize> {
+    let weight_sum: usize = weights.iter().sum();
+    let mut workers: Vec<_> = weights
+        .iter()
+        .map(|weight| total * weight / weight_sum)
+        .collect();
+    let left_over = total - workers.iter().sum::<usize>();
+    let mut by_remainder: Vec<_> = (0..weights.len()).collect();
+    by_remainder.sort_by_key(|i| std::cmp::Reverse(total * weights[*i] % weight_sum));
+    for i in by_remainder.into_iter().take(left_over) {
+        workers[i] += 1;
+    }
+    workers
+}
+
 fn gen_rng_for_reusable_account(count: usize) -> Vec<StdRng> {
     // use same seed for reuse account creation and reuse
     let mut seed = [
@@ -1071,3 +1775,31 @@ where
     }
     Ok(vasp_accounts)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn test_split_workers() {
+        assert_eq!(split_workers(30, &[80, 10, 10]), vec![24, 3, 3]);
+        assert_eq!(split_workers(10, &[1, 1, 1]), vec![4, 3, 3]);
+        assert_eq!(split_workers(4, &[80, 10, 10, 0]), vec![3, 1, 0, 0]);
+        assert_eq!(split_workers(5, &[0, 1]), vec![0, 5]);
+    }
+
+    #[test]
+    pub fn test_funded_accounts() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let accounts: Vec<_> = (0..5).map(|_| LocalAccount::generate(&mut rng)).collect();
+        let addresses: Vec<_> = accounts.iter().map(|account| account.address()).collect();
+        let balances = vec![Some(100), Some(99), None, Some(0), Some(1000)];
+        let funded: Vec<_> = funded_accounts(accounts, balances, 100)
+            .iter()
+            .map(|account| account.address())
+            .collect();
+        assert_eq!(funded, vec![addresses[0], addresses[4]]);
+
+        assert!(funded_accounts(vec![], vec![], 100).is_empty());
+    }
+}
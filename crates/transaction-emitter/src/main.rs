@@ -10,15 +10,22 @@ use rand::{rngs::StdRng, Rng, SeedableRng};
 use rand_core::OsRng;
 use std::{
     cmp::min,
+    net::SocketAddr,
+    num::NonZeroU64,
     process,
     time::{Duration, Instant},
 };
 use structopt::StructOpt;
 use termion::color;
 use transaction_emitter::{
-    cluster::Cluster, instance::Instance, query_sequence_numbers, EmitJobRequest, EmitThreadParams,
-    TxnEmitter,
+    cluster::Cluster,
+    distributed::{run_controller, ControllerClient},
+    instance::Instance,
+    query_sequence_numbers,
+    transaction_mix::TransactionMix,
+    EmitJobRequest, EmitThreadParams, TxnEmitter,
 };
+use url::Url;
 
 #[derive(StructOpt, Debug)]
 struct Args {
@@ -54,12 +61,73 @@ struct Args {
     duration: u64,
     #[structopt(long, help = "Percentage of invalid txs", default_value = "0")]
     invalid_tx: usize,
+    #[structopt(
+        long,
+        help = "Weighted mix of the valid txs, e.g. transfer:70,create_account:20,publish_module:5,script_function:5",
+        default_value = "transfer:1"
+    )]
+    transaction_mix: TransactionMix,
+    #[structopt(
+        long,
+        help = "Send all the transfers to a single account, for maximal write conflicts"
+    )]
+    hot_account: bool,
+    #[structopt(
+        long,
+        help = "Seconds the emitted txs stay valid, after which their accounts are resynced",
+        default_value = "30"
+    )]
+    txn_expiration_time_secs: u64,
+    #[structopt(
+        long,
+        help = "Back off the submissions to a node while its mempool is full and resubmit the rejected txs"
+    )]
+    adaptive_backoff: bool,
+    #[structopt(
+        long,
+        use_delimiter = true,
+        help = "Weights of the load on each peer, in the order of --peers, e.g. 80,10,10. If unset, the load is spread evenly over the peers"
+    )]
+    endpoint_weights: Vec<usize>,
+    #[structopt(
+        long,
+        help = "Seed of the accounts and instances --emit-tx picks, to replay a run. Random if unset"
+    )]
+    seed: Option<u64>,
+
+    // distributed mode options
+    #[structopt(
+        long,
+        help = "Serve as the controller of a distributed run on this address instead of emitting"
+    )]
+    run_controller: Option<SocketAddr>,
+    #[structopt(
+        long,
+        help = "Number of emitters taking part in the distributed run",
+        default_value = "1"
+    )]
+    num_emitters: usize,
+    #[structopt(
+        long,
+        help = "Total TPS of the distributed run, split between emitters. Uncapped if unset"
+    )]
+    target_tps: Option<NonZeroU64>,
+    #[structopt(
+        long,
+        help = "Url of the controller to coordinate with when running --emit-tx as one of several emitters"
+    )]
+    controller: Option<Url>,
 }
 
 #[tokio::main]
 pub async fn main() {
     let args = Args::from_args();
 
+    if let Some(address) = args.run_controller {
+        exit_on_error(controller(address, &args).await);
+        return;
+    }
+
     if !args.emit_tx && !args.diag {
         panic!("Can only use --emit-tx or --diag mode");
     }
@@ -82,32 +150,94 @@ async fn emit_tx(cluster: &Cluster, args: &Args) -> Result<()> {
         wait_millis: args.wait_millis,
         wait_committed: !args.burst,
     };
-    let duration = Duration::from_secs(args.duration);
-    let client = cluster.random_instance().rest_client();
+    let mut duration = Duration::from_secs(args.duration);
+    let mut target_tps = args.target_tps;
+    let controller = args.controller.clone().map(ControllerClient::new);
+    let mut assignment = None;
+    if let Some(controller) = &controller {
+        let assigned = controller.register().await?;
+        println!(
+            "Registered as emitter {} of {}",
+            assigned.index, assigned.num_emitters
+        );
+        duration = Duration::from_secs(assigned.duration_secs);
+        target_tps = assigned.target_tps.and_then(NonZeroU64::new);
+        // Emitters mint from the same root account, so they take turns to avoid sequence
+        // number conflicts and load it only once it is their turn
+        controller.wait_for_minting_turn(assigned.index).await?;
+        assignment = Some(assigned);
+    }
+    let seed = args.seed.unwrap_or_else(|| OsRng.gen());
+    println!("Emitting with seed {}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let client = cluster.random_instance(&mut rng).rest_client();
     let mut root_account = cluster.load_aptos_root_account(&client).await?;
     let mut emitter = TxnEmitter::new(
         &mut root_account,
         client,
         TransactionFactory::new(cluster.chain_id).with_gas_unit_price(1),
-        StdRng::from_seed(OsRng.gen()),
+        StdRng::from_rng(&mut rng)?,
     );
     let mut emit_job_request =
         EmitJobRequest::new(cluster.all_instances().map(Instance::rest_client).collect())
             .accounts_per_client(args.accounts_per_client)
             .thread_params(thread_params)
             .invalid_transaction_ratio(args.invalid_tx)
+            .transaction_mix(args.transaction_mix.clone())
+            .txn_expiration_time_secs(args.txn_expiration_time_secs)
             .gas_price(1);
     if let Some(workers_per_endpoint) = args.workers_per_ac {
         emit_job_request = emit_job_request.workers_per_endpoint(workers_per_endpoint);
     }
+    if let Some(target_tps) = target_tps {
+        emit_job_request = emit_job_request.fixed_tps(target_tps);
+    }
     if args.vasp {
         emit_job_request = emit_job_request.vasp();
     }
+    if args.hot_account {
+        emit_job_request = emit_job_request.hot_account();
+    }
+    if args.adaptive_backoff {
+        emit_job_request = emit_job_request.adaptive_backoff();
+    }
+    if !args.endpoint_weights.is_empty() {
+        emit_job_request = emit_job_request.endpoint_weights(args.endpoint_weights.clone());
+    }
+    if let (Some(controller), Some(assignment)) = (&controller, &assignment) {
+        emitter.prepare_job(&emit_job_request).await?;
+        controller.ready(assignment.index).await?;
+        println!("Waiting for all emitters to be ready");
+        controller.wait_for_start().await?;
+    }
     let stats = emitter
         .emit_txn_for_with_stats(duration, emit_job_request, 10)
         .await?;
     println!("Total stats: {}", stats);
     println!("Average rate: {}", stats.rate(duration));
+    for (endpoint, endpoint_stats) in &stats.endpoints {
+        println!("  {}: {}", endpoint, endpoint_stats);
+    }
+    if let (Some(controller), Some(assignment)) = (&controller, &assignment) {
+        controller.report_stats(assignment.index, &stats).await?;
+    }
+    Ok(())
+}
+
+async fn controller(address: SocketAddr, args: &Args) -> Result<()> {
+    let duration = Duration::from_secs(args.duration);
+    let stats = run_controller(
+        address,
+        args.num_emitters,
+        args.target_tps.map(NonZeroU64::get),
+        duration,
+    )
+    .await?;
+    println!("Total stats of {} emitters: {}", args.num_emitters, stats);
+    println!("Average rate: {}", stats.rate(duration));
+    for (endpoint, endpoint_stats) in &stats.endpoints {
+        println!("  {}: {}", endpoint, endpoint_stats);
+    }
     Ok(())
 }
 
@@ -153,14 +283,15 @@ impl BasicSwarmUtil {
     }
 
     pub async fn diag(&self, _vasp: bool) -> Result<()> {
-        let client = self.cluster.random_instance().rest_client();
+        let mut rng = StdRng::from_seed(OsRng.gen());
+        let client = self.cluster.random_instance(&mut rng).rest_client();
         let mut root_account = self.cluster.load_aptos_root_account(&client).await?;
         let mut faucet_account = self.cluster.load_aptos_root_account(&client).await?;
         let emitter = TxnEmitter::new(
             &mut root_account,
             client,
             TransactionFactory::new(self.cluster.chain_id).with_gas_unit_price(1),
-            StdRng::from_seed(OsRng.gen()),
+            rng,
         );
         let faucet_account_address = faucet_account.address();
         let instances: Vec<_> = self.cluster.all_instances().collect();
@@ -0,0 +1,187 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+use anyhow::{bail, ensure, format_err, Result};
+use rand::Rng;
+use std::{fmt, str::FromStr};
+
+/// Kinds of transactions an emit job submits
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransactionType {
+    /// Coin transfer to another account of the job
+    Transfer,
+    /// Creation of a new account
+    AccountCreation,
+    /// Publishing of a new module under the sender
+    ModulePublish,
+    /// Script function call other than a transfer, which rotates the authentication key of the
+    /// sender to itself
+    ScriptFunction,
+}
+
+impl TransactionType {
+    const VARIANTS: &'static [TransactionType] = &[
+        TransactionType::Transfer,
+        TransactionType::AccountCreation,
+        TransactionType::ModulePublish,
+        TransactionType::ScriptFunction,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            TransactionType::Transfer => "transfer",
+            TransactionType::AccountCreation => "create_account",
+            TransactionType::ModulePublish => "publish_module",
+            TransactionType::ScriptFunction => "script_function",
+        }
+    }
+}
+
+impl fmt::Display for TransactionType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for TransactionType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        TransactionType::VARIANTS
+            .iter()
+            .find(|txn_type| txn_type.name() == s)
+            .copied()
+            .ok_or_else(|| {
+                format_err!(
+                    "Unknown transaction type {}, expected one of {}",
+                    s,
+                    TransactionType::VARIANTS
+                        .iter()
+                        .map(TransactionType::name)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })
+    }
+}
+
+/// Weighted mix of the transactions of an emit job, each transaction being of a type with a
+/// probability proportional to its weight. Parses from and displays as comma separated
+/// `type:weight` pairs, e.g. `transfer:70,create_account:20,publish_module:10`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransactionMix(Vec<(TransactionType, usize)>);
+
+impl Default for TransactionMix {
+    fn default() -> Self {
+        TransactionMix(vec![(TransactionType::Transfer, 1)])
+    }
+}
+
+impl TransactionMix {
+    pub fn new(weights: Vec<(TransactionType, usize)>) -> Result<Self> {
+        ensure!(
+            weights.iter().any(|(_, weight)| *weight > 0),
+            "A transaction mix needs a transaction type with a non zero weight"
+        );
+        Ok(TransactionMix(weights))
+    }
+
+    /// Type of the next transaction
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> TransactionType {
+        let total: usize = self.0.iter().map(|(_, weight)| weight).sum();
+        let mut pick = rng.gen_range(0..total);
+        for (txn_type, weight) in &self.0 {
+            if pick < *weight {
+                return *txn_type;
+            }
+            pick -= weight;
+        }
+        unreachable!("The pick is below the total weight")
+    }
+}
+
+impl fmt::Display for TransactionMix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let weights = self
+            .0
+            .iter()
+            .map(|(txn_type, weight)| format!("{}:{}", txn_type, weight))
+            .collect::<Vec<_>>();
+        write!(f, "{}", weights.join(","))
+    }
+}
+
+impl FromStr for TransactionMix {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut weights = vec![];
+        for entry in s.split(',') {
+            let (txn_type, weight) = match entry.trim().split_once(':') {
+                Some((txn_type, weight)) => (txn_type, weight),
+                None => bail!("Expected type:weight, got {}", entry),
+            };
+            let weight = weight
+                .parse()
+                .map_err(|e| format_err!("Invalid weight for {}: {}", txn_type, e))?;
+            weights.push((txn_type.parse()?, weight));
+        }
+        TransactionMix::new(weights)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    pub fn test_parse() {
+        let mix: TransactionMix = "transfer:70, create_account:20,publish_module:10"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            mix,
+            TransactionMix(vec![
+                (TransactionType::Transfer, 70),
+                (TransactionType::AccountCreation, 20),
+                (TransactionType::ModulePublish, 10),
+            ])
+        );
+        assert_eq!(
+            mix.to_string().parse::<TransactionMix>().unwrap(),
+            mix,
+            "Displays as it parses"
+        );
+
+        assert!("transfer".parse::<TransactionMix>().is_err());
+        assert!("transfer:ten".parse::<TransactionMix>().is_err());
+        assert!("mint:10".parse::<TransactionMix>().is_err());
+        assert!("transfer:0,script_function:0"
+            .parse::<TransactionMix>()
+            .is_err());
+    }
+
+    #[test]
+    pub fn test_sample() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mix: TransactionMix = "transfer:0,create_account:3,script_function:1"
+            .parse()
+            .unwrap();
+        let samples: Vec<_> = (0..4000).map(|_| mix.sample(&mut rng)).collect();
+        let count = |txn_type| samples.iter().filter(|t| **t == txn_type).count();
+        assert_eq!(count(TransactionType::Transfer), 0);
+        assert_eq!(count(TransactionType::ModulePublish), 0);
+        // 3000 account creations expected, far more than the standard deviation of ~27
+        assert!((2800..3200).contains(&count(TransactionType::AccountCreation)));
+        assert_eq!(
+            count(TransactionType::AccountCreation) + count(TransactionType::ScriptFunction),
+            4000
+        );
+
+        let transfers = TransactionMix::default();
+        assert!((0..100).all(|_| transfers.sample(&mut rng) == TransactionType::Transfer));
+    }
+}
@@ -0,0 +1,467 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Audit of the third-party dependencies added or changed since a git ref: their source, their
+//! license, the known advisories against them and the workspace packages depending on them.
+
+use crate::{context::XContext, Result};
+use anyhow::{bail, Context};
+use guppy::{
+    graph::{DependencyDirection, PackageGraph},
+    Version, VersionReq,
+};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    env, fs,
+    path::{Path, PathBuf},
+};
+use structopt::{clap::arg_enum, StructOpt};
+
+arg_enum! {
+    #[derive(Debug, Copy, Clone)]
+    pub enum OutputFormat {
+        Text,
+        Json,
+    }
+}
+
+#[derive(Debug, StructOpt)]
+pub struct Args {
+    /// Audit the dependencies added or changed since the merge base with this commit
+    base: String,
+    #[structopt(long)]
+    /// Local clone of the RustSec advisory database (default: $CARGO_HOME/advisory-db)
+    advisory_db: Option<PathBuf>,
+    #[structopt(long, default_value = "Text")]
+    /// Output the report as text or json
+    output_format: OutputFormat,
+}
+
+/// A third-party dependency added or changed since the base
+#[derive(Debug, Serialize)]
+struct AuditedDependency {
+    name: String,
+    version: String,
+    /// Versions at the base, empty if the dependency is new
+    previous_versions: Vec<String>,
+    source: String,
+    license: Option<String>,
+    license_allowed: bool,
+    /// Advisories against this version, as `ID (kind): title`
+    advisories: Vec<String>,
+    /// Packages depending on this one directly
+    dependents: Vec<String>,
+}
+
+impl AuditedDependency {
+    fn has_issues(&self) -> bool {
+        !self.license_allowed || !self.advisories.is_empty()
+    }
+}
+
+pub fn run(args: Args, xctx: XContext) -> Result<()> {
+    let git_cli = xctx
+        .core()
+        .git_cli()
+        .with_context(|| "`x audit-deps` must be run within a project cloned from a git repo.")?;
+    let merge_base = git_cli
+        .merge_base(&args.base)
+        .with_context(|| "failed to get merge base with HEAD")?;
+    let old_graph = git_cli
+        .package_graph_at(&merge_base)
+        .with_context(|| "failed to build old package graph")?;
+    let new_graph = xctx.core().package_graph()?;
+
+    let excluded = excluded_packages(&xctx)?;
+    let old_versions: BTreeMap<String, BTreeSet<Version>> =
+        third_party_packages(&old_graph, &excluded)
+            .into_iter()
+            .fold(BTreeMap::new(), |mut versions, (name, version)| {
+                versions.entry(name).or_default().insert(version);
+                versions
+            });
+
+    let advisory_db = args.advisory_db.or_else(default_advisory_db);
+    let advisory_db = match advisory_db {
+        Some(path) if path.is_dir() => Some(path),
+        path => {
+            warn!(
+                "no advisory database at {:?}, clone https://github.com/rustsec/advisory-db there \
+                to check advisories",
+                path
+            );
+            None
+        }
+    };
+    let allowed_licenses = &xctx.config().audit_deps_config().allowed_licenses;
+
+    let mut audited = vec![];
+    for (name, version) in third_party_packages(new_graph, &excluded) {
+        let previous_versions = old_versions.get(&name);
+        if previous_versions.map_or(false, |versions| versions.contains(&version)) {
+            continue;
+        }
+        let package = new_graph
+            .packages()
+            .find(|package| package.name() == name && *package.version() == version)
+            .expect("the package is in the graph");
+        let license = package.license().map(str::to_string);
+        let advisories = match &advisory_db {
+            Some(advisory_db) => advisories(advisory_db, &name, &version)?,
+            None => vec![],
+        };
+        audited.push(AuditedDependency {
+            previous_versions: previous_versions
+                .into_iter()
+                .flatten()
+                .map(Version::to_string)
+                .collect(),
+            version: version.to_string(),
+            source: package.source().to_string(),
+            license_allowed: license
+                .as_deref()
+                .map_or(false, |license| license_allowed(license, allowed_licenses)),
+            license,
+            advisories,
+            dependents: package
+                .reverse_direct_links()
+                .map(|link| link.from().name().to_string())
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect(),
+            name,
+        });
+    }
+
+    match args.output_format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&audited)?),
+        OutputFormat::Text => print_report(&args.base, &audited),
+    }
+
+    let num_issues = audited.iter().filter(|dep| dep.has_issues()).count();
+    if num_issues > 0 {
+        bail!(
+            "{} of {} new or changed dependencies have a disallowed license or advisories",
+            num_issues,
+            audited.len()
+        );
+    }
+    Ok(())
+}
+
+/// Packages whose dependencies aren't audited: system tests, which aren't part of any release,
+/// and the workspace hack, which only unifies the features of the other dependencies
+fn excluded_packages(xctx: &XContext) -> Result<HashSet<String>> {
+    let mut excluded: HashSet<_> = xctx.config().system_tests().keys().cloned().collect();
+    if let Some(hakari_package) = xctx.core().hakari_builder()?.hakari_package() {
+        excluded.insert(hakari_package.name().to_string());
+    }
+    Ok(excluded)
+}
+
+/// The third-party packages the workspace depends on, other than through the excluded packages
+fn third_party_packages(
+    graph: &PackageGraph,
+    excluded: &HashSet<String>,
+) -> BTreeSet<(String, Version)> {
+    graph
+        .query_workspace()
+        .resolve_with_fn(|_, link| !excluded.contains(link.from().name()))
+        .packages(DependencyDirection::Forward)
+        .filter(|package| !package.in_workspace())
+        .map(|package| (package.name().to_string(), package.version().clone()))
+        .collect()
+}
+
+fn print_report(base: &str, audited: &[AuditedDependency]) {
+    if audited.is_empty() {
+        info!(
+            "no third-party dependencies added or changed since {}",
+            base
+        );
+        return;
+    }
+    println!(
+        "{} third-party dependencies added or changed since {}:",
+        audited.len(),
+        base
+    );
+    for dep in audited {
+        let change = if dep.previous_versions.is_empty() {
+            "new".to_string()
+        } else {
+            format!("was {}", dep.previous_versions.join(", "))
+        };
+        println!("\n{} {} ({})", dep.name, dep.version, change);
+        println!("  source: {}", dep.source);
+        println!(
+            "  license: {}{}",
+            dep.license.as_deref().unwrap_or("none"),
+            if dep.license_allowed {
+                ""
+            } else {
+                " (not allowed)"
+            }
+        );
+        println!("  depended on by: {}", dep.dependents.join(", "));
+        for advisory in &dep.advisories {
+            println!("  advisory: {}", advisory);
+        }
+    }
+}
+
+fn default_advisory_db() -> Option<PathBuf> {
+    let cargo_home = env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".cargo")))?;
+    Some(cargo_home.join("advisory-db"))
+}
+
+#[derive(Debug, Deserialize)]
+struct Advisory {
+    advisory: AdvisoryMetadata,
+    #[serde(default)]
+    versions: AdvisoryVersions,
+}
+
+#[derive(Debug, Deserialize)]
+struct AdvisoryMetadata {
+    id: String,
+    #[serde(default)]
+    title: Option<String>,
+    /// Kind of an advisory which isn't a vulnerability, e.g. `unmaintained`
+    #[serde(default)]
+    informational: Option<String>,
+    #[serde(default)]
+    withdrawn: Option<toml::Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct AdvisoryVersions {
+    #[serde(default)]
+    patched: Vec<String>,
+    #[serde(default)]
+    unaffected: Vec<String>,
+}
+
+/// Advisories of the database against this version of the package. Advisories are either TOML
+/// files, or Markdown files starting with a TOML block followed by the title.
+fn advisories(advisory_db: &Path, name: &str, version: &Version) -> Result<Vec<String>> {
+    let dir = advisory_db.join("crates").join(name);
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+    let mut advisories = vec![];
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("could not read advisory {}", path.display()))?;
+        let (toml, title) = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => (contents.as_str(), None),
+            Some("md") => {
+                let (toml, markdown) = split_markdown_advisory(&contents)
+                    .with_context(|| format!("invalid advisory {}", path.display()))?;
+                let title = markdown
+                    .lines()
+                    .find_map(|line| line.strip_prefix("# "))
+                    .map(str::to_string);
+                (toml, title)
+            }
+            _ => continue,
+        };
+        let advisory: Advisory = toml::from_str(toml)
+            .with_context(|| format!("could not parse advisory {}", path.display()))?;
+        if advisory.advisory.withdrawn.is_some() || !advisory.affects(version)? {
+            continue;
+        }
+        advisories.push(format!(
+            "{} ({}): {}",
+            advisory.advisory.id,
+            advisory
+                .advisory
+                .informational
+                .as_deref()
+                .unwrap_or("vulnerability"),
+            advisory
+                .advisory
+                .title
+                .or(title)
+                .unwrap_or_else(|| "untitled".to_string())
+        ));
+    }
+    advisories.sort();
+    Ok(advisories)
+}
+
+fn split_markdown_advisory(contents: &str) -> Result<(&str, &str)> {
+    let start = match contents.find("```toml") {
+        Some(start) => start + "```toml".len(),
+        None => bail!("no TOML block"),
+    };
+    let end = match contents[start..].find("```") {
+        Some(len) => start + len,
+        None => bail!("unterminated TOML block"),
+    };
+    Ok((&contents[start..end], &contents[end + "```".len()..]))
+}
+
+impl Advisory {
+    /// Whether the version is neither patched nor unaffected
+    fn affects(&self, version: &Version) -> Result<bool> {
+        for req in self
+            .versions
+            .patched
+            .iter()
+            .chain(&self.versions.unaffected)
+        {
+            let req = VersionReq::parse(req).with_context(|| {
+                format!("invalid version in advisory {}: {}", self.advisory.id, req)
+            })?;
+            if req.matches(version) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Whether the SPDX license expression is satisfied by the allowed licenses, e.g.
+/// `(MIT OR Apache-2.0) AND Unicode-DFS-2016`. The legacy `MIT/Apache-2.0` is read as an `OR`.
+fn license_allowed(expression: &str, allowed: &[String]) -> bool {
+    let expression = expression
+        .replace('/', " OR ")
+        .replace('(', " ( ")
+        .replace(')', " ) ");
+    let mut tokens = expression.split_whitespace().peekable();
+    let allowed = LicenseExpr::parse_or(&mut tokens).map_or(false, |expr| expr.allowed(allowed));
+    // Anything left over is a malformed expression
+    allowed && tokens.next().is_none()
+}
+
+/// Parsed SPDX license expression, in which AND binds tighter than OR
+enum LicenseExpr {
+    License(String),
+    And(Vec<LicenseExpr>),
+    Or(Vec<LicenseExpr>),
+}
+
+type Tokens<'a> = std::iter::Peekable<std::str::SplitWhitespace<'a>>;
+
+impl LicenseExpr {
+    fn parse_or(tokens: &mut Tokens) -> Option<Self> {
+        let mut exprs = vec![Self::parse_and(tokens)?];
+        while tokens.peek() == Some(&"OR") {
+            tokens.next();
+            exprs.push(Self::parse_and(tokens)?);
+        }
+        Some(LicenseExpr::Or(exprs))
+    }
+
+    fn parse_and(tokens: &mut Tokens) -> Option<Self> {
+        let mut exprs = vec![Self::parse_license(tokens)?];
+        while tokens.peek() == Some(&"AND") {
+            tokens.next();
+            exprs.push(Self::parse_license(tokens)?);
+        }
+        Some(LicenseExpr::And(exprs))
+    }
+
+    fn parse_license(tokens: &mut Tokens) -> Option<Self> {
+        match tokens.next()? {
+            "(" => {
+                let expr = Self::parse_or(tokens)?;
+                (tokens.next()? == ")").then(|| expr)
+            }
+            ")" | "AND" | "OR" | "WITH" => None,
+            license => {
+                if tokens.peek() == Some(&"WITH") {
+                    tokens.next();
+                    let exception = tokens.next()?;
+                    Some(LicenseExpr::License(format!(
+                        "{} WITH {}",
+                        license, exception
+                    )))
+                } else {
+                    Some(LicenseExpr::License(license.to_string()))
+                }
+            }
+        }
+    }
+
+    fn allowed(&self, allowed: &[String]) -> bool {
+        match self {
+            // A license with an exception is at least as permissive as the license itself
+            LicenseExpr::License(license) => allowed.iter().any(|allowed| {
+                allowed == license
+                    || license
+                        .split(" WITH ")
+                        .next()
+                        .map_or(false, |base| allowed == base)
+            }),
+            LicenseExpr::And(exprs) => exprs.iter().all(|expr| expr.allowed(allowed)),
+            LicenseExpr::Or(exprs) => exprs.iter().any(|expr| expr.allowed(allowed)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_license_allowed() {
+        let allowed = vec![
+            "Apache-2.0".to_string(),
+            "MIT".to_string(),
+            "Unicode-DFS-2016".to_string(),
+        ];
+        assert!(license_allowed("MIT", &allowed));
+        assert!(license_allowed("MIT OR GPL-3.0", &allowed));
+        assert!(license_allowed("MIT/Apache-2.0", &allowed));
+        assert!(license_allowed(
+            "(MIT OR Apache-2.0) AND Unicode-DFS-2016",
+            &allowed
+        ));
+        assert!(license_allowed("Apache-2.0 WITH LLVM-exception", &allowed));
+        // AND binds tighter than OR
+        assert!(license_allowed("MIT OR GPL-3.0 AND BSD-3-Clause", &allowed));
+        assert!(!license_allowed(
+            "(MIT OR GPL-3.0) AND BSD-3-Clause",
+            &allowed
+        ));
+        assert!(!license_allowed("GPL-3.0", &allowed));
+        assert!(!license_allowed("MIT AND GPL-3.0", &allowed));
+        assert!(!license_allowed(
+            "GPL-2.0 WITH Classpath-exception-2.0",
+            &allowed
+        ));
+        // Malformed expressions are never allowed
+        assert!(!license_allowed("MIT OR", &allowed));
+        assert!(!license_allowed("(MIT", &allowed));
+        assert!(!license_allowed("MIT)", &allowed));
+    }
+
+    #[test]
+    fn test_advisory_affects() {
+        let (toml, markdown) = split_markdown_advisory(
+            "```toml\n\
+            [advisory]\n\
+            id = \"RUSTSEC-2021-0001\"\n\
+            package = \"foo\"\n\
+            [versions]\n\
+            patched = [\">= 1.2.0\"]\n\
+            unaffected = [\"< 1.0.0\"]\n\
+            ```\n\
+            \n\
+            # Foo is broken\n",
+        )
+        .unwrap();
+        let advisory: Advisory = toml::from_str(toml).unwrap();
+        assert!(markdown.contains("# Foo is broken"));
+        assert!(!advisory.affects(&Version::new(0, 9, 0)).unwrap());
+        assert!(advisory.affects(&Version::new(1, 1, 3)).unwrap());
+        assert!(!advisory.affects(&Version::new(1, 2, 0)).unwrap());
+    }
+}
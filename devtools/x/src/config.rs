@@ -43,6 +43,8 @@ pub struct Config {
     grcov: CargoTool,
     /// Determinator configuration
     determinator: DeterminatorRules,
+    /// Dependency audit configuration
+    audit_deps: AuditDepsConfig,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
@@ -179,6 +181,13 @@ pub struct TestOnlyConfig {
     pub members: Vec<String>,
 }
 
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct AuditDepsConfig {
+    /// SPDX identifiers of the licenses third-party dependencies may be under
+    pub allowed_licenses: Vec<String>,
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
 pub struct Clippy {
@@ -254,4 +263,8 @@ impl Config {
     pub fn determinator_rules(&self) -> &DeterminatorRules {
         &self.determinator
     }
+
+    pub fn audit_deps_config(&self) -> &AuditDepsConfig {
+        &self.audit_deps
+    }
 }
@@ -9,6 +9,7 @@ use log::Level;
 use std::{boxed::Box, io::Write};
 use structopt::StructOpt;
 
+mod audit_deps;
 mod bench;
 mod build;
 mod cargo;
@@ -40,6 +41,12 @@ struct Args {
 
 #[derive(Debug, StructOpt)]
 enum Command {
+    /// Audit the third-party dependencies added or changed since merge base with the given commit
+    ///
+    /// Reports the source and license of each dependency, the known advisories against it and the
+    /// packages depending on it, and fails if a license isn't allowed or an advisory is found.
+    #[structopt(name = "audit-deps")]
+    AuditDeps(audit_deps::Args),
     #[structopt(name = "bench")]
     /// Run `cargo bench`
     Bench(bench::Args),
@@ -72,6 +79,10 @@ enum Command {
     #[structopt(name = "nextest")]
     /// Run tests with new test runner
     Nextest(nextest::Args),
+    /// Run a test binary, killing it once it runs past a timeout. `x nextest` runs each test
+    /// binary through it.
+    #[structopt(name = "nextest-watchdog", setting = structopt::clap::AppSettings::Hidden)]
+    NextestWatchdog(nextest::WatchdogArgs),
     #[structopt(name = "tools")]
     /// Run tests
     Tools(tools::Args),
@@ -114,12 +125,19 @@ fn main() -> Result<()> {
         .init();
 
     let args = Args::from_args();
+    let cmd = match args.cmd {
+        // It runs once per test, so it doesn't load the workspace
+        Command::NextestWatchdog(args) => return nextest::run_watchdog(args),
+        cmd => cmd,
+    };
     let xctx = context::XContext::new()?;
 
-    match args.cmd {
+    match cmd {
+        Command::AuditDeps(args) => audit_deps::run(args, xctx),
         Command::Tools(args) => tools::run(args, xctx),
         Command::Test(args) => test::run(args, xctx),
         Command::Nextest(args) => nextest::run(args, xctx),
+        Command::NextestWatchdog(_) => unreachable!("run before loading the workspace"),
         Command::Build(args) => build::run(args, xctx),
         Command::ChangedSince(args) => changed_since::run(args, xctx),
         Command::Check(args) => check::run(args, xctx),
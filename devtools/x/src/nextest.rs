@@ -6,19 +6,27 @@ use crate::{
     context::XContext,
     Result,
 };
-use anyhow::{bail, Context};
+use anyhow::{bail, format_err, Context};
 use camino::Utf8PathBuf;
+use guppy::platform::Platform;
 use nextest_runner::{
     config::NextestConfig,
     partition::PartitionerBuilder,
-    reporter::{StatusLevel, TestOutputDisplay, TestReporterBuilder},
+    reporter::{StatusLevel, TestEvent, TestOutputDisplay, TestReporterBuilder},
     runner::TestRunnerBuilder,
     signal::SignalHandler,
     target_runner::TargetRunner,
     test_filter::{RunIgnored, TestFilterBuilder},
     test_list::{BinaryList, RustTestArtifact, TestList},
 };
-use std::{ffi::OsString, io::Cursor};
+use std::{
+    env,
+    ffi::OsString,
+    io::Cursor,
+    process::{self, Command},
+    thread,
+    time::{Duration, Instant},
+};
 use structopt::StructOpt;
 use supports_color::Stream;
 
@@ -49,6 +57,13 @@ pub struct Args {
     /// Test partition, e.g. hash:1/2 or count:2/3
     #[structopt(long)]
     partition: Option<PartitionerBuilder>,
+    /// Number of slow timeouts of the profile after which a test still running is killed and
+    /// fails, 0 to let tests run for as long as they take
+    #[structopt(long, default_value = "3")]
+    terminate_after: u32,
+    /// Number of slowest tests to report once the run is over, 0 to disable the report
+    #[structopt(long, default_value = "10")]
+    report_slowest: usize,
     #[structopt(name = "FILTERS", last = true)]
     filters: Vec<String>,
 }
@@ -170,13 +185,14 @@ pub fn run(args: Args, xctx: XContext) -> Result<()> {
     let test_filter = TestFilterBuilder::new(args.run_ignored, args.partition, &args.filters);
     let test_artifacts =
         RustTestArtifact::from_binary_list(package_graph, test_binaries, None, None)?;
-    let test_list = TestList::new(test_artifacts, &test_filter, &TargetRunner::empty())?;
+    let target_runner = watchdog_runner(profile.slow_timeout(), args.terminate_after)?;
+    let test_list = TestList::new(test_artifacts, &test_filter, &target_runner)?;
 
     let handler = SignalHandler::new().context("failed to install nextest signal handler")?;
-    let runner =
-        args.runner_opts
-            .to_builder()
-            .build(&test_list, &profile, handler, TargetRunner::empty());
+    let runner = args
+        .runner_opts
+        .to_builder()
+        .build(&test_list, &profile, handler, target_runner);
 
     let mut reporter = args.reporter_opts.to_builder().build(&test_list, &profile);
     if args.build_args.color.should_colorize(Stream::Stderr) {
@@ -184,10 +200,119 @@ pub fn run(args: Args, xctx: XContext) -> Result<()> {
     }
 
     let stderr = std::io::stderr();
-    let run_stats = runner.try_execute(|event| reporter.report_event(event, stderr.lock()))?;
+    let mut test_durations = Vec::new();
+    let run_stats = runner.try_execute(|event| {
+        if let TestEvent::TestFinished {
+            test_instance,
+            run_statuses,
+        } = &event
+        {
+            test_durations.push((
+                format!(
+                    "{} {}",
+                    test_instance.bin_info.binary_id, test_instance.name
+                ),
+                run_statuses.last_status().time_taken,
+            ));
+        }
+        reporter.report_event(event, stderr.lock())
+    })?;
+    report_slowest_tests(test_durations, args.report_slowest);
     if !run_stats.is_success() {
         bail!("test run failed");
     }
 
     Ok(())
 }
+
+/// Prints the `count` slowest tests of the run, so chronically slow tests can be spotted.
+fn report_slowest_tests(mut test_durations: Vec<(String, Duration)>, count: usize) {
+    if count == 0 || test_durations.is_empty() {
+        return;
+    }
+    test_durations.sort_by(|(_, a), (_, b)| b.cmp(a));
+    test_durations.truncate(count);
+
+    eprintln!("\n{} slowest tests:", test_durations.len());
+    for (test, duration) in test_durations {
+        eprintln!("{:>9.3}s {}", duration.as_secs_f64(), test);
+    }
+}
+
+/// Exit code of `x nextest-watchdog` when it kills the test it runs
+const TERMINATED_EXIT_CODE: i32 = 124;
+const WATCHDOG_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+#[derive(Debug, StructOpt)]
+pub struct WatchdogArgs {
+    /// Milliseconds after which the test is killed
+    #[structopt(long)]
+    timeout_ms: u64,
+    /// The test binary and its arguments
+    #[structopt(name = "COMMAND", last = true, required = true, parse(from_os_str))]
+    command: Vec<OsString>,
+}
+
+/// The target runner running each test binary through `x nextest-watchdog`, which kills the
+/// tests still running after `terminate_after` slow timeouts of the profile. nextest-runner
+/// can't terminate tests itself.
+fn watchdog_runner(slow_timeout: Duration, terminate_after: u32) -> Result<TargetRunner> {
+    if terminate_after == 0 {
+        return Ok(TargetRunner::empty());
+    }
+    let x = env::current_exe().context("failed to find the x binary")?;
+    let x = x
+        .to_str()
+        .filter(|x| !x.contains(char::is_whitespace))
+        // nextest splits the runner on whitespace
+        .ok_or_else(|| format_err!("cannot use {:?} as a test runner", x))?;
+    let host = Platform::current().context("failed to detect the host platform")?;
+    // nextest-runner picks the runner of the host the way cargo does
+    env::set_var(
+        format!(
+            "CARGO_TARGET_{}_RUNNER",
+            host.triple_str().to_uppercase().replace('-', "_")
+        ),
+        format!(
+            "{} nextest-watchdog --timeout-ms {} --",
+            x,
+            (slow_timeout * terminate_after).as_millis()
+        ),
+    );
+    Ok(TargetRunner::new(None)?)
+}
+
+/// Runs the test binary with its arguments, and kills it once it runs past the timeout. Exits
+/// with the exit code of the test, so that nextest sees its result.
+pub fn run_watchdog(args: WatchdogArgs) -> Result<()> {
+    let timeout = Duration::from_millis(args.timeout_ms);
+    let (program, program_args) = args
+        .command
+        .split_first()
+        .ok_or_else(|| format_err!("no test binary to run"))?;
+    let mut child = Command::new(program)
+        .args(program_args)
+        .spawn()
+        .with_context(|| format!("failed to run {:?}", program))?;
+
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if start.elapsed() >= timeout {
+            child
+                .kill()
+                .with_context(|| format!("failed to kill {:?}", program))?;
+            child.wait()?;
+            eprintln!(
+                "x nextest: terminated {:?} after {:?}",
+                program_args, timeout
+            );
+            process::exit(TERMINATED_EXIT_CODE);
+        }
+        thread::sleep(WATCHDOG_POLL_INTERVAL);
+    };
+    // A test killed by a signal has no exit code
+    process::exit(status.code().unwrap_or(1));
+}
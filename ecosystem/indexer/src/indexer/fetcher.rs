@@ -43,7 +43,7 @@ impl TransactionFetcher {
             loop {
                 let res = self
                     .client
-                    .get_transactions(Some(self.version), Some(TRANSACTION_FETCH_BATCH_SIZE))
+                    .get_full_transactions(Some(self.version), Some(TRANSACTION_FETCH_BATCH_SIZE))
                     .await;
                 match res {
                     Ok(response) => {
@@ -89,7 +89,7 @@ impl TransactionFetcher {
     /// In the event it can't, it will keep retrying every RETRY_TIME_MILLIS ms
     pub async fn fetch_version(&self, version: u64) -> Transaction {
         loop {
-            let res = self.client.get_transaction_by_version(version).await;
+            let res = self.client.get_full_transaction_by_version(version).await;
             match res {
                 Ok(response) => {
                     FETCHED_TRANSACTION.inc();
@@ -327,6 +327,18 @@ impl ParkingLotIndex {
         }
     }
 
+    /// Number of accounts with parked transactions
+    pub(crate) fn num_accounts(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Accounts with parked transactions, with the sequence numbers of these transactions
+    pub(crate) fn iter_accounts(&self) -> impl Iterator<Item = (&AccountAddress, &BTreeSet<u64>)> {
+        self.data
+            .iter()
+            .map(|(account, seq_nums)| (account, seq_nums))
+    }
+
     pub(crate) fn contains(&self, account: &AccountAddress, seq_num: &u64) -> bool {
         self.account_indices
             .get(account)
@@ -12,6 +12,7 @@ use crate::{
     },
     counters,
     logging::{LogEntry, LogSchema, TxnsLog},
+    MempoolQueueStatus, ParkedAccount,
 };
 use aptos_config::config::NodeConfig;
 use aptos_crypto::HashValue;
@@ -296,6 +297,19 @@ impl Mempool {
         self.transactions.timeline_range(start_id, end_id)
     }
 
+    pub(crate) fn queue_status(&self) -> MempoolQueueStatus {
+        MempoolQueueStatus {
+            size: self.transactions.size(),
+            capacity: self.transactions.capacity(),
+        }
+    }
+
+    /// Accounts with parked transactions, with the sequence numbers missing before them
+    pub(crate) fn parked_accounts(&self) -> Vec<ParkedAccount> {
+        self.transactions
+            .parked_accounts(&self.sequence_number_cache)
+    }
+
     pub fn gen_snapshot(&self) -> TxnsLog {
         self.transactions.gen_snapshot(&self.metrics_cache)
     }
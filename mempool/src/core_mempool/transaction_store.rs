@@ -12,6 +12,7 @@ use crate::{
     },
     counters,
     logging::{LogEntry, LogEvent, LogSchema, TxnsLog},
+    ParkedAccount,
 };
 use aptos_config::config::MempoolConfig;
 use aptos_crypto::HashValue;
@@ -23,6 +24,7 @@ use aptos_types::{
     transaction::SignedTransaction,
 };
 use std::{
+    cmp::max,
     collections::HashMap,
     ops::Bound,
     time::{Duration, SystemTime},
@@ -79,6 +81,15 @@ impl TransactionStore {
         }
     }
 
+    /// Number of transactions in the store
+    pub(crate) fn size(&self) -> usize {
+        self.system_ttl_index.size()
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.capacity
+    }
+
     /// Fetch transaction by account address + sequence_number.
     pub(crate) fn get(
         &self,
@@ -220,6 +231,7 @@ impl TransactionStore {
             counters::TRANSACTION_HASH_INDEX_LABEL,
             self.hash_index.len(),
         );
+        counters::CORE_MEMPOOL_PARKED_ACCOUNTS.set(self.parking_lot_index.num_accounts() as i64);
     }
 
     /// Checks if Mempool is full.
@@ -328,15 +340,24 @@ impl TransactionStore {
             }
 
             let mut parking_lot_txns = 0;
-            for (_, txn) in txns.range_mut((Bound::Excluded(min_seq), Bound::Unbounded)) {
+            let mut lowest_parked_seq = None;
+            for (seq, txn) in txns.range_mut((Bound::Excluded(min_seq), Bound::Unbounded)) {
                 match txn.timeline_state {
                     TimelineState::Ready(_) => {}
                     _ => {
                         self.parking_lot_index.insert(txn);
                         parking_lot_txns += 1;
+                        lowest_parked_seq.get_or_insert(*seq);
                     }
                 }
             }
+            // For sequential accounts `min_seq` is now the first sequence number missing
+            if let (AccountSequenceInfo::Sequential(_), Some(lowest_parked_seq)) =
+                (crsn_or_seqno, lowest_parked_seq)
+            {
+                counters::CORE_MEMPOOL_SEQUENCE_NUMBER_GAP
+                    .observe(lowest_parked_seq.saturating_sub(min_seq) as f64);
+            }
             trace!(
                 LogSchema::new(LogEntry::ProcessReadyTxns).account(*address),
                 first_ready_seq_num = crsn_or_seqno.min_seq(),
@@ -565,6 +586,46 @@ impl TransactionStore {
         txns_log
     }
 
+    /// Accounts with parked transactions, sorted by address. The sequence number of an account is
+    /// the highest of the one in `sequence_number_cache`, updated on commit, and the ones its
+    /// transactions were validated against.
+    pub(crate) fn parked_accounts(
+        &self,
+        sequence_number_cache: &TtlCache<AccountAddress, u64>,
+    ) -> Vec<ParkedAccount> {
+        let mut parked_accounts = self
+            .parking_lot_index
+            .iter_accounts()
+            .filter_map(|(address, parked)| {
+                let txns = self.transactions.get(address)?;
+                let lowest_parked_seq = *parked.iter().next()?;
+                let validated_seq = txns
+                    .values()
+                    .map(|txn| txn.sequence_info.account_sequence_number_type.min_seq())
+                    .max()
+                    .unwrap_or_default();
+                let account_seq = sequence_number_cache
+                    .get(address)
+                    .map_or(validated_seq, |seq| max(*seq, validated_seq));
+                let mut first_missing_seq = account_seq;
+                while txns.contains_key(&first_missing_seq) {
+                    first_missing_seq += 1;
+                }
+                Some(ParkedAccount {
+                    address: *address,
+                    account_sequence_number: account_seq,
+                    num_ready: (first_missing_seq - account_seq) as usize,
+                    first_missing_sequence_number: first_missing_seq,
+                    lowest_parked_sequence_number: lowest_parked_seq,
+                    gap: lowest_parked_seq.saturating_sub(first_missing_seq),
+                    num_parked: parked.len(),
+                })
+            })
+            .collect::<Vec<_>>();
+        parked_accounts.sort_by_key(|account| account.address);
+        parked_accounts
+    }
+
     #[cfg(test)]
     pub(crate) fn get_parking_lot_size(&self) -> usize {
         self.parking_lot_index.size()
@@ -4,8 +4,8 @@
 use aptos_config::network_id::{NetworkId, PeerNetworkId};
 use aptos_metrics::{
     register_histogram, register_histogram_vec, register_int_counter, register_int_counter_vec,
-    register_int_gauge_vec, DurationHistogram, HistogramTimer, HistogramVec, IntCounter,
-    IntCounterVec, IntGauge, IntGaugeVec,
+    register_int_gauge, register_int_gauge_vec, DurationHistogram, Histogram, HistogramTimer,
+    HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
 };
 use once_cell::sync::Lazy;
 use short_hex_str::AsShortHexStr;
@@ -92,6 +92,26 @@ pub fn core_mempool_index_size(label: &'static str, size: usize) {
         .set(size as i64)
 }
 
+/// Gauge tracking the number of accounts with txns in the parking lot of core mempool
+pub static CORE_MEMPOOL_PARKED_ACCOUNTS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "core_mempool_parked_accounts",
+        "Number of accounts with txns parked in core mempool"
+    )
+    .unwrap()
+});
+
+/// Histogram of the number of sequence numbers missing before the parked txns of an account, each
+/// time txns of the account get parked
+pub static CORE_MEMPOOL_SEQUENCE_NUMBER_GAP: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        "core_mempool_sequence_number_gap",
+        "Number of sequence numbers missing before the parked txns of an account",
+        vec![1.0, 2.0, 3.0, 5.0, 10.0, 20.0, 50.0, 100.0]
+    )
+    .unwrap()
+});
+
 /// Counter tracking number of txns removed from core mempool
 pub static CORE_MEMPOOL_REMOVED_TXNS: Lazy<IntCounter> = Lazy::new(|| {
     register_int_counter!(
@@ -330,6 +350,22 @@ pub fn shared_mempool_broadcast_type_inc(network_id: NetworkId, label: &str) {
         .inc();
 }
 
+/// Counter for number of transactions left out of broadcasts, as the peer already had them
+static SHARED_MEMPOOL_BROADCAST_SUPPRESSED_TXNS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "shared_mempool_broadcast_suppressed_txns",
+        "Number of transactions not broadcast to a peer, as it already had them",
+        &["network"]
+    )
+    .unwrap()
+});
+
+pub fn shared_mempool_broadcast_suppressed_inc(network_id: NetworkId, num_txns: usize) {
+    SHARED_MEMPOOL_BROADCAST_SUPPRESSED_TXNS
+        .with_label_values(&[network_id.as_str()])
+        .inc_by(num_txns as u64);
+}
+
 static SHARED_MEMPOOL_ACK_TYPE_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
     register_int_counter_vec!(
         "shared_mempool_ack_count",
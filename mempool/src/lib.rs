@@ -61,7 +61,8 @@ pub use shared_mempool::{
     bootstrap, network,
     types::{
         ConsensusRequest, ConsensusResponse, MempoolClientRequest, MempoolClientSender,
-        MempoolEventsReceiver, SubmissionStatus, TransactionSummary,
+        MempoolEventsReceiver, MempoolQueueStatus, ParkedAccount, SubmissionStatus,
+        TransactionSummary,
     },
 };
 #[cfg(any(test, feature = "fuzzing"))]
@@ -130,6 +130,8 @@ pub enum LogEntry {
     ReconfigUpdate,
     JsonRpc,
     GetTransaction,
+    GetQueueStatus,
+    GetParkedAccounts,
     GetBlock,
     Consensus,
     StateSyncCommit,
@@ -0,0 +1,158 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Filter of the transactions an upstream peer already has, because it broadcast them to us or we
+//! broadcast them to it, so that they aren't broadcast to it again. The filter lives in the peer's
+//! sync state, which `disable_peer` drops on disconnect, so a reconnecting peer starts with an
+//! empty filter.
+
+use aptos_crypto::HashValue;
+use std::convert::TryInto;
+
+/// Bits per remembered transaction, which with `NUM_BIT_INDICES` gives a false positive rate of
+/// about 1 in 15,000 per generation
+const BITS_PER_TXN: usize = 20;
+/// Bits set per transaction, optimal for `BITS_PER_TXN`: 20 * ln(2)
+const NUM_BIT_INDICES: u64 = 14;
+
+/// Bloom filter of the hashes of recently broadcast (or received) transactions of a peer.
+///
+/// Hashes are added to a current generation, which replaces the previous one once it holds
+/// `capacity` hashes, so the filter remembers at least the last `capacity` hashes and uses
+/// a bounded amount of memory: 2 * `capacity` * `BITS_PER_TXN` bits.
+///
+/// A false positive suppresses the broadcast of a transaction the peer doesn't have. The
+/// transaction still gets to the peer through the other nodes broadcasting to it, but a node
+/// with a single upstream peer relies on the rare false positives of both generations.
+#[derive(Clone, Debug)]
+pub(crate) struct BroadcastFilter {
+    capacity: usize,
+    current: Generation,
+    previous: Generation,
+}
+
+#[derive(Clone, Debug, Default)]
+struct Generation {
+    bits: Vec<u64>,
+    len: usize,
+}
+
+impl Generation {
+    fn new(capacity: usize) -> Self {
+        let num_bits = capacity * BITS_PER_TXN;
+        Generation {
+            bits: vec![0; (num_bits + 63) / 64],
+            len: 0,
+        }
+    }
+
+    /// Indices of the bits of `hash`, from double hashing over two 64-bit words of the hash,
+    /// which is already uniformly distributed
+    fn bit_indices(&self, hash: &HashValue) -> impl Iterator<Item = usize> {
+        let bytes: &[u8; HashValue::LENGTH] = hash.as_ref();
+        let h1 = u64::from_le_bytes(bytes[..8].try_into().expect("8 bytes"));
+        // An odd step never cycles back to the first index early
+        let h2 = u64::from_le_bytes(bytes[8..16].try_into().expect("8 bytes")) | 1;
+        let num_bits = (self.bits.len() * 64) as u64;
+        (0..NUM_BIT_INDICES).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % num_bits) as usize)
+    }
+
+    fn insert(&mut self, hash: &HashValue) {
+        for index in self.bit_indices(hash) {
+            self.bits[index / 64] |= 1u64 << (index % 64);
+        }
+        self.len += 1;
+    }
+
+    fn contains(&self, hash: &HashValue) -> bool {
+        self.len > 0
+            && self
+                .bit_indices(hash)
+                .all(|index| (self.bits[index / 64] & (1u64 << (index % 64))) != 0)
+    }
+}
+
+impl BroadcastFilter {
+    /// A filter remembering at least the last `capacity` hashes, which remembers none if
+    /// `capacity` is 0
+    pub fn new(capacity: usize) -> Self {
+        BroadcastFilter {
+            capacity,
+            current: Generation::new(capacity),
+            // Allocated on the first rotation
+            previous: Generation::default(),
+        }
+    }
+
+    pub fn insert(&mut self, hash: &HashValue) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.current.len >= self.capacity {
+            self.previous = std::mem::replace(&mut self.current, Generation::new(self.capacity));
+        }
+        self.current.insert(hash);
+    }
+
+    /// Whether `hash` was inserted recently, or is a false positive
+    pub fn contains(&self, hash: &HashValue) -> bool {
+        self.current.contains(hash) || self.previous.contains(hash)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn hashes(rng: &mut StdRng, n: usize) -> Vec<HashValue> {
+        (0..n).map(|_| HashValue::random_with_rng(rng)).collect()
+    }
+
+    #[test]
+    fn test_remembers_recent_hashes() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut filter = BroadcastFilter::new(1_000);
+        let inserted = hashes(&mut rng, 2_500);
+        for hash in &inserted {
+            filter.insert(hash);
+        }
+
+        // The last 1,000 hashes are always remembered, along with the rest of the previous generation
+        assert!(inserted[1_000..].iter().all(|hash| filter.contains(hash)));
+        // The first generation was forgotten, except for false positives
+        let remembered = inserted[..1_000]
+            .iter()
+            .filter(|hash| filter.contains(hash))
+            .count();
+        assert!(remembered < 5, "{} forgotten hashes remembered", remembered);
+    }
+
+    #[test]
+    fn test_false_positives() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut filter = BroadcastFilter::new(10_000);
+        for hash in hashes(&mut rng, 20_000) {
+            filter.insert(&hash);
+        }
+
+        // Both generations are full, for about 2 false positives in 15,000 lookups
+        let false_positives = hashes(&mut rng, 100_000)
+            .iter()
+            .filter(|hash| filter.contains(hash))
+            .count();
+        assert!(
+            false_positives < 50,
+            "{} false positives in 100,000 lookups",
+            false_positives
+        );
+    }
+
+    #[test]
+    fn test_disabled() {
+        let mut filter = BroadcastFilter::new(0);
+        let hash = HashValue::random();
+        filter.insert(&hash);
+        assert!(!filter.contains(&hash));
+    }
+}
@@ -147,6 +147,27 @@ async fn handle_client_request<V>(
                 ))
                 .await;
         }
+        MempoolClientRequest::GetQueueStatus(callback) => {
+            // Only takes the mempool lock briefly, so no need for a task
+            let status = smp.mempool.lock().queue_status();
+            if callback.send(status).is_err() {
+                error!(LogSchema::event_log(
+                    LogEntry::GetQueueStatus,
+                    LogEvent::CallbackFail
+                ));
+                counters::CLIENT_CALLBACK_FAIL.inc();
+            }
+        }
+        MempoolClientRequest::GetParkedAccounts(callback) => {
+            let parked_accounts = smp.mempool.lock().parked_accounts();
+            if callback.send(parked_accounts).is_err() {
+                error!(LogSchema::event_log(
+                    LogEntry::GetParkedAccounts,
+                    LogEvent::CallbackFail
+                ));
+                counters::CLIENT_CALLBACK_FAIL.inc();
+            }
+        }
     }
 }
 
@@ -1,6 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+mod broadcast_filter;
 pub mod network;
 mod runtime;
 pub(crate) mod types;
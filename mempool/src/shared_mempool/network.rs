@@ -17,6 +17,7 @@ use aptos_config::{
     config::{MempoolConfig, PeerRole, RoleType},
     network_id::{NetworkId, PeerNetworkId},
 };
+use aptos_crypto::HashValue;
 use aptos_infallible::Mutex;
 use aptos_logger::prelude::*;
 use aptos_types::{transaction::SignedTransaction, PeerId};
@@ -190,7 +191,13 @@ impl MempoolNetworkInterface {
             // If we have a new peer, let's insert new data, otherwise, let's just update the current state
             if is_new_peer {
                 counters::active_upstream_peers(&peer.network_id()).inc();
-                sync_states.insert(peer, PeerSyncState::new(metadata));
+                sync_states.insert(
+                    peer,
+                    PeerSyncState::new(
+                        metadata,
+                        self.mempool_config.shared_mempool_broadcast_filter_capacity,
+                    ),
+                );
             } else if let Some(peer_state) = sync_states.get_mut(&peer) {
                 peer_state.metadata = metadata;
             }
@@ -240,6 +247,25 @@ impl MempoolNetworkInterface {
         let _ = std::mem::replace(&mut *prioritized_peers, peers);
     }
 
+    /// Remembers that an upstream peer has the `transactions` it broadcast to us, so that they
+    /// aren't broadcast back to it
+    pub fn add_known_transactions(&self, peer: PeerNetworkId, transactions: &[SignedTransaction]) {
+        if self.mempool_config.shared_mempool_broadcast_filter_capacity == 0
+            || !self.is_upstream_peer(&peer, None)
+        {
+            return;
+        }
+        let txn_hashes: Vec<_> = transactions
+            .iter()
+            .map(|txn| txn.clone().committed_hash())
+            .collect();
+        if let Some(state) = self.sync_states.write_lock().get_mut(&peer) {
+            for hash in &txn_hashes {
+                state.broadcast_filter.insert(hash);
+            }
+        }
+    }
+
     pub fn is_upstream_peer(
         &self,
         peer: &PeerNetworkId,
@@ -354,12 +380,23 @@ impl MempoolNetworkInterface {
     /// * Expired -> This timed out waiting for a response and needs to be resent
     /// * Retry -> This received a response telling it to retry later
     /// * New -> There are no Expired or Retry broadcasts currently waiting
+    ///
+    /// New batches leave out the transactions the peer already has, and come with the hashes of
+    /// their transactions, to remember once they are sent.
     fn determine_broadcast_batch<V>(
         &self,
         peer: PeerNetworkId,
         scheduled_backoff: bool,
         smp: &mut SharedMempool<V>,
-    ) -> Result<(BatchId, Vec<SignedTransaction>, Option<&str>), BroadcastError>
+    ) -> Result<
+        (
+            BatchId,
+            Vec<SignedTransaction>,
+            Vec<HashValue>,
+            Option<&str>,
+        ),
+        BroadcastError,
+    >
     where
         V: TransactionValidation,
     {
@@ -420,7 +457,7 @@ impl MempoolNetworkInterface {
         }
         let retry_batch_id = state.broadcast_info.retry_batches.iter().rev().next();
 
-        let (batch_id, transactions, metric_label) =
+        let (batch_id, transactions, txn_hashes, metric_label) =
             match std::cmp::max(expired_batch_id, retry_batch_id) {
                 Some(id) => {
                     let metric_label = if Some(id) == expired_batch_id {
@@ -430,7 +467,7 @@ impl MempoolNetworkInterface {
                     };
 
                     let txns = mempool.timeline_range(id.0, id.1);
-                    (*id, txns, metric_label)
+                    (*id, txns, vec![], metric_label)
                 }
                 None => {
                     // Fresh broadcast
@@ -438,7 +475,35 @@ impl MempoolNetworkInterface {
                         state.timeline_id,
                         self.mempool_config.shared_mempool_batch_size,
                     );
-                    (BatchId(state.timeline_id, new_timeline_id), txns, None)
+                    let batch_id = BatchId(state.timeline_id, new_timeline_id);
+                    if self.mempool_config.shared_mempool_broadcast_filter_capacity == 0 {
+                        (batch_id, txns, vec![], None)
+                    } else {
+                        // Leave out the transactions the peer already has, because it sent them
+                        // to us or we already sent them to it since it connected
+                        let num_read = txns.len();
+                        let (txns, txn_hashes): (Vec<_>, Vec<_>) = txns
+                            .into_iter()
+                            .map(|txn| {
+                                let hash = txn.clone().committed_hash();
+                                (txn, hash)
+                            })
+                            .filter(|(_, hash)| !state.broadcast_filter.contains(hash))
+                            .unzip();
+                        let num_suppressed = num_read - txns.len();
+                        if num_suppressed > 0 {
+                            counters::shared_mempool_broadcast_suppressed_inc(
+                                peer.network_id(),
+                                num_suppressed,
+                            );
+                            if txns.is_empty() {
+                                // There's nothing to send from this part of the timeline, so
+                                // move past it rather than reading it again
+                                state.timeline_id = new_timeline_id;
+                            }
+                        }
+                        (batch_id, txns, txn_hashes, None)
+                    }
                 }
             };
 
@@ -446,7 +511,7 @@ impl MempoolNetworkInterface {
             return Err(BroadcastError::NoTransactions(peer));
         }
 
-        Ok((batch_id, transactions, metric_label))
+        Ok((batch_id, transactions, txn_hashes, metric_label))
     }
 
     /// Sends a batch to the given `Peer`
@@ -469,11 +534,12 @@ impl MempoolNetworkInterface {
     }
 
     /// Updates the local tracker for a broadcast.  This is used to handle `DirectSend` tracking of
-    /// responses
+    /// responses, and to not broadcast the transactions with `txn_hashes` again
     fn update_broadcast_state(
         &self,
         peer: PeerNetworkId,
         batch_id: BatchId,
+        txn_hashes: &[HashValue],
         send_time: SystemTime,
     ) -> Result<usize, BroadcastError> {
         let mut sync_states = self.sync_states.write_lock();
@@ -490,6 +556,9 @@ impl MempoolNetworkInterface {
             .sent_batches
             .insert(batch_id, send_time);
         state.broadcast_info.retry_batches.remove(&batch_id);
+        for hash in txn_hashes {
+            state.broadcast_filter.insert(hash);
+        }
         Ok(state.broadcast_info.sent_batches.len())
     }
 
@@ -504,13 +573,14 @@ impl MempoolNetworkInterface {
     {
         // Start timer for tracking broadcast latency.
         let start_time = Instant::now();
-        let (batch_id, transactions, metric_label) =
+        let (batch_id, transactions, txn_hashes, metric_label) =
             self.determine_broadcast_batch(peer, scheduled_backoff, smp)?;
 
         let num_txns = transactions.len();
         let send_time = SystemTime::now();
         self.send_batch(peer, batch_id, transactions).await?;
-        let num_pending_broadcasts = self.update_broadcast_state(peer, batch_id, send_time)?;
+        let num_pending_broadcasts =
+            self.update_broadcast_state(peer, batch_id, &txn_hashes, send_time)?;
         notify_subscribers(SharedMempoolNotification::Broadcast, &smp.subscribers);
 
         // Log all the metrics
@@ -3,8 +3,9 @@
 
 //! Objects used by/related to shared mempool
 use crate::{
-    core_mempool::CoreMempool, network::MempoolNetworkInterface,
-    shared_mempool::network::MempoolNetworkSender,
+    core_mempool::CoreMempool,
+    network::MempoolNetworkInterface,
+    shared_mempool::{broadcast_filter::BroadcastFilter, network::MempoolNetworkSender},
 };
 use anyhow::Result;
 use aptos_config::{
@@ -213,9 +214,39 @@ pub type SubmissionStatus = (MempoolStatus, Option<DiscardedVMStatus>);
 
 pub type SubmissionStatusBundle = (SignedTransaction, SubmissionStatus);
 
+/// Occupancy of mempool, for clients to push back on submissions before mempool is full
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MempoolQueueStatus {
+    /// Number of transactions in mempool
+    pub size: usize,
+    /// Maximum number of transactions in mempool
+    pub capacity: usize,
+}
+
+/// An account whose transactions are parked in mempool, i.e. held back from consensus until the
+/// sequence numbers missing before them show up. A transaction "stuck" in mempool is usually
+/// behind such a gap: an earlier transaction of the account never reached this node or expired.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ParkedAccount {
+    pub address: AccountAddress,
+    /// Sequence number of the account as last seen by mempool, i.e. of its next transaction to
+    /// commit
+    pub account_sequence_number: u64,
+    /// Number of transactions of the account ready for consensus, from its sequence number on
+    pub num_ready: usize,
+    /// Lowest sequence number from the account's one which mempool doesn't have
+    pub first_missing_sequence_number: u64,
+    pub lowest_parked_sequence_number: u64,
+    /// Number of sequence numbers missing before the lowest parked transaction
+    pub gap: u64,
+    pub num_parked: usize,
+}
+
 pub enum MempoolClientRequest {
     SubmitTransaction(SignedTransaction, oneshot::Sender<Result<SubmissionStatus>>),
     GetTransactionByHash(HashValue, oneshot::Sender<Option<SignedTransaction>>),
+    GetQueueStatus(oneshot::Sender<MempoolQueueStatus>),
+    GetParkedAccounts(oneshot::Sender<Vec<ParkedAccount>>),
 }
 
 pub type MempoolClientSender = mpsc::Sender<MempoolClientRequest>;
@@ -224,19 +255,22 @@ pub type MempoolEventsReceiver = mpsc::Receiver<MempoolClientRequest>;
 /// State of last sync with peer:
 /// `timeline_id` is position in log of ready transactions
 /// `is_alive` - is connection healthy
+/// `broadcast_filter` - recent transactions the peer already has, which aren't broadcast to it again
 #[derive(Clone, Debug)]
 pub(crate) struct PeerSyncState {
     pub timeline_id: u64,
     pub broadcast_info: BroadcastInfo,
     pub metadata: ConnectionMetadata,
+    pub broadcast_filter: BroadcastFilter,
 }
 
 impl PeerSyncState {
-    pub fn new(metadata: ConnectionMetadata) -> Self {
+    pub fn new(metadata: ConnectionMetadata, broadcast_filter_capacity: usize) -> Self {
         PeerSyncState {
             timeline_id: 0,
             broadcast_info: BroadcastInfo::new(),
             metadata,
+            broadcast_filter: BroadcastFilter::new(broadcast_filter_capacity),
         }
     }
 }
@@ -7,6 +7,7 @@ use crate::{
         add_signed_txn, add_txn, add_txns_to_mempool, exist_in_metrics_cache, setup_mempool,
         TestTransaction,
     },
+    ParkedAccount,
 };
 use aptos_config::config::NodeConfig;
 use aptos_crypto::HashValue;
@@ -466,6 +467,59 @@ fn test_timeline() {
     assert_eq!(0, pool.get_parking_lot_size());
 }
 
+#[test]
+fn test_parked_accounts() {
+    let mut pool = setup_mempool().0;
+    add_txns_to_mempool(
+        &mut pool,
+        vec![
+            TestTransaction::new(0, 0, 1),
+            TestTransaction::new(1, 0, 1),
+            TestTransaction::new(1, 1, 1),
+            TestTransaction::new(1, 4, 1),
+            TestTransaction::new(1, 5, 1),
+        ],
+    );
+    // Account 0 has nothing parked, account 1 is missing txns 2 and 3.
+    let expected = ParkedAccount {
+        address: TestTransaction::get_address(1),
+        account_sequence_number: 0,
+        num_ready: 2,
+        first_missing_sequence_number: 2,
+        lowest_parked_sequence_number: 4,
+        gap: 2,
+        num_parked: 2,
+    };
+    assert_eq!(pool.parked_accounts(), vec![expected.clone()]);
+
+    // The account sequence number follows commits.
+    pool.remove_transaction(&TestTransaction::get_address(1), 0, false);
+    assert_eq!(
+        pool.parked_accounts(),
+        vec![ParkedAccount {
+            account_sequence_number: 1,
+            num_ready: 1,
+            ..expected.clone()
+        }]
+    );
+
+    // Txn 3 narrows the gap, txn 2 closes it.
+    add_txns_to_mempool(&mut pool, vec![TestTransaction::new(1, 3, 1)]);
+    assert_eq!(
+        pool.parked_accounts(),
+        vec![ParkedAccount {
+            account_sequence_number: 1,
+            num_ready: 1,
+            lowest_parked_sequence_number: 3,
+            gap: 1,
+            num_parked: 3,
+            ..expected
+        }]
+    );
+    add_txns_to_mempool(&mut pool, vec![TestTransaction::new(1, 2, 1)]);
+    assert!(pool.parked_accounts().is_empty());
+}
+
 #[test]
 fn test_capacity() {
     let mut config = NodeConfig::random();
@@ -187,6 +187,9 @@ impl NetworkBuilder {
 
         let trusted_peers = Arc::new(RwLock::new(HashMap::new()));
 
+        peer_metadata_storage
+            .set_reputation_config(config.network_id, config.peer_reputation.clone());
+
         let mut network_builder = NetworkBuilder::new(
             chain_id,
             trusted_peers.clone(),
@@ -18,7 +18,7 @@ use std::{
     convert::TryFrom,
     fmt::Debug,
     io,
-    net::SocketAddr,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
     pin::Pin,
     task::{Context, Poll},
 };
@@ -152,7 +152,7 @@ pub async fn resolve_and_connect(addr: NetworkAddress) -> io::Result<TcpStream>
     if let Some(((ipaddr, port), _addr_suffix)) = parse_ip_tcp(protos) {
         // this is an /ip4 or /ip6 address, so we can just connect without any
         // extra resolving or filtering.
-        TcpStream::connect((ipaddr, port)).await
+        TcpStream::connect((dialable_ip(ipaddr), port)).await
     } else if let Some(((ip_filter, dns_name, port), _addr_suffix)) = parse_dns_tcp(protos) {
         // resolve dns name and filter
         let socketaddr_iter = resolve_with_filter(ip_filter, dns_name.as_ref(), port).await?;
@@ -181,6 +181,17 @@ pub async fn resolve_and_connect(addr: NetworkAddress) -> io::Result<TcpStream>
     }
 }
 
+/// Nodes may advertise their listen address as is, e.g. `/ip4/0.0.0.0/tcp/6180` in local swarms.
+/// Dialing the unspecified address reaches this host on unix, but fails on windows, so dial
+/// loopback instead.
+fn dialable_ip(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V4(ip) if ip.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        IpAddr::V6(ip) if ip.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+        ip => ip,
+    }
+}
+
 async fn connect_via_proxy(proxy_addr: String, addr: NetworkAddress) -> io::Result<TcpStream> {
     let protos = addr.as_slice();
 
@@ -322,7 +333,7 @@ impl AsyncWrite for TcpSocket {
 mod test {
     use super::*;
     use crate::transport::{ConnectionOrigin, Transport, TransportExt};
-    use aptos_types::PeerId;
+    use aptos_types::{network_address::Protocol, PeerId};
     use futures::{
         future::{join, FutureExt},
         io::{AsyncReadExt, AsyncWriteExt},
@@ -363,6 +374,28 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn dial_unspecified_address() -> Result<(), ::std::io::Error> {
+        let t = TcpTransport::default();
+        let (listener, addr) = t.listen_on("/ip4/127.0.0.1/tcp/0".parse().unwrap())?;
+        let port = match addr.as_slice() {
+            [_, Protocol::Tcp(port)] => *port,
+            _ => panic!("Unexpected listen address {}", addr),
+        };
+
+        // The unspecified address is dialed as loopback
+        let addr: NetworkAddress = format!("/ip4/0.0.0.0/tcp/{}", port).parse().unwrap();
+        let dial = t.dial(PeerId::random(), addr)?;
+        let listener = listener.into_future().then(|(maybe_result, _stream)| {
+            let (incoming, _addr) = maybe_result.unwrap().unwrap();
+            incoming.map(Result::unwrap)
+        });
+
+        let (outgoing, _incoming) = join(dial, listener).await;
+        assert!(outgoing.is_ok());
+        Ok(())
+    }
+
     #[test]
     fn unsupported_multiaddrs() {
         let t = TcpTransport::default();
@@ -111,4 +111,19 @@ impl<TMessage: Clone + Message + Send, Sender: ApplicationNetworkSender<TMessage
             .send_rpc(recipient.peer_id(), req_msg, timeout)
             .await
     }
+
+    /// Like `send_rpc`, but also returns the size of the response in bytes
+    pub async fn send_rpc_with_response_size(
+        &self,
+        recipient: PeerNetworkId,
+        req_msg: TMessage,
+        timeout: Duration,
+    ) -> Result<(TMessage, usize), RpcError>
+    where
+        Sender: Sync,
+    {
+        self.sender(&recipient.network_id())
+            .send_rpc_with_response_size(recipient.peer_id(), req_msg, timeout)
+            .await
+    }
 }
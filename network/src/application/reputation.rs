@@ -0,0 +1,202 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Peer reputation tracks misbehavior of the peers of a network and temporarily bans peers whose
+//! accumulated penalty crosses the configured threshold.
+//!
+//! Penalties decay over time, so occasional misbehavior, e.g. a single bad message after an
+//! upgrade, doesn't lead to a ban. Only the peers of the public and VFN networks can be banned:
+//! the peers of the validator network are the trusted validators of the set, and banning one
+//! would only hurt consensus. Nor are the peers in the operator's exempt list ever banned.
+//! Misbehavior of the peers which can't be banned is still counted.
+
+use crate::counters;
+use aptos_config::{config::PeerReputationConfig, network_id::NetworkId};
+use aptos_types::PeerId;
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    time::{Duration, Instant},
+};
+
+/// Kinds of misbehavior which count towards banning a peer
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Misbehavior {
+    /// The peer sent a message we couldn't deserialize
+    InvalidMessage,
+    /// The peer sent data which failed proof verification
+    InvalidProof,
+    /// The peer reconnected too often
+    ConnectionChurn,
+}
+
+impl Misbehavior {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Misbehavior::InvalidMessage => "invalid_message",
+            Misbehavior::InvalidProof => "invalid_proof",
+            Misbehavior::ConnectionChurn => "connection_churn",
+        }
+    }
+}
+
+impl fmt::Display for Misbehavior {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug)]
+struct PeerScore {
+    /// Accumulated penalty, decaying over time
+    penalty: u64,
+    /// Last time the penalty decayed
+    last_decay: Instant,
+    /// Start times of the recent connections of the peer
+    recent_connections: VecDeque<Instant>,
+    /// End of the current ban, if the peer is banned
+    banned_until: Option<Instant>,
+}
+
+impl PeerScore {
+    fn new(now: Instant) -> Self {
+        Self {
+            penalty: 0,
+            last_decay: now,
+            recent_connections: VecDeque::new(),
+            banned_until: None,
+        }
+    }
+
+    fn decay(&mut self, now: Instant, penalty_decay_per_sec: u64) {
+        let elapsed_secs = now.saturating_duration_since(self.last_decay).as_secs();
+        if elapsed_secs > 0 {
+            self.penalty = self
+                .penalty
+                .saturating_sub(elapsed_secs.saturating_mul(penalty_decay_per_sec));
+            self.last_decay += Duration::from_secs(elapsed_secs);
+        }
+    }
+
+    fn is_banned(&self, now: Instant) -> bool {
+        self.banned_until.map_or(false, |until| now < until)
+    }
+}
+
+/// Reputation of the peers of a single network
+#[derive(Debug)]
+pub struct PeerReputation {
+    network_id: NetworkId,
+    config: PeerReputationConfig,
+    scores: HashMap<PeerId, PeerScore>,
+}
+
+impl PeerReputation {
+    pub fn new(network_id: NetworkId, config: PeerReputationConfig) -> Self {
+        Self {
+            network_id,
+            config,
+            scores: HashMap::new(),
+        }
+    }
+
+    pub fn set_config(&mut self, config: PeerReputationConfig) {
+        self.config = config;
+    }
+
+    /// Penalizes the peer for the misbehavior, returning true if this got the peer banned
+    pub fn report_misbehavior(
+        &mut self,
+        peer_id: PeerId,
+        misbehavior: Misbehavior,
+        now: Instant,
+    ) -> bool {
+        counters::peer_misbehavior(self.network_id, misbehavior).inc();
+        let penalty = match misbehavior {
+            Misbehavior::InvalidMessage => self.config.invalid_message_penalty,
+            Misbehavior::InvalidProof => self.config.invalid_proof_penalty,
+            Misbehavior::ConnectionChurn => self.config.connection_churn_penalty,
+        };
+        let score = self
+            .scores
+            .entry(peer_id)
+            .or_insert_with(|| PeerScore::new(now));
+        score.decay(now, self.config.penalty_decay_per_sec);
+        score.penalty = score.penalty.saturating_add(penalty);
+
+        if !self.can_ban(&peer_id)
+            || score.is_banned(now)
+            || score.penalty < self.config.ban_threshold
+        {
+            return false;
+        }
+        score.banned_until = Some(now + Duration::from_secs(self.config.ban_duration_secs));
+        // Start over once the ban is lifted
+        score.penalty = 0;
+        counters::peer_bans(self.network_id).inc();
+        true
+    }
+
+    /// Records a new connection of the peer, penalizing it if it reconnects too often. Returns
+    /// true if this got the peer banned.
+    pub fn record_connection(&mut self, peer_id: PeerId, now: Instant) -> bool {
+        let window = Duration::from_secs(self.config.connection_churn_window_secs);
+        let score = self
+            .scores
+            .entry(peer_id)
+            .or_insert_with(|| PeerScore::new(now));
+        while let Some(connected_at) = score.recent_connections.front() {
+            if now.saturating_duration_since(*connected_at) < window {
+                break;
+            }
+            score.recent_connections.pop_front();
+        }
+        score.recent_connections.push_back(now);
+
+        if score.recent_connections.len() > self.config.max_connections_per_window {
+            score.recent_connections.clear();
+            self.report_misbehavior(peer_id, Misbehavior::ConnectionChurn, now)
+        } else {
+            false
+        }
+    }
+
+    pub fn is_banned(&self, peer_id: &PeerId, now: Instant) -> bool {
+        self.can_ban(peer_id)
+            && self
+                .scores
+                .get(peer_id)
+                .map_or(false, |score| score.is_banned(now))
+    }
+
+    fn can_ban(&self, peer_id: &PeerId) -> bool {
+        self.config.enabled
+            && !self.network_id.is_validator_network()
+            && !self.config.ban_exempt_peers.contains(peer_id)
+    }
+
+    /// Returns the peers currently banned, and forgets peers with nothing left to remember
+    pub fn banned_peers(&mut self, now: Instant) -> Vec<PeerId> {
+        let penalty_decay_per_sec = self.config.penalty_decay_per_sec;
+        let window = Duration::from_secs(self.config.connection_churn_window_secs);
+        self.scores.retain(|_, score| {
+            score.decay(now, penalty_decay_per_sec);
+            score.penalty > 0
+                || score.is_banned(now)
+                || score
+                    .recent_connections
+                    .back()
+                    .map_or(false, |connected_at| {
+                        now.saturating_duration_since(*connected_at) < window
+                    })
+        });
+        let banned: Vec<_> = self
+            .scores
+            .keys()
+            .filter(|peer_id| self.is_banned(peer_id, now))
+            .copied()
+            .collect();
+        counters::banned_peers(self.network_id).set(banned.len() as i64);
+        banned
+    }
+}
@@ -2,17 +2,25 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    application::types::{PeerError, PeerInfo},
+    application::{
+        reputation::{Misbehavior, PeerReputation},
+        types::{PeerError, PeerInfo},
+    },
     transport::ConnectionMetadata,
 };
-use aptos_config::network_id::{NetworkId, PeerNetworkId};
-use aptos_infallible::{RwLock, RwLockWriteGuard};
+use aptos_config::{
+    config::PeerReputationConfig,
+    network_id::{NetworkId, PeerNetworkId},
+};
+use aptos_infallible::{Mutex, RwLock, RwLockWriteGuard};
+use aptos_logger::prelude::*;
 use aptos_types::{account_address::AccountAddress, PeerId};
 use std::{
     collections::{hash_map::Entry, HashMap},
     fmt::Debug,
     hash::Hash,
     sync::Arc,
+    time::Instant,
 };
 
 /// Metadata storage for peers across all of networking.  Splits storage of information across
@@ -20,6 +28,7 @@ use std::{
 #[derive(Debug)]
 pub struct PeerMetadataStorage {
     storage: HashMap<NetworkId, LockingHashMap<PeerId, PeerInfo>>,
+    reputations: HashMap<NetworkId, Mutex<PeerReputation>>,
 }
 
 impl PeerMetadataStorage {
@@ -32,11 +41,19 @@ impl PeerMetadataStorage {
     pub fn new(network_ids: &[NetworkId]) -> Arc<PeerMetadataStorage> {
         let mut peer_metadata_storage = PeerMetadataStorage {
             storage: HashMap::new(),
+            reputations: HashMap::new(),
         };
         network_ids.iter().for_each(|network_id| {
             peer_metadata_storage
                 .storage
                 .insert(*network_id, LockingHashMap::new());
+            peer_metadata_storage.reputations.insert(
+                *network_id,
+                Mutex::new(PeerReputation::new(
+                    *network_id,
+                    PeerReputationConfig::default(),
+                )),
+            );
         });
         Arc::new(peer_metadata_storage)
     }
@@ -141,6 +158,66 @@ impl PeerMetadataStorage {
     }
 }
 
+/// Peer reputation, shared by all components of a network so that any of them can report
+/// misbehavior
+impl PeerMetadataStorage {
+    fn get_reputation(&self, network_id: NetworkId) -> &Mutex<PeerReputation> {
+        self.reputations
+            .get(&network_id)
+            .unwrap_or_else(|| panic!("Unexpected network requested: {}", network_id))
+    }
+
+    /// Replaces the default reputation config of the network
+    pub fn set_reputation_config(&self, network_id: NetworkId, config: PeerReputationConfig) {
+        self.get_reputation(network_id).lock().set_config(config)
+    }
+
+    /// Penalizes the peer for the misbehavior, it is banned once it misbehaved too much
+    pub fn report_misbehavior(&self, peer_network_id: PeerNetworkId, misbehavior: Misbehavior) {
+        let banned = self
+            .get_reputation(peer_network_id.network_id())
+            .lock()
+            .report_misbehavior(peer_network_id.peer_id(), misbehavior, Instant::now());
+        if banned {
+            warn!(
+                "Banning peer {} after misbehavior: {}",
+                peer_network_id, misbehavior
+            );
+        }
+    }
+
+    /// Records a new connection of the peer, to detect peers reconnecting too often
+    pub fn record_connection(&self, peer_network_id: PeerNetworkId) {
+        let banned = self
+            .get_reputation(peer_network_id.network_id())
+            .lock()
+            .record_connection(peer_network_id.peer_id(), Instant::now());
+        if banned {
+            warn!(
+                "Banning peer {} after misbehavior: {}",
+                peer_network_id,
+                Misbehavior::ConnectionChurn
+            );
+        }
+    }
+
+    pub fn is_banned(&self, peer_network_id: &PeerNetworkId) -> bool {
+        self.get_reputation(peer_network_id.network_id())
+            .lock()
+            .is_banned(&peer_network_id.peer_id(), Instant::now())
+    }
+
+    /// The peers of the network which are currently banned
+    pub fn banned_peers(&self, network_id: NetworkId) -> Vec<PeerNetworkId> {
+        self.get_reputation(network_id)
+            .lock()
+            .banned_peers(Instant::now())
+            .into_iter()
+            .map(|peer_id| PeerNetworkId::new(network_id, peer_id))
+            .collect()
+    }
+}
+
 fn to_peer_network_ids(
     network_id: NetworkId,
     map: HashMap<PeerId, PeerInfo>,
@@ -4,15 +4,23 @@
 use crate::{
     application::{
         interface::NetworkInterface,
+        reputation::{Misbehavior, PeerReputation},
         storage::{LockingHashMap, PeerMetadataStorage},
         types::{PeerError, PeerState},
     },
     protocols::health_checker::HealthCheckerMsg,
     transport::ConnectionMetadata,
 };
-use aptos_config::network_id::{NetworkId, PeerNetworkId};
+use aptos_config::{
+    config::PeerReputationConfig,
+    network_id::{NetworkId, PeerNetworkId},
+};
 use aptos_types::PeerId;
-use std::{collections::hash_map::Entry, sync::Arc};
+use std::{
+    collections::hash_map::Entry,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 #[derive(Clone)]
 struct DummySender {}
@@ -95,3 +103,141 @@ fn update_state(
         })
         .unwrap()
 }
+
+fn reputation_config() -> PeerReputationConfig {
+    PeerReputationConfig {
+        invalid_message_penalty: 10,
+        invalid_proof_penalty: 50,
+        connection_churn_penalty: 100,
+        max_connections_per_window: 2,
+        connection_churn_window_secs: 60,
+        penalty_decay_per_sec: 1,
+        ban_threshold: 100,
+        ban_duration_secs: 600,
+        ..PeerReputationConfig::default()
+    }
+}
+
+#[test]
+fn test_reputation_ban() {
+    let mut reputation = PeerReputation::new(NetworkId::Public, reputation_config());
+    let peer = PeerId::random();
+    let other_peer = PeerId::random();
+    let now = Instant::now();
+
+    // The peer is banned once its penalty reaches the threshold
+    assert!(!reputation.report_misbehavior(peer, Misbehavior::InvalidProof, now));
+    assert!(!reputation.is_banned(&peer, now));
+    assert!(reputation.report_misbehavior(peer, Misbehavior::InvalidProof, now));
+    assert!(reputation.is_banned(&peer, now));
+    assert!(!reputation.is_banned(&other_peer, now));
+    assert_eq!(reputation.banned_peers(now), vec![peer]);
+
+    // Until the ban is lifted
+    let later = now + Duration::from_secs(600);
+    assert!(!reputation.is_banned(&peer, later));
+    assert!(reputation.banned_peers(later).is_empty());
+}
+
+#[test]
+fn test_reputation_penalty_decay() {
+    let mut reputation = PeerReputation::new(NetworkId::Public, reputation_config());
+    let peer = PeerId::random();
+    let now = Instant::now();
+
+    assert!(!reputation.report_misbehavior(peer, Misbehavior::InvalidProof, now));
+    // Enough time has passed for part of the penalty to be forgiven
+    let later = now + Duration::from_secs(10);
+    assert!(!reputation.report_misbehavior(peer, Misbehavior::InvalidProof, later));
+    assert!(!reputation.is_banned(&peer, later));
+    assert!(reputation.report_misbehavior(peer, Misbehavior::InvalidMessage, later));
+    assert!(reputation.is_banned(&peer, later));
+}
+
+#[test]
+fn test_reputation_connection_churn() {
+    let mut reputation = PeerReputation::new(NetworkId::Public, reputation_config());
+    let peer = PeerId::random();
+    let now = Instant::now();
+
+    // Reconnecting slowly is fine
+    for i in 0..5 {
+        assert!(!reputation.record_connection(peer, now + Duration::from_secs(60 * i)));
+    }
+
+    // But not too often within the window
+    let later = now + Duration::from_secs(600);
+    assert!(!reputation.record_connection(peer, later));
+    assert!(!reputation.record_connection(peer, later));
+    assert!(reputation.record_connection(peer, later));
+    assert!(reputation.is_banned(&peer, later));
+}
+
+#[test]
+fn test_reputation_overrides() {
+    let exempt_peer = PeerId::random();
+    let mut config = reputation_config();
+    config.ban_exempt_peers.insert(exempt_peer);
+    let mut reputation = PeerReputation::new(NetworkId::Public, config);
+    let now = Instant::now();
+
+    // Exempt peers are never banned
+    for _ in 0..10 {
+        assert!(!reputation.report_misbehavior(exempt_peer, Misbehavior::InvalidProof, now));
+    }
+    assert!(!reputation.is_banned(&exempt_peer, now));
+
+    // Nobody is banned while banning is disabled
+    let peer = PeerId::random();
+    reputation.set_config(PeerReputationConfig {
+        enabled: false,
+        ..reputation_config()
+    });
+    for _ in 0..10 {
+        assert!(!reputation.report_misbehavior(peer, Misbehavior::InvalidProof, now));
+    }
+    assert!(!reputation.is_banned(&peer, now));
+}
+
+#[test]
+fn test_reputation_validators_never_banned() {
+    let mut reputation = PeerReputation::new(NetworkId::Validator, reputation_config());
+    let validator = PeerId::random();
+    let now = Instant::now();
+
+    // Misbehaving validators are never banned, however much and however often they misbehave
+    for _ in 0..10 {
+        assert!(!reputation.report_misbehavior(validator, Misbehavior::InvalidProof, now));
+    }
+    for _ in 0..10 {
+        assert!(!reputation.record_connection(validator, now));
+    }
+    assert!(!reputation.is_banned(&validator, now));
+    assert!(reputation.banned_peers(now).is_empty());
+}
+
+#[test]
+fn test_storage_reputation() {
+    let peer_metadata_storage =
+        PeerMetadataStorage::new(&[NetworkId::Validator, NetworkId::Public]);
+    for network_id in [NetworkId::Validator, NetworkId::Public] {
+        peer_metadata_storage.set_reputation_config(network_id, reputation_config());
+    }
+    let peer = PeerNetworkId::new(NetworkId::Public, PeerId::random());
+    let validator = PeerNetworkId::new(NetworkId::Validator, PeerId::random());
+
+    for peer in [peer, validator] {
+        peer_metadata_storage.report_misbehavior(peer, Misbehavior::InvalidProof);
+        assert!(!peer_metadata_storage.is_banned(&peer));
+        peer_metadata_storage.report_misbehavior(peer, Misbehavior::InvalidProof);
+    }
+    assert!(peer_metadata_storage.is_banned(&peer));
+    assert_eq!(
+        peer_metadata_storage.banned_peers(NetworkId::Public),
+        vec![peer]
+    );
+    assert!(!peer_metadata_storage.is_banned(&validator));
+    assert!(peer_metadata_storage
+        .banned_peers(NetworkId::Validator)
+        .is_empty());
+}
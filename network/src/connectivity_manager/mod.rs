@@ -689,6 +689,8 @@ where
         src: DiscoverySource,
         new_discovered_peers: PeerSet,
     ) {
+        let _timer =
+            counters::discovery_update_time(&self.network_context, &src.to_string()).start_timer();
         let self_peer_id = self.network_context.peer_id();
         let mut keys_updated = false;
 
@@ -13,6 +13,8 @@ pub const INBOUND_RPC_TIMEOUT_MS: u64 = 10_000;
 pub const MAX_CONCURRENT_OUTBOUND_RPCS: u32 = 100;
 /// Limit on concurrent Inbound RPC requests before backpressure is applied
 pub const MAX_CONCURRENT_INBOUND_RPCS: u32 = 100;
+/// How often the PeerManager closes connections with peers banned for misbehaving
+pub const BAN_CHECK_INTERVAL_MS: u64 = 1_000;
 
 // These are only used in tests
 // TODO: Fix this so the tests and the defaults in config are the same
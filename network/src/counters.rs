@@ -1,8 +1,8 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::protocols::wire::handshake::v1::ProtocolId;
-use aptos_config::network_id::NetworkContext;
+use crate::{application::reputation::Misbehavior, protocols::wire::handshake::v1::ProtocolId};
+use aptos_config::network_id::{NetworkContext, NetworkId};
 use aptos_metrics::{
     register_histogram_vec, register_int_counter_vec, register_int_gauge, register_int_gauge_vec,
     Histogram, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
@@ -146,6 +146,26 @@ pub fn connection_upgrade_time(
     ])
 }
 
+/// Time for the connectivity manager to apply an update of the discovered peers, e.g. the
+/// validator set of a new epoch
+pub static APTOS_NETWORK_DISCOVERY_UPDATE_TIME: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "aptos_network_discovery_update_time_seconds",
+        "Time to apply an update of the discovered peers",
+        &["role_type", "network_id", "peer_id", "discovery_source"]
+    )
+    .unwrap()
+});
+
+pub fn discovery_update_time(network_context: &NetworkContext, source: &str) -> Histogram {
+    APTOS_NETWORK_DISCOVERY_UPDATE_TIME.with_label_values(&[
+        network_context.role().as_str(),
+        network_context.network_id().as_str(),
+        network_context.peer_id().short_str().as_str(),
+        source,
+    ])
+}
+
 pub static APTOS_NETWORK_DISCOVERY_NOTES: Lazy<IntGaugeVec> = Lazy::new(|| {
     register_int_gauge_vec!(
         "aptos_network_discovery_notes",
@@ -210,6 +230,45 @@ pub static INVALID_NETWORK_MESSAGES: Lazy<IntCounterVec> = Lazy::new(|| {
     .unwrap()
 });
 
+pub static APTOS_NETWORK_PEER_MISBEHAVIOR: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_network_peer_misbehavior",
+        "Number of misbehaviors reported against peers",
+        &["network_id", "misbehavior"]
+    )
+    .unwrap()
+});
+
+pub fn peer_misbehavior(network_id: NetworkId, misbehavior: Misbehavior) -> IntCounter {
+    APTOS_NETWORK_PEER_MISBEHAVIOR.with_label_values(&[network_id.as_str(), misbehavior.as_str()])
+}
+
+pub static APTOS_NETWORK_PEER_BANS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_network_peer_bans",
+        "Number of times peers were banned for misbehaving",
+        &["network_id"]
+    )
+    .unwrap()
+});
+
+pub fn peer_bans(network_id: NetworkId) -> IntCounter {
+    APTOS_NETWORK_PEER_BANS.with_label_values(&[network_id.as_str()])
+}
+
+pub static APTOS_NETWORK_BANNED_PEERS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "aptos_network_banned_peers",
+        "Number of peers currently banned",
+        &["network_id"]
+    )
+    .unwrap()
+});
+
+pub fn banned_peers(network_id: NetworkId) -> IntGauge {
+    APTOS_NETWORK_BANNED_PEERS.with_label_values(&[network_id.as_str()])
+}
+
 pub static PEER_SEND_FAILURES: Lazy<IntCounterVec> = Lazy::new(|| {
     register_int_counter_vec!(
         "aptos_network_peer_send_failures",
@@ -314,6 +373,16 @@ pub static PENDING_NETWORK_NOTIFICATIONS: Lazy<IntCounterVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Counters(queued,dequeued,dropped) related to inbound messages held back by injected delays.
+pub static PENDING_DELAYED_INBOUND_MESSAGES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_network_pending_delayed_inbound_messages",
+        "Number of inbound messages held back by injected delays by state",
+        &["state"]
+    )
+    .unwrap()
+});
+
 /// Counter of pending requests in Network Provider
 pub static PENDING_NETWORK_REQUESTS: Lazy<IntCounterVec> = Lazy::new(|| {
     register_int_counter_vec!(
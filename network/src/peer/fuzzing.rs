@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    application::storage::PeerMetadataStorage,
     constants,
     peer::Peer,
     protocols::wire::{
@@ -100,6 +101,7 @@ pub fn fuzz(data: &[u8]) {
         TimeService::mock(),
         connection,
         connection_notifs_tx,
+        PeerMetadataStorage::test(),
         peer_reqs_rx,
         peer_notifs_tx,
         Duration::from_millis(constants::INBOUND_RPC_TIMEOUT_MS),
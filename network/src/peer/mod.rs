@@ -15,6 +15,7 @@
 //! [`PeerManager`]: crate::peer_manager::PeerManager
 
 use crate::{
+    application::{reputation::Misbehavior, storage::PeerMetadataStorage},
     counters::{
         self, network_application_inbound_traffic, network_application_outbound_traffic,
         RECEIVED_LABEL, SENT_LABEL,
@@ -32,7 +33,7 @@ use crate::{
     transport::{self, Connection, ConnectionMetadata},
     ProtocolId,
 };
-use aptos_config::network_id::NetworkContext;
+use aptos_config::network_id::{NetworkContext, PeerNetworkId};
 use aptos_logger::prelude::*;
 use aptos_rate_limiter::rate_limit::SharedBucket;
 use aptos_time_service::{TimeService, TimeServiceTrait};
@@ -42,13 +43,19 @@ use channel::aptos_channel;
 use futures::{
     self,
     channel::oneshot,
+    future,
     io::{AsyncRead, AsyncWrite},
     stream::StreamExt,
     FutureExt, SinkExt, TryFutureExt,
 };
 use serde::Serialize;
 use short_hex_str::AsShortHexStr;
-use std::{fmt, panic, time::Duration};
+use std::{
+    collections::VecDeque,
+    fmt, panic,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use tokio::runtime::Handle;
 use tokio_util::compat::{
     FuturesAsyncReadCompatExt, TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt,
@@ -60,6 +67,16 @@ mod test;
 #[cfg(any(test, feature = "fuzzing"))]
 pub mod fuzzing;
 
+/// Failpoint dropping the messages received from a remote peer, to inject network chaos in tests.
+/// The full name is suffixed with the remote peer id, e.g. `network::drop_inbound_from::<peer id>`,
+/// and takes actions like `return` (partition) or `10%return` (loss).
+pub const DROP_INBOUND_FAILPOINT: &str = "network::drop_inbound_from";
+/// Failpoint delaying the messages received from a remote peer, suffixed like
+/// [`DROP_INBOUND_FAILPOINT`] and taking the delay in milliseconds, e.g. `return(100)`.
+pub const DELAY_INBOUND_FAILPOINT: &str = "network::delay_inbound_from";
+/// The most inbound messages held back by injected delays per peer; any more are dropped.
+const MAX_DELAYED_INBOUND_MESSAGES: usize = 1024;
+
 /// Requests [`Peer`] receives from the [`PeerManager`](crate::peer_manager::PeerManager).
 #[derive(Debug)]
 pub enum PeerRequest {
@@ -120,6 +137,8 @@ pub struct Peer<TSocket> {
     connection: Option<TSocket>,
     /// Channel to notify PeerManager that we've disconnected.
     connection_notifs_tx: channel::Sender<TransportNotification<TSocket>>,
+    /// Shared metadata storage about peers, to report misbehavior of the remote peer.
+    peer_metadata_storage: Arc<PeerMetadataStorage>,
     /// Channel to receive requests from PeerManager to send messages and rpcs.
     peer_reqs_rx: aptos_channel::Receiver<ProtocolId, PeerRequest>,
     /// Channel to notifty PeerManager of new inbound messages and rpcs.
@@ -137,6 +156,9 @@ pub struct Peer<TSocket> {
     inbound_rate_limiter: Option<SharedBucket>,
     /// Optional outbound rate limiter
     outbound_rate_limiter: Option<SharedBucket>,
+    /// Inbound messages held back by an injected delay, with the time they're due. Holds at
+    /// most [`MAX_DELAYED_INBOUND_MESSAGES`].
+    delayed_inbound_messages: VecDeque<(Instant, Result<NetworkMessage, ReadError>)>,
 }
 
 impl<TSocket> Peer<TSocket>
@@ -149,6 +171,7 @@ where
         time_service: TimeService,
         connection: Connection<TSocket>,
         connection_notifs_tx: channel::Sender<TransportNotification<TSocket>>,
+        peer_metadata_storage: Arc<PeerMetadataStorage>,
         peer_reqs_rx: aptos_channel::Receiver<ProtocolId, PeerRequest>,
         peer_notifs_tx: aptos_channel::Sender<ProtocolId, PeerNotification>,
         inbound_rpc_timeout: Duration,
@@ -170,6 +193,7 @@ where
             connection_metadata,
             connection: Some(socket),
             connection_notifs_tx,
+            peer_metadata_storage,
             peer_reqs_rx,
             peer_notifs_tx,
             inbound_rpcs: InboundRpcs::new(
@@ -189,6 +213,7 @@ where
             max_frame_size,
             inbound_rate_limiter,
             outbound_rate_limiter,
+            delayed_inbound_messages: VecDeque::new(),
         }
     }
 
@@ -240,6 +265,13 @@ where
                 break reason;
             }
 
+            let delayed_inbound_due = match self.delayed_inbound_messages.front() {
+                Some((due, _)) => self.time_service.sleep_until(*due).left_future(),
+                None => future::pending::<()>().right_future(),
+            }
+            .fuse();
+            futures::pin_mut!(delayed_inbound_due);
+
             futures::select! {
                 // Handle a new outbound request from the PeerManager.
                 maybe_request = self.peer_reqs_rx.next() => {
@@ -255,22 +287,55 @@ where
                 maybe_message = reader.next() => {
                     match maybe_message {
                         Some(message) =>  {
-                            if let Err(err) = self.handle_inbound_message(message, &mut write_reqs_tx).await {
-                                warn!(
+                            // Messages are only held back by injected chaos, but once one is,
+                            // the following ones have to queue up behind it to keep their order.
+                            let delay = injected_inbound_delay(remote_peer_id);
+                            if message.is_ok() && injected_inbound_drop(remote_peer_id) {
+                                trace!(
                                     NetworkSchema::new(&self.network_context)
                                         .connection_metadata(&self.connection_metadata),
-                                    error = %err,
-                                    "{} Error in handling inbound message from peer: {}, error: {}",
+                                    "{} Dropped message from peer {} by injected chaos",
                                     self.network_context,
-                                    remote_peer_id.short_str(),
-                                    err
+                                    remote_peer_id.short_str()
                                 );
+                            } else if delay.is_some() || !self.delayed_inbound_messages.is_empty() {
+                                if self.delayed_inbound_messages.len() >= MAX_DELAYED_INBOUND_MESSAGES {
+                                    counters::PENDING_DELAYED_INBOUND_MESSAGES
+                                        .with_label_values(&["dropped"])
+                                        .inc();
+                                    trace!(
+                                        NetworkSchema::new(&self.network_context)
+                                            .connection_metadata(&self.connection_metadata),
+                                        "{} Dropped message from peer {}: too many delayed messages",
+                                        self.network_context,
+                                        remote_peer_id.short_str()
+                                    );
+                                } else {
+                                    counters::PENDING_DELAYED_INBOUND_MESSAGES
+                                        .with_label_values(&["queued"])
+                                        .inc();
+                                    let due = self.time_service.now() + delay.unwrap_or_default();
+                                    self.delayed_inbound_messages.push_back((due, message));
+                                }
+                            } else {
+                                self.handle_inbound_message_or_log(message, &mut write_reqs_tx).await;
                             }
                         },
                         // The socket was gracefully closed by the remote peer.
                         None => self.shutdown(DisconnectReason::ConnectionLost),
                     }
                 },
+                // Handle the inbound messages held back by injected chaos once they're due.
+                _ = delayed_inbound_due => {
+                    let now = self.time_service.now();
+                    while self.delayed_inbound_messages.front().map_or(false, |(due, _)| *due <= now) {
+                        let (_, message) = self.delayed_inbound_messages.pop_front().unwrap();
+                        counters::PENDING_DELAYED_INBOUND_MESSAGES
+                            .with_label_values(&["dequeued"])
+                            .inc();
+                        self.handle_inbound_message_or_log(message, &mut write_reqs_tx).await;
+                    }
+                },
                 // Drive the queue of pending inbound rpcs. When one is fulfilled
                 // by an upstream protocol, send the response to the remote peer.
                 maybe_response = self.inbound_rpcs.next_completed_response() => {
@@ -401,6 +466,27 @@ where
         (write_reqs_tx, close_tx)
     }
 
+    async fn handle_inbound_message_or_log(
+        &mut self,
+        message: Result<NetworkMessage, ReadError>,
+        write_reqs_tx: &mut channel::Sender<(
+            NetworkMessage,
+            oneshot::Sender<Result<(), PeerManagerError>>,
+        )>,
+    ) {
+        if let Err(err) = self.handle_inbound_message(message, write_reqs_tx).await {
+            warn!(
+                NetworkSchema::new(&self.network_context)
+                    .connection_metadata(&self.connection_metadata),
+                error = %err,
+                "{} Error in handling inbound message from peer: {}, error: {}",
+                self.network_context,
+                self.remote_peer_id().short_str(),
+                err
+            );
+        }
+    }
+
     async fn handle_inbound_message(
         &mut self,
         message: Result<NetworkMessage, ReadError>,
@@ -424,6 +510,13 @@ where
                     // DeserializeError's are recoverable so we'll let the other
                     // peer know about the error and log the issue, but we won't
                     // close the connection.
+                    self.peer_metadata_storage.report_misbehavior(
+                        PeerNetworkId::new(
+                            self.network_context.network_id(),
+                            self.remote_peer_id(),
+                        ),
+                        Misbehavior::InvalidMessage,
+                    );
                     let message_type = frame_prefix.as_ref().get(0).unwrap_or(&0);
                     let protocol_id = frame_prefix.as_ref().get(1).unwrap_or(&0);
                     let error_code = ErrorCode::parsing_error(*message_type, *protocol_id);
@@ -641,3 +734,27 @@ where
         );
     }
 }
+
+/// Returns whether chaos injected through [`DROP_INBOUND_FAILPOINT`] drops the next message
+/// received from the peer
+#[allow(unused_variables)]
+fn injected_inbound_drop(remote_peer_id: PeerId) -> bool {
+    fail::fail_point!(
+        format!("{}::{}", DROP_INBOUND_FAILPOINT, remote_peer_id).as_str(),
+        |_| true
+    );
+    false
+}
+
+/// Returns the delay injected through [`DELAY_INBOUND_FAILPOINT`] for the next message received
+/// from the peer
+#[allow(unused_variables)]
+fn injected_inbound_delay(remote_peer_id: PeerId) -> Option<Duration> {
+    fail::fail_point!(
+        format!("{}::{}", DELAY_INBOUND_FAILPOINT, remote_peer_id).as_str(),
+        |delay_ms: Option<String>| delay_ms
+            .and_then(|delay_ms| delay_ms.parse().ok())
+            .map(Duration::from_millis)
+    );
+    None
+}
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    application::storage::PeerMetadataStorage,
     constants::{
         INBOUND_RPC_TIMEOUT_MS, MAX_CONCURRENT_INBOUND_RPCS, MAX_CONCURRENT_OUTBOUND_RPCS,
         MAX_FRAME_SIZE, NETWORK_CHANNEL_SIZE,
@@ -82,6 +83,7 @@ fn build_test_peer(
         time_service,
         connection,
         connection_notifs_tx,
+        PeerMetadataStorage::test(),
         peer_reqs_rx,
         peer_notifs_tx,
         Duration::from_millis(INBOUND_RPC_TIMEOUT_MS),
@@ -21,7 +21,7 @@ use crate::{
     },
     ProtocolId,
 };
-use aptos_config::network_id::NetworkContext;
+use aptos_config::network_id::{NetworkContext, PeerNetworkId};
 use aptos_logger::prelude::*;
 use aptos_rate_limiter::rate_limit::TokenBucketRateLimiter;
 use aptos_time_service::{TimeService, TimeServiceTrait};
@@ -40,7 +40,7 @@ use std::{
     marker::PhantomData,
     net::{IpAddr, Ipv4Addr},
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant},
 };
 use tokio::runtime::Handle;
 
@@ -126,6 +126,8 @@ where
     inbound_rate_limiters: IpAddrTokenBucketLimiter,
     /// Keyed storage of all outbound rate limiters
     outbound_rate_limiters: IpAddrTokenBucketLimiter,
+    /// Next time to disconnect peers which got banned
+    next_ban_check: Instant,
 }
 
 impl<TTransport, TSocket> PeerManager<TTransport, TSocket>
@@ -175,6 +177,7 @@ where
             transport_reqs_rx,
             transport_notifs_tx_clone,
         );
+        let next_ban_check = time_service.now();
 
         Self {
             network_context,
@@ -200,6 +203,7 @@ where
             inbound_connection_limit,
             inbound_rate_limiters,
             outbound_rate_limiters,
+            next_ban_check,
         }
     }
 
@@ -267,6 +271,7 @@ where
                     break;
                 }
             }
+            self.maybe_disconnect_banned_peers();
         }
 
         warn!(
@@ -286,6 +291,29 @@ where
         self.sample_connected_peers();
         match event {
             TransportNotification::NewConnection(mut conn) => {
+                let peer_network_id = PeerNetworkId::new(
+                    self.network_context.network_id(),
+                    conn.metadata.remote_peer_id,
+                );
+                // Only the remote peer is to blame for inbound churn, we redial on our own
+                if conn.metadata.origin == ConnectionOrigin::Inbound {
+                    self.peer_metadata_storage
+                        .record_connection(peer_network_id);
+                }
+                if self.peer_metadata_storage.is_banned(&peer_network_id) {
+                    info!(
+                        NetworkSchema::new(&self.network_context)
+                            .connection_metadata_with_address(&conn.metadata),
+                        "{} Connection rejected as the peer is banned: {}",
+                        self.network_context,
+                        conn.metadata
+                    );
+                    counters::connections_rejected(&self.network_context, conn.metadata.origin)
+                        .inc();
+                    self.disconnect(conn);
+                    return;
+                }
+
                 match conn.metadata.origin {
                     ConnectionOrigin::Outbound => {
                         // TODO: This is right now a hack around having to feed trusted peers deeper in the outbound path.  Inbound ones are assigned at Noise handshake time.
@@ -462,6 +490,29 @@ where
                             send_err
                         );
                     }
+                } else if self.peer_metadata_storage.is_banned(&PeerNetworkId::new(
+                    self.network_context.network_id(),
+                    requested_peer_id,
+                )) {
+                    debug!(
+                        NetworkSchema::new(&self.network_context).remote_peer(&requested_peer_id),
+                        "{} Peer {} is banned. Not dialing address {}",
+                        self.network_context,
+                        requested_peer_id.short_str(),
+                        addr
+                    );
+                    if let Err(send_err) =
+                        response_tx.send(Err(PeerManagerError::Banned(requested_peer_id)))
+                    {
+                        info!(
+                            NetworkSchema::new(&self.network_context)
+                                .remote_peer(&requested_peer_id),
+                            "{} Failed to notify that peer is banned for Peer {}: {:?}",
+                            self.network_context,
+                            requested_peer_id.short_str(),
+                            send_err
+                        );
+                    }
                 } else {
                     let request = TransportRequest::DialPeer(requested_peer_id, addr, response_tx);
                     self.transport_reqs_tx.send(request).await.unwrap();
@@ -542,6 +593,36 @@ where
         }
     }
 
+    /// Closes the connections with peers banned since they connected. Bans are checked at most
+    /// once per `BAN_CHECK_INTERVAL_MS`, piggybacking on the events handled by the PeerManager.
+    fn maybe_disconnect_banned_peers(&mut self) {
+        let now = self.time_service.now();
+        if now < self.next_ban_check {
+            return;
+        }
+        self.next_ban_check = now + Duration::from_millis(constants::BAN_CHECK_INTERVAL_MS);
+
+        for peer_network_id in self
+            .peer_metadata_storage
+            .banned_peers(self.network_context.network_id())
+        {
+            let peer_id = peer_network_id.peer_id();
+            if let Some((conn_metadata, sender)) = self.active_peers.remove(&peer_id) {
+                info!(
+                    NetworkSchema::new(&self.network_context)
+                        .connection_metadata_with_address(&conn_metadata),
+                    "{} Disconnecting banned peer {}",
+                    self.network_context,
+                    peer_id.short_str()
+                );
+                self.peer_metadata_storage
+                    .remove_connection(self.network_context.network_id(), &conn_metadata);
+                // This triggers a disconnect.
+                drop(sender);
+            }
+        }
+    }
+
     fn start_connection_listener(&mut self) {
         let transport_handler = self
             .transport_handler
@@ -678,6 +759,7 @@ where
             self.time_service.clone(),
             connection,
             self.transport_notifs_tx.clone(),
+            self.peer_metadata_storage.clone(),
             peer_reqs_rx,
             peer_notifs_tx,
             Duration::from_millis(constants::INBOUND_RPC_TIMEOUT_MS),
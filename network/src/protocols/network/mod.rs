@@ -340,6 +340,21 @@ impl<TMessage: Message> NetworkSender<TMessage> {
         req_msg: TMessage,
         timeout: Duration,
     ) -> Result<TMessage, RpcError> {
+        let (res_msg, _) = self
+            .send_rpc_with_response_size(recipient, protocol, req_msg, timeout)
+            .await?;
+        Ok(res_msg)
+    }
+
+    /// Send a protobuf rpc request to a single recipient, returning the response along with the
+    /// number of bytes it was received in.
+    pub async fn send_rpc_with_response_size(
+        &self,
+        recipient: PeerId,
+        protocol: ProtocolId,
+        req_msg: TMessage,
+        timeout: Duration,
+    ) -> Result<(TMessage, usize), RpcError> {
         // serialize request
         let req_data = protocol.to_bytes(&req_msg)?.into();
         let res_data = self
@@ -347,7 +362,7 @@ impl<TMessage: Message> NetworkSender<TMessage> {
             .send_rpc(recipient, protocol, req_data, timeout)
             .await?;
         let res_msg: TMessage = protocol.from_bytes(&res_data)?;
-        Ok(res_msg)
+        Ok((res_msg, res_data.len()))
     }
 }
 
@@ -373,6 +388,23 @@ pub trait ApplicationNetworkSender<TMessage: Send>: Clone {
         req_msg: TMessage,
         timeout: Duration,
     ) -> Result<TMessage, RpcError>;
+
+    /// Like `send_rpc`, but also returns the size of the response in bytes. By default this is
+    /// the size of the response serialized with BCS; senders that know the protocol the response
+    /// arrived on override this to report the number of bytes it was actually received in.
+    async fn send_rpc_with_response_size(
+        &self,
+        recipient: PeerId,
+        req_msg: TMessage,
+        timeout: Duration,
+    ) -> Result<(TMessage, usize), RpcError>
+    where
+        TMessage: Serialize,
+    {
+        let res_msg = self.send_rpc(recipient, req_msg, timeout).await?;
+        let res_size = bcs::serialized_size(&res_msg)?;
+        Ok((res_msg, res_size))
+    }
 }
 
 /// Generalized functionality for any request across `DirectSend` and `Rpc`.
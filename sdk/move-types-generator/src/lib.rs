@@ -0,0 +1,280 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Generates Rust definitions of the structs declared by Move modules, so that users of the Rust
+//! SDK can read resources and events into typed structs instead of hand-writing definitions that
+//! match the Move layouts.
+
+use anyhow::{bail, Result};
+use aptos_api_types::{MoveModule, MoveStruct, MoveStructTag, MoveType};
+use heck::SnakeCase;
+use move_deps::move_core_types::{
+    account_address::AccountAddress, identifier::Identifier, language_storage::CORE_CODE_ADDRESS,
+};
+use serde_generate::indent::{IndentConfig, IndentedWriter};
+use std::{
+    collections::{BTreeSet, HashMap},
+    io::Write,
+};
+
+/// The default path of the REST API types used by the generated code
+pub const DEFAULT_API_TYPES_PATH: &str = "aptos_rest_client::aptos_api_types";
+
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in", "let", "loop",
+    "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "static",
+    "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual",
+    "where", "while", "yield",
+];
+
+/// Keywords that can't be raw identifiers either
+const RESERVED_IDENTIFIERS: &[&str] = &["crate", "self", "Self", "super"];
+
+/// Output Rust structs for all the structs declared by the given modules, with one Rust module
+/// per Move module. The structs follow the JSON representation of Move values in the REST API,
+/// so that the data of resources and events can be deserialized into them with
+/// `serde_json::from_value`. Numbers, addresses and bytes use the REST API types found under
+/// `api_types_path`, e.g. `aptos_rest_client::aptos_api_types`.
+///
+/// Phantom type parameters are left out of the generated structs. Types declared by modules which
+/// aren't given, or by native structs, are generated as `serde_json::Value`.
+pub fn output(out: &mut dyn Write, modules: &[MoveModule], api_types_path: &str) -> Result<()> {
+    let mut module_names = HashMap::new();
+    for module in modules {
+        if let Some(address) = module_names.insert(module.name.clone(), module.address) {
+            bail!(
+                "Module {} is declared by both {} and {}, generate their structs separately",
+                module.name,
+                address,
+                module.address
+            );
+        }
+    }
+
+    let mut emitter = MoveTypesEmitter {
+        out: IndentedWriter::new(out, IndentConfig::Space(4)),
+        structs: modules
+            .iter()
+            .flat_map(|module| {
+                module.structs.iter().map(move |s| {
+                    (
+                        (*module.address.inner(), module.name.clone(), s.name.clone()),
+                        s,
+                    )
+                })
+            })
+            .collect(),
+        api_types_path,
+    };
+    emitter.output_preamble()?;
+    for module in modules {
+        emitter.output_module(module)?;
+    }
+    Ok(())
+}
+
+struct MoveTypesEmitter<'a, T> {
+    out: IndentedWriter<T>,
+    /// The structs of all the modules, keyed by address, module name and struct name
+    structs: HashMap<(AccountAddress, Identifier, Identifier), &'a MoveStruct>,
+    api_types_path: &'a str,
+}
+
+impl<'a, T> MoveTypesEmitter<'a, T>
+where
+    T: Write,
+{
+    fn output_preamble(&mut self) -> Result<()> {
+        writeln!(
+            self.out,
+            "// This file was generated by generate-move-types. Do not edit."
+        )?;
+        Ok(())
+    }
+
+    fn output_module(&mut self, module: &MoveModule) -> Result<()> {
+        let structs = module
+            .structs
+            .iter()
+            .filter(|s| !s.is_native)
+            .collect::<Vec<_>>();
+        if structs.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(self.out, "\n/// `{}::{}`", module.address, module.name)?;
+        writeln!(
+            self.out,
+            "pub mod {} {{",
+            escape_identifier(&module.name.as_str().to_snake_case()).0
+        )?;
+        self.out.indent();
+        for (i, s) in structs.into_iter().enumerate() {
+            if i > 0 {
+                writeln!(self.out)?;
+            }
+            self.output_struct(module, s)?;
+        }
+        self.out.unindent();
+        writeln!(self.out, "}}")?;
+        Ok(())
+    }
+
+    fn output_struct(&mut self, module: &MoveModule, s: &MoveStruct) -> Result<()> {
+        let mut used_params = BTreeSet::new();
+        let fields = s
+            .fields
+            .iter()
+            .map(|field| {
+                (
+                    field.name.as_str(),
+                    self.type_name(module, &field.typ, &mut used_params),
+                )
+            })
+            .collect::<Vec<_>>();
+        let params = s
+            .generic_type_params
+            .iter()
+            .enumerate()
+            .filter(|(_, param)| !param.is_phantom)
+            .map(|(index, _)| format!("T{}", index))
+            .collect::<Vec<_>>();
+        let unused_params = s
+            .generic_type_params
+            .iter()
+            .enumerate()
+            .filter(|(index, param)| !param.is_phantom && !used_params.contains(index))
+            .map(|(index, _)| format!("T{}", index))
+            .collect::<Vec<_>>();
+
+        let abilities = s
+            .abilities
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        writeln!(
+            self.out,
+            "/// `{}::{}::{}`, with abilities: {}",
+            module.address,
+            module.name,
+            s.name,
+            if abilities.is_empty() {
+                "none".to_string()
+            } else {
+                abilities.join(", ")
+            }
+        )?;
+        writeln!(
+            self.out,
+            "#[derive(Clone, Debug, serde::Deserialize, PartialEq, serde::Serialize)]"
+        )?;
+        writeln!(self.out, "pub struct {}{} {{", s.name, generics(&params))?;
+        self.out.indent();
+        for (name, typ) in fields {
+            let (rust_name, renamed) = escape_identifier(name);
+            if renamed {
+                writeln!(self.out, "#[serde(rename = \"{}\")]", name)?;
+            }
+            writeln!(self.out, "pub {}: {},", rust_name, typ)?;
+        }
+        if !unused_params.is_empty() {
+            writeln!(self.out, "#[serde(skip)]")?;
+            writeln!(
+                self.out,
+                "pub _phantom: std::marker::PhantomData<({},)>,",
+                unused_params.join(", ")
+            )?;
+        }
+        self.out.unindent();
+        writeln!(self.out, "}}")?;
+        Ok(())
+    }
+
+    /// Returns the Rust type of a field of a struct declared by `module`, recording the generic
+    /// type parameters of the struct it uses
+    fn type_name(
+        &self,
+        module: &MoveModule,
+        typ: &MoveType,
+        used_params: &mut BTreeSet<usize>,
+    ) -> String {
+        match typ {
+            MoveType::Bool => "bool".to_string(),
+            MoveType::U8 => "u8".to_string(),
+            MoveType::U64 => format!("{}::U64", self.api_types_path),
+            MoveType::U128 => format!("{}::U128", self.api_types_path),
+            MoveType::Address | MoveType::Signer => format!("{}::Address", self.api_types_path),
+            MoveType::Vector { items } => match **items {
+                MoveType::U8 => format!("{}::HexEncodedBytes", self.api_types_path),
+                _ => format!("Vec<{}>", self.type_name(module, items, used_params)),
+            },
+            MoveType::Struct(tag) => self.struct_type_name(module, tag, used_params),
+            MoveType::GenericTypeParam { index } => {
+                used_params.insert(*index as usize);
+                format!("T{}", index)
+            }
+            MoveType::Reference { to, .. } => self.type_name(module, to, used_params),
+        }
+    }
+
+    fn struct_type_name(
+        &self,
+        module: &MoveModule,
+        tag: &MoveStructTag,
+        used_params: &mut BTreeSet<usize>,
+    ) -> String {
+        // The REST API represents ASCII strings as JSON strings
+        if *tag.address.inner() == CORE_CODE_ADDRESS
+            && tag.module.as_str() == "ASCII"
+            && tag.name.as_str() == "String"
+        {
+            return "std::string::String".to_string();
+        }
+
+        let s =
+            match self
+                .structs
+                .get(&(*tag.address.inner(), tag.module.clone(), tag.name.clone()))
+            {
+                Some(s) if !s.is_native => s,
+                _ => return "serde_json::Value".to_string(),
+            };
+        let args = tag
+            .generic_type_params
+            .iter()
+            .zip(&s.generic_type_params)
+            .filter(|(_, param)| !param.is_phantom)
+            .map(|(arg, _)| self.type_name(module, arg, used_params))
+            .collect::<Vec<_>>();
+        if tag.address == module.address && tag.module == module.name {
+            format!("{}{}", tag.name, generics(&args))
+        } else {
+            format!(
+                "super::{}::{}{}",
+                escape_identifier(&tag.module.as_str().to_snake_case()).0,
+                tag.name,
+                generics(&args)
+            )
+        }
+    }
+}
+
+fn generics(args: &[String]) -> String {
+    if args.is_empty() {
+        String::new()
+    } else {
+        format!("<{}>", args.join(", "))
+    }
+}
+
+/// Returns a Rust identifier for a Move identifier, and whether it had to be renamed
+fn escape_identifier(name: &str) -> (String, bool) {
+    if RESERVED_IDENTIFIERS.contains(&name) {
+        (format!("{}_", name), true)
+    } else if RUST_KEYWORDS.contains(&name) {
+        (format!("r#{}", name), false)
+    } else {
+        (name.to_string(), false)
+    }
+}
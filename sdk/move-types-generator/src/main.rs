@@ -0,0 +1,86 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! # Code generator for the structs of on-chain Move modules
+//!
+//! '''bash
+//! cargo run -p move-types-generator -- --url http://localhost:8080 --account 0x1 --output src/move_types.rs
+//! '''
+
+use anyhow::{format_err, Result};
+use aptos_api_types::Address;
+use aptos_rest_client::Client;
+use move_types_generator::DEFAULT_API_TYPES_PATH;
+use std::{fs::File, path::PathBuf};
+use structopt::StructOpt;
+use url::Url;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "Move types generator",
+    about = "Generate Rust structs for the resources and events of on-chain Move modules"
+)]
+struct Options {
+    /// REST API endpoint of the node to fetch the modules from.
+    #[structopt(long, default_value = "http://localhost:8080")]
+    url: Url,
+
+    /// Accounts whose modules to generate structs for.
+    #[structopt(long = "account", default_value = "0x1")]
+    accounts: Vec<Address>,
+
+    /// Only generate structs for the modules with these names. Structs of the modules left out
+    /// are generated as `serde_json::Value` where they are used.
+    #[structopt(long = "module")]
+    modules: Vec<String>,
+
+    /// Path of the REST API types used by the generated code, e.g. `aptos_api_types` for crates
+    /// depending on the types directly.
+    #[structopt(long, default_value = DEFAULT_API_TYPES_PATH)]
+    api_types_path: String,
+
+    /// File where to write the generated code (otherwise print code on stdout).
+    #[structopt(long)]
+    output: Option<PathBuf>,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let options = Options::from_args();
+    let client = Client::new(options.url);
+
+    let mut modules = vec![];
+    for account in &options.accounts {
+        let account_modules = client
+            .get_account_modules(*account.inner())
+            .await
+            .map_err(|e| format_err!("Failed to fetch the modules of {}: {}", account, e))?
+            .into_inner();
+        for module in account_modules {
+            let abi = module
+                .try_parse_abi()?
+                .abi
+                .ok_or_else(|| format_err!("Failed to parse the ABI of a module of {}", account))?;
+            if options.modules.is_empty()
+                || options.modules.iter().any(|name| name == abi.name.as_str())
+            {
+                modules.push(abi);
+            }
+        }
+    }
+    // Keep the output stable across runs
+    modules.sort_by(|a, b| a.name.cmp(&b.name));
+
+    match options.output {
+        Some(path) => {
+            let mut file = File::create(&path)
+                .map_err(|e| format_err!("Failed to create {:?}: {}", path, e))?;
+            move_types_generator::output(&mut file, &modules, &options.api_types_path)
+        }
+        None => {
+            let stdout = std::io::stdout();
+            let mut out = stdout.lock();
+            move_types_generator::output(&mut out, &modules, &options.api_types_path)
+        }
+    }
+}
@@ -0,0 +1,57 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_api_types::MoveModule;
+use move_types_generator::DEFAULT_API_TYPES_PATH;
+
+fn generate_framework_structs(api_types_path: &str) -> String {
+    let modules = cached_framework_packages::modules()
+        .iter()
+        .cloned()
+        .map(MoveModule::from)
+        .collect::<Vec<_>>();
+    let mut out = vec![];
+    move_types_generator::output(&mut out, &modules, api_types_path).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn test_framework_structs() {
+    let code = generate_framework_structs(DEFAULT_API_TYPES_PATH);
+
+    assert!(code.contains("pub mod coin {"));
+    // Phantom type parameters are left out
+    assert!(code.contains("pub struct CoinStore {"));
+    assert!(code.contains("pub coin: Coin,"));
+    assert!(code.contains("pub deposit_events: super::event::EventHandle,"));
+    // Types follow the JSON representation of the REST API
+    assert!(code.contains("pub value: aptos_rest_client::aptos_api_types::U64,"));
+    assert!(code.contains("pub name: std::string::String,"));
+    assert!(code
+        .contains("pub supply: super::option::Option<aptos_rest_client::aptos_api_types::U64>,"));
+}
+
+#[test]
+fn test_api_types_path() {
+    let code = generate_framework_structs("aptos_api_types");
+    assert!(code.contains("pub value: aptos_api_types::U64,"));
+    assert!(!code.contains("aptos_rest_client"));
+}
+
+#[test]
+fn test_duplicate_module_names() {
+    let coin = cached_framework_packages::modules()
+        .iter()
+        .cloned()
+        .map(MoveModule::from)
+        .find(|module| module.name.as_str() == "Coin")
+        .unwrap();
+    let mut other_coin = coin.clone();
+    other_coin.address = "0x2".parse().unwrap();
+
+    let mut out = vec![];
+    assert!(
+        move_types_generator::output(&mut out, &[coin, other_coin], DEFAULT_API_TYPES_PATH)
+            .is_err()
+    );
+}
@@ -10,7 +10,7 @@ use crate::{
 };
 use aptos_crypto::ed25519::Ed25519PublicKey;
 
-pub use aptos_transaction_builder::aptos_stdlib;
+pub use aptos_transaction_builder::{aptos_stdlib, transaction_summary};
 use aptos_types::transaction::{
     authenticator::AuthenticationKeyPreimage, ChangeSet, ModuleBundle, Script, ScriptFunction,
     WriteSetPayload,
@@ -5,3 +5,4 @@
 
 pub use cached_framework_packages::aptos_stdlib;
 pub mod error_explain;
+pub mod transaction_summary;
@@ -0,0 +1,334 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! A module for rendering a transaction payload into a human-readable summary, e.g. for a wallet
+//! to show on its confirmation screen before signing.
+//!
+//! Arguments of script functions are decoded against the ABIs of the framework, and are shown as
+//! raw hex for any other function.
+
+use aptos_types::transaction::{
+    Module, ModuleBundle, Script, ScriptABI, ScriptFunction, ScriptFunctionABI, TransactionPayload,
+    WriteSetPayload,
+};
+use move_deps::{
+    move_binary_format::file_format::CompiledModule,
+    move_core_types::{
+        account_address::AccountAddress,
+        language_storage::{ModuleId, TypeTag, CORE_CODE_ADDRESS},
+        transaction_argument::TransactionArgument,
+    },
+};
+use once_cell::sync::Lazy;
+use std::{collections::BTreeMap, fmt};
+
+static SCRIPT_FUNCTION_ABIS: Lazy<BTreeMap<(ModuleId, String), ScriptFunctionABI>> =
+    Lazy::new(|| {
+        cached_framework_packages::abis()
+            .into_iter()
+            .filter_map(|abi| match abi {
+                ScriptABI::ScriptFunction(abi) => {
+                    Some(((abi.module_name().clone(), abi.name().to_string()), abi))
+                }
+                ScriptABI::TransactionScript(_) => None,
+            })
+            .collect()
+    });
+
+/// Framework functions which hand over control of the sender's account or validator
+const KEY_ROTATION_FUNCTIONS: &[(&str, &str)] = &[
+    ("Account", "rotate_authentication_key"),
+    ("Stake", "rotate_consensus_key"),
+];
+
+/// Things in a transaction the signer should be careful about
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum SummaryWarning {
+    /// The transaction rotates a key controlling the account
+    KeyRotation,
+    /// The transaction runs arbitrary code on behalf of the account
+    Script,
+    /// The transaction publishes or upgrades code under the account
+    ModulePublishing,
+    /// The transaction writes to the state directly, without going through the VM
+    WriteSet,
+    /// The function isn't part of the framework, so its arguments couldn't be decoded
+    UnknownFunction,
+}
+
+impl fmt::Display for SummaryWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SummaryWarning::KeyRotation => {
+                "Rotates a key, anyone holding the new key takes control of the account"
+            }
+            SummaryWarning::Script => "Runs arbitrary code with full access to the account",
+            SummaryWarning::ModulePublishing => "Publishes code under the account",
+            SummaryWarning::WriteSet => "Writes to the blockchain state directly",
+            SummaryWarning::UnknownFunction => {
+                "Calls a function outside the framework, its arguments can't be checked"
+            }
+        })
+    }
+}
+
+/// An argument of the transaction, named when known from the ABI
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SummaryArgument {
+    pub name: Option<String>,
+    pub value: String,
+}
+
+impl fmt::Display for SummaryArgument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{}: {}", name, self.value),
+            None => f.write_str(&self.value),
+        }
+    }
+}
+
+/// A human-readable summary of a transaction payload
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TransactionSummary {
+    /// What the transaction does, e.g. `Call 0x1::TestCoin::transfer`
+    pub action: String,
+    pub type_arguments: Vec<String>,
+    pub arguments: Vec<SummaryArgument>,
+    pub warnings: Vec<SummaryWarning>,
+}
+
+impl TransactionSummary {
+    fn new(action: String) -> Self {
+        Self {
+            action,
+            type_arguments: vec![],
+            arguments: vec![],
+            warnings: vec![],
+        }
+    }
+
+    pub fn has_warnings(&self) -> bool {
+        !self.warnings.is_empty()
+    }
+}
+
+impl fmt::Display for TransactionSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.action)?;
+        if !self.type_arguments.is_empty() {
+            write!(f, "<{}>", self.type_arguments.join(", "))?;
+        }
+        writeln!(f)?;
+        for argument in &self.arguments {
+            writeln!(f, "  {}", argument)?;
+        }
+        for warning in &self.warnings {
+            writeln!(f, "WARNING: {}", warning)?;
+        }
+        Ok(())
+    }
+}
+
+/// Summarizes what the payload does, flagging dangerous operations.
+pub fn summarize(payload: &TransactionPayload) -> TransactionSummary {
+    match payload {
+        TransactionPayload::ScriptFunction(script_function) => {
+            summarize_script_function(script_function)
+        }
+        TransactionPayload::Script(script) => summarize_script("Run script".to_string(), script),
+        TransactionPayload::ModuleBundle(modules) => summarize_module_bundle(modules),
+        TransactionPayload::WriteSet(write_set) => summarize_write_set(write_set),
+    }
+}
+
+fn summarize_script_function(script_function: &ScriptFunction) -> TransactionSummary {
+    let module = script_function.module();
+    let function = script_function.function().as_str();
+    let mut summary = TransactionSummary::new(format!(
+        "Call {}::{}::{}",
+        module.address().to_hex_literal(),
+        module.name(),
+        function
+    ));
+    summary.type_arguments = script_function
+        .ty_args()
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+
+    let abi = SCRIPT_FUNCTION_ABIS
+        .get(&(module.clone(), function.to_string()))
+        .filter(|abi| abi.args().len() == script_function.args().len());
+    summary.arguments = match abi {
+        Some(abi) => abi
+            .args()
+            .iter()
+            .zip(script_function.args())
+            .map(|(arg, bytes)| SummaryArgument {
+                name: Some(arg.name().to_string()),
+                value: decode_argument(arg.type_tag(), bytes)
+                    .unwrap_or_else(|| format_bytes(bytes)),
+            })
+            .collect(),
+        None => {
+            summary.warnings.push(SummaryWarning::UnknownFunction);
+            script_function
+                .args()
+                .iter()
+                .map(|bytes| SummaryArgument {
+                    name: None,
+                    value: format_bytes(bytes),
+                })
+                .collect()
+        }
+    };
+
+    if module.address() == &CORE_CODE_ADDRESS
+        && KEY_ROTATION_FUNCTIONS
+            .iter()
+            .any(|(module_name, name)| module.name().as_str() == *module_name && function == *name)
+    {
+        summary.warnings.push(SummaryWarning::KeyRotation);
+    }
+    summary
+}
+
+fn summarize_script(action: String, script: &Script) -> TransactionSummary {
+    let mut summary = TransactionSummary::new(action);
+    summary.type_arguments = script.ty_args().iter().map(ToString::to_string).collect();
+    summary.arguments = script
+        .args()
+        .iter()
+        .map(|arg| SummaryArgument {
+            name: None,
+            value: format_transaction_argument(arg),
+        })
+        .collect();
+    summary.warnings.push(SummaryWarning::Script);
+    summary
+}
+
+fn summarize_module_bundle(modules: &ModuleBundle) -> TransactionSummary {
+    let modules: Vec<&Module> = modules.iter().collect();
+    let mut summary = TransactionSummary::new(format!("Publish {} module(s)", modules.len()));
+    summary.arguments = modules
+        .into_iter()
+        .map(|module| SummaryArgument {
+            name: Some("module".to_string()),
+            value: match CompiledModule::deserialize(module.code()) {
+                Ok(compiled_module) => {
+                    let id = compiled_module.self_id();
+                    format!("{}::{}", id.address().to_hex_literal(), id.name())
+                }
+                Err(_) => "<invalid module>".to_string(),
+            },
+        })
+        .collect();
+    summary.warnings.push(SummaryWarning::ModulePublishing);
+    summary
+}
+
+fn summarize_write_set(write_set: &WriteSetPayload) -> TransactionSummary {
+    let mut summary = match write_set {
+        WriteSetPayload::Direct(change_set) => TransactionSummary::new(format!(
+            "Apply a write set of {} change(s)",
+            change_set.write_set().iter().count()
+        )),
+        WriteSetPayload::Script { execute_as, script } => summarize_script(
+            format!("Run write set script as {}", execute_as.to_hex_literal()),
+            script,
+        ),
+    };
+    summary.warnings.push(SummaryWarning::WriteSet);
+    summary
+}
+
+/// Decodes a BCS encoded argument of the given type, if it's a type worth showing decoded.
+fn decode_argument(type_tag: &TypeTag, bytes: &[u8]) -> Option<String> {
+    match type_tag {
+        TypeTag::Bool => bcs::from_bytes::<bool>(bytes).ok().map(|v| v.to_string()),
+        TypeTag::U8 => bcs::from_bytes::<u8>(bytes).ok().map(|v| v.to_string()),
+        TypeTag::U64 => bcs::from_bytes::<u64>(bytes).ok().map(|v| v.to_string()),
+        TypeTag::U128 => bcs::from_bytes::<u128>(bytes).ok().map(|v| v.to_string()),
+        TypeTag::Address => bcs::from_bytes::<AccountAddress>(bytes)
+            .ok()
+            .map(|v| v.to_hex_literal()),
+        TypeTag::Vector(inner) if **inner == TypeTag::U8 => bcs::from_bytes::<Vec<u8>>(bytes)
+            .ok()
+            .map(|v| format_byte_vector(&v)),
+        _ => None,
+    }
+}
+
+fn format_transaction_argument(arg: &TransactionArgument) -> String {
+    match arg {
+        TransactionArgument::U8(v) => v.to_string(),
+        TransactionArgument::U64(v) => v.to_string(),
+        TransactionArgument::U128(v) => v.to_string(),
+        TransactionArgument::Bool(v) => v.to_string(),
+        TransactionArgument::Address(v) => v.to_hex_literal(),
+        TransactionArgument::U8Vector(v) => format_byte_vector(v),
+    }
+}
+
+/// Shows a `vector<u8>` as a quoted string when it's printable text, e.g. a token name, and as
+/// hex otherwise, e.g. a key.
+fn format_byte_vector(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) if !text.is_empty() && !text.chars().any(char::is_control) => {
+            format!("{:?}", text)
+        }
+        _ => format_bytes(bytes),
+    }
+}
+
+fn format_bytes(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cached_framework_packages::aptos_stdlib;
+
+    #[test]
+    fn test_summarize_transfer() {
+        let to = AccountAddress::from_hex_literal("0xa11ce").unwrap();
+        let summary = summarize(&aptos_stdlib::encode_test_coin_transfer(to, 100));
+        assert_eq!(summary.action, "Call 0x1::TestCoin::transfer");
+        assert_eq!(
+            summary.arguments,
+            vec![
+                SummaryArgument {
+                    name: Some("to".to_string()),
+                    value: to.to_hex_literal(),
+                },
+                SummaryArgument {
+                    name: Some("amount".to_string()),
+                    value: "100".to_string(),
+                },
+            ]
+        );
+        assert!(!summary.has_warnings());
+    }
+
+    #[test]
+    fn test_summarize_key_rotation() {
+        let summary = summarize(&aptos_stdlib::encode_account_rotate_authentication_key(
+            vec![0xff; 32],
+        ));
+        assert_eq!(summary.warnings, vec![SummaryWarning::KeyRotation]);
+        assert_eq!(summary.arguments[0].value, format!("0x{}", "ff".repeat(32)));
+    }
+
+    #[test]
+    fn test_summarize_module_bundle() {
+        let summary = summarize(&TransactionPayload::ModuleBundle(ModuleBundle::new(vec![
+            vec![0xde, 0xad],
+        ])));
+        assert_eq!(summary.action, "Publish 1 module(s)");
+        assert_eq!(summary.arguments[0].value, "<invalid module>");
+        assert_eq!(summary.warnings, vec![SummaryWarning::ModulePublishing]);
+    }
+}
@@ -13,14 +13,24 @@
 //! server.
 //!
 //! Internally both the client and server leverage a NetworkStream that communications in blocks
-//! where a block is a length prefixed array of bytes.
+//! where a block is a length prefixed array of bytes. The stream is either a TCP connection or,
+//! between processes of the same host, a Unix domain socket.
 
 use aptos_logger::{info, trace, warn, Schema};
 use aptos_secure_push_metrics::{register_int_counter_vec, IntCounterVec};
 use once_cell::sync::Lazy;
 use serde::Serialize;
+#[cfg(unix)]
 use std::{
-    io::{Read, Write},
+    fs,
+    os::unix::{
+        fs::FileTypeExt,
+        net::{UnixListener, UnixStream},
+    },
+    path::PathBuf,
+};
+use std::{
+    io::{self, Read, Write},
     net::{Shutdown, SocketAddr, TcpListener, TcpStream},
     thread, time,
 };
@@ -32,7 +42,7 @@ struct SecureNetLogSchema<'a> {
     mode: NetworkMode,
     event: LogEvent,
     #[schema(debug)]
-    remote_peer: Option<&'a SocketAddr>,
+    remote_peer: Option<&'a ServerAddress>,
     #[schema(debug)]
     error: Option<&'a Error>,
 }
@@ -148,19 +158,34 @@ pub enum Error {
     RemoteStreamClosed,
 }
 
+/// Where a server listens and its clients connect to
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ServerAddress {
+    Tcp(SocketAddr),
+    /// Path of a Unix domain socket
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl From<SocketAddr> for ServerAddress {
+    fn from(address: SocketAddr) -> Self {
+        ServerAddress::Tcp(address)
+    }
+}
+
 pub struct NetworkClient {
     service: &'static str,
-    server: SocketAddr,
+    server: ServerAddress,
     stream: Option<NetworkStream>,
     /// Read, Write, Connect timeout in milliseconds.
     timeout_ms: u64,
 }
 
 impl NetworkClient {
-    pub fn new(service: &'static str, server: SocketAddr, timeout_ms: u64) -> Self {
+    pub fn new(service: &'static str, server: impl Into<ServerAddress>, timeout_ms: u64) -> Self {
         Self {
             service,
-            server,
+            server: server.into(),
             stream: None,
             timeout_ms,
         }
@@ -238,7 +263,7 @@ impl NetworkClient {
             .remote_peer(&self.server));
 
             let timeout = std::time::Duration::from_millis(self.timeout_ms);
-            let mut stream = TcpStream::connect_timeout(&self.server, timeout);
+            let mut stream = Stream::connect(&self.server, timeout);
 
             let sleeptime = time::Duration::from_millis(100);
             while let Err(err) = stream {
@@ -252,12 +277,15 @@ impl NetworkClient {
                 .remote_peer(&self.server));
 
                 thread::sleep(sleeptime);
-                stream = TcpStream::connect_timeout(&self.server, timeout);
+                stream = Stream::connect(&self.server, timeout);
             }
 
             let stream = stream?;
-            stream.set_nodelay(true)?;
-            self.stream = Some(NetworkStream::new(stream, self.server, self.timeout_ms));
+            self.stream = Some(NetworkStream::new(
+                stream,
+                self.server.clone(),
+                self.timeout_ms,
+            ));
             self.increment_counter(Method::Connect, MethodResult::Success);
             info!(SecureNetLogSchema::new(
                 self.service,
@@ -273,17 +301,20 @@ impl NetworkClient {
 
 pub struct NetworkServer {
     service: &'static str,
-    listener: Option<TcpListener>,
+    listen: ServerAddress,
+    listener: Option<Listener>,
     stream: Option<NetworkStream>,
     /// Read, Write, Connect timeout in milliseconds.
     timeout_ms: u64,
 }
 
 impl NetworkServer {
-    pub fn new(service: &'static str, listen: SocketAddr, timeout_ms: u64) -> Self {
-        let listener = TcpListener::bind(listen);
+    pub fn new(service: &'static str, listen: impl Into<ServerAddress>, timeout_ms: u64) -> Self {
+        let listen = listen.into();
+        let listener = Listener::bind(&listen);
         Self {
             service,
+            listen,
             listener: Some(listener.unwrap()),
             stream: None,
             timeout_ms,
@@ -301,7 +332,7 @@ impl NetworkServer {
 
         let result = {
             let stream = self.client()?;
-            stream.read().map_err(|e| (stream.remote, e))
+            stream.read().map_err(|e| (stream.remote.clone(), e))
         };
 
         if let Err((remote, err)) = &result {
@@ -343,7 +374,7 @@ impl NetworkServer {
 
         let result = {
             let stream = self.client()?;
-            stream.write(data).map_err(|e| (stream.remote, e))
+            stream.write(data).map_err(|e| (stream.remote.clone(), e))
         };
 
         if let Err((remote, err)) = &result {
@@ -375,7 +406,7 @@ impl NetworkServer {
 
             let listener = self.listener.as_mut().ok_or(Error::AlreadyShutdown)?;
 
-            let (stream, stream_addr) = match listener.accept() {
+            let (stream, stream_addr) = match listener.accept(&self.listen) {
                 Ok(ok) => ok,
                 Err(err) => {
                     self.increment_counter(Method::Connect, MethodResult::Failure);
@@ -398,7 +429,6 @@ impl NetworkServer {
             )
             .remote_peer(&stream_addr));
 
-            stream.set_nodelay(true)?;
             self.stream = Some(NetworkStream::new(stream, stream_addr, self.timeout_ms));
         }
 
@@ -406,19 +436,129 @@ impl NetworkServer {
     }
 }
 
+enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+impl Listener {
+    fn bind(listen: &ServerAddress) -> io::Result<Self> {
+        match listen {
+            ServerAddress::Tcp(address) => Ok(Listener::Tcp(TcpListener::bind(address)?)),
+            #[cfg(unix)]
+            ServerAddress::Unix(path) => {
+                // The socket of a previous server is left behind and would fail the bind
+                if let Ok(metadata) = fs::metadata(path) {
+                    if metadata.file_type().is_socket() {
+                        fs::remove_file(path)?;
+                    }
+                }
+                Ok(Listener::Unix(UnixListener::bind(path)?))
+            }
+        }
+    }
+
+    /// Accepts a client, whose address is the listening one for Unix domain sockets since their
+    /// clients are unnamed
+    fn accept(&self, listen: &ServerAddress) -> io::Result<(Stream, ServerAddress)> {
+        match self {
+            Listener::Tcp(listener) => {
+                let (stream, address) = listener.accept()?;
+                stream.set_nodelay(true)?;
+                Ok((Stream::Tcp(stream), ServerAddress::Tcp(address)))
+            }
+            #[cfg(unix)]
+            Listener::Unix(listener) => {
+                let (stream, _) = listener.accept()?;
+                Ok((Stream::Unix(stream), listen.clone()))
+            }
+        }
+    }
+}
+
+enum Stream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl Stream {
+    fn connect(server: &ServerAddress, timeout: time::Duration) -> io::Result<Self> {
+        match server {
+            ServerAddress::Tcp(address) => {
+                let stream = TcpStream::connect_timeout(address, timeout)?;
+                stream.set_nodelay(true)?;
+                Ok(Stream::Tcp(stream))
+            }
+            #[cfg(unix)]
+            ServerAddress::Unix(path) => Ok(Stream::Unix(UnixStream::connect(path)?)),
+        }
+    }
+
+    fn set_timeout(&self, timeout: Option<time::Duration>) -> io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => {
+                stream.set_read_timeout(timeout)?;
+                stream.set_write_timeout(timeout)
+            }
+            #[cfg(unix)]
+            Stream::Unix(stream) => {
+                stream.set_read_timeout(timeout)?;
+                stream.set_write_timeout(timeout)
+            }
+        }
+    }
+
+    fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.shutdown(how),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.shutdown(how),
+        }
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.read(buf),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.write(buf),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.flush(),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
 struct NetworkStream {
-    stream: TcpStream,
-    remote: SocketAddr,
+    stream: Stream,
+    remote: ServerAddress,
     buffer: Vec<u8>,
     temp_buffer: [u8; 1024],
 }
 
 impl NetworkStream {
-    pub fn new(stream: TcpStream, remote: SocketAddr, timeout_ms: u64) -> Self {
+    pub fn new(stream: Stream, remote: ServerAddress, timeout_ms: u64) -> Self {
         let timeout = Some(std::time::Duration::from_millis(timeout_ms));
-        // These only fail if a duration of 0 is passed in.
-        stream.set_read_timeout(timeout).unwrap();
-        stream.set_write_timeout(timeout).unwrap();
+        // This only fails if a duration of 0 is passed in.
+        stream.set_timeout(timeout).unwrap();
 
         Self {
             stream,
@@ -541,6 +681,36 @@ mod test {
         assert_eq!(data, result);
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_ping_unix_socket() {
+        let socket_dir = aptos_temppath::TempPath::new();
+        socket_dir.create_as_dir().unwrap();
+        let socket_path = socket_dir.path().join("test.sock");
+        let server_addr = ServerAddress::Unix(socket_path.clone());
+        let mut server = NetworkServer::new("test", server_addr.clone(), TIMEOUT);
+        let mut client = NetworkClient::new("test", server_addr.clone(), TIMEOUT);
+
+        let data = vec![0, 1, 2, 3];
+        client.write(&data).unwrap();
+        let result = server.read().unwrap();
+        assert_eq!(data, result);
+
+        let data = vec![4, 5, 6, 7];
+        server.write(&data).unwrap();
+        let result = client.read().unwrap();
+        assert_eq!(data, result);
+
+        // A new server takes over the socket left behind by the previous one
+        server.shutdown().unwrap();
+        assert!(socket_path.exists());
+        let mut server = NetworkServer::new("test", server_addr.clone(), TIMEOUT);
+        let mut client = NetworkClient::new("test", server_addr, TIMEOUT);
+        client.write(&data).unwrap();
+        let result = server.read().unwrap();
+        assert_eq!(data, result);
+    }
+
     #[test]
     fn test_client_shutdown() {
         let server_port = utils::get_available_port();
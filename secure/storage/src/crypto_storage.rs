@@ -1,10 +1,14 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::Error;
+use crate::{derive_ed25519_key_from_seed, Error};
 use aptos_crypto::ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature};
 use enum_dispatch::enum_dispatch;
 use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+/// Name of the key holding the master seed of the keys derived by `derive_ed25519_key`
+pub const MASTER_SEED: &str = "master_seed";
 
 /// CryptoStorage provides an abstraction for secure generation and handling of cryptographic keys.
 #[enum_dispatch]
@@ -23,6 +27,24 @@ pub trait CryptoStorage {
     /// running in test environments where a set of deterministic keys must be generated.
     fn import_private_key(&mut self, name: &str, key: Ed25519PrivateKey) -> Result<(), Error>;
 
+    /// Stores the master seed the keys of `derive_ed25519_key` are derived from. Like
+    /// `import_private_key`, this is a testing API, to derive a set of deterministic keys from a
+    /// single seed. The seed is stored as an Ed25519 private key at 'MASTER_SEED', so it is
+    /// exported the same way as the other keys.
+    fn import_master_seed(&mut self, seed: [u8; 32]) -> Result<(), Error> {
+        let seed = Ed25519PrivateKey::try_from(&seed[..])
+            .map_err(|e| Error::InternalError(e.to_string()))?;
+        self.import_private_key(MASTER_SEED, seed)
+    }
+
+    /// Derives the Ed25519 private key at 'path', e.g. `m/0'/3'`, from the master seed of the
+    /// storage, the way SLIP-0010 (BIP32 for ed25519) does. The same seed and path always give the
+    /// same key.
+    fn derive_ed25519_key(&self, path: &str) -> Result<Ed25519PrivateKey, Error> {
+        let seed = self.export_private_key(MASTER_SEED)?;
+        derive_ed25519_key_from_seed(&seed.to_bytes(), path)
+    }
+
     /// Returns the Ed25519 private key stored at 'name' and identified by 'version', which is the
     /// corresponding public key. This may fail even if the 'named' key exists but the version is
     /// not present.
@@ -11,6 +11,8 @@ pub enum Error {
     EntropyError(String),
     #[error("Internal error: {0}")]
     InternalError(String),
+    #[error("Invalid key derivation path: {0}")]
+    InvalidDerivationPath(String),
     #[error("Key already exists: {0}")]
     KeyAlreadyExists(String),
     #[error("Key not set: {0}")]
@@ -2,9 +2,18 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{CryptoKVStorage, Error, GetResponse, KVStorage};
+#[cfg(any(test, feature = "testing"))]
+use aptos_infallible::Mutex;
 use aptos_time_service::{TimeService, TimeServiceTrait};
+#[cfg(any(test, feature = "testing"))]
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use serde::{de::DeserializeOwned, Serialize};
 use std::collections::HashMap;
+#[cfg(any(test, feature = "testing"))]
+use std::{
+    ops::Range,
+    time::{Duration, Instant},
+};
 
 /// InMemoryStorage represents a key value store that is purely in memory and intended for single
 /// threads (or must be wrapped by a Arc<RwLock<>>). This provides no permission checks and simply
@@ -16,6 +25,8 @@ use std::collections::HashMap;
 pub struct InMemoryStorage {
     data: HashMap<String, Vec<u8>>,
     time_service: TimeService,
+    #[cfg(any(test, feature = "testing"))]
+    faults: Option<FaultInjector>,
 }
 
 impl InMemoryStorage {
@@ -29,16 +40,101 @@ impl InMemoryStorage {
         Self {
             data: HashMap::new(),
             time_service,
+            #[cfg(any(test, feature = "testing"))]
+            faults: None,
+        }
+    }
+
+    /// Injects the configured faults into every subsequent storage operation. Unavailability
+    /// windows are measured from this call.
+    #[cfg(any(test, feature = "testing"))]
+    pub fn with_faults(mut self, config: FaultConfig) -> Self {
+        self.faults = Some(FaultInjector {
+            rng: Mutex::new(StdRng::seed_from_u64(config.seed)),
+            start: self.time_service.now(),
+            config,
+        });
+        self
+    }
+
+    #[cfg(any(test, feature = "testing"))]
+    fn inject_faults(&self, operation: &str) -> Result<(), Error> {
+        let faults = match &self.faults {
+            Some(faults) => faults,
+            None => return Ok(()),
+        };
+
+        let latency = faults.config.latency;
+        if latency > Duration::from_secs(0) {
+            match &self.time_service {
+                // Sleeping on a mock time service would block until another thread advances it
+                TimeService::MockTimeService(time_service) => {
+                    time_service.advance(latency);
+                }
+                time_service => time_service.sleep_blocking(latency),
+            }
+        }
+
+        let elapsed = self
+            .time_service
+            .now()
+            .saturating_duration_since(faults.start);
+        if faults
+            .config
+            .unavailable_windows
+            .iter()
+            .any(|window| window.contains(&elapsed))
+        {
+            return Err(Error::InternalError(format!(
+                "Storage unavailable during {} at {:?}",
+                operation, elapsed
+            )));
         }
+
+        if faults.rng.lock().gen_bool(faults.config.error_probability) {
+            return Err(Error::InternalError(format!(
+                "Injected transient error during {}",
+                operation
+            )));
+        }
+        Ok(())
+    }
+
+    #[cfg(not(any(test, feature = "testing")))]
+    fn inject_faults(&self, _operation: &str) -> Result<(), Error> {
+        Ok(())
     }
 }
 
+/// Faults to inject into `InMemoryStorage`, so that clients can be tested against a flaky secure
+/// backend. Errors are drawn from an RNG seeded with `seed` and time is read from the storage's
+/// time service, so runs are deterministic when using a mock time service.
+#[cfg(any(test, feature = "testing"))]
+#[derive(Clone, Debug, Default)]
+pub struct FaultConfig {
+    /// Probability, in [0, 1], that an operation fails with a transient error.
+    pub error_probability: f64,
+    /// Delay added to every operation. On a mock time service this advances time instead.
+    pub latency: Duration,
+    /// Time ranges, relative to when faults were enabled, during which every operation fails.
+    pub unavailable_windows: Vec<Range<Duration>>,
+    pub seed: u64,
+}
+
+#[cfg(any(test, feature = "testing"))]
+struct FaultInjector {
+    config: FaultConfig,
+    rng: Mutex<StdRng>,
+    start: Instant,
+}
+
 impl KVStorage for InMemoryStorage {
     fn available(&self) -> Result<(), Error> {
-        Ok(())
+        self.inject_faults("available")
     }
 
     fn get<V: DeserializeOwned>(&self, key: &str) -> Result<GetResponse<V>, Error> {
+        self.inject_faults("get")?;
         let response = self
             .data
             .get(key)
@@ -48,6 +144,7 @@ impl KVStorage for InMemoryStorage {
     }
 
     fn set<V: Serialize>(&mut self, key: &str, value: V) -> Result<(), Error> {
+        self.inject_faults("set")?;
         let now = self.time_service.now_secs();
         self.data.insert(
             key.to_string(),
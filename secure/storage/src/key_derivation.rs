@@ -0,0 +1,61 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::Error;
+use aptos_crypto::ed25519::Ed25519PrivateKey;
+use hmac::{Hmac, Mac, NewMac};
+use sha2::Sha512;
+use std::convert::TryFrom;
+
+/// Key of the HMAC computing the master key from the seed, as defined by SLIP-0010 for ed25519
+const ED25519_SEED_KEY: &[u8] = b"ed25519 seed";
+/// First hardened index. Ed25519 only supports the derivation of hardened children.
+const HARDENED_OFFSET: u32 = 1 << 31;
+
+/// Derives the Ed25519 private key at `path` from `seed`, the way SLIP-0010 (BIP32 for ed25519)
+/// does. The path is made of hardened indexes only, e.g. `m/0'/3'`, and `m` is the master key.
+pub fn derive_ed25519_key_from_seed(seed: &[u8], path: &str) -> Result<Ed25519PrivateKey, Error> {
+    let indexes = parse_path(path)?;
+    let (mut key, mut chain_code) = hmac_sha512(ED25519_SEED_KEY, &[seed]);
+    for index in indexes {
+        let index = (index | HARDENED_OFFSET).to_be_bytes();
+        let (child_key, child_chain_code) = hmac_sha512(&chain_code, &[&[0], &key, &index]);
+        key = child_key;
+        chain_code = child_chain_code;
+    }
+    Ed25519PrivateKey::try_from(&key[..]).map_err(|e| Error::InternalError(e.to_string()))
+}
+
+/// Returns the indexes of the path, without their hardened offset
+fn parse_path(path: &str) -> Result<Vec<u32>, Error> {
+    let invalid = || Error::InvalidDerivationPath(path.to_string());
+    let mut segments = path.split('/');
+    if segments.next() != Some("m") {
+        return Err(invalid());
+    }
+    segments
+        .map(|segment| {
+            let index: u32 = segment
+                .strip_suffix('\'')
+                .and_then(|index| index.parse().ok())
+                .ok_or_else(invalid)?;
+            if index >= HARDENED_OFFSET {
+                return Err(invalid());
+            }
+            Ok(index)
+        })
+        .collect()
+}
+
+/// Returns the two halves of the HMAC-SHA512 of the concatenation of `data`
+fn hmac_sha512(key: &[u8], data: &[&[u8]]) -> ([u8; 32], [u8; 32]) {
+    let mut mac = Hmac::<Sha512>::new_varkey(key).expect("HMAC takes keys of any length");
+    for bytes in data {
+        mac.update(bytes);
+    }
+    let output = mac.finalize().into_bytes();
+    let (mut left, mut right) = ([0u8; 32], [0u8; 32]);
+    left.copy_from_slice(&output[..32]);
+    right.copy_from_slice(&output[32..]);
+    (left, right)
+}
@@ -8,6 +8,7 @@ mod crypto_storage;
 mod error;
 mod github;
 mod in_memory;
+mod key_derivation;
 mod kv_storage;
 mod namespaced;
 mod on_disk;
@@ -17,10 +18,11 @@ mod vault;
 
 pub use crate::{
     crypto_kv_storage::CryptoKVStorage,
-    crypto_storage::{CryptoStorage, PublicKeyResponse},
+    crypto_storage::{CryptoStorage, PublicKeyResponse, MASTER_SEED},
     error::Error,
     github::GitHubStorage,
     in_memory::InMemoryStorage,
+    key_derivation::derive_ed25519_key_from_seed,
     kv_storage::{GetResponse, KVStorage},
     namespaced::Namespaced,
     on_disk::OnDiskStorage,
@@ -29,6 +31,9 @@ pub use crate::{
     vault::VaultStorage,
 };
 
+#[cfg(any(test, feature = "testing"))]
+pub use crate::in_memory::FaultConfig;
+
 // Some common serializations for interacting with bytes these must be manually added to types via:
 // #[serde(serialize_with = "to_base64", deserialize_with = "from_base64")]
 // some_value: Vec<u8>
@@ -2,17 +2,25 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{CryptoKVStorage, Error, GetResponse, KVStorage};
+use aptos_logger::warn;
 use aptos_temppath::TempPath;
 use aptos_time_service::{TimeService, TimeServiceTrait};
 use serde::{de::DeserializeOwned, Serialize};
 use serde_json::Value;
 use std::{
     collections::HashMap,
-    fs::{self, File},
-    io::{Read, Write},
-    path::PathBuf,
+    ffi::OsString,
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
 };
 
+/// How long to wait for another process to release the lock on the storage
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(10);
+
 /// OnDiskStorage represents a key value store that is persisted to the local filesystem and is
 /// intended for single threads (or must be wrapped by a Arc<RwLock<>>). This provides no permission
 /// checks and simply offers a proof of concept to unblock building of applications without more
@@ -20,8 +28,16 @@ use std::{
 /// must make copies of all key material which violates the code base. It violates it because
 /// the anticipation is that data stores would securely handle key material. This should not be used
 /// in production.
+///
+/// Several processes, e.g. a node and the operational tool, may share the file: accesses hold an
+/// advisory lock on a `.lock` file next to it (except on windows), and writes replace the file
+/// atomically. The file a write replaces is kept as a `.backup` file, which is read instead if the
+/// file turns out to be corrupted.
 pub struct OnDiskStorage {
     file_path: PathBuf,
+    file_dir: PathBuf,
+    lock_path: PathBuf,
+    backup_path: PathBuf,
     temp_path: TempPath,
     time_service: TimeService,
 }
@@ -43,38 +59,168 @@ impl OnDiskStorage {
             .map_or(PathBuf::new(), |p| p.to_path_buf());
 
         Self {
+            lock_path: with_extension(&file_path, "lock"),
+            backup_path: with_extension(&file_path, "backup"),
             file_path,
-            temp_path: TempPath::new_with_temp_dir(file_dir),
+            temp_path: TempPath::new_with_temp_dir(file_dir.clone()),
+            file_dir,
             time_service,
         }
     }
 
+    /// Reads the data, from the backup if the file is corrupted. The caller holds the lock.
     fn read(&self) -> Result<HashMap<String, Value>, Error> {
-        let mut file = File::open(&self.file_path)?;
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)?;
-        if contents.is_empty() {
-            return Ok(HashMap::new());
+        self.read_with_status().map(|(data, _)| data)
+    }
+
+    /// Reads the data as `read` does, and returns whether it was read from the file rather than
+    /// from the backup
+    fn read_with_status(&self) -> Result<(HashMap<String, Value>, bool), Error> {
+        match read_data(&self.file_path) {
+            Ok(data) => Ok((data, true)),
+            Err(Error::SerializationError(error)) => {
+                let data = read_data(&self.backup_path).map_err(|_| {
+                    Error::SerializationError(format!(
+                        "{:?} is corrupted and has no valid backup: {}",
+                        self.file_path, error
+                    ))
+                })?;
+                warn!(
+                    "{:?} is corrupted ({}), using the last data written instead",
+                    self.file_path, error
+                );
+                Ok((data, false))
+            }
+            Err(error) => Err(error),
         }
-        let data = serde_json::from_str(&contents)?;
-        Ok(data)
     }
 
-    fn write(&self, data: &HashMap<String, Value>) -> Result<(), Error> {
-        let contents = serde_json::to_vec(data)?;
+    /// Replaces the data, so that readers and crashes see either the old or the new data but
+    /// nothing in between. The file replaced becomes the backup if `keep_backup`, which the
+    /// caller leaves out when the file is corrupted. The caller holds the exclusive lock.
+    fn write(&self, data: &HashMap<String, Value>, keep_backup: bool) -> Result<(), Error> {
         let mut file = File::create(self.temp_path.path())?;
-        file.write_all(&contents)?;
+        file.write_all(&serde_json::to_vec(data)?)?;
+        file.sync_all()?;
+        if keep_backup {
+            if let Err(error) = self.link_backup() {
+                warn!("Unable to back up {:?}: {}", self.file_path, error);
+            }
+        }
         fs::rename(&self.temp_path, &self.file_path)?;
-        Ok(())
+        sync_dir(&self.file_dir)
+    }
+
+    /// Links the file as the backup, which keeps its data without writing it again
+    fn link_backup(&self) -> io::Result<()> {
+        match fs::remove_file(&self.backup_path) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error),
+            _ => {}
+        }
+        fs::hard_link(&self.file_path, &self.backup_path)
+    }
+
+    fn lock(&self, exclusive: bool) -> Result<FileLock, Error> {
+        FileLock::acquire(&self.lock_path, exclusive)
+    }
+}
+
+fn read_data(path: &Path) -> Result<HashMap<String, Value>, Error> {
+    let mut file = File::open(path)?;
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)?;
+    if contents.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let data = serde_json::from_str(&contents)?;
+    Ok(data)
+}
+
+/// Persists the entries of the directory, so that a file renamed into it survives a crash
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> Result<(), Error> {
+    let dir = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    File::open(dir)?.sync_all()?;
+    Ok(())
+}
+
+/// Directories can't be opened to be synced on windows
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> Result<(), Error> {
+    Ok(())
+}
+
+/// `path` with `extension` appended to its file name, e.g. `storage.json.lock`
+fn with_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut file_name = path.file_name().map(OsString::from).unwrap_or_default();
+    file_name.push(".");
+    file_name.push(extension);
+    path.with_file_name(file_name)
+}
+
+/// Advisory lock on a file, held until dropped. The file is locked rather than the storage
+/// itself, which writes replace.
+struct FileLock {
+    _file: File,
+}
+
+impl FileLock {
+    fn acquire(path: &Path, exclusive: bool) -> Result<Self, Error> {
+        let file = OpenOptions::new().create(true).write(true).open(path)?;
+        let start = Instant::now();
+        while !try_lock(&file, exclusive)? {
+            if start.elapsed() > LOCK_TIMEOUT {
+                return Err(Error::InternalError(format!(
+                    "Timed out waiting for the lock on {:?}",
+                    path
+                )));
+            }
+            thread::sleep(LOCK_RETRY_INTERVAL);
+        }
+        Ok(Self { _file: file })
+    }
+}
+
+/// Locks the file unless another process holds a conflicting lock, returning whether it did
+#[cfg(unix)]
+fn try_lock(file: &File, exclusive: bool) -> Result<bool, Error> {
+    use nix::{
+        errno::Errno,
+        fcntl::{flock, FlockArg},
+    };
+    use std::os::unix::io::AsRawFd;
+
+    let arg = if exclusive {
+        FlockArg::LockExclusiveNonblock
+    } else {
+        FlockArg::LockSharedNonblock
+    };
+    match flock(file.as_raw_fd(), arg) {
+        Ok(()) => Ok(true),
+        Err(Errno::EWOULDBLOCK) | Err(Errno::EINTR) => Ok(false),
+        Err(error) => Err(Error::InternalError(format!(
+            "Unable to lock the storage: {}",
+            error
+        ))),
     }
 }
 
+#[cfg(not(unix))]
+fn try_lock(_file: &File, _exclusive: bool) -> Result<bool, Error> {
+    Ok(true)
+}
+
 impl KVStorage for OnDiskStorage {
     fn available(&self) -> Result<(), Error> {
         Ok(())
     }
 
     fn get<V: DeserializeOwned>(&self, key: &str) -> Result<GetResponse<V>, Error> {
+        let _lock = self.lock(false)?;
         let mut data = self.read()?;
         data.remove(key)
             .ok_or_else(|| Error::KeyNotSet(key.to_string()))
@@ -83,17 +229,20 @@ impl KVStorage for OnDiskStorage {
 
     fn set<V: Serialize>(&mut self, key: &str, value: V) -> Result<(), Error> {
         let now = self.time_service.now_secs();
-        let mut data = self.read()?;
+        let _lock = self.lock(true)?;
+        let (mut data, file_is_valid) = self.read_with_status()?;
         data.insert(
             key.to_string(),
             serde_json::to_value(&GetResponse::new(value, now))?,
         );
-        self.write(&data)
+        self.write(&data, file_is_valid)
     }
 
     #[cfg(any(test, feature = "testing"))]
     fn reset_and_clear(&mut self) -> Result<(), Error> {
-        self.write(&HashMap::new())
+        let _lock = self.lock(true)?;
+        let (_, file_is_valid) = self.read_with_status()?;
+        self.write(&HashMap::new(), file_is_valid)
     }
 }
 
@@ -1,10 +1,63 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{tests::suite, InMemoryStorage, Storage};
+use crate::{tests::suite, Error, FaultConfig, InMemoryStorage, KVStorage, Storage};
+use aptos_time_service::TimeService;
+use std::time::Duration;
 
 #[test]
 fn in_memory() {
     let mut storage = Storage::from(InMemoryStorage::new());
     suite::execute_all_storage_tests(&mut storage);
 }
+
+#[test]
+fn in_memory_without_faults() {
+    let mut storage = Storage::from(InMemoryStorage::new().with_faults(FaultConfig::default()));
+    suite::execute_all_storage_tests(&mut storage);
+}
+
+#[test]
+fn in_memory_transient_errors_are_deterministic() {
+    let config = FaultConfig {
+        error_probability: 0.5,
+        seed: 7,
+        ..FaultConfig::default()
+    };
+    let outcomes = |config: FaultConfig| {
+        let mut storage = InMemoryStorage::new().with_faults(config);
+        (0..64)
+            .map(|i| storage.set("key", i).is_ok())
+            .collect::<Vec<_>>()
+    };
+
+    let first = outcomes(config.clone());
+    assert_eq!(first, outcomes(config));
+    assert!(first.contains(&true));
+    assert!(first.contains(&false));
+}
+
+#[test]
+fn in_memory_unavailable_window() {
+    let time_service = TimeService::mock();
+    let mock = time_service.clone().into_mock();
+    let mut storage =
+        InMemoryStorage::new_with_time_service(time_service).with_faults(FaultConfig {
+            latency: Duration::from_secs(1),
+            unavailable_windows: vec![Duration::from_secs(5)..Duration::from_secs(10)],
+            ..FaultConfig::default()
+        });
+
+    // Every operation advances the mock time by the configured latency
+    storage.set("key", 1u64).unwrap();
+    assert_eq!(storage.get::<u64>("key").unwrap().value, 1);
+    mock.advance(Duration::from_secs(3));
+    assert!(matches!(storage.available(), Err(Error::InternalError(_))));
+    assert!(matches!(
+        storage.get::<u64>("key"),
+        Err(Error::InternalError(_))
+    ));
+    mock.advance(Duration::from_secs(5));
+    storage.available().unwrap();
+    assert_eq!(storage.get::<u64>("key").unwrap().value, 1);
+}
@@ -0,0 +1,62 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{derive_ed25519_key_from_seed, Error};
+
+/// Test vector 1 of SLIP-0010 for ed25519
+const SEED: &str = "000102030405060708090a0b0c0d0e0f";
+const DERIVED_KEYS: &[(&str, &str)] = &[
+    (
+        "m",
+        "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7",
+    ),
+    (
+        "m/0'",
+        "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3",
+    ),
+    (
+        "m/0'/1'",
+        "b1d0bad404bf35da785a64ca1ac54b2617211d2777696fbffaf208f746ae84f2",
+    ),
+    (
+        "m/0'/1'/2'",
+        "92a5b23c0b8a99e37d07df3fb9966917f5d06e02ddbd909c7e184371463e9fc9",
+    ),
+    (
+        "m/0'/1'/2'/2'",
+        "30d1dc7e5fc04c31219ab25a27ae00b50f6fd66622f6e9c913253d6511d1e662",
+    ),
+    (
+        "m/0'/1'/2'/2'/1000000000'",
+        "8f94d394a8e8fd6b1bc2f3f49f5c47e385281d5c17e65324b0f62483e37e8793",
+    ),
+];
+
+#[test]
+fn test_derive_slip10_vectors() {
+    let seed = hex::decode(SEED).unwrap();
+    for (path, key) in DERIVED_KEYS {
+        let derived = derive_ed25519_key_from_seed(&seed, path).unwrap();
+        assert_eq!(hex::encode(derived.to_bytes()), *key, "{}", path);
+    }
+}
+
+#[test]
+fn test_derive_invalid_paths() {
+    let seed = hex::decode(SEED).unwrap();
+    for path in [
+        "",
+        "0'",
+        "m/",
+        "m/0",
+        "m/-1'",
+        "m/2147483648'",
+        "m/0'/x'",
+        "n/0'",
+    ] {
+        assert_eq!(
+            derive_ed25519_key_from_seed(&seed, path).unwrap_err(),
+            Error::InvalidDerivationPath(path.to_string())
+        );
+    }
+}
@@ -3,6 +3,7 @@
 
 mod github;
 mod in_memory;
+mod key_derivation;
 mod on_disk;
 mod suite;
 mod vault;
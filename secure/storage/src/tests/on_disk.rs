@@ -1,8 +1,9 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{tests::suite, OnDiskStorage, Storage};
+use crate::{tests::suite, KVStorage, OnDiskStorage, Storage};
 use aptos_temppath::TempPath;
+use std::{fs, thread};
 
 #[test]
 fn on_disk() {
@@ -10,3 +11,63 @@ fn on_disk() {
     let mut storage = Storage::from(OnDiskStorage::new(path_buf));
     suite::execute_all_storage_tests(&mut storage);
 }
+
+#[test]
+fn on_disk_corrupted() {
+    let path_buf = TempPath::new().path().to_path_buf();
+    let mut storage = OnDiskStorage::new(path_buf.clone());
+    storage.set("key", 1u64).unwrap();
+    storage.set("key", 2u64).unwrap();
+
+    // A partial write of another process, the data falling back to the file the last write
+    // replaced
+    fs::write(&path_buf, "{\"key\":{\"last_up").unwrap();
+    assert_eq!(storage.get::<u64>("key").unwrap().value, 1);
+
+    // Writes restore the file, keeping the backup rather than the corrupted file
+    storage.set("other_key", 3u64).unwrap();
+    let mut storage = OnDiskStorage::new(path_buf.clone());
+    assert_eq!(storage.get::<u64>("key").unwrap().value, 1);
+    assert_eq!(storage.get::<u64>("other_key").unwrap().value, 3);
+    fs::write(&path_buf, "{").unwrap();
+    assert_eq!(storage.get::<u64>("key").unwrap().value, 1);
+    assert!(storage.get::<u64>("other_key").is_err());
+
+    // Once the file is valid again, the next write backs it up
+    storage.set("key", 4u64).unwrap();
+    storage.set("key", 5u64).unwrap();
+    fs::write(&path_buf, "{").unwrap();
+    assert_eq!(storage.get::<u64>("key").unwrap().value, 4);
+}
+
+#[test]
+fn on_disk_concurrent_writers() {
+    const WRITERS: usize = 4;
+    const KEYS_PER_WRITER: usize = 20;
+
+    let temp_path = TempPath::new();
+    let writers: Vec<_> = (0..WRITERS)
+        .map(|writer| {
+            let path_buf = temp_path.path().to_path_buf();
+            thread::spawn(move || {
+                let mut storage = OnDiskStorage::new(path_buf);
+                for key in 0..KEYS_PER_WRITER {
+                    storage
+                        .set(&format!("{}_{}", writer, key), key as u64)
+                        .unwrap();
+                }
+            })
+        })
+        .collect();
+    for writer in writers {
+        writer.join().unwrap();
+    }
+
+    let storage = OnDiskStorage::new(temp_path.path().to_path_buf());
+    for writer in 0..WRITERS {
+        for key in 0..KEYS_PER_WRITER {
+            let value = storage.get::<u64>(&format!("{}_{}", writer, key)).unwrap();
+            assert_eq!(value.value, key as u64);
+        }
+    }
+}
@@ -1,7 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{CryptoStorage, Error, KVStorage, Storage};
+use crate::{derive_ed25519_key_from_seed, CryptoStorage, Error, KVStorage, Storage};
 
 use aptos_crypto::{
     ed25519::Ed25519PrivateKey, test_utils::TestAptosCrypto, HashValue, PrivateKey, Signature,
@@ -23,6 +23,7 @@ const STORAGE_TESTS: &[fn(&mut Storage)] = &[
     test_create_get_key_pair,
     test_create_key_pair_and_perform_rotations,
     test_create_sign_rotate_sign,
+    test_derive_key,
     test_ensure_storage_is_available,
     test_get_non_existent,
     test_get_public_key_previous_version,
@@ -189,6 +190,27 @@ fn test_import_key(storage: &mut Storage) {
     assert_ne!(message_signature, rotated_message_signature);
 }
 
+/// This test derives keys from an imported master seed, and checks that they only depend on the
+/// seed and their path.
+fn test_derive_key(storage: &mut Storage) {
+    let seed = [7u8; 32];
+    assert!(
+        storage.derive_ed25519_key("m/0'").is_err(),
+        "Deriving a key without a master seed should have failed!"
+    );
+
+    storage.import_master_seed(seed).unwrap();
+    let key = storage.derive_ed25519_key("m/0'").unwrap();
+    assert_eq!(key, storage.derive_ed25519_key("m/0'").unwrap());
+    assert_eq!(key, derive_ed25519_key_from_seed(&seed, "m/0'").unwrap());
+    assert_ne!(key, storage.derive_ed25519_key("m/1'").unwrap());
+    assert_ne!(key, storage.derive_ed25519_key("m/0'/0'").unwrap());
+    assert_eq!(
+        storage.derive_ed25519_key("m/0").unwrap_err(),
+        Error::InvalidDerivationPath("m/0".to_string())
+    );
+}
+
 /// This test stores different types of values into storage, retrieves them, and asserts
 /// that the value unwrap functions return an unexpected type error on an incorrect unwrap.
 fn test_verify_incorrect_value_types(storage: &mut Storage) {
@@ -29,7 +29,7 @@ use aptos_types::{
 use async_trait::async_trait;
 use futures::StreamExt;
 use network::{
-    application::interface::NetworkInterface,
+    application::{interface::NetworkInterface, reputation::Misbehavior},
     protocols::{rpc::error::RpcError, wire::handshake::v1::ProtocolId},
 };
 use rand::seq::SliceRandom;
@@ -319,7 +319,7 @@ impl AptosNetDataClient {
             .await;
 
         match result {
-            Ok(response) => {
+            Ok((response, response_size)) => {
                 debug!(
                     (LogSchema::new(LogEntry::StorageServiceResponse)
                         .event(LogEvent::ResponseSuccess)
@@ -349,6 +349,7 @@ impl AptosNetDataClient {
                 let context = ResponseContext {
                     id,
                     response_callback: Box::new(response_callback),
+                    response_size: response_size as u64,
                 };
                 Ok(Response::new(context, response))
             }
@@ -392,6 +393,12 @@ impl AptosNetDataClient {
         _request: &StorageServiceRequest,
         error_type: ErrorType,
     ) {
+        // Let the network ban peers which keep sending us malicious data
+        if let ErrorType::Malicious = error_type {
+            self.network_client
+                .peer_metadata_storage()
+                .report_misbehavior(peer, Misbehavior::InvalidProof);
+        }
         self.peer_states
             .write()
             .update_score_error(peer, error_type);
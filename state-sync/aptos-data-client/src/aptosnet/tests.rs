@@ -809,6 +809,7 @@ async fn optimal_chunk_size_calculations() {
     let storage_service_config = StorageServiceConfig {
         max_account_states_chunk_sizes,
         max_concurrent_requests: 0,
+        max_concurrent_public_requests: 0,
         max_epoch_chunk_size,
         max_lru_cache_size: 0,
         max_network_channel_size: 0,
@@ -152,6 +152,8 @@ pub struct ResponseContext {
     /// A callback for notifying the data-client source about an error with this
     /// response.
     pub response_callback: Box<dyn ResponseCallback>,
+    /// The number of bytes the response was received in over the network.
+    pub response_size: u64,
 }
 
 /// A response from the Data Client for a single API call.
@@ -37,6 +37,7 @@ mod tests;
 // will be retrieved using FIFO ordering.
 const EVENT_NOTIFICATION_CHANNEL_SIZE: usize = 100;
 const RECONFIG_NOTIFICATION_CHANNEL_SIZE: usize = 1;
+const STATE_CHECKPOINT_NOTIFICATION_CHANNEL_SIZE: usize = 1;
 
 #[derive(Clone, Debug, Deserialize, Error, PartialEq, Serialize)]
 pub enum Error {
@@ -67,6 +68,11 @@ pub trait EventNotificationSender: Send {
     /// This is useful for forcing reconfiguration notifications even if no
     /// reconfiguration event was processed (e.g., on startup).
     fn notify_initial_configs(&mut self, version: Version) -> Result<(), Error>;
+
+    /// Notify the subscription service of the latest state checkpoint version
+    /// covered by a signed ledger info. Data up to this version is final and
+    /// will not be replayed (e.g., after a restore).
+    fn notify_state_checkpoint(&mut self, version: Version) -> Result<(), Error>;
 }
 
 /// The subscription service offered by state sync, responsible for notifying
@@ -79,6 +85,10 @@ pub struct EventSubscriptionService {
     // Reconfig subscription registry
     reconfig_subscriptions: HashMap<SubscriptionId, ReconfigSubscription>,
 
+    // State checkpoint subscription registry and the latest checkpoint notified
+    state_checkpoint_subscriptions: HashMap<SubscriptionId, StateCheckpointSubscription>,
+    latest_state_checkpoint_version: Option<Version>,
+
     // Database to fetch on-chain configuration data
     storage: Arc<RwLock<DbReaderWriter>>,
 
@@ -95,6 +105,8 @@ impl EventSubscriptionService {
             event_key_subscriptions: HashMap::new(),
             subscription_id_to_event_subscription: HashMap::new(),
             reconfig_subscriptions: HashMap::new(),
+            state_checkpoint_subscriptions: HashMap::new(),
+            latest_state_checkpoint_version: None,
             config_registry: config_registry.to_vec(),
             storage,
             subscription_id_generator: U64IdGenerator::new(),
@@ -184,6 +196,42 @@ impl EventSubscriptionService {
         })
     }
 
+    /// Returns a StateCheckpointNotificationListener that can be monitored for
+    /// new state checkpoints. Subscribers will be sent a notification whenever
+    /// the latest state checkpoint covered by a signed ledger info advances,
+    /// i.e., whenever more data becomes safe to finalize. Note: only the latest
+    /// notification is kept if the subscriber falls behind.
+    pub fn subscribe_to_state_checkpoints(
+        &mut self,
+    ) -> Result<StateCheckpointNotificationListener, Error> {
+        let (notification_sender, notification_receiver) = aptos_channel::new(
+            QueueStyle::KLAST,
+            STATE_CHECKPOINT_NOTIFICATION_CHANNEL_SIZE,
+            None,
+        );
+
+        // Create a new state checkpoint subscription
+        let subscription_id = self.get_new_subscription_id();
+        let state_checkpoint_subscription = StateCheckpointSubscription {
+            notification_sender,
+        };
+
+        // Store the new subscription
+        if let Some(old_subscription) = self
+            .state_checkpoint_subscriptions
+            .insert(subscription_id, state_checkpoint_subscription)
+        {
+            panic!(
+                "Duplicate state checkpoint subscription found! This should not occur! ID: {}, subscription: {:?}",
+                subscription_id, old_subscription
+            );
+        }
+
+        Ok(StateCheckpointNotificationListener {
+            notification_receiver,
+        })
+    }
+
     fn get_new_subscription_id(&mut self) -> u64 {
         self.subscription_id_generator.next()
     }
@@ -337,6 +385,21 @@ impl EventNotificationSender for EventSubscriptionService {
     fn notify_initial_configs(&mut self, version: Version) -> Result<(), Error> {
         self.notify_reconfiguration_subscribers(version)
     }
+
+    fn notify_state_checkpoint(&mut self, version: Version) -> Result<(), Error> {
+        if let Some(latest_version) = self.latest_state_checkpoint_version {
+            if version <= latest_version {
+                return Ok(()); // The checkpoint has already been notified!
+            }
+        }
+        self.latest_state_checkpoint_version = Some(version);
+
+        for (_, state_checkpoint_subscription) in self.state_checkpoint_subscriptions.iter_mut() {
+            state_checkpoint_subscription.notify_subscriber_of_checkpoint(version)?;
+        }
+
+        Ok(())
+    }
 }
 
 /// A unique ID used to identify each subscription.
@@ -391,6 +454,23 @@ impl ReconfigSubscription {
     }
 }
 
+/// A single state checkpoint subscription, holding the channel to send the
+/// corresponding notifications.
+#[derive(Debug)]
+struct StateCheckpointSubscription {
+    pub notification_sender: channel::aptos_channel::Sender<(), StateCheckpointNotification>,
+}
+
+impl StateCheckpointSubscription {
+    fn notify_subscriber_of_checkpoint(&mut self, version: Version) -> Result<(), Error> {
+        let state_checkpoint_notification = StateCheckpointNotification { version };
+
+        self.notification_sender
+            .push((), state_checkpoint_notification)
+            .map_err(|error| Error::UnexpectedErrorEncountered(format!("{:?}", error)))
+    }
+}
+
 /// A notification for events.
 #[derive(Debug)]
 pub struct EventNotification {
@@ -405,12 +485,21 @@ pub struct ReconfigNotification {
     pub on_chain_configs: OnChainConfigPayload,
 }
 
+/// A notification for the latest state checkpoint that is safe to finalize.
+#[derive(Debug)]
+pub struct StateCheckpointNotification {
+    pub version: Version,
+}
+
 /// A subscription listener for on-chain events.
 pub type EventNotificationListener = NotificationListener<EventNotification>;
 
 /// A subscription listener for reconfigurations.
 pub type ReconfigNotificationListener = NotificationListener<ReconfigNotification>;
 
+/// A subscription listener for state checkpoints.
+pub type StateCheckpointNotificationListener = NotificationListener<StateCheckpointNotification>;
+
 /// The component responsible for listening to subscription notifications.
 #[derive(Debug)]
 pub struct NotificationListener<T> {
@@ -5,7 +5,7 @@
 
 use crate::{
     Error, EventNotificationListener, EventNotificationSender, EventSubscriptionService,
-    ReconfigNotificationListener,
+    ReconfigNotificationListener, StateCheckpointNotificationListener,
 };
 use aptos_infallible::RwLock;
 use aptos_types::{
@@ -383,6 +383,38 @@ fn test_no_events_no_subscribers() {
     notify_events(&mut event_service, 1, vec![]);
 }
 
+#[test]
+fn test_state_checkpoint_subscribers() {
+    // Create subscription service and mock database
+    let mut event_service = create_event_subscription_service();
+
+    // Add several state checkpoint subscribers
+    let mut checkpoint_listener_1 = event_service.subscribe_to_state_checkpoints().unwrap();
+    let mut checkpoint_listener_2 = event_service.subscribe_to_state_checkpoints().unwrap();
+
+    // Notify the service of a new checkpoint and verify both subscribers are notified
+    assert_ok!(event_service.notify_state_checkpoint(10));
+    verify_state_checkpoint_notification_received(
+        vec![&mut checkpoint_listener_1, &mut checkpoint_listener_2],
+        10,
+    );
+
+    // Verify stale or repeated checkpoints are not notified
+    assert_ok!(event_service.notify_state_checkpoint(10));
+    assert_ok!(event_service.notify_state_checkpoint(5));
+    for listener in [&mut checkpoint_listener_1, &mut checkpoint_listener_2] {
+        assert!(listener.select_next_some().now_or_never().is_none());
+    }
+
+    // Notify the service of several checkpoints and verify only the latest is kept
+    assert_ok!(event_service.notify_state_checkpoint(11));
+    assert_ok!(event_service.notify_state_checkpoint(20));
+    verify_state_checkpoint_notification_received(
+        vec![&mut checkpoint_listener_1, &mut checkpoint_listener_2],
+        20,
+    );
+}
+
 #[test]
 fn test_missing_configs() {
     // Create a subscription service and mock database with a custom config registry that
@@ -492,6 +524,21 @@ fn verify_event_notification_received(
     }
 }
 
+// Ensures that the specified listeners have received a state checkpoint notification
+// for the expected version.
+fn verify_state_checkpoint_notification_received(
+    listeners: Vec<&mut StateCheckpointNotificationListener>,
+    expected_version: Version,
+) {
+    for listener in listeners {
+        if let Some(checkpoint_notification) = listener.select_next_some().now_or_never() {
+            assert_eq!(checkpoint_notification.version, expected_version);
+        } else {
+            panic!("Expected a state checkpoint notification but got None!");
+        }
+    }
+}
+
 // Ensures that the specified listeners have received the expected notifications.
 // Also verifies that the reconfiguration notifications contain all on-chain configs.
 fn verify_reconfig_notifications_received(
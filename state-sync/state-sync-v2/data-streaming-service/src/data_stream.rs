@@ -21,10 +21,11 @@ use aptos_data_client::{
     ResponsePayload,
 };
 use aptos_id_generator::{IdGenerator, U64IdGenerator};
-use aptos_infallible::Mutex;
+use aptos_infallible::{Mutex, RwLock};
 use aptos_logger::prelude::*;
 use channel::{aptos_channel, message_queues::QueueStyle};
 use futures::{stream::FusedStream, Stream};
+use serde::{Deserialize, Serialize};
 use std::{
     collections::{BTreeMap, VecDeque},
     pin::Pin,
@@ -95,6 +96,9 @@ pub struct DataStream<T> {
     // notification to the listener. If so, the stream is dead and it will
     // stop sending notifications. This handles when clients drop the listener.
     send_failure: bool,
+
+    // The summaries of all active streams, where this stream reports its progress
+    active_streams: ActiveDataStreams,
 }
 
 impl<T: AptosDataClient + Send + Clone + 'static> DataStream<T> {
@@ -105,6 +109,7 @@ impl<T: AptosDataClient + Send + Clone + 'static> DataStream<T> {
         aptos_data_client: T,
         notification_id_generator: Arc<U64IdGenerator>,
         advertised_data: &AdvertisedData,
+        active_streams: ActiveDataStreams,
     ) -> Result<(Self, DataStreamListener), Error> {
         // Create a new data stream listener
         let (notification_sender, notification_receiver) = aptos_channel::new(
@@ -112,11 +117,18 @@ impl<T: AptosDataClient + Send + Clone + 'static> DataStream<T> {
             config.max_data_stream_channel_sizes as usize,
             None,
         );
-        let data_stream_listener = DataStreamListener::new(notification_receiver);
+        let data_stream_listener = DataStreamListener::new(
+            data_stream_id,
+            notification_receiver,
+            active_streams.clone(),
+        );
 
         // Create a new stream engine
         let stream_engine = StreamEngine::new(stream_request, advertised_data)?;
 
+        // Report the new stream as active
+        active_streams.insert(DataStreamSummary::new(data_stream_id, stream_request));
+
         // Create a new data stream
         let data_stream = Self {
             config,
@@ -131,6 +143,7 @@ impl<T: AptosDataClient + Send + Clone + 'static> DataStream<T> {
             stream_end_notification_id: None,
             request_failure_count: 0,
             send_failure: false,
+            active_streams,
         };
 
         Ok((data_stream, data_stream_listener))
@@ -268,6 +281,8 @@ impl<T: AptosDataClient + Send + Clone + 'static> DataStream<T> {
     }
 
     fn send_data_notification(&mut self, data_notification: DataNotification) -> Result<(), Error> {
+        self.active_streams
+            .update(self.data_stream_id, |summary| summary.notifications += 1);
         if let Err(error) = self.notification_sender.push((), data_notification) {
             let error = Error::UnexpectedErrorEncountered(error.to_string());
             warn!(
@@ -331,6 +346,10 @@ impl<T: AptosDataClient + Send + Clone + 'static> DataStream<T> {
 
                 match client_response {
                     Ok(client_response) => {
+                        let response_size = client_response.context.response_size;
+                        self.active_streams.update(self.data_stream_id, |summary| {
+                            summary.bytes_received += response_size
+                        });
                         if sanity_check_client_response(client_request, &client_response) {
                             self.send_data_notification_to_client(client_request, client_response)?;
                         } else {
@@ -585,25 +604,201 @@ impl<T> Drop for DataStream<T> {
         for spawned_task in &self.spawned_tasks {
             spawned_task.abort();
         }
+        self.active_streams.remove(self.data_stream_id);
+    }
+}
+
+/// A summary of the progress of an active data stream. Summaries are exported
+/// in the metrics (labeled by stream ID) and listed by the debug interface of
+/// the node, so that stuck streams can be diagnosed without trace logs.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct DataStreamSummary {
+    pub stream_id: DataStreamId,
+    pub stream_type: String,
+
+    /// The first version requested by the stream (or the first epoch or account
+    /// index, depending on the stream type)
+    pub requested_start: u64,
+
+    /// The last version requested by the stream, if the stream has an end
+    pub requested_end: Option<u64>,
+
+    /// The number of data notifications sent along the stream
+    pub notifications: u64,
+
+    /// The number of bytes of the data client responses received for the stream
+    pub bytes_received: u64,
+
+    /// The number of consecutive timeouts encountered by the stream listener
+    pub consecutive_timeouts: u64,
+}
+
+impl DataStreamSummary {
+    fn new(stream_id: DataStreamId, stream_request: &StreamRequest) -> Self {
+        let (requested_start, requested_end) = match stream_request {
+            StreamRequest::GetAllAccounts(request) => (request.start_index, None),
+            StreamRequest::GetAllEpochEndingLedgerInfos(request) => (request.start_epoch, None),
+            StreamRequest::GetAllTransactions(request) => {
+                (request.start_version, Some(request.end_version))
+            }
+            StreamRequest::GetAllTransactionOutputs(request) => {
+                (request.start_version, Some(request.end_version))
+            }
+            StreamRequest::ContinuouslyStreamTransactions(request) => (
+                request.start_version,
+                request
+                    .target
+                    .as_ref()
+                    .map(|target| target.ledger_info().version()),
+            ),
+            StreamRequest::ContinuouslyStreamTransactionOutputs(request) => (
+                request.start_version,
+                request
+                    .target
+                    .as_ref()
+                    .map(|target| target.ledger_info().version()),
+            ),
+            StreamRequest::TerminateStream(_) => (0, None),
+        };
+
+        Self {
+            stream_id,
+            stream_type: stream_request.get_label().into(),
+            requested_start,
+            requested_end,
+            notifications: 0,
+            bytes_received: 0,
+            consecutive_timeouts: 0,
+        }
+    }
+
+    /// Updates the per-stream metrics with the summary
+    fn export_metrics(&self) {
+        let stream_id = self.stream_id.to_string();
+        let labels = [stream_id.as_str(), self.stream_type.as_str()];
+        metrics::ACTIVE_STREAM_REQUESTED_RANGE
+            .with_label_values(&[labels[0], labels[1], "start"])
+            .set(self.requested_start as i64);
+        if let Some(requested_end) = self.requested_end {
+            metrics::ACTIVE_STREAM_REQUESTED_RANGE
+                .with_label_values(&[labels[0], labels[1], "end"])
+                .set(requested_end as i64);
+        }
+        metrics::ACTIVE_STREAM_NOTIFICATIONS
+            .with_label_values(&labels)
+            .set(self.notifications as i64);
+        metrics::ACTIVE_STREAM_BYTES_RECEIVED
+            .with_label_values(&labels)
+            .set(self.bytes_received as i64);
+        metrics::ACTIVE_STREAM_CONSECUTIVE_TIMEOUTS
+            .with_label_values(&labels)
+            .set(self.consecutive_timeouts as i64);
+    }
+
+    /// Removes the per-stream metrics of the summary (e.g., once the stream
+    /// is terminated). Metrics that were never exported are ignored.
+    fn remove_metrics(&self) {
+        let stream_id = self.stream_id.to_string();
+        let labels = [stream_id.as_str(), self.stream_type.as_str()];
+        for bound in ["start", "end"] {
+            let _ = metrics::ACTIVE_STREAM_REQUESTED_RANGE
+                .remove_label_values(&[labels[0], labels[1], bound]);
+        }
+        let _ = metrics::ACTIVE_STREAM_NOTIFICATIONS.remove_label_values(&labels);
+        let _ = metrics::ACTIVE_STREAM_BYTES_RECEIVED.remove_label_values(&labels);
+        let _ = metrics::ACTIVE_STREAM_CONSECUTIVE_TIMEOUTS.remove_label_values(&labels);
+    }
+}
+
+/// The summaries of all active data streams. These are shared between the data
+/// streams (that keep them up-to-date) and the debug interface of the node.
+#[derive(Clone, Debug, Default)]
+pub struct ActiveDataStreams {
+    summaries: Arc<RwLock<BTreeMap<DataStreamId, DataStreamSummary>>>,
+}
+
+impl ActiveDataStreams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the summaries of all active streams, ordered by stream ID
+    pub fn summaries(&self) -> Vec<DataStreamSummary> {
+        self.summaries.read().values().cloned().collect()
+    }
+
+    fn insert(&self, summary: DataStreamSummary) {
+        summary.export_metrics();
+        self.summaries.write().insert(summary.stream_id, summary);
+    }
+
+    /// Applies the given update to the summary of the stream (if it's still
+    /// active) and exports the updated summary.
+    fn update<F: FnOnce(&mut DataStreamSummary)>(&self, data_stream_id: DataStreamId, update: F) {
+        if let Some(summary) = self.summaries.write().get_mut(&data_stream_id) {
+            update(summary);
+            summary.export_metrics();
+        }
+    }
+
+    fn remove(&self, data_stream_id: DataStreamId) {
+        if let Some(summary) = self.summaries.write().remove(&data_stream_id) {
+            summary.remove_metrics();
+        }
     }
 }
 
 /// Allows listening to data streams (i.e., streams of data notifications).
 #[derive(Debug)]
 pub struct DataStreamListener {
+    data_stream_id: DataStreamId,
     notification_receiver: channel::aptos_channel::Receiver<(), DataNotification>,
 
     /// Stores the number of consecutive timeouts encountered when listening to this stream
-    pub num_consecutive_timeouts: u64,
+    num_consecutive_timeouts: u64,
+
+    // The summaries of all active streams, where the timeouts are reported
+    active_streams: ActiveDataStreams,
 }
 
 impl DataStreamListener {
     pub fn new(
+        data_stream_id: DataStreamId,
         notification_receiver: channel::aptos_channel::Receiver<(), DataNotification>,
+        active_streams: ActiveDataStreams,
     ) -> Self {
         Self {
+            data_stream_id,
             notification_receiver,
             num_consecutive_timeouts: 0,
+            active_streams,
+        }
+    }
+
+    /// Returns the number of consecutive timeouts encountered when listening to this stream
+    pub fn num_consecutive_timeouts(&self) -> u64 {
+        self.num_consecutive_timeouts
+    }
+
+    /// Records a timeout when listening to this stream and returns the number
+    /// of consecutive timeouts.
+    pub fn increment_consecutive_timeouts(&mut self) -> u64 {
+        self.set_num_consecutive_timeouts(self.num_consecutive_timeouts + 1);
+        self.num_consecutive_timeouts
+    }
+
+    /// Resets the number of consecutive timeouts (e.g., after a notification
+    /// is received in time).
+    pub fn reset_consecutive_timeouts(&mut self) {
+        self.set_num_consecutive_timeouts(0);
+    }
+
+    fn set_num_consecutive_timeouts(&mut self, num_consecutive_timeouts: u64) {
+        if self.num_consecutive_timeouts != num_consecutive_timeouts {
+            self.num_consecutive_timeouts = num_consecutive_timeouts;
+            self.active_streams.update(self.data_stream_id, |summary| {
+                summary.consecutive_timeouts = num_consecutive_timeouts
+            });
         }
     }
 }
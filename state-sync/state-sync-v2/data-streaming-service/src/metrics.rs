@@ -2,7 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_metrics::{
-    register_histogram_vec, register_int_counter_vec, HistogramTimer, HistogramVec, IntCounterVec,
+    register_histogram_vec, register_int_counter_vec, register_int_gauge_vec, HistogramTimer,
+    HistogramVec, IntCounterVec, IntGaugeVec,
 };
 use once_cell::sync::Lazy;
 
@@ -86,6 +87,46 @@ pub static DATA_REQUEST_PROCESSING_LATENCY: Lazy<HistogramVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Gauge for the range of data requested by each active data stream
+pub static ACTIVE_STREAM_REQUESTED_RANGE: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "aptos_data_streaming_service_active_stream_requested_range",
+        "Gauges related to the start and end of the data requested by active streams",
+        &["stream_id", "stream_type", "bound"]
+    )
+    .unwrap()
+});
+
+/// Gauge for the number of notifications sent along each active data stream
+pub static ACTIVE_STREAM_NOTIFICATIONS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "aptos_data_streaming_service_active_stream_notifications",
+        "Gauges related to the notifications sent along active streams",
+        &["stream_id", "stream_type"]
+    )
+    .unwrap()
+});
+
+/// Gauge for the number of bytes received by each active data stream
+pub static ACTIVE_STREAM_BYTES_RECEIVED: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "aptos_data_streaming_service_active_stream_bytes_received",
+        "Gauges related to the bytes of the data responses received by active streams",
+        &["stream_id", "stream_type"]
+    )
+    .unwrap()
+});
+
+/// Gauge for the number of consecutive timeouts of each active data stream
+pub static ACTIVE_STREAM_CONSECUTIVE_TIMEOUTS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "aptos_data_streaming_service_active_stream_consecutive_timeouts",
+        "Gauges related to the consecutive timeouts of the listeners of active streams",
+        &["stream_id", "stream_type"]
+    )
+    .unwrap()
+});
+
 /// Increments the given counter with the provided label values.
 pub fn increment_counter(counter: &Lazy<IntCounterVec>, label: String) {
     counter.with_label_values(&[&label]).inc();
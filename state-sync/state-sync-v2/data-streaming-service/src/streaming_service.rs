@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    data_stream::{DataStream, DataStreamId, DataStreamListener},
+    data_stream::{ActiveDataStreams, DataStream, DataStreamId, DataStreamListener},
     error::Error,
     logging::{LogEntry, LogEvent, LogSchema},
     metrics,
@@ -43,6 +43,9 @@ pub struct DataStreamingService<T> {
     // Unique ID generators to maintain unique IDs across streams
     stream_id_generator: U64IdGenerator,
     notification_id_generator: Arc<U64IdGenerator>,
+
+    // The summaries of the active data streams (e.g., for the debug interface)
+    active_streams: ActiveDataStreams,
 }
 
 impl<T: AptosDataClient + Send + Clone + 'static> DataStreamingService<T> {
@@ -50,6 +53,7 @@ impl<T: AptosDataClient + Send + Clone + 'static> DataStreamingService<T> {
         config: DataStreamingServiceConfig,
         aptos_data_client: T,
         stream_requests: StreamingServiceListener,
+        active_streams: ActiveDataStreams,
     ) -> Self {
         Self {
             config,
@@ -59,6 +63,7 @@ impl<T: AptosDataClient + Send + Clone + 'static> DataStreamingService<T> {
             stream_requests,
             stream_id_generator: U64IdGenerator::new(),
             notification_id_generator: Arc::new(U64IdGenerator::new()),
+            active_streams,
         }
     }
 
@@ -146,7 +151,7 @@ impl<T: AptosDataClient + Send + Clone + 'static> DataStreamingService<T> {
                         notification_id, notification_feedback,
                     )));
                 data_stream.handle_notification_feedback(notification_id, notification_feedback)?;
-                self.data_streams.remove(notification_id);
+                self.data_streams.remove(data_stream_id);
                 return Ok(());
             }
         }
@@ -180,6 +185,7 @@ impl<T: AptosDataClient + Send + Clone + 'static> DataStreamingService<T> {
             self.aptos_data_client.clone(),
             self.notification_id_generator.clone(),
             &self.global_data_summary.advertised_data,
+            self.active_streams.clone(),
         )?;
 
         // Verify the data stream can be fulfilled using the currently advertised data
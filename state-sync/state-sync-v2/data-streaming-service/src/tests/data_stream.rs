@@ -5,7 +5,7 @@ use crate::{
     data_notification::{
         DataClientRequest, DataPayload, EpochEndingLedgerInfosRequest, PendingClientResponse,
     },
-    data_stream::{DataStream, DataStreamListener},
+    data_stream::{ActiveDataStreams, DataStream, DataStreamListener},
     streaming_client::{
         GetAllAccountsRequest, GetAllEpochEndingLedgerInfosRequest, GetAllTransactionsRequest,
         NotificationFeedback, StreamRequest,
@@ -56,6 +56,7 @@ async fn test_stream_blocked() {
         let context = ResponseContext {
             id: 0,
             response_callback: Box::new(NoopResponseCallback),
+            response_size: 0,
         };
         let pending_response = PendingClientResponse {
             client_request: client_request.clone(),
@@ -223,6 +224,7 @@ async fn test_stream_invalid_response() {
     let context = ResponseContext {
         id: 0,
         response_callback: Box::new(NoopResponseCallback),
+        response_size: 0,
     };
     let client_response = Response::new(context, ResponsePayload::NumberOfAccountStates(10));
     let pending_response = PendingClientResponse {
@@ -455,6 +457,7 @@ fn create_data_stream(
         aptos_data_client,
         notification_generator,
         &advertised_data,
+        ActiveDataStreams::new(),
     )
     .unwrap()
 }
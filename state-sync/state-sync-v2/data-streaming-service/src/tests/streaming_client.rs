@@ -3,7 +3,7 @@
 
 use crate::{
     data_notification::DataNotification,
-    data_stream::DataStreamListener,
+    data_stream::{ActiveDataStreams, DataStreamListener},
     error::Error,
     streaming_client::{
         new_streaming_service_client_listener_pair, ContinuouslyStreamTransactionOutputsRequest,
@@ -286,7 +286,8 @@ fn new_data_stream_sender_listener() -> (
 ) {
     let (notification_sender, notification_receiver) =
         aptos_channel::new(QueueStyle::KLAST, 1, None);
-    let data_stream_listener = DataStreamListener::new(notification_receiver);
+    let data_stream_listener =
+        DataStreamListener::new(0, notification_receiver, ActiveDataStreams::new());
 
     (notification_sender, data_stream_listener)
 }
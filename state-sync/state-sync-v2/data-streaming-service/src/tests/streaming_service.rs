@@ -3,6 +3,7 @@
 
 use crate::{
     data_notification::DataPayload,
+    data_stream::ActiveDataStreams,
     error::Error,
     streaming_client::{
         new_streaming_service_client_listener_pair, DataStreamingClient, NotificationFeedback,
@@ -13,11 +14,13 @@ use crate::{
         create_ledger_info, get_data_notification, initialize_logger, MockAptosDataClient,
         MAX_ADVERTISED_ACCOUNTS, MAX_ADVERTISED_EPOCH_END, MAX_ADVERTISED_TRANSACTION,
         MAX_ADVERTISED_TRANSACTION_OUTPUT, MIN_ADVERTISED_ACCOUNTS, MIN_ADVERTISED_EPOCH_END,
-        MIN_ADVERTISED_TRANSACTION, MIN_ADVERTISED_TRANSACTION_OUTPUT, TOTAL_NUM_ACCOUNTS,
+        MIN_ADVERTISED_TRANSACTION, MIN_ADVERTISED_TRANSACTION_OUTPUT, RESPONSE_SIZE,
+        TOTAL_NUM_ACCOUNTS,
     },
 };
 use aptos_config::config::DataStreamingServiceConfig;
-use claim::{assert_le, assert_matches, assert_ok, assert_some};
+use claim::{assert_ge, assert_gt, assert_le, assert_matches, assert_ok, assert_some};
+use std::time::Duration;
 
 macro_rules! unexpected_payload_type {
     ($received:expr) => {
@@ -25,6 +28,53 @@ macro_rules! unexpected_payload_type {
     };
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_active_stream_summaries() {
+    // Create a new streaming client and service
+    let active_streams = ActiveDataStreams::new();
+    let streaming_client = create_streaming_client_and_service(active_streams.clone());
+
+    // Request a transaction stream and verify the stream is reported as active
+    let mut stream_listener = streaming_client
+        .get_all_transactions(
+            MIN_ADVERTISED_TRANSACTION,
+            MAX_ADVERTISED_TRANSACTION,
+            MAX_ADVERTISED_TRANSACTION,
+            true,
+        )
+        .await
+        .unwrap();
+    let summaries = active_streams.summaries();
+    assert_eq!(summaries.len(), 1);
+    assert_eq!(summaries[0].stream_type, "get_all_transactions");
+    assert_eq!(summaries[0].requested_start, MIN_ADVERTISED_TRANSACTION);
+    assert_eq!(summaries[0].requested_end, Some(MAX_ADVERTISED_TRANSACTION));
+
+    // Read a notification and verify the progress of the stream is reported
+    let data_notification = get_data_notification(&mut stream_listener).await.unwrap();
+    let summaries = active_streams.summaries();
+    assert_ge!(summaries[0].notifications, 1);
+    // The received bytes are those of the data client responses, as received over the network
+    assert_gt!(summaries[0].bytes_received, 0);
+    assert_eq!(summaries[0].bytes_received % RESPONSE_SIZE, 0);
+
+    // Terminate the stream and verify it's no longer reported as active
+    streaming_client
+        .terminate_stream_with_feedback(
+            data_notification.notification_id,
+            NotificationFeedback::InvalidPayloadData,
+        )
+        .await
+        .unwrap();
+    for _ in 0..100 {
+        if active_streams.summaries().is_empty() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("The terminated stream is still reported as active!");
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_notifications_accounts() {
     // Create a new streaming client and service
@@ -942,6 +992,12 @@ async fn test_terminate_stream() {
 }
 
 fn create_new_streaming_client_and_service() -> StreamingServiceClient {
+    create_streaming_client_and_service(ActiveDataStreams::new())
+}
+
+fn create_streaming_client_and_service(
+    active_streams: ActiveDataStreams,
+) -> StreamingServiceClient {
     initialize_logger();
 
     // Create a new streaming client and listener
@@ -954,6 +1010,7 @@ fn create_new_streaming_client_and_service() -> StreamingServiceClient {
         DataStreamingServiceConfig::default(),
         aptos_data_client,
         streaming_service_listener,
+        active_streams,
     );
     tokio::spawn(streaming_service.start_service());
 
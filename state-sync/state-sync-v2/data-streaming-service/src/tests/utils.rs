@@ -42,6 +42,7 @@ pub const TOTAL_NUM_ACCOUNTS: u64 = 2000;
 
 /// Test constants for advertised data
 pub const MAX_RESPONSE_ID: u64 = 100000;
+pub const RESPONSE_SIZE: u64 = 1000;
 pub const MIN_ADVERTISED_ACCOUNTS: u64 = 9500;
 pub const MAX_ADVERTISED_ACCOUNTS: u64 = 10000;
 pub const MIN_ADVERTISED_EPOCH_END: u64 = 100;
@@ -226,6 +227,7 @@ pub fn create_data_client_response<T>(payload: T) -> Response<T> {
     let context = ResponseContext {
         id,
         response_callback,
+        response_size: RESPONSE_SIZE,
     };
     Response::new(context, payload)
 }
@@ -69,12 +69,13 @@ impl CommitNotification {
     }
 
     /// Handles the commit notification by notifying mempool and the event
-    /// subscription service.
+    /// subscription service (including the latest state checkpoint, if any).
     pub async fn handle_transaction_notification<M: MempoolNotificationSender>(
         events: Vec<ContractEvent>,
         transactions: Vec<Transaction>,
         latest_synced_version: Version,
         latest_synced_ledger_info: LedgerInfoWithSignatures,
+        latest_state_checkpoint_version: Option<Version>,
         mut mempool_notification_handler: MempoolNotificationHandler<M>,
         event_subscription_service: Arc<Mutex<EventSubscriptionService>>,
     ) -> Result<(), Error> {
@@ -100,10 +101,14 @@ impl CommitNotification {
                 latest_synced_version
             ))
         );
-        event_subscription_service
-            .lock()
-            .notify_events(latest_synced_version, events.clone())
-            .map_err(|error| error.into())
+        let mut event_subscription_service = event_subscription_service.lock();
+        event_subscription_service.notify_events(latest_synced_version, events.clone())?;
+
+        // Notify the event subscription service of the latest state checkpoint
+        if let Some(latest_state_checkpoint_version) = latest_state_checkpoint_version {
+            event_subscription_service.notify_state_checkpoint(latest_state_checkpoint_version)?;
+        }
+        Ok(())
     }
 }
 
@@ -69,6 +69,9 @@ pub fn create_mock_reader_writer(
     reader
         .expect_get_latest_transaction_info_option()
         .returning(|| Ok(Some((0, create_transaction_info()))));
+    reader
+        .expect_get_latest_state_checkpoint_version()
+        .returning(|| Ok(Some(0)));
 
     let writer = writer.unwrap_or_else(create_mock_db_writer);
     DbReaderWriter {
@@ -270,6 +273,8 @@ mock! {
 
         fn get_latest_transaction_info_option(&self) -> Result<Option<(Version, TransactionInfo)>>;
 
+        fn get_latest_state_checkpoint_version(&self) -> Result<Option<Version>>;
+
         fn get_accumulator_root_hash(&self, _version: Version) -> Result<HashValue>;
 
         fn get_accumulator_consistency_proof(
@@ -30,7 +30,9 @@ use aptos_types::{
 };
 use channel::{aptos_channel, aptos_channel::Sender, message_queues::QueueStyle};
 use data_streaming_service::{
-    data_notification::DataNotification, data_stream::DataStreamListener, streaming_client::Epoch,
+    data_notification::DataNotification,
+    data_stream::{ActiveDataStreams, DataStreamListener},
+    streaming_client::Epoch,
 };
 use move_deps::move_core_types::language_storage::TypeTag;
 use std::collections::BTreeMap;
@@ -41,7 +43,8 @@ use storage_service_types::CompleteDataRange;
 pub fn create_data_stream_listener() -> (Sender<(), DataNotification>, DataStreamListener) {
     let (notification_sender, notification_receiver) =
         aptos_channel::new(QueueStyle::KLAST, 100, None);
-    let data_stream_listener = DataStreamListener::new(notification_receiver);
+    let data_stream_listener =
+        DataStreamListener::new(0, notification_receiver, ActiveDataStreams::new());
 
     (notification_sender, data_stream_listener)
 }
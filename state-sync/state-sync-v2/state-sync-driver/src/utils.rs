@@ -109,14 +109,14 @@ pub async fn get_data_notification(
     if let Ok(data_notification) = timeout(timeout_ms, active_data_stream.select_next_some()).await
     {
         // Reset the number of consecutive timeouts for the data stream
-        active_data_stream.num_consecutive_timeouts = 0;
+        active_data_stream.reset_consecutive_timeouts();
         Ok(data_notification)
     } else {
         // Increase the number of consecutive timeouts for the data stream
-        active_data_stream.num_consecutive_timeouts += 1;
+        let num_consecutive_timeouts = active_data_stream.increment_consecutive_timeouts();
 
         // Check if we've timed out too many times
-        if active_data_stream.num_consecutive_timeouts >= MAX_NUM_DATA_STREAM_TIMEOUTS {
+        if num_consecutive_timeouts >= MAX_NUM_DATA_STREAM_TIMEOUTS {
             Err(Error::CriticalDataStreamTimeout(format!(
                 "{:?}",
                 MAX_NUM_DATA_STREAM_TIMEOUTS
@@ -264,12 +264,28 @@ pub async fn handle_committed_transactions<M: MempoolNotificationSender>(
             }
         };
 
+    // Fetch the latest state checkpoint that is safe to finalize. This is
+    // best effort: failing to fetch it shouldn't block other notifications.
+    let latest_state_checkpoint_version = match storage.get_latest_state_checkpoint_version() {
+        Ok(latest_state_checkpoint_version) => latest_state_checkpoint_version,
+        Err(error) => {
+            warn!(
+                LogSchema::new(LogEntry::SynchronizerNotification).message(&format!(
+                    "Failed to fetch the latest state checkpoint version! Error: {:?}",
+                    error
+                ))
+            );
+            None
+        }
+    };
+
     // Handle the commit notification
     if let Err(error) = CommitNotification::handle_transaction_notification(
         committed_transactions.events,
         committed_transactions.transactions,
         latest_synced_version,
         latest_synced_ledger_info,
+        latest_state_checkpoint_version,
         mempool_notification_handler,
         event_subscription_service,
     )
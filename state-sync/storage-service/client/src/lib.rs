@@ -54,18 +54,24 @@ impl StorageServiceClient {
         }
     }
 
+    /// Sends the request to the recipient, returning the response along with the number of bytes
+    /// it was received in
     pub async fn send_request(
         &self,
         recipient: PeerNetworkId,
         request: StorageServiceRequest,
         timeout: Duration,
-    ) -> Result<StorageServiceResponse, Error> {
-        let message = self
+    ) -> Result<(StorageServiceResponse, usize), Error> {
+        let (message, response_size) = self
             .network_sender
-            .send_rpc(recipient, StorageServiceMessage::Request(request), timeout)
+            .send_rpc_with_response_size(
+                recipient,
+                StorageServiceMessage::Request(request),
+                timeout,
+            )
             .await?;
         match message {
-            StorageServiceMessage::Response(Ok(response)) => Ok(response),
+            StorageServiceMessage::Response(Ok(response)) => Ok((response, response_size)),
             StorageServiceMessage::Response(Err(err)) => Err(Error::StorageServiceError(err)),
             StorageServiceMessage::Request(_) => Err(Error::RpcError(RpcError::InvalidRpcResponse)),
         }
@@ -144,4 +150,15 @@ impl ApplicationNetworkSender<StorageServiceMessage> for StorageServiceNetworkSe
             .send_rpc(recipient, ProtocolId::StorageServiceRpc, message, timeout)
             .await
     }
+
+    async fn send_rpc_with_response_size(
+        &self,
+        recipient: PeerId,
+        message: StorageServiceMessage,
+        timeout: Duration,
+    ) -> Result<(StorageServiceMessage, usize), RpcError> {
+        self.inner
+            .send_rpc_with_response_size(recipient, ProtocolId::StorageServiceRpc, message, timeout)
+            .await
+    }
 }
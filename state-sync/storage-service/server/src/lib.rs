@@ -5,11 +5,15 @@
 
 use crate::{
     logging::{LogEntry, LogSchema},
-    metrics::{increment_counter, start_timer, LRU_CACHE_HIT, LRU_CACHE_PROBE},
+    metrics::{
+        increment_counter, start_timer, IN_FLIGHT_STORAGE_REQUESTS, LRU_CACHE_HIT, LRU_CACHE_PROBE,
+        PENDING_STORAGE_REQUESTS, STORAGE_REQUEST_QUEUE_LATENCY,
+    },
     network::StorageServiceNetworkEvents,
+    scheduler::RequestScheduler,
 };
 use ::network::ProtocolId;
-use aptos_config::config::StorageServiceConfig;
+use aptos_config::{config::StorageServiceConfig, network_id::NetworkId};
 use aptos_infallible::{Mutex, RwLock};
 use aptos_logger::prelude::*;
 use aptos_time_service::{TimeService, TimeServiceTrait};
@@ -18,11 +22,13 @@ use aptos_types::{
     state_store::state_value::StateValueChunkWithProof,
     transaction::{TransactionListWithProof, TransactionOutputListWithProof, Version},
 };
-use bounded_executor::BoundedExecutor;
 use futures::stream::StreamExt;
 use lru::LruCache;
 use serde::{Deserialize, Serialize};
-use std::{sync::Arc, time::Duration};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use storage_interface::DbReader;
 use storage_service_types::{
     AccountStatesChunkWithProofRequest, CompleteDataRange, DataSummary,
@@ -36,6 +42,7 @@ use tokio::runtime::Handle;
 mod logging;
 mod metrics;
 pub mod network;
+pub mod scheduler;
 
 #[cfg(test)]
 mod tests;
@@ -65,12 +72,41 @@ impl Error {
     }
 }
 
+/// The class of the peers served by a storage server. Requests from validators
+/// and VFNs are prioritized over requests from public fullnodes, see
+/// [`RequestScheduler`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PeerClass {
+    Priority, // Validators and VFNs
+    Public,   // Public fullnodes
+}
+
+impl PeerClass {
+    /// Returns the class of the peers connected over the given network
+    pub fn from_network_id(network_id: NetworkId) -> Self {
+        match network_id {
+            NetworkId::Validator | NetworkId::Vfn => PeerClass::Priority,
+            NetworkId::Public => PeerClass::Public,
+        }
+    }
+
+    /// Returns a summary label for the peer class
+    fn get_label(&self) -> &'static str {
+        match self {
+            PeerClass::Priority => "priority",
+            PeerClass::Public => "public",
+        }
+    }
+}
+
 /// The server-side actor for the storage service. Handles inbound storage
 /// service requests from clients.
 pub struct StorageServiceServer<T> {
-    bounded_executor: BoundedExecutor,
     config: StorageServiceConfig,
+    executor: Handle,
     network_requests: StorageServiceNetworkEvents,
+    peer_class: PeerClass,
+    request_scheduler: RequestScheduler,
     storage: T,
     time_service: TimeService,
 
@@ -90,10 +126,11 @@ impl<T: StorageReaderInterface> StorageServiceServer<T> {
         executor: Handle,
         storage: T,
         time_service: TimeService,
+        network_id: NetworkId,
+        request_scheduler: RequestScheduler,
         network_requests: StorageServiceNetworkEvents,
     ) -> Self {
-        let bounded_executor =
-            BoundedExecutor::new(config.max_concurrent_requests as usize, executor);
+        let peer_class = PeerClass::from_network_id(network_id);
         let cached_storage_server_summary = Arc::new(RwLock::new(StorageServerSummary::default()));
         let lru_storage_cache = Arc::new(Mutex::new(LruCache::new(
             config.max_lru_cache_size as usize,
@@ -101,9 +138,11 @@ impl<T: StorageReaderInterface> StorageServiceServer<T> {
 
         Self {
             config,
-            bounded_executor,
+            executor,
             storage,
             network_requests,
+            peer_class,
+            request_scheduler,
             time_service,
             cached_storage_server_summary,
             lru_storage_cache,
@@ -111,44 +150,42 @@ impl<T: StorageReaderInterface> StorageServiceServer<T> {
     }
 
     /// Spawns a non-terminating task that refreshes the cached storage server summary
-    async fn spawn_storage_summary_refresher(&mut self) {
+    fn spawn_storage_summary_refresher(&mut self) {
         let config = self.config;
         let storage = self.storage.clone();
         let time_service = self.time_service.clone();
         let cached_storage_server_summary = self.cached_storage_server_summary.clone();
 
         // Spawn the task
-        self.bounded_executor
-            .spawn(async move {
-                // Create a ticker for the refresh interval
-                let duration = Duration::from_millis(config.storage_summary_refresh_interval_ms);
-                let ticker = time_service.interval(duration);
-                futures::pin_mut!(ticker);
-
-                // Periodically refresh the cache
-                loop {
-                    ticker.next().await;
-
-                    if let Err(error) = refresh_cached_storage_summary(
-                        config,
-                        storage.clone(),
-                        cached_storage_server_summary.clone(),
-                    ) {
-                        let error = format!(
-                            "Failed to refresh the cached storage summary! Error: {:?}",
-                            error
-                        );
-                        error!(LogSchema::new(LogEntry::StorageServiceError).message(&error));
-                    }
+        self.executor.spawn(async move {
+            // Create a ticker for the refresh interval
+            let duration = Duration::from_millis(config.storage_summary_refresh_interval_ms);
+            let ticker = time_service.interval(duration);
+            futures::pin_mut!(ticker);
+
+            // Periodically refresh the cache
+            loop {
+                ticker.next().await;
+
+                if let Err(error) = refresh_cached_storage_summary(
+                    config,
+                    storage.clone(),
+                    cached_storage_server_summary.clone(),
+                ) {
+                    let error = format!(
+                        "Failed to refresh the cached storage summary! Error: {:?}",
+                        error
+                    );
+                    error!(LogSchema::new(LogEntry::StorageServiceError).message(&error));
                 }
-            })
-            .await;
+            }
+        });
     }
 
     /// Starts the storage service server thread
     pub async fn start(mut self) {
         // Spawn the refresher for the cache
-        self.spawn_storage_summary_refresher().await;
+        self.spawn_storage_summary_refresher();
 
         // Handle the storage requests
         while let Some(request) = self.network_requests.next().await {
@@ -167,15 +204,40 @@ impl<T: StorageReaderInterface> StorageServiceServer<T> {
             let storage = self.storage.clone();
             let cached_storage_server_summary = self.cached_storage_server_summary.clone();
             let lru_storage_cache = self.lru_storage_cache.clone();
-            self.bounded_executor
-                .spawn_blocking(move || {
-                    let response =
-                        Handler::new(storage, cached_storage_server_summary, lru_storage_cache)
-                            .call(protocol, request);
-                    log_storage_response(&response);
-                    response_sender.send(response);
-                })
-                .await;
+
+            // Wait for a free task. The tasks are shared by the servers of
+            // all networks, and handed out to validators and VFNs first, so a
+            // flood of public requests can't starve them.
+            let peer_class_label = self.peer_class.get_label();
+            let queued_at = Instant::now();
+            PENDING_STORAGE_REQUESTS
+                .with_label_values(&[peer_class_label])
+                .inc();
+            let permit = self.request_scheduler.acquire(self.peer_class).await;
+            PENDING_STORAGE_REQUESTS
+                .with_label_values(&[peer_class_label])
+                .dec();
+            STORAGE_REQUEST_QUEUE_LATENCY
+                .with_label_values(&[peer_class_label])
+                .observe(queued_at.elapsed().as_secs_f64());
+
+            self.executor.spawn_blocking(move || {
+                // Hold the task until the response is sent
+                let _permit = permit;
+                IN_FLIGHT_STORAGE_REQUESTS
+                    .with_label_values(&[peer_class_label])
+                    .inc();
+
+                let response =
+                    Handler::new(storage, cached_storage_server_summary, lru_storage_cache)
+                        .call(protocol, request);
+                log_storage_response(&response);
+                response_sender.send(response);
+
+                IN_FLIGHT_STORAGE_REQUESTS
+                    .with_label_values(&[peer_class_label])
+                    .dec();
+            });
         }
     }
 }
@@ -2,7 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_metrics::{
-    register_histogram_vec, register_int_counter_vec, HistogramTimer, HistogramVec, IntCounterVec,
+    register_histogram_vec, register_int_counter_vec, register_int_gauge_vec, HistogramTimer,
+    HistogramVec, IntCounterVec, IntGaugeVec,
 };
 use network::ProtocolId;
 use once_cell::sync::Lazy;
@@ -71,6 +72,36 @@ pub static STORAGE_REQUEST_PROCESSING_LATENCY: Lazy<HistogramVec> = Lazy::new(||
     .unwrap()
 });
 
+/// Gauge for the storage requests waiting for a free task, by class of the requesting peer
+pub static PENDING_STORAGE_REQUESTS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "aptos_storage_service_server_pending_requests",
+        "Gauge for the storage requests waiting to be processed",
+        &["peer_class"]
+    )
+    .unwrap()
+});
+
+/// Gauge for the storage requests being processed, by class of the requesting peer
+pub static IN_FLIGHT_STORAGE_REQUESTS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "aptos_storage_service_server_in_flight_requests",
+        "Gauge for the storage requests being processed",
+        &["peer_class"]
+    )
+    .unwrap()
+});
+
+/// Time a storage request waits for a free task before being processed
+pub static STORAGE_REQUEST_QUEUE_LATENCY: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "aptos_storage_service_server_request_queue_latency",
+        "Time a storage service request waits before being processed",
+        &["peer_class"]
+    )
+    .unwrap()
+});
+
 /// Increments the given counter with the provided label values.
 pub fn increment_counter(counter: &Lazy<IntCounterVec>, protocol: ProtocolId, label: String) {
     counter
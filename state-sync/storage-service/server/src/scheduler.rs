@@ -0,0 +1,157 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::PeerClass;
+use aptos_config::config::StorageServiceConfig;
+use aptos_infallible::Mutex;
+use futures::channel::oneshot;
+use std::{collections::VecDeque, sync::Arc};
+
+/// Hands out the tasks for serving storage requests, shared by the storage
+/// servers of all networks. Requests from priority peers (validators and VFNs)
+/// take free tasks ahead of waiting public requests, and public requests can
+/// only hold up to `max_concurrent_public_requests` tasks at once, so the rest
+/// stays free for priority peers even under a flood of public requests.
+#[derive(Clone)]
+pub struct RequestScheduler {
+    state: Arc<Mutex<SchedulerState>>,
+}
+
+impl RequestScheduler {
+    pub fn new(config: &StorageServiceConfig) -> Self {
+        let state = SchedulerState {
+            free_tasks: config.max_concurrent_requests,
+            max_public_tasks: config.max_concurrent_public_requests,
+            public_tasks: 0,
+            priority_waiters: VecDeque::new(),
+            public_waiters: VecDeque::new(),
+        };
+        Self {
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    /// Waits for a task to serve a request from the given peer class. The task
+    /// is held until the returned permit is dropped.
+    pub async fn acquire(&self, peer_class: PeerClass) -> RequestPermit {
+        let permit_receiver = {
+            let mut state = self.state.lock();
+            if state.can_start(peer_class) {
+                state.start(peer_class);
+                return RequestPermit::new(self.clone(), peer_class);
+            }
+
+            // Wait for a finishing request to hand over its task
+            let (permit_sender, permit_receiver) = oneshot::channel();
+            state.waiters(peer_class).push_back(permit_sender);
+            permit_receiver
+        };
+        permit_receiver
+            .await
+            .expect("Waiting requests should always be handed a task!")
+    }
+
+    /// Frees the task of a finished request, and hands it over to the next
+    /// waiting request (if any)
+    fn release(&self, peer_class: PeerClass) {
+        let next_request = {
+            let mut state = self.state.lock();
+            state.finish(peer_class);
+            state
+                .start_next_waiter()
+                .map(|(peer_class, permit_sender)| {
+                    (permit_sender, RequestPermit::new(self.clone(), peer_class))
+                })
+        };
+
+        // If the request stopped waiting, the permit is dropped here (outside
+        // of the lock), which hands the task over to the next request in turn.
+        if let Some((permit_sender, permit)) = next_request {
+            let _ = permit_sender.send(permit);
+        }
+    }
+}
+
+/// The task held by a storage request, freed when the permit is dropped
+pub struct RequestPermit {
+    peer_class: PeerClass,
+    scheduler: RequestScheduler,
+}
+
+impl RequestPermit {
+    fn new(scheduler: RequestScheduler, peer_class: PeerClass) -> Self {
+        Self {
+            peer_class,
+            scheduler,
+        }
+    }
+}
+
+impl Drop for RequestPermit {
+    fn drop(&mut self) {
+        self.scheduler.release(self.peer_class);
+    }
+}
+
+struct SchedulerState {
+    free_tasks: u64,
+    max_public_tasks: u64,
+    public_tasks: u64,
+    priority_waiters: VecDeque<oneshot::Sender<RequestPermit>>,
+    public_waiters: VecDeque<oneshot::Sender<RequestPermit>>,
+}
+
+impl SchedulerState {
+    /// Returns true iff a request from the peer class can take a task now,
+    /// without overtaking the requests already waiting for one
+    fn can_start(&self, peer_class: PeerClass) -> bool {
+        if self.free_tasks == 0 || !self.priority_waiters.is_empty() {
+            return false;
+        }
+        match peer_class {
+            PeerClass::Priority => true,
+            PeerClass::Public => {
+                self.public_waiters.is_empty() && self.public_tasks < self.max_public_tasks
+            }
+        }
+    }
+
+    fn start(&mut self, peer_class: PeerClass) {
+        self.free_tasks -= 1;
+        if peer_class == PeerClass::Public {
+            self.public_tasks += 1;
+        }
+    }
+
+    fn finish(&mut self, peer_class: PeerClass) {
+        self.free_tasks += 1;
+        if peer_class == PeerClass::Public {
+            self.public_tasks -= 1;
+        }
+    }
+
+    /// Gives a free task to the next waiting request, priority requests first,
+    /// and returns the class and the sender of the request
+    fn start_next_waiter(&mut self) -> Option<(PeerClass, oneshot::Sender<RequestPermit>)> {
+        if self.free_tasks == 0 {
+            return None;
+        }
+        let peer_class = if !self.priority_waiters.is_empty() {
+            PeerClass::Priority
+        } else if !self.public_waiters.is_empty() && self.public_tasks < self.max_public_tasks {
+            PeerClass::Public
+        } else {
+            return None;
+        };
+        let permit_sender = self.waiters(peer_class).pop_front()?;
+        self.start(peer_class);
+        Some((peer_class, permit_sender))
+    }
+
+    fn waiters(&mut self, peer_class: PeerClass) -> &mut VecDeque<oneshot::Sender<RequestPermit>> {
+        match peer_class {
+            PeerClass::Priority => &mut self.priority_waiters,
+            PeerClass::Public => &mut self.public_waiters,
+        }
+    }
+}
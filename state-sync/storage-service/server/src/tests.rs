@@ -3,9 +3,12 @@
 
 #![forbid(unsafe_code)]
 
-use crate::{network::StorageServiceNetworkEvents, StorageReader, StorageServiceServer};
+use crate::{
+    network::StorageServiceNetworkEvents, scheduler::RequestScheduler, PeerClass, StorageReader,
+    StorageServiceServer,
+};
 use anyhow::{format_err, Result};
-use aptos_config::config::StorageServiceConfig;
+use aptos_config::{config::StorageServiceConfig, network_id::NetworkId};
 use aptos_crypto::{ed25519::Ed25519PrivateKey, HashValue, PrivateKey, SigningKey, Uniform};
 use aptos_logger::Level;
 use aptos_time_service::{MockTimeService, TimeService};
@@ -35,7 +38,12 @@ use aptos_types::{
 };
 use channel::aptos_channel;
 use claim::assert_matches;
-use futures::channel::oneshot;
+use futures::{
+    channel::{mpsc, oneshot},
+    future::join_all,
+    poll,
+    stream::StreamExt,
+};
 use mockall::{
     mock,
     predicate::{always, eq},
@@ -47,7 +55,11 @@ use network::{
         network::NewNetworkEvents, rpc::InboundRpcRequest, wire::handshake::v1::ProtocolId,
     },
 };
-use std::{collections::BTreeMap, sync::Arc};
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    task::Poll,
+};
 use storage_interface::{DbReader, Order, StartupInfo, TreeState};
 use storage_service_types::{
     AccountStatesChunkWithProofRequest, CompleteDataRange, DataSummary, Epoch,
@@ -664,8 +676,168 @@ async fn test_get_epoch_ending_ledger_infos_invalid() {
     }
 }
 
+#[test]
+fn test_peer_classes() {
+    // Verify validators and VFNs share the priority class
+    for network_id in [NetworkId::Validator, NetworkId::Vfn] {
+        assert_eq!(PeerClass::from_network_id(network_id), PeerClass::Priority);
+    }
+
+    // Verify public fullnodes have their own class
+    assert_eq!(
+        PeerClass::from_network_id(NetworkId::Public),
+        PeerClass::Public
+    );
+}
+
+#[tokio::test]
+async fn test_request_scheduler_priority() {
+    // Create a scheduler with a single task
+    let request_scheduler = RequestScheduler::new(&create_storage_config(1, 1));
+    let permit = request_scheduler.acquire(PeerClass::Public).await;
+
+    // Queue a public request before a priority request
+    let mut public_request = Box::pin(request_scheduler.acquire(PeerClass::Public));
+    let mut priority_request = Box::pin(request_scheduler.acquire(PeerClass::Priority));
+    assert!(poll!(public_request.as_mut()).is_pending());
+    assert!(poll!(priority_request.as_mut()).is_pending());
+
+    // Verify the priority request is served first once the task is free
+    drop(permit);
+    assert!(poll!(public_request.as_mut()).is_pending());
+    let permit = match poll!(priority_request.as_mut()) {
+        Poll::Ready(permit) => permit,
+        Poll::Pending => panic!("The priority request should have been served!"),
+    };
+
+    // Verify the public request is served next
+    drop(permit);
+    assert!(poll!(public_request.as_mut()).is_ready());
+}
+
+#[tokio::test]
+async fn test_request_scheduler_public_limit() {
+    // Create a scheduler with three tasks, only one for public requests
+    let request_scheduler = RequestScheduler::new(&create_storage_config(3, 1));
+    let public_permit = request_scheduler.acquire(PeerClass::Public).await;
+    let mut public_request = Box::pin(request_scheduler.acquire(PeerClass::Public));
+    assert!(poll!(public_request.as_mut()).is_pending());
+
+    // Verify the remaining tasks are kept for priority requests
+    let priority_permits = vec![
+        request_scheduler.acquire(PeerClass::Priority).await,
+        request_scheduler.acquire(PeerClass::Priority).await,
+    ];
+    let mut priority_request = Box::pin(request_scheduler.acquire(PeerClass::Priority));
+    assert!(poll!(priority_request.as_mut()).is_pending());
+
+    // Verify a priority task doesn't go to a public request over the limit
+    drop(priority_permits);
+    assert!(poll!(priority_request.as_mut()).is_ready());
+    assert!(poll!(public_request.as_mut()).is_pending());
+
+    // Verify the public request is served once the public task is free
+    drop(public_permit);
+    assert!(poll!(public_request.as_mut()).is_ready());
+}
+
+#[tokio::test]
+async fn test_request_scheduler_abandoned_request() {
+    // Create a scheduler with a single task
+    let request_scheduler = RequestScheduler::new(&create_storage_config(1, 1));
+    let permit = request_scheduler.acquire(PeerClass::Priority).await;
+
+    // Queue two requests, and abandon the first one
+    let mut abandoned_request = Box::pin(request_scheduler.acquire(PeerClass::Priority));
+    let mut public_request = Box::pin(request_scheduler.acquire(PeerClass::Public));
+    assert!(poll!(abandoned_request.as_mut()).is_pending());
+    assert!(poll!(public_request.as_mut()).is_pending());
+    drop(abandoned_request);
+
+    // Verify the task is handed over to the next request
+    drop(permit);
+    assert!(poll!(public_request.as_mut()).is_ready());
+}
+
+#[tokio::test]
+async fn test_priority_requests_served_under_public_load() {
+    // Create a scheduler with two tasks, only one for public requests
+    let storage_config = create_storage_config(2, 1);
+    let request_scheduler = RequestScheduler::new(&storage_config);
+
+    // Create a public server where requests block in storage until released
+    let (started_sender, mut started_receiver) = mpsc::unbounded();
+    let (release_sender, release_receiver) = std::sync::mpsc::channel::<()>();
+    let release_receiver = Mutex::new(release_receiver);
+    let mut db_reader = create_mock_db_reader();
+    db_reader
+        .expect_get_state_leaf_count()
+        .times(2)
+        .returning(move |_| {
+            started_sender.unbounded_send(()).unwrap();
+            release_receiver.lock().unwrap().recv().unwrap();
+            Ok(10)
+        });
+    let (public_client, public_service, _) = MockClient::new_with_network(
+        Some(db_reader),
+        storage_config,
+        NetworkId::Public,
+        request_scheduler.clone(),
+    );
+    tokio::spawn(public_service.start());
+
+    // Create a validator server sharing the tasks
+    let mut db_reader = create_mock_db_reader();
+    db_reader
+        .expect_get_state_leaf_count()
+        .times(1)
+        .returning(|_| Ok(20));
+    let (mut validator_client, validator_service, _) = MockClient::new_with_network(
+        Some(db_reader),
+        storage_config,
+        NetworkId::Validator,
+        request_scheduler,
+    );
+    tokio::spawn(validator_service.start());
+
+    // Flood the public server, until a public request holds the public task
+    let public_responses: Vec<_> = (0..2)
+        .map(|version| {
+            let mut public_client = public_client.clone();
+            tokio::spawn(async move {
+                public_client
+                    .send_request(StorageServiceRequest::GetNumberOfAccountsAtVersion(version))
+                    .await
+            })
+        })
+        .collect();
+    started_receiver.next().await.unwrap();
+
+    // Verify the validator request is served ahead of the waiting public request
+    let response = validator_client
+        .send_request(StorageServiceRequest::GetNumberOfAccountsAtVersion(0))
+        .await
+        .unwrap();
+    assert_eq!(
+        response,
+        StorageServiceResponse::NumberOfAccountsAtVersion(20)
+    );
+    assert!(started_receiver.try_next().is_err());
+
+    // Verify the public requests are served once released
+    release_sender.send(()).unwrap();
+    release_sender.send(()).unwrap();
+    for public_response in join_all(public_responses).await {
+        assert_eq!(
+            public_response.unwrap().unwrap(),
+            StorageServiceResponse::NumberOfAccountsAtVersion(10)
+        );
+    }
+}
+
 /// A wrapper around the inbound network interface/channel for easily sending
 /// mock client requests to a [`StorageServiceServer`].
+#[derive(Clone)]
 struct MockClient {
     peer_mgr_notifs_tx: aptos_channel::Sender<(PeerId, ProtocolId), PeerManagerNotification>,
 }
@@ -674,8 +846,22 @@ impl MockClient {
     fn new(
         db_reader: Option<MockDatabaseReader>,
     ) -> (Self, StorageServiceServer<StorageReader>, MockTimeService) {
-        initialize_logger();
         let storage_config = StorageServiceConfig::default();
+        Self::new_with_network(
+            db_reader,
+            storage_config,
+            NetworkId::Validator,
+            RequestScheduler::new(&storage_config),
+        )
+    }
+
+    fn new_with_network(
+        db_reader: Option<MockDatabaseReader>,
+        storage_config: StorageServiceConfig,
+        network_id: NetworkId,
+        request_scheduler: RequestScheduler,
+    ) -> (Self, StorageServiceServer<StorageReader>, MockTimeService) {
+        initialize_logger();
         let storage = StorageReader::new(
             storage_config,
             Arc::new(db_reader.unwrap_or_else(create_mock_db_reader)),
@@ -692,10 +878,12 @@ impl MockClient {
         let executor = tokio::runtime::Handle::current();
         let mock_time_service = TimeService::mock();
         let storage_server = StorageServiceServer::new(
-            StorageServiceConfig::default(),
+            storage_config,
             executor,
             storage,
             mock_time_service.clone(),
+            network_id,
+            request_scheduler,
             network_requests,
         );
 
@@ -738,6 +926,18 @@ impl MockClient {
     }
 }
 
+/// Creates a storage service config with the given task limits
+fn create_storage_config(
+    max_concurrent_requests: u64,
+    max_concurrent_public_requests: u64,
+) -> StorageServiceConfig {
+    StorageServiceConfig {
+        max_concurrent_requests,
+        max_concurrent_public_requests,
+        ..Default::default()
+    }
+}
+
 /// Creates a test epoch change proof
 fn create_epoch_ending_ledger_infos(
     start_epoch: Epoch,
@@ -0,0 +1,174 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides `Archiver` which manages a thread moving ancient transactions and write sets
+//! to the cold DB in the background, and is meant to be triggered by other threads as they commit
+//! new data to the DB.
+
+use crate::{metrics::COLD_STORAGE_ARCHIVED_VERSION, TransactionStore};
+use anyhow::Result;
+use aptos_config::config::ColdStorageConfig;
+use aptos_infallible::Mutex;
+use aptos_logger::prelude::*;
+use aptos_types::transaction::Version;
+use std::{
+    cmp::{max, min},
+    sync::{
+        mpsc::{channel, Receiver, Sender, TryRecvError},
+        Arc,
+    },
+    thread::JoinHandle,
+};
+
+/// The `Archiver` is meant to be part of a `AptosDB` instance with cold storage enabled.
+///
+/// It creates a worker thread on construction and joins it on destruction. When destructed, it
+/// quits the worker thread eagerly without waiting for all pending work to be done.
+#[derive(Debug)]
+pub(crate) struct Archiver {
+    /// Number of latest versions kept in the main DB
+    hot_window: Version,
+    /// Number of versions moved to the cold DB at once
+    archiving_batch_size: usize,
+    /// The last target version sent to the worker thread
+    last_target_version_sent: Mutex<Version>,
+    /// The worker thread handle, created upon Archiver instance construction and joined upon its
+    /// destruction. It only becomes `None` after joined in `drop()`.
+    worker_thread: Option<JoinHandle<()>>,
+    /// The sender side of the channel talking to the worker thread.
+    command_sender: Mutex<Sender<Command>>,
+}
+
+impl Archiver {
+    /// Creates a worker thread that waits on a channel for archiving commands.
+    pub fn new(transaction_store: Arc<TransactionStore>, config: &ColdStorageConfig) -> Self {
+        let (command_sender, command_receiver) = channel();
+        let worker = Worker {
+            transaction_store,
+            command_receiver,
+            archiving_batch_size: config.archiving_batch_size as Version,
+            next_version: None,
+        };
+        let worker_thread = std::thread::Builder::new()
+            .name("aptosdb_archiver".into())
+            .spawn(move || worker.work())
+            .expect("Creating archiver thread should succeed.");
+
+        Self {
+            hot_window: config.hot_window,
+            archiving_batch_size: config.archiving_batch_size,
+            last_target_version_sent: Mutex::new(0),
+            worker_thread: Some(worker_thread),
+            command_sender: Mutex::new(command_sender),
+        }
+    }
+
+    /// Sends an archiving command to the worker thread once a batch worth of versions fell out of
+    /// the hot window.
+    pub fn maybe_wake_archiver(&self, latest_version: Version) {
+        let target_version = latest_version.saturating_sub(self.hot_window);
+        let mut last_target_version_sent = self.last_target_version_sent.lock();
+        if target_version >= *last_target_version_sent + self.archiving_batch_size as Version {
+            self.command_sender
+                .lock()
+                .send(Command::Archive { target_version })
+                .expect("Receiver should not destruct prematurely.");
+            *last_target_version_sent = target_version;
+        }
+    }
+}
+
+impl Drop for Archiver {
+    fn drop(&mut self) {
+        self.command_sender
+            .lock()
+            .send(Command::Quit)
+            .expect("Receiver should not destruct.");
+        self.worker_thread
+            .take()
+            .expect("Worker thread must exist.")
+            .join()
+            .expect("Worker thread should join peacefully.");
+    }
+}
+
+enum Command {
+    Quit,
+    /// Move everything below `target_version` to the cold DB
+    Archive {
+        target_version: Version,
+    },
+}
+
+struct Worker {
+    transaction_store: Arc<TransactionStore>,
+    command_receiver: Receiver<Command>,
+    archiving_batch_size: Version,
+    /// First version still in the main DB, looked up on the first batch
+    next_version: Option<Version>,
+}
+
+impl Worker {
+    fn work(mut self) {
+        let mut target_version = 0;
+        let mut blocking_recv = true;
+        loop {
+            // Only block on the channel when there's no pending work, and check for new commands
+            // between batches, so a `Command::Quit` doesn't wait for a long catch up.
+            let command = if blocking_recv {
+                match self.command_receiver.recv() {
+                    Ok(command) => Some(command),
+                    Err(_) => return,
+                }
+            } else {
+                match self.command_receiver.try_recv() {
+                    Ok(command) => Some(command),
+                    Err(TryRecvError::Empty) => None,
+                    Err(TryRecvError::Disconnected) => return,
+                }
+            };
+            match command {
+                Some(Command::Quit) => return,
+                Some(Command::Archive {
+                    target_version: new_target_version,
+                }) => {
+                    target_version = max(target_version, new_target_version);
+                    // Drain the channel before doing any work.
+                    blocking_recv = false;
+                    continue;
+                }
+                None => (),
+            }
+
+            blocking_recv = match self.archive_batch(target_version) {
+                Ok(caught_up) => caught_up,
+                Err(e) => {
+                    error!(error = ?e, "Moving transactions to the cold DB failed.");
+                    true
+                }
+            };
+        }
+    }
+
+    /// Moves the next batch of versions below `target_version` to the cold DB, returning true once
+    /// there's nothing left to move.
+    fn archive_batch(&mut self, target_version: Version) -> Result<bool> {
+        let begin = match self.next_version {
+            Some(version) => version,
+            None => match self.transaction_store.get_first_hot_txn_version()? {
+                Some(version) => version,
+                None => return Ok(true),
+            },
+        };
+        if begin >= target_version {
+            self.next_version = Some(begin);
+            return Ok(true);
+        }
+
+        let end = min(begin + self.archiving_batch_size, target_version);
+        self.transaction_store.archive(begin, end)?;
+        self.next_version = Some(end);
+        COLD_STORAGE_ARCHIVED_VERSION.set(end as i64);
+        Ok(end >= target_version)
+    }
+}
@@ -226,6 +226,25 @@ impl LedgerStore {
         iter.next().transpose()
     }
 
+    /// Gets the latest version no greater than `max_version` whose transaction info carries a
+    /// state checkpoint hash.
+    pub fn get_latest_state_checkpoint_version(
+        &self,
+        max_version: Version,
+    ) -> Result<Option<Version>> {
+        let mut iter = self
+            .db
+            .rev_iter::<TransactionInfoSchema>(ReadOptions::default())?;
+        iter.seek_for_prev(&max_version)?;
+        for res in iter {
+            let (version, txn_info) = res?;
+            if txn_info.state_checkpoint_hash().is_some() {
+                return Ok(Some(version));
+            }
+        }
+        Ok(None)
+    }
+
     /// Get latest transaction info together with its version. Note that during node syncing, this
     /// version can be greater than what's in the latest LedgerInfo.
     pub fn get_latest_transaction_info(&self) -> Result<(Version, TransactionInfo)> {
@@ -100,4 +100,28 @@ proptest! {
             iter.collect::<Result<Vec<_>>>().unwrap()
         );
     }
+
+    #[test]
+    fn test_get_latest_state_checkpoint_version(
+        (infos, max_version) in vec(any::<TransactionInfo>(), 1..100)
+            .prop_flat_map(|infos| {
+                let num_infos = infos.len() as u64;
+                (Just(infos), 0..num_infos)
+            })
+    ) {
+        let tmp_dir = TempPath::new();
+        let db = AptosDB::new_for_test(&tmp_dir);
+        let store = &db.ledger_store;
+        save(store, 0, &infos);
+
+        let expected = infos
+            .iter()
+            .take(max_version as usize + 1)
+            .rposition(|info| info.state_checkpoint_hash().is_some())
+            .map(|idx| idx as Version);
+        prop_assert_eq!(
+            store.get_latest_state_checkpoint_version(max_version).unwrap(),
+            expected
+        );
+    }
 }
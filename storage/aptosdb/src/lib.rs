@@ -18,6 +18,7 @@ pub mod errors;
 pub mod metrics;
 pub mod schema;
 
+mod archiver;
 mod change_set;
 mod event_store;
 mod ledger_counters;
@@ -31,6 +32,7 @@ mod transaction_store;
 mod aptosdb_test;
 
 use crate::{
+    archiver::Archiver,
     backup::{backup_handler::BackupHandler, restore_handler::RestoreHandler, restore_utils},
     change_set::{ChangeSet, SealedChangeSet},
     errors::AptosDbError,
@@ -39,7 +41,9 @@ use crate::{
     ledger_store::LedgerStore,
     metrics::{
         API_LATENCY_SECONDS, COMMITTED_TXNS, LATEST_TXN_VERSION, LEDGER_VERSION, NEXT_BLOCK_EPOCH,
-        OTHER_TIMERS_SECONDS, ROCKSDB_PROPERTIES, STATE_ITEM_COUNT,
+        OTHER_TIMERS_SECONDS, ROCKSDB_PROPERTIES, SAVE_TRANSACTIONS_BATCH_BYTES,
+        SAVE_TRANSACTIONS_BATCH_SIZE, SAVE_TRANSACTIONS_CF_BYTES, SAVE_TRANSACTIONS_SYNC_SECONDS,
+        SAVE_TRANSACTIONS_WRITE_AMPLIFICATION, STATE_ITEM_COUNT,
     },
     pruner::{utils, Pruner},
     schema::*,
@@ -48,7 +52,9 @@ use crate::{
     transaction_store::TransactionStore,
 };
 use anyhow::{ensure, format_err, Result};
-use aptos_config::config::{RocksdbConfig, StoragePrunerConfig, NO_OP_STORAGE_PRUNER_CONFIG};
+use aptos_config::config::{
+    ColdStorageConfig, RocksdbConfig, StoragePrunerConfig, NO_OP_STORAGE_PRUNER_CONFIG,
+};
 use aptos_crypto::hash::{HashValue, SPARSE_MERKLE_PLACEHOLDER_HASH};
 use aptos_infallible::Mutex;
 use aptos_logger::prelude::*;
@@ -80,7 +86,7 @@ use itertools::zip_eq;
 use once_cell::sync::Lazy;
 use schemadb::{ColumnFamilyName, Options, SchemaBatch, DB, DEFAULT_CF_NAME};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     iter::Iterator,
     path::Path,
     sync::{mpsc, Arc},
@@ -219,6 +225,7 @@ pub struct AptosDB {
     event_store: Arc<EventStore>,
     system_store: Arc<SystemStore>,
     pruner: Option<Pruner>,
+    archiver: Option<Archiver>,
     _rocksdb_property_reporter: RocksdbPropertyReporter,
 }
 
@@ -244,9 +251,23 @@ impl AptosDB {
         ]
     }
 
-    fn new_with_db(db: DB, storage_pruner_config: StoragePrunerConfig) -> Self {
+    /// Column families of the cold DB, which holds the transactions and write sets moved out of the
+    /// main DB.
+    fn cold_column_families() -> Vec<ColumnFamilyName> {
+        vec![DEFAULT_CF_NAME, TRANSACTION_CF_NAME, WRITE_SET_CF_NAME]
+    }
+
+    fn new_with_db(
+        db: DB,
+        cold_db: Option<DB>,
+        storage_pruner_config: StoragePrunerConfig,
+        cold_storage_config: Option<&ColdStorageConfig>,
+    ) -> Self {
         let db = Arc::new(db);
-        let transaction_store = Arc::new(TransactionStore::new(Arc::clone(&db)));
+        let transaction_store = Arc::new(TransactionStore::new(
+            Arc::clone(&db),
+            cold_db.map(Arc::new),
+        ));
         let event_store = Arc::new(EventStore::new(Arc::clone(&db)));
         let ledger_store = Arc::new(LedgerStore::new(Arc::clone(&db)));
         let system_store = Arc::new(SystemStore::new(Arc::clone(&db)));
@@ -263,11 +284,12 @@ impl AptosDB {
                 _ => Some(Pruner::new(
                     Arc::clone(&db),
                     storage_pruner_config,
-                    transaction_store,
+                    Arc::clone(&transaction_store),
                     ledger_store,
                     event_store,
                 )),
             },
+            archiver: cold_storage_config.map(|config| Archiver::new(transaction_store, config)),
             _rocksdb_property_reporter: RocksdbPropertyReporter::new(Arc::clone(&db)),
         }
     }
@@ -277,11 +299,39 @@ impl AptosDB {
         readonly: bool,
         storage_pruner_config: StoragePrunerConfig,
         rocksdb_config: RocksdbConfig,
+    ) -> Result<Self> {
+        Self::open_with_cold_storage(
+            db_root_path,
+            readonly,
+            storage_pruner_config,
+            rocksdb_config,
+            None, /* cold_storage_config */
+        )
+    }
+
+    /// Opens the DB like [`AptosDB::open`], additionally keeping the transactions and write sets
+    /// older than the hot window in a cold DB if `cold_storage_config` is set.
+    pub fn open_with_cold_storage<P: AsRef<Path> + Clone>(
+        db_root_path: P,
+        readonly: bool,
+        storage_pruner_config: StoragePrunerConfig,
+        rocksdb_config: RocksdbConfig,
+        cold_storage_config: Option<ColdStorageConfig>,
     ) -> Result<Self> {
         ensure!(
             storage_pruner_config.eq(&NO_OP_STORAGE_PRUNER_CONFIG) || !readonly,
             "Do not set prune_window when opening readonly.",
         );
+        if let Some(cold_storage_config) = &cold_storage_config {
+            ensure!(
+                storage_pruner_config.ledger_prune_window.is_none(),
+                "Cold storage keeps the full ledger history, do not set ledger_prune_window.",
+            );
+            ensure!(
+                cold_storage_config.archiving_batch_size > 0,
+                "Cold storage archiving_batch_size must be positive.",
+            );
+        }
 
         let path = db_root_path.as_ref().join("aptosdb");
         let instant = Instant::now();
@@ -306,7 +356,37 @@ impl AptosDB {
             )?
         };
 
-        let ret = Self::new_with_db(db, storage_pruner_config);
+        let cold_db = match &cold_storage_config {
+            Some(cold_storage_config) => {
+                let cold_path = cold_storage_config.dir.join("aptosdb_cold");
+                let cold_db = if readonly {
+                    DB::open_readonly(
+                        cold_path.clone(),
+                        "aptosdb_cold_ro",
+                        Self::cold_column_families(),
+                        &rocksdb_opts,
+                    )?
+                } else {
+                    DB::open(
+                        cold_path.clone(),
+                        "aptosdb_cold",
+                        Self::cold_column_families(),
+                        &rocksdb_opts,
+                    )?
+                };
+                info!(path = cold_path, "Opened AptosDB cold storage.");
+                Some(cold_db)
+            }
+            None => None,
+        };
+
+        let ret = Self::new_with_db(
+            db,
+            cold_db,
+            storage_pruner_config,
+            // Only move data around when we own the DB.
+            cold_storage_config.as_ref().filter(|_| !readonly),
+        );
         info!(
             path = path,
             time_ms = %instant.elapsed().as_millis(),
@@ -334,7 +414,9 @@ impl AptosDB {
                 Self::column_families(),
                 &rocksdb_opts,
             )?,
+            None, /* cold_db */
             NO_OP_STORAGE_PRUNER_CONFIG,
+            None, /* cold_storage_config */
         ))
     }
 
@@ -643,6 +725,12 @@ impl AptosDB {
             pruner.maybe_wake_pruner(latest_version)
         }
     }
+
+    fn wake_archiver(&self, latest_version: Version) {
+        if let Some(archiver) = self.archiver.as_ref() {
+            archiver.maybe_wake_archiver(latest_version)
+        }
+    }
 }
 
 impl DbReader for AptosDB {
@@ -1166,6 +1254,17 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn get_latest_state_checkpoint_version(&self) -> Result<Option<Version>> {
+        gauged_api("get_latest_state_checkpoint_version", || {
+            match self.ledger_store.get_latest_ledger_info_option() {
+                Some(ledger_info) => self
+                    .ledger_store
+                    .get_latest_state_checkpoint_version(ledger_info.ledger_info().version()),
+                None => Ok(None),
+            }
+        })
+    }
+
     fn get_accumulator_root_hash(&self, version: Version) -> Result<HashValue> {
         gauged_api("get_accumulator_root_hash", || {
             self.ledger_store.get_root_hash(version)
@@ -1283,12 +1382,15 @@ impl DbWriter for AptosDB {
 
             // Persist.
             let (sealed_cs, counters) = self.seal_change_set(first_version, num_txns, cs)?;
+            let bytes_by_cf = sealed_cs.batch.bytes_by_cf();
+            let commit_start = Instant::now();
             {
                 let _timer = OTHER_TIMERS_SECONDS
                     .with_label_values(&["save_transactions_commit"])
                     .start_timer();
                 self.commit(sealed_cs)?;
             }
+            observe_commit(num_txns, &bytes_by_cf, commit_start.elapsed());
 
             // Only increment counter if commit succeeds and there are at least one transaction written
             // to the storage. That's also when we'd inform the pruner thread to work.
@@ -1308,6 +1410,7 @@ impl DbWriter for AptosDB {
                 );
 
                 self.wake_pruner(last_version);
+                self.wake_archiver(last_version);
             }
 
             // Once everything is successfully persisted, update the latest in-memory ledger info.
@@ -1449,6 +1552,30 @@ impl GetRestoreHandler for Arc<AptosDB> {
     }
 }
 
+/// Updates the metrics of a commit of `save_transactions`, once it succeeded
+fn observe_commit(
+    num_txns: u64,
+    bytes_by_cf: &BTreeMap<ColumnFamilyName, usize>,
+    sync_time: Duration,
+) {
+    SAVE_TRANSACTIONS_BATCH_SIZE.observe(num_txns as f64);
+    SAVE_TRANSACTIONS_SYNC_SECONDS.observe(sync_time.as_secs_f64());
+    for (cf_name, bytes) in bytes_by_cf {
+        SAVE_TRANSACTIONS_CF_BYTES
+            .with_label_values(&[*cf_name])
+            .observe(*bytes as f64);
+    }
+    let total_bytes = bytes_by_cf.values().sum::<usize>();
+    SAVE_TRANSACTIONS_BATCH_BYTES.observe(total_bytes as f64);
+    // Commits of a ledger info alone write no transaction
+    if let Some(txn_bytes) = bytes_by_cf
+        .get(TRANSACTION_CF_NAME)
+        .filter(|bytes| **bytes > 0)
+    {
+        SAVE_TRANSACTIONS_WRITE_AMPLIFICATION.observe(total_bytes as f64 / *txn_bytes as f64);
+    }
+}
+
 fn gauged_api<T, F>(api_name: &'static str, api_impl: F) -> Result<T>
 where
     F: FnOnce() -> Result<T>,
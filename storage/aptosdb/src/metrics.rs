@@ -2,8 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_metrics::{
-    register_histogram_vec, register_int_counter, register_int_gauge, register_int_gauge_vec,
-    HistogramVec, IntCounter, IntGauge, IntGaugeVec,
+    register_histogram, register_histogram_vec, register_int_counter, register_int_gauge,
+    register_int_gauge_vec, Histogram, HistogramVec, IntCounter, IntGauge, IntGaugeVec,
 };
 use once_cell::sync::Lazy;
 
@@ -87,6 +87,15 @@ pub static PRUNER_LEAST_READABLE_VERSION: Lazy<IntGaugeVec> = Lazy::new(|| {
 pub static PRUNER_BATCH_SIZE: Lazy<IntGauge> =
     Lazy::new(|| register_int_gauge!("pruner_batch_size", "Aptos pruner batch size").unwrap());
 
+/// Versions below this one were moved to the cold DB
+pub static COLD_STORAGE_ARCHIVED_VERSION: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_storage_cold_storage_archived_version",
+        "Aptos storage version up to which transactions were moved to the cold DB"
+    )
+    .unwrap()
+});
+
 pub static API_LATENCY_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
     register_histogram_vec!(
         // metric name
@@ -111,6 +120,64 @@ pub static OTHER_TIMERS_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Powers of 4 from 1KiB to 4GiB, for the sizes of commits
+fn commit_bytes_buckets() -> Vec<f64> {
+    (0..12).map(|i| 1024.0 * 4f64.powi(i)).collect()
+}
+
+pub static SAVE_TRANSACTIONS_BATCH_SIZE: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        "aptos_storage_save_transactions_batch_size",
+        "Number of transactions saved by each commit of save_transactions",
+        vec![
+            0.0, 1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0, 200.0, 500.0, 1000.0, 2000.0, 5000.0,
+            10000.0
+        ]
+    )
+    .unwrap()
+});
+
+pub static SAVE_TRANSACTIONS_BATCH_BYTES: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        "aptos_storage_save_transactions_batch_bytes",
+        "Bytes written to the DB by each commit of save_transactions",
+        commit_bytes_buckets()
+    )
+    .unwrap()
+});
+
+pub static SAVE_TRANSACTIONS_CF_BYTES: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        // metric name
+        "aptos_storage_save_transactions_cf_bytes",
+        // metric description
+        "Bytes written to each column family by each commit of save_transactions",
+        // metric labels (dimensions)
+        &["cf_name"],
+        commit_bytes_buckets()
+    )
+    .unwrap()
+});
+
+/// Bytes written to the DB per byte of the transactions themselves, i.e. what the indices, the
+/// write sets, the events and the state add on top of them
+pub static SAVE_TRANSACTIONS_WRITE_AMPLIFICATION: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        "aptos_storage_save_transactions_write_amplification",
+        "Bytes written to the DB per byte of transactions by each commit of save_transactions",
+        vec![1.0, 2.0, 3.0, 5.0, 10.0, 20.0, 50.0, 100.0, 200.0, 500.0, 1000.0]
+    )
+    .unwrap()
+});
+
+pub static SAVE_TRANSACTIONS_SYNC_SECONDS: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        "aptos_storage_save_transactions_sync_seconds",
+        "Seconds each commit of save_transactions takes to write its batch and sync it to disk"
+    )
+    .unwrap()
+});
+
 /// Rocksdb metrics
 pub static ROCKSDB_PROPERTIES: Lazy<IntGaugeVec> = Lazy::new(|| {
     register_int_gauge_vec!(
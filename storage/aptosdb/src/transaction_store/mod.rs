@@ -2,6 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! This file defines transaction store APIs that are related to committed signed transactions.
+//!
+//! With cold storage enabled, transactions and write sets older than the hot window are moved to a
+//! secondary DB by [`TransactionStore::archive`]. Reads fall back to the cold DB transparently.
+//! Data is written to the cold DB before being deleted from the main DB, and reads look at the main
+//! DB before the cold one, so a concurrent move never hides a version from readers.
 
 use crate::{
     change_set::ChangeSet,
@@ -21,17 +26,80 @@ use aptos_types::{
     transaction::{Transaction, Version},
     write_set::WriteSet,
 };
-use schemadb::{ReadOptions, SchemaBatch, SchemaIterator, DB};
+use schemadb::{
+    schema::{Schema, SeekKeyCodec},
+    ReadOptions, SchemaBatch, SchemaIterator, DB,
+};
 use std::sync::Arc;
 
 #[derive(Debug)]
 pub struct TransactionStore {
     db: Arc<DB>,
+    /// Holds the transactions and write sets moved out of `db`, if cold storage is enabled.
+    cold_db: Option<Arc<DB>>,
 }
 
 impl TransactionStore {
-    pub fn new(db: Arc<DB>) -> Self {
-        Self { db }
+    pub fn new(db: Arc<DB>, cold_db: Option<Arc<DB>>) -> Self {
+        Self { db, cold_db }
+    }
+
+    /// Gets the entry of `S` at `version`, from the main DB or else from the cold DB.
+    fn get_tiered<S>(&self, version: &Version) -> Result<Option<S::Value>>
+    where
+        S: Schema<Key = Version>,
+    {
+        if let Some(value) = self.db.get::<S>(version)? {
+            return Ok(Some(value));
+        }
+        match &self.cold_db {
+            Some(cold_db) => cold_db.get::<S>(version),
+            None => Ok(None),
+        }
+    }
+
+    /// Gets an iterator over the entries of `S` starting at `start_version`, going through the
+    /// cold DB first and then the main DB.
+    fn iter_tiered<S>(&self, start_version: Version) -> Result<TieredIter<S>>
+    where
+        S: Schema<Key = Version>,
+        Version: SeekKeyCodec<S>,
+    {
+        // Iterators read from a snapshot, so the main DB one needs to be created first to not miss
+        // versions being moved.
+        let mut hot = self.db.iter::<S>(ReadOptions::default())?;
+        hot.seek(&start_version)?;
+        let cold = match &self.cold_db {
+            Some(cold_db) => {
+                let mut cold = cold_db.iter::<S>(ReadOptions::default())?;
+                cold.seek(&start_version)?;
+                Some(cold)
+            }
+            None => None,
+        };
+        Ok(TieredIter {
+            cold,
+            hot,
+            last_cold_version: None,
+        })
+    }
+
+    /// Gets the first version of `S`, looking at the cold DB first.
+    fn get_first_tiered_version<S>(&self) -> Result<Option<Version>>
+    where
+        S: Schema<Key = Version>,
+    {
+        let mut iter = self.db.iter::<S>(Default::default())?;
+        iter.seek_to_first();
+        let first_hot_version = iter.next().map(|res| res.map(|(v, _)| v)).transpose()?;
+        if let Some(cold_db) = &self.cold_db {
+            let mut iter = cold_db.iter::<S>(Default::default())?;
+            iter.seek_to_first();
+            if let Some(version) = iter.next().map(|res| res.map(|(v, _)| v)).transpose()? {
+                return Ok(Some(version));
+            }
+        }
+        Ok(first_hot_version)
     }
 
     /// Gets the version of a transaction by the sender `address` and `sequence_number`.
@@ -96,8 +164,7 @@ impl TransactionStore {
 
     /// Get signed transaction given `version`
     pub fn get_transaction(&self, version: Version) -> Result<Transaction> {
-        self.db
-            .get::<TransactionSchema>(&version)?
+        self.get_tiered::<TransactionSchema>(&version)?
             .ok_or_else(|| AptosDbError::NotFound(format!("Txn {}", version)).into())
     }
 
@@ -107,10 +174,8 @@ impl TransactionStore {
         start_version: Version,
         num_transactions: usize,
     ) -> Result<TransactionIter> {
-        let mut iter = self.db.iter::<TransactionSchema>(ReadOptions::default())?;
-        iter.seek(&start_version)?;
         Ok(TransactionIter {
-            inner: iter,
+            inner: self.iter_tiered::<TransactionSchema>(start_version)?,
             expected_next_version: start_version,
             end_version: start_version
                 .checked_add(num_transactions as u64)
@@ -120,9 +185,7 @@ impl TransactionStore {
 
     /// Get the first version that txn starts existent.
     pub fn get_first_txn_version(&self) -> Result<Option<Version>> {
-        let mut iter = self.db.iter::<TransactionSchema>(Default::default())?;
-        iter.seek_to_first();
-        iter.next().map(|res| res.map(|(v, _)| v)).transpose()
+        self.get_first_tiered_version::<TransactionSchema>()
     }
 
     /// Returns the block metadata carried on the block metadata transaction at or preceding
@@ -138,7 +201,19 @@ impl TransactionStore {
         // each block.
         let mut iter = self.db.rev_iter::<TransactionSchema>(Default::default())?;
         iter.seek(&version)?;
-        for res in iter.take(MAX_VERSIONS_TO_SEARCH) {
+        // Continue into the cold DB once the main DB runs out of older versions.
+        let cold_iter = match &self.cold_db {
+            Some(cold_db) => {
+                let mut cold_iter = cold_db.rev_iter::<TransactionSchema>(Default::default())?;
+                cold_iter.seek(&version)?;
+                Some(cold_iter)
+            }
+            None => None,
+        };
+        for res in iter
+            .chain(cold_iter.into_iter().flatten())
+            .take(MAX_VERSIONS_TO_SEARCH)
+        {
             let (v, txn) = res?;
             if let Transaction::BlockMetadata(block_meta) = txn {
                 return Ok(Some((v, block_meta)));
@@ -172,7 +247,7 @@ impl TransactionStore {
 
     /// Get executed transaction vm output given `version`
     pub fn get_write_set(&self, version: Version) -> Result<WriteSet> {
-        self.db.get::<WriteSetSchema>(&version)?.ok_or_else(|| {
+        self.get_tiered::<WriteSetSchema>(&version)?.ok_or_else(|| {
             AptosDbError::NotFound(format!("WriteSet at version {}", version)).into()
         })
     }
@@ -195,8 +270,7 @@ impl TransactionStore {
             end_version
         );
 
-        let mut iter = self.db.iter::<WriteSetSchema>(Default::default())?;
-        iter.seek(&begin_version)?;
+        let mut iter = self.iter_tiered::<WriteSetSchema>(begin_version)?;
 
         let mut ret = Vec::with_capacity((end_version - begin_version) as usize);
         for current_version in begin_version..end_version {
@@ -218,9 +292,7 @@ impl TransactionStore {
 
     /// Get the first version that write set starts existent.
     pub fn get_first_write_set_version(&self) -> Result<Option<Version>> {
-        let mut iter = self.db.iter::<WriteSetSchema>(Default::default())?;
-        iter.seek_to_first();
-        iter.next().map(|res| res.map(|(v, _)| v)).transpose()
+        self.get_first_tiered_version::<WriteSetSchema>()
     }
 
     /// Save executed transaction vm output given `version`
@@ -233,6 +305,48 @@ impl TransactionStore {
         cs.batch.put::<WriteSetSchema>(&version, write_set)
     }
 
+    /// Moves the transactions and write sets in `[begin, end)` from the main DB to the cold DB.
+    pub fn archive(&self, begin: Version, end: Version) -> Result<()> {
+        let cold_db = self
+            .cold_db
+            .as_ref()
+            .ok_or_else(|| format_err!("Cold storage is not enabled."))?;
+        let mut cold_batch = SchemaBatch::new();
+        let mut hot_batch = SchemaBatch::new();
+
+        let mut iter = self.db.iter::<TransactionSchema>(ReadOptions::default())?;
+        iter.seek(&begin)?;
+        for res in iter {
+            let (version, transaction) = res?;
+            if version >= end {
+                break;
+            }
+            cold_batch.put::<TransactionSchema>(&version, &transaction)?;
+            hot_batch.delete::<TransactionSchema>(&version)?;
+        }
+        let mut iter = self.db.iter::<WriteSetSchema>(ReadOptions::default())?;
+        iter.seek(&begin)?;
+        for res in iter {
+            let (version, write_set) = res?;
+            if version >= end {
+                break;
+            }
+            cold_batch.put::<WriteSetSchema>(&version, &write_set)?;
+            hot_batch.delete::<WriteSetSchema>(&version)?;
+        }
+
+        // The cold DB must have the data before it's gone from the main DB.
+        cold_db.write_schemas(cold_batch)?;
+        self.db.write_schemas(hot_batch)
+    }
+
+    /// Gets the first version of the transactions still in the main DB.
+    pub fn get_first_hot_txn_version(&self) -> Result<Option<Version>> {
+        let mut iter = self.db.iter::<TransactionSchema>(Default::default())?;
+        iter.seek_to_first();
+        iter.next().map(|res| res.map(|(v, _)| v)).transpose()
+    }
+
     /// Prune the transaction by hash store given a list of transaction
     pub fn prune_transaction_by_hash(
         &self,
@@ -311,8 +425,48 @@ impl TransactionStore {
     }
 }
 
+/// Iterates over the entries of `S` in the cold DB and then those in the main DB, skipping versions
+/// present in both while they are being moved.
+struct TieredIter<'a, S> {
+    cold: Option<SchemaIterator<'a, S>>,
+    hot: SchemaIterator<'a, S>,
+    last_cold_version: Option<Version>,
+}
+
+impl<'a, S> Iterator for TieredIter<'a, S>
+where
+    S: Schema<Key = Version>,
+{
+    type Item = Result<(Version, S::Value)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(cold) = self.cold.as_mut() {
+            match cold.next() {
+                Some(Ok((version, value))) => {
+                    self.last_cold_version = Some(version);
+                    return Some(Ok((version, value)));
+                }
+                Some(Err(err)) => return Some(Err(err)),
+                None => self.cold = None,
+            }
+        }
+        loop {
+            match self.hot.next()? {
+                Ok((version, _))
+                    if self
+                        .last_cold_version
+                        .map_or(false, |last_cold_version| version <= last_cold_version) =>
+                {
+                    continue
+                }
+                res => return Some(res),
+            }
+        }
+    }
+}
+
 pub struct TransactionIter<'a> {
-    inner: SchemaIterator<'a, TransactionSchema>,
+    inner: TieredIter<'a, TransactionSchema>,
     expected_next_version: Version,
     end_version: Version,
 }
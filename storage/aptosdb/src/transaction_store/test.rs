@@ -3,6 +3,7 @@
 
 use super::*;
 use crate::AptosDB;
+use aptos_config::config::{ColdStorageConfig, RocksdbConfig, NO_OP_STORAGE_PRUNER_CONFIG};
 use aptos_proptest_helpers::Index;
 use aptos_temppath::TempPath;
 use aptos_types::{
@@ -248,6 +249,61 @@ proptest! {
 
         prop_assert_eq!(&actual_scan, &expected_scan);
     }
+
+    #[test]
+    fn test_archive_read_through(
+        universe in any_with::<AccountInfoUniverse>(3),
+        gens_and_write_sets in vec(
+            ((any::<Index>(), any::<SignatureCheckedTransactionGen>()), any::<WriteSet>()),
+            1..10
+        ),
+        archive_ratio in 0.0..=1.0f64,
+    ) {
+        let tmp_dir = TempPath::new();
+        let cold_dir = TempPath::new();
+        let db = AptosDB::open_with_cold_storage(
+            &tmp_dir,
+            false, /* readonly */
+            NO_OP_STORAGE_PRUNER_CONFIG,
+            RocksdbConfig::default(),
+            Some(ColdStorageConfig {
+                dir: cold_dir.path().to_path_buf(),
+                ..ColdStorageConfig::default()
+            }),
+        )
+        .unwrap();
+        let store = &db.transaction_store;
+        let (gens, write_sets):(Vec<_>, Vec<_>) = gens_and_write_sets.into_iter().unzip();
+        let txns = init_store(universe, gens, store);
+        let mut cs = ChangeSet::new();
+        for (ver, ws) in write_sets.iter().enumerate() {
+            store.put_write_set(ver as Version, ws, &mut cs).unwrap();
+        }
+        store.db.write_schemas(cs.batch).unwrap();
+
+        let num_txns = txns.len() as Version;
+        let archived = (num_txns as f64 * archive_ratio) as Version;
+        store.archive(0, archived).unwrap();
+
+        prop_assert_eq!(
+            store.get_first_hot_txn_version().unwrap(),
+            if archived < num_txns { Some(archived) } else { None }
+        );
+        prop_assert_eq!(store.get_first_txn_version().unwrap(), Some(0));
+        prop_assert_eq!(store.get_first_write_set_version().unwrap(), Some(0));
+        for (ver, (txn, write_set)) in itertools::zip_eq(txns.iter(), write_sets.iter()).enumerate() {
+            prop_assert_eq!(store.get_transaction(ver as Version).unwrap(), txn.clone());
+            prop_assert_eq!(store.get_write_set(ver as Version).unwrap(), write_set.clone());
+        }
+        let actual = store
+            .get_transaction_iter(0, txns.len())
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        prop_assert_eq!(actual, txns);
+        prop_assert_eq!(store.get_write_sets(0, num_txns).unwrap(), write_sets);
+        prop_assert!(store.get_transaction(num_txns).is_err());
+    }
 }
 
 fn init_store(
@@ -0,0 +1,91 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use aptos_logger::{prelude::*, Level, Logger};
+use aptos_secure_push_metrics::MetricsPusher;
+use backup_cli::{
+    coordinators::{
+        restore::{RestoreCoordinator, RestoreCoordinatorOpt},
+        snapshot_export::{SnapshotExportCoordinator, SnapshotExportOpt},
+    },
+    metadata::cache::MetadataCacheOpt,
+    storage::StorageOpt,
+    utils::{GlobalBackupOpt, GlobalRestoreOpt, GlobalRestoreOptions},
+};
+use std::convert::TryInto;
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+#[structopt(
+    about = "Exports the state of a DB at a version into a self-contained, verifiable snapshot and \
+    imports such snapshots into empty DBs, e.g. to bootstrap fullnodes."
+)]
+enum Command {
+    #[structopt(about = "Export a snapshot from a DB, which doesn't need a running node.")]
+    Export(ExportOpt),
+    #[structopt(about = "Import a snapshot into an empty DB, verifying it along the way.")]
+    Import(ImportOpt),
+}
+
+#[derive(StructOpt)]
+struct ExportOpt {
+    #[structopt(flatten)]
+    global: GlobalBackupOpt,
+
+    #[structopt(flatten)]
+    opt: SnapshotExportOpt,
+
+    #[structopt(subcommand)]
+    storage: StorageOpt,
+}
+
+#[derive(StructOpt)]
+struct ImportOpt {
+    #[structopt(flatten)]
+    global: GlobalRestoreOpt,
+
+    #[structopt(flatten)]
+    metadata_cache_opt: MetadataCacheOpt,
+
+    #[structopt(subcommand)]
+    storage: StorageOpt,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    main_impl().await.map_err(|e| {
+        error!("main_impl() failed: {}", e);
+        e
+    })
+}
+
+async fn main_impl() -> Result<()> {
+    Logger::new().level(Level::Info).read_env().init();
+    let _mp = MetricsPusher::start();
+
+    match Command::from_args() {
+        Command::Export(opt) => {
+            SnapshotExportCoordinator::new(opt.opt, opt.global, opt.storage.init_storage().await?)
+                .run()
+                .await?;
+        }
+        Command::Import(opt) => {
+            let global_opt: GlobalRestoreOptions = opt.global.try_into()?;
+            RestoreCoordinator::new(
+                RestoreCoordinatorOpt {
+                    metadata_cache_opt: opt.metadata_cache_opt,
+                    replay_all: false,
+                    ledger_history_start_version: 0,
+                    skip_epoch_endings: false,
+                },
+                global_opt,
+                opt.storage.init_storage().await?,
+            )
+            .run()
+            .await?;
+        }
+    }
+
+    Ok(())
+}
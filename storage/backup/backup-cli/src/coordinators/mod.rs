@@ -4,4 +4,8 @@
 pub mod backup;
 pub mod replay_verify;
 pub mod restore;
+pub mod snapshot_export;
 pub mod verify;
+
+#[cfg(test)]
+mod tests;
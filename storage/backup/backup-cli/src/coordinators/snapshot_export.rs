@@ -0,0 +1,160 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        epoch_ending::backup::{EpochEndingBackupController, EpochEndingBackupOpt},
+        state_snapshot::backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
+        transaction::backup::{TransactionBackupController, TransactionBackupOpt},
+    },
+    storage::BackupStorage,
+    utils::{backup_service_client::BackupServiceClient, GlobalBackupOpt, RocksdbOpt},
+};
+use anyhow::{anyhow, ensure, Result};
+use aptos_config::{config::NO_OP_STORAGE_PRUNER_CONFIG, utils::get_available_port};
+use aptos_logger::prelude::*;
+use aptos_types::{
+    ledger_info::LedgerInfoWithSignatures, proof::TransactionInfoWithProof, transaction::Version,
+};
+use aptosdb::AptosDB;
+use backup_service::start_backup_service;
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::PathBuf,
+    sync::Arc,
+};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+pub struct SnapshotExportOpt {
+    #[structopt(
+        long = "db-dir",
+        parse(from_os_str),
+        help = "DB to export the snapshot from, opened read only."
+    )]
+    pub db_dir: PathBuf,
+
+    #[structopt(
+        long = "state-version",
+        help = "Version of the state checkpoint to export."
+    )]
+    pub version: Version,
+
+    #[structopt(flatten)]
+    pub rocksdb_opt: RocksdbOpt,
+}
+
+/// Exports the state at a state checkpoint version of a DB into a backup storage, along with
+/// everything needed to restore it into an empty DB and verify it: the epoch ending ledger infos
+/// up to the epoch of the version and the transaction at the version. The export is a regular
+/// backup, so `db-restore auto` (or `db-snapshot import`) restores it.
+pub struct SnapshotExportCoordinator {
+    db_dir: PathBuf,
+    version: Version,
+    rocksdb_opt: RocksdbOpt,
+    global_opt: GlobalBackupOpt,
+    storage: Arc<dyn BackupStorage>,
+}
+
+impl SnapshotExportCoordinator {
+    pub fn new(
+        opt: SnapshotExportOpt,
+        global_opt: GlobalBackupOpt,
+        storage: Arc<dyn BackupStorage>,
+    ) -> Self {
+        Self {
+            db_dir: opt.db_dir,
+            version: opt.version,
+            rocksdb_opt: opt.rocksdb_opt,
+            global_opt,
+            storage,
+        }
+    }
+
+    pub async fn run(self) -> Result<()> {
+        info!("Snapshot export started, for version {}.", self.version);
+
+        let db = Arc::new(AptosDB::open(
+            &self.db_dir,
+            true,                        /* read_only */
+            NO_OP_STORAGE_PRUNER_CONFIG, /* pruner config */
+            self.rocksdb_opt.clone().into(),
+        )?);
+        // The backup controllers read from a backup service, so serve the DB locally for them.
+        let port = get_available_port();
+        let backup_service =
+            start_backup_service(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port), db);
+        let client = Arc::new(BackupServiceClient::new(format!(
+            "http://localhost:{}",
+            port
+        )));
+
+        let ret = self.run_impl(client).await;
+        // Dropping a runtime blocks, which isn't allowed in an async context.
+        backup_service.shutdown_background();
+
+        ret.map_err(|e| anyhow!("Snapshot export failed: {}", e))?;
+        info!("Snapshot export succeeded.");
+        Ok(())
+    }
+}
+
+impl SnapshotExportCoordinator {
+    async fn run_impl(&self, client: Arc<BackupServiceClient>) -> Result<()> {
+        let db_state = client
+            .get_db_state()
+            .await?
+            .ok_or_else(|| anyhow!("DB not bootstrapped."))?;
+        ensure!(
+            self.version <= db_state.committed_version,
+            "Version {} is newer than the latest committed version {}.",
+            self.version,
+            db_state.committed_version,
+        );
+
+        // The ledger info proving the state is in the same epoch as the version, so the epochs
+        // before it are what's needed to verify it.
+        let (_, li): (TransactionInfoWithProof, LedgerInfoWithSignatures) =
+            bcs::from_bytes(&client.get_state_root_proof(self.version).await?)?;
+        let end_epoch = li.ledger_info().epoch();
+        if end_epoch > 0 {
+            EpochEndingBackupController::new(
+                EpochEndingBackupOpt {
+                    start_epoch: 0,
+                    end_epoch,
+                },
+                self.global_opt.clone(),
+                Arc::clone(&client),
+                Arc::clone(&self.storage),
+            )
+            .run()
+            .await?;
+        }
+
+        StateSnapshotBackupController::new(
+            StateSnapshotBackupOpt {
+                version: self.version,
+            },
+            self.global_opt.clone(),
+            Arc::clone(&client),
+            Arc::clone(&self.storage),
+        )
+        .run()
+        .await?;
+
+        // The restore starts the ledger history of the DB at the snapshot, from this transaction.
+        TransactionBackupController::new(
+            TransactionBackupOpt {
+                start_version: self.version,
+                num_transactions: 1,
+            },
+            self.global_opt.clone(),
+            client,
+            Arc::clone(&self.storage),
+        )
+        .run()
+        .await?;
+
+        Ok(())
+    }
+}
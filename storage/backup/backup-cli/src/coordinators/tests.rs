@@ -0,0 +1,88 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    coordinators::{
+        restore::{RestoreCoordinator, RestoreCoordinatorOpt},
+        snapshot_export::{SnapshotExportCoordinator, SnapshotExportOpt},
+    },
+    metadata::cache::MetadataCacheOpt,
+    storage::{local_fs::LocalFs, BackupStorage},
+    utils::{
+        test_utils::tmp_db_with_random_content, ConcurrentDownloadsOpt, GlobalBackupOpt,
+        GlobalRestoreOpt, RocksdbOpt, TrustedWaypointOpt,
+    },
+};
+use aptos_temppath::TempPath;
+use aptosdb::AptosDB;
+use std::{convert::TryInto, sync::Arc};
+use storage_interface::DbReader;
+use structopt::StructOpt;
+use tokio::runtime::Runtime;
+
+#[test]
+fn snapshot_export_and_import() {
+    let (src_db_dir, src_db, _blocks) = tmp_db_with_random_content();
+    let latest_tree_state = src_db.get_latest_tree_state().unwrap();
+    let version = latest_tree_state.num_transactions - 1;
+    let state_root_hash = latest_tree_state.state_root_hash;
+    drop(src_db);
+
+    let tgt_db_dir = TempPath::new();
+    tgt_db_dir.create_as_dir().unwrap();
+    let snapshot_dir = TempPath::new();
+    snapshot_dir.create_as_dir().unwrap();
+    let metadata_cache_dir = TempPath::new();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(snapshot_dir.path().to_path_buf()));
+
+    let rt = Runtime::new().unwrap();
+    rt.block_on(
+        SnapshotExportCoordinator::new(
+            SnapshotExportOpt {
+                db_dir: src_db_dir.path().to_path_buf(),
+                version,
+                rocksdb_opt: RocksdbOpt::default(),
+            },
+            GlobalBackupOpt {
+                max_chunk_size: 500,
+            },
+            Arc::clone(&store),
+        )
+        .run(),
+    )
+    .unwrap();
+
+    rt.block_on(
+        RestoreCoordinator::new(
+            RestoreCoordinatorOpt {
+                metadata_cache_opt: MetadataCacheOpt::from_iter(vec![
+                    "exe",
+                    "--metadata-cache-dir",
+                    metadata_cache_dir.path().to_str().unwrap(),
+                ]),
+                replay_all: false,
+                ledger_history_start_version: 0,
+                // Restoring the epoch history is covered by the epoch ending tests
+                skip_epoch_endings: true,
+            },
+            GlobalRestoreOpt {
+                dry_run: false,
+                db_dir: Some(tgt_db_dir.path().to_path_buf()),
+                target_version: None, // max
+                trusted_waypoints: TrustedWaypointOpt::default(),
+                rocksdb_opt: RocksdbOpt::default(),
+                concurernt_downloads: ConcurrentDownloadsOpt::default(),
+            }
+            .try_into()
+            .unwrap(),
+            store,
+        )
+        .run(),
+    )
+    .unwrap();
+
+    let tgt_db = AptosDB::new_for_test(&tgt_db_dir);
+    let tgt_tree_state = tgt_db.get_latest_tree_state().unwrap();
+    assert_eq!(tgt_tree_state.num_transactions, version + 1);
+    assert_eq!(tgt_tree_state.state_root_hash, state_root_hash);
+}
@@ -30,7 +30,7 @@ use crate::{
 use anyhow::{ensure, format_err, Result};
 use aptos_logger::prelude::*;
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     iter::Iterator,
     marker::PhantomData,
     path::Path,
@@ -121,6 +121,25 @@ impl SchemaBatch {
             .push(WriteOp::DeletionRangeInclusive { begin, end });
         Ok(())
     }
+
+    /// Bytes of the keys and values the batch writes to each column family
+    pub fn bytes_by_cf(&self) -> BTreeMap<ColumnFamilyName, usize> {
+        self.rows
+            .iter()
+            .map(|(cf_name, rows)| {
+                let bytes = rows
+                    .iter()
+                    .map(|write_op| match write_op {
+                        WriteOp::Value { key, value } => key.len() + value.len(),
+                        WriteOp::Deletion { key } => key.len(),
+                        WriteOp::DeletionRange { begin, end }
+                        | WriteOp::DeletionRangeInclusive { begin, end } => begin.len() + end.len(),
+                    })
+                    .sum();
+                (*cf_name, bytes)
+            })
+            .collect()
+    }
 }
 
 pub enum ScanDirection {
@@ -468,3 +468,23 @@ fn test_checkpoint() {
         assert_eq!(db.get::<TestSchema1>(&TestField(1)).unwrap(), None);
     }
 }
+
+#[test]
+fn test_schemabatch_bytes_by_cf() {
+    let mut db_batch = SchemaBatch::new();
+    for i in 0..10u32 {
+        db_batch
+            .put::<TestSchema1>(&TestField(i), &TestField(i))
+            .unwrap();
+    }
+    db_batch.delete::<TestSchema2>(&TestField(0)).unwrap();
+    db_batch
+        .delete_range::<TestSchema2>(&TestField(1), &TestField(5))
+        .unwrap();
+
+    let bytes_by_cf = db_batch.bytes_by_cf();
+    // Keys and values are 4 bytes each
+    assert_eq!(bytes_by_cf.len(), 2);
+    assert_eq!(bytes_by_cf[TestSchema1::COLUMN_FAMILY_NAME], 80);
+    assert_eq!(bytes_by_cf[TestSchema2::COLUMN_FAMILY_NAME], 12);
+}
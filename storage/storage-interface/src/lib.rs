@@ -462,6 +462,13 @@ pub trait DbReader: Send + Sync {
         unimplemented!()
     }
 
+    /// Gets the latest version with a state checkpoint that is covered by the latest ledger info.
+    /// Unlike versions merely synced past the ledger info, which can be replayed after a restore,
+    /// data up to this version is final. Returns `None` if the db is not bootstrapped.
+    fn get_latest_state_checkpoint_version(&self) -> Result<Option<Version>> {
+        unimplemented!()
+    }
+
     /// Gets the transaction accumulator root hash at specified version.
     /// Caller must guarantee the version is not greater than the latest version.
     fn get_accumulator_root_hash(&self, _version: Version) -> Result<HashValue> {
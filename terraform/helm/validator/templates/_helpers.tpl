@@ -93,3 +93,16 @@ http://{{ include "aptos-validator.fullname" . }}-pushgateway:9091
 {{- define "backup.loggingAddress" -}}
 {{ include "aptos-validator.fullname" . }}-logging:5044
 {{- end -}}
+
+{{/*
+Renders a node config file of files/configs, with the fields of the overrides, a YAML or JSON
+string, merged over it.
+*/}}
+{{- define "aptos-validator.nodeConfig" -}}
+{{- $config := tpl (.root.Files.Get (printf "files/configs/%s" .file)) .root -}}
+{{- if .overrides -}}
+{{- mustMergeOverwrite (fromYaml $config) (fromYaml .overrides) | toYaml -}}
+{{- else -}}
+{{- $config -}}
+{{- end -}}
+{{- end -}}
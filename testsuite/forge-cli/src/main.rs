@@ -1,16 +1,48 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+use anyhow::{bail, format_err};
 use aptos_rest_client::Client as RestClient;
-use aptos_sdk::{move_types::account_address::AccountAddress, transaction_builder::aptos_stdlib};
-use forge::{ForgeConfig, Options, Result, *};
-use std::{env, num::NonZeroUsize, process, time::Duration};
+use aptos_sdk::{
+    crypto::ed25519::Ed25519PrivateKey,
+    move_types::account_address::AccountAddress,
+    transaction_builder::aptos_stdlib,
+    types::{account_config::aptos_root_address, LocalAccount},
+};
+use forge::{transaction_mix::TransactionMix, ForgeConfig, Options, Result, *};
+use std::{
+    env, fs,
+    num::NonZeroUsize,
+    path::PathBuf,
+    process, thread,
+    time::{Duration, Instant},
+};
 use structopt::StructOpt;
 use testcases::{
-    compatibility_test::SimpleValidatorUpgrade, fixed_tps_test::FixedTpsTest,
-    gas_price_test::NonZeroGasPrice, generate_traffic, partial_nodes_down_test::PartialNodesDown,
-    performance_test::PerformanceBenchmark, reconfiguration_test::ReconfigurationTest,
+    clock_skew_test::ClockSkew,
+    compatibility_test::{
+        CompatibilityMatrix, MixedVersionSoak, SimpleValidatorUpgrade, UpgradeUnderPartition,
+    },
+    epoch_change_latency_test::EpochChangeLatency,
+    fixed_tps_test::FixedTpsTest,
+    gas_price_test::{GasPriceMarket, NonZeroGasPrice},
+    generate_traffic,
+    genesis_ceremony_test::GenesisCeremony,
+    partial_nodes_down_test::PartialNodesDown,
+    partition_recovery_test::PartitionRecovery,
+    performance_test::{HotAccountBenchmark, PerformanceBenchmark},
+    quorum_loss_recovery_test::QuorumLossRecovery,
+    random_restart_fuzzer::RandomRestartFuzzer,
+    reconfiguration_test::ReconfigurationTest,
+    safety_rules_sidecar_test::SafetyRulesSidecarCompat,
+    slow_disk_test::SlowDiskValidator,
+    soak_test::SoakTest,
     state_sync_performance::StateSyncPerformance,
+    twin_validator_test::TwinValidator,
+    validator_key_rotation_test::ValidatorKeyRotation,
+    validator_rotation_test::ValidatorRotation,
+    validator_set_growth_test::ValidatorSetGrowth,
+    zone_failure_test::ZoneFailure,
 };
 use tokio::runtime::Runtime;
 use url::Url;
@@ -26,12 +58,43 @@ struct Args {
     wait_millis: u64,
     #[structopt(long)]
     burst: bool,
+    #[structopt(
+        long,
+        help = "Weighted mix of the emitted txns, e.g. transfer:70,create_account:20,publish_module:5,script_function:5",
+        default_value = "transfer:1"
+    )]
+    transaction_mix: TransactionMix,
     #[structopt(flatten)]
     options: Options,
     #[structopt(long, help = "Specify a test suite to run")]
     suite: Option<String>,
+    #[structopt(
+        long,
+        parse(try_from_str = parse_interval),
+        help = "How long the soak suite runs its traffic, e.g. 30m or 12h",
+        default_value = "6h"
+    )]
+    duration: Duration,
     #[structopt(long, multiple = true)]
     changelog: Option<Vec<String>>,
+    #[structopt(
+        long,
+        parse(try_from_str = parse_env_var),
+        help = "Environment variable to set on every node, as KEY=VALUE. Can be repeated"
+    )]
+    node_env: Vec<(String, String)>,
+    #[structopt(
+        long,
+        help = "Seed to replay a run with, as printed by the run. Random if unset"
+    )]
+    seed: Option<u64>,
+    #[structopt(
+        long,
+        help = "Submit the traffic of k8s tests to the fullnodes of the validators, which forward it to their validator"
+    )]
+    emit_to_fullnodes: bool,
+    #[structopt(flatten)]
+    performance_gate: PerformanceGate,
 
     // subcommand groups
     #[structopt(flatten)]
@@ -42,12 +105,15 @@ struct Args {
 enum CliCommand {
     Test(TestCommand),
     Operator(OperatorCommand),
+    Daemon(Daemon),
+    Baseline(BaselineCommand),
 }
 
 #[derive(StructOpt, Debug)]
 enum TestCommand {
     LocalSwarm(LocalSwarm),
     K8sSwarm(K8sSwarm),
+    Remote(Remote),
 }
 
 #[derive(StructOpt, Debug)]
@@ -60,6 +126,19 @@ enum OperatorCommand {
 #[derive(StructOpt, Debug)]
 struct LocalSwarm {}
 
+/// Runs the Aptos and Admin tests of the suite against an already running network, e.g. an
+/// externally hosted devnet, without launching or controlling any node
+#[derive(StructOpt, Debug)]
+struct Remote {
+    #[structopt(long, help = "REST API of a node of the network")]
+    rest_api: Url,
+    #[structopt(
+        long,
+        help = "BCS encoded private key of the root account of the network, like mint.key"
+    )]
+    root_key_path: PathBuf,
+}
+
 #[derive(StructOpt, Debug)]
 struct K8sSwarm {
     #[structopt(
@@ -80,13 +159,159 @@ struct K8sSwarm {
         default_value = "devnet"
     )]
     base_image_tag: String,
-    #[structopt(long, help = "Name of the EKS cluster")]
+    #[structopt(long, help = "Name of the EKS or GKE cluster")]
     cluster_name: String,
+    #[structopt(
+        long,
+        help = "Cloud provider of the cluster, whose node pools are resized",
+        possible_values = CloudProvider::VARIANTS,
+        default_value = "aws"
+    )]
+    cloud_provider: CloudProvider,
+    #[structopt(
+        long,
+        help = "Number of fullnodes to launch, spread evenly over the validators. One per validator if unset"
+    )]
+    num_fullnodes: Option<usize>,
     #[structopt(
         long,
         help = "Path to flattened directory containing compiled Move modules"
     )]
     move_modules_dir: Option<String>,
+    #[structopt(
+        long,
+        help = "How validators are scheduled across k8s nodes and availability zones",
+        possible_values = ValidatorPlacement::VARIANTS,
+        default_value = "default"
+    )]
+    validator_placement: ValidatorPlacement,
+    #[structopt(
+        long,
+        env = "FORGE_TRIAGE_BUCKET",
+        help = "S3 bucket to upload logs, events and configs to when a test fails"
+    )]
+    triage_bucket: Option<String>,
+    #[structopt(long, help = "Keep the cluster running if any test fails")]
+    keep_cluster_on_failure: bool,
+    #[structopt(long, help = "Keep the cluster running once tests finish")]
+    no_teardown: bool,
+    #[structopt(
+        long,
+        help = "Fund the tests through this faucet rather than the root key exported from Vault"
+    )]
+    faucet_url: Option<Url>,
+    #[structopt(
+        long,
+        help = "Coins to ask the faucet for, which it may cap",
+        default_value = "100000000000000"
+    )]
+    faucet_coins: u64,
+}
+
+/// Runs a suite against a long-lived cluster over and over, and alerts on Slack (`SLACK_URL`)
+/// when a run fails or a metric regresses from its rolling baseline. The cluster is never torn
+/// down.
+#[derive(StructOpt, Debug)]
+struct Daemon {
+    #[structopt(long, help = "The test suite to run")]
+    suite: String,
+    #[structopt(
+        long,
+        parse(try_from_str = parse_interval),
+        help = "Time between the starts of two runs, e.g. 30m or 6h",
+        default_value = "6h"
+    )]
+    interval: Duration,
+    #[structopt(
+        long,
+        help = "File the rolling baselines are kept in across runs",
+        default_value = "forge-baselines.json"
+    )]
+    baseline_file: PathBuf,
+    #[structopt(
+        long,
+        help = "Number of recent runs the baseline of a metric is computed from",
+        default_value = "10"
+    )]
+    baseline_window: usize,
+    #[structopt(
+        long,
+        help = "Number of recorded runs a metric needs before it's checked for regressions",
+        default_value = "3"
+    )]
+    baseline_min_runs: usize,
+    #[structopt(
+        long,
+        help = "How much worse than its baseline, in percent, a metric has to be to alert",
+        default_value = "10"
+    )]
+    regression_threshold_pct: f64,
+    #[structopt(long, help = "Stop after this many runs instead of running forever")]
+    max_runs: Option<usize>,
+    #[structopt(flatten)]
+    k8s: K8sSwarm,
+}
+
+/// Fails a run whose TPS or p99 latency is worse than the baseline of a previous commit, as
+/// stored with `baseline update`
+#[derive(StructOpt, Debug)]
+struct PerformanceGate {
+    #[structopt(
+        long,
+        help = "Baselines to gate the run on, a local json file or an s3:// prefix"
+    )]
+    baseline: Option<BaselineStore>,
+    #[structopt(
+        long,
+        help = "Commit whose baseline the run is compared to",
+        requires = "baseline"
+    )]
+    baseline_commit: Option<String>,
+    #[structopt(
+        long,
+        help = "How much lower than its baseline, in percent, the TPS of a test may be",
+        default_value = "10"
+    )]
+    max_tps_drop_pct: f64,
+    #[structopt(
+        long,
+        help = "How much higher than its baseline, in percent, the p99 latency of a test may be",
+        default_value = "20"
+    )]
+    max_p99_latency_rise_pct: f64,
+}
+
+#[derive(StructOpt, Debug)]
+enum BaselineCommand {
+    /// Stores the TPS and p99 latency of a run, from its json report, as the baseline of a commit
+    Update(UpdateBaseline),
+    /// Prints the baseline of a commit
+    Show(ShowBaseline),
+}
+
+#[derive(StructOpt, Debug)]
+struct UpdateBaseline {
+    #[structopt(
+        long,
+        help = "Baselines to update, a local json file or an s3:// prefix"
+    )]
+    baseline: BaselineStore,
+    #[structopt(long, help = "Commit the run tested")]
+    commit: String,
+    #[structopt(
+        long,
+        help = "Json report of the run, as written with --report-format json",
+        default_value = "forge_report.json"
+    )]
+    report_file: PathBuf,
+}
+
+#[derive(StructOpt, Debug)]
+struct ShowBaseline {
+    #[structopt(long, help = "Baselines to read, a local json file or an s3:// prefix")]
+    baseline: BaselineStore,
+    #[structopt(long, help = "Commit whose baseline is printed")]
+    commit: String,
 }
 
 #[derive(StructOpt, Debug)]
@@ -106,8 +331,15 @@ struct SetValidator {
 struct CleanUp {
     #[structopt(long, help = "If set, uses k8s service account to auth with AWS")]
     auth_with_k8s_env: bool,
-    #[structopt(long, help = "Name of the EKS cluster")]
+    #[structopt(long, help = "Name of the EKS or GKE cluster")]
     cluster_name: String,
+    #[structopt(
+        long,
+        help = "Cloud provider of the cluster, whose node pools are resized",
+        possible_values = CloudProvider::VARIANTS,
+        default_value = "aws"
+    )]
+    cloud_provider: CloudProvider,
 }
 
 #[derive(StructOpt, Debug)]
@@ -139,13 +371,32 @@ struct Resize {
         default_value = "testnet-internal"
     )]
     helm_repo: String,
-    #[structopt(long, help = "Name of the EKS cluster")]
+    #[structopt(long, help = "Name of the EKS or GKE cluster")]
     cluster_name: String,
+    #[structopt(
+        long,
+        help = "Cloud provider of the cluster, whose node pools are resized",
+        possible_values = CloudProvider::VARIANTS,
+        default_value = "aws"
+    )]
+    cloud_provider: CloudProvider,
+    #[structopt(
+        long,
+        help = "Number of fullnodes to launch, spread evenly over the validators. One per validator if unset"
+    )]
+    num_fullnodes: Option<usize>,
     #[structopt(
         long,
         help = "Path to flattened directory containing compiled Move modules"
     )]
     move_modules_dir: Option<String>,
+    #[structopt(
+        long,
+        help = "How validators are scheduled across k8s nodes and availability zones",
+        possible_values = ValidatorPlacement::VARIANTS,
+        default_value = "default"
+    )]
+    validator_placement: ValidatorPlacement,
 }
 
 fn main() -> Result<()> {
@@ -155,7 +406,8 @@ fn main() -> Result<()> {
         .thread_params(EmitThreadParams {
             wait_millis: args.wait_millis,
             wait_committed: !args.burst,
-        });
+        })
+        .transaction_mix(args.transaction_mix.clone());
     if let Some(workers_per_endpoint) = args.workers_per_ac {
         global_emit_job_request =
             global_emit_job_request.workers_per_endpoint(workers_per_endpoint);
@@ -166,31 +418,46 @@ fn main() -> Result<()> {
         // cmd input for test
         CliCommand::Test(test_cmd) => match test_cmd {
             TestCommand::LocalSwarm(..) => run_forge(
-                local_test_suite(),
+                with_seed(with_node_env(local_test_suite(), args.node_env), args.seed),
                 LocalFactory::from_workspace()?,
                 &args.options,
                 args.changelog,
+                &args.performance_gate,
                 global_emit_job_request,
             ),
+            TestCommand::Remote(remote) => {
+                let test_suite = match args.suite.as_ref() {
+                    Some(suite) => get_test_suite(suite, args.duration),
+                    None => local_test_suite(),
+                };
+                run_remote(
+                    &remote,
+                    with_seed(test_suite, args.seed),
+                    &args.options,
+                    &runtime,
+                )
+            }
             TestCommand::K8sSwarm(k8s) => {
                 let mut test_suite = k8s_test_suite();
                 if let Some(suite) = args.suite.as_ref() {
-                    test_suite = get_test_suite(suite);
+                    test_suite = get_test_suite(suite, args.duration);
                 }
-                if let Some(move_modules_dir) = k8s.move_modules_dir {
+                test_suite = with_seed(with_node_env(test_suite, args.node_env), args.seed);
+                if let Some(move_modules_dir) = k8s.move_modules_dir.clone() {
                     test_suite = test_suite.with_genesis_modules_path(move_modules_dir);
                 }
+                if args.emit_to_fullnodes {
+                    test_suite = test_suite.with_emit_to_fullnodes();
+                }
+                if let Some(num_fullnodes) = k8s.num_fullnodes {
+                    test_suite = test_suite.with_initial_fullnode_count(num_fullnodes);
+                }
                 run_forge(
                     test_suite,
-                    K8sFactory::new(
-                        k8s.cluster_name,
-                        k8s.helm_repo,
-                        k8s.image_tag,
-                        k8s.base_image_tag,
-                    )
-                    .unwrap(),
+                    k8s_factory(&k8s).unwrap(),
                     &args.options,
                     args.changelog,
+                    &args.performance_gate,
                     global_emit_job_request,
                 )
             }
@@ -204,30 +471,189 @@ fn main() -> Result<()> {
             ),
             OperatorCommand::CleanUp(cleanup) => {
                 uninstall_from_k8s_cluster()?;
-                runtime.block_on(set_eks_nodegroup_size(
+                runtime.block_on(cleanup.cloud_provider.set_node_pool_size(
                     cleanup.cluster_name,
                     0,
                     cleanup.auth_with_k8s_env,
                 ))
             }
             OperatorCommand::Resize(resize) => {
-                runtime.block_on(set_eks_nodegroup_size(
+                runtime.block_on(resize.cloud_provider.set_node_pool_size(
                     resize.cluster_name,
-                    resize.num_validators,
+                    node_pool_validator_count(resize.num_validators, resize.num_fullnodes),
                     resize.auth_with_k8s_env,
                 ))?;
                 uninstall_from_k8s_cluster()?;
                 runtime.block_on(clean_k8s_cluster(
                     resize.helm_repo,
                     resize.num_validators,
+                    resize.num_fullnodes,
+                    0,
                     resize.validator_image_tag,
                     resize.testnet_image_tag,
                     resize.require_validator_healthcheck,
                     resize.move_modules_dir,
+                    resize.validator_placement,
+                    &NodeEnvironment::default(),
+                    &TopologySpec::default(),
                 ))?;
                 Ok(())
             }
         },
+        CliCommand::Daemon(daemon) => run_daemon(
+            daemon,
+            &args.options,
+            args.node_env,
+            args.seed,
+            args.duration,
+            global_emit_job_request,
+        ),
+        CliCommand::Baseline(baseline_cmd) => match baseline_cmd {
+            BaselineCommand::Update(update) => {
+                let baseline =
+                    PerformanceBaseline::from_json_report(update.commit, &update.report_file)?;
+                if baseline.metrics.is_empty() {
+                    bail!("{:?} reports no TPS or latency", update.report_file);
+                }
+                update.baseline.save(&baseline)?;
+                println!(
+                    "Stored the baseline of {} in {}",
+                    baseline.commit, update.baseline
+                );
+                Ok(())
+            }
+            BaselineCommand::Show(show) => {
+                let baseline = show.baseline.load(&show.commit)?.ok_or_else(|| {
+                    format_err!("No baseline of {} in {}", show.commit, show.baseline)
+                })?;
+                println!("{}", baseline);
+                Ok(())
+            }
+        },
+    }
+}
+
+fn run_remote(
+    remote: &Remote,
+    tests: ForgeConfig<'_>,
+    options: &Options,
+    runtime: &Runtime,
+) -> Result<()> {
+    let root_key: Ed25519PrivateKey = bcs::from_bytes(&fs::read(&remote.root_key_path)?)
+        .map_err(|e| format_err!("Invalid root key {:?}: {}", remote.root_key_path, e))?;
+    let client = RestClient::new(remote.rest_api.clone());
+    let chain_id = runtime.block_on(client.get_chain_id())?;
+    let sequence_number = runtime
+        .block_on(client.get_account(aptos_root_address()))?
+        .into_inner()
+        .sequence_number;
+    let mut root_account = LocalAccount::new(aptos_root_address(), root_key, sequence_number);
+    run_remote_tests(
+        options,
+        &tests,
+        remote.rest_api.to_string(),
+        chain_id,
+        &mut root_account,
+    )?;
+    Ok(())
+}
+
+fn k8s_factory(k8s: &K8sSwarm) -> Result<K8sFactory> {
+    let factory = match &k8s.faucet_url {
+        Some(faucet_url) => K8sFactory::new_with_faucet(
+            k8s.cluster_name.clone(),
+            k8s.helm_repo.clone(),
+            k8s.image_tag.clone(),
+            k8s.base_image_tag.clone(),
+            faucet_url.clone(),
+            k8s.faucet_coins,
+        ),
+        None => K8sFactory::new(
+            k8s.cluster_name.clone(),
+            k8s.helm_repo.clone(),
+            k8s.image_tag.clone(),
+            k8s.base_image_tag.clone(),
+        )?,
+    };
+    Ok(factory
+        .with_validator_placement(k8s.validator_placement)
+        .with_cloud_provider(k8s.cloud_provider)
+        .with_triage_bucket(k8s.triage_bucket.clone())
+        .with_keep_cluster_on_failure(k8s.keep_cluster_on_failure)
+        .with_no_teardown(k8s.no_teardown))
+}
+
+fn run_daemon(
+    daemon: Daemon,
+    options: &Options,
+    node_env: Vec<(String, String)>,
+    seed: Option<u64>,
+    soak_duration: Duration,
+    global_job_request: EmitJobRequest,
+) -> Result<()> {
+    let mut baselines = RollingBaselines::load(&daemon.baseline_file)?;
+    let mut runs = 0;
+    loop {
+        let started = Instant::now();
+        runs += 1;
+        println!("Starting run {} of suite {}", runs, daemon.suite);
+
+        let mut test_suite = with_seed(
+            with_node_env(
+                get_test_suite(&daemon.suite, soak_duration),
+                node_env.clone(),
+            ),
+            seed,
+        );
+        if let Some(move_modules_dir) = daemon.k8s.move_modules_dir.clone() {
+            test_suite = test_suite.with_genesis_modules_path(move_modules_dir);
+        }
+        let result = k8s_factory(&daemon.k8s).and_then(|factory| {
+            let forge = Forge::new(
+                options,
+                test_suite,
+                factory.with_no_teardown(true),
+                global_job_request.clone(),
+            );
+            let result = forge.run();
+            forge.teardown(result.is_ok())?;
+            result
+        });
+
+        match result {
+            Ok(report) => {
+                let regressions = baselines.regressions(
+                    &report,
+                    daemon.regression_threshold_pct,
+                    daemon.baseline_min_runs,
+                );
+                if !regressions.is_empty() {
+                    let regressions: Vec<_> = regressions
+                        .iter()
+                        .map(|regression| format!(">\u{2022} {}", regression))
+                        .collect();
+                    send_alert(&format!(
+                        "*Forge suite {} regressed on {}*\n{}",
+                        daemon.suite,
+                        daemon.k8s.cluster_name,
+                        regressions.join("\n")
+                    ));
+                }
+                baselines.record(&report, daemon.baseline_window);
+                baselines.save(&daemon.baseline_file)?;
+            }
+            Err(e) => send_alert(&format!(
+                "*Forge suite {} failed on {}*\n{}",
+                daemon.suite, daemon.k8s.cluster_name, e
+            )),
+        }
+
+        if daemon.max_runs.map_or(false, |max_runs| runs >= max_runs) {
+            return Ok(());
+        }
+        let next_run_in = daemon.interval.saturating_sub(started.elapsed());
+        println!("Next run of suite {} in {:?}", daemon.suite, next_run_in);
+        thread::sleep(next_run_in);
     }
 }
 
@@ -236,6 +662,7 @@ pub fn run_forge<F: Factory>(
     factory: F,
     options: &Options,
     logs: Option<Vec<String>>,
+    performance_gate: &PerformanceGate,
     global_job_request: EmitJobRequest,
 ) -> Result<()> {
     let forge = Forge::new(options, tests, factory, global_job_request);
@@ -246,7 +673,9 @@ pub fn run_forge<F: Factory>(
         return Ok(());
     }
 
-    match forge.run() {
+    let result = forge.run();
+    let teardown_result = forge.teardown(result.is_ok());
+    match result {
         Ok(report) => {
             if let Some(mut changelog) = logs {
                 if changelog.len() != 2 {
@@ -257,15 +686,51 @@ pub fn run_forge<F: Factory>(
                 let from_commit = Some(changelog.remove(0));
                 send_changelog_message(&report.to_string(), &from_commit, &to_commit);
             }
-            Ok(())
+            teardown_result?;
+            check_performance(&report, performance_gate)
         }
         Err(e) => {
             eprintln!("Failed to run tests:\n{}", e);
+            if let Err(teardown_error) = teardown_result {
+                eprintln!("Failed to tear down swarm:\n{:?}", teardown_error);
+            }
             Err(e)
         }
     }
 }
 
+/// Fails if the report regressed from the baseline the run is gated on, if any
+fn check_performance(report: &TestReport, gate: &PerformanceGate) -> Result<()> {
+    let (store, commit) = match (&gate.baseline, &gate.baseline_commit) {
+        (Some(store), Some(commit)) => (store, commit),
+        (Some(_), None) => bail!("--baseline needs --baseline-commit"),
+        _ => return Ok(()),
+    };
+    let baseline = store
+        .load(commit)?
+        .ok_or_else(|| format_err!("No baseline of {} in {}", commit, store))?;
+    let regressions = baseline.regressions(
+        report,
+        RegressionThresholds {
+            max_tps_drop_pct: gate.max_tps_drop_pct,
+            max_p99_latency_rise_pct: gate.max_p99_latency_rise_pct,
+        },
+    );
+    if regressions.is_empty() {
+        println!("No performance regression from the baseline of {}", commit);
+        return Ok(());
+    }
+    let regressions: Vec<_> = regressions
+        .iter()
+        .map(|regression| regression.to_string())
+        .collect();
+    bail!(
+        "Performance regressed from the baseline of {}:\n{}",
+        commit,
+        regressions.join("\n")
+    )
+}
+
 pub fn send_changelog_message(perf_msg: &str, from_commit: &Option<String>, to_commit: &str) {
     println!(
         "Generating changelog from {:?} to {}",
@@ -284,6 +749,19 @@ pub fn send_changelog_message(perf_msg: &str, from_commit: &Option<String>, to_c
     }
 }
 
+fn send_alert(msg: &str) {
+    println!("{}", msg);
+    let slack_url: Option<Url> = env::var("SLACK_URL")
+        .map(|u| u.parse().expect("Failed to parse SLACK_URL"))
+        .ok();
+    if let Some(ref slack_url) = slack_url {
+        let slack_client = SlackClient::new();
+        if let Err(e) = slack_client.send_message(slack_url, msg) {
+            println!("Failed to send slack message: {}", e);
+        }
+    }
+}
+
 fn get_changelog(prev_commit: Option<&String>, upstream_commit: &str) -> String {
     let github_client = GitHub::new();
     let commits = github_client.get_commits("aptos-labs/aptos-core", upstream_commit);
@@ -314,11 +792,52 @@ fn get_changelog(prev_commit: Option<&String>, upstream_commit: &str) -> String
     }
 }
 
-fn get_test_suite(suite_name: &str) -> ForgeConfig<'static> {
+fn parse_env_var(s: &str) -> std::result::Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("Expected KEY=VALUE, got: {}", s)),
+    }
+}
+
+fn parse_interval(s: &str) -> std::result::Result<Duration, String> {
+    let unit_secs = match s.chars().last() {
+        Some('s') => 1,
+        Some('m') => 60,
+        Some('h') => 60 * 60,
+        Some('d') => 24 * 60 * 60,
+        _ => return Err(format!("Expected a number of s, m, h or d, got: {}", s)),
+    };
+    match s[..s.len() - 1].parse::<u64>() {
+        Ok(count) if count > 0 => Ok(Duration::from_secs(count * unit_secs)),
+        _ => Err(format!("Expected a number of s, m, h or d, got: {}", s)),
+    }
+}
+
+fn with_node_env(
+    test_suite: ForgeConfig<'static>,
+    node_env: Vec<(String, String)>,
+) -> ForgeConfig<'static> {
+    node_env
+        .into_iter()
+        .fold(test_suite, |test_suite, (key, value)| {
+            test_suite.with_env_var(key, value)
+        })
+}
+
+fn with_seed(test_suite: ForgeConfig<'static>, seed: Option<u64>) -> ForgeConfig<'static> {
+    match seed {
+        Some(seed) => test_suite.with_seed(seed),
+        None => test_suite,
+    }
+}
+
+fn get_test_suite(suite_name: &str, soak_duration: Duration) -> ForgeConfig<'static> {
     match suite_name {
         "land_blocking_compat" => land_blocking_test_compat_suite(),
         "land_blocking" => land_blocking_test_suite(),
         "pre_release" => pre_release_suite(),
+        "soak" => soak_suite(soak_duration),
+        "chaos_soak" => soak_suite(soak_duration).with_chaos_schedule(continuous_chaos_schedule()),
         single_test => single_test_suite(single_test),
     }
 }
@@ -346,11 +865,275 @@ fn single_test_suite(test_name: &str) -> ForgeConfig<'static> {
         "bench" => config.with_network_tests(&[&PerformanceBenchmark]),
         "state_sync" => config.with_network_tests(&[&StateSyncPerformance]),
         "compat" => config.with_network_tests(&[&SimpleValidatorUpgrade]),
+        "compat_soak" => mixed_version_soak_suite(),
+        "compat_matrix" => compatibility_matrix_suite(),
+        "compat_partition" => upgrade_under_partition_suite(),
         "config" => config.with_network_tests(&[&ReconfigurationTest]),
+        "zone_failure" => config.with_network_tests(&[&ZoneFailure]),
+        "partition_recovery" => config
+            .with_network_tests(&[&PartitionRecovery])
+            // catching up from peers is off by default
+            .with_node_config_fn(|config| config.consensus.max_catch_up_rounds = 100),
+        "slow_disk" => slow_disk_suite(),
+        "validator_set_growth" => validator_set_growth_suite(DEFAULT_VALIDATOR_SET_GROWTH_TARGET),
+        growth if growth.starts_with("validator_set_growth_") => {
+            let target = growth["validator_set_growth_".len()..]
+                .parse()
+                .unwrap_or_else(|_| panic!("Invalid validator set size in suite {}", growth));
+            validator_set_growth_suite(target)
+        }
+        "validator_key_rotation" => validator_key_rotation_suite(),
+        "validator_rotation" => validator_rotation_suite(),
+        "twin_validator" => twin_validator_suite(),
+        "clock_skew" => clock_skew_suite(),
+        "safety_rules_sidecar" => safety_rules_sidecar_suite(),
+        "gas_price_market" => gas_price_market_suite(),
+        "hot_account" => hot_account_suite(),
+        "random_restart" => random_restart_suite(),
+        "geo_distributed" => geo_distributed_suite(),
+        "quorum_loss_recovery" => quorum_loss_recovery_suite(),
+        "genesis_ceremony" => genesis_ceremony_suite(),
+        "epoch_change_30" => epoch_change_suite(30, Duration::from_secs(10)),
+        "epoch_change_100" => epoch_change_suite(100, Duration::from_secs(30)),
         _ => config.with_network_tests(&[&PerformanceBenchmark]),
     }
 }
 
+/// Size the validator set grows to in the `validator_set_growth` suite. The test is meant to
+/// grow the set from 10 to 100 validators, but the k8s clusters of forge fit 30 validators at
+/// most, the joining ones included, so the default falls short of it. `validator_set_growth_100`
+/// grows the set to 100 validators, which needs a cluster with room for them and a k8s backend
+/// allowing as many validators.
+const DEFAULT_VALIDATOR_SET_GROWTH_TARGET: usize = 30;
+
+/// Grows the validator set from 10 validators to `target_validators`, 5 at a time
+fn validator_set_growth_suite(target_validators: usize) -> ForgeConfig<'static> {
+    const INITIAL_VALIDATORS: usize = 10;
+    assert!(
+        target_validators >= INITIAL_VALIDATORS,
+        "The validator set starts with {} validators",
+        INITIAL_VALIDATORS
+    );
+    // The target depends on the suite, so the test is leaked to live as long as the suite
+    let validator_set_growth: &'static ValidatorSetGrowth =
+        Box::leak(Box::new(ValidatorSetGrowth {
+            target_validators,
+            batch_size: 5,
+            traffic_duration: Duration::from_secs(60),
+        }));
+    ForgeConfig::default()
+        .with_initial_validator_count(NonZeroUsize::new(INITIAL_VALIDATORS).unwrap())
+        .with_standby_validator_count(target_validators - INITIAL_VALIDATORS)
+        .with_network_tests(Box::leak(Box::new([
+            validator_set_growth as &dyn NetworkTest
+        ])))
+}
+
+fn validator_key_rotation_suite() -> ForgeConfig<'static> {
+    // Rotates the keys of a third of the validators, the most which can be restarted at once
+    const VALIDATOR_KEY_ROTATION: ValidatorKeyRotation = ValidatorKeyRotation {
+        num_rotated: 3,
+        target_tps: 100,
+        traffic_duration: Duration::from_secs(120),
+    };
+    ForgeConfig::default()
+        .with_initial_validator_count(NonZeroUsize::new(10).unwrap())
+        .with_network_tests(&[&VALIDATOR_KEY_ROTATION])
+}
+
+fn validator_rotation_suite() -> ForgeConfig<'static> {
+    const VALIDATOR_ROTATION: ValidatorRotation = ValidatorRotation {
+        observation_duration: Duration::from_secs(30),
+        traffic_duration: Duration::from_secs(120),
+    };
+    // The standby validator takes the place of the one leaving the validator set
+    ForgeConfig::default()
+        .with_initial_validator_count(NonZeroUsize::new(5).unwrap())
+        .with_standby_validator_count(1)
+        .with_network_tests(&[&VALIDATOR_ROTATION])
+}
+
+fn twin_validator_suite() -> ForgeConfig<'static> {
+    const TWIN_VALIDATOR: TwinValidator = TwinValidator {
+        duration: Duration::from_secs(120),
+    };
+    ForgeConfig::default()
+        .with_initial_validator_count(NonZeroUsize::new(4).unwrap())
+        .with_network_tests(&[&TWIN_VALIDATOR])
+}
+
+fn clock_skew_suite() -> ForgeConfig<'static> {
+    const CLOCK_SKEW: ClockSkew = ClockSkew {
+        skew_secs: 20,
+        traffic_duration: Duration::from_secs(60),
+    };
+    // Only the local backend can skew the clocks of validators, and the two skewed validators
+    // need 7 validators to be tolerated
+    ForgeConfig::default()
+        .with_initial_validator_count(NonZeroUsize::new(7).unwrap())
+        .with_network_tests(&[&CLOCK_SKEW])
+}
+
+fn safety_rules_sidecar_suite() -> ForgeConfig<'static> {
+    const SAFETY_RULES_SIDECAR: SafetyRulesSidecarCompat = SafetyRulesSidecarCompat {
+        duration: Duration::from_secs(120),
+    };
+    // Only the validator chart has the sidecar, the local backend ignores helm values and runs
+    // safety rules in-process everywhere, which the test reports as a failure
+    let sidecar =
+        serde_yaml::from_str("validator: { safetyRules: { sidecar: { enabled: true } } }")
+            .expect("Invalid helm values");
+    ForgeConfig::default()
+        .with_initial_validator_count(NonZeroUsize::new(4).unwrap())
+        .with_validator_helm_values(1, sidecar)
+        .with_network_tests(&[&SAFETY_RULES_SIDECAR])
+}
+
+fn hot_account_suite() -> ForgeConfig<'static> {
+    const HOT_ACCOUNT: HotAccountBenchmark = HotAccountBenchmark {
+        duration: Duration::from_secs(120),
+    };
+    ForgeConfig::default()
+        .with_initial_validator_count(NonZeroUsize::new(30).unwrap())
+        .with_network_tests(&[&HOT_ACCOUNT])
+}
+
+fn random_restart_suite() -> ForgeConfig<'static> {
+    const RANDOM_RESTART_FUZZER: RandomRestartFuzzer = RandomRestartFuzzer {
+        duration: Duration::from_secs(600),
+        max_down_time: Duration::from_secs(60),
+        max_restart_interval: Duration::from_secs(30),
+    };
+    ForgeConfig::default()
+        .with_initial_validator_count(NonZeroUsize::new(10).unwrap())
+        .with_network_tests(&[&RANDOM_RESTART_FUZZER])
+}
+
+fn quorum_loss_recovery_suite() -> ForgeConfig<'static> {
+    const QUORUM_LOSS_RECOVERY: QuorumLossRecovery = QuorumLossRecovery {
+        down_time: Duration::from_secs(60),
+    };
+    ForgeConfig::default()
+        .with_initial_validator_count(NonZeroUsize::new(10).unwrap())
+        .with_network_tests(&[&QUORUM_LOSS_RECOVERY])
+}
+
+fn genesis_ceremony_suite() -> ForgeConfig<'static> {
+    // The ceremony boots its own local swarm from the workspace, next to the one of the suite
+    const GENESIS_CEREMONY: GenesisCeremony = GenesisCeremony { num_validators: 4 };
+    ForgeConfig::default()
+        .with_initial_validator_count(NonZeroUsize::new(1).unwrap())
+        .with_admin_tests(&[&GENESIS_CEREMONY])
+}
+
+fn epoch_change_suite(num_validators: usize, max_latency: Duration) -> ForgeConfig<'static> {
+    // The threshold grows with the validator set, so the test is leaked to live as long as the
+    // suite
+    let epoch_change_latency: &'static EpochChangeLatency =
+        Box::leak(Box::new(EpochChangeLatency {
+            num_epochs: 10,
+            max_latency,
+        }));
+    ForgeConfig::default()
+        .with_initial_validator_count(NonZeroUsize::new(num_validators).unwrap())
+        .with_network_tests(Box::leak(Box::new([
+            epoch_change_latency as &dyn NetworkTest
+        ])))
+}
+
+fn geo_distributed_suite() -> ForgeConfig<'static> {
+    // Rough one way latencies between the US, Europe and Asia
+    let topology = TopologySpec::new()
+        .with_region("us")
+        .with_region("eu")
+        .with_region("ap")
+        .with_latency("us", "eu", Duration::from_millis(40))
+        .with_latency("us", "ap", Duration::from_millis(75))
+        .with_latency("eu", "ap", Duration::from_millis(110));
+    ForgeConfig::default()
+        .with_initial_validator_count(NonZeroUsize::new(30).unwrap())
+        .with_topology(topology)
+        .with_network_tests(&[&PerformanceBenchmark])
+}
+
+fn gas_price_market_suite() -> ForgeConfig<'static> {
+    const GAS_PRICE_MARKET: GasPriceMarket = GasPriceMarket {
+        duration: Duration::from_secs(180),
+        gas_prices: &[1, 5, 25],
+    };
+    ForgeConfig::default()
+        .with_initial_validator_count(NonZeroUsize::new(30).unwrap())
+        .with_network_tests(&[&GAS_PRICE_MARKET])
+}
+
+fn mixed_version_soak_suite() -> ForgeConfig<'static> {
+    const MIXED_VERSION_SOAK: MixedVersionSoak = MixedVersionSoak {
+        duration: Duration::from_secs(6 * 60 * 60),
+        check_interval: Duration::from_secs(10 * 60),
+    };
+    ForgeConfig::default()
+        .with_initial_validator_count(NonZeroUsize::new(30).unwrap())
+        .with_network_tests(&[&MIXED_VERSION_SOAK])
+}
+
+fn compatibility_matrix_suite() -> ForgeConfig<'static> {
+    const COMPATIBILITY_MATRIX: CompatibilityMatrix = CompatibilityMatrix {
+        traffic_duration: Duration::from_secs(30),
+    };
+    ForgeConfig::default()
+        .with_initial_validator_count(NonZeroUsize::new(30).unwrap())
+        .with_network_tests(&[&COMPATIBILITY_MATRIX])
+}
+
+fn upgrade_under_partition_suite() -> ForgeConfig<'static> {
+    const UPGRADE_UNDER_PARTITION: UpgradeUnderPartition = UpgradeUnderPartition {
+        traffic_duration: Duration::from_secs(10 * 60),
+    };
+    ForgeConfig::default()
+        .with_initial_validator_count(NonZeroUsize::new(10).unwrap())
+        .with_network_tests(&[&UPGRADE_UNDER_PARTITION])
+}
+
+fn slow_disk_suite() -> ForgeConfig<'static> {
+    const SLOW_DISK_VALIDATOR: SlowDiskValidator = SlowDiskValidator {
+        iops: 100,
+        latency: Duration::from_millis(50),
+        traffic_duration: Duration::from_secs(120),
+        min_tps_pct: 70,
+    };
+    ForgeConfig::default()
+        .with_initial_validator_count(NonZeroUsize::new(30).unwrap())
+        .with_network_tests(&[&SLOW_DISK_VALIDATOR])
+}
+
+fn soak_suite(duration: Duration) -> ForgeConfig<'static> {
+    // Unlike the other suites, the test depends on the arguments, so it's leaked to live as long
+    // as the suite
+    let soak_test: &'static SoakTest = Box::leak(Box::new(SoakTest {
+        duration,
+        checkpoint_interval: duration.min(Duration::from_secs(10 * 60)),
+        min_tps_pct: 70,
+        max_degraded_checkpoints: 3,
+    }));
+    ForgeConfig::default()
+        .with_initial_validator_count(NonZeroUsize::new(30).unwrap())
+        .with_network_tests(Box::leak(Box::new([soak_test as &dyn NetworkTest])))
+}
+
+/// Kills a fullnode every 5 minutes and cuts a minority of the validators off for 30s every 10
+/// minutes, which the network should ride through
+fn continuous_chaos_schedule() -> ChaosSchedule {
+    ChaosSchedule::new()
+        .every(Duration::from_secs(5 * 60), ScheduledChaos::KillFullNode)
+        .every(
+            Duration::from_secs(10 * 60),
+            ScheduledChaos::Partition {
+                percent: 20,
+                duration: Duration::from_secs(30),
+            },
+        )
+}
+
 fn land_blocking_test_suite() -> ForgeConfig<'static> {
     ForgeConfig::default()
         .with_initial_validator_count(NonZeroUsize::new(30).unwrap())
@@ -389,6 +1172,10 @@ impl Test for GetMetadata {
     fn name(&self) -> &'static str {
         "get_metadata"
     }
+
+    fn exclusive(&self) -> bool {
+        false
+    }
 }
 
 impl AdminTest for GetMetadata {
@@ -423,6 +1210,10 @@ impl Test for FundAccount {
     fn name(&self) -> &'static str {
         "fund_account"
     }
+
+    fn exclusive(&self) -> bool {
+        false
+    }
 }
 
 #[async_trait::async_trait]
@@ -447,6 +1238,10 @@ impl Test for TransferCoins {
     fn name(&self) -> &'static str {
         "transfer_coins"
     }
+
+    fn exclusive(&self) -> bool {
+        false
+    }
 }
 
 #[async_trait::async_trait]
@@ -0,0 +1,205 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Recording of the REST API traffic of the tests, through proxies in front of the APIs of the
+//! nodes, so that API regressions found by expensive network tests can be replayed against the
+//! API alone, see the replay tests of the `aptos-api` crate
+
+use crate::Result;
+use anyhow::anyhow;
+use aptos_rest_client::aptos_api_types::{ApiRecord, RecordedBody, X_REQUEST_ID};
+use hyper::{
+    body::to_bytes,
+    client::HttpConnector,
+    header::{CONTENT_TYPE, HOST},
+    service::{make_service_fn, service_fn},
+    Body, Client, Request, Response, Server, Uri,
+};
+use hyper_tls::HttpsConnector;
+use once_cell::sync::OnceCell;
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    fs::File,
+    io::{LineWriter, Write},
+    net::{Ipv4Addr, TcpListener},
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+use tokio::runtime::Runtime;
+use url::Url;
+
+static RECORDER: OnceCell<ApiRecorder> = OnceCell::new();
+
+struct ApiRecorder {
+    /// Runs the proxies, which outlive the runtimes of the tests
+    runtime: Runtime,
+    records: Arc<Records>,
+    /// The url of the proxy in front of each API
+    proxies: Mutex<HashMap<Url, Url>>,
+}
+
+struct Records {
+    client: Client<HttpsConnector<HttpConnector>>,
+    file: Mutex<LineWriter<File>>,
+    /// The tests running, which the requests made meanwhile are recorded for
+    running_tests: Mutex<Vec<String>>,
+}
+
+/// Records the REST API requests the tests make from now on, and the responses to them, to
+/// `path` as one json [`ApiRecord`] per line. Only the requests made with the clients forge hands
+/// out are recorded, e.g. by [`NodeExt::rest_client`](crate::NodeExt::rest_client), not those of
+/// the backends themselves, e.g. to check the health of the nodes.
+pub fn start_api_recording(path: &Path) -> Result<()> {
+    let recorder = ApiRecorder {
+        runtime: Runtime::new()?,
+        records: Arc::new(Records {
+            client: Client::builder().build(HttpsConnector::new()),
+            file: Mutex::new(LineWriter::new(File::create(path)?)),
+            running_tests: Mutex::new(vec![]),
+        }),
+        proxies: Mutex::new(HashMap::new()),
+    };
+    RECORDER
+        .set(recorder)
+        .map_err(|_| anyhow!("The REST API traffic is already recorded"))
+}
+
+/// The url to reach the API at `url` through, which is that of its proxy while the traffic is
+/// recorded
+pub(crate) fn endpoint(url: Url) -> Url {
+    let recorder = match RECORDER.get() {
+        Some(recorder) => recorder,
+        None => return url,
+    };
+    recorder.proxy(&url).unwrap_or_else(|e| {
+        println!("Failed to record the REST API traffic to {}: {:?}", url, e);
+        url
+    })
+}
+
+/// Marks the test as running until the returned guard is dropped, so that the requests made
+/// meanwhile are recorded for it
+pub(crate) fn record_test(name: &str) -> RecordedTest {
+    if let Some(recorder) = RECORDER.get() {
+        recorder
+            .records
+            .running_tests
+            .lock()
+            .unwrap()
+            .push(name.to_owned());
+    }
+    RecordedTest {
+        name: name.to_owned(),
+    }
+}
+
+pub(crate) struct RecordedTest {
+    name: String,
+}
+
+impl Drop for RecordedTest {
+    fn drop(&mut self) {
+        if let Some(recorder) = RECORDER.get() {
+            let mut running_tests = recorder.records.running_tests.lock().unwrap();
+            if let Some(i) = running_tests.iter().position(|test| *test == self.name) {
+                running_tests.remove(i);
+            }
+        }
+    }
+}
+
+impl ApiRecorder {
+    /// The proxy in front of the API at `upstream`, started on first use
+    fn proxy(&self, upstream: &Url) -> Result<Url> {
+        let mut proxies = self.proxies.lock().unwrap();
+        if let Some(proxy) = proxies.get(upstream) {
+            return Ok(proxy.clone());
+        }
+
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        listener.set_nonblocking(true)?;
+        // The clients resolve the paths of the API against the url, so it keeps that of the API
+        let mut proxy = Url::parse(&format!("http://{}", listener.local_addr()?))?;
+        proxy.set_path(upstream.path());
+
+        let records = self.records.clone();
+        let target = upstream.clone();
+        let make_service = make_service_fn(move |_| {
+            let records = records.clone();
+            let target = target.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    forward(records.clone(), target.clone(), request)
+                }))
+            }
+        });
+        let _guard = self.runtime.enter();
+        let server = Server::from_tcp(listener)?.serve(make_service);
+        self.runtime.spawn(server);
+
+        proxies.insert(upstream.clone(), proxy.clone());
+        Ok(proxy)
+    }
+}
+
+/// Forwards the request to the API at `upstream` and records it along with the response
+async fn forward(
+    records: Arc<Records>,
+    upstream: Url,
+    request: Request<Body>,
+) -> std::result::Result<Response<Body>, hyper::Error> {
+    let start = Instant::now();
+    let (parts, body) = request.into_parts();
+    let request_body = to_bytes(body).await?;
+
+    let mut target = upstream.clone();
+    target.set_path(parts.uri.path());
+    target.set_query(parts.uri.query());
+    let mut forwarded = Request::new(Body::from(request_body.clone()));
+    *forwarded.method_mut() = parts.method.clone();
+    *forwarded.uri_mut() = target.as_str().parse().expect("Invalid url");
+    *forwarded.headers_mut() = parts.headers.clone();
+    // The client sets the host of the API instead of that of the proxy
+    forwarded.headers_mut().remove(HOST);
+
+    let (response_parts, body) = records.client.request(forwarded).await?.into_parts();
+    let response_body = to_bytes(body).await?;
+    let header = |headers: &hyper::HeaderMap, name| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned)
+    };
+    records.record(ApiRecord {
+        tests: vec![],
+        endpoint: upstream.to_string(),
+        method: parts.method.to_string(),
+        path: relative_path(&upstream, &parts.uri),
+        content_type: header(&parts.headers, CONTENT_TYPE.as_str()),
+        request_body: RecordedBody::new(&request_body),
+        status: response_parts.status.as_u16(),
+        request_id: header(&response_parts.headers, X_REQUEST_ID),
+        response_body: RecordedBody::new(&response_body),
+        elapsed_ms: start.elapsed().as_millis() as u64,
+    });
+    Ok(Response::from_parts(response_parts, response_body.into()))
+}
+
+/// Path and query of the request, without the path of the API it goes to, if any
+fn relative_path(upstream: &Url, uri: &Uri) -> String {
+    let path = uri.path_and_query().map_or("/", |path| path.as_str());
+    let prefix = upstream.path().trim_end_matches('/');
+    path.strip_prefix(prefix).unwrap_or(path).to_owned()
+}
+
+impl Records {
+    fn record(&self, mut record: ApiRecord) {
+        record.tests = self.running_tests.lock().unwrap().clone();
+        let line = serde_json::to_string(&record).expect("Failed to serialize a record");
+        if let Err(e) = writeln!(self.file.lock().unwrap(), "{}", line) {
+            println!("Failed to record a REST API request: {}", e);
+        }
+    }
+}
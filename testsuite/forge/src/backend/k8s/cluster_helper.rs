@@ -1,16 +1,24 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{get_validators, k8s_retry_strategy, nodes_healthcheck, Result};
+use crate::{
+    get_validators, k8s_retry_strategy, nodes_healthcheck, unhealthy_node_names, LivenessCriterion,
+    NodeConfigFn, NodeEnvironment, NodeFeature, Result, TopologySpec,
+};
 use ::aptos_logger::*;
 use anyhow::{bail, format_err};
+use aptos_config::config::{Identity, NodeConfig};
+use aptos_sdk::{
+    crypto::{x25519, Uniform},
+    types::account_address::from_identity_public_key,
+};
 use futures::future::try_join_all;
 use hyper::{Client, Uri};
 use hyper_proxy::{Intercept, Proxy, ProxyConnector};
 use hyper_tls::HttpsConnector;
 use k8s_openapi::api::batch::v1::Job;
 use kube::{api::Api, client::Client as K8sClient, Config};
-use rand::Rng;
+use rand::{rngs::OsRng, Rng};
 use rayon::prelude::*;
 use regex::Regex;
 use rusoto_core::Region;
@@ -19,20 +27,22 @@ use rusoto_eks::{
     DescribeUpdateRequest, Eks, EksClient, NodegroupScalingConfig, UpdateNodegroupConfigRequest,
 };
 use rusoto_sts::WebIdentityProvider;
-use serde_json::Value;
+use serde_json::{json, Value};
 use std::{
     cmp,
+    collections::HashMap,
     convert::TryFrom,
     env,
     fs::File,
     io::Write,
     process::{Command, Stdio},
-    str,
+    str::{self, FromStr},
 };
 use tempfile::TempDir;
 
 const HELM_BIN: &str = "helm";
 const KUBECTL_BIN: &str = "kubectl";
+const GCLOUD_BIN: &str = "gcloud";
 const MAX_NUM_VALIDATORS: usize = 30;
 const HEALTH_CHECK_URL: &str = "http://127.0.0.1:8001";
 const VALIDATOR_SCALING_FACTOR: i64 = 3;
@@ -40,6 +50,445 @@ const UTILITIES_SCALING_FACTOR: i64 = 3;
 
 const GENESIS_MODULES_DIR: &str = "/aptos-framework/move/modules";
 
+const VALIDATOR_POD_SELECTOR: &str = "app.kubernetes.io/name=validator";
+const ZONE_LABEL: &str = "topology.kubernetes.io/zone";
+const HOSTNAME_LABEL: &str = "kubernetes.io/hostname";
+/// How long `fail_zone` waits for each validator pod of the zone to terminate
+const ZONE_POD_DELETION_TIMEOUT_SECS: u64 = 120;
+/// Label of the validator pods holding their simulated region, see `TopologySpec`
+pub const REGION_LABEL: &str = "forge-region";
+/// Value of the validator releases recording the helm values of the run, which the chart ignores
+const FORGE_HELM_VALUES_KEY: &str = "forgeHelmValues";
+
+/// Controls how validator pods are scheduled across the failure domains of the cluster
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValidatorPlacement {
+    /// Leave scheduling decisions to the chart defaults
+    Default,
+    /// Never schedule two validators onto the same k8s node
+    HostAntiAffinity,
+    /// Spread validators evenly across availability zones, with at most one validator per node
+    ZoneSpread,
+}
+
+impl Default for ValidatorPlacement {
+    fn default() -> Self {
+        ValidatorPlacement::Default
+    }
+}
+
+impl FromStr for ValidatorPlacement {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "default" => Ok(ValidatorPlacement::Default),
+            "host-anti-affinity" => Ok(ValidatorPlacement::HostAntiAffinity),
+            "zone-spread" => Ok(ValidatorPlacement::ZoneSpread),
+            _ => bail!("Unknown validator placement: {}", s),
+        }
+    }
+}
+
+impl ValidatorPlacement {
+    pub const VARIANTS: &'static [&'static str] = &["default", "host-anti-affinity", "zone-spread"];
+
+    /// Helm values for the aptos-validator chart which enforce this placement
+    fn helm_values(&self) -> Option<Value> {
+        let label_selector = json!({ "matchLabels": { "app.kubernetes.io/name": "validator" } });
+        let anti_affinity = json!({
+            "podAntiAffinity": {
+                "requiredDuringSchedulingIgnoredDuringExecution": [{
+                    "topologyKey": HOSTNAME_LABEL,
+                    "labelSelector": label_selector,
+                }]
+            }
+        });
+        match self {
+            ValidatorPlacement::Default => None,
+            ValidatorPlacement::HostAntiAffinity => {
+                Some(json!({ "validator": { "affinity": anti_affinity } }))
+            }
+            ValidatorPlacement::ZoneSpread => Some(json!({
+                "validator": {
+                    "affinity": anti_affinity,
+                    "topologySpreadConstraints": [{
+                        "maxSkew": 1,
+                        "topologyKey": ZONE_LABEL,
+                        "whenUnsatisfiable": "DoNotSchedule",
+                        "labelSelector": label_selector,
+                    }]
+                }
+            })),
+        }
+    }
+}
+
+/// Cloud provider hosting the k8s cluster, whose `validators` and `utilities` node pools are
+/// resized to fit the validators
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CloudProvider {
+    /// An EKS cluster in us-west-2, with `validators` and `utilities` nodegroups
+    Aws,
+    /// A zonal GKE cluster, with `validators` and `utilities` node pools. The project and zone of
+    /// the cluster come from the gcloud configuration, e.g. `CLOUDSDK_CORE_PROJECT` and
+    /// `CLOUDSDK_COMPUTE_ZONE`.
+    Gcp,
+}
+
+impl Default for CloudProvider {
+    fn default() -> Self {
+        CloudProvider::Aws
+    }
+}
+
+impl FromStr for CloudProvider {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "aws" => Ok(CloudProvider::Aws),
+            "gcp" => Ok(CloudProvider::Gcp),
+            _ => bail!("Unknown cloud provider: {}", s),
+        }
+    }
+}
+
+impl CloudProvider {
+    pub const VARIANTS: &'static [&'static str] = &["aws", "gcp"];
+
+    /// Resizes the node pools of the cluster to fit `num_validators`, and waits for the nodes to
+    /// be ready. `auth_with_k8s_env` authenticates with the k8s service account on AWS, while
+    /// gcloud always uses its configured credentials.
+    pub async fn set_node_pool_size(
+        &self,
+        cluster_name: String,
+        num_validators: usize,
+        auth_with_k8s_env: bool,
+    ) -> Result<()> {
+        match self {
+            CloudProvider::Aws => {
+                set_eks_nodegroup_size(cluster_name, num_validators, auth_with_k8s_env).await
+            }
+            CloudProvider::Gcp => set_gke_node_pool_size(&cluster_name, num_validators).await,
+        }
+    }
+
+    /// Command pointing kubectl at the cluster
+    pub fn kubeconfig_command(&self, cluster_name: &str) -> String {
+        match self {
+            CloudProvider::Aws => format!(
+                "aws eks --region us-west-2 update-kubeconfig --name {}",
+                cluster_name
+            ),
+            CloudProvider::Gcp => {
+                format!("gcloud container clusters get-credentials {}", cluster_name)
+            }
+        }
+    }
+}
+
+/// Name of the `index`th fullnode group of a validator release. The first one keeps the name of
+/// the only group of the chart defaults.
+pub(crate) fn fullnode_group_name(index: usize) -> String {
+    if index == 0 {
+        "fullnode".to_string()
+    } else {
+        format!("fullnode{}", index)
+    }
+}
+
+/// A network identity of its own for a fullnode group beyond the first, as the chart otherwise
+/// gives every fullnode of a validator the identity of its owner. The peer id is derived from the
+/// key, as for public fullnodes.
+fn fullnode_group_identity() -> Identity {
+    let key = x25519::PrivateKey::generate(&mut OsRng);
+    let peer_id = from_identity_public_key(key.public_key());
+    Identity::from_config(key, peer_id)
+}
+
+/// Number of fullnodes of the validator `validator_index` when `num_fullnodes` are spread evenly
+/// over `num_validators`, the first validators taking one more. Every validator has one fullnode
+/// when the number isn't set, as in the chart defaults.
+fn validator_fullnode_count(
+    num_fullnodes: Option<usize>,
+    num_validators: usize,
+    validator_index: usize,
+) -> usize {
+    match num_fullnodes {
+        Some(num_fullnodes) => {
+            num_fullnodes / num_validators
+                + usize::from(validator_index < num_fullnodes % num_validators)
+        }
+        None => 1,
+    }
+}
+
+/// Number of validators to size the node pools for, so that they fit the fullnodes beyond one per
+/// validator too. The pools are sized at `VALIDATOR_SCALING_FACTOR` nodes per validator, which
+/// fits its first fullnode, so the other fullnodes are counted as validators in groups of that
+/// many.
+pub fn node_pool_validator_count(num_validators: usize, num_fullnodes: Option<usize>) -> usize {
+    let extra_fullnodes = num_fullnodes.map_or(0, |n| n.saturating_sub(num_validators));
+    let factor = VALIDATOR_SCALING_FACTOR as usize;
+    num_validators + (extra_fullnodes + factor - 1) / factor
+}
+
+/// Helm values for the aptos-validator chart which apply the environment to the validator
+/// `validator_index` and its `num_fullnodes` fullnodes, each in a group of its own, and each group
+/// but the first with an identity of its own on the public network. The extra environment
+/// variables, config overrides and fullnode groups are always set, so that those of a previous run
+/// are cleared. The config overrides are left empty, see `release_node_environment_values`.
+fn node_environment_helm_values(
+    node_environment: &NodeEnvironment,
+    validator_index: usize,
+    num_fullnodes: usize,
+) -> Result<Value> {
+    let extra_env = node_environment
+        .env_vars
+        .iter()
+        .map(|(name, value)| json!({ "name": name, "value": value }))
+        .collect::<Vec<_>>();
+    let mut config = json!({});
+    for feature in &node_environment.features {
+        match feature {
+            NodeFeature::ParallelExecution(concurrency_level) => {
+                config["concurrency_level"] = json!(concurrency_level);
+            }
+            NodeFeature::StateSyncV2 => {
+                config["enable_state_sync_v2"] = json!(true);
+            }
+        }
+    }
+    config["overrides"] = json!("");
+    let groups = (0..num_fullnodes)
+        .map(|i| -> Result<Value> {
+            let mut group =
+                json!({ "name": fullnode_group_name(i), "replicas": 1, "enableRestApi": true });
+            if i > 0 {
+                group["identity"] = serde_json::to_value(fullnode_group_identity())?;
+            }
+            Ok(group)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let mut values = json!({
+        "validator": { "extraEnv": extra_env, "config": config },
+        "fullnode": {
+            "groups": groups,
+            "extraEnv": extra_env,
+            "config": { "overrides": "" },
+        },
+    });
+    let mut helm_values = match &node_environment.helm_values {
+        Some(helm_values) => serde_json::to_value(helm_values)?,
+        None => json!({}),
+    };
+    if let Some(validator_helm_values) =
+        node_environment.validator_helm_values.get(&validator_index)
+    {
+        merge_values(
+            &mut helm_values,
+            &serde_json::to_value(validator_helm_values)?,
+        );
+    }
+    if helm_values != json!({}) {
+        merge_values(&mut values, &helm_values);
+        values[FORGE_HELM_VALUES_KEY] = helm_values;
+    }
+    Ok(values)
+}
+
+/// Merges `overrides` over `values` the way helm merges values files: maps are merged key by key,
+/// anything else is replaced
+fn merge_values(values: &mut Value, overrides: &Value) {
+    match (values, overrides) {
+        (Value::Object(values), Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                match values.get_mut(key) {
+                    Some(existing) => merge_values(existing, value),
+                    None => {
+                        values.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (values, overrides) => *values = overrides.clone(),
+    }
+}
+
+/// Removes from the values of a release the helm values a previous run set, recorded under
+/// `FORGE_HELM_VALUES_KEY`, so that the chart defaults apply again unless this run sets them too
+fn clear_previous_helm_values(config: &mut Value) {
+    if let Some(previous) = config
+        .as_object_mut()
+        .and_then(|config| config.remove(FORGE_HELM_VALUES_KEY))
+    {
+        remove_values(config, &previous);
+    }
+}
+
+fn remove_values(values: &mut Value, removed: &Value) {
+    if let (Value::Object(values), Value::Object(removed)) = (values, removed) {
+        for (key, removed) in removed {
+            match (values.get_mut(key), removed) {
+                (Some(value @ Value::Object(_)), Value::Object(_)) => remove_values(value, removed),
+                _ => {
+                    values.remove(key);
+                }
+            }
+        }
+    }
+}
+
+/// Helm values of the validator release `release_name` for the node environment, see
+/// `node_environment_helm_values`, with the changes the node config fn makes to the node configs
+/// the chart renders for the release as config overrides. The configs are rendered from the
+/// values of the release, `release_values`, with the environment merged over them.
+fn release_node_environment_values(
+    release_name: &str,
+    helm_repo: &str,
+    release_values: &Value,
+    node_environment: &NodeEnvironment,
+    validator_index: usize,
+    num_fullnodes: usize,
+) -> Result<Value> {
+    let mut values =
+        node_environment_helm_values(node_environment, validator_index, num_fullnodes)?;
+    if let Some(node_config_fn) = &node_environment.node_config_fn {
+        let mut rendered_values = release_values.clone();
+        merge_values(&mut rendered_values, &values);
+        let configs = render_node_configs(release_name, helm_repo, &rendered_values)?;
+        set_node_config_overrides(&mut values, node_config_fn, &configs)?;
+    }
+    Ok(values)
+}
+
+/// Renders the node configs of the aptos-validator chart for the release `release_name` with
+/// `values`, by their key in the config map of the release
+fn render_node_configs(
+    release_name: &str,
+    helm_repo: &str,
+    values: &Value,
+) -> Result<HashMap<String, NodeConfig>> {
+    let tmp_dir = TempDir::new()?;
+    let file_path = tmp_dir.path().join(format!("{}_values.json", release_name));
+    File::create(&file_path)?.write_all(&values.to_string().into_bytes())?;
+    let template_args = [
+        "template",
+        release_name,
+        &format!("{}/aptos-validator", helm_repo),
+        "-f",
+        &file_path.display().to_string(),
+        "--show-only",
+        "templates/configmaps.yaml",
+    ];
+    let output = Command::new(HELM_BIN).args(&template_args).output()?;
+    if !output.status.success() {
+        bail!(
+            "Could not render the node configs of {}: {}",
+            release_name,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    parse_node_configs(str::from_utf8(&output.stdout)?)
+}
+
+/// The node configs held by the config maps of rendered manifests, by key
+fn parse_node_configs(manifests: &str) -> Result<HashMap<String, NodeConfig>> {
+    let mut configs = HashMap::new();
+    for manifest in manifests.split("\n---") {
+        let is_empty = manifest.lines().all(|line| {
+            let line = line.trim();
+            line.is_empty() || line.starts_with('#') || line == "---"
+        });
+        if is_empty {
+            continue;
+        }
+        let manifest: serde_yaml::Value = serde_yaml::from_str(manifest)?;
+        if let Some(data) = manifest["data"].as_mapping() {
+            for (key, config) in data {
+                if let (Some(key), Some(config)) = (key.as_str(), config.as_str()) {
+                    if key.ends_with(".yaml") {
+                        let config = serde_yaml::from_str(config)
+                            .map_err(|e| format_err!("Invalid node config {}: {}", key, e))?;
+                        configs.insert(key.to_string(), config);
+                    }
+                }
+            }
+        }
+    }
+    Ok(configs)
+}
+
+/// Sets the config overrides of the validator and of the fullnodes in the values to the changes
+/// the node config fn makes to their rendered node configs. The fullnode groups with an identity
+/// of their own have a node config of their own, and so overrides of their own.
+fn set_node_config_overrides(
+    values: &mut Value,
+    node_config_fn: &NodeConfigFn,
+    configs: &HashMap<String, NodeConfig>,
+) -> Result<()> {
+    let overrides = |key: &str| -> Result<Value> {
+        let config = configs
+            .get(key)
+            .ok_or_else(|| format_err!("The chart renders no node config {}", key))?;
+        Ok(json!(node_config_overrides(
+            node_config_fn,
+            config.clone()
+        )?))
+    };
+    values["validator"]["config"]["overrides"] = overrides("validator.yaml")?;
+    values["fullnode"]["config"]["overrides"] = overrides("fullnode.yaml")?;
+    if let Some(groups) = values["fullnode"]["groups"].as_array_mut() {
+        for group in groups {
+            if group.get("identity").is_some() {
+                let key = format!(
+                    "fullnode-{}.yaml",
+                    group["name"].as_str().unwrap_or_default()
+                );
+                group["overrides"] = overrides(&key)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The fields of the rendered node config `base` the node config fn changes, as the JSON the chart
+/// merges over it. It's passed as a string, since helm merges maps with those of the values of the
+/// previous run. The chart merges maps key by key but replaces arrays, so a changed array is set
+/// whole, with the elements of `base` the fn kept.
+fn node_config_overrides(node_config_fn: &NodeConfigFn, base: NodeConfig) -> Result<String> {
+    let before = serde_json::to_value(&base)?;
+    let mut config = base;
+    node_config_fn.apply(&mut config);
+    let after = serde_json::to_value(&config)?;
+    Ok(changed_fields(&before, &after)
+        .map(|overrides| overrides.to_string())
+        .unwrap_or_default())
+}
+
+/// The fields of `after` which differ from those of `before`, none if they're equal
+fn changed_fields(before: &Value, after: &Value) -> Option<Value> {
+    match (before, after) {
+        (Value::Object(before), Value::Object(after)) => {
+            let changed = after
+                .iter()
+                .filter_map(|(key, value)| match before.get(key) {
+                    Some(before) => changed_fields(before, value).map(|value| (key.clone(), value)),
+                    None => Some((key.clone(), value.clone())),
+                })
+                .collect::<serde_json::Map<_, _>>();
+            if changed.is_empty() {
+                None
+            } else {
+                Some(Value::Object(changed))
+            }
+        }
+        _ if before == after => None,
+        _ => Some(after.clone()),
+    }
+}
+
 async fn wait_genesis_job(kube_client: &K8sClient, era: &str) -> Result<()> {
     aptos_retrier::retry_async(k8s_retry_strategy(), || {
         let jobs: Api<Job> = Api::namespaced(kube_client.clone(), "default");
@@ -209,6 +658,91 @@ fn get_helm_values(helm_release_name: &str) -> Result<Value> {
     Ok(v["config"].take())
 }
 
+/// Installs the release of the standby validator `val{node_id}` on the chain of `era`, whose
+/// identity the genesis job provisioned, see `genesis.numStandbyValidators` of the testnet chart.
+/// The release is upgraded from the history terraform left it with, which only the validators
+/// terraform provisions have, with the node environment of the run and a single fullnode.
+pub(crate) fn install_standby_validator(
+    node_id: usize,
+    era: &str,
+    image_tag: &str,
+    helm_repo: &str,
+    node_environment: &NodeEnvironment,
+) -> Result<()> {
+    let release_name = format!("val{}", node_id);
+    let v: Value = get_helm_status(&release_name).map_err(|e| {
+        format_err!(
+            "No history for release {}, is it one of the validators provisioned by terraform? {}",
+            release_name,
+            e
+        )
+    })?;
+    let version = v["version"]
+        .as_i64()
+        .ok_or_else(|| format_err!("No history for release {}", release_name))?
+        as usize;
+    let release_values = standby_validator_values(v["config"].clone(), era, image_tag, &json!({}));
+    let environment_values = release_node_environment_values(
+        &release_name,
+        helm_repo,
+        &release_values,
+        node_environment,
+        node_id,
+        1,
+    )?;
+    let values = standby_validator_values(v["config"].clone(), era, image_tag, &environment_values);
+
+    let tmp_dir = TempDir::new()?;
+    let file_path = tmp_dir.path().join(format!("{}_status.json", release_name));
+    let mut file = File::create(&file_path)?;
+    file.write_all(&values.to_string().into_bytes())?;
+    helm_release_patch(&release_name, version)?;
+    upgrade_validator(
+        &release_name,
+        helm_repo,
+        &[
+            "-f",
+            &file_path.display().to_string(),
+            "--install",
+            "--history-max",
+            "2",
+        ],
+    )
+}
+
+/// The values of the release of a standby validator, from those of its previous release: the
+/// values a previous run set are cleared and the environment of this run is merged over them.
+/// The standby validator isn't part of the topology, so it's taken out of its previous region.
+fn standby_validator_values(
+    mut config: Value,
+    era: &str,
+    image_tag: &str,
+    environment_values: &Value,
+) -> Value {
+    clear_previous_helm_values(&mut config);
+    if let Some(pod_labels) = config
+        .pointer_mut("/validator/podLabels")
+        .and_then(Value::as_object_mut)
+    {
+        pod_labels.remove(REGION_LABEL);
+    }
+    merge_values(&mut config, environment_values);
+    merge_values(
+        &mut config,
+        &json!({ "chain": { "era": era }, "imageTag": image_tag }),
+    );
+    config
+}
+
+/// The cluster IP of the k8s service `name`
+pub(crate) fn service_cluster_ip(name: &str) -> Result<String> {
+    let service = kubectl_json(&["get", "service", name])?;
+    service["spec"]["clusterIP"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| format_err!("Service {} has no cluster IP", name))
+}
+
 pub fn uninstall_from_k8s_cluster() -> Result<()> {
     // helm uninstall validators while keeping history for later
     (0..MAX_NUM_VALIDATORS).into_par_iter().for_each(|i| {
@@ -225,22 +759,82 @@ pub fn uninstall_from_k8s_cluster() -> Result<()> {
 pub async fn clean_k8s_cluster(
     helm_repo: String,
     base_num_validators: usize,
+    num_fullnodes: Option<usize>,
+    num_standby_validators: usize,
     base_validator_image_tag: String,
     base_genesis_image_tag: String,
     require_validator_healthcheck: bool,
     genesis_modules_path: Option<String>,
+    placement: ValidatorPlacement,
+    node_environment: &NodeEnvironment,
+    topology: &TopologySpec,
 ) -> Result<String> {
-    assert!(base_num_validators <= MAX_NUM_VALIDATORS);
+    assert!(base_num_validators + num_standby_validators <= MAX_NUM_VALIDATORS);
+
+    // chaos left behind by a previous run selects validator pods by name, so it would apply to
+    // the new pods as well
+    for kind in ["networkchaos", "iochaos"] {
+        if let Err(e) = kubectl(&["delete", kind, "--all"]) {
+            println!("Could not remove leftover {}: {}", kind, e);
+        }
+    }
 
     let new_era = get_new_era().unwrap();
 
     let tmp_dir = TempDir::new().expect("Could not create temp dir");
 
+    // write out the placement values so they can be layered on top of each release's values
+    let placement_file_path = placement.helm_values().map(|values| {
+        let file_path = tmp_dir.path().join("validator_placement.json");
+        println!(
+            "Wrote {:?} placement values to: {:?}",
+            placement, &file_path
+        );
+        let mut file = File::create(&file_path).expect("Could not create file in temp dir");
+        file.write_all(&values.to_string().into_bytes())
+            .expect("Could not write to file");
+        file_path.display().to_string()
+    });
+
+    // likewise for the environment variables and features of the nodes, which may differ
+    // between validators
+    println!("Node environment: {:?}", node_environment);
+    let node_environment_file_paths = (0..base_num_validators)
+        .map(|i| {
+            let file_path = tmp_dir
+                .path()
+                .join(format!("val{}_node_environment.json", i));
+            let mut file = File::create(&file_path).expect("Could not create file in temp dir");
+            let num_validator_fullnodes =
+                validator_fullnode_count(num_fullnodes, base_num_validators, i);
+            let release_name = format!("val{}", i);
+            let mut status = get_helm_status(&release_name)?;
+            let mut release_values = status["config"].take();
+            clear_previous_helm_values(&mut release_values);
+            let values = release_node_environment_values(
+                &release_name,
+                &helm_repo,
+                &release_values,
+                node_environment,
+                i,
+                num_validator_fullnodes,
+            )?;
+            file.write_all(&values.to_string().into_bytes())
+                .expect("Could not write to file");
+            Ok(file_path.display().to_string())
+        })
+        .collect::<Result<Vec<_>>>()?;
+    println!(
+        "Wrote node environment values to: {:?}",
+        node_environment_file_paths
+    );
+
     // prepare for scale up. get the helm values to upgrade later
     (0..base_num_validators).into_par_iter().for_each(|i| {
         let v: Value = get_helm_status(&format!("val{}", i)).unwrap();
         let version = v["version"].as_i64().expect("not a i64") as usize;
-        let config = &v["config"];
+        let mut config = v["config"].clone();
+        clear_previous_helm_values(&mut config);
 
         let era: &str = &era_to_string(&v["config"]["chain"]["era"]).unwrap();
         assert!(
@@ -268,17 +862,30 @@ pub async fn clean_k8s_cluster(
             .join(format!("val{}_status.json", i))
             .display()
             .to_string();
-        let validator_upgrade_options = [
-            "-f",
-            &file_path,
+        let era_option = format!("chain.era={}", &new_era);
+        let image_tag_option = format!("imageTag={}", &base_validator_image_tag);
+        // the label moves with the pod template, so the region survives pod restarts
+        let region_option = topology
+            .region_of(i)
+            .map(|region| format!("validator.podLabels.{}={}", REGION_LABEL, region));
+        let mut validator_upgrade_options = vec!["-f", &file_path];
+        if let Some(placement_file_path) = &placement_file_path {
+            validator_upgrade_options.extend_from_slice(&["-f", placement_file_path.as_str()]);
+        }
+        validator_upgrade_options
+            .extend_from_slice(&["-f", node_environment_file_paths[i].as_str()]);
+        validator_upgrade_options.extend_from_slice(&[
             "--install",
             "--history-max",
             "2",
             "--set",
-            &format!("chain.era={}", &new_era),
+            &era_option,
             "--set",
-            &format!("imageTag={}", &base_validator_image_tag),
-        ];
+            &image_tag_option,
+        ]);
+        if let Some(region_option) = &region_option {
+            validator_upgrade_options.extend_from_slice(&["--set", region_option.as_str()]);
+        }
         upgrade_validator(&format!("val{}", i), &helm_repo, &validator_upgrade_options).unwrap();
     });
     println!("All validators upgraded");
@@ -320,6 +927,8 @@ pub async fn clean_k8s_cluster(
         "--set",
         &format!("genesis.numValidators={}", base_num_validators),
         "--set",
+        &format!("genesis.numStandbyValidators={}", num_standby_validators),
+        "--set",
         &format!("imageTag={}", &base_genesis_image_tag),
         "--set",
         "monitoring.prometheus.useHttps=false",
@@ -339,7 +948,7 @@ pub async fn clean_k8s_cluster(
     let all_nodes = vals.values().collect();
     // healthcheck on each of the validators wait until they all healthy
     let unhealthy_nodes = if require_validator_healthcheck {
-        nodes_healthcheck(all_nodes).await.unwrap()
+        unhealthy_node_names(&nodes_healthcheck(all_nodes, LivenessCriterion::Reachable).await)
     } else {
         vec![]
     };
@@ -388,6 +997,59 @@ pub async fn create_k8s_client() -> K8sClient {
     K8sClient::try_from(config).unwrap()
 }
 
+/// Size of a node pool
+struct NodePoolSize {
+    desired: i64,
+    min: i64,
+    max: i64,
+}
+
+/// Sizes of the node pools fitting a number of validators
+struct NodePoolSizes {
+    validators: NodePoolSize,
+    utilities: NodePoolSize,
+    /// Number of nodes which have to be ready before the validators can be scheduled
+    min_ready_nodes: i64,
+}
+
+impl NodePoolSizes {
+    fn new(num_validators: usize) -> Self {
+        let max_surge = 2; // multiplier for max size
+        let num_validators: i64 = num_validators as i64;
+        let idle_utilities_size = 10; // keep extra utilities nodes around for forge pods and monitoring
+        let buffer_node = if num_validators != 0 {
+            cmp::max(5, num_validators / 5)
+        } else {
+            0
+        };
+        Self {
+            validators: NodePoolSize {
+                desired: cmp::max(num_validators * VALIDATOR_SCALING_FACTOR + buffer_node, 1),
+                max: cmp::max(
+                    (num_validators * VALIDATOR_SCALING_FACTOR + 1) * max_surge,
+                    1,
+                ),
+                min: cmp::max(num_validators * VALIDATOR_SCALING_FACTOR, 1),
+            },
+            utilities: NodePoolSize {
+                desired: cmp::max(
+                    num_validators * UTILITIES_SCALING_FACTOR + buffer_node,
+                    idle_utilities_size,
+                ),
+                max: cmp::max(
+                    num_validators * UTILITIES_SCALING_FACTOR * max_surge,
+                    idle_utilities_size,
+                ),
+                min: cmp::max(
+                    num_validators * UTILITIES_SCALING_FACTOR,
+                    idle_utilities_size,
+                ),
+            },
+            min_ready_nodes: num_validators * (VALIDATOR_SCALING_FACTOR + UTILITIES_SCALING_FACTOR),
+        }
+    }
+}
+
 fn create_eks_client(auth_with_k8s_env: bool) -> Result<EksClient> {
     let connector = HttpsConnector::new();
     let http_connector: hyper_proxy::ProxyConnector<
@@ -451,7 +1113,7 @@ async fn submit_update_nodegroup_config_request(
     Ok(update_id)
 }
 
-pub async fn set_eks_nodegroup_size(
+async fn set_eks_nodegroup_size(
     cluster_name: String,
     num_validators: usize,
     auth_with_k8s_env: bool,
@@ -461,42 +1123,14 @@ pub async fn set_eks_nodegroup_size(
     let eks_client = create_eks_client(auth_with_k8s_env)?;
     println!("Created rusoto http client");
 
-    // nodegroup scaling factors
-    let max_surge = 2; // multiplier for max size
-    let num_validators: i64 = num_validators as i64;
-    let idle_utilities_size = 10; // keep extra utilities nodes around for forge pods and monitoring
-    let buffer_node = if num_validators != 0 {
-        cmp::max(5, num_validators / 5)
-    } else {
-        0
-    };
-    let validator_scaling = NodegroupScalingConfig {
-        desired_size: Some(cmp::max(
-            num_validators * VALIDATOR_SCALING_FACTOR + buffer_node,
-            1,
-        )),
-        max_size: Some(cmp::max(
-            (num_validators * VALIDATOR_SCALING_FACTOR + 1) * max_surge,
-            1,
-        )),
-        min_size: Some(cmp::max(num_validators * VALIDATOR_SCALING_FACTOR, 1)),
+    let sizes = NodePoolSizes::new(num_validators);
+    let scaling_config = |size: &NodePoolSize| NodegroupScalingConfig {
+        desired_size: Some(size.desired),
+        max_size: Some(size.max),
+        min_size: Some(size.min),
     };
-    let utilities_scaling = NodegroupScalingConfig {
-        desired_size: Some(cmp::max(
-            num_validators * UTILITIES_SCALING_FACTOR + buffer_node,
-            idle_utilities_size,
-        )),
-        max_size: Some(cmp::max(
-            num_validators * UTILITIES_SCALING_FACTOR * max_surge,
-            idle_utilities_size,
-        )),
-        min_size: Some(cmp::max(
-            num_validators * UTILITIES_SCALING_FACTOR,
-            idle_utilities_size,
-        )),
-    };
-    let desire_nodegroup_size =
-        num_validators * (VALIDATOR_SCALING_FACTOR + UTILITIES_SCALING_FACTOR);
+    let validator_scaling = scaling_config(&sizes.validators);
+    let utilities_scaling = scaling_config(&sizes.utilities);
 
     // submit the scaling requests
     let validators_update_id = submit_update_nodegroup_config_request(
@@ -530,7 +1164,7 @@ pub async fn set_eks_nodegroup_size(
     .await
     .unwrap();
 
-    nodegroup_state_check(desire_nodegroup_size).await.unwrap();
+    nodegroup_state_check(sizes.min_ready_nodes).await.unwrap();
 
     Ok(())
 }
@@ -582,6 +1216,48 @@ async fn describe_update(
     .await
 }
 
+/// Resizes the node pools of a GKE cluster. Resizing blocks until the nodes are created or
+/// drained, so unlike EKS there is no update to wait for.
+async fn set_gke_node_pool_size(cluster_name: &str, num_validators: usize) -> Result<()> {
+    let sizes = NodePoolSizes::new(num_validators);
+    for &(node_pool, size) in &[
+        ("validators", &sizes.validators),
+        ("utilities", &sizes.utilities),
+    ] {
+        gcloud(&[
+            "container",
+            "clusters",
+            "resize",
+            cluster_name,
+            "--node-pool",
+            node_pool,
+            "--num-nodes",
+            &size.desired.to_string(),
+            "--quiet",
+        ])?;
+        println!("Resized {} node pool to {} nodes", node_pool, size.desired);
+    }
+
+    nodegroup_state_check(sizes.min_ready_nodes).await
+}
+
+fn gcloud(args: &[&str]) -> Result<()> {
+    println!("{:?}", args);
+    let output = Command::new(GCLOUD_BIN)
+        .stdout(Stdio::inherit())
+        .args(args)
+        .output()
+        .map_err(|e| format_err!("failed to run gcloud {:?}: {}", args, e))?;
+    if !output.status.success() {
+        bail!(
+            "gcloud {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(())
+}
+
 pub fn scale_sts_replica(sts_name: &str, replica_num: u64) -> Result<()> {
     let scale_sts_args = [
         "scale",
@@ -603,3 +1279,531 @@ pub fn scale_sts_replica(sts_name: &str, replica_num: u64) -> Result<()> {
 
     Ok(())
 }
+
+fn kubectl_json(args: &[&str]) -> Result<Value> {
+    let output = Command::new(KUBECTL_BIN)
+        .args(args)
+        .args(&["-o", "json"])
+        .output()
+        .map_err(|e| format_err!("failed to run kubectl {:?}: {}", args, e))?;
+    if !output.status.success() {
+        bail!(
+            "kubectl {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    serde_json::from_slice(&output.stdout)
+        .map_err(|e| format_err!("failed to deserialize kubectl output: {}", e))
+}
+
+fn kubectl(args: &[&str]) -> Result<()> {
+    println!("{:?}", args);
+    let output = Command::new(KUBECTL_BIN)
+        .stdout(Stdio::inherit())
+        .args(args)
+        .output()
+        .map_err(|e| format_err!("failed to run kubectl {:?}: {}", args, e))?;
+    if !output.status.success() {
+        bail!(
+            "kubectl {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(())
+}
+
+/// Returns the availability zone of every k8s node, keyed by node name
+fn get_node_zones() -> Result<HashMap<String, String>> {
+    let nodes = kubectl_json(&["get", "nodes"])?;
+    Ok(nodes["items"]
+        .as_array()
+        .ok_or_else(|| format_err!("unexpected output from kubectl get nodes"))?
+        .iter()
+        .filter_map(|node| {
+            let name = node["metadata"]["name"].as_str()?;
+            let zone = node["metadata"]["labels"][ZONE_LABEL].as_str()?;
+            Some((name.to_string(), zone.to_string()))
+        })
+        .collect())
+}
+
+/// Returns the availability zone each validator is currently scheduled in, keyed by the
+/// validator's helm release name (e.g. `val0`)
+pub fn get_validator_zones() -> Result<HashMap<String, String>> {
+    let node_zones = get_node_zones()?;
+    let pods = kubectl_json(&["get", "pods", "-l", VALIDATOR_POD_SELECTOR])?;
+    Ok(pods["items"]
+        .as_array()
+        .ok_or_else(|| format_err!("unexpected output from kubectl get pods"))?
+        .iter()
+        .filter_map(|pod| {
+            let release = pod["metadata"]["labels"]["app.kubernetes.io/instance"].as_str()?;
+            let node_name = pod["spec"]["nodeName"].as_str()?;
+            let zone = node_zones.get(node_name)?;
+            Some((release.to_string(), zone.clone()))
+        })
+        .collect())
+}
+
+/// Simulates the loss of an entire availability zone by cordoning every k8s node in `zone` and
+/// deleting the validator pods scheduled there, waiting for them to terminate. Returns the names
+/// of the cordoned nodes, which should later be passed to `restore_zone`.
+pub fn fail_zone(zone: &str) -> Result<Vec<String>> {
+    let zone_nodes = get_node_zones()?
+        .into_iter()
+        .filter(|(_, node_zone)| node_zone == zone)
+        .map(|(node, _)| node)
+        .collect::<Vec<_>>();
+    if zone_nodes.is_empty() {
+        bail!("No k8s nodes found in zone {}", zone);
+    }
+    for node in &zone_nodes {
+        kubectl(&["cordon", node])?;
+    }
+
+    let pods = kubectl_json(&["get", "pods", "-l", VALIDATOR_POD_SELECTOR])?;
+    let zone_pods = pods["items"]
+        .as_array()
+        .ok_or_else(|| format_err!("unexpected output from kubectl get pods"))?
+        .iter()
+        .filter(|pod| {
+            pod["spec"]["nodeName"]
+                .as_str()
+                .map(|node| zone_nodes.iter().any(|n| n == node))
+                .unwrap_or(false)
+        })
+        .filter_map(|pod| pod["metadata"]["name"].as_str().map(str::to_string))
+        .collect::<Vec<_>>();
+    // kubectl waits for the deleted pods themselves to be gone, not for the pods their
+    // statefulsets recreate under the same names, which stay pending on the cordoned nodes
+    let deleted = zone_pods.iter().try_for_each(|pod| {
+        kubectl(&[
+            "delete",
+            "pod",
+            pod,
+            &format!("--timeout={}s", ZONE_POD_DELETION_TIMEOUT_SECS),
+        ])
+    });
+    if let Err(e) = deleted {
+        restore_zone(&zone_nodes)?;
+        return Err(e);
+    }
+    println!(
+        "Failed zone {}: cordoned {} nodes and deleted {} validator pods",
+        zone,
+        zone_nodes.len(),
+        zone_pods.len()
+    );
+
+    Ok(zone_nodes)
+}
+
+/// Undoes `fail_zone` by uncordoning the given k8s nodes
+pub fn restore_zone(zone_nodes: &[String]) -> Result<()> {
+    for node in zone_nodes {
+        kubectl(&["uncordon", node])?;
+    }
+    Ok(())
+}
+
+/// Deletes the pod without waiting for it to terminate, e.g. to kill a node which its statefulset
+/// then recreates
+pub fn delete_pod(name: &str) -> Result<()> {
+    kubectl(&["delete", "pod", name, "--wait=false"])
+}
+
+/// Applies a chaos-mesh `NetworkChaos` resource named `name` with the given spec, which needs
+/// chaos-mesh to be installed on the cluster
+pub fn apply_network_chaos(name: &str, spec: Value) -> Result<()> {
+    apply_chaos("NetworkChaos", name, spec)
+}
+
+/// Deletes a `NetworkChaos` resource applied with `apply_network_chaos`
+pub fn delete_network_chaos(name: &str) -> Result<()> {
+    kubectl(&["delete", "networkchaos", name])
+}
+
+/// Applies a chaos-mesh `IOChaos` resource named `name` with the given spec, replacing the one
+/// already named so, which needs chaos-mesh to be installed on the cluster
+pub fn apply_io_chaos(name: &str, spec: Value) -> Result<()> {
+    apply_chaos("IOChaos", name, spec)
+}
+
+/// Deletes an `IOChaos` resource applied with `apply_io_chaos`, if it exists
+pub fn delete_io_chaos(name: &str) -> Result<()> {
+    kubectl(&["delete", "iochaos", name, "--ignore-not-found"])
+}
+
+fn apply_chaos(kind: &str, name: &str, spec: Value) -> Result<()> {
+    let manifest = json!({
+        "apiVersion": "chaos-mesh.org/v1alpha1",
+        "kind": kind,
+        "metadata": { "name": name },
+        "spec": spec,
+    });
+    let tmp_dir = TempDir::new()?;
+    let file_path = tmp_dir.path().join(format!("{}.json", name));
+    let mut file = File::create(&file_path)?;
+    file.write_all(&manifest.to_string().into_bytes())?;
+    kubectl(&["apply", "-f", &file_path.display().to_string()])
+}
+
+/// Delays the traffic between the regions of the topology, whose validator pods were labeled by
+/// `clean_k8s_cluster`. The chaos selects the pods by label, so it keeps applying to validators
+/// restarted or upgraded by the tests. It's removed along with any other chaos by the next
+/// `clean_k8s_cluster`.
+pub fn apply_topology(topology: &TopologySpec) -> Result<()> {
+    let selector = |region: &str| json!({ "labelSelectors": { REGION_LABEL: region } });
+    for (i, (a, b, latency)) in topology.latencies().iter().enumerate() {
+        println!(
+            "Delaying traffic between regions {} and {} by {:?}",
+            a, b, latency
+        );
+        apply_network_chaos(
+            &format!("forge-topology-{}", i),
+            json!({
+                "action": "delay",
+                "mode": "all",
+                "selector": selector(a),
+                "direction": "both",
+                "target": { "mode": "all", "selector": selector(b) },
+                "delay": { "latency": format!("{}ms", latency.as_millis()) },
+            }),
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_config::{
+        config::{ExecutionConfig, NetworkConfig, RoleType},
+        network_id::NetworkId,
+    };
+
+    #[test]
+    fn test_standby_validator_values() {
+        let previous = json!({
+            "chain": { "era": "fg1", "name": "forge" },
+            "imageTag": "old",
+            "validator": {
+                "name": "val5",
+                "podLabels": { REGION_LABEL: "us", "app": "aptos" },
+                "extraEnv": [{ "name": "PREVIOUS", "value": "1" }],
+            },
+            "fullnode": { "groups": [{ "name": "fullnode" }, { "name": "fullnode1" }] },
+            FORGE_HELM_VALUES_KEY: { "validator": { "resources": { "cpu": 1 } } },
+        });
+        let environment = json!({
+            "validator": { "extraEnv": [] },
+            "fullnode": { "groups": [{ "name": "fullnode", "replicas": 1 }] },
+        });
+        let values = standby_validator_values(previous, "fg2", "new", &environment);
+
+        assert_eq!(values["chain"], json!({ "era": "fg2", "name": "forge" }));
+        assert_eq!(values["imageTag"], json!("new"));
+        // the identity of the release is kept
+        assert_eq!(values["validator"]["name"], json!("val5"));
+        assert_eq!(values["validator"]["podLabels"], json!({ "app": "aptos" }));
+        assert_eq!(values["validator"]["extraEnv"], json!([]));
+        assert_eq!(
+            values["fullnode"]["groups"],
+            json!([{ "name": "fullnode", "replicas": 1 }])
+        );
+        assert!(values.get(FORGE_HELM_VALUES_KEY).is_none());
+    }
+
+    #[test]
+    fn test_merge_values() {
+        let mut values = json!({
+            "imageTag": "old",
+            "validator": {
+                "name": "val0",
+                "resources": { "limits": { "cpu": 2, "memory": "4Gi" } },
+                "extraEnv": [{ "name": "A", "value": "1" }],
+            },
+        });
+        let overrides = json!({
+            "imageTag": "new",
+            "validator": {
+                "resources": { "limits": { "cpu": 4 }, "requests": { "cpu": 1 } },
+                "extraEnv": [{ "name": "B", "value": "2" }],
+            },
+            "fullnode": { "groups": [] },
+        });
+        merge_values(&mut values, &overrides);
+        assert_eq!(
+            values,
+            json!({
+                "imageTag": "new",
+                "validator": {
+                    "name": "val0",
+                    // maps are merged key by key, at any depth
+                    "resources": {
+                        "limits": { "cpu": 4, "memory": "4Gi" },
+                        "requests": { "cpu": 1 },
+                    },
+                    // lists are replaced, not appended to
+                    "extraEnv": [{ "name": "B", "value": "2" }],
+                },
+                "fullnode": { "groups": [] },
+            })
+        );
+
+        // a map replaces a scalar, and the other way around
+        let mut values = json!({ "a": 1, "b": { "c": 1 } });
+        merge_values(&mut values, &json!({ "a": { "c": 1 }, "b": null }));
+        assert_eq!(values, json!({ "a": { "c": 1 }, "b": null }));
+    }
+
+    #[test]
+    fn test_remove_values() {
+        let mut values = json!({
+            "imageTag": "new",
+            "validator": {
+                "name": "val0",
+                "resources": { "limits": { "cpu": 4, "memory": "4Gi" } },
+                "extraEnv": [{ "name": "B", "value": "2" }],
+            },
+        });
+        remove_values(
+            &mut values,
+            &json!({
+                "validator": {
+                    "resources": { "limits": { "cpu": 4 } },
+                    "extraEnv": [{ "name": "B", "value": "2" }],
+                    // keys that are already gone are ignored
+                    "nodeSelector": { "pool": "validators" },
+                },
+            }),
+        );
+        assert_eq!(
+            values,
+            json!({
+                "imageTag": "new",
+                "validator": {
+                    "name": "val0",
+                    "resources": { "limits": { "memory": "4Gi" } },
+                },
+            })
+        );
+
+        // a removed map takes out a value that isn't a map anymore as a whole
+        let mut values = json!({ "a": 1, "b": 2 });
+        remove_values(&mut values, &json!({ "a": { "c": 1 } }));
+        assert_eq!(values, json!({ "b": 2 }));
+    }
+
+    #[test]
+    fn test_clear_previous_helm_values() {
+        let mut config = json!({
+            "imageTag": "new",
+            "validator": {
+                "name": "val0",
+                "resources": { "limits": { "cpu": 4 } },
+            },
+            FORGE_HELM_VALUES_KEY: { "validator": { "resources": { "limits": { "cpu": 4 } } } },
+        });
+        clear_previous_helm_values(&mut config);
+        assert_eq!(
+            config,
+            json!({
+                "imageTag": "new",
+                "validator": { "name": "val0", "resources": { "limits": {} } },
+            })
+        );
+
+        // values without a previous run's helm values are left as they are
+        let mut config = json!({ "validator": { "resources": { "limits": { "cpu": 4 } } } });
+        let expected = config.clone();
+        clear_previous_helm_values(&mut config);
+        assert_eq!(config, expected);
+    }
+
+    #[test]
+    fn test_changed_fields() {
+        let before = json!({ "a": { "b": 1, "c": [1, 2] }, "d": "x" });
+        assert_eq!(changed_fields(&before, &before), None);
+        assert_eq!(
+            changed_fields(&before, &json!({ "a": { "b": 2, "c": [1, 2] }, "d": "x" })),
+            Some(json!({ "a": { "b": 2 } }))
+        );
+        // arrays are set whole
+        assert_eq!(
+            changed_fields(&before, &json!({ "a": { "b": 1, "c": [1, 3] }, "d": "x" })),
+            Some(json!({ "a": { "c": [1, 3] } }))
+        );
+        assert_eq!(
+            changed_fields(
+                &before,
+                &json!({ "a": { "b": 1, "c": [1, 2] }, "d": "x", "e": 0 })
+            ),
+            Some(json!({ "e": 0 }))
+        );
+    }
+
+    #[test]
+    fn test_node_config_overrides() {
+        let mut rendered = NodeConfig::default_for_validator();
+        rendered.execution.concurrency_level = 8;
+        let mut vfn_network = NetworkConfig::network_with_id(NetworkId::Vfn);
+        vfn_network.identity = fullnode_group_identity();
+        rendered.full_node_networks = vec![vfn_network.clone()];
+
+        let node_config_fn = NodeConfigFn::new(|config| {
+            // back to the default of the node, from the value the chart sets
+            config.execution.concurrency_level = ExecutionConfig::default().concurrency_level;
+            config.full_node_networks[0].max_outbound_connections = 7;
+        });
+        let overrides: Value =
+            serde_json::from_str(&node_config_overrides(&node_config_fn, rendered).unwrap())
+                .unwrap();
+        assert_eq!(
+            overrides["execution"],
+            json!({ "concurrency_level": ExecutionConfig::default().concurrency_level })
+        );
+        // the changed network is set whole, with the identity the chart renders
+        vfn_network.max_outbound_connections = 7;
+        assert_eq!(
+            overrides["full_node_networks"],
+            serde_json::to_value(vec![vfn_network]).unwrap()
+        );
+        assert_eq!(overrides.as_object().unwrap().len(), 2);
+
+        let unchanged = NodeConfigFn::new(|_| {});
+        assert_eq!(
+            node_config_overrides(&unchanged, NodeConfig::default_for_validator()).unwrap(),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_parse_node_configs() {
+        let manifests = r#"---
+# Source: aptos-validator/templates/configmaps.yaml
+apiVersion: v1
+kind: ConfigMap
+metadata:
+  name: val0-aptos-validator
+data:
+  validator.yaml: |-
+    execution:
+      concurrency_level: 8
+  fullnode.yaml: |-
+    base:
+      role: "full_node"
+---
+# Source: aptos-validator/templates/configmaps.yaml
+apiVersion: v1
+kind: ConfigMap
+metadata:
+  name: val0-aptos-validator-dashboards
+binaryData:
+  consensus.json.gz: H4sIAAAAAAAA
+"#;
+        let configs = parse_node_configs(manifests).unwrap();
+        assert_eq!(configs.len(), 2);
+        assert_eq!(configs["validator.yaml"].execution.concurrency_level, 8);
+        assert_eq!(configs["fullnode.yaml"].base.role, RoleType::FullNode);
+
+        let invalid = "data:\n  validator.yaml: |-\n    unknown_field: 1\n";
+        assert!(parse_node_configs(invalid).is_err());
+    }
+
+    #[test]
+    fn test_set_node_config_overrides() {
+        let mut values = node_environment_helm_values(&NodeEnvironment::default(), 0, 2).unwrap();
+        assert_eq!(values["validator"]["config"]["overrides"], json!(""));
+        assert_eq!(values["fullnode"]["config"]["overrides"], json!(""));
+
+        let mut configs = HashMap::new();
+        configs.insert(
+            "validator.yaml".to_string(),
+            NodeConfig::default_for_validator(),
+        );
+        configs.insert(
+            "fullnode.yaml".to_string(),
+            NodeConfig::default_for_validator_full_node(),
+        );
+        let node_config_fn = NodeConfigFn::new(|config| {
+            config.mempool.capacity = 42;
+        });
+        // the group with an identity of its own has a config of its own
+        assert!(set_node_config_overrides(&mut values.clone(), &node_config_fn, &configs).is_err());
+        configs.insert(
+            "fullnode-fullnode1.yaml".to_string(),
+            NodeConfig::default_for_public_full_node(),
+        );
+        set_node_config_overrides(&mut values, &node_config_fn, &configs).unwrap();
+
+        let expected = json!({ "mempool": { "capacity": 42 } }).to_string();
+        assert_eq!(values["validator"]["config"]["overrides"], json!(expected));
+        assert_eq!(values["fullnode"]["config"]["overrides"], json!(expected));
+        let groups = values["fullnode"]["groups"].as_array().unwrap();
+        assert!(groups[0].get("overrides").is_none());
+        assert_eq!(groups[1]["overrides"], json!(expected));
+    }
+
+    #[test]
+    fn test_validator_fullnode_count() {
+        // one per validator by default
+        assert_eq!(validator_fullnode_count(None, 4, 0), 1);
+        assert_eq!(validator_fullnode_count(None, 4, 3), 1);
+        // the first validators take the remainder
+        let counts = (0..4)
+            .map(|i| validator_fullnode_count(Some(10), 4, i))
+            .collect::<Vec<_>>();
+        assert_eq!(counts, vec![3, 3, 2, 2]);
+        let counts = (0..4)
+            .map(|i| validator_fullnode_count(Some(2), 4, i))
+            .collect::<Vec<_>>();
+        assert_eq!(counts, vec![1, 1, 0, 0]);
+        assert_eq!(validator_fullnode_count(Some(0), 4, 0), 0);
+        assert_eq!(validator_fullnode_count(Some(8), 4, 3), 2);
+    }
+
+    #[test]
+    fn test_node_pool_validator_count() {
+        assert_eq!(node_pool_validator_count(4, None), 4);
+        // up to one fullnode per validator fits in the pools of the validators
+        assert_eq!(node_pool_validator_count(4, Some(0)), 4);
+        assert_eq!(node_pool_validator_count(4, Some(4)), 4);
+        // the others are counted as validators, VALIDATOR_SCALING_FACTOR at a time
+        assert_eq!(node_pool_validator_count(4, Some(5)), 5);
+        assert_eq!(node_pool_validator_count(4, Some(7)), 5);
+        assert_eq!(node_pool_validator_count(4, Some(8)), 6);
+    }
+
+    #[test]
+    fn test_fullnode_group_identities() {
+        let values = node_environment_helm_values(&NodeEnvironment::default(), 0, 3).unwrap();
+        let groups = values["fullnode"]["groups"].as_array().unwrap();
+        let names = groups
+            .iter()
+            .map(|group| group["name"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["fullnode", "fullnode1", "fullnode2"]);
+
+        // the first group keeps the identity of the owner of the validator
+        assert!(groups[0].get("identity").is_none());
+        let peer_ids = groups[1..]
+            .iter()
+            .map(
+                |group| match serde_json::from_value(group["identity"].clone()).unwrap() {
+                    Identity::FromConfig(identity) => {
+                        assert_eq!(
+                            identity.peer_id,
+                            from_identity_public_key(identity.key.public_key())
+                        );
+                        identity.peer_id
+                    }
+                    identity => panic!("Unexpected identity {:?}", identity),
+                },
+            )
+            .collect::<Vec<_>>();
+        assert_ne!(peer_ids[0], peer_ids[1]);
+    }
+}
@@ -0,0 +1,169 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{NodePoolUsage, Result};
+use k8s_openapi::api::core::v1::{Node, Pod};
+use kube::{
+    api::{Api, ListParams},
+    client::Client as K8sClient,
+};
+use std::collections::{BTreeMap, HashSet};
+
+/// Labels of the node pool of a node, on EKS and GKE
+const NODE_POOL_LABELS: &[&str] = &[
+    "eks.amazonaws.com/nodegroup",
+    "cloud.google.com/gke-nodepool",
+];
+const INSTANCE_TYPE_LABEL: &str = "node.kubernetes.io/instance-type";
+/// Selects the pods of the validators and fullnodes of the swarm
+const SWARM_POD_SELECTOR: &str = "app.kubernetes.io/name in (validator,fullnode)";
+
+/// On-demand prices in USD per hour of the instance types forge clusters run on, in us-west-2
+/// for AWS and us-central1 for GCP. They only need to be close enough to compare runs.
+const INSTANCE_HOURLY_PRICES: &[(&str, f64)] = &[
+    ("t3.medium", 0.0416),
+    ("t3.large", 0.0832),
+    ("t3.xlarge", 0.1664),
+    ("c5.large", 0.085),
+    ("c5.xlarge", 0.17),
+    ("c5.2xlarge", 0.34),
+    ("c5.4xlarge", 0.68),
+    ("c5.9xlarge", 1.53),
+    ("m5.large", 0.096),
+    ("m5.xlarge", 0.192),
+    ("m5.2xlarge", 0.384),
+    ("m5.4xlarge", 0.768),
+    ("c2-standard-4", 0.2088),
+    ("c2-standard-8", 0.4176),
+    ("c2-standard-16", 0.8352),
+    ("e2-standard-4", 0.134),
+    ("e2-standard-8", 0.268),
+    ("n2-standard-4", 0.1942),
+    ("n2-standard-8", 0.3885),
+];
+
+fn instance_hourly_price(instance_type: &str) -> Option<f64> {
+    INSTANCE_HOURLY_PRICES
+        .iter()
+        .find(|(name, _)| *name == instance_type)
+        .map(|(_, price)| *price)
+}
+
+/// Counts the nodes of the node pools the swarm runs on, by node pool and instance type. The
+/// node pools of the swarm are those of the nodes its validators and fullnodes are scheduled on,
+/// other pools of the cluster aren't billed for the run.
+pub(crate) async fn list_node_pools(client: K8sClient) -> Result<Vec<NodePoolUsage>> {
+    let pod_api: Api<Pod> = Api::namespaced(client.clone(), "default");
+    let swarm_nodes = pod_api
+        .list(&ListParams::default().labels(SWARM_POD_SELECTOR))
+        .await?
+        .items
+        .into_iter()
+        .filter_map(|pod| pod.spec?.node_name)
+        .collect::<HashSet<_>>();
+    let node_api: Api<Node> = Api::all(client);
+    let nodes = node_api
+        .list(&ListParams::default())
+        .await?
+        .items
+        .into_iter()
+        .map(|node| {
+            (
+                node.metadata.name.unwrap_or_default(),
+                node.metadata.labels.unwrap_or_default(),
+            )
+        })
+        .collect::<Vec<_>>();
+    Ok(count_node_pools(&nodes, &swarm_nodes))
+}
+
+/// Counts the `nodes`, given by name with their labels, of the node pools of the `swarm_nodes`.
+/// Nodes outside of a node pool, e.g. those of a local cluster, are counted in an `unknown` pool.
+fn count_node_pools(
+    nodes: &[(String, BTreeMap<String, String>)],
+    swarm_nodes: &HashSet<String>,
+) -> Vec<NodePoolUsage> {
+    let node_pool = |labels: &BTreeMap<String, String>| {
+        let node_pool = NODE_POOL_LABELS
+            .iter()
+            .find_map(|label| labels.get(*label))
+            .cloned()
+            .unwrap_or_else(|| "unknown".to_string());
+        let instance_type = labels
+            .get(INSTANCE_TYPE_LABEL)
+            .cloned()
+            .unwrap_or_else(|| "unknown".to_string());
+        (node_pool, instance_type)
+    };
+    let swarm_node_pools = nodes
+        .iter()
+        .filter(|(name, _)| swarm_nodes.contains(name))
+        .map(|(_, labels)| node_pool(labels))
+        .collect::<HashSet<_>>();
+
+    let mut counts = BTreeMap::new();
+    for (_, labels) in nodes {
+        let node_pool = node_pool(labels);
+        if swarm_node_pools.contains(&node_pool) {
+            *counts.entry(node_pool).or_insert(0) += 1;
+        }
+    }
+    counts
+        .into_iter()
+        .map(|((node_pool, instance_type), num_nodes)| NodePoolUsage {
+            hourly_price_usd: instance_hourly_price(&instance_type),
+            node_pool,
+            instance_type,
+            num_nodes,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(
+        name: &str,
+        node_pool: Option<&str>,
+        instance_type: &str,
+    ) -> (String, BTreeMap<String, String>) {
+        let mut labels = BTreeMap::new();
+        if let Some(node_pool) = node_pool {
+            labels.insert(NODE_POOL_LABELS[0].to_string(), node_pool.to_string());
+        }
+        labels.insert(INSTANCE_TYPE_LABEL.to_string(), instance_type.to_string());
+        (name.to_string(), labels)
+    }
+
+    #[test]
+    fn test_count_node_pools() {
+        let nodes = vec![
+            node("validators-1", Some("validators"), "c5.4xlarge"),
+            node("validators-2", Some("validators"), "c5.4xlarge"),
+            // idle, but in the pool of the swarm
+            node("validators-3", Some("validators"), "c5.4xlarge"),
+            node("utilities-1", Some("utilities"), "t3.xlarge"),
+            node("other-run-1", Some("other-run"), "c5.4xlarge"),
+            node("local-1", None, "kind"),
+        ];
+        let swarm_nodes = ["validators-1", "validators-2", "utilities-1"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        let node_pools = count_node_pools(&nodes, &swarm_nodes);
+        assert_eq!(node_pools.len(), 2);
+        assert_eq!(node_pools[0].node_pool, "utilities");
+        assert_eq!(node_pools[0].num_nodes, 1);
+        assert_eq!(node_pools[0].hourly_price_usd, Some(0.1664));
+        assert_eq!(node_pools[1].node_pool, "validators");
+        assert_eq!(node_pools[1].instance_type, "c5.4xlarge");
+        assert_eq!(node_pools[1].num_nodes, 3);
+
+        let swarm_nodes = ["local-1".to_string()].iter().cloned().collect();
+        let node_pools = count_node_pools(&nodes, &swarm_nodes);
+        assert_eq!(node_pools.len(), 1);
+        assert_eq!(node_pools[0].node_pool, "unknown");
+        assert_eq!(node_pools[0].hourly_price_usd, None);
+    }
+}
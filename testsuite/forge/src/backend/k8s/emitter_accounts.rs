@@ -0,0 +1,148 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::Result;
+use aes_gcm::{
+    aead::{generic_array::GenericArray, Aead, NewAead, Payload},
+    Aes256Gcm,
+};
+use anyhow::{bail, format_err};
+use aptos_sdk::{
+    crypto::{
+        ed25519::{Ed25519PrivateKey, ED25519_PRIVATE_KEY_LENGTH},
+        HashValue,
+    },
+    types::LocalAccount,
+};
+use k8s_openapi::{
+    api::core::v1::Secret, apimachinery::pkg::apis::meta::v1::ObjectMeta, ByteString,
+};
+use kube::{
+    api::{Api, PostParams},
+    client::Client as K8sClient,
+};
+use rand::{rngs::OsRng, Rng};
+use std::{collections::BTreeMap, convert::TryFrom};
+
+const EMITTER_ACCOUNTS_SECRET: &str = "forge-emitter-accounts";
+const ERA_KEY: &str = "era";
+const ACCOUNTS_KEY: &str = "accounts";
+const NONCE_SIZE: usize = 12;
+/// Keeps the secret well below the 1MB limit of k8s, even once base64 encoded
+const MAX_SAVED_ACCOUNTS: usize = 20_000;
+
+/// Loads the keys of the emitter accounts saved by `save_emitter_account_keys`, or none if they
+/// were saved during another era, whose chain doesn't have the accounts anymore
+pub(crate) async fn load_emitter_account_keys(
+    kube_client: &K8sClient,
+    root_key: &Ed25519PrivateKey,
+    era: &str,
+) -> Result<Vec<Ed25519PrivateKey>> {
+    let secrets: Api<Secret> = Api::namespaced(kube_client.clone(), "default");
+    let mut data = match secrets.get(EMITTER_ACCOUNTS_SECRET).await {
+        Ok(secret) => secret.data.unwrap_or_default(),
+        Err(kube::Error::Api(e)) if e.code == 404 => return Ok(vec![]),
+        Err(e) => return Err(e.into()),
+    };
+    match data.get(ERA_KEY) {
+        Some(saved_era) if saved_era.0 == era.as_bytes() => {}
+        _ => return Ok(vec![]),
+    }
+    let ciphertext = data
+        .remove(ACCOUNTS_KEY)
+        .ok_or_else(|| format_err!("No accounts in secret {}", EMITTER_ACCOUNTS_SECRET))?;
+
+    let plaintext = decrypt(root_key, era, &ciphertext.0)?;
+    plaintext
+        .chunks(ED25519_PRIVATE_KEY_LENGTH)
+        .map(|bytes| Ed25519PrivateKey::try_from(bytes).map_err(Into::into))
+        .collect()
+}
+
+/// Saves the keys of emitter accounts of the chain of `era` in a k8s secret, replacing the ones
+/// saved before. The keys are encrypted with a key derived from the root key, which only those
+/// able to mint on the chain anyway have.
+pub(crate) async fn save_emitter_account_keys(
+    kube_client: &K8sClient,
+    root_key: &Ed25519PrivateKey,
+    era: &str,
+    accounts: &[LocalAccount],
+) -> Result<()> {
+    let plaintext: Vec<u8> = accounts
+        .iter()
+        .take(MAX_SAVED_ACCOUNTS)
+        .flat_map(|account| account.private_key().to_bytes())
+        .collect();
+    let mut data = BTreeMap::new();
+    data.insert(ERA_KEY.to_string(), ByteString(era.as_bytes().to_vec()));
+    data.insert(
+        ACCOUNTS_KEY.to_string(),
+        ByteString(encrypt(root_key, era, &plaintext)?),
+    );
+
+    let secrets: Api<Secret> = Api::namespaced(kube_client.clone(), "default");
+    match secrets.get(EMITTER_ACCOUNTS_SECRET).await {
+        Ok(mut secret) => {
+            secret.data = Some(data);
+            secrets
+                .replace(EMITTER_ACCOUNTS_SECRET, &PostParams::default(), &secret)
+                .await?;
+        }
+        Err(kube::Error::Api(e)) if e.code == 404 => {
+            let secret = Secret {
+                metadata: ObjectMeta {
+                    name: Some(EMITTER_ACCOUNTS_SECRET.to_string()),
+                    ..Default::default()
+                },
+                data: Some(data),
+                ..Default::default()
+            };
+            secrets.create(&PostParams::default(), &secret).await?;
+        }
+        Err(e) => return Err(e.into()),
+    }
+    Ok(())
+}
+
+fn cipher(root_key: &Ed25519PrivateKey) -> Aes256Gcm {
+    let key = HashValue::sha3_256_of(
+        &[
+            EMITTER_ACCOUNTS_SECRET.as_bytes(),
+            root_key.to_bytes().as_ref(),
+        ]
+        .concat(),
+    );
+    Aes256Gcm::new(GenericArray::from_slice(&key.to_vec()))
+}
+
+/// Encrypts `plaintext` bound to `era`, prefixed with its random nonce
+fn encrypt(root_key: &Ed25519PrivateKey, era: &str, plaintext: &[u8]) -> Result<Vec<u8>> {
+    let nonce: [u8; NONCE_SIZE] = OsRng.gen();
+    let payload = Payload {
+        msg: plaintext,
+        aad: era.as_bytes(),
+    };
+    let mut ciphertext = nonce.to_vec();
+    ciphertext.extend(
+        cipher(root_key)
+            .encrypt(GenericArray::from_slice(&nonce), payload)
+            .map_err(|_| format_err!("Failed to encrypt the emitter accounts"))?,
+    );
+    Ok(ciphertext)
+}
+
+fn decrypt(root_key: &Ed25519PrivateKey, era: &str, ciphertext: &[u8]) -> Result<Vec<u8>> {
+    if ciphertext.len() < NONCE_SIZE {
+        bail!("Truncated emitter accounts");
+    }
+    let (nonce, msg) = ciphertext.split_at(NONCE_SIZE);
+    let payload = Payload {
+        msg,
+        aad: era.as_bytes(),
+    };
+    cipher(root_key)
+        .decrypt(GenericArray::from_slice(nonce), payload)
+        .map_err(|_| {
+            format_err!("Failed to decrypt the emitter accounts, was the root key rotated?")
+        })
+}
@@ -1,32 +1,55 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{Factory, GenesisConfig, Result, Swarm, Version};
+use crate::{
+    Factory, GenesisConfig, InitialState, NodeEnvironment, Result, Swarm, TopologySpec, Version,
+};
 use anyhow::{bail, format_err};
 use rand::rngs::StdRng;
-use std::{env, fs::File, io::Read, num::NonZeroUsize, path::PathBuf};
+use std::{
+    env,
+    fs::File,
+    io::Read,
+    num::NonZeroUsize,
+    path::PathBuf,
+    sync::atomic::{AtomicBool, Ordering},
+};
 use tokio::runtime::Runtime;
+use url::Url;
 
 mod cluster_helper;
+mod cost;
+mod emitter_accounts;
 mod node;
+mod standby;
 mod swarm;
+mod triage;
 
 pub use cluster_helper::*;
 pub use node::K8sNode;
 pub use swarm::*;
+pub use triage::*;
 
-use aptos_sdk::crypto::ed25519::ED25519_PRIVATE_KEY_LENGTH;
 use aptos_secure_storage::{CryptoStorage, KVStorage, VaultStorage};
 
 pub struct K8sFactory {
-    root_key: [u8; ED25519_PRIVATE_KEY_LENGTH],
+    root_account: RootAccountSource,
     cluster_name: String,
     helm_repo: String,
     image_tag: String,
     base_image_tag: String,
+    validator_placement: ValidatorPlacement,
+    cloud_provider: CloudProvider,
+    triage_bucket: Option<String>,
+    keep_cluster_on_failure: bool,
+    no_teardown: bool,
+    // Set once a swarm is launched, until the cluster is torn down or deliberately kept
+    needs_teardown: AtomicBool,
 }
 
 impl K8sFactory {
+    /// A factory funding the tests from the root account, whose key it exports from Vault
+    /// (`VAULT_ADDR`, `VAULT_CACERT` and `VAULT_TOKEN`)
     pub fn new(
         cluster_name: String,
         helm_repo: String,
@@ -64,24 +87,114 @@ impl K8sFactory {
             .unwrap()
             .to_bytes();
 
-        Ok(Self {
-            root_key,
+        Ok(Self::from_root_account(
+            RootAccountSource::RootKey(root_key),
             cluster_name,
             helm_repo,
             image_tag,
             base_image_tag,
-        })
+        ))
+    }
+
+    /// A factory funding the tests from an account the faucet at `faucet_url` creates with
+    /// `faucet_coins`, for networks whose root key isn't exportable. Tests which need the root
+    /// account, to mint or reconfigure, fail against its swarms.
+    pub fn new_with_faucet(
+        cluster_name: String,
+        helm_repo: String,
+        image_tag: String,
+        base_image_tag: String,
+        faucet_url: Url,
+        faucet_coins: u64,
+    ) -> K8sFactory {
+        Self::from_root_account(
+            RootAccountSource::Faucet {
+                url: faucet_url,
+                coins: faucet_coins,
+            },
+            cluster_name,
+            helm_repo,
+            image_tag,
+            base_image_tag,
+        )
+    }
+
+    fn from_root_account(
+        root_account: RootAccountSource,
+        cluster_name: String,
+        helm_repo: String,
+        image_tag: String,
+        base_image_tag: String,
+    ) -> K8sFactory {
+        Self {
+            root_account,
+            cluster_name,
+            helm_repo,
+            image_tag,
+            base_image_tag,
+            validator_placement: ValidatorPlacement::default(),
+            cloud_provider: CloudProvider::default(),
+            triage_bucket: None,
+            keep_cluster_on_failure: false,
+            no_teardown: false,
+            needs_teardown: AtomicBool::new(false),
+        }
+    }
+
+    pub fn with_validator_placement(mut self, validator_placement: ValidatorPlacement) -> Self {
+        self.validator_placement = validator_placement;
+        self
+    }
+
+    /// Resizes the node pools of the cluster through this cloud provider
+    pub fn with_cloud_provider(mut self, cloud_provider: CloudProvider) -> Self {
+        self.cloud_provider = cloud_provider;
+        self
+    }
+
+    /// Uploads a triage bundle to this S3 bucket whenever a test fails
+    pub fn with_triage_bucket(mut self, triage_bucket: Option<String>) -> Self {
+        self.triage_bucket = triage_bucket;
+        self
+    }
+
+    /// Leaves the cluster running after a failed run so it can be inspected
+    pub fn with_keep_cluster_on_failure(mut self, keep_cluster_on_failure: bool) -> Self {
+        self.keep_cluster_on_failure = keep_cluster_on_failure;
+        self
+    }
+
+    /// Leaves the cluster running after every run
+    pub fn with_no_teardown(mut self, no_teardown: bool) -> Self {
+        self.no_teardown = no_teardown;
+        self
+    }
+
+    fn clean_up_command(&self) -> String {
+        match self.cloud_provider {
+            CloudProvider::Aws => format!(
+                "forge operator clean-up --cluster-name {}",
+                self.cluster_name
+            ),
+            CloudProvider::Gcp => format!(
+                "forge operator clean-up --cluster-name {} --cloud-provider gcp",
+                self.cluster_name
+            ),
+        }
     }
 }
 
 impl Drop for K8sFactory {
-    // When the K8sSwarm struct goes out of scope we need to wipe the chain state and scale down
+    // Tearing down is fallible and slow, so it has to be requested explicitly with
+    // `Factory::teardown`. Here we only warn about clusters left running by accident.
     fn drop(&mut self) {
-        uninstall_from_k8s_cluster().unwrap();
-        let runtime = Runtime::new().unwrap();
-        runtime
-            .block_on(set_eks_nodegroup_size(self.cluster_name.clone(), 0, true))
-            .unwrap();
+        if self.needs_teardown.load(Ordering::SeqCst) {
+            println!(
+                "Cluster {} was not torn down, clean it up with `{}`",
+                self.cluster_name,
+                self.clean_up_command()
+            );
+        }
     }
 }
 
@@ -99,10 +212,22 @@ impl Factory for K8sFactory {
         &self,
         _rng: &mut StdRng,
         node_num: NonZeroUsize,
+        fullnode_num: Option<usize>,
+        standby_validator_num: usize,
         init_version: &Version,
         genesis_version: &Version,
         genesis_config: Option<&GenesisConfig>,
+        initial_state: &InitialState,
+        node_environment: &NodeEnvironment,
+        topology: &TopologySpec,
     ) -> Result<Box<dyn Swarm>> {
+        if !initial_state.accounts.is_empty() || !initial_state.packages.is_empty() {
+            bail!("k8s forge backend does not support seeding accounts and packages at genesis")
+        }
+        if !node_environment.validator_resource_limits.is_empty() {
+            bail!("k8s forge backend does not support resource limits, set the resources of validators through helm values instead")
+        }
+        topology.validate()?;
         let genesis_modules_path = match genesis_config {
             Some(config) => match config {
                 GenesisConfig::Bytes(_) => {
@@ -113,29 +238,74 @@ impl Factory for K8sFactory {
             None => None,
         };
 
-        set_eks_nodegroup_size(self.cluster_name.clone(), node_num.get(), true).await?;
+        self.needs_teardown.store(true, Ordering::SeqCst);
+        self.cloud_provider
+            .set_node_pool_size(
+                self.cluster_name.clone(),
+                node_pool_validator_count(
+                    node_num.get() + standby_validator_num,
+                    fullnode_num.map(|n| n + standby_validator_num),
+                ),
+                true,
+            )
+            .await?;
         uninstall_from_k8s_cluster()?;
         let era = clean_k8s_cluster(
             self.helm_repo.clone(),
             node_num.get(),
+            fullnode_num,
+            standby_validator_num,
             format!("{}", init_version),
             format!("{}", genesis_version),
             false,
             genesis_modules_path,
+            self.validator_placement,
+            node_environment,
+            topology,
         )
         .await?;
+        apply_topology(topology)?;
 
         let swarm = K8sSwarm::new(
-            &self.root_key,
+            &self.root_account,
             &self.cluster_name,
             &self.helm_repo,
             &self.image_tag,
             &self.base_image_tag,
             format!("{}", init_version).as_str(),
             &era,
+            self.cloud_provider,
+            self.triage_bucket.clone(),
+            node_environment.clone(),
         )
         .await
         .unwrap();
         Ok(Box::new(swarm))
     }
+
+    // Wipes the chain state and scales down the cluster
+    fn teardown(&self, success: bool) -> Result<()> {
+        if !self.needs_teardown.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        if self.no_teardown || (!success && self.keep_cluster_on_failure) {
+            println!(
+                "Keeping cluster {} running, clean it up with `{}`",
+                self.cluster_name,
+                self.clean_up_command()
+            );
+            self.needs_teardown.store(false, Ordering::SeqCst);
+            return Ok(());
+        }
+
+        uninstall_from_k8s_cluster()?;
+        let runtime = Runtime::new()?;
+        runtime.block_on(self.cloud_provider.set_node_pool_size(
+            self.cluster_name.clone(),
+            0,
+            true,
+        ))?;
+        self.needs_teardown.store(false, Ordering::SeqCst);
+        Ok(())
+    }
 }
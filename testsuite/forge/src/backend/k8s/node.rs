@@ -2,14 +2,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    scale_sts_replica, FullNode, HealthCheckError, Node, NodeExt, Result, Validator, Version,
+    apply_io_chaos, backend::k8s::standby::StandbyIdentity, delete_io_chaos, io_delay,
+    scale_sts_replica, FullNode, HealthCheckError, Node, NodeExt, Result, StorageMetrics,
+    Validator, ValidatorCandidate, Version,
 };
-use anyhow::{format_err, Context};
+use anyhow::{bail, format_err, Context};
 use aptos_config::config::NodeConfig;
 use aptos_rest_client::Client as RestClient;
-use aptos_sdk::types::PeerId;
+use aptos_sdk::types::{LocalAccount, PeerId};
 use reqwest::Url;
-use serde_json::Value;
+use serde_json::{json, Value};
 use std::{
     fmt::{Debug, Formatter},
     process::{Command, Stdio},
@@ -19,6 +21,9 @@ use std::{
 };
 
 const NODE_METRIC_PORT: u64 = 9101;
+/// Data directory of the nodes in their pods, as set by the helm chart
+const DATA_DIR: &str = "/opt/aptos/data";
+const DB_DIR: &str = "/opt/aptos/data/db";
 
 pub struct K8sNode {
     pub(crate) name: String,
@@ -30,6 +35,8 @@ pub struct K8sNode {
     pub(crate) port: u32,
     pub(crate) rest_api_port: u32,
     pub version: Version,
+    // The identity of a validator added to the swarm, none for the validators of the genesis
+    pub(crate) standby_identity: Option<StandbyIdentity>,
 }
 
 impl K8sNode {
@@ -62,6 +69,48 @@ impl K8sNode {
     fn sts_name(&self) -> &str {
         &self.sts_name
     }
+
+    fn pod_name(&self) -> String {
+        format!("{}-0", self.sts_name)
+    }
+
+    /// The container of the node in its pod
+    fn container(&self) -> &'static str {
+        if self.sts_name.contains("fullnode") {
+            "fullnode"
+        } else {
+            "validator"
+        }
+    }
+
+    /// Name of the IOChaos throttling the IO of the node
+    fn io_chaos_name(&self) -> String {
+        format!("forge-io-{}", self.sts_name)
+    }
+
+    /// Spec of the IOChaos delaying every IO operation on the data directory of the node, see
+    /// `Node::throttle_io`
+    fn io_chaos_spec(&self, iops: u64, latency: Duration) -> Value {
+        json!({
+            "action": "latency",
+            "mode": "all",
+            "selector": { "pods": { "default": [self.pod_name()] } },
+            "containerNames": [self.container()],
+            "volumePath": DATA_DIR,
+            "path": format!("{}/**/*", DATA_DIR),
+            "delay": format!("{}us", io_delay(iops, latency).as_micros()),
+            "percent": 100,
+        })
+    }
+
+    fn standby_identity(&self) -> Result<&StandbyIdentity> {
+        self.standby_identity.as_ref().ok_or_else(|| {
+            format_err!(
+                "Only the keys of the validators added to the swarm are known, not those of {}",
+                self.name
+            )
+        })
+    }
 }
 
 #[async_trait::async_trait]
@@ -180,9 +229,61 @@ impl Node for K8sNode {
 
         Ok(port)
     }
+
+    fn storage_metrics(&self) -> Result<StorageMetrics> {
+        let pod_name = self.pod_name();
+        let container = self.container();
+        let output = Command::new("kubectl")
+            .args(&[
+                "exec", &pod_name, "-c", container, "--", "du", "-sb", DB_DIR, DATA_DIR,
+            ])
+            .output()
+            .with_context(|| format!("Error running du in pod {}", pod_name))?;
+        if !output.status.success() {
+            bail!(
+                "du failed in pod {}: {}",
+                pod_name,
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+        // du prints the size and the path of each directory, in the order they were given
+        let stdout = String::from_utf8(output.stdout)?;
+        let sizes = stdout
+            .lines()
+            .map(|line| {
+                line.split_whitespace()
+                    .next()
+                    .and_then(|size| size.parse::<u64>().ok())
+                    .ok_or_else(|| format_err!("Unexpected output of du: {}", line))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        match sizes[..] {
+            [db_bytes, data_dir_bytes] => Ok(StorageMetrics {
+                db_bytes,
+                data_dir_bytes,
+            }),
+            _ => bail!("Unexpected output of du in pod {}: {}", pod_name, stdout),
+        }
+    }
+
+    fn throttle_io(&mut self, iops: u64, latency: Duration) -> Result<()> {
+        apply_io_chaos(&self.io_chaos_name(), self.io_chaos_spec(iops, latency))
+    }
+
+    fn restore_io(&mut self) -> Result<()> {
+        delete_io_chaos(&self.io_chaos_name())
+    }
 }
 
-impl Validator for K8sNode {}
+impl Validator for K8sNode {
+    fn owner_account(&self) -> Result<LocalAccount> {
+        self.standby_identity()?.owner_account()
+    }
+
+    fn candidate(&self) -> Result<ValidatorCandidate> {
+        self.standby_identity()?.candidate(self.node_id)
+    }
+}
 
 impl FullNode for K8sNode {}
 
@@ -191,3 +292,55 @@ impl Debug for K8sNode {
         write!(f, "{}", self.name)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(sts_name: &str) -> K8sNode {
+        K8sNode {
+            name: "val1".to_string(),
+            sts_name: sts_name.to_string(),
+            peer_id: PeerId::random(),
+            node_id: 1,
+            dns: sts_name.to_string(),
+            ip: "127.0.0.1".to_string(),
+            port: 80,
+            rest_api_port: 8080,
+            version: Version::new(0, "test".to_string()),
+            standby_identity: None,
+        }
+    }
+
+    #[test]
+    fn test_io_chaos_spec() {
+        let validator = node("val1-aptos-validator-validator");
+        assert_eq!(
+            validator.io_chaos_name(),
+            "forge-io-val1-aptos-validator-validator"
+        );
+        assert_eq!(
+            validator.io_chaos_spec(100, Duration::from_millis(5)),
+            json!({
+                "action": "latency",
+                "mode": "all",
+                "selector": { "pods": { "default": ["val1-aptos-validator-validator-0"] } },
+                "containerNames": ["validator"],
+                "volumePath": "/opt/aptos/data",
+                "path": "/opt/aptos/data/**/*",
+                // 100 IOPS take 10ms per operation, more than the latency
+                "delay": "10000us",
+                "percent": 100,
+            })
+        );
+
+        let fullnode = node("val1-aptos-validator-fullnode-e2");
+        let spec = fullnode.io_chaos_spec(0, Duration::from_millis(5));
+        assert_eq!(
+            spec["selector"],
+            json!({ "pods": { "default": ["val1-aptos-validator-fullnode-e2-0"] } })
+        );
+        assert_eq!(spec["containerNames"], json!(["fullnode"]));
+        assert_eq!(spec["delay"], json!("5000us"));
+    }
+}
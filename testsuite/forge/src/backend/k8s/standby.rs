@@ -0,0 +1,237 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Result, ValidatorCandidate};
+use anyhow::format_err;
+use aptos_global_constants::HANDSHAKE_VERSION;
+use aptos_sdk::{
+    crypto::{
+        ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+        x25519, PrivateKey, ValidCryptoMaterialStringExt,
+    },
+    types::{
+        network_address::NetworkAddress, transaction::authenticator::AuthenticationKey, AccountKey,
+        LocalAccount, PeerId,
+    },
+};
+use k8s_openapi::{api::core::v1::Secret, ByteString};
+use kube::{
+    api::{Api, ListParams},
+    client::Client as K8sClient,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::TryFrom,
+    str::{self, FromStr},
+};
+
+const OWNER_KEY: &str = "owner.key";
+const CONSENSUS_KEY: &str = "consensus.pub";
+const VALIDATOR_NETWORK_KEY: &str = "validator_network.pub";
+const FULLNODE_NETWORK_KEY: &str = "fullnode_network.pub";
+
+/// The identity of a standby validator, provisioned by the genesis job of the testnet without
+/// putting it in the validator set. It's kept in the secret
+/// `val{node_id}-aptos-validator-standby-e{era}`, with the hex encoded keys generated for it.
+pub struct StandbyIdentity {
+    owner_key: Ed25519PrivateKey,
+    consensus_key: Ed25519PublicKey,
+    validator_network_key: x25519::PublicKey,
+    fullnode_network_key: x25519::PublicKey,
+}
+
+impl StandbyIdentity {
+    fn from_secret_data(data: &BTreeMap<String, ByteString>) -> Result<Self> {
+        let field = |name: &str| -> Result<&str> {
+            let value = data
+                .get(name)
+                .ok_or_else(|| format_err!("No {} in the standby validator secret", name))?;
+            Ok(str::from_utf8(&value.0)?.trim())
+        };
+        Ok(Self {
+            owner_key: Ed25519PrivateKey::from_encoded_string(field(OWNER_KEY)?)?,
+            consensus_key: Ed25519PublicKey::from_encoded_string(field(CONSENSUS_KEY)?)?,
+            validator_network_key: x25519::PublicKey::from_encoded_string(field(
+                VALIDATOR_NETWORK_KEY,
+            )?)?,
+            fullnode_network_key: x25519::PublicKey::from_encoded_string(field(
+                FULLNODE_NETWORK_KEY,
+            )?)?,
+        })
+    }
+
+    /// The account of the owner of the validator, which is also the peer id of its nodes
+    pub fn peer_id(&self) -> PeerId {
+        AuthenticationKey::ed25519(&self.owner_key.public_key()).derived_address()
+    }
+
+    /// The owner account, with a sequence number of 0
+    pub fn owner_account(&self) -> Result<LocalAccount> {
+        // The key isn't Clone, so the account gets a copy made from its bytes
+        let key = Ed25519PrivateKey::try_from(self.owner_key.to_bytes().as_ref())?;
+        Ok(LocalAccount::new(
+            self.peer_id(),
+            AccountKey::from_private_key(key),
+            0,
+        ))
+    }
+
+    /// The consensus key and the network addresses of the validator `val{node_id}`, as the
+    /// genesis job sets them for the validators it puts in the validator set: the load balancers
+    /// of its validator and of its first fullnode
+    pub fn candidate(&self, node_id: usize) -> Result<ValidatorCandidate> {
+        let network_addresses = |address: String, key: x25519::PublicKey| -> Result<Vec<u8>> {
+            let address = NetworkAddress::from_str(&address)?;
+            Ok(bcs::to_bytes(&vec![
+                address.append_prod_protos(key, HANDSHAKE_VERSION)
+            ])?)
+        };
+        Ok(ValidatorCandidate {
+            consensus_pubkey: self.consensus_key.clone(),
+            validator_network_addresses: network_addresses(
+                format!("/dns4/val{}-aptos-validator-validator-lb/tcp/6180", node_id),
+                self.validator_network_key,
+            )?,
+            fullnode_network_addresses: network_addresses(
+                format!("/dns4/val{}-aptos-validator-fullnode-lb/tcp/6182", node_id),
+                self.fullnode_network_key,
+            )?,
+        })
+    }
+}
+
+/// Loads the identities of the standby validators the genesis job of `era` provisioned, by node
+/// id
+pub(crate) async fn load_standby_identities(
+    kube_client: &K8sClient,
+    era: &str,
+) -> Result<HashMap<usize, StandbyIdentity>> {
+    let secrets: Api<Secret> = Api::namespaced(kube_client.clone(), "default");
+    let mut identities = HashMap::new();
+    for secret in secrets.list(&ListParams::default()).await?.items {
+        let name = secret.metadata.name.unwrap_or_default();
+        if let Some(node_id) = parse_standby_secret_name(&name, era) {
+            let identity = StandbyIdentity::from_secret_data(&secret.data.unwrap_or_default())
+                .map_err(|e| format_err!("Invalid standby validator secret {}: {}", name, e))?;
+            identities.insert(node_id, identity);
+        }
+    }
+    Ok(identities)
+}
+
+/// The node id of the standby validator of the secret `val{node_id}-aptos-validator-standby-e{era}`,
+/// none for other secrets and other eras
+fn parse_standby_secret_name(name: &str, era: &str) -> Option<usize> {
+    let release = name.strip_suffix(&format!("-aptos-validator-standby-e{}", era))?;
+    release.strip_prefix("val")?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_sdk::{crypto::Uniform, types::network_address::Protocol};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn hex_upper(bytes: &[u8]) -> ByteString {
+        // as the operational tool encodes keys
+        let hex: String = bytes.iter().map(|b| format!("{:02X}", b)).collect();
+        ByteString(hex.into_bytes())
+    }
+
+    fn secret_data() -> (BTreeMap<String, ByteString>, Ed25519PrivateKey) {
+        let mut rng = StdRng::from_seed([7u8; 32]);
+        let owner_key = Ed25519PrivateKey::generate(&mut rng);
+        let consensus_key = Ed25519PrivateKey::generate(&mut rng);
+        let validator_network_key = x25519::PrivateKey::generate(&mut rng);
+        let fullnode_network_key = x25519::PrivateKey::generate(&mut rng);
+        let mut data = BTreeMap::new();
+        data.insert(OWNER_KEY.to_string(), hex_upper(&owner_key.to_bytes()));
+        data.insert(
+            CONSENSUS_KEY.to_string(),
+            hex_upper(&consensus_key.public_key().to_bytes()),
+        );
+        data.insert(
+            VALIDATOR_NETWORK_KEY.to_string(),
+            hex_upper(&validator_network_key.public_key().to_bytes()),
+        );
+        data.insert(
+            FULLNODE_NETWORK_KEY.to_string(),
+            hex_upper(&fullnode_network_key.public_key().to_bytes()),
+        );
+        (data, owner_key)
+    }
+
+    #[test]
+    fn test_parse_standby_secret_name() {
+        assert_eq!(
+            parse_standby_secret_name("val4-aptos-validator-standby-efg123", "fg123"),
+            Some(4)
+        );
+        assert_eq!(
+            parse_standby_secret_name("val12-aptos-validator-standby-efg123", "fg123"),
+            Some(12)
+        );
+        // secrets of a previous genesis
+        assert_eq!(
+            parse_standby_secret_name("val4-aptos-validator-standby-efg122", "fg123"),
+            None
+        );
+        assert_eq!(
+            parse_standby_secret_name("aptos-testnet-faucet-efg123", "fg123"),
+            None
+        );
+        assert_eq!(
+            parse_standby_secret_name("valx-aptos-validator-standby-efg123", "fg123"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_owner_account_is_peer_id() {
+        let (data, owner_key) = secret_data();
+        let identity = StandbyIdentity::from_secret_data(&data).unwrap();
+        let expected = AuthenticationKey::ed25519(&owner_key.public_key()).derived_address();
+        assert_eq!(identity.peer_id(), expected);
+
+        let account = identity.owner_account().unwrap();
+        assert_eq!(account.address(), expected);
+        assert_eq!(account.sequence_number(), 0);
+        assert_eq!(account.public_key(), &owner_key.public_key());
+    }
+
+    #[test]
+    fn test_candidate_addresses() {
+        let (data, _) = secret_data();
+        let identity = StandbyIdentity::from_secret_data(&data).unwrap();
+        let candidate = identity.candidate(3).unwrap();
+        assert_eq!(candidate.consensus_pubkey, identity.consensus_key);
+
+        let check = |bytes: &[u8], dns: &str, port: u16, key: x25519::PublicKey| {
+            let addresses: Vec<NetworkAddress> = bcs::from_bytes(bytes).unwrap();
+            assert_eq!(addresses.len(), 1);
+            let protocols = addresses[0].as_slice();
+            assert_eq!(protocols[0], Protocol::Dns4(dns.parse().unwrap()));
+            assert_eq!(protocols[1], Protocol::Tcp(port));
+            assert_eq!(addresses[0].find_noise_proto(), Some(key));
+        };
+        check(
+            &candidate.validator_network_addresses,
+            "val3-aptos-validator-validator-lb",
+            6180,
+            identity.validator_network_key,
+        );
+        check(
+            &candidate.fullnode_network_addresses,
+            "val3-aptos-validator-fullnode-lb",
+            6182,
+            identity.fullnode_network_key,
+        );
+    }
+
+    #[test]
+    fn test_missing_key() {
+        let (mut data, _) = secret_data();
+        data.remove(VALIDATOR_NETWORK_KEY);
+        assert!(StandbyIdentity::from_secret_data(&data).is_err());
+    }
+}
@@ -2,71 +2,144 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    backend::k8s::node::K8sNode, create_k8s_client, query_sequence_numbers, remove_helm_release,
-    set_validator_image_tag, ChainInfo, FullNode, Node, Result, Swarm, Validator, Version,
+    apply_network_chaos,
+    backend::k8s::{
+        cluster_helper::fullnode_group_name,
+        cost::list_node_pools,
+        emitter_accounts::{load_emitter_account_keys, save_emitter_account_keys},
+        node::K8sNode,
+        standby::{load_standby_identities, StandbyIdentity},
+    },
+    create_k8s_client, delete_network_chaos, delete_pod, install_standby_validator,
+    query_sequence_numbers, remove_helm_release, service_cluster_ip, set_validator_image_tag,
+    upload_triage_bundle, AccountPool, ChainInfo, ChaosDriver, ChaosSpec, CloudProvider, FullNode,
+    Node, NodeEnvironment, NodeExt, NodePoolUsage, Result, Swarm, SwarmAbandoner, TestReport,
+    Validator, Version,
 };
 use ::aptos_logger::*;
 use anyhow::{anyhow, bail, format_err};
-use aptos_config::config::NodeConfig;
+use aptos_config::{
+    config::{Identity, NodeConfig},
+    network_id::NetworkId,
+};
+use aptos_rest_client::{aptos_api_types::HexEncodedBytes, Client as RestClient};
 use aptos_sdk::{
-    crypto::ed25519::Ed25519PrivateKey,
+    crypto::{
+        ed25519::{Ed25519PrivateKey, ED25519_PRIVATE_KEY_LENGTH},
+        HashValue,
+    },
     types::{
+        account_config::aptos_root_address,
         chain_id::{ChainId, NamedChain},
+        network_address::{NetworkAddress, Protocol},
         AccountKey, LocalAccount, PeerId,
     },
 };
-use k8s_openapi::api::core::v1::Service;
+use futures::future::join_all;
+use k8s_openapi::api::core::v1::{ConfigMap, Service};
 use kube::{
     api::{Api, ListParams},
     client::Client as K8sClient,
 };
-use std::{collections::HashMap, convert::TryFrom, env, process::Command, str, sync::Arc};
+use rand::rngs::OsRng;
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    env,
+    process::Command,
+    str::{self, FromStr},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
 use tokio::time::Duration;
+use url::Url;
 
 const JSON_RPC_PORT: u32 = 80;
 const REST_API_PORT: u32 = 80;
 const VALIDATOR_LB: &str = "validator-validator-lb";
-const FULLNODES_LB: &str = "validator-fullnode-lb";
+const CURRENT_ROUND_METRIC: &str = "aptos_consensus_current_round";
+
+/// Where the account the tests are funded from comes from
+pub enum RootAccountSource {
+    /// The root account of the chain, with its private key
+    RootKey([u8; ED25519_PRIVATE_KEY_LENGTH]),
+    /// A new account, which the faucet at `url` funds with `coins`, for networks whose root key
+    /// can't be exported. The tests can spend its coins but, as it isn't the root account, can't
+    /// mint or reconfigure with it.
+    Faucet { url: Url, coins: u64 },
+}
 
 pub struct K8sSwarm {
     validators: HashMap<PeerId, K8sNode>,
     fullnodes: HashMap<PeerId, K8sNode>,
     root_account: LocalAccount,
+    // The faucet funded a new root account, whose key changes on every run
+    funded_by_faucet: bool,
     kube_client: K8sClient,
     cluster_name: String,
+    cloud_provider: CloudProvider,
     helm_repo: String,
     versions: Arc<HashMap<Version, String>>,
+    triage_bucket: Option<String>,
+    // The injected chaos and the name of the NetworkChaos resource injecting it
+    network_chaos: Vec<(ChaosSpec, String)>,
+    next_chaos_id: usize,
+    // The era of the chain, which a new genesis changes
+    era: String,
+    // The identities the genesis provisioned for the validators `add_validator` adds, by node id
+    standby_identities: HashMap<usize, StandbyIdentity>,
+    // The environment of the nodes of the run, which the added validators get too
+    node_environment: NodeEnvironment,
     pub chain_id: ChainId,
+    account_pool: AccountPool,
 }
 
 impl K8sSwarm {
     pub async fn new(
-        root_key: &[u8],
+        root_account: &RootAccountSource,
         cluster_name: &str,
         helm_repo: &str,
         image_tag: &str,
         base_image_tag: &str,
         init_image_tag: &str,
         era: &str,
+        cloud_provider: CloudProvider,
+        triage_bucket: Option<String>,
+        node_environment: NodeEnvironment,
     ) -> Result<Self> {
         let kube_client = create_k8s_client().await;
         let validators = get_validators(kube_client.clone(), init_image_tag).await?;
         let fullnodes = get_fullnodes(kube_client.clone(), init_image_tag, era).await?;
+        let standby_identities = load_standby_identities(&kube_client, era).await?;
 
         let client = validators.values().next().unwrap().rest_client();
-        let key = load_root_key(root_key);
-        let account_key = AccountKey::from_private_key(key);
-        let address = aptos_sdk::types::account_config::aptos_root_address();
-        let sequence_number = query_sequence_numbers(&client, &[address])
-            .await
-            .map_err(|e| {
-                format_err!(
-                    "query_sequence_numbers on {:?} for dd account failed: {}",
-                    client,
-                    e
+        let (root_account, funded_by_faucet) = match root_account {
+            RootAccountSource::RootKey(root_key) => {
+                let key = load_root_key(root_key);
+                let account_key = AccountKey::from_private_key(key);
+                let address = aptos_root_address();
+                let sequence_number =
+                    query_sequence_numbers(&client, &[address])
+                        .await
+                        .map_err(|e| {
+                            format_err!(
+                                "query_sequence_numbers on {:?} for dd account failed: {}",
+                                client,
+                                e
+                            )
+                        })?[0];
+                (
+                    LocalAccount::new(address, account_key, sequence_number),
+                    false,
                 )
-            })?[0];
-        let root_account = LocalAccount::new(address, account_key, sequence_number);
+            }
+            RootAccountSource::Faucet { url, coins } => {
+                (fund_from_faucet(&client, url, *coins).await?, true)
+            }
+        };
 
         let mut versions = HashMap::new();
         let base_version = Version::new(0, base_image_tag.to_string());
@@ -78,11 +151,20 @@ impl K8sSwarm {
             validators,
             fullnodes,
             root_account,
+            funded_by_faucet,
             kube_client,
             chain_id: ChainId::new(NamedChain::DEVNET.id()),
+            account_pool: AccountPool::new(),
             cluster_name: cluster_name.to_string(),
+            cloud_provider,
             helm_repo: helm_repo.to_string(),
             versions: Arc::new(versions),
+            triage_bucket,
+            network_chaos: vec![],
+            next_chaos_id: 0,
+            era: era.to_string(),
+            standby_identities,
+            node_environment,
         })
     }
 
@@ -95,6 +177,14 @@ impl K8sSwarm {
             .to_string()
     }
 
+    /// Names of the pods of the validators, which chaos is injected into
+    fn validator_pods(&self) -> HashMap<PeerId, String> {
+        self.validators
+            .iter()
+            .map(|(id, validator)| (*id, format!("{}-0", validator.sts_name)))
+            .collect()
+    }
+
     #[allow(dead_code)]
     fn get_kube_client(&self) -> K8sClient {
         self.kube_client.clone()
@@ -105,7 +195,9 @@ impl K8sSwarm {
 impl Swarm for K8sSwarm {
     async fn health_check(&mut self) -> Result<()> {
         let nodes = self.validators.values().collect();
-        let unhealthy_nodes = nodes_healthcheck(nodes).await.unwrap();
+        let unhealthy_nodes = unhealthy_node_names(
+            &nodes_healthcheck(nodes, LivenessCriterion::LedgerVersionAdvancing).await,
+        );
         if !unhealthy_nodes.is_empty() {
             bail!("Unhealthy nodes: {:?}", unhealthy_nodes)
         }
@@ -168,8 +260,42 @@ impl Swarm for K8sSwarm {
         self.fullnodes.get_mut(&id).map(|v| v as &mut dyn FullNode)
     }
 
-    fn add_validator(&mut self, _version: &Version, _template: NodeConfig) -> Result<PeerId> {
-        todo!()
+    // Installs the release of a standby validator, with the identity the genesis provisioned for
+    // it. Its config is rendered by the chart, so the template is ignored.
+    fn add_validator(&mut self, version: &Version, _template: NodeConfig) -> Result<PeerId> {
+        let image_tag = self
+            .versions
+            .get(version)
+            .cloned()
+            .ok_or_else(|| anyhow!("Invalid version: {:?}", version))?;
+        let node_id = *self.standby_identities.keys().min().ok_or_else(|| {
+            anyhow!("No standby validator left, raise the standby validator count of the test")
+        })?;
+        install_standby_validator(
+            node_id,
+            &self.era,
+            &image_tag,
+            &self.helm_repo,
+            &self.node_environment,
+        )?;
+        let dns = format!("val{}-aptos-{}", node_id, VALIDATOR_LB);
+        let ip = service_cluster_ip(&dns)?;
+        let identity = self.standby_identities.remove(&node_id).unwrap();
+        let node = K8sNode {
+            name: format!("val{}", node_id),
+            sts_name: format!("val{}-aptos-validator-validator", node_id),
+            peer_id: identity.peer_id(),
+            node_id,
+            ip,
+            port: JSON_RPC_PORT,
+            rest_api_port: REST_API_PORT,
+            dns,
+            version: version.clone(),
+            standby_identity: Some(identity),
+        };
+        let peer_id = node.peer_id();
+        self.validators.insert(peer_id, node);
+        Ok(peer_id)
     }
 
     fn remove_validator(&mut self, id: PeerId) -> Result<()> {
@@ -188,13 +314,33 @@ impl Swarm for K8sSwarm {
         Box::new(self.versions.keys().cloned())
     }
 
+    async fn inject_chaos(&mut self, chaos: ChaosSpec) -> Result<()> {
+        chaos.validate()?;
+        let spec = network_chaos_spec(&chaos, &self.validator_pods())?;
+        let name = format!("forge-chaos-{}", self.next_chaos_id);
+        self.next_chaos_id += 1;
+        apply_network_chaos(&name, spec)?;
+        self.network_chaos.push((chaos, name));
+        Ok(())
+    }
+
+    async fn remove_chaos(&mut self, chaos: ChaosSpec) -> Result<()> {
+        let index = self
+            .network_chaos
+            .iter()
+            .position(|(injected, _)| *injected == chaos)
+            .ok_or_else(|| anyhow!("Chaos was never injected: {:?}", chaos))?;
+        let (_, name) = self.network_chaos.remove(index);
+        delete_network_chaos(&name)
+    }
+
     fn chain_info(&mut self) -> ChainInfo<'_> {
         let rest_api_url = self.get_rest_api_url();
         ChainInfo::new(&mut self.root_account, rest_api_url, self.chain_id)
     }
 
     // Returns env CENTRAL_LOGGING_ADDRESS if present (without timestamps)
-    // otherwise returns a kubectl logs command to retrieve the logs manually
+    // otherwise returns the commands to retrieve the logs manually
     fn logs_location(&mut self) -> String {
         if let Ok(central_logging_address) = std::env::var("CENTRAL_LOGGING_ADDRESS") {
             central_logging_address
@@ -204,11 +350,175 @@ impl Swarm for K8sSwarm {
                 .expect("failed to get pod hostname");
             let hostname = String::from_utf8(hostname_output.stdout).unwrap();
             format!(
-                "aws eks --region us-west-2 update-kubeconfig --name {} && kubectl logs {}",
-                &self.cluster_name, hostname
+                "{} && kubectl logs {}",
+                self.cloud_provider.kubeconfig_command(&self.cluster_name),
+                hostname
             )
         }
     }
+
+    // The keys are encrypted with the key of the root account, which a faucet funded one doesn't
+    // keep from one run to the next
+    async fn emitter_account_keys(&self) -> Result<Vec<Ed25519PrivateKey>> {
+        if self.funded_by_faucet {
+            return Ok(vec![]);
+        }
+        load_emitter_account_keys(
+            &self.kube_client,
+            self.root_account.private_key(),
+            &self.era,
+        )
+        .await
+    }
+
+    async fn save_emitter_accounts(&mut self, accounts: &[LocalAccount]) -> Result<()> {
+        if self.funded_by_faucet {
+            return Ok(());
+        }
+        save_emitter_account_keys(
+            &self.kube_client,
+            self.root_account.private_key(),
+            &self.era,
+            accounts,
+        )
+        .await
+    }
+
+    fn account_pool(&mut self) -> &mut AccountPool {
+        &mut self.account_pool
+    }
+
+    fn collect_failure_artifacts(&mut self, report: &TestReport) -> Result<String> {
+        self.abandoner()(report)
+    }
+
+    // The nodes of the cluster are left running, they're scaled down once forge exits
+    fn abandoner(&mut self) -> SwarmAbandoner {
+        let triage_bucket = self.triage_bucket.clone();
+        let cluster_name = self.cluster_name.clone();
+        let logs_location = self.logs_location();
+        Box::new(move |report| match triage_bucket {
+            Some(bucket) => {
+                let uri = upload_triage_bundle(&bucket, &cluster_name, report)?;
+                Ok(format!("{} (download with `aws s3 cp {} .`)", uri, uri))
+            }
+            None => Ok(logs_location),
+        })
+    }
+
+    fn chaos_driver(&self) -> Result<Box<dyn ChaosDriver>> {
+        Ok(Box::new(K8sChaosDriver {
+            validator_pods: self.validator_pods(),
+            full_node_pods: self
+                .fullnodes
+                .iter()
+                .map(|(id, full_node)| (*id, format!("{}-0", full_node.sts_name)))
+                .collect(),
+            next_chaos_id: AtomicUsize::new(0),
+            network_chaos: Mutex::new(vec![]),
+        }))
+    }
+
+    async fn node_pools(&self) -> Result<Vec<NodePoolUsage>> {
+        list_node_pools(self.kube_client.clone()).await
+    }
+}
+
+/// Returns the spec of the chaos-mesh NetworkChaos injecting the chaos between the pods of the
+/// validators, named in `validator_pods`
+fn network_chaos_spec(
+    chaos: &ChaosSpec,
+    validator_pods: &HashMap<PeerId, String>,
+) -> Result<Value> {
+    let selector = |peer_ids: &[PeerId]| -> Result<Value> {
+        let pods = peer_ids
+            .iter()
+            .map(|id| {
+                validator_pods
+                    .get(id)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("Invalid id: {}", id))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(json!({ "pods": { "default": pods } }))
+    };
+
+    Ok(match chaos {
+        ChaosSpec::NetworkPartition { group_a, group_b } => json!({
+            "action": "partition",
+            "mode": "all",
+            "selector": selector(group_a)?,
+            "direction": "both",
+            "target": { "mode": "all", "selector": selector(group_b)? },
+        }),
+        ChaosSpec::NetworkDelay { targets, latency } => json!({
+            "action": "delay",
+            "mode": "all",
+            "selector": selector(targets)?,
+            "delay": { "latency": format!("{}ms", latency.as_millis()) },
+        }),
+        ChaosSpec::PacketLoss { targets, loss_pct } => json!({
+            "action": "loss",
+            "mode": "all",
+            "selector": selector(targets)?,
+            "loss": { "loss": loss_pct.to_string() },
+        }),
+    })
+}
+
+/// Applies the chaos of a `ChaosSchedule` to the pods of a k8s swarm. Its network chaos is named
+/// apart from that of the swarm, and killed fullnodes are brought back up by their statefulset.
+struct K8sChaosDriver {
+    validator_pods: HashMap<PeerId, String>,
+    full_node_pods: HashMap<PeerId, String>,
+    next_chaos_id: AtomicUsize,
+    network_chaos: Mutex<Vec<(ChaosSpec, String)>>,
+}
+
+#[async_trait::async_trait]
+impl ChaosDriver for K8sChaosDriver {
+    fn validators(&self) -> Vec<PeerId> {
+        self.validator_pods.keys().copied().collect()
+    }
+
+    fn full_nodes(&self) -> Vec<PeerId> {
+        self.full_node_pods.keys().copied().collect()
+    }
+
+    async fn kill_full_node(&self, id: PeerId) -> Result<()> {
+        let pod = self
+            .full_node_pods
+            .get(&id)
+            .ok_or_else(|| anyhow!("Invalid id: {}", id))?;
+        delete_pod(pod)
+    }
+
+    async fn inject_chaos(&self, chaos: &ChaosSpec) -> Result<()> {
+        chaos.validate()?;
+        let spec = network_chaos_spec(chaos, &self.validator_pods)?;
+        let name = format!(
+            "forge-scheduled-chaos-{}",
+            self.next_chaos_id.fetch_add(1, Ordering::Relaxed)
+        );
+        apply_network_chaos(&name, spec)?;
+        self.network_chaos
+            .lock()
+            .unwrap()
+            .push((chaos.clone(), name));
+        Ok(())
+    }
+
+    async fn remove_chaos(&self, chaos: &ChaosSpec) -> Result<()> {
+        let name = {
+            let mut network_chaos = self.network_chaos.lock().unwrap();
+            let index = network_chaos
+                .iter()
+                .position(|(injected, _)| injected == chaos)
+                .ok_or_else(|| anyhow!("Chaos was never injected: {:?}", chaos))?;
+            network_chaos.remove(index).1
+        };
+        delete_network_chaos(&name)
+    }
 }
 
 pub(crate) fn k8s_retry_strategy() -> impl Iterator<Item = Duration> {
@@ -249,28 +559,31 @@ pub(crate) async fn get_validators(
     image_tag: &str,
 ) -> Result<HashMap<PeerId, K8sNode>> {
     let services = list_services(client).await?;
+    let peer_ids = get_validator_peer_ids(&services).await?;
     let validators = services
         .into_iter()
         .filter(|s| s.name.contains(VALIDATOR_LB))
-        .map(|s| {
+        .filter_map(|s| {
             let node_id = parse_node_id(&s.name).expect("error to parse node id");
+            let peer_id = *peer_ids.get(&node_id)?;
             let node = K8sNode {
                 name: format!("val{}", node_id),
                 sts_name: format!("val{}-aptos-validator-validator", node_id),
-                // TODO: fetch this from running node
-                peer_id: PeerId::random(),
+                peer_id,
                 node_id,
                 ip: s.host_ip.clone(),
                 port: JSON_RPC_PORT,
                 rest_api_port: REST_API_PORT,
                 dns: s.name,
                 version: Version::new(0, image_tag.to_string()),
+                standby_identity: None,
             };
-            (node.peer_id(), node)
+            Some((node.peer_id(), node))
         })
         .collect::<HashMap<_, _>>();
     let all_nodes = validators.values().collect();
-    let unhealthy_nodes = nodes_healthcheck(all_nodes).await.unwrap();
+    let unhealthy_nodes =
+        unhealthy_node_names(&nodes_healthcheck(all_nodes, LivenessCriterion::Reachable).await);
     let mut health_nodes = HashMap::new();
     for node in validators {
         if !unhealthy_nodes.contains(&node.1.name) {
@@ -286,31 +599,194 @@ pub(crate) async fn get_fullnodes(
     image_tag: &str,
     era: &str,
 ) -> Result<HashMap<PeerId, K8sNode>> {
+    let group_peer_ids = get_fullnode_group_peer_ids(client.clone()).await?;
     let services = list_services(client).await?;
-    let fullnodes = services
-        .into_iter()
-        .filter(|s| s.name.contains(FULLNODES_LB))
-        .map(|s| {
-            let node_id = parse_node_id(&s.name).expect("error to parse node id");
-            let node = K8sNode {
-                name: format!("val{}", node_id),
-                sts_name: format!("val{}-aptos-validator-fullnode-e{}", node_id, era),
-                // TODO: fetch this from running node
-                peer_id: PeerId::random(),
-                node_id,
-                ip: s.host_ip.clone(),
-                port: JSON_RPC_PORT,
-                rest_api_port: REST_API_PORT,
-                dns: s.name,
-                version: Version::new(0, image_tag.to_string()),
-            };
-            (node.peer_id(), node)
-        })
-        .collect::<HashMap<_, _>>();
+    let validator_peer_ids = get_validator_peer_ids(&services).await?;
+    let mut fullnodes = HashMap::new();
+    for s in services {
+        let (node_id, group) = match parse_fullnode_lb(&s.name) {
+            Some(fullnode) => fullnode,
+            None => continue,
+        };
+        let (name, peer_id) = if group == fullnode_group_name(0) {
+            // The first fullnode shares the identity of its validator
+            match validator_peer_ids.get(&node_id) {
+                Some(peer_id) => (format!("val{}", node_id), *peer_id),
+                None => continue,
+            }
+        } else {
+            let peer_id = group_peer_ids
+                .get(&(node_id, group.clone()))
+                .copied()
+                .ok_or_else(|| {
+                    format_err!(
+                        "The fullnode group {} of val{} has no identity of its own",
+                        group,
+                        node_id
+                    )
+                })?;
+            (format!("val{}-{}", node_id, group), peer_id)
+        };
+        let node = K8sNode {
+            name,
+            sts_name: format!("val{}-aptos-validator-{}-e{}", node_id, group, era),
+            peer_id,
+            node_id,
+            ip: s.host_ip.clone(),
+            port: JSON_RPC_PORT,
+            rest_api_port: REST_API_PORT,
+            dns: s.name,
+            version: Version::new(0, image_tag.to_string()),
+            standby_identity: None,
+        };
+        fullnodes.insert(node.peer_id(), node);
+    }
 
     Ok(fullnodes)
 }
 
+/// Peer ids of the fullnode groups with an identity of their own, by node id of their validator
+/// and group, read back from the configs the chart renders for them in the config map
+/// `val{node_id}-aptos-validator` of the release
+async fn get_fullnode_group_peer_ids(
+    client: K8sClient,
+) -> Result<HashMap<(usize, String), PeerId>> {
+    let config_maps: Api<ConfigMap> = Api::namespaced(client, "default");
+    let mut peer_ids = HashMap::new();
+    for config_map in config_maps.list(&ListParams::default()).await?.items {
+        let node_id = match config_map
+            .metadata
+            .name
+            .as_deref()
+            .and_then(|name| name.strip_suffix("-aptos-validator"))
+            .and_then(|release| release.strip_prefix("val"))
+            .and_then(|node_id| node_id.parse::<usize>().ok())
+        {
+            Some(node_id) => node_id,
+            None => continue,
+        };
+        for (key, config) in config_map.data.unwrap_or_default() {
+            if let Some(group) = key
+                .strip_prefix("fullnode-")
+                .and_then(|key| key.strip_suffix(".yaml"))
+            {
+                let peer_id = public_network_peer_id(&config)
+                    .map_err(|e| format_err!("Invalid config {} of val{}: {}", key, node_id, e))?;
+                peer_ids.insert((node_id, group.to_string()), peer_id);
+            }
+        }
+    }
+    Ok(peer_ids)
+}
+
+/// The peer id of the identity a node config sets on the public network
+fn public_network_peer_id(config: &str) -> Result<PeerId> {
+    let config: NodeConfig = serde_yaml::from_str(config)?;
+    let network = config
+        .full_node_networks
+        .iter()
+        .find(|network| network.network_id == NetworkId::Public)
+        .ok_or_else(|| format_err!("No public network"))?;
+    match &network.identity {
+        Identity::FromConfig(identity) => Ok(identity.peer_id),
+        _ => bail!("No identity in the config of the public network"),
+    }
+}
+
+/// The node id of the validator and the fullnode group of the load balancer of a fullnode, named
+/// `val{node_id}-aptos-validator-{group}-lb`, none for other services
+fn parse_fullnode_lb(name: &str) -> Option<(usize, String)> {
+    let (release, group) = name.strip_suffix("-lb")?.split_once("-aptos-validator-")?;
+    if !group.starts_with("fullnode") {
+        return None;
+    }
+    let node_id = release.strip_prefix("val")?.parse().ok()?;
+    Some((node_id, group.to_string()))
+}
+
+/// Peer ids of the validators, by node id, read from the validator set on chain. The validators
+/// identify with the account the genesis registered them under, along with the address of their
+/// load balancer. Validators which left the validator set can't be told apart from other nodes
+/// anymore, and are left out with a warning.
+async fn get_validator_peer_ids(services: &[KubeService]) -> Result<HashMap<usize, PeerId>> {
+    let validator_lbs = services
+        .iter()
+        .filter(|s| s.name.contains(VALIDATOR_LB))
+        .collect::<Vec<_>>();
+    let rest_urls = validator_lbs
+        .iter()
+        .map(|lb| Url::parse(&format!("http://{}:{}", lb.host_ip, REST_API_PORT)))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    // Any validator which is up serves the validator set
+    let validator_set = aptos_retrier::retry_async(k8s_retry_strategy(), || {
+        let rest_urls = rest_urls.clone();
+        Box::pin(async move {
+            let mut last_error = format_err!("No validator load balancer found");
+            for url in rest_urls {
+                match RestClient::new(url)
+                    .get_account_resource(aptos_root_address(), "0x1::Stake::ValidatorSet")
+                    .await
+                {
+                    Ok(resource) => match resource.into_inner() {
+                        Some(resource) => return Ok(resource.data),
+                        None => last_error = format_err!("No validator set on chain"),
+                    },
+                    Err(e) => last_error = e,
+                }
+            }
+            Err(last_error)
+        })
+    })
+    .await?;
+    let peer_ids_by_lb = parse_validator_set_peer_ids(&validator_set)?;
+
+    let mut peer_ids = HashMap::new();
+    for lb in validator_lbs {
+        let node_id = parse_node_id(&lb.name)?;
+        match peer_ids_by_lb.get(&lb.name) {
+            Some(peer_id) => {
+                peer_ids.insert(node_id, *peer_id);
+            }
+            None => warn!(
+                "The validator of {} isn't in the validator set, and is left out",
+                lb.name
+            ),
+        }
+    }
+    Ok(peer_ids)
+}
+
+/// Accounts of the validators of the `0x1::Stake::ValidatorSet` resource, current and pending,
+/// by the host of their validator network address, which is the name of their load balancer
+fn parse_validator_set_peer_ids(validator_set: &Value) -> Result<HashMap<String, PeerId>> {
+    let mut peer_ids = HashMap::new();
+    for list in ["active_validators", "pending_active", "pending_inactive"] {
+        let validators = validator_set[list]
+            .as_array()
+            .ok_or_else(|| format_err!("Invalid validator set: {}", validator_set))?;
+        for info in validators {
+            let invalid = || format_err!("Invalid validator info: {}", info);
+            let peer_id = PeerId::from_hex_literal(info["addr"].as_str().ok_or_else(invalid)?)?;
+            let network_addresses = HexEncodedBytes::from_str(
+                info["config"]["network_address"]
+                    .as_str()
+                    .ok_or_else(invalid)?,
+            )?;
+            let network_addresses: Vec<NetworkAddress> =
+                bcs::from_bytes(&Vec::from(network_addresses))?;
+            for address in network_addresses {
+                if let Some(Protocol::Dns4(name)) = address.as_slice().first() {
+                    // The name may be qualified with the namespace
+                    let host = name.to_string();
+                    let lb = host.split('.').next().unwrap_or_default().to_string();
+                    peer_ids.insert(lb, peer_id);
+                }
+            }
+        }
+    }
+    Ok(peer_ids)
+}
+
 fn parse_node_id(s: &str) -> Result<usize> {
     let v = s.split('-').collect::<Vec<&str>>();
     if v.len() < 5 {
@@ -324,35 +800,284 @@ fn load_root_key(root_key_bytes: &[u8]) -> Ed25519PrivateKey {
     Ed25519PrivateKey::try_from(root_key_bytes).unwrap()
 }
 
-pub async fn nodes_healthcheck(nodes: Vec<&K8sNode>) -> Result<Vec<String>> {
-    let mut unhealthy_nodes = vec![];
-    for node in nodes {
-        let node_name = node.name().to_string();
-        println!("Attempting health check: {}", node_name);
-        // perform healthcheck with retry, returning unhealthy
-        let check = aptos_retrier::retry_async(k8s_retry_strategy(), || {
-            Box::pin(async move {
-                println!("Attempting health check: {}", node.name());
-                match node.rest_client().get_ledger_information().await {
-                    Ok(_) => {
-                        println!("Node {} healthy", node.name());
-                        Ok(())
-                    }
-                    Err(x) => {
-                        debug!("K8s Node {} unhealthy: {}", node.name(), &x);
-                        Err(x)
+/// Generates an account and has the faucet at `faucet_url` create it with `coins`, waiting for
+/// the transactions of the faucet to commit
+async fn fund_from_faucet(
+    client: &RestClient,
+    faucet_url: &Url,
+    coins: u64,
+) -> Result<LocalAccount> {
+    let account = LocalAccount::generate(&mut OsRng);
+    let mut url = faucet_url.join("mint")?;
+    url.query_pairs_mut()
+        .append_pair("address", &account.address().to_hex_literal())
+        .append_pair("amount", &coins.to_string());
+    let response = reqwest::Client::new().post(url).send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        bail!(
+            "The faucet at {} failed to fund the root account ({}): {}",
+            faucet_url,
+            status,
+            response.text().await.unwrap_or_default()
+        );
+    }
+    let hashes: Vec<HashValue> = response.json().await?;
+    for hash in hashes {
+        // The faucet doesn't return the expiration of its transactions, so waiting is only bound
+        // by the timeout of the client
+        client.wait_for_transaction_by_hash(hash, u64::MAX).await?;
+    }
+
+    // The faucet caps what it gives, so the tests may get less than they asked for
+    let balance = client
+        .get_account_balance(account.address())
+        .await?
+        .into_inner()
+        .get();
+    if balance < coins {
+        warn!(
+            "The faucet funded the root account with {} coins out of the {} requested",
+            balance, coins
+        );
+    }
+    info!(
+        "Root account {} funded by the faucet at {}",
+        account.address(),
+        faucet_url
+    );
+    Ok(account)
+}
+
+/// What a node has to show for its health check to pass
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LivenessCriterion {
+    /// The REST API of the node answers
+    Reachable,
+    /// The latest ledger version of the node advances
+    LedgerVersionAdvancing,
+    /// The consensus round of the node advances, which only validators have
+    RoundAdvancing,
+}
+
+/// The outcome of the health check of a node
+#[derive(Clone, Debug)]
+pub struct NodeHealth {
+    pub name: String,
+    pub peer_id: PeerId,
+    /// The latest ledger version of the node, when it answered
+    pub ledger_version: Option<u64>,
+    /// The consensus round of the node, when it answered and the criterion is `RoundAdvancing`
+    pub round: Option<u64>,
+    /// Why the node failed the health check, if it did
+    pub error: Option<String>,
+}
+
+impl NodeHealth {
+    pub fn is_healthy(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Checks the health of the nodes concurrently, retrying the check of each node with
+/// `k8s_retry_strategy` until it meets the liveness criterion
+pub async fn nodes_healthcheck(
+    nodes: Vec<&K8sNode>,
+    criterion: LivenessCriterion,
+) -> Vec<NodeHealth> {
+    let health = join_all(
+        nodes
+            .into_iter()
+            .map(|node| node_healthcheck(node, criterion)),
+    )
+    .await;
+    let unhealthy_nodes = unhealthy_node_names(&health);
+    if !unhealthy_nodes.is_empty() {
+        debug!("Unhealthy nodes: {:?}", unhealthy_nodes);
+    }
+
+    health
+}
+
+pub fn unhealthy_node_names(health: &[NodeHealth]) -> Vec<String> {
+    health
+        .iter()
+        .filter(|health| !health.is_healthy())
+        .map(|health| health.name.clone())
+        .collect()
+}
+
+async fn node_healthcheck(node: &K8sNode, criterion: LivenessCriterion) -> NodeHealth {
+    println!("Attempting health check: {}", node.name());
+    let mut health = NodeHealth {
+        name: node.name().to_string(),
+        peer_id: node.peer_id(),
+        ledger_version: None,
+        round: None,
+        error: None,
+    };
+    // The progress the node first answered with, which it has to advance from
+    let mut first_progress = None;
+    let mut delays = k8s_retry_strategy();
+    loop {
+        let error = match probe_node(node, criterion).await {
+            Ok((ledger_version, round)) => {
+                health.ledger_version = Some(ledger_version);
+                health.round = round;
+                let progress = match criterion {
+                    LivenessCriterion::Reachable => None,
+                    LivenessCriterion::LedgerVersionAdvancing => Some(ledger_version),
+                    LivenessCriterion::RoundAdvancing => round,
+                };
+                match (progress, first_progress) {
+                    (None, _) => break,
+                    (Some(progress), Some(first)) if progress > first => break,
+                    (Some(progress), _) => {
+                        first_progress.get_or_insert(progress);
+                        format!("{:?} failed: stuck at {}", criterion, progress)
                     }
                 }
-            })
-        })
-        .await;
-        if check.is_err() {
-            unhealthy_nodes.push(node_name);
+            }
+            Err(e) => {
+                debug!("K8s Node {} unhealthy: {}", node.name(), e);
+                e.to_string()
+            }
+        };
+        match delays.next() {
+            Some(delay) => tokio::time::sleep(delay).await,
+            None => {
+                health.error = Some(error);
+                return health;
+            }
         }
     }
-    if !unhealthy_nodes.is_empty() {
-        debug!("Unhealthy validators after cleanup: {:?}", unhealthy_nodes);
+    println!("Node {} healthy", node.name());
+
+    health
+}
+
+/// The latest ledger version of the node, and its consensus round if the criterion is about it
+async fn probe_node(node: &K8sNode, criterion: LivenessCriterion) -> Result<(u64, Option<u64>)> {
+    let ledger_version = node
+        .rest_client()
+        .get_ledger_information()
+        .await?
+        .into_inner()
+        .version;
+    let round = if criterion == LivenessCriterion::RoundAdvancing {
+        let round = node
+            .get_metric(CURRENT_ROUND_METRIC)
+            .await?
+            .ok_or_else(|| anyhow!("Node {} has no {}", node.name(), CURRENT_ROUND_METRIC))?;
+        Some(round as u64)
+    } else {
+        None
+    };
+
+    Ok((ledger_version, round))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_config::config::NetworkConfig;
+    use aptos_global_constants::HANDSHAKE_VERSION;
+    use aptos_sdk::{
+        crypto::{x25519, Uniform},
+        types::account_address::from_identity_public_key,
+    };
+
+    #[test]
+    fn test_parse_fullnode_lb() {
+        assert_eq!(
+            parse_fullnode_lb("val3-aptos-validator-fullnode-lb"),
+            Some((3, "fullnode".to_string()))
+        );
+        assert_eq!(
+            parse_fullnode_lb("val12-aptos-validator-fullnode2-lb"),
+            Some((12, "fullnode2".to_string()))
+        );
+        assert_eq!(parse_fullnode_lb("val3-aptos-validator-validator-lb"), None);
+        // the services of the groups, not their load balancers
+        assert_eq!(parse_fullnode_lb("val3-aptos-validator-fullnode"), None);
+        assert_eq!(parse_fullnode_lb("aptos-testnet-faucet-lb"), None);
+        assert_eq!(parse_fullnode_lb("valx-aptos-validator-fullnode-lb"), None);
     }
 
-    Ok(unhealthy_nodes)
+    fn validator_info(peer_id: PeerId, address: &str) -> Value {
+        let key = x25519::PrivateKey::generate(&mut OsRng).public_key();
+        let addresses = vec![NetworkAddress::from_str(address)
+            .unwrap()
+            .append_prod_protos(key, HANDSHAKE_VERSION)];
+        json!({
+            "addr": peer_id.to_hex_literal(),
+            "voting_power": "1",
+            "config": {
+                "consensus_pubkey": "0x00",
+                "network_address": HexEncodedBytes::from(bcs::to_bytes(&addresses).unwrap()),
+                "fullnode_address": "0x",
+            },
+        })
+    }
+
+    #[test]
+    fn test_parse_validator_set_peer_ids() {
+        let (val0, val1, val2) = (PeerId::random(), PeerId::random(), PeerId::random());
+        let validator_set = json!({
+            "consensus_scheme": 0,
+            "minimum_stake": "0",
+            "maximum_stake": "100",
+            "active_validators": [
+                validator_info(val0, "/dns4/val0-aptos-validator-validator-lb/tcp/6180"),
+                validator_info(
+                    val1,
+                    "/dns4/val1-aptos-validator-validator-lb.default.svc.cluster.local/tcp/6180",
+                ),
+            ],
+            "pending_inactive": [],
+            "pending_active": [
+                validator_info(val2, "/dns4/val2-aptos-validator-validator-lb/tcp/6180"),
+            ],
+        });
+        let peer_ids = parse_validator_set_peer_ids(&validator_set).unwrap();
+        assert_eq!(peer_ids.len(), 3);
+        assert_eq!(peer_ids["val0-aptos-validator-validator-lb"], val0);
+        assert_eq!(peer_ids["val1-aptos-validator-validator-lb"], val1);
+        assert_eq!(peer_ids["val2-aptos-validator-validator-lb"], val2);
+
+        // validators registered with an ip have no load balancer
+        let validator_set = json!({
+            "active_validators": [validator_info(val0, "/ip4/10.0.0.1/tcp/6180")],
+            "pending_inactive": [],
+            "pending_active": [],
+        });
+        assert!(parse_validator_set_peer_ids(&validator_set)
+            .unwrap()
+            .is_empty());
+        assert!(parse_validator_set_peer_ids(&json!({})).is_err());
+    }
+
+    #[test]
+    fn test_public_network_peer_id() {
+        let key = x25519::PrivateKey::generate(&mut OsRng);
+        let peer_id = from_identity_public_key(key.public_key());
+        let mut vfn_network = NetworkConfig::network_with_id(NetworkId::Vfn);
+        vfn_network.identity =
+            Identity::from_config(x25519::PrivateKey::generate(&mut OsRng), PeerId::random());
+        let mut public_network = NetworkConfig::network_with_id(NetworkId::Public);
+        public_network.identity = Identity::from_config(key, peer_id);
+        let mut config = NodeConfig::default();
+        config.full_node_networks = vec![vfn_network, public_network];
+        let rendered = serde_yaml::to_string(&config).unwrap();
+        assert_eq!(public_network_peer_id(&rendered).unwrap(), peer_id);
+
+        // no public network
+        config.full_node_networks.pop();
+        let rendered = serde_yaml::to_string(&config).unwrap();
+        assert!(public_network_peer_id(&rendered).is_err());
+        // no identity of its own on the public network
+        config.full_node_networks = vec![NetworkConfig::network_with_id(NetworkId::Public)];
+        let rendered = serde_yaml::to_string(&config).unwrap();
+        assert!(public_network_peer_id(&rendered).is_err());
+    }
 }
@@ -0,0 +1,183 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Result, TestReport};
+use anyhow::{bail, format_err};
+use serde_json::Value;
+use std::{
+    fs,
+    path::Path,
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tempfile::TempDir;
+
+const AWS_BIN: &str = "aws";
+const HELM_BIN: &str = "helm";
+const KUBECTL_BIN: &str = "kubectl";
+const TAR_BIN: &str = "tar";
+
+/// Collects everything needed to triage a failed run into a tarball and uploads it to
+/// `s3://<bucket>/forge/<cluster_name>/`, returning the uri of the uploaded object. The cluster
+/// is scaled down once forge exits, so this is the only place these artifacts survive.
+///
+/// Collection is best effort: artifacts which cannot be fetched are listed in `errors.txt`
+/// inside the bundle rather than failing the upload.
+pub fn upload_triage_bundle(
+    bucket: &str,
+    cluster_name: &str,
+    report: &TestReport,
+) -> Result<String> {
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let bundle_name = format!("forge-triage-{}-{}", cluster_name, timestamp);
+    let tmp_dir = TempDir::new()?;
+    let bundle_dir = tmp_dir.path().join(&bundle_name);
+    fs::create_dir_all(bundle_dir.join("logs"))?;
+    fs::create_dir_all(bundle_dir.join("values"))?;
+
+    let mut errors = vec![];
+    let dir = bundle_dir.as_path();
+    collect(
+        dir,
+        &mut errors,
+        "events.txt",
+        KUBECTL_BIN,
+        &["get", "events", "--sort-by=.lastTimestamp"],
+    );
+    collect(
+        dir,
+        &mut errors,
+        "pods.txt",
+        KUBECTL_BIN,
+        &["describe", "pods"],
+    );
+    collect(
+        dir,
+        &mut errors,
+        "nodes.txt",
+        KUBECTL_BIN,
+        &["describe", "nodes"],
+    );
+    collect(
+        dir,
+        &mut errors,
+        "configmaps.yaml",
+        KUBECTL_BIN,
+        &["get", "configmaps", "-o", "yaml"],
+    );
+    collect(
+        dir,
+        &mut errors,
+        "helm-releases.txt",
+        HELM_BIN,
+        &["list", "--all"],
+    );
+    match list_names(
+        KUBECTL_BIN,
+        &["get", "pods", "-o", "json"],
+        "/metadata/name",
+    ) {
+        Ok(pods) => {
+            for pod in pods {
+                collect(
+                    dir,
+                    &mut errors,
+                    &format!("logs/{}.log", pod),
+                    KUBECTL_BIN,
+                    &["logs", &pod, "--all-containers"],
+                );
+                // Only exists if the pod restarted, which is exactly when we want it
+                let _ = capture(
+                    &dir.join(format!("logs/{}.previous.log", pod)),
+                    KUBECTL_BIN,
+                    &["logs", &pod, "--all-containers", "--previous"],
+                );
+            }
+        }
+        Err(e) => errors.push(format!("logs: {:?}", e)),
+    }
+    match list_names(HELM_BIN, &["list", "--all", "-o", "json"], "/name") {
+        Ok(releases) => {
+            for release in releases {
+                collect(
+                    dir,
+                    &mut errors,
+                    &format!("values/{}.yaml", release),
+                    HELM_BIN,
+                    &["get", "values", &release, "--all"],
+                );
+            }
+        }
+        Err(e) => errors.push(format!("values: {:?}", e)),
+    }
+
+    fs::write(bundle_dir.join("report.txt"), report.to_string())?;
+    fs::write(
+        bundle_dir.join("report.json"),
+        serde_json::to_string_pretty(report)?,
+    )?;
+    fs::write(bundle_dir.join("errors.txt"), errors.join("\n"))?;
+
+    let tarball = tmp_dir.path().join(format!("{}.tar.gz", bundle_name));
+    run(
+        TAR_BIN,
+        &[
+            "-czf",
+            tarball.to_str().unwrap(),
+            "-C",
+            tmp_dir.path().to_str().unwrap(),
+            &bundle_name,
+        ],
+    )?;
+
+    let uri = format!(
+        "s3://{}/forge/{}/{}.tar.gz",
+        bucket, cluster_name, bundle_name
+    );
+    run(AWS_BIN, &["s3", "cp", tarball.to_str().unwrap(), &uri])?;
+    Ok(uri)
+}
+
+fn run(bin: &str, args: &[&str]) -> Result<Vec<u8>> {
+    let output = Command::new(bin)
+        .args(args)
+        .output()
+        .map_err(|e| format_err!("failed to run {} {:?}: {}", bin, args, e))?;
+    if !output.status.success() {
+        bail!(
+            "{} {:?} failed: {}",
+            bin,
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(output.stdout)
+}
+
+/// Saves the output of a command to `file` in the bundle, recording any failure in `errors`
+fn collect(dir: &Path, errors: &mut Vec<String>, file: &str, bin: &str, args: &[&str]) {
+    if let Err(e) = capture(&dir.join(file), bin, args) {
+        errors.push(format!("{}: {:?}", file, e));
+    }
+}
+
+fn capture(path: &Path, bin: &str, args: &[&str]) -> Result<()> {
+    let output = run(bin, args)?;
+    fs::write(path, output)?;
+    Ok(())
+}
+
+/// Runs a command with json output and extracts the string at `pointer` from every item
+fn list_names(bin: &str, args: &[&str], pointer: &str) -> Result<Vec<String>> {
+    let output: Value = serde_json::from_slice(&run(bin, args)?)?;
+    // kubectl wraps lists in an object, helm returns a bare array
+    let items = output
+        .get("items")
+        .unwrap_or(&output)
+        .as_array()
+        .ok_or_else(|| format_err!("unexpected output from {} {:?}", bin, args))?;
+    Ok(items
+        .iter()
+        .filter_map(|item| item.pointer(pointer)?.as_str().map(str::to_string))
+        .collect())
+}
@@ -5,12 +5,14 @@ use crate::Result;
 use anyhow::{bail, Context};
 use serde::Deserialize;
 use std::{
-    env, fs,
+    env,
+    ffi::OsString,
+    fs,
     io::{self, Write},
-    path::{Path, PathBuf},
+    path::{Path, PathBuf, MAIN_SEPARATOR},
     process::Command,
 };
-use tempfile::NamedTempFile;
+use tempfile::TempDir;
 
 #[derive(Deserialize)]
 pub struct Metadata {
@@ -108,23 +110,27 @@ fn git_is_worktree_dirty() -> Result<bool> {
 /// Attempt to query the local git repository's remotes for the one that points to the upstream
 /// aptos-labs/aptos-core repository, falling back to "origin" if unable to locate the remote
 pub fn git_get_upstream_remote() -> Result<String> {
-    let output = Command::new("sh")
-        .arg("-c")
-        .arg(
-            "git remote -v | grep \"https://github.com/aptos-labs/aptos-core.* (fetch)\" | cut -f1",
-        )
+    let output = Command::new("git")
+        .args(&["remote", "-v"])
         .output()
         .context("Failed to get upstream remote")?;
 
     if output.status.success() {
-        let remote = String::from_utf8(output.stdout).map(|s| s.trim().to_owned())?;
-
-        // If its empty, fall back to "origin"
-        if remote.is_empty() {
-            Ok("origin".into())
-        } else {
-            Ok(remote)
-        }
+        // Lines are formatted as "<remote>\t<url> (fetch|push)"
+        let remote = String::from_utf8(output.stdout)?.lines().find_map(|line| {
+            let mut fields = line.split_whitespace();
+            match (fields.next(), fields.next(), fields.next()) {
+                (Some(remote), Some(url), Some("(fetch)"))
+                    if url.starts_with("https://github.com/aptos-labs/aptos-core") =>
+                {
+                    Some(remote.to_owned())
+                }
+                _ => None,
+            }
+        });
+
+        // If there's none, fall back to "origin"
+        Ok(remote.unwrap_or_else(|| "origin".into()))
     } else {
         Ok("origin".into())
     }
@@ -162,8 +168,11 @@ where
         .context("Failed to build aptos-node")?;
 
     if output.status.success() {
-        let bin_path =
-            target_directory.join(format!("debug/{}{}", "aptos-node", env::consts::EXE_SUFFIX));
+        let bin_path = target_directory.join("debug").join(format!(
+            "{}{}",
+            "aptos-node",
+            env::consts::EXE_SUFFIX
+        ));
         if !bin_path.exists() {
             bail!(
                 "Can't find binary aptos-node at expected path {:?}",
@@ -183,33 +192,39 @@ where
     }
 }
 
+/// Checks the revision out with git alone, through an index of its own so that the index of the
+/// worktree is left alone, rather than extracting a `git archive` with tar which isn't available
+/// everywhere.
 fn checkout_revision(metadata: &Metadata, revision: &str, to: &Path) -> Result<()> {
     fs::create_dir_all(to)?;
 
-    let archive_file = NamedTempFile::new()?.into_temp_path();
+    let index_dir = TempDir::new()?;
+    let index_file = index_dir.path().join("index");
 
     let output = Command::new("git")
         .current_dir(&metadata.workspace_root)
-        .arg("archive")
-        .arg("--format=tar")
-        .arg("--output")
-        .arg(&archive_file)
+        .env("GIT_INDEX_FILE", &index_file)
+        .arg("read-tree")
         .arg(&revision)
         .output()
-        .context("Failed to run git archive")?;
+        .context("Failed to run git read-tree")?;
     if !output.status.success() {
-        bail!("Failed to run git archive");
+        bail!("Failed to run git read-tree");
     }
 
-    let output = Command::new("tar")
-        .current_dir(to)
-        .arg("xf")
-        .arg(&archive_file)
+    // The prefix is prepended to the paths of the files as is, so it needs a trailing separator
+    let mut prefix = OsString::from("--prefix=");
+    prefix.push(to);
+    prefix.push(MAIN_SEPARATOR.to_string());
+    let output = Command::new("git")
+        .current_dir(&metadata.workspace_root)
+        .env("GIT_INDEX_FILE", &index_file)
+        .args(&["checkout-index", "--all", "--force"])
+        .arg(prefix)
         .output()
-        .context("Failed to run tar")?;
-
+        .context("Failed to run git checkout-index")?;
     if !output.status.success() {
-        bail!("Failed to run tar");
+        bail!("Failed to run git checkout-index");
     }
 
     Ok(())
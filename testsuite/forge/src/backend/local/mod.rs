@@ -1,10 +1,13 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{Factory, GenesisConfig, Result, Swarm, Version};
+use crate::{
+    Factory, GenesisConfig, InitialState, NodeEnvironment, Result, Swarm, TopologySpec, Version,
+};
 use anyhow::{bail, Context};
 use rand::rngs::StdRng;
 use std::{
+    cmp::min,
     collections::HashMap,
     num::NonZeroUsize,
     path::{Path, PathBuf},
@@ -112,8 +115,22 @@ impl LocalFactory {
         R: ::rand::RngCore + ::rand::CryptoRng,
     {
         let version = self.versions.keys().max().unwrap();
-        self.new_swarm_with_version(rng, number_of_validators, version, None, 1)
-            .await
+        self.new_swarm_with_version(
+            rng,
+            number_of_validators,
+            version,
+            None,
+            InitialState::default(),
+            1,
+            NodeEnvironment::default(),
+        )
+        .await
+    }
+
+    /// A builder for swarms running the versions of this factory, for setups the `new_swarm*`
+    /// functions don't cover. The swarm still needs to be launched once built.
+    pub fn swarm_builder(&self) -> LocalSwarmBuilder {
+        LocalSwarm::builder(self.versions.clone())
     }
 
     pub async fn new_swarm_with_version<R>(
@@ -122,19 +139,50 @@ impl LocalFactory {
         number_of_validators: NonZeroUsize,
         version: &Version,
         genesis_modules: Option<Vec<Vec<u8>>>,
+        initial_state: InitialState,
         min_price_per_gas_unit: u64,
+        node_environment: NodeEnvironment,
     ) -> Result<LocalSwarm>
     where
         R: ::rand::RngCore + ::rand::CryptoRng,
     {
-        let mut builder = LocalSwarm::builder(self.versions.clone())
+        let builder = self.swarm_builder_with_version(
+            number_of_validators,
+            version,
+            genesis_modules,
+            initial_state,
+            min_price_per_gas_unit,
+            node_environment,
+        );
+        Self::launch_swarm_from_builder(builder, rng).await
+    }
+
+    fn swarm_builder_with_version(
+        &self,
+        number_of_validators: NonZeroUsize,
+        version: &Version,
+        genesis_modules: Option<Vec<Vec<u8>>>,
+        initial_state: InitialState,
+        min_price_per_gas_unit: u64,
+        node_environment: NodeEnvironment,
+    ) -> LocalSwarmBuilder {
+        let builder = self
+            .swarm_builder()
             .number_of_validators(number_of_validators)
             .initial_version(version.clone())
-            .min_price_per_gas_unit(min_price_per_gas_unit);
-        if let Some(genesis_modules) = genesis_modules {
-            builder = builder.genesis_modules(genesis_modules);
+            .initial_state(initial_state)
+            .min_price_per_gas_unit(min_price_per_gas_unit)
+            .node_environment(node_environment);
+        match genesis_modules {
+            Some(genesis_modules) => builder.genesis_modules(genesis_modules),
+            None => builder,
         }
+    }
 
+    async fn launch_swarm_from_builder<R>(builder: LocalSwarmBuilder, rng: R) -> Result<LocalSwarm>
+    where
+        R: ::rand::RngCore + ::rand::CryptoRng,
+    {
         let mut swarm = builder.build(rng)?;
         swarm
             .launch()
@@ -155,10 +203,19 @@ impl Factory for LocalFactory {
         &self,
         rng: &mut StdRng,
         node_num: NonZeroUsize,
+        fullnode_num: Option<usize>,
+        // The local swarm derives the keys of the validators it adds whenever it adds them
+        _standby_validator_num: usize,
         version: &Version,
         _genesis_version: &Version,
         genesis_config: Option<&GenesisConfig>,
+        initial_state: &InitialState,
+        node_environment: &NodeEnvironment,
+        topology: &TopologySpec,
     ) -> Result<Box<dyn Swarm>> {
+        if !topology.is_empty() {
+            bail!("local forge backend does not support topologies");
+        }
         let genesis_modules = match genesis_config {
             Some(config) => match config {
                 GenesisConfig::Bytes(bytes) => Some(bytes.clone()),
@@ -168,9 +225,22 @@ impl Factory for LocalFactory {
             },
             None => None,
         };
-        let swarm = self
-            .new_swarm_with_version(rng, node_num, version, genesis_modules, 1)
-            .await?;
+        let mut builder = self.swarm_builder_with_version(
+            node_num,
+            version,
+            genesis_modules,
+            initial_state.clone(),
+            1,
+            node_environment.clone(),
+        );
+        // The first fullnodes are attached to the validators, one each, and the others are public
+        if let Some(fullnode_num) = fullnode_num {
+            let validator_fullnodes = min(fullnode_num, node_num.get());
+            builder = builder
+                .num_validator_fullnodes(validator_fullnodes)
+                .num_public_fullnodes(fullnode_num - validator_fullnodes);
+        }
+        let swarm = Self::launch_swarm_from_builder(builder, rng).await?;
 
         Ok(Box::new(swarm))
     }
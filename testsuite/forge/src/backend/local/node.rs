@@ -1,37 +1,394 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{FullNode, HealthCheckError, LocalVersion, Node, NodeExt, Validator, Version};
-use anyhow::{anyhow, Context, Result};
-use aptos_config::config::NodeConfig;
+use crate::{
+    io_delay, FullNode, HealthCheckError, LocalVersion, Node, NodeExt, ResourceLimits,
+    StorageMetrics, Validator, ValidatorCandidate, Version,
+};
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use aptos_config::{
+    config::{Identity, NodeConfig, SecureBackend, HANDSHAKE_VERSION},
+    network_id::NetworkId,
+};
+use aptos_global_constants::{
+    CONSENSUS_KEY, FULLNODE_NETWORK_KEY, OWNER_ACCOUNT, OWNER_KEY, VALIDATOR_NETWORK_KEY,
+};
 use aptos_logger::{debug, warn};
-use aptos_sdk::types::{account_address::AccountAddress, PeerId};
+use aptos_sdk::{
+    crypto::{ed25519::Ed25519PublicKey, x25519},
+    types::{
+        account_address::AccountAddress, network_address::NetworkAddress, LocalAccount, PeerId,
+    },
+};
+use aptos_secure_storage::{CryptoStorage, KVStorage, Storage};
 use std::{
+    cmp::max,
+    collections::{BTreeMap, BTreeSet},
     env,
-    fs::{self, OpenOptions},
-    path::PathBuf,
-    process::{Child, Command},
+    fs::{self, File, OpenOptions},
+    io, iter,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    path::{Path, PathBuf},
+    process::{Child, Command, ExitStatus},
     str::FromStr,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::Duration,
 };
 use url::Url;
 
+/// Directory of the copy of the DB in the snapshot of a node
+const DB_SNAPSHOT_DIR: &str = "db";
+/// Room the throttled disk leaves the DB to grow, on top of twice its size when it's throttled
+const THROTTLED_DISK_HEADROOM: u64 = 1 << 30;
+/// Overrides where libfaketime is looked for, to skew the clock of nodes
+const LIBFAKETIME_ENV: &str = "FORGE_LIBFAKETIME";
+/// Where distributions install libfaketime
+const LIBFAKETIME_PATHS: &[&str] = &[
+    "/usr/lib/x86_64-linux-gnu/faketime/libfaketime.so.1",
+    "/usr/lib/aarch64-linux-gnu/faketime/libfaketime.so.1",
+    "/usr/lib/faketime/libfaketime.so.1",
+    "/usr/local/lib/faketime/libfaketime.so.1",
+];
+/// Where the cgroup v2 hierarchy is mounted
+const CGROUP_FS: &str = "/sys/fs/cgroup";
+/// Overrides the cgroup under which the cgroups limiting the resources of nodes are created, e.g.
+/// one delegated to the user by systemd when not running as root
+const CGROUP_PARENT_ENV: &str = "FORGE_CGROUP_PARENT";
+/// Period over which the CPU time of nodes is limited
+const CPU_PERIOD_USECS: u64 = 100_000;
+
+/// A handle on the process of a node, which can outlive the node, see `LocalNode::process_handle`
+#[derive(Clone, Debug)]
+pub(crate) struct ProcessHandle(Arc<Mutex<Child>>);
+
+impl ProcessHandle {
+    fn child(&self) -> MutexGuard<'_, Child> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn id(&self) -> u32 {
+        self.child().id()
+    }
+
+    /// Kills the process unless it has already terminated, and waits for it, returning its exit
+    /// status. As the process is only reaped here, its pid can't have been reused by then.
+    pub(crate) fn kill(&self) -> io::Result<ExitStatus> {
+        let mut child = self.child();
+        // The child process may have already terminated, perhaps due to a crash
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        if let Err(e) = child.kill() {
+            // Unless it terminated in the meantime, which is an error to kill on some platforms
+            return match child.try_wait()? {
+                Some(status) => Ok(status),
+                None => Err(e),
+            };
+        }
+        child.wait()
+    }
+}
+
 #[derive(Debug)]
-struct Process(Child);
+struct Process(ProcessHandle);
 
 impl Drop for Process {
     // When the Process struct goes out of scope we need to kill the child process
     fn drop(&mut self) {
-        // check if the process has already been terminated
-        match self.0.try_wait() {
-            // The child process has already terminated, perhaps due to a crash
-            Ok(Some(_)) => {}
-
-            // The process is still running so we need to attempt to kill it
-            _ => {
-                self.0.kill().expect("Process wasn't running");
-                self.0.wait().unwrap();
-            }
+        if let Err(e) = self.0.kill() {
+            panic!("Failed to kill process: {}", e);
+        }
+    }
+}
+
+/// A device-mapper delay device over a loop device backed by `image`, with a filesystem mounted
+/// at `mount_point`, which delays every IO operation on the filesystem. It's torn down when
+/// dropped, after the process of the node, which holds files open on it.
+#[derive(Debug)]
+struct ThrottledDisk {
+    image: PathBuf,
+    loop_device: Option<String>,
+    dm_name: Option<String>,
+    mount_point: Option<PathBuf>,
+}
+
+impl ThrottledDisk {
+    fn mount(
+        image: PathBuf,
+        size: u64,
+        delay: Duration,
+        dm_name: String,
+        mount_point: &Path,
+    ) -> Result<Self> {
+        let mut disk = Self {
+            image,
+            loop_device: None,
+            dm_name: None,
+            mount_point: None,
+        };
+        // Whatever was set up is torn down when the disk is dropped on errors
+        File::create(&disk.image)?.set_len(size)?;
+        let loop_device = run("losetup", &["--find", "--show", path_str(&disk.image)?])?;
+        disk.loop_device = Some(loop_device.clone());
+        run("mkfs.ext4", &["-q", &loop_device])?;
+        let sectors = run("blockdev", &["--getsz", &loop_device])?;
+        run(
+            "dmsetup",
+            &[
+                "create",
+                &dm_name,
+                "--table",
+                &delay_table(&sectors, &loop_device, delay),
+            ],
+        )?;
+        disk.dm_name = Some(dm_name.clone());
+        run(
+            "mount",
+            &[&format!("/dev/mapper/{}", dm_name), path_str(mount_point)?],
+        )?;
+        disk.mount_point = Some(mount_point.to_path_buf());
+        Ok(disk)
+    }
+
+    /// Tears down what was set up of the disk, in the reverse order
+    fn teardown(&mut self) -> Result<()> {
+        if let Some(mount_point) = self.mount_point.take() {
+            run("umount", &[path_str(&mount_point)?])?;
+        }
+        if let Some(dm_name) = self.dm_name.take() {
+            run("dmsetup", &["remove", &dm_name])?;
         }
+        if let Some(loop_device) = self.loop_device.take() {
+            run("losetup", &["-d", &loop_device])?;
+        }
+        if self.image.exists() {
+            fs::remove_file(&self.image)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for ThrottledDisk {
+    fn drop(&mut self) {
+        if let Err(e) = self.teardown() {
+            warn!("Failed to tear down throttled disk {:?}: {}", self.image, e);
+        }
+    }
+}
+
+/// A cgroup v2 limiting the CPU and memory of the process of a node, which is moved into it once
+/// started. It's removed when dropped, after the process of the node, as cgroups with processes
+/// can't be removed.
+#[derive(Debug)]
+struct NodeCgroup {
+    path: PathBuf,
+}
+
+impl NodeCgroup {
+    fn create(name: &str) -> Result<Self> {
+        ensure!(
+            Path::new(CGROUP_FS).join("cgroup.controllers").exists(),
+            "Limiting the resources of nodes requires cgroup v2 mounted at {}",
+            CGROUP_FS
+        );
+        let parent = match env::var(CGROUP_PARENT_ENV) {
+            Ok(path) => PathBuf::from(path),
+            Err(_) => Path::new(CGROUP_FS).join("forge"),
+        };
+        fs::create_dir_all(&parent)?;
+        fs::write(parent.join("cgroup.subtree_control"), "+cpu +memory").with_context(|| {
+            format!(
+                "Failed to enable the cpu and memory controllers under {:?}, which requires root \
+                 or a cgroup delegated to the user set in {}",
+                parent, CGROUP_PARENT_ENV
+            )
+        })?;
+        let path = parent.join(name);
+        if !path.exists() {
+            fs::create_dir(&path)?;
+        }
+        Ok(Self { path })
+    }
+
+    fn set_limits(&self, limits: &ResourceLimits) -> Result<()> {
+        self.write("cpu.max", &cgroup_cpu_max(limits))?;
+        self.write("memory.max", &cgroup_memory_max(limits))?;
+        // Swapping would let the node use more memory than its limit, only slower
+        if self.path.join("memory.swap.max").exists() {
+            let swap_max = if limits.memory_bytes.is_some() {
+                "0"
+            } else {
+                "max"
+            };
+            self.write("memory.swap.max", swap_max)?;
+        }
+        Ok(())
+    }
+
+    fn add_process(&self, pid: u32) -> Result<()> {
+        self.write("cgroup.procs", &pid.to_string())
+    }
+
+    fn write(&self, file: &str, value: &str) -> Result<()> {
+        let path = self.path.join(file);
+        fs::write(&path, value).with_context(|| format!("Failed to write {} to {:?}", value, path))
+    }
+}
+
+impl Drop for NodeCgroup {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_dir(&self.path) {
+            warn!("Failed to remove cgroup {:?}: {}", self.path, e);
+        }
+    }
+}
+
+/// The `cpu.max` of a cgroup enforcing `limits`: the CPU time the node may use per period, both
+/// in microseconds
+fn cgroup_cpu_max(limits: &ResourceLimits) -> String {
+    match limits.cpu_millis {
+        Some(cpu_millis) => {
+            // The kernel doesn't take quotas under 1ms
+            let quota = max(cpu_millis * CPU_PERIOD_USECS / 1000, 1000);
+            format!("{} {}", quota, CPU_PERIOD_USECS)
+        }
+        None => format!("max {}", CPU_PERIOD_USECS),
+    }
+}
+
+/// The `memory.max` of a cgroup enforcing `limits`, in bytes
+fn cgroup_memory_max(limits: &ResourceLimits) -> String {
+    limits
+        .memory_bytes
+        .map_or_else(|| "max".to_string(), |bytes| bytes.to_string())
+}
+
+/// The hard cap on the CPU of a job object enforcing `cpu_millis` on a machine with `cpus` CPUs.
+/// Windows takes it as a share of the CPU time of the whole machine, in hundredths of a percent.
+#[cfg(any(windows, test))]
+fn job_cpu_rate(cpu_millis: u64, cpus: u64) -> u32 {
+    (cpu_millis * 10_000 / (cpus * 1000)).clamp(1, 10_000) as u32
+}
+
+/// A job object limiting the CPU and memory of the process of a node on Windows, which has no
+/// cgroups. The processes of the node are assigned to it once started, and stay in it until they
+/// exit. Unlike the cgroup, the memory limit makes allocations beyond it fail rather than
+/// reclaiming memory.
+#[cfg(windows)]
+#[derive(Debug)]
+struct NodeJob {
+    handle: winapi::um::winnt::HANDLE,
+    /// The process last assigned to the job, which can't be assigned to it twice
+    pid: Option<u32>,
+}
+
+// The handle of the job is only used through the methods of the job, which take it by reference
+#[cfg(windows)]
+unsafe impl Send for NodeJob {}
+#[cfg(windows)]
+unsafe impl Sync for NodeJob {}
+
+#[cfg(windows)]
+impl NodeJob {
+    fn create() -> Result<Self> {
+        let handle = unsafe {
+            winapi::um::jobapi2::CreateJobObjectW(std::ptr::null_mut(), std::ptr::null())
+        };
+        if handle.is_null() {
+            bail!(
+                "Failed to create a job object: {}",
+                std::io::Error::last_os_error()
+            );
+        }
+        Ok(Self { handle, pid: None })
+    }
+
+    fn set_limits(&self, limits: &ResourceLimits) -> Result<()> {
+        use winapi::um::winnt::{
+            JobObjectCpuRateControlInformation, JobObjectExtendedLimitInformation,
+            JOBOBJECT_CPU_RATE_CONTROL_INFORMATION, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+            JOB_OBJECT_CPU_RATE_CONTROL_ENABLE, JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP,
+            JOB_OBJECT_LIMIT_JOB_MEMORY,
+        };
+
+        // Zeroed flags lift the limits
+        let mut cpu_info: JOBOBJECT_CPU_RATE_CONTROL_INFORMATION = unsafe { std::mem::zeroed() };
+        if let Some(cpu_millis) = limits.cpu_millis {
+            let cpus = std::thread::available_parallelism().map_or(1, |cpus| cpus.get() as u64);
+            cpu_info.ControlFlags =
+                JOB_OBJECT_CPU_RATE_CONTROL_ENABLE | JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP;
+            unsafe { *cpu_info.u.CpuRate_mut() = job_cpu_rate(cpu_millis, cpus) };
+        }
+        self.set_information(JobObjectCpuRateControlInformation, &mut cpu_info)?;
+
+        let mut memory_info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = unsafe { std::mem::zeroed() };
+        if let Some(memory_bytes) = limits.memory_bytes {
+            memory_info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_JOB_MEMORY;
+            memory_info.JobMemoryLimit = memory_bytes as usize;
+        }
+        self.set_information(JobObjectExtendedLimitInformation, &mut memory_info)
+    }
+
+    fn set_information<T>(
+        &self,
+        class: winapi::um::winnt::JOBOBJECTINFOCLASS,
+        info: &mut T,
+    ) -> Result<()> {
+        let set = unsafe {
+            winapi::um::jobapi2::SetInformationJobObject(
+                self.handle,
+                class,
+                info as *mut T as *mut _,
+                std::mem::size_of::<T>() as u32,
+            )
+        };
+        if set == 0 {
+            bail!(
+                "Failed to set the limits of a job object: {}",
+                std::io::Error::last_os_error()
+            );
+        }
+        Ok(())
+    }
+
+    fn add_process(&mut self, pid: u32) -> Result<()> {
+        use winapi::um::{
+            handleapi::CloseHandle,
+            jobapi2::AssignProcessToJobObject,
+            processthreadsapi::OpenProcess,
+            winnt::{PROCESS_SET_QUOTA, PROCESS_TERMINATE},
+        };
+
+        if self.pid == Some(pid) {
+            return Ok(());
+        }
+        let process = unsafe { OpenProcess(PROCESS_SET_QUOTA | PROCESS_TERMINATE, 0, pid) };
+        if process.is_null() {
+            bail!(
+                "Failed to open process {}: {}",
+                pid,
+                std::io::Error::last_os_error()
+            );
+        }
+        let assigned = unsafe { AssignProcessToJobObject(self.handle, process) };
+        let error = std::io::Error::last_os_error();
+        unsafe { CloseHandle(process) };
+        if assigned == 0 {
+            bail!(
+                "Failed to assign process {} to a job object: {}",
+                pid,
+                error
+            );
+        }
+        self.pid = Some(pid);
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+impl Drop for NodeJob {
+    fn drop(&mut self) {
+        unsafe { winapi::um::handleapi::CloseHandle(self.handle) };
     }
 }
 
@@ -39,10 +396,23 @@ impl Drop for Process {
 pub struct LocalNode {
     version: LocalVersion,
     process: Option<Process>,
+    /// The cgroup limiting the resources of the process, declared after the process so that it's
+    /// dropped after the process is killed
+    cgroup: Option<NodeCgroup>,
+    /// The job object limiting the resources of the process on Windows, in place of the cgroup
+    #[cfg(windows)]
+    job: Option<NodeJob>,
+    /// The disk the DB is on while its IO is throttled, declared after the process so that it's
+    /// dropped after the process is killed
+    throttled_disk: Option<ThrottledDisk>,
     name: String,
     peer_id: AccountAddress,
     directory: PathBuf,
     config: NodeConfig,
+    env_vars: BTreeMap<String, String>,
+    /// Seconds the system clock of the node process is off by, see `set_clock_skew`
+    clock_skew_secs: i64,
+    resource_limits: ResourceLimits,
 }
 
 impl LocalNode {
@@ -57,13 +427,120 @@ impl LocalNode {
         Ok(Self {
             version,
             process: None,
+            cgroup: None,
+            #[cfg(windows)]
+            job: None,
+            throttled_disk: None,
             name,
             peer_id,
             directory,
             config,
+            env_vars: BTreeMap::new(),
+            clock_skew_secs: 0,
+            resource_limits: ResourceLimits::default(),
         })
     }
 
+    /// Sets extra environment variables on the node process whenever it is started
+    pub fn with_env_vars(mut self, env_vars: BTreeMap<String, String>) -> Self {
+        self.env_vars = env_vars;
+        self
+    }
+
+    /// Replaces the extra environment variables of the node process, from the next time it's
+    /// started
+    pub fn set_env_vars(&mut self, env_vars: BTreeMap<String, String>) {
+        self.env_vars = env_vars;
+    }
+
+    /// Skews the system clock of the node process by `skew_secs` seconds, ahead if positive and
+    /// behind if negative, from the next time it's started. It's done by preloading libfaketime,
+    /// which only fakes the wall clock: timers, which run on the monotonic clock, aren't skewed.
+    pub fn set_clock_skew(&mut self, skew_secs: i64) {
+        self.clock_skew_secs = skew_secs;
+    }
+
+    pub fn clock_skew_secs(&self) -> i64 {
+        self.clock_skew_secs
+    }
+
+    pub fn with_resource_limits(mut self, limits: ResourceLimits) -> Self {
+        self.resource_limits = limits;
+        self
+    }
+
+    /// Limits the CPU and memory of the node process, through a cgroup on Linux and, as far as
+    /// Windows allows, a job object on Windows. Elsewhere, or if the job object can't be set up,
+    /// the limits aren't enforced, which is only warned about. The limits apply right away if the
+    /// node is running, and whenever it's started.
+    pub fn set_resource_limits(&mut self, limits: ResourceLimits) -> Result<()> {
+        self.resource_limits = limits;
+        match self.process.as_ref().map(|process| process.0.id()) {
+            Some(pid) => self.limit_resources(pid),
+            None => Ok(()),
+        }
+    }
+
+    pub fn resource_limits(&self) -> ResourceLimits {
+        self.resource_limits
+    }
+
+    /// Moves the process into the cgroup of the node with the current limits, creating the cgroup
+    /// the first time the node has limits
+    #[cfg(not(windows))]
+    fn limit_resources(&mut self, pid: u32) -> Result<()> {
+        if self.cgroup.is_none() {
+            if self.resource_limits.is_unlimited() {
+                return Ok(());
+            }
+            if !cfg!(target_os = "linux") {
+                warn!(
+                    "The resource limits of node '{}' aren't enforced, which requires cgroups on \
+                     Linux",
+                    self.name
+                );
+                return Ok(());
+            }
+            self.cgroup = Some(NodeCgroup::create(&format!(
+                "{}-{}",
+                self.name, self.peer_id
+            ))?);
+        }
+        let cgroup = self.cgroup.as_ref().unwrap();
+        cgroup.set_limits(&self.resource_limits)?;
+        cgroup.add_process(pid)
+    }
+
+    /// Assigns the process to the job object of the node with the current limits, creating the
+    /// job the first time the node has limits. Failures leave the node unlimited, with a warning.
+    #[cfg(windows)]
+    fn limit_resources(&mut self, pid: u32) -> Result<()> {
+        if self.job.is_none() {
+            if self.resource_limits.is_unlimited() {
+                return Ok(());
+            }
+            match NodeJob::create() {
+                Ok(job) => self.job = Some(job),
+                Err(e) => {
+                    warn!(
+                        "The resource limits of node '{}' aren't enforced: {}",
+                        self.name, e
+                    );
+                    return Ok(());
+                }
+            }
+        }
+        let limits = self.resource_limits;
+        let job = self.job.as_mut().unwrap();
+        if let Err(e) = job.set_limits(&limits).and_then(|_| job.add_process(pid)) {
+            warn!(
+                "The resource limits of node '{}' aren't enforced: {}",
+                self.name, e
+            );
+        }
+        Ok(())
+    }
+
     pub fn config_path(&self) -> PathBuf {
         self.directory.join("node.yaml")
     }
@@ -98,6 +575,10 @@ impl LocalNode {
             // Only set our RUST_LOG if its not present in environment
             node_command.env("RUST_LOG", "debug");
         }
+        node_command.envs(&self.env_vars);
+        if self.clock_skew_secs != 0 {
+            node_command.envs(faketime_env(self.clock_skew_secs)?);
+        }
         node_command.stdout(log_file.try_clone()?).stderr(log_file);
         let process = node_command.spawn().with_context(|| {
             format!(
@@ -105,8 +586,13 @@ impl LocalNode {
                 self.version.bin()
             )
         })?;
+        let process = Process(ProcessHandle(Arc::new(Mutex::new(process))));
+        // The process runs unlimited for the moment it takes to move it into its cgroup, before
+        // the node even loaded its config. It's killed if it can't be moved, except on Windows
+        // where the limits are only best effort.
+        self.limit_resources(process.0.id())?;
 
-        self.process = Some(Process(process));
+        self.process = Some(process);
 
         Ok(())
     }
@@ -115,6 +601,11 @@ impl LocalNode {
         self.process = None;
     }
 
+    /// A handle on the process of the node while it's running, to kill it without the node
+    pub(crate) fn process_handle(&self) -> Option<ProcessHandle> {
+        self.process.as_ref().map(|process| process.0.clone())
+    }
+
     pub fn port(&self) -> u16 {
         self.config.api.address.port()
     }
@@ -139,6 +630,130 @@ impl LocalNode {
         self.start()
     }
 
+    /// Moves the DB of the node onto a device-mapper delay device, see `Node::throttle_io`
+    pub fn throttle_io(&mut self, iops: u64, latency: Duration) -> Result<()> {
+        // Throttling again starts over from the unthrottled DB
+        self.restore_io()?;
+        let db_dir = self.config.storage.dir();
+        let unthrottled_dir = db_dir.with_extension("unthrottled");
+        let size = dir_size(&db_dir)? * 2 + THROTTLED_DISK_HEADROOM;
+        let image = self.directory.join("throttled-disk.img");
+        let dm_name = format!("forge-io-{}", self.peer_id);
+
+        // The node keeps the DB open, so it's moved while the node is stopped
+        self.stop();
+        let throttled = (|| -> Result<_> {
+            fs::rename(&db_dir, &unthrottled_dir)?;
+            fs::create_dir(&db_dir)?;
+            let disk =
+                ThrottledDisk::mount(image, size, io_delay(iops, latency), dm_name, &db_dir)?;
+            copy_dir_contents(&unthrottled_dir, &db_dir)?;
+            Ok(disk)
+        })();
+        let moved = match throttled {
+            Ok(disk) => {
+                self.throttled_disk = Some(disk);
+                fs::remove_dir_all(&unthrottled_dir).map_err(Into::into)
+            }
+            // The disk was torn down when dropped, so the DB is moved back
+            Err(e) => {
+                if unthrottled_dir.exists() {
+                    if db_dir.exists() {
+                        fs::remove_dir_all(&db_dir)?;
+                    }
+                    fs::rename(&unthrottled_dir, &db_dir)?;
+                }
+                Err(e)
+            }
+        };
+        self.start()?;
+        moved
+    }
+
+    /// Moves the DB of the node back off the device of `throttle_io`, if it's on it
+    pub fn restore_io(&mut self) -> Result<()> {
+        let mut disk = match self.throttled_disk.take() {
+            Some(disk) => disk,
+            None => return Ok(()),
+        };
+        let db_dir = self.config.storage.dir();
+        let unthrottled_dir = db_dir.with_extension("unthrottled");
+
+        self.stop();
+        let restored = (|| -> Result<()> {
+            fs::create_dir(&unthrottled_dir)?;
+            copy_dir_contents(&db_dir, &unthrottled_dir)?;
+            disk.teardown()?;
+            fs::remove_dir(&db_dir)?;
+            fs::rename(&unthrottled_dir, &db_dir)?;
+            Ok(())
+        })();
+        self.start()?;
+        restored
+    }
+
+    /// Copies the DB and the on disk secure storage of the node to `dir`. The node has to be
+    /// stopped, for the copy to be consistent
+    pub(crate) fn save_state(&self, dir: &Path) -> Result<()> {
+        ensure!(self.process.is_none(), "Node '{}' is running", self.name);
+        let snapshot_db_dir = dir.join(DB_SNAPSHOT_DIR);
+        fs::create_dir_all(&snapshot_db_dir)?;
+        let db_dir = self.config.storage.dir();
+        if db_dir.exists() {
+            copy_dir_contents(&db_dir, &snapshot_db_dir)?;
+        }
+        for path in self.secure_storage_paths() {
+            if path.exists() {
+                fs::copy(&path, dir.join(path.file_name().unwrap()))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces the DB and the on disk secure storage of the node with the ones `save_state` copied
+    /// to `dir`. The node has to be stopped
+    pub(crate) fn load_state(&self, dir: &Path) -> Result<()> {
+        ensure!(self.process.is_none(), "Node '{}' is running", self.name);
+        let snapshot_db_dir = dir.join(DB_SNAPSHOT_DIR);
+        ensure!(
+            snapshot_db_dir.is_dir(),
+            "No snapshot of node '{}' in {:?}",
+            self.name,
+            dir
+        );
+        let db_dir = self.config.storage.dir();
+        clear_dir(&db_dir)?;
+        copy_dir_contents(&snapshot_db_dir, &db_dir)?;
+        for path in self.secure_storage_paths() {
+            let snapshot = dir.join(path.file_name().unwrap());
+            if snapshot.exists() {
+                fs::copy(&snapshot, &path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The files of the on disk secure storages of the node, for safety rules and the identities
+    /// of its networks
+    fn secure_storage_paths(&self) -> BTreeSet<PathBuf> {
+        let identity_backends = self
+            .config
+            .validator_network
+            .iter()
+            .chain(self.config.full_node_networks.iter())
+            .filter_map(|network| match &network.identity {
+                Identity::FromStorage(identity) => Some(&identity.backend),
+                _ => None,
+            });
+        iter::once(&self.config.consensus.safety_rules.backend)
+            .chain(identity_backends)
+            .filter_map(|backend| match backend {
+                SecureBackend::OnDiskStorage(storage) => Some(storage.path()),
+                _ => None,
+            })
+            .collect()
+    }
+
     pub fn get_log_contents(&self) -> Result<String> {
         fs::read_to_string(self.log_path()).map_err(Into::into)
     }
@@ -147,7 +762,8 @@ impl LocalNode {
         debug!("Health check on node '{}'", self.name);
 
         if let Some(p) = &mut self.process {
-            match p.0.try_wait() {
+            let status = p.0.child().try_wait();
+            match status {
                 // This would mean the child process has crashed
                 Ok(Some(status)) => {
                     debug!("Node '{}' crashed with: {}", self.name, status);
@@ -196,9 +812,14 @@ impl Node for LocalNode {
     }
 
     fn rest_api_endpoint(&self) -> Url {
-        let ip = self.config().api.address.ip();
-        let port = self.config().api.address.port();
-        Url::from_str(&format!("http://{}:{}", ip, port)).expect("Invalid URL.")
+        // The API may listen on the unspecified address, which can't be connected to on windows
+        let ip = match self.config().api.address.ip() {
+            IpAddr::V4(ip) if ip.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+            IpAddr::V6(ip) if ip.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+            ip => ip,
+        };
+        let address = SocketAddr::new(ip, self.config().api.address.port());
+        Url::from_str(&format!("http://{}", address)).expect("Invalid URL.")
     }
 
     fn debug_endpoint(&self) -> Url {
@@ -234,7 +855,263 @@ impl Node for LocalNode {
     fn expose_metric(&self) -> Result<u64> {
         Ok(0)
     }
+
+    fn storage_metrics(&self) -> Result<StorageMetrics> {
+        Ok(StorageMetrics {
+            db_bytes: dir_size(&self.config().storage.dir())?,
+            data_dir_bytes: dir_size(self.config().data_dir())?,
+        })
+    }
+
+    fn throttle_io(&mut self, iops: u64, latency: Duration) -> Result<()> {
+        self.throttle_io(iops, latency)
+    }
+
+    fn restore_io(&mut self) -> Result<()> {
+        self.restore_io()
+    }
 }
 
-impl Validator for LocalNode {}
+impl Validator for LocalNode {
+    fn rotate_consensus_key(&mut self) -> Result<(LocalAccount, Ed25519PublicKey)> {
+        let mut storage: Storage = (&self.config().consensus.safety_rules.backend).into();
+        // The node writes to the same storage, so the key is rotated while it's stopped
+        self.stop();
+        let rotated = (|| -> Result<_> {
+            let owner = self.owner_account()?;
+            let consensus_key = storage.rotate_key(CONSENSUS_KEY)?;
+            Ok((owner, consensus_key))
+        })();
+        self.start()?;
+        rotated
+    }
+
+    fn owner_account(&self) -> Result<LocalAccount> {
+        let storage: Storage = (&self.config().consensus.safety_rules.backend).into();
+        let owner_address = storage.get::<AccountAddress>(OWNER_ACCOUNT)?.value;
+        let owner_key = storage.export_private_key(OWNER_KEY)?;
+        Ok(LocalAccount::new(owner_address, owner_key, 0))
+    }
+
+    fn candidate(&self) -> Result<ValidatorCandidate> {
+        let storage: Storage = (&self.config().consensus.safety_rules.backend).into();
+        // As in the validator config transaction of the genesis, the addresses are the listen
+        // addresses of the networks, with the protocols and the keys to reach them
+        let network_addresses = |address: &NetworkAddress, key_name: &str| -> Result<Vec<u8>> {
+            let key = storage.get_public_key(key_name)?.public_key;
+            let key = x25519::PublicKey::from_ed25519_public_bytes(&key.to_bytes())?;
+            Ok(bcs::to_bytes(&vec![address
+                .clone()
+                .append_prod_protos(key, HANDSHAKE_VERSION)])?)
+        };
+        let validator_network = self
+            .config()
+            .validator_network
+            .as_ref()
+            .ok_or_else(|| anyhow!("Node {} has no validator network", self.name))?;
+        let public_network = self
+            .config()
+            .full_node_networks
+            .iter()
+            .find(|network| network.network_id == NetworkId::Public)
+            .ok_or_else(|| anyhow!("Node {} has no public network", self.name))?;
+        Ok(ValidatorCandidate {
+            consensus_pubkey: storage.get_public_key(CONSENSUS_KEY)?.public_key,
+            validator_network_addresses: network_addresses(
+                &validator_network.listen_address,
+                VALIDATOR_NETWORK_KEY,
+            )?,
+            fullnode_network_addresses: network_addresses(
+                &public_network.listen_address,
+                FULLNODE_NETWORK_KEY,
+            )?,
+        })
+    }
+}
 impl FullNode for LocalNode {}
+
+/// Total size of the files under `path`, which is empty while the node hasn't created it yet
+fn dir_size(path: &Path) -> Result<u64> {
+    if !path.exists() {
+        return Ok(0);
+    }
+    let mut size = 0;
+    for entry in fs::read_dir(path).with_context(|| format!("Failed to read {:?}", path))? {
+        let entry = entry?;
+        // The files the node writes may be removed while the directory is walked, e.g. by
+        // compactions of the DB
+        let metadata = match entry.metadata() {
+            Ok(metadata) => metadata,
+            Err(_) => continue,
+        };
+        size += if metadata.is_dir() {
+            dir_size(&entry.path())?
+        } else {
+            metadata.len()
+        };
+    }
+    Ok(size)
+}
+
+/// The device-mapper table of a delay device over all the `sectors` of `device`, delaying reads
+/// and writes by `delay`, which the target takes in whole milliseconds
+fn delay_table(sectors: &str, device: &str, delay: Duration) -> String {
+    format!("0 {} delay {} 0 {}", sectors, device, delay.as_millis())
+}
+
+/// Copies the files under `from` into `to`, preserving their attributes
+fn copy_dir_contents(from: &Path, to: &Path) -> Result<()> {
+    run(
+        "cp",
+        &["-a", &format!("{}/.", path_str(from)?), path_str(to)?],
+    )?;
+    Ok(())
+}
+
+/// Removes the files under `path`, creating it if it doesn't exist. The directory itself is kept,
+/// as it may be the mount point of a throttled disk
+fn clear_dir(path: &Path) -> Result<()> {
+    if !path.exists() {
+        fs::create_dir_all(path)?;
+        return Ok(());
+    }
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            fs::remove_dir_all(entry.path())?;
+        } else {
+            fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+/// Runs the command, returning its trimmed stdout
+fn run(program: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .with_context(|| format!("Failed to run {}", program))?;
+    if !output.status.success() {
+        bail!(
+            "{} {:?} failed: {}",
+            program,
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
+fn path_str(path: &Path) -> Result<&str> {
+    path.to_str()
+        .ok_or_else(|| anyhow!("Invalid path: {:?}", path))
+}
+
+/// The environment preloading libfaketime into a process, to skew its system clock by
+/// `skew_secs` seconds
+fn faketime_env(skew_secs: i64) -> Result<Vec<(&'static str, String)>> {
+    let library = match env::var(LIBFAKETIME_ENV) {
+        Ok(path) => PathBuf::from(path),
+        Err(_) => LIBFAKETIME_PATHS
+            .iter()
+            .map(PathBuf::from)
+            .find(|path| path.exists())
+            .ok_or_else(|| {
+                anyhow!(
+                    "libfaketime is needed to skew the clock of nodes, install it (e.g. the \
+                     faketime package) or set {} to its path",
+                    LIBFAKETIME_ENV
+                )
+            })?,
+    };
+    ensure!(library.exists(), "No libfaketime at {:?}", library);
+    Ok(vec![
+        ("LD_PRELOAD", path_str(&library)?.to_string()),
+        ("FAKETIME", format!("{:+}s", skew_secs)),
+        // The timers of the node, e.g. the round timeouts of consensus, keep the real pace
+        ("FAKETIME_DONT_FAKE_MONOTONIC", "1".to_string()),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits(cpu_millis: Option<u64>, memory_bytes: Option<u64>) -> ResourceLimits {
+        ResourceLimits {
+            cpu_millis,
+            memory_bytes,
+        }
+    }
+
+    #[test]
+    fn test_cgroup_cpu_max() {
+        assert_eq!(cgroup_cpu_max(&limits(None, None)), "max 100000");
+        assert_eq!(cgroup_cpu_max(&limits(Some(500), None)), "50000 100000");
+        assert_eq!(cgroup_cpu_max(&limits(Some(2_500), None)), "250000 100000");
+        // raised to the 1ms the kernel takes at least
+        assert_eq!(cgroup_cpu_max(&limits(Some(5), None)), "1000 100000");
+        assert_eq!(cgroup_cpu_max(&limits(Some(10), None)), "1000 100000");
+    }
+
+    #[test]
+    fn test_cgroup_memory_max() {
+        assert_eq!(cgroup_memory_max(&limits(Some(500), None)), "max");
+        assert_eq!(
+            cgroup_memory_max(&limits(None, Some(512 << 20))),
+            "536870912"
+        );
+    }
+
+    #[test]
+    fn test_job_cpu_rate() {
+        assert_eq!(job_cpu_rate(500, 1), 5_000);
+        assert_eq!(job_cpu_rate(500, 4), 1_250);
+        assert_eq!(job_cpu_rate(1_000, 8), 1_250);
+        // capped to the whole machine, and kept above nothing
+        assert_eq!(job_cpu_rate(16_000, 8), 10_000);
+        assert_eq!(job_cpu_rate(1, 64), 1);
+    }
+
+    #[test]
+    fn test_delay_table() {
+        assert_eq!(
+            delay_table("2097152", "/dev/loop3", Duration::from_millis(10)),
+            "0 2097152 delay /dev/loop3 0 10"
+        );
+        // the delay is rounded down to the millisecond
+        assert_eq!(
+            delay_table("8", "/dev/loop0", Duration::from_micros(2_500)),
+            "0 8 delay /dev/loop0 0 2"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_copy_dir_contents() {
+        let from = tempfile::tempdir().unwrap();
+        let to = tempfile::tempdir().unwrap();
+        fs::create_dir(from.path().join("db")).unwrap();
+        fs::write(from.path().join("db").join("000001.sst"), "sst").unwrap();
+        fs::write(from.path().join(".lock"), "").unwrap();
+
+        copy_dir_contents(from.path(), to.path()).unwrap();
+        // the directory itself isn't copied, only what it holds, hidden files included
+        assert_eq!(
+            fs::read_to_string(to.path().join("db").join("000001.sst")).unwrap(),
+            "sst"
+        );
+        assert!(to.path().join(".lock").exists());
+        assert!(from.path().join("db").join("000001.sst").exists());
+        assert_eq!(dir_size(to.path()).unwrap(), dir_size(from.path()).unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run() {
+        assert_eq!(run("echo", &[" a b "]).unwrap(), "a b");
+        assert!(run("false", &[]).is_err());
+        assert!(run("forge-no-such-program", &[]).is_err());
+    }
+}
@@ -1,19 +1,37 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+use super::node::ProcessHandle;
 use crate::{
-    ChainInfo, FullNode, HealthCheckError, LocalNode, LocalVersion, Node, NodeExt, Swarm, SwarmExt,
-    Validator, Version,
+    AccountPool, ChainInfo, ChaosDriver, ChaosSpec, FullNode, GenesisConfiguration,
+    HealthCheckError, InitialState, LocalNode, LocalVersion, Node, NodeConfigFn, NodeEnvironment,
+    NodeExt, NodeFeature, Swarm, SwarmAbandoner, SwarmExt, Validator, Version,
 };
-use anyhow::{anyhow, bail, Result};
-use aptos_config::config::NodeConfig;
-use aptos_genesis_tool::{fullnode_builder::FullnodeConfig, validator_builder::ValidatorBuilder};
-use aptos_sdk::types::{
-    chain_id::ChainId, transaction::Transaction, waypoint::Waypoint, AccountKey, LocalAccount,
-    PeerId,
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use aptos_config::{
+    config::{Identity, NodeConfig, PeerRole, SecureBackend},
+    generator::build_seed_for_network,
+    network_id::NetworkId,
+};
+use aptos_genesis_tool::{
+    fullnode_builder::FullnodeConfig,
+    validator_builder::{build_test_network_genesis, ValidatorBuilder},
+};
+use aptos_rest_client::Client as RestClient;
+use aptos_sdk::{
+    crypto::{ed25519::Ed25519PrivateKey, x25519, Uniform},
+    types::{
+        account_address, chain_id::ChainId, transaction::Transaction, waypoint::Waypoint,
+        AccountKey, LocalAccount, PeerId,
+    },
+};
+use rand::{
+    distributions::Alphanumeric,
+    rngs::{OsRng, StdRng},
+    Rng, SeedableRng,
 };
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fs, mem,
     num::NonZeroUsize,
     ops,
@@ -23,6 +41,9 @@ use std::{
 };
 use tempfile::TempDir;
 
+/// File of the genesis waypoint of the swarm in its snapshots
+const SNAPSHOT_WAYPOINT_FILE: &str = "waypoint.txt";
+
 #[derive(Debug)]
 pub enum SwarmDirectory {
     Persistent(PathBuf),
@@ -76,7 +97,13 @@ pub struct LocalSwarmBuilder {
     number_of_validators: NonZeroUsize,
     dir: Option<PathBuf>,
     genesis_modules: Option<Vec<Vec<u8>>>,
+    initial_state: InitialState,
+    genesis_configuration: GenesisConfiguration,
     min_price_per_gas_unit: u64,
+    node_environment: NodeEnvironment,
+    ipv6_validators: usize,
+    num_validator_fullnodes: usize,
+    num_public_fullnodes: usize,
 }
 
 impl LocalSwarmBuilder {
@@ -88,7 +115,13 @@ impl LocalSwarmBuilder {
             number_of_validators: NonZeroUsize::new(1).unwrap(),
             dir: None,
             genesis_modules: None,
+            initial_state: InitialState::default(),
+            genesis_configuration: GenesisConfiguration::default(),
             min_price_per_gas_unit: 1,
+            node_environment: NodeEnvironment::default(),
+            ipv6_validators: 0,
+            num_validator_fullnodes: 0,
+            num_public_fullnodes: 0,
         }
     }
 
@@ -117,15 +150,63 @@ impl LocalSwarmBuilder {
         self
     }
 
+    /// Accounts and packages to create at genesis
+    pub fn initial_state(mut self, initial_state: InitialState) -> Self {
+        self.initial_state = initial_state;
+        self
+    }
+
     pub fn min_price_per_gas_unit(mut self, min_price_per_gas_unit: u64) -> Self {
         self.min_price_per_gas_unit = min_price_per_gas_unit;
         self
     }
 
-    pub fn build<R>(mut self, rng: R) -> Result<LocalSwarm>
+    /// On-chain configuration the chain starts with, e.g. the duration of epochs or the limits on
+    /// the size of blocks, instead of reconfiguring it once the swarm is up
+    pub fn genesis_configuration(mut self, genesis_configuration: GenesisConfiguration) -> Self {
+        self.genesis_configuration = genesis_configuration;
+        self
+    }
+
+    /// Applies the features to the validator template and sets the environment variables on
+    /// every node process, including fullnodes added later on
+    pub fn node_environment(mut self, node_environment: NodeEnvironment) -> Self {
+        self.node_environment = node_environment;
+        self
+    }
+
+    /// Number of validators, counting from the last one, listening on IPv6 for validator network
+    /// connections, to run a dual-stack validator set
+    pub fn ipv6_validators(mut self, ipv6_validators: usize) -> Self {
+        self.ipv6_validators = ipv6_validators;
+        self
+    }
+
+    /// Number of validators which get a VFN attached when the swarm is launched
+    pub fn num_validator_fullnodes(mut self, num_validator_fullnodes: usize) -> Self {
+        self.num_validator_fullnodes = num_validator_fullnodes;
+        self
+    }
+
+    /// Number of public fullnodes added when the swarm is launched, after the VFNs
+    pub fn num_public_fullnodes(mut self, num_public_fullnodes: usize) -> Self {
+        self.num_public_fullnodes = num_public_fullnodes;
+        self
+    }
+
+    /// Builds the swarm with keys drawn from `rng`, so that a seeded `rng` gives the same keys to
+    /// every node, including fullnodes added later on
+    pub fn build<R>(mut self, mut rng: R) -> Result<LocalSwarm>
     where
         R: ::rand::RngCore + ::rand::CryptoRng,
     {
+        ensure!(
+            self.num_validator_fullnodes <= self.number_of_validators.get(),
+            "Can't attach {} VFNs to {} validators",
+            self.num_validator_fullnodes,
+            self.number_of_validators
+        );
+
         let dir = if let Some(dir) = self.dir {
             if dir.exists() {
                 fs::remove_dir_all(&dir)?;
@@ -143,7 +224,32 @@ impl LocalSwarmBuilder {
             self.template.consensus.mempool_poll_count = 30;
         }
 
-        let (root_keys, genesis, genesis_waypoint, validators) = ValidatorBuilder::new(
+        // Chaos is injected through the failpoints admin API of the validators, see `ChaosSpec`.
+        // The token is drawn from the OS so the seeded rng gives the same keys either way.
+        if self.template.api.failpoints_admin_token.is_none() {
+            let token = OsRng
+                .sample_iter(&Alphanumeric)
+                .take(32)
+                .map(char::from)
+                .collect();
+            self.template.api.failpoints_admin_token = Some(token);
+        }
+
+        for feature in &self.node_environment.features {
+            match feature {
+                NodeFeature::ParallelExecution(concurrency_level) => {
+                    self.template.execution.concurrency_level = *concurrency_level;
+                }
+                NodeFeature::StateSyncV2 => {
+                    self.template.state_sync.enable_state_sync_v2 = true;
+                }
+            }
+        }
+        let env_vars = self.node_environment.env_vars;
+        let node_config_fn = self.node_environment.node_config_fn;
+        let resource_limits = self.node_environment.validator_resource_limits;
+
+        let builder = ValidatorBuilder::new(
             &dir,
             self.genesis_modules
                 .unwrap_or_else(|| cached_framework_packages::module_blobs().to_vec()),
@@ -151,28 +257,33 @@ impl LocalSwarmBuilder {
         .num_validators(self.number_of_validators)
         .template(self.template)
         .min_price_per_gas_unit(self.min_price_per_gas_unit)
-        .build(rng)?;
+        .initial_state(self.initial_state)
+        .genesis_configuration(self.genesis_configuration)
+        .ipv6_validators(self.ipv6_validators);
+        let mut seed = [0u8; 32];
+        rng.fill_bytes(&mut seed);
+        let (root_keys, genesis, genesis_waypoint, mut validators) =
+            build_test_network_genesis(builder, seed)?;
+        if let Some(node_config_fn) = &node_config_fn {
+            for validator in &mut validators {
+                node_config_fn.apply(&mut validator.config);
+                validator.config.save(validator.config_path())?;
+            }
+        }
 
-        // Get the initial version to start the nodes with, either the one provided or fallback to
-        // using the the latest version
         let versions = self.versions;
-        let initial_version = self.initial_version.unwrap_or_else(|| {
-            versions
-                .iter()
-                .max_by(|v1, v2| v1.0.cmp(v2.0))
-                .unwrap()
-                .0
-                .clone()
-        });
-        let version = versions.get(&initial_version).unwrap();
+        let version = initial_local_version(&versions, self.initial_version);
 
         let validators = validators
             .into_iter()
-            .map(|v| {
-                let node = LocalNode::new(version.to_owned(), v.name, v.directory)?;
+            .enumerate()
+            .map(|(i, v)| {
+                let node = LocalNode::new(version.to_owned(), v.name, v.directory)?
+                    .with_env_vars(env_vars.clone())
+                    .with_resource_limits(resource_limits.get(&i).copied().unwrap_or_default());
                 Ok((node.peer_id(), node))
             })
-            .collect::<Result<HashMap<_, _>>>()?;
+            .collect::<Result<BTreeMap<_, _>>>()?;
 
         let root_account = LocalAccount::new(
             aptos_sdk::types::account_config::aptos_root_address(),
@@ -186,12 +297,122 @@ impl LocalSwarmBuilder {
             genesis_waypoint,
             versions,
             validators,
-            fullnodes: HashMap::new(),
+            fullnodes: BTreeMap::new(),
+            twins: BTreeMap::new(),
             dir,
             root_account,
             chain_id: ChainId::test(),
+            account_pool: AccountPool::new(),
+            env_vars,
+            test_env_vars: BTreeMap::new(),
+            node_config_fn,
+            num_validator_fullnodes: self.num_validator_fullnodes,
+            num_public_fullnodes: self.num_public_fullnodes,
+            rng: StdRng::from_rng(rng)?,
         })
     }
+
+    /// Builds the swarm from a genesis produced outside of forge, e.g. by the genesis tool,
+    /// instead of generating one. Each of `validators` is the name and the directory of a
+    /// validator of `genesis`, holding its `node.yaml`. Settings of the builder which only shape
+    /// the generated genesis, like the number of validators or the template, are ignored.
+    pub fn build_with_genesis<R>(
+        self,
+        rng: R,
+        genesis: Transaction,
+        genesis_waypoint: Waypoint,
+        root_key: Ed25519PrivateKey,
+        validators: Vec<(String, PathBuf)>,
+    ) -> Result<LocalSwarm>
+    where
+        R: ::rand::RngCore + ::rand::CryptoRng,
+    {
+        ensure!(
+            !validators.is_empty(),
+            "Can't build a swarm without validators"
+        );
+        ensure!(
+            self.num_validator_fullnodes <= validators.len(),
+            "Can't attach {} VFNs to {} validators",
+            self.num_validator_fullnodes,
+            validators.len()
+        );
+
+        // The validators may live in the directory, so unlike `build` this doesn't clear it
+        let dir = if let Some(dir) = self.dir {
+            fs::create_dir_all(&dir)?;
+            SwarmDirectory::Persistent(dir)
+        } else {
+            SwarmDirectory::Temporary(TempDir::new()?)
+        };
+
+        let env_vars = self.node_environment.env_vars;
+        let node_config_fn = self.node_environment.node_config_fn;
+        let resource_limits = self.node_environment.validator_resource_limits;
+        if let Some(node_config_fn) = &node_config_fn {
+            for (_, directory) in &validators {
+                let config_path = directory.join("node.yaml");
+                let mut config = NodeConfig::load(&config_path)?;
+                node_config_fn.apply(&mut config);
+                config.save(&config_path)?;
+            }
+        }
+
+        let versions = self.versions;
+        let version = initial_local_version(&versions, self.initial_version);
+        let validators = validators
+            .into_iter()
+            .enumerate()
+            .map(|(i, (name, directory))| {
+                let node = LocalNode::new(version.to_owned(), name, directory)?
+                    .with_env_vars(env_vars.clone())
+                    .with_resource_limits(resource_limits.get(&i).copied().unwrap_or_default());
+                Ok((node.peer_id(), node))
+            })
+            .collect::<Result<BTreeMap<_, _>>>()?;
+
+        let root_account = LocalAccount::new(
+            aptos_sdk::types::account_config::aptos_root_address(),
+            AccountKey::from_private_key(root_key),
+            0,
+        );
+
+        Ok(LocalSwarm {
+            node_name_counter: validators.len() as u64,
+            genesis,
+            genesis_waypoint,
+            versions,
+            validators,
+            fullnodes: BTreeMap::new(),
+            twins: BTreeMap::new(),
+            dir,
+            root_account,
+            chain_id: ChainId::test(),
+            account_pool: AccountPool::new(),
+            env_vars,
+            test_env_vars: BTreeMap::new(),
+            node_config_fn,
+            num_validator_fullnodes: self.num_validator_fullnodes,
+            num_public_fullnodes: self.num_public_fullnodes,
+            rng: StdRng::from_rng(rng)?,
+        })
+    }
+}
+
+/// The version to start the nodes with, either the one provided or the latest one
+fn initial_local_version(
+    versions: &HashMap<Version, LocalVersion>,
+    initial_version: Option<Version>,
+) -> &LocalVersion {
+    let initial_version = initial_version.unwrap_or_else(|| {
+        versions
+            .iter()
+            .max_by(|v1, v2| v1.0.cmp(v2.0))
+            .unwrap()
+            .0
+            .clone()
+    });
+    versions.get(&initial_version).unwrap()
 }
 
 #[derive(Debug)]
@@ -200,11 +421,24 @@ pub struct LocalSwarm {
     genesis: Transaction,
     genesis_waypoint: Waypoint,
     versions: Arc<HashMap<Version, LocalVersion>>,
-    validators: HashMap<PeerId, LocalNode>,
-    fullnodes: HashMap<PeerId, LocalNode>,
+    validators: BTreeMap<PeerId, LocalNode>,
+    fullnodes: BTreeMap<PeerId, LocalNode>,
+    // The twins of validators, by the peer id they share with the validator
+    twins: BTreeMap<PeerId, LocalNode>,
     dir: SwarmDirectory,
     root_account: LocalAccount,
     chain_id: ChainId,
+    account_pool: AccountPool,
+    env_vars: BTreeMap<String, String>,
+    // Set on top of `env_vars` for the duration of a test, see `Swarm::set_test_env_vars`
+    test_env_vars: BTreeMap<String, String>,
+    // Applied to the config of the fullnodes added to the swarm, as it was to the validators
+    node_config_fn: Option<NodeConfigFn>,
+    // The fullnodes to add once the validators are launched
+    num_validator_fullnodes: usize,
+    num_public_fullnodes: usize,
+    // Draws the identities of the public fullnodes
+    rng: StdRng,
 }
 
 impl LocalSwarm {
@@ -224,16 +458,52 @@ impl LocalSwarm {
         self.wait_for_connectivity(deadline).await?;
         self.liveness_check(deadline).await?;
 
+        if self.num_validator_fullnodes > 0 || self.num_public_fullnodes > 0 {
+            self.launch_fullnodes().await?;
+        }
+
         println!("Swarm launched successfully.");
         Ok(())
     }
 
+    /// Attaches VFNs to validators and adds public fullnodes as configured by the builder, then
+    /// waits for all of them to be up and connected
+    async fn launch_fullnodes(&mut self) -> Result<()> {
+        let validators = self
+            .validators
+            .values()
+            .map(|v| (v.peer_id(), v.version()))
+            .collect::<Vec<_>>();
+        for (peer_id, version) in validators.iter().take(self.num_validator_fullnodes) {
+            self.add_validator_fullnode(
+                version,
+                NodeConfig::default_for_validator_full_node(),
+                *peer_id,
+            )
+            .await?;
+        }
+        let (_, version) = &validators[0];
+        for _ in 0..self.num_public_fullnodes {
+            self.add_fullnode(version, NodeConfig::default_for_public_full_node())?;
+        }
+
+        let deadline = Instant::now() + Duration::from_secs(60);
+        self.wait_for_startup().await?;
+        self.wait_for_connectivity(deadline).await?;
+        self.liveness_check(deadline).await
+    }
+
     async fn wait_for_startup(&mut self) -> Result<()> {
         let num_attempts = 10;
-        let mut done = vec![false; self.validators.len()];
+        let mut done = vec![false; self.validators.len() + self.fullnodes.len() + self.twins.len()];
         for i in 0..num_attempts {
             println!("Wait for startup attempt: {} of {}", i, num_attempts);
-            for (node, done) in self.validators.values_mut().zip(done.iter_mut()) {
+            let nodes = self
+                .validators
+                .values_mut()
+                .chain(self.fullnodes.values_mut())
+                .chain(self.twins.values_mut());
+            for (node, done) in nodes.zip(done.iter_mut()) {
                 if *done {
                     continue;
                 }
@@ -282,7 +552,7 @@ impl LocalSwarm {
         let mut validator_config = validator.config().clone();
         let name = self.node_name_counter.to_string();
         self.node_name_counter += 1;
-        let fullnode_config = FullnodeConfig::validator_fullnode(
+        let mut fullnode_config = FullnodeConfig::validator_fullnode(
             name,
             self.dir.as_ref(),
             template,
@@ -295,13 +565,15 @@ impl LocalSwarm {
         validator_config.save(validator.config_path())?;
         *validator.config_mut() = validator_config;
         validator.restart().await?;
+        self.apply_node_config_fn(&mut fullnode_config)?;
 
         let version = self.versions.get(version).unwrap();
         let mut fullnode = LocalNode::new(
             version.to_owned(),
             fullnode_config.name,
             fullnode_config.directory,
-        )?;
+        )?
+        .with_env_vars(self.node_env_vars());
 
         let peer_id = fullnode.peer_id();
         assert_eq!(peer_id, validator_peer_id);
@@ -312,23 +584,76 @@ impl LocalSwarm {
         Ok(peer_id)
     }
 
-    fn add_fullnode(&mut self, version: &Version, template: NodeConfig) -> Result<PeerId> {
+    /// Launches a twin of the validator: a second node with the identity and keys of the validator,
+    /// running on its own ports from a copy of the validator's storage. The validator is stopped
+    /// while its storage is copied.
+    async fn add_twin(&mut self, validator_peer_id: PeerId) -> Result<()> {
+        if self.twins.contains_key(&validator_peer_id) {
+            bail!("Twin of validator {} already launched", validator_peer_id);
+        }
+        let validator = self
+            .validators
+            .get_mut(&validator_peer_id)
+            .ok_or_else(|| anyhow!("no validator with peer_id: {}", validator_peer_id))?;
+
+        let name = format!("{}-twin", self.node_name_counter);
+        self.node_name_counter += 1;
+        let validator_dir = validator.config_path().parent().unwrap().to_path_buf();
+        let directory = self.dir.join(&name);
+        validator.stop();
+        let copied = copy_node_dir(&validator_dir, &directory);
+        validator.start()?;
+        copied?;
+
+        let mut config = validator.config().clone();
+        config.set_data_dir(directory.clone());
+        relocate_on_disk_storage(
+            &mut config.consensus.safety_rules.backend,
+            &validator_dir,
+            &directory,
+        );
+        for network in config
+            .validator_network
+            .iter_mut()
+            .chain(config.full_node_networks.iter_mut())
+        {
+            if let Identity::FromStorage(identity) = &mut network.identity {
+                relocate_on_disk_storage(&mut identity.backend, &validator_dir, &directory);
+            }
+        }
+        config.randomize_ports();
+        config.save(directory.join("node.yaml"))?;
+
+        let version = self.versions.get(&validator.version()).unwrap();
+        let mut twin = LocalNode::new(version.to_owned(), name, directory)?
+            .with_env_vars(self.node_env_vars());
+        twin.start()?;
+        self.twins.insert(validator_peer_id, twin);
+
+        self.wait_for_startup().await
+    }
+
+    fn add_fullnode(&mut self, version: &Version, mut template: NodeConfig) -> Result<PeerId> {
+        self.add_public_network_seeds(&mut template);
+        self.add_public_network_identity(&mut template);
         let name = self.node_name_counter.to_string();
         self.node_name_counter += 1;
-        let fullnode_config = FullnodeConfig::public_fullnode(
+        let mut fullnode_config = FullnodeConfig::public_fullnode(
             name,
             self.dir.as_ref(),
             template,
             &self.genesis_waypoint,
             &self.genesis,
         )?;
+        self.apply_node_config_fn(&mut fullnode_config)?;
 
         let version = self.versions.get(version).unwrap();
         let mut fullnode = LocalNode::new(
             version.to_owned(),
             fullnode_config.name,
             fullnode_config.directory,
-        )?;
+        )?
+        .with_env_vars(self.node_env_vars());
 
         let peer_id = fullnode.peer_id();
         fullnode.start()?;
@@ -338,6 +663,164 @@ impl LocalSwarm {
         Ok(peer_id)
     }
 
+    /// Applies the node config fn of the swarm to a fullnode which isn't started yet
+    fn apply_node_config_fn(&self, fullnode_config: &mut FullnodeConfig) -> Result<()> {
+        if let Some(node_config_fn) = &self.node_config_fn {
+            node_config_fn.apply(&mut fullnode_config.config);
+            fullnode_config.config.save(fullnode_config.config_path())?;
+        }
+        Ok(())
+    }
+
+    /// Gives the public network of a public fullnode an identity from the rng of the swarm, unless
+    /// the template has one of its own
+    fn add_public_network_identity(&mut self, template: &mut NodeConfig) {
+        if let Some(network) = template
+            .full_node_networks
+            .iter_mut()
+            .find(|network| network.network_id == NetworkId::Public)
+        {
+            if let Identity::None = network.identity {
+                let key = x25519::PrivateKey::generate(&mut self.rng);
+                let peer_id = account_address::from_identity_public_key(key.public_key());
+                network.identity = Identity::from_config(key, peer_id);
+            }
+        }
+    }
+
+    /// Seeds the public network of a public fullnode with the VFNs of the swarm, which are the
+    /// nodes serving the public network, unless the template has seeds of its own
+    fn add_public_network_seeds(&self, template: &mut NodeConfig) {
+        let public_network = match template
+            .full_node_networks
+            .iter_mut()
+            .find(|network| network.network_id == NetworkId::Public)
+        {
+            Some(network) if network.seeds.is_empty() => network,
+            _ => return,
+        };
+        for node in self.validators.values().chain(self.fullnodes.values()) {
+            let config = node.config();
+            // Public fullnodes don't have a VFN network
+            if !config
+                .full_node_networks
+                .iter()
+                .any(|network| network.network_id.is_vfn_network())
+            {
+                continue;
+            }
+            if let Some(network) = config
+                .full_node_networks
+                .iter()
+                .find(|network| network.network_id == NetworkId::Public)
+            {
+                public_network
+                    .seeds
+                    .extend(build_seed_for_network(network, PeerRole::Upstream));
+            }
+        }
+    }
+
+    /// Saves the state of the nodes to `dir`, to be brought back by `restore`, e.g. to reuse the
+    /// state of a long warm-up across runs of a performance test. All the nodes are stopped while
+    /// their DB and secure storage are copied, each to the directory named after it, then
+    /// relaunched.
+    pub async fn snapshot<P: AsRef<Path>>(&mut self, dir: P) -> Result<()> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        self.stop_nodes();
+        let saved = (|| -> Result<()> {
+            for node in self.nodes() {
+                node.save_state(&dir.join(node.name()))?;
+            }
+            fs::write(
+                dir.join(SNAPSHOT_WAYPOINT_FILE),
+                self.genesis_waypoint.to_string(),
+            )?;
+            Ok(())
+        })();
+        self.relaunch().await?;
+        saved
+    }
+
+    /// Brings the nodes back to the state `snapshot` saved to `dir`, then relaunches them.
+    ///
+    /// The snapshot has to be of a swarm with the same genesis and nodes of the same names. As the
+    /// genesis holds the addresses of the validators, which get random ports, a swarm built anew
+    /// has another genesis: the swarm restored is one built with `build_with_genesis` from the
+    /// directories of the validators of the snapshotted swarm, or the snapshotted swarm itself.
+    pub async fn restore<P: AsRef<Path>>(&mut self, dir: P) -> Result<()> {
+        let dir = dir.as_ref();
+        let waypoint_path = dir.join(SNAPSHOT_WAYPOINT_FILE);
+        let waypoint: Waypoint = fs::read_to_string(&waypoint_path)
+            .with_context(|| format!("Failed to read the snapshot waypoint {:?}", waypoint_path))?
+            .trim()
+            .parse()?;
+        ensure!(
+            waypoint == self.genesis_waypoint,
+            "The snapshot in {:?} is of a swarm with genesis waypoint {}, not {}",
+            dir,
+            waypoint,
+            self.genesis_waypoint
+        );
+        if let Some(node) = self.nodes().find(|node| !dir.join(node.name()).is_dir()) {
+            bail!("No snapshot of node '{}' in {:?}", node.name(), dir);
+        }
+
+        self.stop_nodes();
+        let restored = (|| -> Result<()> {
+            for node in self.nodes() {
+                node.load_state(&dir.join(node.name()))?;
+            }
+            Ok(())
+        })();
+        self.relaunch().await?;
+        restored
+    }
+
+    /// All the nodes of the swarm: validators, fullnodes and twins
+    fn nodes(&self) -> impl Iterator<Item = &LocalNode> {
+        self.validators
+            .values()
+            .chain(self.fullnodes.values())
+            .chain(self.twins.values())
+    }
+
+    /// The environment variables of the nodes: those of the swarm, overridden by those of the
+    /// running test
+    fn node_env_vars(&self) -> BTreeMap<String, String> {
+        let mut env_vars = self.env_vars.clone();
+        env_vars.extend(self.test_env_vars.clone());
+        env_vars
+    }
+
+    fn stop_nodes(&mut self) {
+        for node in self
+            .validators
+            .values_mut()
+            .chain(self.fullnodes.values_mut())
+            .chain(self.twins.values_mut())
+        {
+            node.stop();
+        }
+    }
+
+    /// Starts the nodes stopped by `stop_nodes` and waits for them to be up and connected
+    async fn relaunch(&mut self) -> Result<()> {
+        for node in self
+            .validators
+            .values_mut()
+            .chain(self.fullnodes.values_mut())
+            .chain(self.twins.values_mut())
+        {
+            node.start()?;
+        }
+        let deadline = Instant::now() + Duration::from_secs(60);
+        self.wait_for_startup().await?;
+        self.wait_for_connectivity(deadline).await?;
+        self.liveness_check(deadline).await
+    }
+
     pub fn chain_id(&self) -> ChainId {
         self.chain_id
     }
@@ -366,11 +849,46 @@ impl LocalSwarm {
         self.fullnodes.get_mut(&peer_id)
     }
 
+    /// Returns the failpoints injecting the chaos in the validators, see `ChaosSpec::failpoints`
+    fn chaos_failpoints(&self, chaos: &ChaosSpec) -> Result<Vec<(PeerId, String, String)>> {
+        let validators = self.validators.keys().copied().collect::<Vec<_>>();
+        chaos_failpoints(chaos, &validators)
+    }
+
     pub fn dir(&self) -> &Path {
         self.dir.as_ref()
     }
 }
 
+/// Copies the files of a node to `to`, except for its log
+fn copy_node_dir(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        if entry.file_name() == "log" {
+            continue;
+        }
+        let path = entry.path();
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_node_dir(&path, &target)?;
+        } else {
+            fs::copy(&path, &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Points an on disk storage under `from` to the same file under `to`
+fn relocate_on_disk_storage(backend: &mut SecureBackend, from: &Path, to: &Path) {
+    if let SecureBackend::OnDiskStorage(storage) = backend {
+        if let Ok(relative) = storage.path.strip_prefix(from) {
+            storage.path = to.join(relative);
+        }
+        storage.set_data_dir(to.to_path_buf());
+    }
+}
+
 impl Drop for LocalSwarm {
     fn drop(&mut self) {
         // If panicking, persist logs
@@ -441,8 +959,27 @@ impl Swarm for LocalSwarm {
         self.fullnodes.get_mut(&id).map(|v| v as &mut dyn FullNode)
     }
 
-    fn add_validator(&mut self, _version: &Version, _template: NodeConfig) -> Result<PeerId> {
-        todo!()
+    fn add_validator(&mut self, version: &Version, template: NodeConfig) -> Result<PeerId> {
+        // The validator isn't in the genesis, so it only joins the validator set once registered
+        // on chain, see `NetworkContext::register_validator`
+        let index = self.node_name_counter as usize;
+        self.node_name_counter += 1;
+        let mut validator = ValidatorBuilder::new(self.dir.as_ref(), vec![])
+            .template(template)
+            .build_standby_validator(index, &self.genesis, &self.genesis_waypoint)?;
+        if let Some(node_config_fn) = &self.node_config_fn {
+            node_config_fn.apply(&mut validator.config);
+            validator.config.save(validator.config_path())?;
+        }
+
+        let version = self.versions.get(version).unwrap();
+        let mut node = LocalNode::new(version.to_owned(), validator.name, validator.directory)?
+            .with_env_vars(self.node_env_vars());
+        let peer_id = node.peer_id();
+        node.start()?;
+        self.validators.insert(peer_id, node);
+
+        Ok(peer_id)
     }
 
     fn remove_validator(&mut self, _id: PeerId) -> Result<()> {
@@ -453,6 +990,18 @@ impl Swarm for LocalSwarm {
         self.add_fullnode(version, template)
     }
 
+    async fn add_twin_validator(&mut self, id: PeerId) -> Result<()> {
+        self.add_twin(id).await
+    }
+
+    fn remove_twin_validator(&mut self, id: PeerId) -> Result<()> {
+        if let Some(mut twin) = self.twins.remove(&id) {
+            twin.stop();
+        }
+
+        Ok(())
+    }
+
     fn remove_full_node(&mut self, id: PeerId) -> Result<()> {
         if let Some(mut fullnode) = self.fullnodes.remove(&id) {
             fullnode.stop();
@@ -461,10 +1010,58 @@ impl Swarm for LocalSwarm {
         Ok(())
     }
 
+    fn skew_validator_clocks(&mut self, skews: &[(PeerId, i64)]) -> Result<()> {
+        if let Some((id, _)) = skews
+            .iter()
+            .find(|(id, _)| !self.validators.contains_key(id))
+        {
+            bail!("Invalid id: {}", id);
+        }
+        // All of them are stopped before any is started, so that they come back together
+        for (id, skew_secs) in skews {
+            let validator = self.validators.get_mut(id).unwrap();
+            validator.stop();
+            validator.set_clock_skew(*skew_secs);
+        }
+        for (id, _) in skews {
+            self.validators.get_mut(id).unwrap().start()?;
+        }
+        Ok(())
+    }
+
+    async fn set_test_env_vars(&mut self, env_vars: &BTreeMap<String, String>) -> Result<()> {
+        self.test_env_vars = env_vars.clone();
+        let node_env_vars = self.node_env_vars();
+        self.stop_nodes();
+        for node in self
+            .validators
+            .values_mut()
+            .chain(self.fullnodes.values_mut())
+            .chain(self.twins.values_mut())
+        {
+            node.set_env_vars(node_env_vars.clone());
+        }
+        self.relaunch().await
+    }
+
     fn versions<'a>(&'a self) -> Box<dyn Iterator<Item = Version> + 'a> {
         Box::new(self.versions.keys().cloned())
     }
 
+    async fn inject_chaos(&mut self, chaos: ChaosSpec) -> Result<()> {
+        for (id, name, actions) in self.chaos_failpoints(&chaos)? {
+            self.validators[&id].set_failpoint(&name, &actions).await?;
+        }
+        Ok(())
+    }
+
+    async fn remove_chaos(&mut self, chaos: ChaosSpec) -> Result<()> {
+        for (id, name, _) in self.chaos_failpoints(&chaos)? {
+            self.validators[&id].remove_failpoint(&name).await?;
+        }
+        Ok(())
+    }
+
     fn chain_info(&mut self) -> ChainInfo<'_> {
         ChainInfo::new(
             &mut self.root_account,
@@ -477,8 +1074,133 @@ impl Swarm for LocalSwarm {
         )
     }
 
+    fn node_binary_size(&self, version: &Version) -> Result<Option<u64>> {
+        let version = self
+            .versions
+            .get(version)
+            .ok_or_else(|| anyhow!("Invalid version: {:?}", version))?;
+        let metadata = fs::metadata(version.bin())
+            .map_err(|e| anyhow!("Failed to stat {:?}: {}", version.bin(), e))?;
+        Ok(Some(metadata.len()))
+    }
+
     fn logs_location(&mut self) -> String {
         self.dir.persist();
         self.dir.display().to_string()
     }
+
+    // The directory doesn't need to be persisted, as the runner never drops an abandoned swarm.
+    // The test holding on to the swarm keeps its nodes, so their processes are killed through
+    // handles shared with the nodes.
+    fn abandoner(&mut self) -> SwarmAbandoner {
+        let location = self.dir.display().to_string();
+        let processes: Vec<(String, ProcessHandle)> = self
+            .validators
+            .values()
+            .chain(self.fullnodes.values())
+            .chain(self.twins.values())
+            .filter_map(|node| {
+                node.process_handle()
+                    .map(|process| (node.name().to_string(), process))
+            })
+            .collect();
+        Box::new(move |_| {
+            let mut failures = vec![];
+            for (name, process) in processes {
+                if let Err(e) = process.kill() {
+                    failures.push(format!("{}: {}", name, e));
+                }
+            }
+            ensure!(
+                failures.is_empty(),
+                "Failed to kill the processes of nodes {}, logs are in {}",
+                failures.join(", "),
+                location
+            );
+            Ok(location)
+        })
+    }
+
+    // Every local swarm starts a new chain, where accounts of earlier runs don't exist
+    async fn emitter_account_keys(&self) -> Result<Vec<Ed25519PrivateKey>> {
+        Ok(vec![])
+    }
+
+    async fn save_emitter_accounts(&mut self, _accounts: &[LocalAccount]) -> Result<()> {
+        Ok(())
+    }
+
+    fn account_pool(&mut self) -> &mut AccountPool {
+        &mut self.account_pool
+    }
+
+    fn chaos_driver(&self) -> Result<Box<dyn ChaosDriver>> {
+        let validators = self
+            .validators
+            .iter()
+            .map(|(id, v)| Ok((*id, (v.rest_client(), v.failpoints_admin_token()?))))
+            .collect::<Result<_>>()?;
+        Ok(Box::new(LocalChaosDriver {
+            validators,
+            full_nodes: self.fullnodes.keys().copied().collect(),
+        }))
+    }
+}
+
+/// The failpoints injecting the chaos between `validators`, all the validators of the swarm
+fn chaos_failpoints(
+    chaos: &ChaosSpec,
+    validators: &[PeerId],
+) -> Result<Vec<(PeerId, String, String)>> {
+    chaos.validate()?;
+    if let Some(id) = chaos
+        .validators()
+        .into_iter()
+        .find(|id| !validators.contains(id))
+    {
+        bail!("Invalid id: {}", id);
+    }
+    Ok(chaos.failpoints(validators))
+}
+
+/// Applies the chaos of a `ChaosSchedule` through the failpoints of the validators, with their
+/// REST clients and failpoints admin tokens. It can't kill fullnodes, as nothing would bring them
+/// back up: `ScheduledChaos::KillFullNode` fails, and the failure is logged in the report.
+struct LocalChaosDriver {
+    validators: HashMap<PeerId, (RestClient, String)>,
+    full_nodes: Vec<PeerId>,
+}
+
+#[async_trait::async_trait]
+impl ChaosDriver for LocalChaosDriver {
+    fn validators(&self) -> Vec<PeerId> {
+        self.validators.keys().copied().collect()
+    }
+
+    fn full_nodes(&self) -> Vec<PeerId> {
+        self.full_nodes.clone()
+    }
+
+    async fn kill_full_node(&self, id: PeerId) -> Result<()> {
+        bail!(
+            "Can't kill fullnode {}: the local backend doesn't restart nodes",
+            id
+        )
+    }
+
+    async fn inject_chaos(&self, chaos: &ChaosSpec) -> Result<()> {
+        for (id, name, actions) in chaos_failpoints(chaos, &self.validators())? {
+            let (client, admin_token) = &self.validators[&id];
+            client.set_failpoint(&name, &actions, admin_token).await?;
+        }
+        Ok(())
+    }
+
+    async fn remove_chaos(&self, chaos: &ChaosSpec) -> Result<()> {
+        for (id, name, _) in chaos_failpoints(chaos, &self.validators())? {
+            let (client, admin_token) = &self.validators[&id];
+            client.remove_failpoint(&name, admin_token).await?;
+        }
+        Ok(())
+    }
 }
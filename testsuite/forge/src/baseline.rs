@@ -0,0 +1,551 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{ReportedMetric, TestReport};
+use anyhow::{bail, format_err, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt, fs,
+    path::{Path, PathBuf},
+    process::Command,
+    str::FromStr,
+};
+use tempfile::TempDir;
+
+const AWS_BIN: &str = "aws";
+
+/// Rolling baselines of the metrics reported by recurring runs of a suite, so that a regression
+/// can be told apart from run to run noise. A metric's baseline is the median of its recent values.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct RollingBaselines {
+    /// The recent values of every metric, keyed by `<test>/<metric>`, oldest first
+    history: BTreeMap<String, VecDeque<f64>>,
+}
+
+/// A metric of a run that's worse than its baseline
+#[derive(Debug)]
+pub struct Regression {
+    pub test_name: String,
+    pub metric: String,
+    pub baseline: f64,
+    pub value: f64,
+}
+
+impl fmt::Display for Regression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {}: {:.1} (baseline {:.1}, {:+.1}%)",
+            self.test_name,
+            self.metric,
+            self.value,
+            self.baseline,
+            (self.value - self.baseline) / self.baseline * 100.0
+        )
+    }
+}
+
+impl RollingBaselines {
+    /// Loads the baselines from `path`, starting from scratch if the file doesn't exist yet
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format_err!("Failed to read baselines {:?}: {}", path, e))?;
+        serde_json::from_str(&contents)
+            .map_err(|e| format_err!("Failed to parse baselines {:?}: {}", path, e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = serde_json::to_string_pretty(self)?;
+        fs::write(path, contents)
+            .map_err(|e| format_err!("Failed to write baselines {:?}: {}", path, e))
+    }
+
+    /// Returns the metrics of the report that are worse than their baseline by more than
+    /// `threshold_pct` percent. Metrics with fewer than `min_samples` past values, or that aren't
+    /// known to be better when higher or lower, are never reported.
+    pub fn regressions(
+        &self,
+        report: &TestReport,
+        threshold_pct: f64,
+        min_samples: usize,
+    ) -> Vec<Regression> {
+        report
+            .metrics()
+            .iter()
+            .filter_map(|reported| {
+                let higher_is_better = higher_is_better(&reported.metric)?;
+                let history = self
+                    .history
+                    .get(&key(&reported.test_name, &reported.metric))?;
+                if history.len() < min_samples.max(1) {
+                    return None;
+                }
+                let baseline = median(history);
+                if baseline == 0.0 {
+                    return None;
+                }
+                let change_pct = (reported.value - baseline) / baseline * 100.0;
+                let worse_pct = if higher_is_better {
+                    -change_pct
+                } else {
+                    change_pct
+                };
+                (worse_pct > threshold_pct).then(|| Regression {
+                    test_name: reported.test_name.clone(),
+                    metric: reported.metric.clone(),
+                    baseline,
+                    value: reported.value,
+                })
+            })
+            .collect()
+    }
+
+    /// Adds the metrics of the report to the baselines, keeping the last `window` values of each
+    pub fn record(&mut self, report: &TestReport, window: usize) {
+        for reported in report.metrics() {
+            let history = self
+                .history
+                .entry(key(&reported.test_name, &reported.metric))
+                .or_default();
+            history.push_back(reported.value);
+            while history.len() > window {
+                history.pop_front();
+            }
+        }
+    }
+}
+
+fn key(test_name: &str, metric: &str) -> String {
+    format!("{}/{}", test_name, metric)
+}
+
+/// Whether a higher value of the metric is better, for the metrics reported by the tests
+fn higher_is_better(metric: &str) -> Option<bool> {
+    if metric.contains("tps") {
+        Some(true)
+    } else if metric.contains("latency")
+        || metric.contains("expired")
+        || metric.contains("rejected")
+        || metric.contains("binary_size")
+        || metric.contains("time_to_healthy")
+        || metric.contains("recovery_time")
+        || metric.contains("bytes_per_txn")
+    {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+fn median(values: &VecDeque<f64>) -> f64 {
+    let mut sorted: Vec<_> = values.iter().copied().collect();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let mid = sorted.len() / 2;
+    if sorted.len() % 2 == 0 {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+/// The throughput and latency a suite reached on a commit, which runs of later commits are gated
+/// on. Keyed by `<test>/<metric>`, like the rolling baselines.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct PerformanceBaseline {
+    pub commit: String,
+    pub metrics: BTreeMap<String, f64>,
+}
+
+/// How much worse than the baseline a run may perform before it fails
+#[derive(Clone, Copy, Debug)]
+pub struct RegressionThresholds {
+    pub max_tps_drop_pct: f64,
+    pub max_p99_latency_rise_pct: f64,
+}
+
+/// The part of a json report of a run, see `JsonReport`, that baselines are made from
+#[derive(Deserialize)]
+struct ReportMetrics {
+    metrics: Vec<ReportedMetric>,
+}
+
+impl PerformanceBaseline {
+    /// Makes a baseline of the TPS and p99 latency in the metrics of a run of `commit`
+    pub fn from_metrics<'a>(
+        commit: String,
+        metrics: impl IntoIterator<Item = &'a ReportedMetric>,
+    ) -> Self {
+        let metrics = metrics
+            .into_iter()
+            .filter(|reported| gated_metric(&reported.metric).is_some())
+            .map(|reported| (key(&reported.test_name, &reported.metric), reported.value))
+            .collect();
+        Self { commit, metrics }
+    }
+
+    /// Makes a baseline of the metrics of the json report of a run of `commit`, as written with
+    /// `--report-format json`
+    pub fn from_json_report(commit: String, path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format_err!("Failed to read json report {:?}: {}", path, e))?;
+        let report: ReportMetrics = serde_json::from_str(&contents)
+            .map_err(|e| format_err!("Failed to parse json report {:?}: {}", path, e))?;
+        Ok(Self::from_metrics(commit, &report.metrics))
+    }
+
+    /// Returns the TPS and p99 latency of the report that are worse than the baseline by more than
+    /// the thresholds. Metrics of tests the baseline has no value for are never reported.
+    pub fn regressions(
+        &self,
+        report: &TestReport,
+        thresholds: RegressionThresholds,
+    ) -> Vec<Regression> {
+        report
+            .metrics()
+            .iter()
+            .filter_map(|reported| {
+                let higher_is_better = gated_metric(&reported.metric)?;
+                let baseline = *self
+                    .metrics
+                    .get(&key(&reported.test_name, &reported.metric))?;
+                if baseline == 0.0 {
+                    return None;
+                }
+                let change_pct = (reported.value - baseline) / baseline * 100.0;
+                let regressed = if higher_is_better {
+                    -change_pct > thresholds.max_tps_drop_pct
+                } else {
+                    change_pct > thresholds.max_p99_latency_rise_pct
+                };
+                regressed.then(|| Regression {
+                    test_name: reported.test_name.clone(),
+                    metric: reported.metric.clone(),
+                    baseline,
+                    value: reported.value,
+                })
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for PerformanceBaseline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Baseline of {}", self.commit)?;
+        for (metric, value) in &self.metrics {
+            write!(f, "\n{}: {:.1}", metric, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether a higher value is better for the metrics runs are gated on, i.e. the TPS and p99
+/// latency of `TestReport::report_txn_stats`
+fn gated_metric(metric: &str) -> Option<bool> {
+    match metric {
+        "avg_tps" => Some(true),
+        "p99_latency" => Some(false),
+        _ => None,
+    }
+}
+
+/// Where the performance baselines of the commits are kept: either a local json file holding
+/// all of them, or an S3 prefix under which each is an object named after its commit
+#[derive(Clone, Debug)]
+pub enum BaselineStore {
+    File(PathBuf),
+    S3(String),
+}
+
+impl FromStr for BaselineStore {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if s.is_empty() {
+            bail!("Empty baseline location");
+        }
+        Ok(if s.starts_with("s3://") {
+            Self::S3(s.trim_end_matches('/').to_string())
+        } else {
+            Self::File(PathBuf::from(s))
+        })
+    }
+}
+
+impl fmt::Display for BaselineStore {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "{}", path.display()),
+            Self::S3(prefix) => write!(f, "{}", prefix),
+        }
+    }
+}
+
+impl BaselineStore {
+    /// Loads the baseline of `commit`, if one was stored
+    pub fn load(&self, commit: &str) -> Result<Option<PerformanceBaseline>> {
+        match self {
+            Self::File(path) => Ok(load_baseline_file(path)?.remove(commit)),
+            Self::S3(prefix) => {
+                let uri = format!("{}/{}.json", prefix, commit);
+                let output = Command::new(AWS_BIN)
+                    .args(&["s3", "cp", &uri, "-"])
+                    .output()
+                    .map_err(|e| format_err!("Failed to run {}: {}", AWS_BIN, e))?;
+                if !output.status.success() {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    // `aws s3 cp` reports a missing object as a 404 on its HeadObject call
+                    if stderr.contains("404") {
+                        return Ok(None);
+                    }
+                    bail!("Failed to download baseline {}: {}", uri, stderr);
+                }
+                let baseline = serde_json::from_slice(&output.stdout)
+                    .map_err(|e| format_err!("Failed to parse baseline {}: {}", uri, e))?;
+                Ok(Some(baseline))
+            }
+        }
+    }
+
+    /// Stores the baseline of its commit, replacing any previous one
+    pub fn save(&self, baseline: &PerformanceBaseline) -> Result<()> {
+        match self {
+            Self::File(path) => {
+                let mut baselines = load_baseline_file(path)?;
+                baselines.insert(baseline.commit.clone(), baseline.clone());
+                let contents = serde_json::to_string_pretty(&baselines)?;
+                fs::write(path, contents)
+                    .map_err(|e| format_err!("Failed to write baselines {:?}: {}", path, e))
+            }
+            Self::S3(prefix) => {
+                let uri = format!("{}/{}.json", prefix, baseline.commit);
+                let tmp_dir = TempDir::new()?;
+                let file = tmp_dir.path().join("baseline.json");
+                fs::write(&file, serde_json::to_string_pretty(baseline)?)?;
+                let output = Command::new(AWS_BIN)
+                    .args(&["s3", "cp", file.to_str().unwrap(), &uri])
+                    .output()
+                    .map_err(|e| format_err!("Failed to run {}: {}", AWS_BIN, e))?;
+                if !output.status.success() {
+                    bail!(
+                        "Failed to upload baseline {}: {}",
+                        uri,
+                        String::from_utf8_lossy(&output.stderr)
+                    );
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Loads the baselines of a local file by commit, none if the file doesn't exist yet
+fn load_baseline_file(path: &Path) -> Result<BTreeMap<String, PerformanceBaseline>> {
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format_err!("Failed to read baselines {:?}: {}", path, e))?;
+    serde_json::from_str(&contents)
+        .map_err(|e| format_err!("Failed to parse baselines {:?}: {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const THRESHOLDS: RegressionThresholds = RegressionThresholds {
+        max_tps_drop_pct: 10.0,
+        max_p99_latency_rise_pct: 20.0,
+    };
+
+    fn report(metrics: &[(&str, &str, f64)]) -> TestReport {
+        let mut report = TestReport::new();
+        for (test, metric, value) in metrics {
+            report.report_metric(test, metric, *value);
+        }
+        report
+    }
+
+    fn regressed(regressions: Vec<Regression>) -> Vec<String> {
+        regressions
+            .into_iter()
+            .map(|r| key(&r.test_name, &r.metric))
+            .collect()
+    }
+
+    fn baseline() -> PerformanceBaseline {
+        PerformanceBaseline::from_metrics(
+            "abc".to_string(),
+            report(&[
+                ("perf", "avg_tps", 1000.0),
+                ("perf", "p99_latency", 500.0),
+                ("perf", "expired_txn", 3.0),
+            ])
+            .metrics(),
+        )
+    }
+
+    #[test]
+    fn test_from_metrics_keeps_gated_metrics() {
+        let baseline = baseline();
+        assert_eq!(baseline.commit, "abc");
+        assert_eq!(
+            baseline.metrics.keys().collect::<Vec<_>>(),
+            vec!["perf/avg_tps", "perf/p99_latency"]
+        );
+    }
+
+    #[test]
+    fn test_tps_tolerance() {
+        let baseline = baseline();
+        // a drop of exactly the threshold passes, and higher tps never regresses
+        for tps in [900.0, 950.0, 1500.0] {
+            let regressions =
+                baseline.regressions(&report(&[("perf", "avg_tps", tps)]), THRESHOLDS);
+            assert!(regressions.is_empty(), "{} tps", tps);
+        }
+        let regressions = baseline.regressions(&report(&[("perf", "avg_tps", 899.0)]), THRESHOLDS);
+        assert_eq!(regressed(regressions), vec!["perf/avg_tps"]);
+    }
+
+    #[test]
+    fn test_latency_tolerance() {
+        let baseline = baseline();
+        for latency in [100.0, 500.0, 600.0] {
+            let regressions =
+                baseline.regressions(&report(&[("perf", "p99_latency", latency)]), THRESHOLDS);
+            assert!(regressions.is_empty(), "{}ms", latency);
+        }
+        let regressions =
+            baseline.regressions(&report(&[("perf", "p99_latency", 601.0)]), THRESHOLDS);
+        assert_eq!(regressed(regressions), vec!["perf/p99_latency"]);
+    }
+
+    #[test]
+    fn test_ungated_metrics_never_regress() {
+        let mut baseline = baseline();
+        baseline.metrics.insert("zero/avg_tps".to_string(), 0.0);
+        let regressions = baseline.regressions(
+            &report(&[
+                // not gated, even though worse
+                ("perf", "expired_txn", 300.0),
+                // no baseline for the test
+                ("other", "avg_tps", 1.0),
+                // no percentage of a zero baseline
+                ("zero", "avg_tps", 1.0),
+            ]),
+            THRESHOLDS,
+        );
+        assert!(regressions.is_empty());
+    }
+
+    #[test]
+    fn test_regression_display() {
+        let regression = Regression {
+            test_name: "perf".to_string(),
+            metric: "avg_tps".to_string(),
+            baseline: 1000.0,
+            value: 850.0,
+        };
+        assert_eq!(
+            regression.to_string(),
+            "perf avg_tps: 850.0 (baseline 1000.0, -15.0%)"
+        );
+    }
+
+    #[test]
+    fn test_rolling_baselines() {
+        let mut baselines = RollingBaselines::default();
+        for tps in [100.0, 300.0, 200.0, 1000.0] {
+            baselines.record(&report(&[("perf", "avg_tps", tps)]), 3);
+        }
+        // the window keeps 300, 200 and 1000, of which the median is 300
+        let run = report(&[("perf", "avg_tps", 260.0)]);
+        assert!(baselines.regressions(&run, 15.0, 3).is_empty());
+        assert_eq!(
+            regressed(baselines.regressions(&run, 10.0, 3)),
+            vec!["perf/avg_tps"]
+        );
+        // too few samples to tell
+        assert!(baselines.regressions(&run, 10.0, 4).is_empty());
+
+        let mut baselines = RollingBaselines::default();
+        for latency in [10.0, 20.0] {
+            baselines.record(&report(&[("perf", "p99_latency", latency)]), 10);
+        }
+        // the median of an even number of values is the mean of the middle ones
+        let regressions = baselines.regressions(&report(&[("perf", "p99_latency", 18.0)]), 10.0, 1);
+        assert_eq!(regressions[0].baseline, 15.0);
+        assert!(baselines
+            .regressions(&report(&[("perf", "p99_latency", 16.0)]), 10.0, 1)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_higher_is_better() {
+        assert_eq!(higher_is_better("avg_tps"), Some(true));
+        assert_eq!(higher_is_better("p99_latency"), Some(false));
+        assert_eq!(higher_is_better("recovery_time"), Some(false));
+        assert_eq!(higher_is_better("committed_txn"), None);
+    }
+
+    #[test]
+    fn test_file_store() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("baselines.json");
+        let store = BaselineStore::File(path.clone());
+        assert!(store.load("abc").unwrap().is_none());
+
+        store.save(&baseline()).unwrap();
+        let mut other = baseline();
+        other.commit = "def".to_string();
+        other.metrics.insert("perf/avg_tps".to_string(), 2000.0);
+        store.save(&other).unwrap();
+
+        let loaded = store.load("abc").unwrap().unwrap();
+        assert_eq!(loaded.metrics, baseline().metrics);
+        assert_eq!(
+            store.load("def").unwrap().unwrap().metrics["perf/avg_tps"],
+            2000.0
+        );
+        assert!(store.load("ghi").unwrap().is_none());
+
+        fs::write(&path, "not json").unwrap();
+        assert!(store.load("abc").is_err());
+    }
+
+    #[test]
+    fn test_from_json_report() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("report.json");
+        let report = json!({
+            "text": "",
+            "metrics": [
+                { "test_name": "perf", "metric": "avg_tps", "value": 1000.0 },
+                { "test_name": "perf", "metric": "committed_txn", "value": 5.0 },
+            ],
+        });
+        fs::write(&path, report.to_string()).unwrap();
+        let baseline = PerformanceBaseline::from_json_report("abc".to_string(), &path).unwrap();
+        assert_eq!(baseline.metrics.len(), 1);
+        assert_eq!(baseline.metrics["perf/avg_tps"], 1000.0);
+    }
+
+    #[test]
+    fn test_parse_store() {
+        assert!(matches!(
+            "s3://bucket/baselines/".parse::<BaselineStore>().unwrap(),
+            BaselineStore::S3(prefix) if prefix == "s3://bucket/baselines"
+        ));
+        assert!(matches!(
+            "baselines.json".parse::<BaselineStore>().unwrap(),
+            BaselineStore::File(path) if path == Path::new("baselines.json")
+        ));
+        assert!("".parse::<BaselineStore>().is_err());
+    }
+}
@@ -0,0 +1,40 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_sdk::types::LocalAccount;
+use std::mem;
+
+/// The emitter accounts minted on the chain of a swarm, kept with their sequence numbers across
+/// the traffic of successive tests so that each job only mints the accounts the pool lacks.
+///
+/// A job borrows the accounts with [`AccountPool::take`] and returns them with
+/// [`AccountPool::put_back`] once it stops. The emitter re-syncs the sequence numbers of the
+/// accounts whose transactions expired, so the pool hands out accounts matching the chain.
+#[derive(Debug, Default)]
+pub struct AccountPool {
+    accounts: Vec<LocalAccount>,
+}
+
+impl AccountPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    /// Takes all the accounts out of the pool for a job
+    pub fn take(&mut self) -> Vec<LocalAccount> {
+        mem::take(&mut self.accounts)
+    }
+
+    /// Returns the accounts of a job to the pool, along with any it minted
+    pub fn put_back(&mut self, mut accounts: Vec<LocalAccount>) {
+        self.accounts.append(&mut accounts);
+    }
+}
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::{ChainInfo, CoreContext, Test};
-use crate::{Result, TestReport};
+use crate::{api_recording, Result, TestReport};
 use aptos_rest_client::Client as RestClient;
 use aptos_sdk::types::LocalAccount;
 use reqwest::Url;
@@ -41,7 +41,9 @@ impl<'t> AdminContext<'t> {
     }
 
     pub fn rest_client(&self) -> RestClient {
-        RestClient::new(Url::parse(self.chain_info.rest_api()).unwrap())
+        RestClient::new(api_recording::endpoint(
+            Url::parse(self.chain_info.rest_api()).unwrap(),
+        ))
     }
 
     pub fn chain_info(&mut self) -> &mut ChainInfo<'t> {
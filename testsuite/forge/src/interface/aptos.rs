@@ -2,7 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::Test;
-use crate::{CoreContext, Result, TestReport};
+use crate::{api_recording, CoreContext, Result, TestReport};
+use anyhow::anyhow;
 use aptos_rest_client::{Client as RestClient, PendingTransaction};
 use aptos_sdk::{
     crypto::ed25519::Ed25519PublicKey,
@@ -10,14 +11,45 @@ use aptos_sdk::{
     transaction_builder::TransactionFactory,
     types::{
         account_address::AccountAddress,
+        account_config::aptos_root_address,
         chain_id::ChainId,
-        transaction::authenticator::{AuthenticationKey, AuthenticationKeyPreimage},
+        transaction::{
+            authenticator::{AuthenticationKey, AuthenticationKeyPreimage},
+            TransactionPayload,
+        },
         LocalAccount,
     },
 };
 use aptos_transaction_builder::aptos_stdlib;
 use reqwest::Url;
 
+/// Coins covering the gas of a transaction of a validator owner, at the price and maximum amount
+/// of gas of `AptosPublicInfo::transaction_factory`
+const VALIDATOR_OWNER_GAS_COINS: u64 = 1000;
+/// How long the stake of a validator registered by `AptosPublicInfo::register_validator_candidate`
+/// is locked for, past the minimum lock period of a day of `Stake::delegate_stake`
+const VALIDATOR_STAKE_LOCK_SECS: u64 = 2 * 86400;
+
+/// What a validator registers on chain to become a candidate of the validator set, see
+/// `Stake::register_validator_candidate`
+#[derive(Clone, Debug)]
+pub struct ValidatorCandidate {
+    pub consensus_pubkey: Ed25519PublicKey,
+    /// BCS-encoded addresses of the validator network of the validator, with its key
+    pub validator_network_addresses: Vec<u8>,
+    /// BCS-encoded addresses of the public network of the validator, with its key
+    pub fullnode_network_addresses: Vec<u8>,
+}
+
+/// A validator of the current epoch, as in the validator set on chain
+#[derive(Clone, Debug)]
+pub struct ActiveValidator {
+    pub address: AccountAddress,
+    /// Consensus public key, hex encoded
+    pub consensus_pubkey: String,
+    pub voting_power: u64,
+}
+
 #[async_trait::async_trait]
 pub trait AptosTest: Test {
     /// Executes the test against the given context.
@@ -123,7 +155,7 @@ impl<'t> AptosPublicInfo<'t> {
         rest_api_url_str: String,
         root_account: &'t mut LocalAccount,
     ) -> Self {
-        let rest_api_url = Url::parse(&rest_api_url_str).unwrap();
+        let rest_api_url = api_recording::endpoint(Url::parse(&rest_api_url_str).unwrap());
         Self {
             rest_client: RestClient::new(rest_api_url.clone()),
             rest_api_url,
@@ -169,6 +201,166 @@ impl<'t> AptosPublicInfo<'t> {
         Ok(pending_txn)
     }
 
+    /// Creates an account with `amount` coins which, like the root account, can create accounts
+    /// and mint, so that a test can use it in place of the root account while other tests run
+    pub async fn create_delegated_root_account(
+        &mut self,
+        rng: &mut ::rand::rngs::StdRng,
+        amount: u64,
+    ) -> Result<LocalAccount> {
+        let mut account = LocalAccount::generate(rng);
+        self.create_user_account(account.public_key()).await?;
+        self.mint(account.address(), amount).await?;
+
+        let delegate_txn =
+            self.root_account
+                .sign_with_transaction_builder(self.transaction_factory().payload(
+                    aptos_stdlib::encode_test_coin_delegate_mint_capability(account.address()),
+                ));
+        self.rest_client.submit_and_wait(&delegate_txn).await?;
+        let claim_txn = account.sign_with_transaction_builder(
+            self.transaction_factory()
+                .payload(aptos_stdlib::encode_test_coin_claim_mint_capability()),
+        );
+        self.rest_client.submit_and_wait(&claim_txn).await?;
+        Ok(account)
+    }
+
+    /// Registers `consensus_key` as the consensus key of the validator of `owner`, which takes
+    /// effect at the next epoch. The root account mints the coins the owner pays the gas with.
+    pub async fn rotate_consensus_key(
+        &mut self,
+        owner: &mut LocalAccount,
+        consensus_key: &Ed25519PublicKey,
+    ) -> Result<()> {
+        self.submit_as_owner(
+            owner,
+            aptos_stdlib::encode_stake_rotate_consensus_key(consensus_key.to_bytes().to_vec()),
+        )
+        .await
+    }
+
+    /// Registers the validator of `owner` as a candidate of the validator set, with `stake`
+    /// coins the root account mints delegated to it by the owner, so that it can join the
+    /// validator set. The owner account is created first if it doesn't exist, as for validators
+    /// which weren't part of the genesis.
+    pub async fn register_validator_candidate(
+        &mut self,
+        owner: &mut LocalAccount,
+        candidate: &ValidatorCandidate,
+        stake: u64,
+    ) -> Result<()> {
+        if self.rest_client.get_account(owner.address()).await.is_err() {
+            self.create_user_account(owner.public_key()).await?;
+        }
+        self.mint(owner.address(), stake).await?;
+        self.submit_as_owner(
+            owner,
+            aptos_stdlib::encode_stake_register_validator_candidate(
+                candidate.consensus_pubkey.to_bytes().to_vec(),
+                candidate.validator_network_addresses.clone(),
+                candidate.fullnode_network_addresses.clone(),
+            ),
+        )
+        .await?;
+
+        let now_secs = self
+            .rest_client
+            .get_ledger_information()
+            .await?
+            .into_inner()
+            .timestamp_usecs
+            / 1_000_000;
+        let address = owner.address();
+        self.submit_as_owner(
+            owner,
+            aptos_stdlib::encode_stake_delegate_stake(
+                address,
+                stake,
+                now_secs + VALIDATOR_STAKE_LOCK_SECS,
+            ),
+        )
+        .await
+    }
+
+    /// Submits a transaction of the owner of a validator, with its current sequence number. The
+    /// root account mints the coins the owner pays the gas with.
+    async fn submit_as_owner(
+        &mut self,
+        owner: &mut LocalAccount,
+        payload: TransactionPayload,
+    ) -> Result<()> {
+        *owner.sequence_number_mut() = self
+            .rest_client
+            .get_account(owner.address())
+            .await?
+            .into_inner()
+            .sequence_number;
+        self.mint(owner.address(), VALIDATOR_OWNER_GAS_COINS)
+            .await?;
+        let txn = owner.sign_with_transaction_builder(self.transaction_factory().payload(payload));
+        self.rest_client.submit_and_wait(&txn).await?;
+        Ok(())
+    }
+
+    /// Forces the start of a new epoch, in which the pending changes to the validator set take
+    /// effect
+    pub async fn reconfigure(&mut self) -> Result<()> {
+        let reconfigure_txn = self.root_account.sign_with_transaction_builder(
+            self.transaction_factory()
+                .payload(aptos_stdlib::encode_reconfiguration_force_reconfigure()),
+        );
+        self.rest_client.submit_and_wait(&reconfigure_txn).await?;
+        Ok(())
+    }
+
+    /// Validators of the current epoch
+    pub async fn validator_set(&self) -> Result<Vec<ActiveValidator>> {
+        let validator_set = self
+            .rest_client
+            .get_account_resource(aptos_root_address(), "0x1::Stake::ValidatorSet")
+            .await?
+            .into_inner()
+            .ok_or_else(|| anyhow!("No validator set on chain"))?
+            .data;
+        validator_set["active_validators"]
+            .as_array()
+            .ok_or_else(|| anyhow!("Invalid validator set: {}", validator_set))?
+            .iter()
+            .map(|info| {
+                let invalid = || anyhow!("Invalid validator info: {}", info);
+                let address = info["addr"].as_str().ok_or_else(invalid)?;
+                let consensus_pubkey = info["config"]["consensus_pubkey"]
+                    .as_str()
+                    .ok_or_else(invalid)?;
+                // u64 fields of Move resources are serialized as strings
+                let voting_power = info["voting_power"]
+                    .as_str()
+                    .and_then(|power| power.parse().ok())
+                    .ok_or_else(invalid)?;
+                Ok(ActiveValidator {
+                    address: AccountAddress::from_hex_literal(address)?,
+                    consensus_pubkey: consensus_pubkey.to_string(),
+                    voting_power,
+                })
+            })
+            .collect()
+    }
+
+    /// Has the validator of `owner` leave the validator set at the next epoch
+    pub async fn leave_validator_set(&mut self, owner: &mut LocalAccount) -> Result<()> {
+        self.submit_as_owner(owner, aptos_stdlib::encode_stake_leave_validator_set())
+            .await
+    }
+
+    /// Has the validator of `owner` join the validator set at the next epoch, with the consensus
+    /// key and addresses already in its validator config. It has to be registered as a candidate
+    /// with enough stake, see [`AptosPublicInfo::register_validator_candidate`].
+    pub async fn join_validator_set(&mut self, owner: &mut LocalAccount) -> Result<()> {
+        self.submit_as_owner(owner, aptos_stdlib::encode_stake_join_validator_set())
+            .await
+    }
+
     pub fn transaction_factory(&self) -> TransactionFactory {
         TransactionFactory::new(self.chain_id)
             .with_gas_unit_price(1)
@@ -1,7 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::AptosPublicInfo;
+use crate::{api_recording, AptosPublicInfo};
 use aptos_rest_client::Client as RestClient;
 use aptos_sdk::{
     transaction_builder::TransactionFactory,
@@ -38,7 +38,10 @@ impl<'t> ChainInfo<'t> {
     }
 
     pub fn rest_client(&self) -> RestClient {
-        RestClient::new(Url::parse(self.rest_api()).unwrap())
+        RestClient::new(api_recording::endpoint(
+            Url::parse(self.rest_api()).unwrap(),
+        ))
+        .with_chain_id(self.chain_id)
     }
 
     pub fn chain_id(&self) -> ChainId {
@@ -0,0 +1,232 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::Result;
+use anyhow::bail;
+use aptos_sdk::types::PeerId;
+use network::peer::{DELAY_INBOUND_FAILPOINT, DROP_INBOUND_FAILPOINT};
+use std::time::Duration;
+
+/// A degraded network condition injected between the validators of a swarm, see
+/// [`Swarm::inject_chaos`](crate::Swarm::inject_chaos).
+///
+/// The local backend injects the chaos in the nodes themselves, through network failpoints on the
+/// messages they receive, so it only affects messages rather than packets and the validators need
+/// a `failpoints_admin_token` in their API config, which `LocalSwarmBuilder::build` sets unless
+/// the template has one. Locally, chaos between a pair of validators
+/// replaces any chaos of the same kind already injected between them. The k8s backend injects it
+/// with chaos-mesh, which has to be installed on the cluster.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ChaosSpec {
+    /// Cuts all traffic between the validators of `group_a` and those of `group_b`
+    NetworkPartition {
+        group_a: Vec<PeerId>,
+        group_b: Vec<PeerId>,
+    },
+    /// Delays all traffic sent by the `targets` by `latency`
+    NetworkDelay {
+        targets: Vec<PeerId>,
+        latency: Duration,
+    },
+    /// Drops `loss_pct` percent of the traffic sent by the `targets`
+    PacketLoss { targets: Vec<PeerId>, loss_pct: u32 },
+}
+
+impl ChaosSpec {
+    /// Returns all the validators the chaos applies to
+    pub fn validators(&self) -> Vec<PeerId> {
+        match self {
+            ChaosSpec::NetworkPartition { group_a, group_b } => {
+                group_a.iter().chain(group_b.iter()).copied().collect()
+            }
+            ChaosSpec::NetworkDelay { targets, .. } | ChaosSpec::PacketLoss { targets, .. } => {
+                targets.clone()
+            }
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            ChaosSpec::NetworkPartition { group_a, group_b } => {
+                if group_a.is_empty() || group_b.is_empty() {
+                    bail!("Both sides of a network partition need validators");
+                }
+                if group_a.iter().any(|peer_id| group_b.contains(peer_id)) {
+                    bail!("A validator can't be on both sides of a network partition");
+                }
+            }
+            ChaosSpec::NetworkDelay { targets, latency } => {
+                if targets.is_empty() || latency.is_zero() {
+                    bail!("A network delay needs targets and a latency");
+                }
+            }
+            ChaosSpec::PacketLoss { targets, loss_pct } => {
+                if targets.is_empty() || *loss_pct == 0 || *loss_pct > 100 {
+                    bail!("A packet loss needs targets and a loss between 1 and 100 percent");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the failpoints injecting the chaos in the nodes, as the validator to configure the
+    /// failpoint on, the failpoint name and its actions. `validators` are all the validators of
+    /// the swarm.
+    pub fn failpoints(&self, validators: &[PeerId]) -> Vec<(PeerId, String, String)> {
+        let drop_from = |peer_id: &PeerId| format!("{}::{}", DROP_INBOUND_FAILPOINT, peer_id);
+        let delay_from = |peer_id: &PeerId| format!("{}::{}", DELAY_INBOUND_FAILPOINT, peer_id);
+        let receivers_from = |targets: &[PeerId]| {
+            targets
+                .iter()
+                .flat_map(|target| {
+                    validators
+                        .iter()
+                        .filter(move |receiver| *receiver != target)
+                        .map(move |receiver| (*receiver, *target))
+                })
+                .collect::<Vec<_>>()
+        };
+
+        match self {
+            ChaosSpec::NetworkPartition { group_a, group_b } => group_a
+                .iter()
+                .flat_map(|a| group_b.iter().map(move |b| (*a, *b)))
+                .flat_map(|(a, b)| {
+                    vec![
+                        (a, drop_from(&b), "return".to_string()),
+                        (b, drop_from(&a), "return".to_string()),
+                    ]
+                })
+                .collect(),
+            ChaosSpec::NetworkDelay { targets, latency } => receivers_from(targets)
+                .into_iter()
+                .map(|(receiver, target)| {
+                    (
+                        receiver,
+                        delay_from(&target),
+                        format!("return({})", latency.as_millis()),
+                    )
+                })
+                .collect(),
+            ChaosSpec::PacketLoss { targets, loss_pct } => receivers_from(targets)
+                .into_iter()
+                .map(|(receiver, target)| {
+                    (receiver, drop_from(&target), format!("{}%return", loss_pct))
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peer(i: u8) -> PeerId {
+        PeerId::new([i; PeerId::LENGTH])
+    }
+
+    fn drop_from(i: u8) -> String {
+        format!("{}::{}", DROP_INBOUND_FAILPOINT, peer(i))
+    }
+
+    fn delay_from(i: u8) -> String {
+        format!("{}::{}", DELAY_INBOUND_FAILPOINT, peer(i))
+    }
+
+    #[test]
+    fn test_validate() {
+        let partition = |group_a: Vec<PeerId>, group_b: Vec<PeerId>| ChaosSpec::NetworkPartition {
+            group_a,
+            group_b,
+        };
+        partition(vec![peer(0)], vec![peer(1), peer(2)])
+            .validate()
+            .unwrap();
+        assert!(partition(vec![], vec![peer(1)]).validate().is_err());
+        assert!(partition(vec![peer(0), peer(1)], vec![peer(1)])
+            .validate()
+            .is_err());
+
+        let delay = |targets: Vec<PeerId>, millis: u64| ChaosSpec::NetworkDelay {
+            targets,
+            latency: Duration::from_millis(millis),
+        };
+        delay(vec![peer(0)], 100).validate().unwrap();
+        assert!(delay(vec![], 100).validate().is_err());
+        assert!(delay(vec![peer(0)], 0).validate().is_err());
+
+        let loss =
+            |targets: Vec<PeerId>, loss_pct: u32| ChaosSpec::PacketLoss { targets, loss_pct };
+        loss(vec![peer(0)], 1).validate().unwrap();
+        loss(vec![peer(0)], 100).validate().unwrap();
+        assert!(loss(vec![], 10).validate().is_err());
+        assert!(loss(vec![peer(0)], 0).validate().is_err());
+        assert!(loss(vec![peer(0)], 101).validate().is_err());
+    }
+
+    #[test]
+    fn test_validators() {
+        let partition = ChaosSpec::NetworkPartition {
+            group_a: vec![peer(0)],
+            group_b: vec![peer(1), peer(2)],
+        };
+        assert_eq!(partition.validators(), vec![peer(0), peer(1), peer(2)]);
+
+        let loss = ChaosSpec::PacketLoss {
+            targets: vec![peer(3)],
+            loss_pct: 10,
+        };
+        assert_eq!(loss.validators(), vec![peer(3)]);
+    }
+
+    #[test]
+    fn test_partition_failpoints() {
+        let validators = [peer(0), peer(1), peer(2)];
+        let partition = ChaosSpec::NetworkPartition {
+            group_a: vec![peer(0)],
+            group_b: vec![peer(1), peer(2)],
+        };
+        // each side drops what it receives from the other, and peer(1) still talks to peer(2)
+        assert_eq!(
+            partition.failpoints(&validators),
+            vec![
+                (peer(0), drop_from(1), "return".to_string()),
+                (peer(1), drop_from(0), "return".to_string()),
+                (peer(0), drop_from(2), "return".to_string()),
+                (peer(2), drop_from(0), "return".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_delay_and_loss_failpoints() {
+        let validators = [peer(0), peer(1), peer(2)];
+        // every other validator delays what it receives from the target
+        let delay = ChaosSpec::NetworkDelay {
+            targets: vec![peer(1)],
+            latency: Duration::from_millis(250),
+        };
+        assert_eq!(
+            delay.failpoints(&validators),
+            vec![
+                (peer(0), delay_from(1), "return(250)".to_string()),
+                (peer(2), delay_from(1), "return(250)".to_string()),
+            ]
+        );
+
+        let loss = ChaosSpec::PacketLoss {
+            targets: vec![peer(0), peer(2)],
+            loss_pct: 30,
+        };
+        assert_eq!(
+            loss.failpoints(&validators),
+            vec![
+                (peer(1), drop_from(0), "30%return".to_string()),
+                (peer(2), drop_from(0), "30%return".to_string()),
+                (peer(0), drop_from(2), "30%return".to_string()),
+                (peer(1), drop_from(2), "30%return".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,483 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{ChaosSpec, Result};
+use anyhow::anyhow;
+use aptos_sdk::types::PeerId;
+use rand::{rngs::StdRng, seq::SliceRandom};
+use std::{
+    cmp::{max, min},
+    time::Duration,
+};
+use tokio::{
+    runtime::Runtime,
+    sync::oneshot,
+    task::JoinHandle,
+    time::{self, Instant},
+};
+
+/// Chaos a [`ChaosSchedule`] applies at each of its periods, to nodes picked at random. Partitions
+/// and delays only apply to validators.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScheduledChaos {
+    /// Kills a fullnode, which the backend brings back up. The local backend doesn't restart
+    /// nodes, so there it fails, which is logged like any chaos that couldn't be applied.
+    KillFullNode,
+    /// Cuts `percent` percent of the validators off from the others for `duration`
+    Partition { percent: usize, duration: Duration },
+    /// Delays the traffic sent by `percent` percent of the validators by `latency` for `duration`
+    Delay {
+        percent: usize,
+        latency: Duration,
+        duration: Duration,
+    },
+}
+
+/// Chaos applied to the swarm for the lifetime of a suite, e.g. killing a random fullnode every 5
+/// minutes, from a background task alongside whatever tests run. The chaos is applied one at a
+/// time, so chaos falling due while another is in effect waits for it to end. What was done is
+/// logged in the report of the suite.
+///
+/// Only the backends with a [`ChaosDriver`] support it, see
+/// [`Swarm::chaos_driver`](crate::Swarm::chaos_driver).
+#[derive(Clone, Debug, Default)]
+pub struct ChaosSchedule {
+    entries: Vec<(Duration, ScheduledChaos)>,
+}
+
+impl ChaosSchedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Applies `chaos` every `period`, the first time one period after the tests start
+    pub fn every(mut self, period: Duration, chaos: ScheduledChaos) -> Self {
+        self.entries.push((period, chaos));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Applies chaos to the nodes of a swarm from a background task, while the tests hold the `Swarm`
+/// itself
+#[async_trait::async_trait]
+pub trait ChaosDriver: Send + Sync {
+    fn validators(&self) -> Vec<PeerId>;
+
+    fn full_nodes(&self) -> Vec<PeerId>;
+
+    /// Kills the fullnode, which the backend brings back up
+    async fn kill_full_node(&self, id: PeerId) -> Result<()>;
+
+    async fn inject_chaos(&self, chaos: &ChaosSpec) -> Result<()>;
+
+    async fn remove_chaos(&self, chaos: &ChaosSpec) -> Result<()>;
+}
+
+/// The background task applying a [`ChaosSchedule`] on `runtime`. Dropping it stops the schedule,
+/// waiting for the chaos still in effect to be removed, so that the chaos doesn't outlive a run
+/// cut short by an error.
+pub(crate) struct ChaosScheduleTask<'r> {
+    runtime: &'r Runtime,
+    stop: Option<oneshot::Sender<()>>,
+    task: Option<JoinHandle<Vec<String>>>,
+}
+
+impl<'r> ChaosScheduleTask<'r> {
+    pub(crate) fn spawn(
+        runtime: &'r Runtime,
+        schedule: ChaosSchedule,
+        driver: Box<dyn ChaosDriver>,
+        rng: StdRng,
+    ) -> Self {
+        let (stop, stop_rx) = oneshot::channel();
+        let task = runtime.spawn(run_chaos_schedule(schedule, driver, rng, stop_rx));
+        Self {
+            runtime,
+            stop: Some(stop),
+            task: Some(task),
+        }
+    }
+
+    /// Stops the schedule, and returns the log of what was done
+    pub(crate) fn stop(mut self) -> Result<Vec<String>> {
+        self.stop_task()
+    }
+
+    fn stop_task(&mut self) -> Result<Vec<String>> {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+        match self.task.take() {
+            Some(task) => Ok(self.runtime.block_on(task)?),
+            None => Ok(vec![]),
+        }
+    }
+}
+
+impl Drop for ChaosScheduleTask<'_> {
+    fn drop(&mut self) {
+        if let Err(e) = self.stop_task() {
+            println!("Failed to stop the chaos schedule: {:?}", e);
+        }
+    }
+}
+
+/// Applies the chaos of `schedule` until `stop` fires, removing the chaos still in effect then.
+/// Returns the log of what was done, with the time since the start of the schedule.
+async fn run_chaos_schedule(
+    schedule: ChaosSchedule,
+    driver: Box<dyn ChaosDriver>,
+    mut rng: StdRng,
+    mut stop: oneshot::Receiver<()>,
+) -> Vec<String> {
+    let start = Instant::now();
+    let mut due = schedule
+        .entries
+        .iter()
+        .map(|(period, _)| start + *period)
+        .collect::<Vec<_>>();
+    let mut log = vec![];
+    loop {
+        let (i, at) = match due.iter().copied().enumerate().min_by_key(|(_, at)| *at) {
+            Some(next) => next,
+            None => return log,
+        };
+        tokio::select! {
+            _ = &mut stop => return log,
+            _ = time::sleep_until(at) => {}
+        }
+
+        let (period, chaos) = &schedule.entries[i];
+        let elapsed = start.elapsed().as_secs();
+        let (result, stopped) = apply_chaos(&*driver, chaos, &mut rng, &mut stop).await;
+        log.push(match result {
+            Ok(done) => format!("[{}s] {}", elapsed, done),
+            Err(e) => format!("[{}s] Failed to apply {:?}: {}", elapsed, chaos, e),
+        });
+        if stopped {
+            return log;
+        }
+        due[i] = max(at + *period, Instant::now());
+    }
+}
+
+/// Applies `chaos` to random nodes, returning what was done and whether `stop` fired while the
+/// chaos was in effect
+async fn apply_chaos(
+    driver: &dyn ChaosDriver,
+    chaos: &ScheduledChaos,
+    rng: &mut StdRng,
+    stop: &mut oneshot::Receiver<()>,
+) -> (Result<String>, bool) {
+    let (spec, description, duration) = match chaos {
+        ScheduledChaos::KillFullNode => {
+            let result = match driver.full_nodes().choose(rng) {
+                Some(id) => driver
+                    .kill_full_node(*id)
+                    .await
+                    .map(|()| format!("Killed fullnode {}", id)),
+                None => Err(anyhow!("No fullnode to kill")),
+            };
+            return (result, false);
+        }
+        ScheduledChaos::Partition { percent, duration } => {
+            let (group_a, group_b) = pick_validators(driver, *percent, rng);
+            let description = format!(
+                "Partitioned {} from the other validators",
+                peer_list(&group_a)
+            );
+            (
+                ChaosSpec::NetworkPartition { group_a, group_b },
+                description,
+                *duration,
+            )
+        }
+        ScheduledChaos::Delay {
+            percent,
+            latency,
+            duration,
+        } => {
+            let (targets, _) = pick_validators(driver, *percent, rng);
+            let description = format!(
+                "Delayed the traffic of {} by {}ms",
+                peer_list(&targets),
+                latency.as_millis()
+            );
+            (
+                ChaosSpec::NetworkDelay {
+                    targets,
+                    latency: *latency,
+                },
+                description,
+                *duration,
+            )
+        }
+    };
+
+    if let Err(e) = driver.inject_chaos(&spec).await {
+        return (Err(e), false);
+    }
+    let stopped = tokio::select! {
+        _ = stop => true,
+        _ = time::sleep(duration) => false,
+    };
+    let result = driver
+        .remove_chaos(&spec)
+        .await
+        .map(|()| format!("{} for {}s", description, duration.as_secs()));
+    (result, stopped)
+}
+
+/// Splits the validators at random into `percent` percent of them, at least one, and the others
+fn pick_validators(
+    driver: &dyn ChaosDriver,
+    percent: usize,
+    rng: &mut StdRng,
+) -> (Vec<PeerId>, Vec<PeerId>) {
+    let mut validators = driver.validators();
+    validators.shuffle(rng);
+    let count = min(max(validators.len() * percent / 100, 1), validators.len());
+    let others = validators.split_off(count);
+    (validators, others)
+}
+
+fn peer_list(peer_ids: &[PeerId]) -> String {
+    peer_ids
+        .iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use std::sync::{Arc, Mutex};
+
+    /// Records the chaos applied to it, with the time since `start`
+    #[derive(Clone)]
+    struct FakeChaosDriver {
+        validators: Vec<PeerId>,
+        full_nodes: Vec<PeerId>,
+        start: Instant,
+        events: Arc<Mutex<Vec<(Duration, String)>>>,
+    }
+
+    impl FakeChaosDriver {
+        fn new(num_validators: usize, num_full_nodes: usize) -> Self {
+            Self {
+                validators: (0..num_validators).map(|_| PeerId::random()).collect(),
+                full_nodes: (0..num_full_nodes).map(|_| PeerId::random()).collect(),
+                start: Instant::now(),
+                events: Arc::new(Mutex::new(vec![])),
+            }
+        }
+
+        fn record(&self, event: String) {
+            self.events
+                .lock()
+                .unwrap()
+                .push((self.start.elapsed(), event));
+        }
+
+        fn events(&self) -> Vec<(Duration, String)> {
+            self.events.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ChaosDriver for FakeChaosDriver {
+        fn validators(&self) -> Vec<PeerId> {
+            self.validators.clone()
+        }
+
+        fn full_nodes(&self) -> Vec<PeerId> {
+            self.full_nodes.clone()
+        }
+
+        async fn kill_full_node(&self, id: PeerId) -> Result<()> {
+            self.record(format!("kill {}", id));
+            Ok(())
+        }
+
+        async fn inject_chaos(&self, chaos: &ChaosSpec) -> Result<()> {
+            chaos.validate()?;
+            self.record(format!("inject {:?}", chaos));
+            Ok(())
+        }
+
+        async fn remove_chaos(&self, chaos: &ChaosSpec) -> Result<()> {
+            self.record(format!("remove {:?}", chaos));
+            Ok(())
+        }
+    }
+
+    fn rng() -> StdRng {
+        StdRng::seed_from_u64(0)
+    }
+
+    const PARTITION: ScheduledChaos = ScheduledChaos::Partition {
+        percent: 30,
+        duration: Duration::from_secs(60),
+    };
+
+    #[test]
+    fn test_pick_validators() {
+        let driver = FakeChaosDriver::new(10, 0);
+        for (percent, count) in [(30, 3), (0, 1), (5, 1), (100, 10), (150, 10)] {
+            let (picked, others) = pick_validators(&driver, percent, &mut rng());
+            assert_eq!(picked.len(), count, "{} percent", percent);
+            assert_eq!(picked.len() + others.len(), 10);
+            let mut all = picked.iter().chain(others.iter()).collect::<Vec<_>>();
+            all.sort();
+            all.dedup();
+            assert_eq!(all.len(), 10);
+        }
+        let (picked, others) = pick_validators(&FakeChaosDriver::new(0, 0), 30, &mut rng());
+        assert!(picked.is_empty() && others.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_apply_partition() {
+        let driver = FakeChaosDriver::new(10, 0);
+        let (_stop_tx, mut stop) = oneshot::channel();
+        let (result, stopped) = apply_chaos(&driver, &PARTITION, &mut rng(), &mut stop).await;
+        assert!(!stopped);
+        assert!(result.unwrap().ends_with("for 60s"));
+
+        // the chaos is removed once its duration is over
+        let events = driver.events();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].0, Duration::ZERO);
+        assert!(events[0].1.starts_with("inject NetworkPartition"));
+        assert_eq!(events[1].0, Duration::from_secs(60));
+        assert_eq!(events[1].1, events[0].1.replacen("inject", "remove", 1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_apply_chaos_stopped() {
+        let driver = FakeChaosDriver::new(4, 0);
+        let (stop_tx, mut stop) = oneshot::channel();
+        stop_tx.send(()).unwrap();
+        let delay = ScheduledChaos::Delay {
+            percent: 50,
+            latency: Duration::from_millis(200),
+            duration: Duration::from_secs(60),
+        };
+        let (result, stopped) = apply_chaos(&driver, &delay, &mut rng(), &mut stop).await;
+        assert!(stopped);
+        assert!(result.is_ok());
+        // the chaos is removed right away
+        let events = driver.events();
+        assert_eq!(events.len(), 2);
+        assert!(events[0].1.starts_with("inject NetworkDelay"));
+        assert!(events[1].1.starts_with("remove NetworkDelay"));
+        assert_eq!(events[1].0, Duration::ZERO);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_apply_kill_full_node() {
+        let (_stop_tx, mut stop) = oneshot::channel();
+        let driver = FakeChaosDriver::new(4, 0);
+        let (result, _) = apply_chaos(
+            &driver,
+            &ScheduledChaos::KillFullNode,
+            &mut rng(),
+            &mut stop,
+        )
+        .await;
+        assert!(result.is_err());
+
+        let driver = FakeChaosDriver::new(4, 2);
+        let (result, _) = apply_chaos(
+            &driver,
+            &ScheduledChaos::KillFullNode,
+            &mut rng(),
+            &mut stop,
+        )
+        .await;
+        assert!(result.unwrap().starts_with("Killed fullnode"));
+        assert_eq!(driver.events().len(), 1);
+
+        // a partition of a single validator has no other side
+        let driver = FakeChaosDriver::new(1, 0);
+        let (result, _) = apply_chaos(&driver, &PARTITION, &mut rng(), &mut stop).await;
+        assert!(result.is_err());
+        assert!(driver.events().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_run_chaos_schedule() {
+        let driver = FakeChaosDriver::new(10, 2);
+        let schedule = ChaosSchedule::new()
+            .every(Duration::from_secs(100), PARTITION)
+            .every(Duration::from_secs(150), ScheduledChaos::KillFullNode);
+        let (stop_tx, stop) = oneshot::channel();
+        let task = tokio::spawn(run_chaos_schedule(
+            schedule,
+            Box::new(driver.clone()),
+            rng(),
+            stop,
+        ));
+        time::sleep(Duration::from_secs(330)).await;
+        stop_tx.send(()).unwrap();
+        let log = task.await.unwrap();
+
+        let events = driver
+            .events()
+            .into_iter()
+            .map(|(at, event)| (at.as_secs(), event.split(' ').next().unwrap().to_string()))
+            .collect::<Vec<_>>();
+        let expected = [
+            (100, "inject"),
+            (160, "remove"),
+            // the kill due at 150s waits for the partition to end
+            (160, "kill"),
+            (200, "inject"),
+            (260, "remove"),
+            (300, "inject"),
+            // the partition in effect when the schedule stops is removed
+            (330, "remove"),
+        ]
+        .iter()
+        .map(|(at, event)| (*at, event.to_string()))
+        .collect::<Vec<_>>();
+        assert_eq!(events, expected);
+        assert_eq!(log.len(), 4);
+        assert!(log[0].starts_with("[100s] Partitioned"));
+        assert!(log[1].starts_with("[160s] Killed fullnode"));
+    }
+
+    #[test]
+    fn test_chaos_task_stopped_on_drop() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .start_paused(true)
+            .build()
+            .unwrap();
+        let driver = runtime.block_on(async { FakeChaosDriver::new(10, 0) });
+        let schedule = ChaosSchedule::new().every(
+            Duration::from_secs(10),
+            ScheduledChaos::Partition {
+                percent: 30,
+                duration: Duration::from_secs(3600),
+            },
+        );
+        {
+            let _task =
+                ChaosScheduleTask::spawn(&runtime, schedule, Box::new(driver.clone()), rng());
+            runtime.block_on(time::sleep(Duration::from_secs(20)));
+            assert_eq!(driver.events().len(), 1);
+            // as when the run fails before stopping the schedule
+        }
+        let events = driver.events();
+        assert_eq!(events.len(), 2);
+        assert!(events[1].1.starts_with("remove NetworkPartition"));
+        assert!(events[1].0 < Duration::from_secs(3600));
+    }
+}
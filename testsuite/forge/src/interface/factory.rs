@@ -1,7 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use super::{GenesisConfig, Swarm, Version};
+use super::{GenesisConfig, InitialState, NodeEnvironment, Swarm, TopologySpec, Version};
 use crate::Result;
 use rand::rngs::StdRng;
 use std::num::NonZeroUsize;
@@ -11,12 +11,27 @@ use std::num::NonZeroUsize;
 pub trait Factory {
     fn versions<'a>(&'a self) -> Box<dyn Iterator<Item = Version> + 'a>;
 
+    /// Launches a swarm of `node_num` validators, along with `fullnode_num` fullnodes spread over
+    /// them, or the number of fullnodes the backend launches by default if it's not set. Room is
+    /// made for `standby_validator_num` more validators, which the tests can add to the swarm and
+    /// have join the validator set, see `Swarm::add_validator`.
     async fn launch_swarm(
         &self,
         rng: &mut StdRng,
         node_num: NonZeroUsize,
+        fullnode_num: Option<usize>,
+        standby_validator_num: usize,
         version: &Version,
         genesis_version: &Version,
         genesis_modules: Option<&GenesisConfig>,
+        initial_state: &InitialState,
+        node_environment: &NodeEnvironment,
+        topology: &TopologySpec,
     ) -> Result<Box<dyn Swarm>>;
+
+    /// Releases the resources backing the swarms launched by this factory. `success` is whether
+    /// all tests passed, so factories can keep resources around for debugging failures.
+    fn teardown(&self, _success: bool) -> Result<()> {
+        Ok(())
+    }
 }
@@ -17,6 +17,21 @@ mod node;
 pub use node::*;
 mod chain_info;
 pub use chain_info::*;
+mod chaos;
+pub use chaos::*;
+mod chaos_schedule;
+pub use chaos_schedule::*;
+mod account_pool;
+pub use account_pool::*;
+mod topology;
+pub use topology::*;
+
+use aptos_config::config::NodeConfig;
+/// On-chain configuration the chain starts with, see `LocalSwarmBuilder::genesis_configuration`
+pub use aptos_genesis_tool::validator_builder::GenesisConfiguration;
+/// Accounts and packages created at genesis, see `ForgeConfig::with_initial_accounts`
+pub use aptos_genesis_tool::validator_builder::InitialState;
+use std::{collections::BTreeMap, fmt, sync::Arc};
 
 /// A wrapper around a usize in order to represent an opaque version of a Node.
 ///
@@ -46,3 +61,71 @@ pub enum GenesisConfig {
     Bytes(Vec<Vec<u8>>),
     Path(String),
 }
+
+/// Node features which can be toggled for the nodes of a swarm without code changes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeFeature {
+    /// Runs the parallel executor with the given concurrency level
+    ParallelExecution(u16),
+    /// Syncs with state sync v2
+    StateSyncV2,
+}
+
+/// Changes to the config of every node of a swarm, see `ForgeConfig::with_node_config_fn`
+#[derive(Clone)]
+pub struct NodeConfigFn(Arc<dyn Fn(&mut NodeConfig) + Send + Sync>);
+
+impl NodeConfigFn {
+    pub fn new<F>(node_config_fn: F) -> Self
+    where
+        F: Fn(&mut NodeConfig) + Send + Sync + 'static,
+    {
+        Self(Arc::new(node_config_fn))
+    }
+
+    pub fn apply(&self, config: &mut NodeConfig) {
+        (self.0)(config)
+    }
+}
+
+impl fmt::Debug for NodeConfigFn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("NodeConfigFn")
+    }
+}
+
+/// Environment variables, features and config changes applied to every node of a swarm when it
+/// is launched.
+///
+/// The local backend sets the variables on each node process and the k8s backend passes them
+/// on to the helm charts.
+#[derive(Clone, Debug, Default)]
+pub struct NodeEnvironment {
+    pub env_vars: BTreeMap<String, String>,
+    pub features: Vec<NodeFeature>,
+    pub node_config_fn: Option<NodeConfigFn>,
+    /// Values merged over those of the validator chart, which only the k8s backend has
+    pub helm_values: Option<serde_yaml::Value>,
+    /// Values merged over `helm_values` for single validators, by their index
+    pub validator_helm_values: BTreeMap<usize, serde_yaml::Value>,
+    /// Limits on the resources of single validators, by their index, which only the local backend
+    /// supports. The k8s backend sets resources through `validator_helm_values` instead.
+    pub validator_resource_limits: BTreeMap<usize, ResourceLimits>,
+}
+
+/// Limits on the CPU and memory of a node process, e.g. to test how an under-provisioned
+/// validator copes with its load. Unset limits leave the resource unlimited.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ResourceLimits {
+    /// CPU time the node may use, in thousandths of a CPU, e.g. 500 for half a CPU
+    pub cpu_millis: Option<u64>,
+    /// Memory the node may use, in bytes. The kernel reclaims memory from the node beyond it and
+    /// kills the node when it can't.
+    pub memory_bytes: Option<u64>,
+}
+
+impl ResourceLimits {
+    pub fn is_unlimited(&self) -> bool {
+        self.cpu_millis.is_none() && self.memory_bytes.is_none()
+    }
+}
@@ -3,6 +3,10 @@
 
 use super::Test;
 use crate::{CoreContext, Result, Swarm, TestReport};
+use anyhow::format_err;
+use aptos_sdk::{crypto::ed25519::Ed25519PublicKey, types::PeerId};
+use std::future::Future;
+use tokio::{runtime::Runtime, task::JoinHandle};
 use transaction_emitter::EmitJobRequest;
 
 /// The testing interface which defines a test written with full control over an existing network.
@@ -18,6 +22,8 @@ pub struct NetworkContext<'t> {
     swarm: &'t mut dyn Swarm,
     pub report: &'t mut TestReport,
     pub global_job: EmitJobRequest,
+    emit_to_fullnodes: bool,
+    background: Option<Runtime>,
 }
 
 impl<'t> NetworkContext<'t> {
@@ -32,9 +38,24 @@ impl<'t> NetworkContext<'t> {
             swarm,
             report,
             global_job,
+            emit_to_fullnodes: false,
+            background: None,
         }
     }
 
+    /// Has the traffic of the test go through the fullnodes of the validators, see
+    /// [`NetworkContext::emit_to_fullnodes`]
+    pub fn with_emit_to_fullnodes(mut self, emit_to_fullnodes: bool) -> Self {
+        self.emit_to_fullnodes = emit_to_fullnodes;
+        self
+    }
+
+    /// Whether the traffic of the test is submitted to the fullnodes of the validators, which
+    /// forward it to their validator from their mempool, rather than to the validators
+    pub fn emit_to_fullnodes(&self) -> bool {
+        self.emit_to_fullnodes
+    }
+
     pub fn swarm(&mut self) -> &mut dyn Swarm {
         self.swarm
     }
@@ -42,4 +63,89 @@ impl<'t> NetworkContext<'t> {
     pub fn core(&mut self) -> &mut CoreContext {
         &mut self.core
     }
+
+    /// Runs `future` in the background while the test goes on, e.g. traffic while the test
+    /// restarts nodes. The task runs on a runtime of the context, so it can be awaited from any
+    /// runtime of the test, and whatever is still running when the context is dropped is
+    /// cancelled.
+    pub fn spawn_background<F>(&mut self, future: F) -> Result<JoinHandle<F::Output>>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        if self.background.is_none() {
+            self.background = Some(Runtime::new()?);
+        }
+        Ok(self.background.as_ref().unwrap().spawn(future))
+    }
+
+    /// Rotates the consensus key of the validator in its secure storage and on chain, where the
+    /// new key takes effect at the next epoch, see [`NetworkContext::reconfigure`]
+    pub async fn rotate_consensus_key(&mut self, validator: PeerId) -> Result<Ed25519PublicKey> {
+        let (mut owner, consensus_key) = self
+            .swarm
+            .validator_mut(validator)
+            .ok_or_else(|| format_err!("No validator {}", validator))?
+            .rotate_consensus_key()?;
+        self.swarm
+            .chain_info()
+            .into_aptos_public_info()
+            .rotate_consensus_key(&mut owner, &consensus_key)
+            .await?;
+        Ok(consensus_key)
+    }
+
+    /// Registers a validator added to the swarm after the genesis, see [`Swarm::add_validator`],
+    /// as a candidate on chain with `stake` delegated to it by its owner. It can then join the
+    /// validator set, see [`NetworkContext::join_validator_set`].
+    pub async fn register_validator(&mut self, validator: PeerId, stake: u64) -> Result<()> {
+        let validator = self
+            .swarm
+            .validator(validator)
+            .ok_or_else(|| format_err!("No validator {}", validator))?;
+        let mut owner = validator.owner_account()?;
+        let candidate = validator.candidate()?;
+        self.swarm
+            .chain_info()
+            .into_aptos_public_info()
+            .register_validator_candidate(&mut owner, &candidate, stake)
+            .await
+    }
+
+    /// Has the validator leave the validator set, which takes effect at the next epoch
+    pub async fn leave_validator_set(&mut self, validator: PeerId) -> Result<()> {
+        let mut owner = self
+            .swarm
+            .validator(validator)
+            .ok_or_else(|| format_err!("No validator {}", validator))?
+            .owner_account()?;
+        self.swarm
+            .chain_info()
+            .into_aptos_public_info()
+            .leave_validator_set(&mut owner)
+            .await
+    }
+
+    /// Has the validator join the validator set, which takes effect at the next epoch
+    pub async fn join_validator_set(&mut self, validator: PeerId) -> Result<()> {
+        let mut owner = self
+            .swarm
+            .validator(validator)
+            .ok_or_else(|| format_err!("No validator {}", validator))?
+            .owner_account()?;
+        self.swarm
+            .chain_info()
+            .into_aptos_public_info()
+            .join_validator_set(&mut owner)
+            .await
+    }
+
+    /// Forces the start of a new epoch
+    pub async fn reconfigure(&mut self) -> Result<()> {
+        self.swarm
+            .chain_info()
+            .into_aptos_public_info()
+            .reconfigure()
+            .await
+    }
 }
@@ -1,11 +1,14 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{Result, Version};
-use anyhow::anyhow;
+use crate::{api_recording, Result, ValidatorCandidate, Version};
+use anyhow::{anyhow, bail};
 use aptos_config::{config::NodeConfig, network_id::NetworkId};
 use aptos_rest_client::Client as RestClient;
-use aptos_sdk::types::PeerId;
+use aptos_sdk::{
+    crypto::ed25519::Ed25519PublicKey,
+    types::{LocalAccount, PeerId},
+};
 use debug_interface::AsyncNodeDebugClient;
 use std::{
     collections::HashMap,
@@ -66,11 +69,76 @@ pub trait Node: Send + Sync {
     fn counter(&self, counter: &str, port: u64) -> Result<f64>;
 
     fn expose_metric(&self) -> Result<u64>;
+
+    /// Returns the disk usage of the data directory of this Node and of the AptosDB within it
+    fn storage_metrics(&self) -> Result<StorageMetrics>;
+
+    /// Degrades the disk of the storage of this Node until `restore_io` is called, limiting it to
+    /// `iops` operations per second, or not limiting them when 0, and delaying each operation by
+    /// `latency`. Throttling the IO again replaces the previous throttling.
+    ///
+    /// Neither backend can limit the IOPS of a disk, so both throttle it with the latency of its
+    /// operations, see `io_delay`. The local backend moves the DB of the node onto a
+    /// device-mapper delay device, which needs root and restarts the node, while the k8s backend
+    /// injects a chaos-mesh IOChaos, which has to be installed on the cluster.
+    fn throttle_io(&mut self, iops: u64, latency: Duration) -> Result<()>;
+
+    /// Removes the throttling of `throttle_io`, which is a noop if the IO isn't throttled
+    fn restore_io(&mut self) -> Result<()>;
+}
+
+/// The latency to delay each IO operation by to limit a disk serving one operation at a time to
+/// `iops` operations per second, while delaying each by at least `latency`
+pub fn io_delay(iops: u64, latency: Duration) -> Duration {
+    if iops == 0 {
+        latency
+    } else {
+        latency.max(Duration::from_secs_f64(1.0 / iops as f64))
+    }
+}
+
+/// Disk usage of the storage of a node, in bytes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StorageMetrics {
+    /// Size of the AptosDB of the node, its ledger and state
+    pub db_bytes: u64,
+    /// Size of the whole data directory of the node, which also holds the consensus DB and, for
+    /// local nodes, the config and the logs
+    pub data_dir_bytes: u64,
 }
 
 /// Trait used to represent a running Validator
 #[async_trait::async_trait]
 pub trait Validator: Node + Sync {
+    /// Generates a new consensus key in the secure storage of this Validator, which keeps signing
+    /// with the previous key until the new one is registered on chain and the next epoch starts.
+    /// Returns the owner account of this Validator, with a sequence number of 0, which registers
+    /// the new key, and the new key.
+    fn rotate_consensus_key(&mut self) -> Result<(LocalAccount, Ed25519PublicKey)> {
+        bail!(
+            "Rotating the consensus key of {} isn't supported by its backend",
+            self.name()
+        )
+    }
+
+    /// Returns the owner account of this Validator, with a sequence number of 0, which manages its
+    /// stake and its membership of the validator set
+    fn owner_account(&self) -> Result<LocalAccount> {
+        bail!(
+            "Getting the owner account of {} isn't supported by its backend",
+            self.name()
+        )
+    }
+
+    /// Returns the consensus key and the network addresses this Validator registers on chain to
+    /// become a candidate of the validator set
+    fn candidate(&self) -> Result<ValidatorCandidate> {
+        bail!(
+            "Getting the validator config of {} isn't supported by its backend",
+            self.name()
+        )
+    }
+
     async fn check_connectivity(&self, expected_peers: usize) -> Result<bool> {
         if expected_peers == 0 {
             return Ok(true);
@@ -126,7 +194,7 @@ impl<T: ?Sized> NodeExt for T where T: Node {}
 pub trait NodeExt: Node {
     /// Return REST API client of this Node
     fn rest_client(&self) -> RestClient {
-        RestClient::new(self.rest_api_endpoint())
+        RestClient::new(api_recording::endpoint(self.rest_api_endpoint()))
     }
 
     /// Return a NodeDebugClient for this Node
@@ -188,6 +256,36 @@ pub trait NodeExt: Node {
         self.get_metric_with_fields("aptos_connections", map).await
     }
 
+    /// Configures a failpoint on this Node at runtime, which needs its API config to set a
+    /// `failpoints_admin_token`
+    async fn set_failpoint(&self, name: &str, actions: &str) -> Result<()> {
+        let admin_token = self.failpoints_admin_token()?;
+        self.rest_client()
+            .set_failpoint(name, actions, &admin_token)
+            .await
+    }
+
+    /// Removes a failpoint configured with `set_failpoint`
+    async fn remove_failpoint(&self, name: &str) -> Result<()> {
+        let admin_token = self.failpoints_admin_token()?;
+        self.rest_client()
+            .remove_failpoint(name, &admin_token)
+            .await
+    }
+
+    fn failpoints_admin_token(&self) -> Result<String> {
+        self.config()
+            .api
+            .failpoints_admin_token
+            .clone()
+            .ok_or_else(|| {
+                anyhow!(
+                    "Node {} doesn't enable the failpoints admin API",
+                    self.name()
+                )
+            })
+    }
+
     async fn liveness_check(&self, seconds: u64) -> Result<()> {
         self.rest_client().health_check(seconds).await
     }
@@ -216,3 +314,24 @@ pub trait NodeExt: Node {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_io_delay() {
+        let latency = Duration::from_millis(2);
+        // unlimited IOPS
+        assert_eq!(io_delay(0, latency), latency);
+        // the IOPS limit takes longer per operation than the latency
+        assert_eq!(io_delay(100, latency), Duration::from_millis(10));
+        assert_eq!(io_delay(100, Duration::ZERO), Duration::from_millis(10));
+        // the latency alone keeps the operations under the IOPS limit
+        assert_eq!(
+            io_delay(1000, Duration::from_millis(5)),
+            Duration::from_millis(5)
+        );
+        assert_eq!(io_delay(1000, latency), latency);
+    }
+}
@@ -1,18 +1,35 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{ChainInfo, FullNode, NodeExt, Result, Validator, Version};
+use crate::{
+    AccountPool, ChainInfo, ChaosDriver, ChaosSpec, FullNode, NodeExt, NodePoolUsage, Result,
+    TestReport, Validator, Version,
+};
 use anyhow::{anyhow, bail};
 use aptos_config::config::NodeConfig;
-use aptos_rest_client::Client as RestClient;
-use aptos_sdk::types::PeerId;
-use futures::future::try_join_all;
-use std::time::{Duration, Instant};
+use aptos_rest_client::{aptos_api_types::HashValue, Client as RestClient};
+use aptos_sdk::{
+    crypto::ed25519::Ed25519PrivateKey,
+    types::{LocalAccount, PeerId},
+};
+use futures::future::{join_all, try_join_all};
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
 use tokio::runtime::Runtime;
 
+/// How long a node gets to report its ledger version while waiting for nodes to catch up, before
+/// it is asked again
+const CATCHUP_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Gives up on a Swarm, returning where its failure artifacts can be found, see
+/// [`Swarm::abandoner`]
+pub type SwarmAbandoner = Box<dyn FnOnce(&TestReport) -> Result<String> + Send>;
+
 /// Trait used to represent a running network comprised of Validators and FullNodes
 #[async_trait::async_trait]
-pub trait Swarm: Sync {
+pub trait Swarm: Send + Sync {
     /// Performs a health check on the entire swarm, ensuring all Nodes are Live and that no forks
     /// have occurred
     async fn health_check(&mut self) -> Result<()>;
@@ -56,13 +73,100 @@ pub trait Swarm: Sync {
     /// Removes the FullNode with the provided PeerId
     fn remove_full_node(&mut self, id: PeerId) -> Result<()>;
 
+    /// Launches a twin of the Validator with the provided PeerId: another instance with the same
+    /// identity and keys, which votes independently of the Validator to simulate equivocation
+    async fn add_twin_validator(&mut self, id: PeerId) -> Result<()> {
+        bail!(
+            "Swarm doesn't support twin validators, can't add one for {}",
+            id
+        )
+    }
+
+    /// Removes the twin launched by `add_twin_validator` for the Validator with the provided PeerId
+    fn remove_twin_validator(&mut self, id: PeerId) -> Result<()> {
+        bail!(
+            "Swarm doesn't support twin validators, can't remove the one of {}",
+            id
+        )
+    }
+
+    /// Restarts the listed Validators, all at once, with their system clocks off by the given
+    /// number of seconds: ahead if positive, behind if negative. A skew of 0 puts a clock back
+    /// right.
+    fn skew_validator_clocks(&mut self, skews: &[(PeerId, i64)]) -> Result<()> {
+        bail!(
+            "Swarm doesn't support clock skew, can't skew the clocks of {} validators",
+            skews.len()
+        )
+    }
+
+    /// Restarts all the nodes with the environment variables of the swarm overridden by
+    /// `env_vars`, and waits for them to be back up. The nodes added to the swarm afterwards get
+    /// them as well. Empty `env_vars` restart the nodes with the variables of the swarm alone.
+    async fn set_test_env_vars(&mut self, env_vars: &BTreeMap<String, String>) -> Result<()> {
+        bail!(
+            "Swarm doesn't support per-test environment variables, can't set {:?}",
+            env_vars.keys().collect::<Vec<_>>()
+        )
+    }
+
     /// Return a list of supported Versions
     fn versions<'a>(&'a self) -> Box<dyn Iterator<Item = Version> + 'a>;
 
+    /// Size in bytes of the node binary of `version`, for backends which build it themselves
+    fn node_binary_size(&self, _version: &Version) -> Result<Option<u64>> {
+        Ok(None)
+    }
+
+    /// Injects network chaos between the Validators of the Swarm, until it's removed with
+    /// `remove_chaos`
+    async fn inject_chaos(&mut self, chaos: ChaosSpec) -> Result<()>;
+
+    /// Removes network chaos injected with `inject_chaos`
+    async fn remove_chaos(&mut self, chaos: ChaosSpec) -> Result<()>;
+
+    /// Returns a driver applying chaos to the nodes of this Swarm from a background task, for a
+    /// `ChaosSchedule`
+    fn chaos_driver(&self) -> Result<Box<dyn ChaosDriver>> {
+        bail!("Chaos schedules are not supported by this backend")
+    }
+
     /// Construct a ChainInfo from this Swarm
     fn chain_info(&mut self) -> ChainInfo<'_>;
 
     fn logs_location(&mut self) -> String;
+
+    /// Returns the keys of the accounts saved with `save_emitter_accounts` by earlier runs
+    /// against the chain of this Swarm, for emitters to reuse rather than mint new ones
+    async fn emitter_account_keys(&self) -> Result<Vec<Ed25519PrivateKey>>;
+
+    /// Saves the accounts of an emitter for later runs against the chain of this Swarm, if the
+    /// chain outlives the Swarm
+    async fn save_emitter_accounts(&mut self, accounts: &[LocalAccount]) -> Result<()>;
+
+    /// The emitter accounts minted on the chain of this Swarm so far, which the traffic of
+    /// successive tests shares
+    fn account_pool(&mut self) -> &mut AccountPool;
+
+    /// Saves whatever is needed to debug a failed run somewhere that outlives the swarm, returning
+    /// where it can be found
+    fn collect_failure_artifacts(&mut self, _report: &TestReport) -> Result<String> {
+        Ok(self.logs_location())
+    }
+
+    /// Returns what it takes to give up on this Swarm once a test holding on to it runs out of
+    /// time, without the Swarm: collecting its failure artifacts, as `collect_failure_artifacts`
+    /// does, and stopping its nodes where the backend can
+    fn abandoner(&mut self) -> SwarmAbandoner {
+        let location = self.logs_location();
+        Box::new(move |_| Ok(location))
+    }
+
+    /// The machines this Swarm runs on, to estimate the cost of a run. Empty for the backends
+    /// whose machines aren't billed for the run.
+    async fn node_pools(&self) -> Result<Vec<NodePoolUsage>> {
+        Ok(vec![])
+    }
 }
 
 impl<T: ?Sized> SwarmExt for T where T: Swarm {}
@@ -124,42 +228,14 @@ pub trait SwarmExt: Swarm {
 
     /// Perform a safety check, ensuring that no forks have occurred in the network.
     fn fork_check(&self) -> Result<()> {
-        // Checks if root_hashes are equal across all nodes at a given version
-        async fn are_root_hashes_equal_at_version(
-            clients: &[RestClient],
-            version: u64,
-        ) -> Result<bool> {
-            let root_hashes = try_join_all(
-                clients
-                    .iter()
-                    .map(|node| node.get_transaction_by_version(version))
-                    .collect::<Vec<_>>(),
-            )
-            .await?
-            .into_iter()
-            .map(|r| {
-                r.into_inner()
-                    .transaction_info()
-                    .unwrap()
-                    .accumulator_root_hash
-            })
-            .collect::<Vec<_>>();
-
-            Ok(root_hashes.windows(2).all(|w| w[0] == w[1]))
-        }
-
         let runtime = Runtime::new().unwrap();
 
-        let clients = self
-            .validators()
-            .map(|node| node.rest_client())
-            .chain(self.full_nodes().map(|node| node.rest_client()))
-            .collect::<Vec<_>>();
+        let nodes = self.named_rest_clients();
+        let clients = nodes.iter().map(|(_, client)| client);
 
         let versions = runtime
             .block_on(try_join_all(
                 clients
-                    .iter()
                     .map(|node| node.get_ledger_information())
                     .collect::<Vec<_>>(),
             ))?
@@ -177,53 +253,109 @@ pub trait SwarmExt: Swarm {
             .copied()
             .ok_or_else(|| anyhow!("Unable to query nodes for their latest version"))?;
 
-        if !runtime.block_on(are_root_hashes_equal_at_version(&clients, min_version))? {
-            return Err(anyhow!("Fork check failed"));
-        }
+        runtime.block_on(check_root_hashes_at_version(&nodes, min_version))?;
 
         runtime.block_on(self.wait_for_all_nodes_to_catchup_to_version(
             max_version,
             Instant::now() + Duration::from_secs(10),
         ))?;
 
-        if !runtime.block_on(are_root_hashes_equal_at_version(&clients, max_version))? {
-            return Err(anyhow!("Fork check failed"));
+        runtime.block_on(check_root_hashes_at_version(&nodes, max_version))
+    }
+
+    /// Compares the ledgers of the nodes at `num_samples` versions spread over the history all of
+    /// them have, failing with the first diverging version and the nodes on each side of the
+    /// fork. Nodes which don't respond, e.g. because a test stopped them, are left out.
+    async fn sampled_fork_check(&self, num_samples: u64) -> Result<()> {
+        let mut nodes = vec![];
+        let mut min_version = u64::MAX;
+        for (name, client) in self.named_rest_clients() {
+            if let Ok(resp) = client.get_ledger_information().await {
+                min_version = min_version.min(resp.into_inner().version);
+                nodes.push((name, client));
+            }
+        }
+        if nodes.len() < 2 {
+            return Ok(());
         }
 
+        // The root hash at a version covers all the transactions up to it, so the samples only
+        // narrow down where the nodes diverged
+        let mut versions = (1..=num_samples)
+            .map(|i| min_version * i / num_samples)
+            .collect::<Vec<_>>();
+        versions.dedup();
+        for version in versions {
+            check_root_hashes_at_version(&nodes, version).await?;
+        }
         Ok(())
     }
 
-    /// Waits for all nodes to have caught up to the specified `verison`.
+    /// The most validators which can fail without the swarm losing liveness, `f` in `n = 3f + 1`,
+    /// assuming the validators have the same voting power
+    fn max_faulty_validators(&self) -> usize {
+        self.validators().count().saturating_sub(1) / 3
+    }
+
+    /// Names and REST clients of all the nodes
+    fn named_rest_clients(&self) -> Vec<(String, RestClient)> {
+        self.validators()
+            .map(|node| (node.name().to_string(), node.rest_client()))
+            .chain(
+                self.full_nodes()
+                    .map(|node| (node.name().to_string(), node.rest_client())),
+            )
+            .collect()
+    }
+
+    /// Waits for all nodes to have caught up to the specified `version`.
+    ///
+    /// Each node is polled on its own with a timeout per request, so nodes which are slow to
+    /// answer, e.g. pods being restarted, are retried until the deadline instead of failing the
+    /// wait. On timeout the error lists the nodes which are still behind.
     async fn wait_for_all_nodes_to_catchup_to_version(
         &self,
         version: u64,
         deadline: Instant,
     ) -> Result<()> {
-        let clients = self
-            .validators()
-            .map(|node| node.rest_client())
-            .chain(self.full_nodes().map(|node| node.rest_client()))
-            .collect::<Vec<_>>();
+        let nodes = self.named_rest_clients();
+        let mut last_versions: Vec<Option<u64>> = vec![None; nodes.len()];
 
         loop {
-            let results =
-                try_join_all(clients.iter().map(|node| node.get_ledger_information())).await;
-            let all_catchup = results
-                .map(|resps| {
-                    resps
-                        .into_iter()
-                        .map(|r| r.into_inner().version)
-                        .all(|v| v >= version)
-                })
-                .unwrap_or(false);
-            if all_catchup {
+            let lagging = nodes
+                .iter()
+                .zip(last_versions.iter_mut())
+                .filter(|(_, last_version)| last_version.map_or(true, |v| v < version));
+            join_all(lagging.map(|((_, client), last_version)| async move {
+                if let Ok(Ok(resp)) =
+                    tokio::time::timeout(CATCHUP_REQUEST_TIMEOUT, client.get_ledger_information())
+                        .await
+                {
+                    *last_version = Some(resp.into_inner().version);
+                }
+            }))
+            .await;
+            if last_versions
+                .iter()
+                .all(|last_version| last_version.map_or(false, |v| v >= version))
+            {
                 break;
             }
 
             if Instant::now() > deadline {
+                let lagging = nodes
+                    .iter()
+                    .zip(last_versions.iter())
+                    .filter(|(_, last_version)| last_version.map_or(true, |v| v < version))
+                    .map(|((name, _), last_version)| match last_version {
+                        Some(v) => format!("{} at version {}", name, v),
+                        None => format!("{} unreachable", name),
+                    })
+                    .collect::<Vec<_>>();
                 return Err(anyhow!(
-                    "waiting for nodes to catch up to version {} timed out",
-                    version
+                    "waiting for nodes to catch up to version {} timed out, lagging: {}",
+                    version,
+                    lagging.join(", ")
                 ));
             }
 
@@ -238,26 +370,67 @@ pub trait SwarmExt: Swarm {
     /// that version. Once done, we can guarantee that all transactions committed before invocation
     /// of this function are available at all the nodes in the swarm
     async fn wait_for_all_nodes_to_catchup(&self, deadline: Instant) -> Result<()> {
-        let clients = self
-            .validators()
-            .map(|node| node.rest_client())
-            .chain(self.full_nodes().map(|node| node.rest_client()))
-            .collect::<Vec<_>>();
+        let nodes = self.named_rest_clients();
 
-        if clients.is_empty() {
+        if nodes.is_empty() {
             bail!("no nodes available")
         }
-        let mut latest_version = 0u64;
-        for c in clients {
-            latest_version = latest_version.max(
-                c.get_ledger_information()
-                    .await
-                    .map(|r| r.into_inner().version)
-                    .unwrap_or(0),
-            );
-        }
+        let latest_version = loop {
+            let versions = join_all(nodes.iter().map(|(_, client)| {
+                tokio::time::timeout(CATCHUP_REQUEST_TIMEOUT, client.get_ledger_information())
+            }))
+            .await
+            .into_iter()
+            .filter_map(|result| Some(result.ok()?.ok()?.into_inner().version))
+            .collect::<Vec<_>>();
+            if let Some(latest_version) = versions.into_iter().max() {
+                break latest_version;
+            }
+
+            if Instant::now() > deadline {
+                bail!("waiting for nodes to catch up timed out, none of them is reachable");
+            }
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        };
 
         self.wait_for_all_nodes_to_catchup_to_version(latest_version, deadline)
             .await
     }
 }
+
+/// Fails if the accumulator root hashes of the nodes differ at `version`, listing the nodes with
+/// each root hash
+async fn check_root_hashes_at_version(nodes: &[(String, RestClient)], version: u64) -> Result<()> {
+    let root_hashes = try_join_all(
+        nodes
+            .iter()
+            .map(|(_, client)| client.get_transaction_by_version(version)),
+    )
+    .await?
+    .into_iter()
+    .map(|resp| Ok(resp.into_inner().transaction_info()?.accumulator_root_hash))
+    .collect::<Result<Vec<_>>>()?;
+
+    let mut nodes_by_root_hash: Vec<(HashValue, Vec<&str>)> = vec![];
+    for ((name, _), root_hash) in nodes.iter().zip(root_hashes) {
+        match nodes_by_root_hash
+            .iter_mut()
+            .find(|(hash, _)| *hash == root_hash)
+        {
+            Some((_, names)) => names.push(name.as_str()),
+            None => nodes_by_root_hash.push((root_hash, vec![name.as_str()])),
+        }
+    }
+    if nodes_by_root_hash.len() > 1 {
+        bail!(
+            "Fork check failed, nodes diverged at or before version {}: {}",
+            version,
+            nodes_by_root_hash
+                .iter()
+                .map(|(hash, names)| format!("{} on {}", hash, names.join(", ")))
+                .collect::<Vec<_>>()
+                .join("; ")
+        );
+    }
+    Ok(())
+}
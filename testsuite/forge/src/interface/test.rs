@@ -27,6 +27,15 @@ pub trait Test: Send + Sync {
     fn should_fail(&self) -> ShouldFail {
         ShouldFail::No
     }
+
+    /// Indicates if the Test needs the swarm to itself. When Forge runs tests in parallel, Aptos
+    /// and Admin tests which aren't exclusive run concurrently, each with its own root account
+    /// which can create accounts and mint but has none of the other privileges of the root
+    /// account, while the Network tests which aren't exclusive run one after the other alongside
+    /// them.
+    fn exclusive(&self) -> bool {
+        true
+    }
 }
 
 impl<T: Test + ?Sized> Test for &T {
@@ -41,6 +50,10 @@ impl<T: Test + ?Sized> Test for &T {
     fn should_fail(&self) -> ShouldFail {
         (**self).should_fail()
     }
+
+    fn exclusive(&self) -> bool {
+        (**self).exclusive()
+    }
 }
 
 #[derive(Debug)]
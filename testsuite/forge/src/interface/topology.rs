@@ -0,0 +1,108 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::Result;
+use anyhow::bail;
+use std::time::Duration;
+
+/// A simulated geographic distribution of the validators of a swarm, see
+/// [`ForgeConfig::with_topology`](crate::ForgeConfig::with_topology).
+///
+/// Validators are assigned to the regions round robin, in the order they were created, and the
+/// traffic between validators of two regions is delayed by the latency set between them. Traffic
+/// within a region, or between regions without a latency, isn't delayed.
+///
+/// Only the k8s backend supports topologies: it labels the validator pods with their region and
+/// delays the traffic between regions with chaos-mesh, which has to be installed on the cluster.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TopologySpec {
+    regions: Vec<String>,
+    latencies: Vec<(String, String, Duration)>,
+}
+
+impl TopologySpec {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a region. Its name labels the validators of the region, so it's limited to lowercase
+    /// alphanumeric characters and '-'.
+    pub fn with_region<S: Into<String>>(mut self, name: S) -> Self {
+        self.regions.push(name.into());
+        self
+    }
+
+    /// Delays the traffic between the validators of regions `a` and `b` by `latency` in each
+    /// direction, so round trips between them take twice as long
+    pub fn with_latency<A: Into<String>, B: Into<String>>(
+        mut self,
+        a: A,
+        b: B,
+        latency: Duration,
+    ) -> Self {
+        self.latencies.push((a.into(), b.into(), latency));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    pub fn regions(&self) -> &[String] {
+        &self.regions
+    }
+
+    /// The one way latency between each pair of regions that has one
+    pub fn latencies(&self) -> &[(String, String, Duration)] {
+        &self.latencies
+    }
+
+    /// Returns the region of the `index`th validator, none if the topology has no regions
+    pub fn region_of(&self, index: usize) -> Option<&str> {
+        if self.regions.is_empty() {
+            return None;
+        }
+        Some(&self.regions[index % self.regions.len()])
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        for (i, region) in self.regions.iter().enumerate() {
+            if region.is_empty()
+                || !region
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+            {
+                bail!(
+                    "Region names are limited to lowercase alphanumeric characters and '-', got {:?}",
+                    region
+                );
+            }
+            if self.regions[..i].contains(region) {
+                bail!("Region {} is defined more than once", region);
+            }
+        }
+        for (i, (a, b, latency)) in self.latencies.iter().enumerate() {
+            for region in [a, b] {
+                if !self.regions.contains(region) {
+                    bail!("Latency set for unknown region {}", region);
+                }
+            }
+            if a == b {
+                bail!(
+                    "Latency can only be set between different regions, got {}",
+                    a
+                );
+            }
+            if latency.is_zero() {
+                bail!("Latency between {} and {} is zero", a, b);
+            }
+            if self.latencies[..i]
+                .iter()
+                .any(|(x, y, _)| (x == a && y == b) || (x == b && y == a))
+            {
+                bail!("Latency between {} and {} is set more than once", a, b);
+            }
+        }
+        Ok(())
+    }
+}
@@ -19,8 +19,14 @@ pub use transaction_emitter::*;
 mod report;
 pub use report::*;
 
+mod baseline;
+pub use baseline::*;
+
 mod github;
 pub use github::*;
 
 mod slack;
 pub use slack::*;
+
+mod api_recording;
+pub use api_recording::start_api_recording;
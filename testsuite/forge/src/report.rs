@@ -2,8 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::TxnStats;
-use serde::Serialize;
-use std::{fmt, time::Duration};
+use anyhow::{format_err, Result};
+use aptos_sdk::types::PeerId;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt, fs, path::Path, time::Duration};
 
 #[derive(Default, Debug, Serialize)]
 pub struct TestReport {
@@ -11,13 +13,172 @@ pub struct TestReport {
     text: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct ReportedMetric {
     pub test_name: String,
     pub metric: String,
     pub value: f64,
 }
 
+/// The outcome of a single test of a Forge run
+#[derive(Clone, Debug, Serialize)]
+pub struct TestOutcome {
+    pub name: String,
+    pub passed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub duration_secs: f64,
+    /// Whether the test was stopped for running longer than the test timeout
+    pub timed_out: bool,
+}
+
+/// The version a node of the swarm ran at the end of a Forge run
+#[derive(Debug, Serialize)]
+pub struct NodeVersion {
+    pub peer_id: PeerId,
+    pub validator: bool,
+    pub version: String,
+}
+
+/// The nodes of one instance type in a node pool of the cluster a swarm runs on
+#[derive(Clone, Debug, Serialize)]
+pub struct NodePoolUsage {
+    pub node_pool: String,
+    pub instance_type: String,
+    pub num_nodes: usize,
+    /// On-demand price of an instance, when known
+    pub hourly_price_usd: Option<f64>,
+}
+
+/// The nodes of one instance type in a node pool of the cluster, over a whole Forge run
+#[derive(Clone, Debug, Serialize)]
+pub struct NodePoolCost {
+    pub node_pool: String,
+    pub instance_type: String,
+    /// The most nodes the pool had at once during the run
+    pub max_nodes: usize,
+    /// Hours of the nodes of the pool, added up over the run
+    pub node_hours: f64,
+    /// On-demand price of an instance, when known
+    pub hourly_price_usd: Option<f64>,
+}
+
+/// Estimated price of the machines a Forge run used, billed for the whole run
+#[derive(Clone, Debug, Serialize)]
+pub struct RunCost {
+    pub node_pools: Vec<NodePoolCost>,
+    pub duration_secs: f64,
+    /// Price of the node pools whose instance type has a known price
+    pub estimated_cost_usd: f64,
+    /// Instance types without a known price, which the estimate leaves out
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unpriced_instance_types: Vec<String>,
+}
+
+impl RunCost {
+    /// Bills the node pools of a run lasting `duration` from `samples` of them, each taken some
+    /// time into the run. The nodes of a sample are billed until the next sample, those of the
+    /// first one from the start of the run and those of the last one until its end.
+    pub fn new(samples: &[(Duration, Vec<NodePoolUsage>)], duration: Duration) -> Self {
+        let mut node_pools = BTreeMap::new();
+        for (i, (taken_at, usages)) in samples.iter().enumerate() {
+            let from = if i == 0 { Duration::ZERO } else { *taken_at };
+            let until = samples.get(i + 1).map_or(duration, |(next, _)| *next);
+            let hours = until.saturating_sub(from).as_secs_f64() / 3600.0;
+            for usage in usages {
+                let pool = node_pools
+                    .entry((usage.node_pool.clone(), usage.instance_type.clone()))
+                    .or_insert_with(|| NodePoolCost {
+                        node_pool: usage.node_pool.clone(),
+                        instance_type: usage.instance_type.clone(),
+                        max_nodes: 0,
+                        node_hours: 0.0,
+                        hourly_price_usd: usage.hourly_price_usd,
+                    });
+                pool.max_nodes = pool.max_nodes.max(usage.num_nodes);
+                pool.node_hours += usage.num_nodes as f64 * hours;
+            }
+        }
+        let node_pools = node_pools
+            .into_iter()
+            .map(|(_, pool)| pool)
+            .collect::<Vec<_>>();
+
+        let estimated_cost_usd = node_pools
+            .iter()
+            .filter_map(|pool| pool.hourly_price_usd.map(|price| price * pool.node_hours))
+            .sum::<f64>();
+        let mut unpriced_instance_types = node_pools
+            .iter()
+            .filter(|pool| pool.hourly_price_usd.is_none())
+            .map(|pool| pool.instance_type.clone())
+            .collect::<Vec<_>>();
+        unpriced_instance_types.sort();
+        unpriced_instance_types.dedup();
+        Self {
+            node_pools,
+            duration_secs: duration.as_secs_f64(),
+            estimated_cost_usd,
+            unpriced_instance_types,
+        }
+    }
+}
+
+impl fmt::Display for RunCost {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Estimated cost: ${:.2} for a run of {:.1} hours, with",
+            self.estimated_cost_usd,
+            self.duration_secs / 3600.0
+        )?;
+        for (i, pool) in self.node_pools.iter().enumerate() {
+            let separator = if i == 0 { " " } else { ", " };
+            write!(
+                f,
+                "{}{:.1} node hours of up to {} {} nodes in {}",
+                separator, pool.node_hours, pool.max_nodes, pool.instance_type, pool.node_pool
+            )?;
+        }
+        if !self.unpriced_instance_types.is_empty() {
+            write!(
+                f,
+                " (no price for {})",
+                self.unpriced_instance_types.join(", ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Machine readable report of a Forge run, written with `--report-format json`
+#[derive(Debug, Serialize)]
+pub struct JsonReport<'a> {
+    pub success: bool,
+    /// Seed of the run, to replay it
+    pub seed: u64,
+    /// Shard of the suite the run executed, as `i/n`, when it was sharded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shard: Option<String>,
+    pub duration_secs: f64,
+    pub tests: &'a [TestOutcome],
+    pub metrics: &'a [ReportedMetric],
+    pub nodes: &'a [NodeVersion],
+    /// Estimated price of the machines of the run, for the backends which bill them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost: Option<&'a RunCost>,
+    /// The human readable report, as sent to Slack
+    pub text: &'a str,
+}
+
+impl<'a> JsonReport<'a> {
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let contents = serde_json::to_string_pretty(self)?;
+        fs::write(path, contents)
+            .map_err(|e| format_err!("Failed to write json report {:?}: {}", path, e))
+    }
+}
+
 impl TestReport {
     pub fn new() -> Self {
         Default::default()
@@ -31,6 +192,14 @@ impl TestReport {
         });
     }
 
+    pub fn metrics(&self) -> &[ReportedMetric] {
+        &self.metrics
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
     pub fn report_text(&mut self, text: String) {
         if !self.text.is_empty() {
             self.text.push('\n');
@@ -38,29 +207,72 @@ impl TestReport {
         self.text.push_str(&text);
     }
 
+    /// Appends the metrics and text of the report of a test which ran alongside others
+    pub fn merge(&mut self, other: TestReport) {
+        self.metrics.extend(other.metrics);
+        if !other.text.is_empty() {
+            self.report_text(other.text);
+        }
+    }
+
+    /// Reports how long the network took to recover from a failure injected by the test
+    pub fn report_recovery_time<T: ToString>(&mut self, test_name: T, recovery_time: Duration) {
+        self.report_metric(
+            test_name,
+            "recovery_time_ms",
+            recovery_time.as_millis() as f64,
+        );
+    }
+
     pub fn report_txn_stats(&mut self, test_name: String, stats: TxnStats, window: Duration) {
         let submitted_txn = stats.submitted;
         let expired_txn = stats.expired;
+        let rejected_txn = stats.rejected;
         let avg_tps = stats.committed / window.as_secs();
         let avg_latency_client = if stats.committed == 0 {
             0u64
         } else {
             stats.latency / stats.committed
         };
-        let p99_latency = stats.latency_buckets.percentile(99, 100);
+        let p50_latency = stats.latency_histogram.percentile(50.0);
+        let p90_latency = stats.latency_histogram.percentile(90.0);
+        let p99_latency = stats.latency_histogram.percentile(99.0);
+        let max_latency = stats.latency_histogram.max();
         self.report_metric(test_name.clone(), "submitted_txn", submitted_txn as f64);
         self.report_metric(test_name.clone(), "expired_txn", expired_txn as f64);
+        self.report_metric(test_name.clone(), "rejected_txn", rejected_txn as f64);
         self.report_metric(test_name.clone(), "avg_tps", avg_tps as f64);
         self.report_metric(test_name.clone(), "avg_latency", avg_latency_client as f64);
+        self.report_metric(test_name.clone(), "p50_latency", p50_latency as f64);
+        self.report_metric(test_name.clone(), "p90_latency", p90_latency as f64);
         self.report_metric(test_name.clone(), "p99_latency", p99_latency as f64);
+        self.report_metric(test_name.clone(), "max_latency", max_latency as f64);
         let expired_text = if expired_txn == 0 {
             "no expired txns".to_string()
         } else {
             format!("(!) expired {} out of {} txns", expired_txn, submitted_txn)
         };
+        let rejected_text = if rejected_txn == 0 {
+            String::new()
+        } else {
+            format!(", (!) mempool full for {} submissions", rejected_txn)
+        };
+        let requested_text = stats
+            .requested_tps
+            .map(|tps| format!(" of {} requested", tps))
+            .unwrap_or_default();
         self.report_text(format!(
-            "{} : {:.0} TPS, {:.1} ms latency, {:.1} ms p99 latency,{}",
-            test_name, avg_tps, avg_latency_client, p99_latency, expired_text
+            "{} : {:.0} TPS{}, {:.1} ms latency, {} ms p50 / {} ms p90 / {} ms p99 / {} ms max latency, {}{}",
+            test_name,
+            avg_tps,
+            requested_text,
+            avg_latency_client,
+            p50_latency,
+            p90_latency,
+            p99_latency,
+            max_latency,
+            expired_text,
+            rejected_text
         ));
     }
 
@@ -81,3 +293,80 @@ impl fmt::Display for TestReport {
         write!(f, "{}", self.text)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(node_pool: &str, num_nodes: usize, hourly_price_usd: Option<f64>) -> NodePoolUsage {
+        NodePoolUsage {
+            node_pool: node_pool.to_string(),
+            instance_type: if hourly_price_usd.is_some() {
+                "c5.4xlarge".to_string()
+            } else {
+                "unknown".to_string()
+            },
+            num_nodes,
+            hourly_price_usd,
+        }
+    }
+
+    fn hours(hours: u64) -> Duration {
+        Duration::from_secs(hours * 3600)
+    }
+
+    #[test]
+    fn test_run_cost_over_samples() {
+        // the pool grows from 2 to 4 nodes an hour in, and is back to 2 an hour later
+        let samples = vec![
+            (
+                Duration::from_secs(60),
+                vec![usage("validators", 2, Some(1.0))],
+            ),
+            (hours(1), vec![usage("validators", 4, Some(1.0))]),
+            (hours(2), vec![usage("validators", 2, Some(1.0))]),
+        ];
+        let cost = RunCost::new(&samples, hours(3));
+        assert_eq!(cost.node_pools.len(), 1);
+        assert_eq!(cost.node_pools[0].max_nodes, 4);
+        assert!((cost.node_pools[0].node_hours - 8.0).abs() < 1e-9);
+        assert!((cost.estimated_cost_usd - 8.0).abs() < 1e-9);
+        assert!((cost.duration_secs - 10_800.0).abs() < 1e-9);
+        assert!(cost.unpriced_instance_types.is_empty());
+    }
+
+    #[test]
+    fn test_run_cost_unpriced_pools() {
+        // a pool appearing in a later sample is only billed from then on
+        let samples = vec![
+            (Duration::ZERO, vec![usage("validators", 1, Some(0.5))]),
+            (
+                hours(1),
+                vec![usage("validators", 1, Some(0.5)), usage("local", 3, None)],
+            ),
+        ];
+        let cost = RunCost::new(&samples, hours(2));
+        assert_eq!(cost.node_pools.len(), 2);
+        assert_eq!(cost.node_pools[0].node_pool, "local");
+        assert!((cost.node_pools[0].node_hours - 3.0).abs() < 1e-9);
+        assert!((cost.estimated_cost_usd - 1.0).abs() < 1e-9);
+        assert_eq!(cost.unpriced_instance_types, vec!["unknown".to_string()]);
+
+        assert!(RunCost::new(&[], hours(1)).node_pools.is_empty());
+    }
+
+    #[test]
+    fn test_run_cost_display() {
+        let samples = vec![(
+            Duration::ZERO,
+            vec![usage("validators", 2, Some(0.68)), usage("local", 1, None)],
+        )];
+        let cost = RunCost::new(&samples, Duration::from_secs(5_400));
+        assert_eq!(
+            cost.to_string(),
+            "Estimated cost: $2.04 for a run of 1.5 hours, with 1.5 node hours of up to 1 unknown \
+             nodes in local, 3.0 node hours of up to 2 c5.4xlarge nodes in validators (no price \
+             for unknown)"
+        );
+    }
+}
@@ -1,12 +1,29 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::*;
-use rand::{Rng, SeedableRng};
+use crate::{api_recording, *};
+use anyhow::{bail, format_err};
+use aptos_config::config::NodeConfig;
+use aptos_sdk::{
+    crypto::ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+    types::{chain_id::ChainId, AccountKey, LocalAccount},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use regex::Regex;
 use std::{
+    collections::{BTreeMap, HashSet},
+    convert::TryFrom,
+    fmt,
+    future::Future,
     io::{self, Write},
+    mem,
     num::NonZeroUsize,
+    path::PathBuf,
     process,
+    str::FromStr,
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
 };
 use structopt::{clap::arg_enum, StructOpt};
 use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
@@ -14,6 +31,11 @@ use tokio::runtime::Runtime;
 // TODO going to remove random seed once cluster deployment supports re-run genesis
 use rand::rngs::OsRng;
 
+/// Coins of the root account of each test running alongside others
+const DELEGATED_ROOT_ACCOUNT_COINS: u64 = 1_000_000_000;
+/// Versions at which the ledgers of the nodes are compared after each Network test
+const FORK_CHECK_SAMPLES: u64 = 10;
+
 #[derive(Debug, StructOpt)]
 #[structopt(about = "Forged in Fire")]
 pub struct Options {
@@ -23,10 +45,20 @@ pub struct Options {
     #[structopt(long = "exact")]
     /// Exactly match filters rather than by substring
     filter_exact: bool,
-    #[allow(dead_code)]
+    #[structopt(long)]
+    /// Only run the tests whose names match one of these regexes, all of them by default
+    include: Vec<Regex>,
+    #[structopt(long)]
+    /// Don't run the tests whose names match one of these regexes
+    exclude: Vec<Regex>,
+    #[structopt(long)]
+    /// Only run the share of the tests of shard `i/n`, with `i` from 1 to `n`, to split a suite
+    /// between `n` parallel jobs. The tests the filters keep are dealt out between the shards in
+    /// the order of their names, so every test runs in exactly one of them.
+    shard: Option<Shard>,
     #[structopt(long, default_value = "1", env = "RUST_TEST_THREADS")]
-    /// NO-OP: unsupported option, exists for compatibility with the default test harness
-    /// Number of threads used for running tests in parallel
+    /// Number of threads used for running tests in parallel. With more than 1, the tests which
+    /// aren't exclusive run alongside each other before the exclusive ones run one at a time.
     test_threads: NonZeroUsize,
     #[allow(dead_code)]
     #[structopt(short = "q", long)]
@@ -62,12 +94,109 @@ pub struct Options {
     /// NO-OP: unsupported option, exists for compatibility with the default test harness
     /// Show captured stdout of successful tests
     show_output: bool,
+    /// Format of the test report:
+    ///   text = Only print the report on stdout;
+    ///   json = Also write a machine readable report to the report file
+    #[structopt(long, possible_values = &ReportFormat::variants(), default_value, case_insensitive = true)]
+    report_format: ReportFormat,
+    #[structopt(long, default_value = "forge_report.json", parse(from_os_str))]
+    /// File the json report is written to
+    report_file: PathBuf,
+    #[structopt(long)]
+    /// Seconds each test may run before it's stopped and marked as timed out, unlimited by
+    /// default. Aptos tests are stopped wherever they are. Admin and Network tests run
+    /// synchronously and can't be interrupted, so they're left behind on a thread of their own.
+    /// When such a test holds on to the swarm, the logs of the swarm are collected and its nodes
+    /// stopped, and the tests after it run against a new swarm.
+    test_timeout_secs: Option<u64>,
+    #[structopt(long, parse(from_os_str))]
+    /// Records the REST API requests the tests make and the responses to them to this file, one
+    /// json record per line, to be replayed against the API alone by the replay tests of the API
+    record_api: Option<PathBuf>,
 }
 
 impl Options {
     pub fn from_args() -> Self {
         StructOpt::from_args()
     }
+
+    fn start_api_recording(&self) -> Result<()> {
+        match &self.record_api {
+            Some(path) => start_api_recording(path),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether the filters keep the test, before sharding
+    fn matches(&self, test: &dyn Test) -> bool {
+        let ignored = match (self.include_ignored, self.ignored) {
+            (true, _) => true, // Don't filter anything
+            (false, true) => test.ignored(),
+            (false, false) => !test.ignored(),
+        };
+        let name = test.name();
+        let filtered = match &self.filter {
+            Some(filter) if self.filter_exact => name == filter.as_str(),
+            Some(filter) => name.contains(&filter[..]),
+            None => true,
+        };
+        let included = self.include.is_empty() || self.include.iter().any(|re| re.is_match(name));
+        let excluded = self.exclude.iter().any(|re| re.is_match(name));
+        ignored && filtered && included && !excluded
+    }
+
+    /// Adds the tests the shard of the run executed to the report
+    fn report_shard(&self, report: &mut TestReport, outcomes: &[TestOutcome]) {
+        if let Some(shard) = &self.shard {
+            let names = outcomes
+                .iter()
+                .map(|outcome| outcome.name.as_str())
+                .collect::<Vec<_>>();
+            report.report_text(format!("Shard {} ran: {}", shard, names.join(", ")));
+        }
+    }
+}
+
+/// One of the `count` shards a suite is split between, numbered from 1
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Shard {
+    index: usize,
+    count: usize,
+}
+
+impl Shard {
+    pub fn new(index: usize, count: usize) -> Result<Self> {
+        if count == 0 || index == 0 || index > count {
+            bail!(
+                "Invalid shard {}/{}, expected i/n with 1 <= i <= n",
+                index,
+                count
+            );
+        }
+        Ok(Self { index, count })
+    }
+
+    /// Whether the test at `position`, in the order of the names of the tests, is in this shard
+    fn contains(&self, position: usize) -> bool {
+        position % self.count == self.index - 1
+    }
+}
+
+impl FromStr for Shard {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (index, count) = s
+            .split_once('/')
+            .ok_or_else(|| format_err!("Invalid shard {}, expected i/n", s))?;
+        Self::new(index.trim().parse()?, count.trim().parse()?)
+    }
+}
+
+impl fmt::Display for Shard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
 }
 
 arg_enum! {
@@ -85,6 +214,20 @@ impl Default for Format {
     }
 }
 
+arg_enum! {
+    #[derive(Debug, Eq, PartialEq)]
+    pub enum ReportFormat {
+        Text,
+        Json,
+    }
+}
+
+impl Default for ReportFormat {
+    fn default() -> Self {
+        ReportFormat::Text
+    }
+}
+
 pub fn forge_main<F: Factory>(tests: ForgeConfig<'_>, factory: F, options: &Options) -> Result<()> {
     let forge = Forge::new(options, tests, factory, EmitJobRequest::default());
 
@@ -94,7 +237,14 @@ pub fn forge_main<F: Factory>(tests: ForgeConfig<'_>, factory: F, options: &Opti
         return Ok(());
     }
 
-    match forge.run() {
+    let result = forge.run();
+    if let Err(e) = forge.teardown(result.is_ok()) {
+        eprintln!("Failed to tear down swarm:\n{:?}", e);
+        if result.is_ok() {
+            process::exit(101);
+        }
+    }
+    match result {
         Ok(..) => Ok(()),
         Err(e) => {
             eprintln!("Failed to run tests:\n{}", e);
@@ -110,18 +260,49 @@ pub enum InitialVersion {
 }
 
 pub struct ForgeConfig<'cfg> {
-    aptos_tests: &'cfg [&'cfg dyn AptosTest],
-    admin_tests: &'cfg [&'cfg dyn AdminTest],
-    network_tests: &'cfg [&'cfg dyn NetworkTest],
+    aptos_tests: &'cfg [&'static dyn AptosTest],
+    admin_tests: &'cfg [&'static dyn AdminTest],
+    network_tests: &'cfg [&'static dyn NetworkTest],
 
     /// The initial number of validators to spawn when the test harness creates a swarm
     initial_validator_count: NonZeroUsize,
 
+    /// The initial number of fullnodes to spawn along with the validators, the default of the
+    /// backend if unset
+    initial_fullnode_count: Option<usize>,
+
+    /// The number of validators the tests can add to the swarm after the genesis
+    standby_validator_count: usize,
+
     /// The initial version to use when the test harness creates a swarm
     initial_version: InitialVersion,
 
     /// The initial genesis modules to use when starting a network
     genesis_config: Option<GenesisConfig>,
+
+    /// Environment variables and features applied to every node when starting a network. All
+    /// tests of a config share the same network, so these apply to all of them.
+    node_environment: NodeEnvironment,
+
+    /// Environment variables set on top of those of `node_environment` while a given Network
+    /// test runs, by test name, see `with_test_env_var`
+    test_env_vars: BTreeMap<&'static str, BTreeMap<String, String>>,
+
+    /// Accounts and packages created at genesis, so tests needing on-chain state don't have to
+    /// create it from the root account
+    initial_state: InitialState,
+
+    /// Seed of the rng the swarm and the tests draw their randomness from, a random one if unset
+    seed: Option<u64>,
+
+    /// Whether the Network tests submit their traffic to the fullnodes of the validators
+    emit_to_fullnodes: bool,
+
+    /// Simulated regions the validators are spread over, with the latency between them
+    topology: TopologySpec,
+
+    /// Chaos applied to the swarm in the background while the tests run
+    chaos_schedule: ChaosSchedule,
 }
 
 impl<'cfg> ForgeConfig<'cfg> {
@@ -129,17 +310,17 @@ impl<'cfg> ForgeConfig<'cfg> {
         Self::default()
     }
 
-    pub fn with_aptos_tests(mut self, aptos_tests: &'cfg [&'cfg dyn AptosTest]) -> Self {
+    pub fn with_aptos_tests(mut self, aptos_tests: &'cfg [&'static dyn AptosTest]) -> Self {
         self.aptos_tests = aptos_tests;
         self
     }
 
-    pub fn with_admin_tests(mut self, admin_tests: &'cfg [&'cfg dyn AdminTest]) -> Self {
+    pub fn with_admin_tests(mut self, admin_tests: &'cfg [&'static dyn AdminTest]) -> Self {
         self.admin_tests = admin_tests;
         self
     }
 
-    pub fn with_network_tests(mut self, network_tests: &'cfg [&'cfg dyn NetworkTest]) -> Self {
+    pub fn with_network_tests(mut self, network_tests: &'cfg [&'static dyn NetworkTest]) -> Self {
         self.network_tests = network_tests;
         self
     }
@@ -149,6 +330,25 @@ impl<'cfg> ForgeConfig<'cfg> {
         self
     }
 
+    /// Sets the number of fullnodes the swarm starts with, spread evenly over the validators, so
+    /// that suites can scale the fullnodes independently of the validators, e.g. for state sync
+    /// or API load. By default the k8s backend gives every validator one fullnode, and the local
+    /// backend launches none. The local backend attaches one fullnode to each validator and
+    /// launches the others as public fullnodes. On k8s the fullnodes beyond the first of a
+    /// validator get identities of their own on the public network.
+    pub fn with_initial_fullnode_count(mut self, initial_fullnode_count: usize) -> Self {
+        self.initial_fullnode_count = Some(initial_fullnode_count);
+        self
+    }
+
+    /// Makes room for validators the tests add to the swarm and have join the validator set, see
+    /// `Swarm::add_validator`. The k8s backend provisions their identities at genesis, so it can
+    /// only add this many, while the local backend can add any number of them.
+    pub fn with_standby_validator_count(mut self, standby_validator_count: usize) -> Self {
+        self.standby_validator_count = standby_validator_count;
+        self
+    }
+
     pub fn with_initial_version(mut self, initial_version: InitialVersion) -> Self {
         self.initial_version = initial_version;
         self
@@ -164,6 +364,123 @@ impl<'cfg> ForgeConfig<'cfg> {
         self
     }
 
+    /// Creates the accounts of the given public keys at genesis, with the given TestCoin balance
+    pub fn with_initial_accounts(mut self, accounts: Vec<(Ed25519PublicKey, u64)>) -> Self {
+        self.initial_state.accounts = accounts;
+        self
+    }
+
+    /// Publishes the packages at genesis, given as the module blobs of each package. Each package
+    /// is published under the address of its modules.
+    pub fn with_published_packages(mut self, packages: Vec<Vec<Vec<u8>>>) -> Self {
+        self.initial_state.packages = packages;
+        self
+    }
+
+    pub fn with_env_var<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.node_environment
+            .env_vars
+            .insert(key.into(), value.into());
+        self
+    }
+
+    /// Sets an environment variable on every node while the Network test `test_name` runs, on
+    /// top of those of `with_env_var`. The nodes are restarted with it before the test and
+    /// without it after, so the test has to be exclusive when tests run in parallel. Only the
+    /// local backend supports it.
+    pub fn with_test_env_var<K: Into<String>, V: Into<String>>(
+        mut self,
+        test_name: &'static str,
+        key: K,
+        value: V,
+    ) -> Self {
+        self.test_env_vars
+            .entry(test_name)
+            .or_default()
+            .insert(key.into(), value.into());
+        self
+    }
+
+    pub fn with_node_feature(mut self, feature: NodeFeature) -> Self {
+        self.node_environment.features.push(feature);
+        self
+    }
+
+    /// Changes the config of every node before the network starts, e.g. to lower
+    /// `state_sync.chunk_limit`. Fullnodes get it too, tests can tell them apart by
+    /// `config.base.role`. The k8s backend only passes on the fields the function changes from
+    /// the default config of the node.
+    pub fn with_node_config_fn<F>(mut self, node_config_fn: F) -> Self
+    where
+        F: Fn(&mut NodeConfig) + Send + Sync + 'static,
+    {
+        self.node_environment.node_config_fn = Some(NodeConfigFn::new(node_config_fn));
+        self
+    }
+
+    /// Merges `helm_values` over the values the k8s backend installs the validator chart with,
+    /// e.g. `validator: { resources: ... }` or node config through `validator: { config: ... }`.
+    /// The values of a previous run are cleared. The local backend ignores them.
+    pub fn with_helm_values(mut self, helm_values: serde_yaml::Value) -> Self {
+        self.node_environment.helm_values = Some(helm_values);
+        self
+    }
+
+    /// Like [`ForgeConfig::with_helm_values`] for the validator `index` alone, e.g. to run one
+    /// validator differently from the others. Its values are merged over the ones of all the
+    /// validators.
+    pub fn with_validator_helm_values(
+        mut self,
+        index: usize,
+        helm_values: serde_yaml::Value,
+    ) -> Self {
+        self.node_environment
+            .validator_helm_values
+            .insert(index, helm_values);
+        self
+    }
+
+    /// Limits the CPU and memory of the validator `index`, e.g. to check that an under-provisioned
+    /// validator keeps up through state sync when it's too slow to take part in consensus. Only
+    /// the local backend supports it, through cgroups on Linux, see
+    /// `LocalNode::set_resource_limits`.
+    pub fn with_validator_resource_limits(mut self, index: usize, limits: ResourceLimits) -> Self {
+        self.node_environment
+            .validator_resource_limits
+            .insert(index, limits);
+        self
+    }
+
+    /// Replays a run with the seed it printed, so the swarm gets the same keys and the tests the
+    /// same accounts and peer picks. Ports and timings still differ between runs.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Has the Network tests submit their traffic to the fullnodes of the validators instead of
+    /// the validators, so that it goes through mempool forwarding. The swarm needs fullnodes for
+    /// this, which the local backend doesn't launch.
+    pub fn with_emit_to_fullnodes(mut self) -> Self {
+        self.emit_to_fullnodes = true;
+        self
+    }
+
+    /// Spreads the validators over simulated regions with latency between them, to benchmark
+    /// consensus under geo-distributed conditions. See `TopologySpec` for the backends supporting
+    /// it.
+    pub fn with_topology(mut self, topology: TopologySpec) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    /// Applies the chaos of `chaos_schedule` to the swarm while the tests run, logging it in the
+    /// report. See `ChaosSchedule` for the backends supporting it.
+    pub fn with_chaos_schedule(mut self, chaos_schedule: ChaosSchedule) -> Self {
+        self.chaos_schedule = chaos_schedule;
+        self
+    }
+
     pub fn number_of_tests(&self) -> usize {
         self.admin_tests.len() + self.network_tests.len() + self.aptos_tests.len()
     }
@@ -175,6 +492,30 @@ impl<'cfg> ForgeConfig<'cfg> {
             .chain(self.network_tests.iter().map(|t| t as &dyn Test))
             .chain(self.aptos_tests.iter().map(|t| t as &dyn Test))
     }
+
+    /// Checks that the tests given environment variables of their own are Network tests of the
+    /// config, and exclusive ones if tests run in `parallel`, as the nodes restart for them
+    fn check_test_env_vars(&self, parallel: bool) -> Result<()> {
+        for test_name in self.test_env_vars.keys() {
+            match self
+                .network_tests
+                .iter()
+                .find(|test| test.name() == *test_name)
+            {
+                None => bail!(
+                    "Environment variables set for {}, which isn't a Network test",
+                    test_name
+                ),
+                Some(test) if parallel && !test.exclusive() => bail!(
+                    "Network test {} sets environment variables of its own, so it has to be \
+                     exclusive to run with other tests in parallel",
+                    test_name
+                ),
+                Some(_) => {}
+            }
+        }
+        Ok(())
+    }
 }
 
 impl<'cfg> Default for ForgeConfig<'cfg> {
@@ -184,8 +525,17 @@ impl<'cfg> Default for ForgeConfig<'cfg> {
             admin_tests: &[],
             network_tests: &[],
             initial_validator_count: NonZeroUsize::new(1).unwrap(),
+            initial_fullnode_count: None,
+            standby_validator_count: 0,
             initial_version: InitialVersion::Newest,
             genesis_config: None,
+            node_environment: NodeEnvironment::default(),
+            test_env_vars: BTreeMap::new(),
+            initial_state: InitialState::default(),
+            seed: None,
+            emit_to_fullnodes: false,
+            topology: TopologySpec::default(),
+            chaos_schedule: ChaosSchedule::default(),
         }
     }
 }
@@ -193,6 +543,8 @@ impl<'cfg> Default for ForgeConfig<'cfg> {
 pub struct Forge<'cfg, F> {
     options: &'cfg Options,
     tests: ForgeConfig<'cfg>,
+    /// Names of the tests the run executes, see [`select_tests`]
+    selected_tests: HashSet<&'static str>,
     factory: F,
     global_job_request: EmitJobRequest,
 }
@@ -204,9 +556,11 @@ impl<'cfg, F: Factory> Forge<'cfg, F> {
         factory: F,
         global_job_request: EmitJobRequest,
     ) -> Self {
+        let selected_tests = select_tests(options, tests.all_tests());
         Self {
             options,
             tests,
+            selected_tests,
             factory,
             global_job_request,
         }
@@ -244,13 +598,29 @@ impl<'cfg, F: Factory> Forge<'cfg, F> {
             .expect("There has to be at least 1 version")
     }
 
+    /// Tears down the resources backing the swarm, see [`Factory::teardown`]
+    pub fn teardown(&self, success: bool) -> Result<()> {
+        self.factory.teardown(success)
+    }
+
     pub fn run(&self) -> Result<TestReport> {
         let test_count = self.filter_tests(self.tests.all_tests()).count();
-        let filtered_out = test_count.saturating_sub(self.tests.all_tests().count());
+        let filtered_out = self.tests.all_tests().count() - test_count;
 
+        let seed = self.tests.seed.unwrap_or_else(|| OsRng.gen());
+        let start = Instant::now();
         let mut report = TestReport::new();
+        let mut nodes = vec![];
+        let mut cost = None;
         let mut summary = TestSummary::new(test_count, filtered_out);
         summary.write_starting_msg()?;
+        let timeout = TestTimeout::new(self.options);
+        let parallel = self.options.test_threads.get() > 1;
+        self.tests.check_test_env_vars(parallel)?;
+        self.options.start_api_recording()?;
+        if let Some(shard) = &self.options.shard {
+            println!("Running {} tests in shard {}", test_count, shard);
+        }
 
         if test_count > 0 {
             println!(
@@ -260,119 +630,682 @@ impl<'cfg, F: Factory> Forge<'cfg, F> {
                     .map(|v| v.to_string())
                     .collect::<Vec<_>>()
             );
-            let initial_version = self.initial_version();
-            let genesis_version = self.genesis_version();
             let runtime = Runtime::new().unwrap();
-            let mut rng = ::rand::rngs::StdRng::from_seed(OsRng.gen());
-            let mut swarm = runtime.block_on(self.factory.launch_swarm(
-                &mut rng,
-                self.tests.initial_validator_count,
-                &initial_version,
-                &genesis_version,
-                self.tests.genesis_config.as_ref(),
-            ))?;
+            println!("Running with seed {}", seed);
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut swarm = self.launch_swarm(&runtime, &mut rng)?;
+            let mut node_pool_samples = vec![];
+            sample_node_pools(&runtime, &*swarm, start, &mut node_pool_samples);
+
+            let chaos = if self.tests.chaos_schedule.is_empty() {
+                None
+            } else {
+                let driver = swarm.chaos_driver()?;
+                let chaos_rng = StdRng::from_rng(&mut rng)?;
+                // stopped on drop as well, if a test fails the run before the end
+                Some(ChaosScheduleTask::spawn(
+                    &runtime,
+                    self.tests.chaos_schedule.clone(),
+                    driver,
+                    chaos_rng,
+                ))
+            };
+
+            if parallel {
+                swarm = self.run_parallel_tests(
+                    &runtime,
+                    &mut rng,
+                    swarm,
+                    &mut report,
+                    &mut summary,
+                    timeout,
+                )?;
+                sample_node_pools(&runtime, &*swarm, start, &mut node_pool_samples);
+            }
 
             // Run AptosTests
-            for test in self.filter_tests(self.tests.aptos_tests.iter()) {
+            for test in self
+                .filter_tests(self.tests.aptos_tests.iter())
+                .filter(|test| !parallel || test.exclusive())
+            {
                 let mut aptos_ctx = AptosContext::new(
                     CoreContext::from_rng(&mut rng),
                     swarm.chain_info().into_aptos_public_info(),
                     &mut report,
                 );
-                let result = run_test(|| runtime.block_on(test.run(&mut aptos_ctx)));
-                summary.handle_result(test.name().to_owned(), result)?;
+                let (result, duration) =
+                    timeout.run_async(test.name(), &runtime, test.run(&mut aptos_ctx));
+                summary.handle_result(test.name().to_owned(), result, duration)?;
             }
 
             // Run AdminTests
-            for test in self.filter_tests(self.tests.admin_tests.iter()) {
-                let mut admin_ctx = AdminContext::new(
-                    CoreContext::from_rng(&mut rng),
-                    swarm.chain_info(),
+            for test in self
+                .filter_tests(self.tests.admin_tests.iter())
+                .filter(|test| !parallel || test.exclusive())
+            {
+                let test: &'static dyn AdminTest = *test;
+                let core = CoreContext::from_rng(&mut rng);
+                let (result, duration, returned_swarm) = self.run_swarm_test(
+                    test.name(),
+                    &runtime,
+                    &mut rng,
+                    swarm,
                     &mut report,
-                );
-                let result = run_test(|| test.run(&mut admin_ctx));
-                summary.handle_result(test.name().to_owned(), result)?;
+                    timeout,
+                    move |swarm, report| {
+                        test.run(&mut AdminContext::new(core, swarm.chain_info(), report))
+                    },
+                )?;
+                swarm = returned_swarm;
+                sample_node_pools(&runtime, &*swarm, start, &mut node_pool_samples);
+                summary.handle_result(test.name().to_owned(), result, duration)?;
             }
 
-            for test in self.filter_tests(self.tests.network_tests.iter()) {
-                let mut network_ctx = NetworkContext::new(
-                    CoreContext::from_rng(&mut rng),
-                    &mut *swarm,
+            for test in self
+                .filter_tests(self.tests.network_tests.iter())
+                .filter(|test| !parallel || test.exclusive())
+            {
+                let test_env_vars = self.tests.test_env_vars.get(test.name());
+                if let Some(env_vars) = test_env_vars {
+                    runtime.block_on(swarm.set_test_env_vars(env_vars))?;
+                }
+                let core = CoreContext::from_rng(&mut rng);
+                let (result, duration, returned_swarm) = self.run_swarm_test(
+                    test.name(),
+                    &runtime,
+                    &mut rng,
+                    swarm,
                     &mut report,
-                    self.global_job_request.clone(),
-                );
-                let result = run_test(|| test.run(&mut network_ctx));
-                summary.handle_result(test.name().to_owned(), result)?;
+                    timeout,
+                    self.network_test(*test, core),
+                )?;
+                swarm = returned_swarm;
+                let result = check_for_forks(&runtime, &*swarm, result);
+                if test_env_vars.is_some() {
+                    runtime.block_on(swarm.set_test_env_vars(&BTreeMap::new()))?;
+                }
+                sample_node_pools(&runtime, &*swarm, start, &mut node_pool_samples);
+                summary.handle_result(test.name().to_owned(), result, duration)?;
             }
 
+            if let Some(chaos) = chaos {
+                let log = chaos.stop()?;
+                if log.is_empty() {
+                    report.report_text("Chaos schedule: no chaos applied".to_string());
+                } else {
+                    report.report_text(format!("Chaos schedule:\n{}", log.join("\n")));
+                }
+            }
+
+            // The nodes are billed for the whole run, which includes resizing the node pools
+            sample_node_pools(&runtime, &*swarm, start, &mut node_pool_samples);
+            if node_pool_samples.iter().any(|(_, pools)| !pools.is_empty()) {
+                cost = Some(RunCost::new(&node_pool_samples, start.elapsed()));
+            }
+            if let Some(cost) = &cost {
+                report.report_text(cost.to_string());
+            }
+            self.options.report_shard(&mut report, &summary.outcomes);
+
             report.print_report();
+            nodes = node_versions(&*swarm);
 
             io::stdout().flush()?;
             io::stderr().flush()?;
 
             if !summary.success() {
                 println!();
-                println!("Swarm logs can be found here: {}", swarm.logs_location());
+                println!("Replay this run with --seed {}", seed);
+                match swarm.collect_failure_artifacts(&report) {
+                    Ok(location) => println!("Swarm logs can be found here: {}", location),
+                    Err(e) => {
+                        println!("Failed to collect failure artifacts: {:?}", e);
+                        println!("Swarm logs can be found here: {}", swarm.logs_location());
+                    }
+                }
             }
         }
 
         summary.write_summary()?;
 
+        if self.options.report_format == ReportFormat::Json {
+            JsonReport {
+                success: summary.success(),
+                seed,
+                shard: self.options.shard.map(|shard| shard.to_string()),
+                duration_secs: start.elapsed().as_secs_f64(),
+                tests: &summary.outcomes,
+                metrics: report.metrics(),
+                nodes: &nodes,
+                cost: cost.as_ref(),
+                text: report.text(),
+            }
+            .write(&self.options.report_file)?;
+            println!("Json report written to {:?}", self.options.report_file);
+        }
+        if let Some(path) = &self.options.record_api {
+            println!("REST API traffic recorded to {:?}", path);
+        }
+
         if summary.success() {
             Ok(report)
         } else {
-            Err(anyhow::anyhow!("Tests Failed"))
+            Err(anyhow::anyhow!("Tests Failed with seed {}", seed))
+        }
+    }
+
+    fn launch_swarm(&self, runtime: &Runtime, rng: &mut StdRng) -> Result<Box<dyn Swarm>> {
+        runtime.block_on(self.factory.launch_swarm(
+            rng,
+            self.tests.initial_validator_count,
+            self.tests.initial_fullnode_count,
+            self.tests.standby_validator_count,
+            &self.initial_version(),
+            &self.genesis_version(),
+            self.tests.genesis_config.as_ref(),
+            &self.tests.initial_state,
+            &self.tests.node_environment,
+            &self.tests.topology,
+        ))
+    }
+
+    /// Runs a test against the swarm with a report of its own, merged into `report`, see
+    /// [`TestTimeout::run_blocking`]. A test which runs out of time is left holding on to the
+    /// swarm, which the run gives up on: its failure artifacts are collected, and a new swarm is
+    /// launched for the tests after it.
+    #[allow(clippy::too_many_arguments)]
+    fn run_swarm_test<F>(
+        &self,
+        name: &str,
+        runtime: &Runtime,
+        rng: &mut StdRng,
+        mut swarm: Box<dyn Swarm>,
+        report: &mut TestReport,
+        timeout: TestTimeout,
+        test: F,
+    ) -> Result<(TestResult, Duration, Box<dyn Swarm>)>
+    where
+        F: FnOnce(&mut dyn Swarm, &mut TestReport) -> Result<()> + Send + 'static,
+    {
+        let abandoner = timeout.timeout.map(|_| swarm.abandoner());
+        let (result, duration, state) = timeout.run_blocking(
+            name,
+            (swarm, TestReport::new()),
+            move |(swarm, test_report): &mut (Box<dyn Swarm>, TestReport)| {
+                test(&mut **swarm, test_report)
+            },
+        );
+        match state {
+            Some((swarm, test_report)) => {
+                report.merge(test_report);
+                Ok((result, duration, swarm))
+            }
+            None => {
+                println!("Giving up on the swarm {} holds on to", name);
+                let abandoner = abandoner.expect("Only tests with a timeout are left behind");
+                match abandoner(report) {
+                    Ok(location) => println!("Swarm logs can be found here: {}", location),
+                    Err(e) => println!("Failed to collect failure artifacts: {:?}", e),
+                }
+                println!("Starting a new Swarm for the next tests");
+                Ok((result, duration, self.launch_swarm(runtime, rng)?))
+            }
         }
     }
 
+    fn network_test(
+        &self,
+        test: &'static dyn NetworkTest,
+        core: CoreContext,
+    ) -> impl FnOnce(&mut dyn Swarm, &mut TestReport) -> Result<()> + Send + 'static {
+        let global_job_request = self.global_job_request.clone();
+        let emit_to_fullnodes = self.tests.emit_to_fullnodes;
+        move |swarm, report| {
+            let mut network_ctx = NetworkContext::new(core, swarm, report, global_job_request)
+                .with_emit_to_fullnodes(emit_to_fullnodes);
+            test.run(&mut network_ctx)
+        }
+    }
+
+    /// Runs the Aptos and Admin tests which aren't exclusive on `test_threads` threads, each with
+    /// its own root account so they don't race on the sequence number of the actual one, while
+    /// the Network tests which aren't exclusive run one after the other on this thread. The
+    /// tests running alongside others get their own report, merged into `report` once they pass
+    /// or fail. Returns the swarm the tests after these run against, a new one if a Network test
+    /// ran out of time.
+    fn run_parallel_tests(
+        &self,
+        runtime: &Runtime,
+        rng: &mut StdRng,
+        mut swarm: Box<dyn Swarm>,
+        report: &mut TestReport,
+        summary: &mut TestSummary,
+        timeout: TestTimeout,
+    ) -> Result<Box<dyn Swarm>> {
+        let aptos_tests: Vec<_> = self
+            .filter_tests(self.tests.aptos_tests.iter())
+            .filter(|test| !test.exclusive())
+            .collect();
+        let admin_tests: Vec<_> = self
+            .filter_tests(self.tests.admin_tests.iter())
+            .filter(|test| !test.exclusive())
+            .collect();
+        let network_tests: Vec<_> = self
+            .filter_tests(self.tests.network_tests.iter())
+            .filter(|test| !test.exclusive())
+            .collect();
+
+        let (rest_api_url, chain_id) = {
+            let chain_info = swarm.chain_info();
+            (chain_info.rest_api_url.clone(), chain_info.chain_id)
+        };
+        let mut root_accounts = vec![];
+        if !aptos_tests.is_empty() || !admin_tests.is_empty() {
+            let mut public_info = swarm.chain_info().into_aptos_public_info();
+            for _ in 0..aptos_tests.len() + admin_tests.len() {
+                root_accounts.push(runtime.block_on(
+                    public_info.create_delegated_root_account(rng, DELEGATED_ROOT_ACCOUNT_COINS),
+                )?);
+            }
+        }
+        let mut root_accounts = root_accounts.into_iter();
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(self.options.test_threads.get())
+            .build()?;
+        let (outcome_tx, outcome_rx) = mpsc::channel();
+        pool.in_place_scope(|scope| {
+            for test in aptos_tests {
+                let mut root_account = root_accounts.next().expect("A root account per test");
+                let core = CoreContext::from_rng(&mut *rng);
+                let rest_api_url = rest_api_url.clone();
+                let outcome_tx = outcome_tx.clone();
+                scope.spawn(move |_| {
+                    let mut test_report = TestReport::new();
+                    let (result, duration) = {
+                        let chain_info = ChainInfo::new(&mut root_account, rest_api_url, chain_id);
+                        let mut aptos_ctx = AptosContext::new(
+                            core,
+                            chain_info.into_aptos_public_info(),
+                            &mut test_report,
+                        );
+                        timeout.run_async(test.name(), runtime, test.run(&mut aptos_ctx))
+                    };
+                    let _ = outcome_tx.send((test.name(), result, duration, test_report));
+                });
+            }
+            for test in admin_tests {
+                let test: &'static dyn AdminTest = *test;
+                let root_account = root_accounts.next().expect("A root account per test");
+                let core = CoreContext::from_rng(&mut *rng);
+                let rest_api_url = rest_api_url.clone();
+                let outcome_tx = outcome_tx.clone();
+                scope.spawn(move |_| {
+                    // The test only reaches the swarm through its own root account, so it's only
+                    // left behind if it runs out of time
+                    let (result, duration, state) = timeout.run_blocking(
+                        test.name(),
+                        (root_account, TestReport::new()),
+                        move |(root_account, test_report): &mut (LocalAccount, TestReport)| {
+                            let chain_info = ChainInfo::new(root_account, rest_api_url, chain_id);
+                            test.run(&mut AdminContext::new(core, chain_info, test_report))
+                        },
+                    );
+                    let test_report = state.map_or_else(TestReport::new, |(_, report)| report);
+                    let _ = outcome_tx.send((test.name(), result, duration, test_report));
+                });
+            }
+            // Only the tests still running hold a sender, so the outcomes end with the last one
+            drop(outcome_tx);
+
+            for test in network_tests {
+                let core = CoreContext::from_rng(&mut *rng);
+                let (result, duration, returned_swarm) = self.run_swarm_test(
+                    test.name(),
+                    runtime,
+                    &mut *rng,
+                    swarm,
+                    &mut *report,
+                    timeout,
+                    self.network_test(*test, core),
+                )?;
+                swarm = returned_swarm;
+                let result = check_for_forks(runtime, &*swarm, result);
+                summary.handle_result(test.name().to_owned(), result, duration)?;
+
+                for (name, result, duration, test_report) in outcome_rx.try_iter() {
+                    summary.handle_result(name.to_owned(), result, duration)?;
+                    report.merge(test_report);
+                }
+            }
+            for (name, result, duration, test_report) in outcome_rx.iter() {
+                summary.handle_result(name.to_owned(), result, duration)?;
+                report.merge(test_report);
+            }
+            Ok(swarm)
+        })
+    }
+
     fn filter_tests<'a, T: Test, I: Iterator<Item = T> + 'a>(
         &'a self,
         tests: I,
     ) -> impl Iterator<Item = T> + 'a {
+        filter_tests(&self.selected_tests, tests)
+    }
+}
+
+/// Adds the node pools of the swarm, `start` of the run being when it started, to the samples the
+/// cost of the run is estimated from. The nodes can change during the run, e.g. with tests adding
+/// validators or the cluster autoscaling, so they're sampled after each test.
+fn sample_node_pools(
+    runtime: &Runtime,
+    swarm: &dyn Swarm,
+    start: Instant,
+    samples: &mut Vec<(Duration, Vec<NodePoolUsage>)>,
+) {
+    match runtime.block_on(swarm.node_pools()) {
+        Ok(node_pools) => samples.push((start.elapsed(), node_pools)),
+        Err(e) => println!("Failed to list the node pools of the swarm: {:?}", e),
+    }
+}
+
+/// Names of the tests to run out of `tests`: those the filters of `options` keep, dealt out in the
+/// order of their names between the shards when the run is sharded
+fn select_tests<'a>(
+    options: &Options,
+    tests: impl Iterator<Item = &'a dyn Test>,
+) -> HashSet<&'static str> {
+    let mut names = tests
+        .filter(|test| options.matches(*test))
+        .map(|test| test.name())
+        .collect::<Vec<_>>();
+    names.sort_unstable();
+    names.dedup();
+    names
+        .into_iter()
+        .enumerate()
+        .filter(|(position, _)| {
+            options
+                .shard
+                .map_or(true, |shard| shard.contains(*position))
+        })
+        .map(|(_, name)| name)
+        .collect()
+}
+
+fn filter_tests<'a, T: Test, I: Iterator<Item = T> + 'a>(
+    selected_tests: &'a HashSet<&'static str>,
+    tests: I,
+) -> impl Iterator<Item = T> + 'a {
+    tests.filter(move |test| selected_tests.contains(test.name()))
+}
+
+/// Runs the Aptos and Admin tests of the config against an already running network, reached at
+/// `rest_api_url` and administered with `root_account`, rather than against a swarm launched by
+/// a factory. The Network tests of the config need control over the nodes and are left out, as
+/// are the settings of the config which only apply to launching a swarm.
+pub fn run_remote_tests(
+    options: &Options,
+    tests: &ForgeConfig<'_>,
+    rest_api_url: String,
+    chain_id: ChainId,
+    root_account: &mut LocalAccount,
+) -> Result<TestReport> {
+    let total = tests.aptos_tests.len() + tests.admin_tests.len();
+    let selected_tests = select_tests(
+        options,
         tests
-            // Filter by ignored
-            .filter(
-                move |test| match (self.options.include_ignored, self.options.ignored) {
-                    (true, _) => true, // Don't filter anything
-                    (false, true) => test.ignored(),
-                    (false, false) => !test.ignored(),
-                },
-            )
-            // Filter by test name
-            .filter(move |test| {
-                if let Some(filter) = &self.options.filter {
-                    if self.options.filter_exact {
-                        test.name() == &filter[..]
-                    } else {
-                        test.name().contains(&filter[..])
-                    }
-                } else {
-                    true
-                }
-            })
+            .aptos_tests
+            .iter()
+            .map(|t| t as &dyn Test)
+            .chain(tests.admin_tests.iter().map(|t| t as &dyn Test)),
+    );
+    let test_count = filter_tests(&selected_tests, tests.aptos_tests.iter()).count()
+        + filter_tests(&selected_tests, tests.admin_tests.iter()).count();
+
+    let seed = tests.seed.unwrap_or_else(|| OsRng.gen());
+    let start = Instant::now();
+    let mut report = TestReport::new();
+    let mut summary = TestSummary::new(total, total - test_count);
+    summary.write_starting_msg()?;
+    let timeout = TestTimeout::new(options);
+    options.start_api_recording()?;
+    if let Some(shard) = &options.shard {
+        println!("Running {} tests in shard {}", test_count, shard);
+    }
+    println!("Running against {} with seed {}", rest_api_url, seed);
+    let runtime = Runtime::new()?;
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for test in filter_tests(&selected_tests, tests.aptos_tests.iter()) {
+        let mut aptos_ctx = AptosContext::new(
+            CoreContext::from_rng(&mut rng),
+            AptosPublicInfo::new(chain_id, rest_api_url.clone(), &mut *root_account),
+            &mut report,
+        );
+        let (result, duration) = timeout.run_async(test.name(), &runtime, test.run(&mut aptos_ctx));
+        summary.handle_result(test.name().to_owned(), result, duration)?;
+    }
+
+    for test in filter_tests(&selected_tests, tests.admin_tests.iter()) {
+        let test: &'static dyn AdminTest = *test;
+        let core = CoreContext::from_rng(&mut rng);
+        let chain_url = rest_api_url.clone();
+        let (result, duration, state) = timeout.run_blocking(
+            test.name(),
+            (copy_account(root_account)?, TestReport::new()),
+            move |(account, test_report): &mut (LocalAccount, TestReport)| {
+                let chain_info = ChainInfo::new(account, chain_url, chain_id);
+                test.run(&mut AdminContext::new(core, chain_info, test_report))
+            },
+        );
+        match state {
+            Some((account, test_report)) => {
+                *root_account.sequence_number_mut() = account.sequence_number();
+                report.merge(test_report);
+            }
+            // The test left behind may still submit transactions of the root account, the next
+            // ones start from the sequence number on chain
+            None => {
+                let address = root_account.address();
+                let rest_client =
+                    ChainInfo::new(&mut *root_account, rest_api_url.clone(), chain_id)
+                        .rest_client();
+                *root_account.sequence_number_mut() = runtime
+                    .block_on(rest_client.get_account(address))?
+                    .into_inner()
+                    .sequence_number;
+            }
+        }
+        summary.handle_result(test.name().to_owned(), result, duration)?;
+    }
+
+    options.report_shard(&mut report, &summary.outcomes);
+    report.print_report();
+    summary.write_summary()?;
+
+    if options.report_format == ReportFormat::Json {
+        JsonReport {
+            success: summary.success(),
+            seed,
+            shard: options.shard.map(|shard| shard.to_string()),
+            duration_secs: start.elapsed().as_secs_f64(),
+            tests: &summary.outcomes,
+            metrics: report.metrics(),
+            nodes: &[],
+            cost: None,
+            text: report.text(),
+        }
+        .write(&options.report_file)?;
+        println!("Json report written to {:?}", options.report_file);
+    }
+    if let Some(path) = &options.record_api {
+        println!("REST API traffic recorded to {:?}", path);
+    }
+
+    if summary.success() {
+        Ok(report)
+    } else {
+        Err(anyhow::anyhow!("Tests Failed with seed {}", seed))
     }
 }
 
+/// A copy of `account` for a test to own
+fn copy_account(account: &LocalAccount) -> Result<LocalAccount> {
+    // The key isn't Clone, so the copy gets one made from its bytes
+    let key = Ed25519PrivateKey::try_from(account.private_key().to_bytes().as_ref())?;
+    Ok(LocalAccount::new(
+        account.address(),
+        AccountKey::from_private_key(key),
+        account.sequence_number(),
+    ))
+}
+
 enum TestResult {
     Ok,
     Failed,
     FailedWithMsg(String),
+    TimedOut(Duration),
 }
 
-fn run_test<F: FnOnce() -> Result<()>>(f: F) -> TestResult {
-    match ::std::panic::catch_unwind(::std::panic::AssertUnwindSafe(f)) {
+fn run_test<F: FnOnce() -> Result<()>>(f: F) -> (TestResult, Duration) {
+    let start = Instant::now();
+    let result = match ::std::panic::catch_unwind(::std::panic::AssertUnwindSafe(f)) {
         Ok(Ok(())) => TestResult::Ok,
         Ok(Err(e)) => TestResult::FailedWithMsg(format!("{:?}", e)),
         Err(_) => TestResult::Failed,
+    };
+    (result, start.elapsed())
+}
+
+/// Enforces `Options::test_timeout_secs` on the tests of a run
+#[derive(Clone, Copy, Debug)]
+struct TestTimeout {
+    timeout: Option<Duration>,
+}
+
+impl TestTimeout {
+    fn new(options: &Options) -> Self {
+        Self {
+            timeout: options.test_timeout_secs.map(Duration::from_secs),
+        }
+    }
+
+    /// Runs an async test, which is dropped if it runs out of time
+    fn run_async<Fut: Future<Output = Result<()>>>(
+        &self,
+        name: &str,
+        runtime: &Runtime,
+        test: Fut,
+    ) -> (TestResult, Duration) {
+        let _recording = api_recording::record_test(name);
+        let timeout = match self.timeout {
+            Some(timeout) => timeout,
+            None => return run_test(|| runtime.block_on(test)),
+        };
+        let mut timed_out = false;
+        let (result, duration) = run_test(|| {
+            runtime.block_on(async {
+                match tokio::time::timeout(timeout, test).await {
+                    Ok(result) => result,
+                    Err(_) => {
+                        timed_out = true;
+                        Ok(())
+                    }
+                }
+            })
+        });
+        if timed_out {
+            (TestResult::TimedOut(timeout), duration)
+        } else {
+            (result, duration)
+        }
+    }
+
+    /// Runs a synchronous test on `state`, handed back once the test is done. The test can't be
+    /// interrupted, so with a timeout it runs on a thread of its own, owning the state: if it
+    /// runs out of time, it's left behind with the state, which is never handed back.
+    fn run_blocking<S, F>(
+        &self,
+        name: &str,
+        mut state: S,
+        test: F,
+    ) -> (TestResult, Duration, Option<S>)
+    where
+        S: Send + 'static,
+        F: FnOnce(&mut S) -> Result<()> + Send + 'static,
+    {
+        let _recording = api_recording::record_test(name);
+        let timeout = match self.timeout {
+            Some(timeout) => timeout,
+            None => {
+                let (result, duration) = run_test(|| test(&mut state));
+                return (result, duration, Some(state));
+            }
+        };
+        let start = Instant::now();
+        let (done_tx, done_rx) = mpsc::channel();
+        thread::spawn(move || {
+            let (result, duration) = run_test(|| test(&mut state));
+            // A test done after running out of time leaks its state rather than drop it, so that
+            // whatever was collected of it, like the logs of a swarm, isn't cleaned up
+            if let Err(mpsc::SendError(done)) = done_tx.send((result, duration, state)) {
+                mem::forget(done);
+            }
+        });
+        match done_rx.recv_timeout(timeout) {
+            Ok((result, duration, state)) => (result, duration, Some(state)),
+            Err(RecvTimeoutError::Timeout) => (TestResult::TimedOut(timeout), timeout, None),
+            // The thread only ends without sending if it panics past run_test, which catches
+            // the panics of the test
+            Err(RecvTimeoutError::Disconnected) => (TestResult::Failed, start.elapsed(), None),
+        }
     }
 }
 
+fn timed_out_error(timeout: Duration) -> String {
+    format!("timed out after {}s", timeout.as_secs())
+}
+
+/// Fails a Network test if the nodes forked during it, as tests focused on liveness wouldn't
+/// notice it
+fn check_for_forks(runtime: &Runtime, swarm: &dyn Swarm, result: TestResult) -> TestResult {
+    // The swarm of a test which ran out of time was given up on for a new one
+    if let TestResult::TimedOut(_) = result {
+        return result;
+    }
+    match runtime.block_on(swarm.sampled_fork_check(FORK_CHECK_SAMPLES)) {
+        Ok(()) => result,
+        Err(e) => match result {
+            TestResult::FailedWithMsg(msg) => {
+                TestResult::FailedWithMsg(format!("{}\n{:?}", msg, e))
+            }
+            _ => TestResult::FailedWithMsg(format!("{:?}", e)),
+        },
+    }
+}
+
+fn node_versions(swarm: &dyn Swarm) -> Vec<NodeVersion> {
+    let validators = swarm.validators().map(|node| NodeVersion {
+        peer_id: node.peer_id(),
+        validator: true,
+        version: node.version().to_string(),
+    });
+    let full_nodes = swarm.full_nodes().map(|node| NodeVersion {
+        peer_id: node.peer_id(),
+        validator: false,
+        version: node.version().to_string(),
+    });
+    validators.chain(full_nodes).collect()
+}
+
 struct TestSummary {
     stdout: StandardStream,
     total: usize,
     filtered_out: usize,
     passed: usize,
     failed: Vec<String>,
+    outcomes: Vec<TestOutcome>,
 }
 
 impl TestSummary {
@@ -383,10 +1316,28 @@ impl TestSummary {
             filtered_out,
             passed: 0,
             failed: Vec::new(),
+            outcomes: Vec::new(),
         }
     }
 
-    fn handle_result(&mut self, name: String, result: TestResult) -> io::Result<()> {
+    fn handle_result(
+        &mut self,
+        name: String,
+        result: TestResult,
+        duration: Duration,
+    ) -> io::Result<()> {
+        self.outcomes.push(TestOutcome {
+            name: name.clone(),
+            passed: matches!(result, TestResult::Ok),
+            error: match &result {
+                TestResult::Ok => None,
+                TestResult::Failed => Some("panicked".to_string()),
+                TestResult::FailedWithMsg(msg) => Some(msg.clone()),
+                TestResult::TimedOut(timeout) => Some(timed_out_error(*timeout)),
+            },
+            duration_secs: duration.as_secs_f64(),
+            timed_out: matches!(result, TestResult::TimedOut(_)),
+        });
         write!(self.stdout, "test {} ... ", name)?;
         match result {
             TestResult::Ok => {
@@ -404,6 +1355,13 @@ impl TestSummary {
 
                 write!(self.stdout, "Error: {}", msg)?;
             }
+            TestResult::TimedOut(timeout) => {
+                self.failed.push(name);
+                self.write_failed()?;
+                writeln!(self.stdout)?;
+
+                write!(self.stdout, "Error: {}", timed_out_error(timeout))?;
+            }
         }
         writeln!(self.stdout)?;
         Ok(())
@@ -467,3 +1425,213 @@ impl TestSummary {
         self.failed.is_empty()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timeout_millis(millis: u64) -> TestTimeout {
+        TestTimeout {
+            timeout: Some(Duration::from_millis(millis)),
+        }
+    }
+
+    #[test]
+    fn test_blocking_test_timed_out() {
+        let start = Instant::now();
+        let (result, duration, state) = timeout_millis(100).run_blocking("sleeping", 0u64, |_| {
+            thread::sleep(Duration::from_secs(5));
+            Ok(())
+        });
+        assert!(
+            matches!(result, TestResult::TimedOut(timeout) if timeout == Duration::from_millis(100))
+        );
+        assert_eq!(duration, Duration::from_millis(100));
+        assert!(state.is_none());
+        // The test is left behind rather than waited for
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_blocking_test_in_time() {
+        let (result, _, state) = timeout_millis(5_000).run_blocking("fast", 1u64, |state| {
+            *state += 1;
+            Ok(())
+        });
+        assert!(matches!(result, TestResult::Ok));
+        assert_eq!(state, Some(2));
+
+        let (result, _, state) = timeout_millis(5_000).run_blocking("failing", 1u64, |_| {
+            bail!("failed");
+        });
+        assert!(matches!(result, TestResult::FailedWithMsg(msg) if msg.contains("failed")));
+        assert_eq!(state, Some(1));
+
+        let (result, _, state) =
+            timeout_millis(5_000).run_blocking("panicking", 1u64, |_| panic!("panicked"));
+        assert!(matches!(result, TestResult::Failed));
+        assert_eq!(state, Some(1));
+    }
+
+    #[test]
+    fn test_blocking_test_without_timeout() {
+        let caller = thread::current().id();
+        let (result, _, state) =
+            TestTimeout { timeout: None }.run_blocking("unlimited", None, |state| {
+                *state = Some(thread::current().id());
+                Ok(())
+            });
+        assert!(matches!(result, TestResult::Ok));
+        // Without a timeout, the test runs on the thread of the run
+        assert_eq!(state, Some(Some(caller)));
+    }
+
+    struct NamedTest(&'static str);
+
+    impl Test for NamedTest {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+    }
+
+    const TEST_NAMES: [&str; 7] = [
+        "api::basic-client",
+        "api::get-index",
+        "api::golden-shapes",
+        "consensus::twins",
+        "network::partition",
+        "state-sync::fast-sync",
+        "state-sync::fullnode",
+    ];
+
+    fn selected(args: &[&str]) -> HashSet<&'static str> {
+        let options = Options::from_iter(std::iter::once("forge").chain(args.iter().copied()));
+        let tests = TEST_NAMES
+            .iter()
+            .copied()
+            .map(NamedTest)
+            .collect::<Vec<_>>();
+        select_tests(&options, tests.iter().map(|test| test as &dyn Test))
+    }
+
+    #[test]
+    fn test_parse_shard() {
+        assert_eq!("1/2".parse::<Shard>().unwrap(), Shard::new(1, 2).unwrap());
+        assert_eq!(
+            " 2 / 2 ".parse::<Shard>().unwrap(),
+            Shard::new(2, 2).unwrap()
+        );
+        assert_eq!(Shard::new(3, 4).unwrap().to_string(), "3/4");
+        for invalid in ["0/2", "3/2", "1/0", "1", "a/2", "1/2/3"] {
+            assert!(invalid.parse::<Shard>().is_err(), "{} was parsed", invalid);
+        }
+    }
+
+    #[test]
+    fn test_include_exclude() {
+        assert_eq!(selected(&[]).len(), TEST_NAMES.len());
+        assert_eq!(
+            selected(&["--include", "^api::", "--include", "twins"]),
+            [
+                "api::basic-client",
+                "api::get-index",
+                "api::golden-shapes",
+                "consensus::twins"
+            ]
+            .iter()
+            .copied()
+            .collect::<HashSet<_>>()
+        );
+        // A test both included and excluded is excluded
+        assert_eq!(
+            selected(&["--include", "^api::", "--exclude", "golden"]),
+            ["api::basic-client", "api::get-index"]
+                .iter()
+                .copied()
+                .collect::<HashSet<_>>()
+        );
+        assert!(selected(&["--include", "^api::", "--exclude", "api"]).is_empty());
+        assert_eq!(
+            selected(&["state-sync", "--exclude", "fast"]),
+            ["state-sync::fullnode"]
+                .iter()
+                .copied()
+                .collect::<HashSet<_>>()
+        );
+    }
+
+    #[test]
+    fn test_shards_split_the_filtered_tests() {
+        let filters = ["--exclude", "network"];
+        let filtered = selected(&filters);
+        assert_eq!(filtered.len(), TEST_NAMES.len() - 1);
+        for count in 1..=filtered.len() + 1 {
+            let mut union = HashSet::new();
+            for index in 1..=count {
+                let shard = format!("{}/{}", index, count);
+                let tests = selected(&[filters[0], filters[1], "--shard", &shard]);
+                assert!(tests.is_subset(&filtered));
+                // Shards are balanced, and no test runs in two of them
+                assert!(tests.len() <= (filtered.len() + count - 1) / count);
+                for test in tests {
+                    assert!(union.insert(test), "{} is in two shards of {}", test, count);
+                }
+            }
+            assert_eq!(union, filtered);
+        }
+    }
+
+    #[test]
+    fn test_async_test_timed_out() {
+        let runtime = Runtime::new().unwrap();
+        let (result, _) = timeout_millis(100).run_async("sleeping", &runtime, async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(())
+        });
+        assert!(matches!(result, TestResult::TimedOut(_)));
+
+        let (result, _) = timeout_millis(5_000).run_async("fast", &runtime, async { Ok(()) });
+        assert!(matches!(result, TestResult::Ok));
+    }
+
+    struct SharingTest(bool);
+
+    impl Test for SharingTest {
+        fn name(&self) -> &'static str {
+            if self.0 {
+                "exclusive"
+            } else {
+                "shared"
+            }
+        }
+
+        fn exclusive(&self) -> bool {
+            self.0
+        }
+    }
+
+    impl NetworkTest for SharingTest {
+        fn run<'t>(&self, _ctx: &mut NetworkContext<'t>) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_check_test_env_vars() {
+        let network_tests: [&'static dyn NetworkTest; 2] =
+            [&SharingTest(true), &SharingTest(false)];
+        let config = || ForgeConfig::new().with_network_tests(&network_tests);
+
+        let config_exclusive = config().with_test_env_var("exclusive", "RUST_LOG", "debug");
+        config_exclusive.check_test_env_vars(false).unwrap();
+        config_exclusive.check_test_env_vars(true).unwrap();
+
+        // the nodes can't restart under the tests running alongside
+        let config_shared = config().with_test_env_var("shared", "RUST_LOG", "debug");
+        config_shared.check_test_env_vars(false).unwrap();
+        assert!(config_shared.check_test_env_vars(true).is_err());
+
+        let config_unknown = config().with_test_env_var("unknown", "RUST_LOG", "debug");
+        assert!(config_unknown.check_test_env_vars(false).is_err());
+    }
+}
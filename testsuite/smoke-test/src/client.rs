@@ -7,6 +7,8 @@ use crate::{
         assert_balance, check_create_mint_transfer, create_and_fund_account, transfer_coins,
     },
 };
+use aptos_rest_client::error::Error as RestClientError;
+use aptos_sdk::{transaction_builder::TransactionFactory, types::chain_id::ChainId};
 use aptos_transaction_builder::aptos_stdlib;
 use forge::{NodeExt, Swarm};
 use std::time::{Duration, Instant};
@@ -106,3 +108,35 @@ async fn test_concurrent_transfers_single_node() {
     // assert_balance(&client, &account_0, 79).await;
     assert_balance(&client, &account_1, 31).await;
 }
+
+#[tokio::test]
+async fn test_client_rejects_other_chain() {
+    let mut swarm = new_local_swarm_with_aptos(1).await;
+    let client = swarm.validators().next().unwrap().rest_client();
+    let chain_id = swarm.chain_info().chain_id();
+    assert_eq!(client.get_chain_id().await.unwrap(), chain_id);
+
+    let other_chain_id = ChainId::new(chain_id.id().wrapping_add(1));
+    let error = client
+        .clone()
+        .with_chain_id(other_chain_id)
+        .get_ledger_information()
+        .await
+        .unwrap_err();
+    assert!(error
+        .downcast_ref::<RestClientError>()
+        .unwrap()
+        .is_chain_id_mismatch());
+
+    // A transaction signed for another chain isn't submitted
+    let mut account = create_and_fund_account(&mut swarm, 10).await;
+    let txn =
+        account.sign_with_transaction_builder(TransactionFactory::new(other_chain_id).payload(
+            aptos_stdlib::encode_test_coin_transfer(account.address(), 1),
+        ));
+    let error = client.submit(&txn).await.unwrap_err();
+    assert!(error
+        .downcast_ref::<RestClientError>()
+        .unwrap()
+        .is_chain_id_mismatch());
+}
@@ -1,12 +1,19 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::operational_tooling::launch_swarm_with_op_tool_and_backend;
+use crate::{
+    operational_tooling::launch_swarm_with_op_tool_and_backend,
+    smoke_test_environment::new_local_swarm_with_genesis_configuration,
+};
 use aptos_config::config::SecureBackend;
 use aptos_secure_storage::{KVStorage, Storage};
 use aptos_types::network_address::NetworkAddress;
-use forge::NodeExt;
-use std::{convert::TryInto, str::FromStr};
+use forge::{GenesisConfiguration, NodeExt};
+use std::{
+    convert::TryInto,
+    str::FromStr,
+    time::{Duration, Instant},
+};
 
 #[ignore]
 #[tokio::test]
@@ -60,6 +67,45 @@ async fn test_consensus_observer_mode_storage_error() {
     assert_eq!(sequence_number_0, sequence_number_1);
 }
 
+#[tokio::test]
+async fn test_genesis_epoch_duration() {
+    // Epochs end on their own after 5s, without any reconfiguration transaction
+    let swarm = new_local_swarm_with_genesis_configuration(
+        1,
+        GenesisConfiguration {
+            epoch_duration_secs: 5,
+            ..GenesisConfiguration::default()
+        },
+    )
+    .await;
+    let client = swarm.validators().next().unwrap().rest_client();
+    let epoch = client
+        .get_ledger_information()
+        .await
+        .unwrap()
+        .into_inner()
+        .epoch;
+
+    let deadline = Instant::now() + Duration::from_secs(60);
+    loop {
+        let new_epoch = client
+            .get_ledger_information()
+            .await
+            .unwrap()
+            .into_inner()
+            .epoch;
+        if new_epoch > epoch {
+            break;
+        }
+        assert!(
+            Instant::now() < deadline,
+            "Epoch {} didn't end within 60s",
+            epoch
+        );
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
 // TODO(https://github.com/aptos-labs/aptos-core/issues/317): add back after support update consensus config in aptos-framework
 // #[allow(dead_code)]
 // async fn test_onchain_upgrade(new_onfig: OnChainConsensusConfig) {
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    smoke_test_environment::new_local_swarm_with_aptos,
+    smoke_test_environment::{new_local_swarm_with_aptos, new_local_swarm_with_fullnodes},
     test_utils::{
         assert_balance, create_and_fund_account, transfer_coins, transfer_coins_non_blocking,
     },
@@ -121,6 +121,41 @@ async fn test_full_node_basic_flow() {
     assert_balance(&pfn_client, &account_1, 13).await;
 }
 
+#[tokio::test]
+async fn test_launch_swarm_with_fullnodes() {
+    let mut swarm = new_local_swarm_with_fullnodes(2, 2, 1).await;
+    assert_eq!(swarm.full_nodes().count(), 3);
+
+    let transaction_factory = swarm.chain_info().transaction_factory();
+    let mut account_0 = create_and_fund_account(&mut swarm, 10).await;
+    let account_1 = create_and_fund_account(&mut swarm, 10).await;
+
+    // Every fullnode is synced, whether it's attached to a validator or not
+    let pfn_peer_id = swarm
+        .full_nodes()
+        .find(|node| swarm.validator(node.peer_id()).is_none())
+        .unwrap()
+        .peer_id();
+    let pfn_client = swarm.full_node(pfn_peer_id).unwrap().rest_client();
+    transfer_coins(
+        &pfn_client,
+        &transaction_factory,
+        &mut account_0,
+        &account_1,
+        1,
+    )
+    .await;
+    swarm
+        .wait_for_all_nodes_to_catchup(Instant::now() + Duration::from_secs(10))
+        .await
+        .unwrap();
+    for fullnode in swarm.full_nodes() {
+        let client = fullnode.rest_client();
+        assert_balance(&client, &account_0, 9).await;
+        assert_balance(&client, &account_1, 11).await;
+    }
+}
+
 #[tokio::test]
 async fn test_vfn_failover() {
     let mut swarm = new_local_swarm_with_aptos(4).await;
@@ -1,7 +1,10 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::smoke_test_environment::new_local_swarm_with_aptos;
+use crate::{
+    smoke_test_environment::{new_local_dual_stack_swarm, new_local_swarm_with_aptos},
+    test_utils::check_create_mint_transfer,
+};
 use aptos::op::key::GenerateKey;
 use aptos_config::{
     config::{DiscoveryMethod, Identity, NetworkConfig, NodeConfig, PeerSet, PersistableConfig},
@@ -11,7 +14,7 @@ use aptos_crypto::{x25519, x25519::PrivateKey};
 use aptos_operational_tool::{keys::EncodingType, test_helper::OperationalTool};
 use aptos_temppath::TempPath;
 use aptos_types::network_address::{NetworkAddress, Protocol};
-use forge::{FullNode, LocalNode, NodeExt, Swarm};
+use forge::{FullNode, LocalNode, NodeExt, Swarm, SwarmExt};
 use std::{
     collections::HashMap,
     path::Path,
@@ -19,6 +22,36 @@ use std::{
     time::{Duration, Instant},
 };
 
+#[tokio::test]
+async fn test_dual_stack_validators() {
+    let mut swarm = new_local_dual_stack_swarm(4, 2).await;
+    let num_ipv6_validators = swarm
+        .validators()
+        .filter(|validator| {
+            let listen_address = &validator
+                .config()
+                .validator_network
+                .as_ref()
+                .unwrap()
+                .listen_address;
+            matches!(listen_address.as_slice().first(), Some(Protocol::Ip6(_)))
+        })
+        .count();
+    assert_eq!(num_ipv6_validators, 2);
+
+    // Every validator connects to all others, regardless of the IP version they listen on
+    swarm
+        .wait_for_connectivity(Instant::now() + Duration::from_secs(60))
+        .await
+        .unwrap();
+
+    check_create_mint_transfer(&mut swarm).await;
+    swarm
+        .wait_for_all_nodes_to_catchup(Instant::now() + Duration::from_secs(60))
+        .await
+        .unwrap();
+}
+
 #[tokio::test]
 async fn test_connection_limiting() {
     let mut swarm = new_local_swarm_with_aptos(1).await;
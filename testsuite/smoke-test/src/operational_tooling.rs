@@ -431,6 +431,7 @@ async fn test_single_node_operations() {
     let (mut swarm, op_tool, backend, mut storage) = launch_swarm_with_op_tool_and_backend(1).await;
 
     test_account_resource(&swarm, &op_tool, &backend, &mut storage).await;
+    test_collect_diagnostics(&swarm, &op_tool).await;
     test_create_operator_bcs_file(&mut swarm, &op_tool, &backend, &mut storage).await;
     test_create_operator_hex_file(&mut swarm, &op_tool, &backend, &mut storage).await;
     test_create_validator_bcs_file(&mut swarm, &op_tool, &backend, &mut storage).await;
@@ -445,6 +446,51 @@ async fn test_single_node_operations() {
     test_verify_validator_state(&swarm, &op_tool, &backend, &mut storage).await;
 }
 
+async fn test_collect_diagnostics(swarm: &LocalSwarm, op_tool: &OperationalTool) {
+    let validator = swarm.validators().next().unwrap();
+    let output = TempPath::new();
+    let bundle = op_tool
+        .collect_diagnostics(
+            &validator.config_path(),
+            Some(&validator.log_path()),
+            output.path(),
+        )
+        .await
+        .unwrap();
+    assert!(bundle.errors.is_empty(), "{:?}", bundle.errors);
+    for file in [
+        "node.yaml",
+        "git_revision.txt",
+        "metrics.json",
+        "storage.json",
+        "sync_status.json",
+        "logs.txt",
+    ] {
+        assert!(
+            bundle.files.iter().any(|f| f == file),
+            "{} is missing",
+            file
+        );
+    }
+
+    // The config in the bundle holds no private key
+    let mut archive = tar::Archive::new(std::fs::File::open(output.path()).unwrap());
+    let mut config = String::new();
+    for entry in archive.entries().unwrap() {
+        let mut entry = entry.unwrap();
+        if entry.path().unwrap().to_str() == Some("node.yaml") {
+            std::io::Read::read_to_string(&mut entry, &mut config).unwrap();
+        }
+    }
+    let config: serde_yaml::Value = serde_yaml::from_str(&config).unwrap();
+    let storage_key = &config["consensus"]["safety_rules"]["backend"]["token"];
+    assert!(storage_key.is_null() || storage_key.as_str() == Some("<redacted>"));
+    let identity = &config["validator_network"]["identity"];
+    if let Some(key) = identity.get("key") {
+        assert_eq!(key.as_str(), Some("<redacted>"));
+    }
+}
+
 async fn test_extract_private_key(
     swarm: &LocalSwarm,
     op_tool: &OperationalTool,
@@ -1,9 +1,26 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+use anyhow::{bail, ensure};
+use aptos_rest_client::aptos_api_types::mime_types::BCS_SIGNED_TRANSACTION;
 use aptos_transaction_builder::aptos_stdlib;
-use aptos_types::account_config::aptos_root_address;
+use aptos_types::{
+    account_config::aptos_root_address, transaction::authenticator::TransactionAuthenticator,
+};
 use forge::{AptosContext, AptosTest, Result, Test};
+use goldenfile::Mint;
+use reqwest::{header::CONTENT_TYPE, RequestBuilder, StatusCode};
+use serde_json::{json, Map, Value};
+use std::{
+    io::Write,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+/// Directory of the golden files of `ApiGoldenShapes`, relative to the crate
+const API_GOLDEN_DIR: &str = "goldens/api";
+/// How long the transactions submitted by `ApiGoldenShapes` get to commit
+const TXN_COMMIT_TIMEOUT: Duration = Duration::from_secs(30);
 
 pub struct GetIndex;
 
@@ -11,6 +28,10 @@ impl Test for GetIndex {
     fn name(&self) -> &'static str {
         "api::get-index"
     }
+
+    fn exclusive(&self) -> bool {
+        false
+    }
 }
 
 #[async_trait::async_trait]
@@ -29,6 +50,10 @@ impl Test for BasicClient {
     fn name(&self) -> &'static str {
         "api::basic-client"
     }
+
+    fn exclusive(&self) -> bool {
+        false
+    }
 }
 
 #[async_trait::async_trait]
@@ -62,3 +87,466 @@ impl AptosTest for BasicClient {
         Ok(())
     }
 }
+
+/// Calls the REST endpoints serving chain data and compares the shape of their JSON responses,
+/// that is the field names, the enum tags and the types of the values, with the golden files in
+/// `goldens/api`, so that serde changes in `aptos-api-types` which would break external clients,
+/// like renamed fields or enum tags, fail here first. The values themselves, which depend on the
+/// chain, aren't compared.
+///
+/// After an intended change, the golden files are updated by running the test with
+/// `UPDATE_GOLDENFILES=1`.
+pub struct ApiGoldenShapes;
+
+impl Test for ApiGoldenShapes {
+    fn name(&self) -> &'static str {
+        "api::golden-shapes"
+    }
+
+    fn exclusive(&self) -> bool {
+        false
+    }
+}
+
+#[async_trait::async_trait]
+impl AptosTest for ApiGoldenShapes {
+    async fn run<'t>(&self, ctx: &mut AptosContext<'t>) -> Result<()> {
+        let client = reqwest::Client::new();
+        let url = ctx.url().trim_end_matches('/').to_owned();
+        let root = aptos_root_address().to_hex_literal();
+        let mut responses = vec![];
+
+        let get = |path: String| client.get(format!("{}{}", url, path));
+        responses.push((
+            "ledger_info",
+            request_json(get("/".to_owned()), StatusCode::OK).await?,
+        ));
+        responses.push((
+            "account",
+            request_json(get(format!("/accounts/{}", root)), StatusCode::OK).await?,
+        ));
+        responses.push((
+            "account_resources",
+            request_json(get(format!("/accounts/{}/resources", root)), StatusCode::OK).await?,
+        ));
+        responses.push((
+            "account_resource",
+            request_json(
+                get(format!("/accounts/{}/resource/0x1::GUID::Generator", root)),
+                StatusCode::OK,
+            )
+            .await?,
+        ));
+        responses.push((
+            "account_modules",
+            request_json(get("/accounts/0x1/modules".to_owned()), StatusCode::OK).await?,
+        ));
+        responses.push((
+            "account_module",
+            request_json(get("/accounts/0x1/module/GUID".to_owned()), StatusCode::OK).await?,
+        ));
+        responses.push((
+            "genesis_transaction",
+            request_json(
+                get("/transactions?start=0&limit=1&full=true".to_owned()),
+                StatusCode::OK,
+            )
+            .await?,
+        ));
+        responses.push((
+            "genesis_transaction_summary",
+            request_json(
+                get("/transactions?start=0&limit=1".to_owned()),
+                StatusCode::OK,
+            )
+            .await?,
+        ));
+        let events = request_json(
+            get(format!(
+                "/accounts/{}/events/0x1::Reconfiguration::Configuration/events",
+                root
+            )),
+            StatusCode::OK,
+        )
+        .await?;
+        let event_key = match events[0]["key"].as_str() {
+            Some(key) => key.to_owned(),
+            None => bail!("The reconfiguration events have no key: {}", events),
+        };
+        responses.push(("events_by_handle", events));
+        responses.push((
+            "events_by_key",
+            request_json(get(format!("/events/{}", event_key)), StatusCode::OK).await?,
+        ));
+        responses.push((
+            "not_found_error",
+            request_json(get("/accounts/0xf".to_owned()), StatusCode::NOT_FOUND).await?,
+        ));
+
+        // Submits a transaction as JSON, the way clients without BCS do it, from the signing
+        // message to the committed transaction
+        let new_account = ctx.random_account();
+        let factory = ctx.transaction_factory();
+        let root_account = ctx.root_account();
+        let txn = root_account.sign_with_transaction_builder(factory.payload(
+            aptos_stdlib::encode_account_create_account(new_account.address()),
+        ));
+        let (public_key, signature) = match txn.authenticator() {
+            TransactionAuthenticator::Ed25519 {
+                public_key,
+                signature,
+            } => (public_key, signature),
+            _ => bail!("Expected an ed25519 signature"),
+        };
+        let mut request = json!({
+            "sender": txn.sender().to_hex_literal(),
+            "sequence_number": txn.sequence_number().to_string(),
+            "max_gas_amount": txn.max_gas_amount().to_string(),
+            "gas_unit_price": txn.gas_unit_price().to_string(),
+            "expiration_timestamp_secs": txn.expiration_timestamp_secs().to_string(),
+            "payload": {
+                "type": "script_function_payload",
+                "function": "0x1::Account::create_account",
+                "type_arguments": [],
+                "arguments": [new_account.address().to_hex_literal()],
+            },
+        });
+        let signing_message = request_json(
+            client
+                .post(format!("{}/transactions/signing_message", url))
+                .json(&request),
+            StatusCode::OK,
+        )
+        .await?;
+        let expected_message = format!(
+            "0x{}",
+            hex::encode(txn.clone().into_raw_transaction().signing_message())
+        );
+        ensure!(
+            signing_message["message"].as_str() == Some(expected_message.as_str()),
+            "The signing message {} differs from the one of the transaction, {}",
+            signing_message,
+            expected_message
+        );
+        responses.push(("signing_message", signing_message));
+
+        request["signature"] = json!({
+            "type": "ed25519_signature",
+            "public_key": format!("0x{}", hex::encode(public_key.to_bytes())),
+            "signature": format!("0x{}", hex::encode(signature.to_bytes())),
+        });
+        let pending = request_json(
+            client.post(format!("{}/transactions", url)).json(&request),
+            StatusCode::ACCEPTED,
+        )
+        .await?;
+        let hash = match pending["hash"].as_str() {
+            Some(hash) => hash.to_owned(),
+            None => bail!("The pending transaction has no hash: {}", pending),
+        };
+        responses.push(("pending_transaction", pending));
+
+        let committed = wait_for_commit(&client, &url, &hash).await?;
+        let version = match committed["version"].as_str() {
+            Some(version) => version.to_owned(),
+            None => bail!("The committed transaction has no version: {}", committed),
+        };
+        responses.push(("user_transaction", committed));
+        responses.push((
+            "transaction_by_version",
+            request_json(get(format!("/transactions/{}", version)), StatusCode::OK).await?,
+        ));
+        responses.push((
+            "account_transactions",
+            request_json(
+                get(format!(
+                    "/accounts/{}/transactions?start={}&limit=1",
+                    root,
+                    txn.sequence_number()
+                )),
+                StatusCode::OK,
+            )
+            .await?,
+        ));
+
+        // Submits a transaction as BCS, creating a token collection, which is kept in a table
+        let mut creator = ctx.create_and_fund_user_account(10_000_000).await?;
+        let collection_name = "golden shapes";
+        let txn = creator.sign_with_transaction_builder(ctx.transaction_factory().payload(
+            aptos_stdlib::encode_token_create_unlimited_collection_script(
+                collection_name.as_bytes().to_vec(),
+                b"description".to_vec(),
+                b"uri".to_vec(),
+            ),
+        ));
+        let pending = request_json(
+            client
+                .post(format!("{}/transactions", url))
+                .header(CONTENT_TYPE, BCS_SIGNED_TRANSACTION)
+                .body(bcs::to_bytes(&txn)?),
+            StatusCode::ACCEPTED,
+        )
+        .await?;
+        let hash = match pending["hash"].as_str() {
+            Some(hash) => hash.to_owned(),
+            None => bail!("The pending transaction has no hash: {}", pending),
+        };
+        responses.push(("pending_bcs_transaction", pending));
+        wait_for_commit(&client, &url, &hash).await?;
+
+        let collections = request_json(
+            get(format!(
+                "/accounts/{}/resource/0x1::Token::Collections",
+                creator.address().to_hex_literal()
+            )),
+            StatusCode::OK,
+        )
+        .await?;
+        let handle = match collections["data"]["collections"]["handle"].as_str() {
+            Some(handle) => handle.to_owned(),
+            None => bail!("The collections have no table handle: {}", collections),
+        };
+        responses.push((
+            "table_item",
+            request_json(
+                client
+                    .post(format!("{}/tables/{}/item", url, handle))
+                    .json(&json!({
+                        "key_type": "0x1::ASCII::String",
+                        "value_type": "0x1::Token::Collection",
+                        "key": collection_name,
+                    })),
+                StatusCode::OK,
+            )
+            .await?,
+        ));
+
+        responses.push((
+            "state_proof",
+            request_json(
+                get("/state_proof?known_version=0&with_accumulator=true".to_owned()),
+                StatusCode::OK,
+            )
+            .await?,
+        ));
+        // The genesis is the only block whose transactions don't depend on the load of the chain
+        responses.push((
+            "genesis_block",
+            request_json(
+                get("/blocks/0/transactions?with_events=true".to_owned()),
+                StatusCode::OK,
+            )
+            .await?,
+        ));
+        responses.push((
+            "head_account_resource",
+            request_headers(
+                client.head(format!(
+                    "{}/accounts/{}/resource/0x1::GUID::Generator",
+                    url, root
+                )),
+                StatusCode::OK,
+            )
+            .await?,
+        ));
+        responses.push((
+            "head_account_module",
+            request_headers(
+                client.head(format!("{}/accounts/0x1/module/GUID", url)),
+                StatusCode::OK,
+            )
+            .await?,
+        ));
+
+        check_golden_shapes(&responses)
+    }
+}
+
+async fn request_json(request: RequestBuilder, expected_status: StatusCode) -> Result<Value> {
+    let resp = request.send().await?;
+    let status = resp.status();
+    let url = resp.url().clone();
+    let body: Value = resp.json().await?;
+    ensure!(
+        status == expected_status,
+        "{} returned {} instead of {}: {}",
+        url,
+        status,
+        expected_status,
+        body
+    );
+    Ok(body)
+}
+
+/// Sends a HEAD request, and returns its `X-Aptos-*` headers as a JSON object, since those are the
+/// whole answer
+async fn request_headers(request: RequestBuilder, expected_status: StatusCode) -> Result<Value> {
+    let resp = request.send().await?;
+    let status = resp.status();
+    let url = resp.url().clone();
+    ensure!(
+        status == expected_status,
+        "{} returned {} instead of {}",
+        url,
+        status,
+        expected_status
+    );
+    let headers = resp
+        .headers()
+        .iter()
+        .filter(|(name, _)| name.as_str().starts_with("x-aptos-"))
+        .map(|(name, value)| -> Result<(String, Value)> {
+            Ok((name.as_str().to_owned(), json!(value.to_str()?)))
+        })
+        .collect::<Result<Map<String, Value>>>()?;
+    let body = resp.bytes().await?;
+    ensure!(body.is_empty(), "{} returned a body to a HEAD request", url);
+    Ok(Value::Object(headers))
+}
+
+/// Waits for the transaction to be committed, and returns it, failing unless it succeeded
+async fn wait_for_commit(client: &reqwest::Client, url: &str, hash: &str) -> Result<Value> {
+    let deadline = Instant::now() + TXN_COMMIT_TIMEOUT;
+    let committed = loop {
+        let resp = client
+            .get(format!("{}/transactions/{}", url, hash))
+            .send()
+            .await?;
+        if resp.status() == StatusCode::OK {
+            let txn: Value = resp.json().await?;
+            if txn["type"] != "pending_transaction" {
+                break txn;
+            }
+        }
+        if Instant::now() > deadline {
+            bail!("Transaction {} wasn't committed in time", hash);
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    };
+    ensure!(
+        committed["success"] == true,
+        "Transaction {} failed: {}",
+        hash,
+        committed["vm_status"]
+    );
+    Ok(committed)
+}
+
+/// Compares the shapes of the responses with their golden files, panicking with a diff on the
+/// first mismatch. This is kept out of the async test, since the golden files can't be held across
+/// an await point.
+fn check_golden_shapes(responses: &[(&str, Value)]) -> Result<()> {
+    let mut mint = Mint::new(Path::new(env!("CARGO_MANIFEST_DIR")).join(API_GOLDEN_DIR));
+    for (name, response) in responses {
+        let mut file = mint.new_goldenfile(format!("{}.json", name))?;
+        file.write_all((serde_json::to_string_pretty(&json_shape(response))? + "\n").as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Reduces a JSON value to its shape: values are replaced by their type, like `"<string>"`, object
+/// fields are sorted, and the elements of an array are merged into one shape per enum variant.
+///
+/// Enum tags, in `type` fields, are kept since renaming them breaks clients. Move values, whose
+/// shape depends on their Move type rather than on the API, are replaced by `"<move_value>"`.
+fn json_shape(value: &Value) -> Value {
+    match value {
+        Value::Null => json!("<null>"),
+        Value::Bool(_) => json!("<bool>"),
+        Value::Number(_) => json!("<number>"),
+        Value::String(_) => json!("<string>"),
+        Value::Array(values) => merge_variants(values.iter().map(json_shape).collect()),
+        Value::Object(fields) => {
+            // Resources and events hold a Move value of the Move type in their `type` field
+            let holds_move_value = fields
+                .get("type")
+                .and_then(Value::as_str)
+                .map_or(false, is_move_type);
+            let mut names = fields.keys().collect::<Vec<_>>();
+            names.sort();
+            let mut shape = Map::new();
+            for name in names {
+                let field = &fields[name];
+                let field_shape = match (name.as_str(), field) {
+                    ("type", Value::String(tag)) if is_enum_tag(tag) => field.clone(),
+                    ("data", _) if holds_move_value => json!("<move_value>"),
+                    ("arguments", _) => json!("<move_values>"),
+                    _ => json_shape(field),
+                };
+                shape.insert(name.clone(), field_shape);
+            }
+            Value::Object(shape)
+        }
+    }
+}
+
+fn is_move_type(value: &str) -> bool {
+    value.contains("::")
+}
+
+/// Enum tags are snake case, like `user_transaction`, unlike the Move types also found in `type`
+/// fields, like `u64` or `0x1::GUID::Generator`
+fn is_enum_tag(value: &str) -> bool {
+    value.contains('_')
+        && value
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Merges shapes by enum variant, sorted by tag, so that a list holding different kinds of
+/// transactions or changes keeps one shape for each of them
+fn merge_variants(shapes: Vec<Value>) -> Value {
+    let mut variants: Vec<(String, Value)> = vec![];
+    for shape in shapes {
+        let tag = match shape.get("type").and_then(Value::as_str) {
+            Some(tag) if !tag.starts_with('<') => tag.to_owned(),
+            _ => String::new(),
+        };
+        match variants.iter_mut().find(|(t, _)| *t == tag) {
+            Some((_, merged)) => *merged = merge_shapes(merged.take(), shape),
+            None => variants.push((tag, shape)),
+        }
+    }
+    variants.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Value::Array(variants.into_iter().map(|(_, shape)| shape).collect())
+}
+
+fn merge_shapes(a: Value, b: Value) -> Value {
+    if a == b {
+        return a;
+    }
+    match (a, b) {
+        (Value::Object(mut a), Value::Object(b)) => {
+            for (name, b_field) in b {
+                let merged = match a.remove(&name) {
+                    Some(a_field) => merge_shapes(a_field, b_field),
+                    None => b_field,
+                };
+                a.insert(name, merged);
+            }
+            let mut names = a.keys().cloned().collect::<Vec<_>>();
+            names.sort();
+            Value::Object(
+                names
+                    .into_iter()
+                    .map(|name| {
+                        let field = a.remove(&name).unwrap();
+                        (name, field)
+                    })
+                    .collect(),
+            )
+        }
+        (Value::Array(mut a), Value::Array(b)) => {
+            a.extend(b);
+            merge_variants(a)
+        }
+        // Fields which may hold values of different types, like an optional one
+        (Value::String(a), Value::String(b)) => {
+            let mut types = a.split('|').chain(b.split('|')).collect::<Vec<_>>();
+            types.sort_unstable();
+            types.dedup();
+            json!(types.join("|"))
+        }
+        _ => json!("<mixed>"),
+    }
+}
@@ -1,17 +1,21 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use forge::{Factory, LocalFactory, LocalSwarm};
+use aptos_config::config::NodeConfig;
+use forge::{
+    Factory, GenesisConfiguration, InitialState, LocalFactory, LocalSwarm, NodeConfigFn,
+    NodeEnvironment,
+};
 use once_cell::sync::Lazy;
 use rand::rngs::OsRng;
 use std::num::NonZeroUsize;
 
+static FACTORY: Lazy<LocalFactory> = Lazy::new(|| LocalFactory::from_workspace().unwrap());
+
 pub async fn new_local_swarm(
     num_validators: usize,
     genesis_modules: Option<Vec<Vec<u8>>>,
 ) -> LocalSwarm {
-    static FACTORY: Lazy<LocalFactory> = Lazy::new(|| LocalFactory::from_workspace().unwrap());
-
     ::aptos_logger::Logger::new().init();
     let version = FACTORY.versions().max().unwrap();
 
@@ -21,8 +25,10 @@ pub async fn new_local_swarm(
             NonZeroUsize::new(num_validators).unwrap(),
             &version,
             genesis_modules,
+            InitialState::default(),
             // TODO: migrate to > 0
             0,
+            NodeEnvironment::default(),
         )
         .await
         .unwrap()
@@ -36,3 +42,94 @@ pub async fn new_local_swarm_with_aptos(num_validators: usize) -> LocalSwarm {
     )
     .await
 }
+
+/// Launches a swarm like `new_local_swarm_with_aptos`, with `node_config_fn` applied to the
+/// config of every node before it starts.
+pub async fn new_local_swarm_with_node_config_fn<F>(
+    num_validators: usize,
+    node_config_fn: F,
+) -> LocalSwarm
+where
+    F: Fn(&mut NodeConfig) + Send + Sync + 'static,
+{
+    ::aptos_logger::Logger::new().init();
+    let version = FACTORY.versions().max().unwrap();
+
+    FACTORY
+        .new_swarm_with_version(
+            OsRng,
+            NonZeroUsize::new(num_validators).unwrap(),
+            &version,
+            Some(cached_framework_packages::module_blobs().to_vec()),
+            InitialState::default(),
+            // TODO: migrate to > 0
+            0,
+            NodeEnvironment {
+                node_config_fn: Some(NodeConfigFn::new(node_config_fn)),
+                ..NodeEnvironment::default()
+            },
+        )
+        .await
+        .unwrap()
+}
+
+/// Launches a swarm where the last `num_ipv6_validators` validators listen on IPv6 and the others
+/// on IPv4.
+pub async fn new_local_dual_stack_swarm(
+    num_validators: usize,
+    num_ipv6_validators: usize,
+) -> LocalSwarm {
+    ::aptos_logger::Logger::new().init();
+
+    let mut swarm = FACTORY
+        .swarm_builder()
+        .number_of_validators(NonZeroUsize::new(num_validators).unwrap())
+        .ipv6_validators(num_ipv6_validators)
+        .genesis_modules(cached_framework_packages::module_blobs().to_vec())
+        .min_price_per_gas_unit(0)
+        .build(OsRng)
+        .unwrap();
+    swarm.launch().await.unwrap();
+    swarm
+}
+
+/// Launches a swarm where the first `num_vfns` validators have a VFN attached, along with
+/// `num_pfns` public fullnodes.
+pub async fn new_local_swarm_with_fullnodes(
+    num_validators: usize,
+    num_vfns: usize,
+    num_pfns: usize,
+) -> LocalSwarm {
+    ::aptos_logger::Logger::new().init();
+
+    let mut swarm = FACTORY
+        .swarm_builder()
+        .number_of_validators(NonZeroUsize::new(num_validators).unwrap())
+        .num_validator_fullnodes(num_vfns)
+        .num_public_fullnodes(num_pfns)
+        .genesis_modules(cached_framework_packages::module_blobs().to_vec())
+        .min_price_per_gas_unit(0)
+        .build(OsRng)
+        .unwrap();
+    swarm.launch().await.unwrap();
+    swarm
+}
+
+/// Launches a swarm whose chain starts with the on-chain configuration `genesis_configuration`
+pub async fn new_local_swarm_with_genesis_configuration(
+    num_validators: usize,
+    genesis_configuration: GenesisConfiguration,
+) -> LocalSwarm {
+    ::aptos_logger::Logger::new().init();
+
+    let mut swarm = FACTORY
+        .swarm_builder()
+        .number_of_validators(NonZeroUsize::new(num_validators).unwrap())
+        .genesis_modules(cached_framework_packages::module_blobs().to_vec())
+        .genesis_configuration(genesis_configuration)
+        .min_price_per_gas_unit(0)
+        .build(OsRng)
+        .unwrap();
+    swarm.launch().await.unwrap();
+    swarm
+}
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    smoke_test_environment::new_local_swarm_with_aptos,
+    smoke_test_environment::{new_local_swarm_with_aptos, new_local_swarm_with_node_config_fn},
     test_utils::{assert_balance, create_and_fund_account, transfer_coins},
 };
 use forge::{NodeExt, Swarm, SwarmExt};
@@ -20,14 +20,10 @@ async fn test_basic_state_synchronization() {
     // - Verify that the restarted node has synced up with the submitted transactions.
 
     // we set a smaller chunk limit (=5) here to properly test multi-chunk state sync
-    let mut swarm = new_local_swarm_with_aptos(4).await;
-    for validator in swarm.validators_mut() {
-        let mut config = validator.config().clone();
+    let mut swarm = new_local_swarm_with_node_config_fn(4, |config| {
         config.state_sync.chunk_limit = 5;
-        config.save(validator.config_path()).unwrap();
-        validator.restart().await.unwrap();
-    }
-    swarm.launch().await.unwrap(); // Make sure all nodes are healthy and live
+    })
+    .await;
     let validator_peer_ids = swarm.validators().map(|v| v.peer_id()).collect::<Vec<_>>();
 
     let client_1 = swarm
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    smoke_test_environment::new_local_swarm_with_aptos,
+    smoke_test_environment::{new_local_swarm_with_aptos, new_local_swarm_with_node_config_fn},
     test_utils::{create_and_fund_account, transfer_and_reconfig, transfer_coins},
 };
 use aptos_config::config::{BootstrappingMode, ContinuousSyncingMode, NodeConfig};
@@ -197,17 +197,14 @@ async fn test_full_node_sync(vfn_peer_id: PeerId, mut swarm: LocalSwarm, epoch_c
 #[tokio::test]
 async fn test_validator_bootstrap_outputs() {
     // Create a swarm of 4 validators with state sync v2 enabled (output syncing)
-    let mut swarm = new_local_swarm_with_aptos(4).await;
-    for validator in swarm.validators_mut() {
-        let mut config = validator.config().clone();
+    let swarm = new_local_swarm_with_node_config_fn(4, |config| {
         config.state_sync.state_sync_driver.enable_state_sync_v2 = true;
         config.state_sync.state_sync_driver.bootstrapping_mode =
             BootstrappingMode::ApplyTransactionOutputsFromGenesis;
         config.state_sync.state_sync_driver.continuous_syncing_mode =
             ContinuousSyncingMode::ApplyTransactionOutputs;
-        config.save(validator.config_path()).unwrap();
-        validator.restart().await.unwrap();
-    }
+    })
+    .await;
 
     // Test the ability of the validators to sync
     test_validator_sync(swarm).await;
@@ -216,17 +213,14 @@ async fn test_validator_bootstrap_outputs() {
 #[tokio::test]
 async fn test_validator_bootstrap_transactions() {
     // Create a swarm of 4 validators with state sync v2 enabled (transaction syncing)
-    let mut swarm = new_local_swarm_with_aptos(4).await;
-    for validator in swarm.validators_mut() {
-        let mut config = validator.config().clone();
+    let swarm = new_local_swarm_with_node_config_fn(4, |config| {
         config.state_sync.state_sync_driver.enable_state_sync_v2 = true;
         config.state_sync.state_sync_driver.bootstrapping_mode =
             BootstrappingMode::ExecuteTransactionsFromGenesis;
         config.state_sync.state_sync_driver.continuous_syncing_mode =
             ContinuousSyncingMode::ExecuteTransactions;
-        config.save(validator.config_path()).unwrap();
-        validator.restart().await.unwrap();
-    }
+    })
+    .await;
 
     // Test the ability of the validators to sync
     test_validator_sync(swarm).await;
@@ -287,17 +281,14 @@ async fn test_validator_sync(mut swarm: LocalSwarm) {
 async fn test_validator_failure_bootstrap_outputs() {
     // Create a swarm of 4 validators with state sync v2 enabled (account
     // bootstrapping and transaction output application).
-    let mut swarm = new_local_swarm_with_aptos(4).await;
-    for validator in swarm.validators_mut() {
-        let mut config = validator.config().clone();
+    let swarm = new_local_swarm_with_node_config_fn(4, |config| {
         config.state_sync.state_sync_driver.enable_state_sync_v2 = true;
         config.state_sync.state_sync_driver.bootstrapping_mode =
             BootstrappingMode::DownloadLatestAccountStates;
         config.state_sync.state_sync_driver.continuous_syncing_mode =
             ContinuousSyncingMode::ApplyTransactionOutputs;
-        config.save(validator.config_path()).unwrap();
-        validator.restart().await.unwrap();
-    }
+    })
+    .await;
 
     // Test the ability of the validators to sync
     test_all_validator_failures(swarm).await;
@@ -307,17 +298,14 @@ async fn test_validator_failure_bootstrap_outputs() {
 async fn test_validator_failure_bootstrap_execution() {
     // Create a swarm of 4 validators with state sync v2 enabled (account
     // bootstrapping and transaction execution).
-    let mut swarm = new_local_swarm_with_aptos(4).await;
-    for validator in swarm.validators_mut() {
-        let mut config = validator.config().clone();
+    let swarm = new_local_swarm_with_node_config_fn(4, |config| {
         config.state_sync.state_sync_driver.enable_state_sync_v2 = true;
         config.state_sync.state_sync_driver.bootstrapping_mode =
             BootstrappingMode::DownloadLatestAccountStates;
         config.state_sync.state_sync_driver.continuous_syncing_mode =
             ContinuousSyncingMode::ExecuteTransactions;
-        config.save(validator.config_path()).unwrap();
-        validator.restart().await.unwrap();
-    }
+    })
+    .await;
 
     // Test the ability of the validators to sync
     test_all_validator_failures(swarm).await;
@@ -138,6 +138,46 @@ async fn test_db_restore() {
     assert_balance(&client_0, &account_1, expected_balance_1).await;
 }
 
+#[tokio::test]
+async fn test_swarm_snapshot_restore() {
+    let mut swarm = new_local_swarm_with_aptos(4).await;
+    let client = swarm.validators().next().unwrap().rest_client();
+    let transaction_factory = swarm.chain_info().transaction_factory();
+
+    let mut account_0 = create_and_fund_account(&mut swarm, 1000000).await;
+    let account_1 = create_and_fund_account(&mut swarm, 1000000).await;
+    transfer_coins(&client, &transaction_factory, &mut account_0, &account_1, 1).await;
+    swarm
+        .wait_for_all_nodes_to_catchup(Instant::now() + Duration::from_secs(60))
+        .await
+        .unwrap();
+
+    // A swarm built anew gets another genesis, as the validators listen on random ports, so the
+    // snapshot is restored into the swarm it was taken of, which has the same genesis waypoint
+    let snapshot_dir = TempPath::new();
+    swarm.snapshot(snapshot_dir.path()).await.unwrap();
+    assert_balance(&client, &account_0, 999999).await;
+    assert_balance(&client, &account_1, 1000001).await;
+
+    // The transfer after the snapshot is undone by the restore
+    transfer_coins(
+        &client,
+        &transaction_factory,
+        &mut account_0,
+        &account_1,
+        20,
+    )
+    .await;
+    assert_balance(&client, &account_0, 999979).await;
+    swarm.restore(snapshot_dir.path()).await.unwrap();
+
+    for validator in swarm.validators() {
+        let client = validator.rest_client();
+        assert_balance(&client, &account_0, 999999).await;
+        assert_balance(&client, &account_1, 1000001).await;
+    }
+}
+
 fn db_backup_verify(backup_path: &Path, trusted_waypoints: &[Waypoint]) {
     let now = Instant::now();
     let bin_path = workspace_builder::get_bin("db-backup-verify");
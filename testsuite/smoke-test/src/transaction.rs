@@ -92,6 +92,7 @@ impl AptosTest for ExternalTransactionSigner {
                     function: _,
                     type_arguments: _,
                     arguments,
+                    argument_types: _,
                 }) = user_txn.request.payload
                 {
                     assert_eq!(
@@ -9,6 +9,7 @@ fn main() -> Result<()> {
         .with_aptos_tests(&[
             &rest_api::GetIndex,
             &rest_api::BasicClient,
+            &rest_api::ApiGoldenShapes,
             &indexer::Indexer,
         ])
         //TODO Re-enable these tests once we fix how the move compiler is invoked
@@ -0,0 +1,248 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::generate_traffic;
+use anyhow::{anyhow, bail, ensure};
+use aptos_rest_client::Client as RestClient;
+use aptos_sdk::types::{LocalAccount, PeerId};
+use forge::{NetworkContext, NetworkTest, NodeExt, Result, Test};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::runtime::Runtime;
+
+/// How far the ledger timestamps may be off the clocks they are checked against, for the time
+/// blocks take to be committed
+const TIMESTAMP_TOLERANCE: Duration = Duration::from_secs(5);
+/// How many times the ledger timestamps of the validators are sampled, one second apart
+const TIMESTAMP_SAMPLES: usize = 10;
+
+/// Restarts one validator with its clock `skew_secs` seconds ahead and another with its clock as
+/// far behind, and checks that:
+/// - the validators keep committing the traffic sent to all of them,
+/// - the timestamps of the ledger keep increasing and follow the real time, i.e. the clocks of
+///   the other validators, rather than that of the validator ahead. The validator behind only
+///   inserts blocks once its clock has passed their timestamps, so its ledger lags and mustn't be
+///   ahead of its clock,
+/// - the expiration of transactions is judged against the time of the ledger rather than the
+///   clocks of the validators they are submitted to: the validator ahead accepts a transaction
+///   expiring before its own time, and the one behind rejects a transaction which expired for
+///   its ledger.
+///
+/// The skewed validators count as faulty: the others don't vote for the proposals of the one
+/// ahead, and the one behind doesn't vote for theirs, so the swarm needs at least 7 validators.
+/// The clocks are put back right at the end.
+pub struct ClockSkew {
+    pub skew_secs: u64,
+    pub traffic_duration: Duration,
+}
+
+impl Test for ClockSkew {
+    fn name(&self) -> &'static str {
+        "clock-skew"
+    }
+}
+
+impl NetworkTest for ClockSkew {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        ensure!(
+            self.skew_secs > 2 * TIMESTAMP_TOLERANCE.as_secs(),
+            "The skew is required to be over {}s, for the ledger timestamps to tell it apart",
+            2 * TIMESTAMP_TOLERANCE.as_secs()
+        );
+        ensure!(
+            ctx.swarm().max_faulty_validators() >= 2,
+            "The swarm can only tolerate two skewed validators with >= 7 validators"
+        );
+        let runtime = Runtime::new()?;
+        let mut validators = ctx
+            .swarm()
+            .validators()
+            .map(|v| v.peer_id())
+            .collect::<Vec<_>>();
+        let behind = validators.pop().unwrap();
+        let ahead = validators.pop().unwrap();
+        let skew_secs = self.skew_secs as i64;
+
+        println!(
+            "Skewing the clock of {} {}s ahead and that of {} {}s behind",
+            ahead, skew_secs, behind, skew_secs
+        );
+        ctx.swarm()
+            .skew_validator_clocks(&[(ahead, skew_secs), (behind, -skew_secs)])?;
+        // The clocks are put back right even when the checks fail, so as not to skew the later
+        // tests
+        let result = runtime
+            .block_on(wait_until_healthy(ctx, &[ahead, behind]))
+            .and_then(|()| self.check_skewed(ctx, &runtime, &validators, ahead, behind));
+        ctx.swarm()
+            .skew_validator_clocks(&[(ahead, 0), (behind, 0)])?;
+        runtime.block_on(wait_until_healthy(ctx, &[ahead, behind]))?;
+        result
+    }
+}
+
+impl ClockSkew {
+    fn check_skewed(
+        &self,
+        ctx: &mut NetworkContext<'_>,
+        runtime: &Runtime,
+        others: &[PeerId],
+        ahead: PeerId,
+        behind: PeerId,
+    ) -> Result<()> {
+        // The liveness check of the swarm can't tell, as it compares the ledgers with the clocks of
+        // the validators
+        let mut all = others.to_vec();
+        all.extend(&[ahead, behind]);
+        let stats = generate_traffic(ctx, &all, self.traffic_duration, 1, None)?;
+        ensure!(
+            stats.committed > 0,
+            "No transaction was committed with skewed clocks"
+        );
+        ctx.report
+            .report_txn_stats(self.name().to_string(), stats, self.traffic_duration);
+
+        let clients = all
+            .iter()
+            .map(|id| {
+                let clock_behind = if *id == behind {
+                    Duration::from_secs(self.skew_secs)
+                } else {
+                    Duration::ZERO
+                };
+                let client = ctx.swarm().validator(*id).unwrap().rest_client();
+                (*id, client, clock_behind)
+            })
+            .collect::<Vec<_>>();
+        let max_drift = runtime.block_on(check_ledger_timestamps(&clients))?;
+        ctx.report
+            .report_metric(self.name(), "max_timestamp_drift_ms", max_drift as f64);
+
+        let ahead_client = ctx.swarm().validator(ahead).unwrap().rest_client();
+        let behind_client = ctx.swarm().validator(behind).unwrap().rest_client();
+        let mut account = LocalAccount::generate(ctx.core().rng());
+        let chain_info = ctx.swarm().chain_info();
+        let receiver = chain_info.root_account.address();
+        let mut info = chain_info.into_aptos_public_info();
+        runtime.block_on(async {
+            info.create_user_account(account.public_key()).await?;
+            info.mint(account.address(), 1_000_000).await?;
+            let factory = info.transaction_factory();
+
+            // Expires before the time of the validator ahead, but not before that of the ledger
+            let ledger_secs = ledger_timestamp_secs(&ahead_client).await?;
+            let txn = account.sign_with_transaction_builder(
+                factory
+                    .transfer(receiver, 1)
+                    .expiration_timestamp_secs(ledger_secs + self.skew_secs / 2),
+            );
+            let pending = ahead_client.submit(&txn).await.map_err(|e| {
+                anyhow!(
+                    "Validator {}, {}s ahead, rejected a transaction which expires in {}s: {:?}",
+                    ahead,
+                    self.skew_secs,
+                    self.skew_secs / 2,
+                    e
+                )
+            })?;
+            ahead_client.wait_for_transaction(pending.inner()).await?;
+
+            // Expired for the ledger of the validator behind, which lags that of the others
+            let ledger_secs = ledger_timestamp_secs(&behind_client).await?;
+            let txn = account.sign_with_transaction_builder(
+                factory
+                    .transfer(receiver, 1)
+                    .expiration_timestamp_secs(ledger_secs),
+            );
+            match behind_client.submit(&txn).await {
+                Ok(_) => bail!(
+                    "Validator {}, {}s behind, accepted a transaction which expired",
+                    behind,
+                    self.skew_secs
+                ),
+                Err(e) if format!("{:?}", e).contains("TRANSACTION_EXPIRED") => {}
+                Err(e) => bail!(
+                    "Validator {} rejected an expired transaction for another reason: {:?}",
+                    behind,
+                    e
+                ),
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Samples the ledger timestamps of the validators, each along with how far its clock is behind
+/// the real time. Checks that they increase, that none is more than `TIMESTAMP_TOLERANCE` ahead
+/// of the clock of its validator, and that those of the validators whose clocks aren't behind are
+/// no more than `TIMESTAMP_TOLERANCE` behind the real time. Returns how far off the real time
+/// the latter were at most, in milliseconds.
+async fn check_ledger_timestamps(clients: &[(PeerId, RestClient, Duration)]) -> Result<u64> {
+    let mut last_timestamps = vec![Duration::ZERO; clients.len()];
+    let mut max_drift = 0;
+    for _ in 0..TIMESTAMP_SAMPLES {
+        for ((id, client, clock_behind), last_timestamp) in clients.iter().zip(&mut last_timestamps)
+        {
+            let timestamp = Duration::from_micros(
+                client
+                    .get_ledger_information()
+                    .await?
+                    .into_inner()
+                    .timestamp_usecs,
+            );
+            let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+            ensure!(
+                timestamp >= *last_timestamp,
+                "The ledger timestamp of validator {} went back from {}us to {}us",
+                id,
+                last_timestamp.as_micros(),
+                timestamp.as_micros()
+            );
+            *last_timestamp = timestamp;
+            let clock = now - *clock_behind;
+            ensure!(
+                timestamp <= clock + TIMESTAMP_TOLERANCE,
+                "The ledger of validator {} is {}ms ahead of its clock",
+                id,
+                (timestamp - clock).as_millis()
+            );
+            if *clock_behind > Duration::ZERO {
+                continue;
+            }
+            ensure!(
+                timestamp + TIMESTAMP_TOLERANCE >= now,
+                "The ledger of validator {} is {}ms behind the real time",
+                id,
+                (now - timestamp).as_millis()
+            );
+            let drift = if timestamp > now {
+                timestamp - now
+            } else {
+                now - timestamp
+            };
+            max_drift = max_drift.max(drift.as_millis() as u64);
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+    Ok(max_drift)
+}
+
+async fn wait_until_healthy(ctx: &mut NetworkContext<'_>, validators: &[PeerId]) -> Result<()> {
+    let deadline = Instant::now() + Duration::from_secs(60);
+    for id in validators {
+        ctx.swarm()
+            .validator_mut(*id)
+            .ok_or_else(|| anyhow!("No validator {}", id))?
+            .wait_until_healthy(deadline)
+            .await?;
+    }
+    Ok(())
+}
+
+async fn ledger_timestamp_secs(client: &RestClient) -> Result<u64> {
+    Ok(client
+        .get_ledger_information()
+        .await?
+        .into_inner()
+        .timestamp_usecs
+        / 1_000_000)
+}
@@ -1,11 +1,101 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{batch_update, generate_traffic};
-use anyhow::bail;
-use forge::{NetworkContext, NetworkTest, Result, SwarmExt, Test};
+use crate::{batch_update, generate_traffic, start_background_traffic, update_to_version};
+use anyhow::{anyhow, bail, ensure};
+use aptos_rest_client::Client as RestClient;
+use aptos_sdk::types::PeerId;
+use forge::{ChaosSpec, NetworkContext, NetworkTest, NodeExt, Result, SwarmExt, Test, Version};
+use std::time::Instant;
 use tokio::{runtime::Runtime, time::Duration};
 
+/// How long the upgraded validators get to catch up with the others before their ledgers are
+/// compared
+const CATCHUP_TIMEOUT: Duration = Duration::from_secs(120);
+/// How long an upgraded validator gets to be healthy again
+const UPGRADE_TIMEOUT: Duration = Duration::from_secs(60);
+const MIB: f64 = (1 << 20) as f64;
+
+/// Returns the older and the newer of the two versions the swarm runs
+fn get_versions(ctx: &mut NetworkContext<'_>) -> Result<(Version, Version)> {
+    let mut versions = ctx.swarm().versions().collect::<Vec<_>>();
+    versions.sort();
+    if versions.len() != 2 {
+        bail!("exactly two different versions needed to run compat test");
+    }
+
+    Ok((versions[0].clone(), versions[1].clone()))
+}
+
+/// Reports the size of the node binary of both versions, when the backend builds them, and the
+/// longest a batch of validators took to become healthy after being updated to each version,
+/// along with how much the new version changed them. The metrics are named after the role of the
+/// version rather than the version itself, so that rolling baselines track them across runs.
+fn report_version_costs(
+    ctx: &mut NetworkContext<'_>,
+    test_name: &str,
+    (old_version, old_time_to_healthy): (&Version, Option<Duration>),
+    (new_version, new_time_to_healthy): (&Version, Option<Duration>),
+) -> Result<()> {
+    let old_size = ctx.swarm().node_binary_size(old_version)?;
+    let new_size = ctx.swarm().node_binary_size(new_version)?;
+    if let (Some(old_size), Some(new_size)) = (old_size, new_size) {
+        ctx.report
+            .report_metric(test_name, "old_binary_size", old_size as f64);
+        ctx.report
+            .report_metric(test_name, "new_binary_size", new_size as f64);
+        report_change(
+            ctx,
+            "aptos-node binary size",
+            (old_version, format!("{:.1} MiB", old_size as f64 / MIB)),
+            (new_version, format!("{:.1} MiB", new_size as f64 / MIB)),
+            change_pct(old_size as f64, new_size as f64),
+        );
+    }
+
+    if let Some(time) = old_time_to_healthy {
+        ctx.report
+            .report_metric(test_name, "old_time_to_healthy", time.as_secs_f64());
+    }
+    if let Some(time) = new_time_to_healthy {
+        ctx.report
+            .report_metric(test_name, "new_time_to_healthy", time.as_secs_f64());
+    }
+    if let (Some(old_time), Some(new_time)) = (old_time_to_healthy, new_time_to_healthy) {
+        report_change(
+            ctx,
+            "Time to healthy",
+            (old_version, format!("{:.1}s", old_time.as_secs_f64())),
+            (new_version, format!("{:.1}s", new_time.as_secs_f64())),
+            change_pct(old_time.as_secs_f64(), new_time.as_secs_f64()),
+        );
+    }
+
+    Ok(())
+}
+
+fn change_pct(old: f64, new: f64) -> Option<f64> {
+    (old != 0.0).then(|| (new - old) / old * 100.0)
+}
+
+fn report_change(
+    ctx: &mut NetworkContext<'_>,
+    what: &str,
+    (old_version, old): (&Version, String),
+    (new_version, new): (&Version, String),
+    change_pct: Option<f64>,
+) {
+    let change = change_pct
+        .map(|pct| format!(" ({:+.1}%)", pct))
+        .unwrap_or_default();
+    let msg = format!(
+        "{}: {} on {} ==> {} on {}{}",
+        what, old, old_version, new, new_version, change
+    );
+    println!("{}", msg);
+    ctx.report.report_text(msg);
+}
+
 pub struct SimpleValidatorUpgrade;
 
 impl Test for SimpleValidatorUpgrade {
@@ -19,15 +109,7 @@ impl NetworkTest for SimpleValidatorUpgrade {
         let runtime = Runtime::new()?;
 
         // Get the different versions we're testing with
-        let (old_version, new_version) = {
-            let mut versions = ctx.swarm().versions().collect::<Vec<_>>();
-            versions.sort();
-            if versions.len() != 2 {
-                bail!("exactly two different versions needed to run compat test");
-            }
-
-            (versions[0].clone(), versions[1].clone())
-        };
+        let (old_version, new_version) = get_versions(ctx)?;
 
         let msg = format!(
             "Compatibility test results for {} ==> {} (PR)",
@@ -57,13 +139,8 @@ impl NetworkTest for SimpleValidatorUpgrade {
         println!("{}", msg);
         ctx.report.report_text(msg);
         // Ensure that all validators are running the older version of the software
-        let validators_to_downgrade = ctx
-            .swarm()
-            .validators()
-            .filter(|v| v.version() != old_version)
-            .map(|v| v.peer_id())
-            .collect::<Vec<_>>();
-        runtime.block_on(batch_update(ctx, &validators_to_downgrade, &old_version))?;
+        let old_time_to_healthy =
+            runtime.block_on(update_to_version(ctx, &all_validators, &old_version))?;
 
         // Generate some traffic
         generate_traffic(ctx, &all_validators, duration, 1, None)?;
@@ -75,7 +152,8 @@ impl NetworkTest for SimpleValidatorUpgrade {
         );
         println!("{}", msg);
         ctx.report.report_text(msg);
-        runtime.block_on(batch_update(ctx, &[first_node], &new_version))?;
+        let mut new_time_to_healthy =
+            runtime.block_on(batch_update(ctx, &[first_node], &new_version))?;
         generate_traffic(ctx, &[first_node], duration, 1, None)?;
 
         // Update the rest of the first batch
@@ -85,22 +163,44 @@ impl NetworkTest for SimpleValidatorUpgrade {
         );
         println!("{}", msg);
         ctx.report.report_text(msg);
-        runtime.block_on(batch_update(ctx, &first_batch, &new_version))?;
+        new_time_to_healthy = new_time_to_healthy.max(runtime.block_on(batch_update(
+            ctx,
+            &first_batch,
+            &new_version,
+        ))?);
         generate_traffic(ctx, &first_batch, duration, 1, None)?;
 
+        runtime.block_on(
+            ctx.swarm()
+                .wait_for_all_nodes_to_catchup(Instant::now() + CATCHUP_TIMEOUT),
+        )?;
         ctx.swarm().fork_check()?;
 
         // Update the second batch
         let msg = format!("4. upgrading second batch to new version: {}", new_version);
         println!("{}", msg);
         ctx.report.report_text(msg);
-        runtime.block_on(batch_update(ctx, &second_batch, &new_version))?;
+        new_time_to_healthy = new_time_to_healthy.max(runtime.block_on(batch_update(
+            ctx,
+            &second_batch,
+            &new_version,
+        ))?);
         generate_traffic(ctx, &second_batch, duration, 1, None)?;
 
         let msg = "5. check swarm health".to_string();
         println!("{}", msg);
         ctx.report.report_text(msg);
+        runtime.block_on(
+            ctx.swarm()
+                .wait_for_all_nodes_to_catchup(Instant::now() + CATCHUP_TIMEOUT),
+        )?;
         ctx.swarm().fork_check()?;
+        report_version_costs(
+            ctx,
+            self.name(),
+            (&old_version, old_time_to_healthy),
+            (&new_version, Some(new_time_to_healthy)),
+        )?;
         ctx.report.report_text(format!(
             "Compatibility test for {} ==> {} passed",
             old_version, new_version
@@ -109,3 +209,368 @@ impl NetworkTest for SimpleValidatorUpgrade {
         Ok(())
     }
 }
+
+/// Keeps half of the validators on the old version and half on the new one under traffic for a
+/// long time, checking periodically that both halves keep committing the same state. The upgrade
+/// test only covers the brief window in which versions are mixed during an upgrade.
+pub struct MixedVersionSoak {
+    /// How long the swarm runs with mixed versions
+    pub duration: Duration,
+    /// How often the committed state is compared across the version boundary
+    pub check_interval: Duration,
+}
+
+impl Test for MixedVersionSoak {
+    fn name(&self) -> &'static str {
+        "compatibility::mixed-version-soak"
+    }
+}
+
+impl NetworkTest for MixedVersionSoak {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        let runtime = Runtime::new()?;
+        let (old_version, new_version) = get_versions(ctx)?;
+
+        if ctx.swarm().validators().count() < 4 {
+            bail!("mixed version soak requires >= 4 validators");
+        }
+        let all_validators = ctx
+            .swarm()
+            .validators()
+            .map(|v| v.peer_id())
+            .collect::<Vec<_>>();
+        let mut old_batch = all_validators.clone();
+        let new_batch = old_batch.split_off(old_batch.len() / 2);
+
+        let msg = format!(
+            "1. Running {} validators on {} and {} validators on {}",
+            old_batch.len(),
+            old_version,
+            new_batch.len(),
+            new_version
+        );
+        println!("{}", msg);
+        ctx.report.report_text(msg);
+        for (batch, version) in [(&old_batch, &old_version), (&new_batch, &new_version)] {
+            runtime.block_on(update_to_version(ctx, batch, version))?;
+        }
+
+        let msg = format!(
+            "2. Soaking with mixed versions for {}s, comparing state every {}s",
+            self.duration.as_secs(),
+            self.check_interval.as_secs()
+        );
+        println!("{}", msg);
+        ctx.report.report_text(msg);
+        let deadline = Instant::now() + self.duration;
+        let mut checked_version = 0;
+        while Instant::now() < deadline {
+            generate_traffic(ctx, &all_validators, self.check_interval, 1, None)?;
+
+            let validators = ctx
+                .swarm()
+                .validators()
+                .map(|v| (v.peer_id(), v.version(), v.rest_client()))
+                .collect::<Vec<_>>();
+            let version = runtime.block_on(check_committed_state(&validators))?;
+            if version <= checked_version {
+                bail!(
+                    "Validators stopped committing at version {} with mixed versions",
+                    checked_version
+                );
+            }
+            checked_version = version;
+            println!("State matches across versions at version {}", version);
+        }
+
+        ctx.report.report_text(format!(
+            "Mixed version soak of {} and {} passed, with matching state up to version {}",
+            old_version, new_version, checked_version
+        ));
+
+        Ok(())
+    }
+}
+
+/// Moves the swarm back and forth between the two versions it runs, checking after each
+/// transition that the validators keep committing the same state under traffic, and reports
+/// whether each transition passed as a matrix. The upgrade test only covers going from the old
+/// version to the new one: this one also covers rolling a minority of the validators back to the
+/// old version after the upgrade, as operators do when the new version misbehaves on their nodes,
+/// and upgrading them again.
+pub struct CompatibilityMatrix {
+    /// How long the traffic checking each transition runs
+    pub traffic_duration: Duration,
+}
+
+impl Test for CompatibilityMatrix {
+    fn name(&self) -> &'static str {
+        "compatibility::compatibility-matrix"
+    }
+}
+
+impl NetworkTest for CompatibilityMatrix {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        let runtime = Runtime::new()?;
+        let (old_version, new_version) = get_versions(ctx)?;
+
+        if ctx.swarm().validators().count() < 4 {
+            bail!("compatibility matrix requires >= 4 validators");
+        }
+        let all_validators = ctx
+            .swarm()
+            .validators()
+            .map(|v| v.peer_id())
+            .collect::<Vec<_>>();
+        // The most validators the others can keep committing without
+        let minority = all_validators[..(all_validators.len() - 1) / 3].to_vec();
+
+        let msg = format!(
+            "Compatibility matrix of {} and {}: starting with all validators on {}",
+            old_version, new_version, old_version
+        );
+        println!("{}", msg);
+        ctx.report.report_text(msg);
+        runtime.block_on(update_to_version(ctx, &all_validators, &old_version))?;
+        check_commits(ctx, &all_validators, self.traffic_duration)?;
+
+        let transitions = [
+            ("all", &all_validators, &old_version, &new_version),
+            ("minority", &minority, &new_version, &old_version),
+            ("minority", &minority, &old_version, &new_version),
+        ];
+        let mut results = Vec::with_capacity(transitions.len());
+        let mut failed = false;
+        for (batch_name, batch, from, to) in transitions {
+            let transition = format!(
+                "{} ==> {} ({} {} validators)",
+                from,
+                to,
+                batch_name,
+                batch.len()
+            );
+            // A failed transition leaves the swarm in an unknown state, which the later
+            // transitions can't start from
+            if failed {
+                results.push((transition, "skipped".to_string()));
+                continue;
+            }
+            println!("Transition {}", transition);
+            let result = runtime
+                .block_on(update_to_version(ctx, batch, to))
+                .and_then(|_| check_commits(ctx, &all_validators, self.traffic_duration));
+            let result = match result {
+                Ok(()) => "passed".to_string(),
+                Err(e) => {
+                    failed = true;
+                    format!("FAILED: {}", e)
+                }
+            };
+            results.push((transition, result));
+        }
+
+        let mut matrix = format!(
+            "Compatibility matrix of {} and {}:",
+            old_version, new_version
+        );
+        for (transition, result) in &results {
+            matrix.push_str(&format!("\n  {}: {}", transition, result));
+        }
+        println!("{}", matrix);
+        ctx.report.report_text(matrix);
+        ensure!(
+            !failed,
+            "Compatibility matrix of {} and {} failed",
+            old_version,
+            new_version
+        );
+
+        Ok(())
+    }
+}
+
+/// Upgrades the validators one at a time under traffic while a minority of them, still on the old
+/// version, is partitioned from the others, then heals the partition and checks that the
+/// partitioned validators catch up with the upgraded ones before upgrading them too: the worst
+/// case of an upgrade of mainnet, where some operators are unreachable while the others upgrade.
+pub struct UpgradeUnderPartition {
+    /// How long the traffic runs from the start of the upgrade, which it has to outlast
+    pub traffic_duration: Duration,
+}
+
+impl Test for UpgradeUnderPartition {
+    fn name(&self) -> &'static str {
+        "compatibility::upgrade-under-partition"
+    }
+}
+
+impl NetworkTest for UpgradeUnderPartition {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        let runtime = Runtime::new()?;
+        let (old_version, new_version) = get_versions(ctx)?;
+
+        if ctx.swarm().validators().count() < 4 {
+            bail!("upgrade under partition requires >= 4 validators");
+        }
+        let all_validators = ctx
+            .swarm()
+            .validators()
+            .map(|v| v.peer_id())
+            .collect::<Vec<_>>();
+        // The most validators the others can keep committing without
+        let mut upgraded = all_validators.clone();
+        let partitioned = upgraded
+            .drain(..(all_validators.len() - 1) / 3)
+            .collect::<Vec<_>>();
+
+        let msg = format!(
+            "1. Starting with all validators on {}, under traffic",
+            old_version
+        );
+        println!("{}", msg);
+        ctx.report.report_text(msg);
+        runtime.block_on(update_to_version(ctx, &all_validators, &old_version))?;
+        let traffic = start_background_traffic(ctx, &upgraded, self.traffic_duration, 1)?;
+
+        let msg = format!(
+            "2. Partitioning {} validators, then upgrading the other {} to {} one at a time",
+            partitioned.len(),
+            upgraded.len(),
+            new_version
+        );
+        println!("{}", msg);
+        ctx.report.report_text(msg);
+        let chaos = ChaosSpec::NetworkPartition {
+            group_a: partitioned.clone(),
+            group_b: upgraded.clone(),
+        };
+        runtime.block_on(ctx.swarm().inject_chaos(chaos.clone()))?;
+        let upgrade_start = Instant::now();
+        for validator in &upgraded {
+            ctx.swarm().upgrade_validator(*validator, &new_version)?;
+            runtime.block_on(
+                ctx.swarm()
+                    .validator_mut(*validator)
+                    .unwrap()
+                    .wait_until_healthy(Instant::now() + UPGRADE_TIMEOUT),
+            )?;
+            // A restarted node forgets the failpoints partitioning it on a local swarm
+            runtime.block_on(ctx.swarm().inject_chaos(chaos.clone()))?;
+        }
+        let upgrade_time = upgrade_start.elapsed();
+
+        let msg = "3. Healing the partition".to_string();
+        println!("{}", msg);
+        ctx.report.report_text(msg);
+        runtime.block_on(ctx.swarm().remove_chaos(chaos))?;
+        let stats = runtime.block_on(traffic)??;
+        ensure!(
+            stats.committed > 0,
+            "No transaction committed out of {} submitted during the upgrade",
+            stats.submitted
+        );
+        if let Some(v) = ctx
+            .swarm()
+            .validators()
+            .find(|v| partitioned.contains(&v.peer_id()) && v.version() != old_version)
+        {
+            bail!(
+                "Partitioned validator {} runs {} instead of {}",
+                v.peer_id(),
+                v.version(),
+                old_version
+            );
+        }
+        runtime.block_on(
+            ctx.swarm()
+                .wait_for_all_nodes_to_catchup(Instant::now() + CATCHUP_TIMEOUT),
+        )?;
+        ctx.swarm().fork_check()?;
+
+        let msg = format!(
+            "4. Upgrading the {} partitioned validators to {}",
+            partitioned.len(),
+            new_version
+        );
+        println!("{}", msg);
+        ctx.report.report_text(msg);
+        runtime.block_on(update_to_version(ctx, &partitioned, &new_version))?;
+        check_commits(ctx, &all_validators, Duration::from_secs(30))?;
+
+        ctx.report
+            .report_metric(self.name(), "upgrade_time", upgrade_time.as_secs_f64());
+        ctx.report.report_text(format!(
+            "Upgrade of {} ==> {} under a partition of {} of {} validators passed, the upgrade took {:.1}s and committed {} transactions",
+            old_version,
+            new_version,
+            partitioned.len(),
+            all_validators.len(),
+            upgrade_time.as_secs_f64(),
+            stats.committed
+        ));
+
+        Ok(())
+    }
+}
+
+/// Checks that the validators commit the traffic sent to them, and that they all catch up to the
+/// same state
+fn check_commits(
+    ctx: &mut NetworkContext<'_>,
+    validators: &[PeerId],
+    duration: Duration,
+) -> Result<()> {
+    let stats = generate_traffic(ctx, validators, duration, 1, None)?;
+    ensure!(
+        stats.committed > 0,
+        "No transaction committed out of {} submitted",
+        stats.submitted
+    );
+    let runtime = Runtime::new()?;
+    runtime.block_on(
+        ctx.swarm()
+            .wait_for_all_nodes_to_catchup(Instant::now() + CATCHUP_TIMEOUT),
+    )?;
+    ctx.swarm().fork_check()
+}
+
+/// Checks that all the validators committed the same state at the highest version they all
+/// committed, and returns that version
+async fn check_committed_state(validators: &[(PeerId, Version, RestClient)]) -> Result<u64> {
+    let mut min_version = None;
+    for (_, _, client) in validators {
+        let version = client.get_ledger_information().await?.into_inner().version;
+        min_version = Some(min_version.map_or(version, |min: u64| min.min(version)));
+    }
+    let version = min_version.ok_or_else(|| anyhow!("No validators to check"))?;
+
+    let mut expected = None;
+    for (peer_id, node_version, client) in validators {
+        let txn = client
+            .get_transaction_by_version(version)
+            .await?
+            .into_inner();
+        let info = txn.transaction_info()?;
+        let roots = (info.state_root_hash, info.accumulator_root_hash);
+        match expected {
+            None => expected = Some((roots, peer_id, node_version)),
+            Some((expected_roots, expected_peer_id, expected_node_version))
+                if expected_roots != roots =>
+            {
+                bail!(
+                    "Validator {} on {} diverged from validator {} on {} at version {}: state root {} instead of {}",
+                    peer_id,
+                    node_version,
+                    expected_peer_id,
+                    expected_node_version,
+                    version,
+                    roots.0,
+                    expected_roots.0
+                );
+            }
+            _ => {}
+        }
+    }
+
+    Ok(version)
+}
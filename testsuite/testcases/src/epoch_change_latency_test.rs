@@ -0,0 +1,126 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{bail, ensure};
+use aptos_rest_client::Client as RestClient;
+use forge::{NetworkContext, NetworkTest, NodeExt, Result, Test};
+use futures::future::try_join_all;
+use std::time::{Duration, Instant};
+use tokio::runtime::Runtime;
+
+/// How long the validators get to commit in a new epoch before the test gives up on it
+const EPOCH_CHANGE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Forces `num_epochs` epoch changes one after the other and measures, for each, the time from
+/// submitting the reconfiguration to every validator committing in the new epoch. Fails when the
+/// slowest epoch change takes longer than `max_latency`.
+///
+/// The validators break the latency down in their metrics: the phases of the epoch change in
+/// `aptos_consensus_epoch_change_phase_duration_s`, the time to the first proposal of the new
+/// epoch in `aptos_consensus_epoch_first_proposal_latency_s`, and the update of the network to
+/// the new validator set in `aptos_network_discovery_update_time_seconds`.
+pub struct EpochChangeLatency {
+    pub num_epochs: usize,
+    pub max_latency: Duration,
+}
+
+impl Test for EpochChangeLatency {
+    fn name(&self) -> &'static str {
+        "epoch-change-latency"
+    }
+}
+
+impl NetworkTest for EpochChangeLatency {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        ensure!(
+            self.num_epochs > 0,
+            "The test needs at least one epoch change"
+        );
+        let runtime = Runtime::new()?;
+        let clients = ctx
+            .swarm()
+            .validators()
+            .map(|v| (v.name().to_string(), v.rest_client()))
+            .collect::<Vec<_>>();
+        let num_validators = clients.len();
+
+        let mut latencies = Vec::with_capacity(self.num_epochs);
+        for _ in 0..self.num_epochs {
+            let epoch = runtime
+                .block_on(clients[0].1.get_ledger_information())?
+                .into_inner()
+                .epoch;
+            let start = Instant::now();
+            runtime.block_on(ctx.reconfigure())?;
+            let deadline = start + EPOCH_CHANGE_TIMEOUT;
+            runtime.block_on(try_join_all(
+                clients
+                    .iter()
+                    .map(|(name, client)| wait_for_epoch(name, client, epoch + 1, deadline)),
+            ))?;
+            let latency = start.elapsed();
+            println!(
+                "Epoch {} started in {:.2}s",
+                epoch + 1,
+                latency.as_secs_f64()
+            );
+            latencies.push(latency);
+        }
+
+        let max = *latencies.iter().max().unwrap();
+        let avg = latencies.iter().sum::<Duration>() / latencies.len() as u32;
+        ctx.report.report_metric(
+            self.name(),
+            format!(
+                "avg_epoch_change_latency_secs_{}_validators",
+                num_validators
+            ),
+            avg.as_secs_f64(),
+        );
+        ctx.report.report_metric(
+            self.name(),
+            format!(
+                "max_epoch_change_latency_secs_{}_validators",
+                num_validators
+            ),
+            max.as_secs_f64(),
+        );
+        ctx.report.report_text(format!(
+            "{} : {} epoch changes with {} validators, {:.2}s on average, {:.2}s at most",
+            self.name(),
+            self.num_epochs,
+            num_validators,
+            avg.as_secs_f64(),
+            max.as_secs_f64()
+        ));
+        ensure!(
+            max <= self.max_latency,
+            "An epoch change with {} validators took {:.2}s, more than the threshold of {:.2}s",
+            num_validators,
+            max.as_secs_f64(),
+            self.max_latency.as_secs_f64()
+        );
+        Ok(())
+    }
+}
+
+/// Waits for the validator to commit in `epoch`, i.e. for its latest ledger info to be of that
+/// epoch, which comes after the ledger info ending the previous one
+async fn wait_for_epoch(
+    name: &str,
+    client: &RestClient,
+    epoch: u64,
+    deadline: Instant,
+) -> Result<()> {
+    loop {
+        if let Ok(resp) = client.get_ledger_information().await {
+            if resp.into_inner().epoch >= epoch {
+                return Ok(());
+            }
+        }
+        if Instant::now() > deadline {
+            bail!("{} did not commit in epoch {} in time", name, epoch);
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
@@ -2,8 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::generate_traffic;
-use forge::{NetworkContext, NetworkTest, Result, Test};
-use tokio::time::Duration;
+use anyhow::{anyhow, ensure};
+use aptos_sdk::transaction_builder::TransactionFactory;
+use forge::{NetworkContext, NetworkTest, NodeExt, Result, Test, TxnEmitter};
+use rand::SeedableRng;
+use tokio::{runtime::Runtime, time::Duration};
 
 pub struct NonZeroGasPrice;
 
@@ -30,3 +33,108 @@ impl NetworkTest for NonZeroGasPrice {
         Ok(())
     }
 }
+
+/// Saturates the validators with one emit job per gas price, all running at the same time, and
+/// checks that mempool's priority ordering lets the highest priced transactions commit faster
+/// than the lowest priced ones. Reports the latencies of every gas price as a latency-vs-price
+/// curve.
+pub struct GasPriceMarket {
+    /// How long the jobs run together
+    pub duration: Duration,
+    /// Gas prices of the jobs, in increasing order
+    pub gas_prices: &'static [u64],
+}
+
+impl Test for GasPriceMarket {
+    fn name(&self) -> &'static str {
+        "gas-price-market"
+    }
+}
+
+impl NetworkTest for GasPriceMarket {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        ensure!(
+            self.gas_prices.len() >= 2,
+            "A gas price market needs at least two gas prices"
+        );
+        ensure!(
+            self.gas_prices.windows(2).all(|w| w[0] < w[1]),
+            "Gas prices are required to be increasing"
+        );
+
+        let rt = Runtime::new()?;
+        let rng = SeedableRng::from_rng(ctx.core().rng())?;
+        let validator_clients = ctx
+            .swarm()
+            .validators()
+            .map(|n| n.rest_client())
+            .collect::<Vec<_>>();
+        let chain_info = ctx.swarm().chain_info();
+        let transaction_factory =
+            TransactionFactory::new(chain_info.chain_id).with_gas_unit_price(1);
+        let mut emitter = TxnEmitter::new(
+            chain_info.root_account,
+            validator_clients
+                .first()
+                .ok_or_else(|| anyhow!("No validator in the swarm"))?
+                .clone(),
+            transaction_factory,
+            rng,
+        );
+
+        let mut jobs = vec![];
+        for gas_price in self.gas_prices {
+            let req = ctx
+                .global_job
+                .clone()
+                .rest_clients(validator_clients.clone())
+                .gas_price(*gas_price);
+            jobs.push(rt.block_on(emitter.start_job(req))?);
+        }
+        // The jobs start one after the other, so only count what they do once all of them run
+        let baselines = jobs
+            .iter()
+            .map(|job| emitter.peek_job_stats(job))
+            .collect::<Vec<_>>();
+        std::thread::sleep(self.duration);
+        let mut latencies = vec![];
+        for ((job, baseline), gas_price) in jobs.into_iter().zip(baselines).zip(self.gas_prices) {
+            let stats = &rt.block_on(emitter.stop_job(job)) - &baseline;
+            let p50_latency = stats.latency_histogram.percentile(50.0);
+            let p99_latency = stats.latency_histogram.percentile(99.0);
+            latencies.push((*gas_price, p50_latency, p99_latency, stats.expired));
+            ctx.report.report_txn_stats(
+                format!("{}-{}", self.name(), gas_price),
+                stats,
+                self.duration,
+            );
+        }
+
+        let curve = latencies
+            .iter()
+            .map(|(gas_price, p50, p99, expired)| {
+                format!(
+                    "gas price {}: {} ms p50 / {} ms p99 latency, {} expired",
+                    gas_price, p50, p99, expired
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        ctx.report
+            .report_text(format!("{} latency curve : {}", self.name(), curve));
+
+        let (lowest_price, lowest_p50, _, _) = latencies[0];
+        let (highest_price, highest_p50, _, _) = latencies[latencies.len() - 1];
+        ensure!(
+            highest_p50 > 0 && highest_p50 < lowest_p50,
+            "Transactions with gas price {} should commit faster than with gas price {}, \
+            but their p50 latency is {} ms against {} ms",
+            highest_price,
+            lowest_price,
+            highest_p50,
+            lowest_p50
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,255 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{bail, ensure, Context};
+use aptos_config::config::{
+    Identity, NodeConfig, OnDiskStorageConfig, SafetyRulesService, SecureBackend, WaypointConfig,
+};
+use aptos_genesis_tool::{layout::Layout, storage_helper::StorageHelper};
+use aptos_global_constants::{
+    APTOS_ROOT_KEY, FULLNODE_NETWORK_KEY, OWNER_ACCOUNT, VALIDATOR_NETWORK_KEY,
+};
+use aptos_sdk::types::{chain_id::ChainId, transaction::Transaction};
+use aptos_secure_storage::CryptoStorage;
+use forge::{AdminContext, AdminTest, LocalFactory, Result, Swarm, SwarmExt, Test};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+use tempfile::TempDir;
+use tokio::runtime::Runtime;
+
+const ROOT_NS: &str = "root";
+const SHARED_NS_SUFFIX: &str = "_shared";
+/// How long the nodes booted from the genesis get to catch up with each other
+const CATCHUP_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Runs a genesis ceremony with the genesis tool, the way operators do it, from the layout to the
+/// verification of the genesis by each operator, and checks that the genesis it produces boots a
+/// local swarm.
+///
+/// The ceremony doesn't involve the swarm of the test: the genesis boots a new local swarm running
+/// the `aptos-node` of the workspace, so the test builds it when it isn't built yet.
+pub struct GenesisCeremony {
+    pub num_validators: usize,
+}
+
+impl Test for GenesisCeremony {
+    fn name(&self) -> &'static str {
+        "genesis-ceremony"
+    }
+
+    fn exclusive(&self) -> bool {
+        false
+    }
+}
+
+impl AdminTest for GenesisCeremony {
+    fn run<'t>(&self, ctx: &mut AdminContext<'t>) -> Result<()> {
+        ensure!(self.num_validators > 0, "The ceremony needs validators");
+        let dir = TempDir::new()?;
+        let helper = StorageHelper::new();
+
+        // The validator and fullnode addresses go on chain during the ceremony, so the configs
+        // of the nodes, with their ports, have to exist first
+        let mut validators = (0..self.num_validators)
+            .map(|i| {
+                let directory = dir.path().join(i.to_string());
+                fs::create_dir_all(&directory)?;
+                let mut config = NodeConfig::default_for_validator();
+                config.set_data_dir(directory.clone());
+                config.randomize_ports();
+                Ok((directory, config))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // Step 1) Upload the layout, which maps the roles to the namespaces of their owners
+        let layout = Layout {
+            operators: (0..self.num_validators)
+                .map(|i| shared_ns(&operator_ns(i)))
+                .collect(),
+            owners: (0..self.num_validators)
+                .map(|i| shared_ns(&owner_ns(i)))
+                .collect(),
+            aptos_root: shared_ns(ROOT_NS),
+        };
+        let layout_path = dir.path().join("layout.toml");
+        fs::write(&layout_path, layout.to_toml()?)?;
+        helper.set_layout(path_str(&layout_path))?;
+
+        // Step 2) Upload the Move modules
+        let modules_dir = dir.path().join("modules");
+        fs::create_dir_all(&modules_dir)?;
+        for (i, module) in cached_framework_packages::module_blobs().iter().enumerate() {
+            fs::write(modules_dir.join(format!("{}.mv", i)), module)?;
+        }
+        helper.set_move_modules(path_str(&modules_dir))?;
+
+        // Step 3) Upload the root key
+        let mut storage_idx = 0;
+        helper.initialize_by_idx(ROOT_NS.into(), storage_idx);
+        helper.aptos_root_key(ROOT_NS, &shared_ns(ROOT_NS))?;
+
+        // Step 4) Upload the owner and operator keys
+        for i in 0..self.num_validators {
+            for ns in [owner_ns(i), operator_ns(i)] {
+                storage_idx += 1;
+                helper.initialize_by_idx(ns, storage_idx);
+            }
+            helper.owner_key(&owner_ns(i), &shared_ns(&owner_ns(i)))?;
+            helper.operator_key(&operator_ns(i), &shared_ns(&operator_ns(i)))?;
+        }
+
+        // Step 5) Each owner picks its operator
+        for i in 0..self.num_validators {
+            helper.set_operator(&shared_ns(&operator_ns(i)), &shared_ns(&owner_ns(i)))?;
+        }
+
+        // Step 6) Each operator uploads the signed config of its validator
+        for (i, (_, config)) in validators.iter().enumerate() {
+            helper.validator_config(
+                &shared_ns(&owner_ns(i)),
+                config
+                    .validator_network
+                    .as_ref()
+                    .unwrap()
+                    .listen_address
+                    .clone(),
+                config.full_node_networks[0].listen_address.clone(),
+                ChainId::test(),
+                &operator_ns(i),
+                &shared_ns(&operator_ns(i)),
+            )?;
+        }
+
+        // Step 7) Produce the genesis and its waypoint
+        let genesis_path = dir.path().join("genesis.blob");
+        let genesis = helper.genesis(ChainId::test(), &genesis_path)?;
+        let waypoint = helper.create_waypoint(ChainId::test())?;
+
+        // Step 8) Each operator inserts the waypoint and verifies the genesis against its storage
+        for i in 0..self.num_validators {
+            helper.insert_waypoint(&operator_ns(i), waypoint)?;
+            let output = helper.verify_genesis(&operator_ns(i), &genesis_path)?;
+            if output.contains("MISMATCH") {
+                bail!(
+                    "The genesis doesn't match the storage of {}:\n{}",
+                    operator_ns(i),
+                    output
+                );
+            }
+        }
+
+        // Each validator gets its own copy of the storage, as nodes sharing one would overwrite
+        // each other's safety data
+        for (i, (directory, config)) in validators.iter_mut().enumerate() {
+            let storage_path = directory.join("secure-storage.json");
+            fs::copy(helper.path_string(), &storage_path)?;
+            write_validator_config(
+                config,
+                directory,
+                storage_path,
+                operator_ns(i),
+                &genesis,
+                &genesis_path,
+            )?;
+        }
+        let root_key = helper
+            .storage(ROOT_NS.into())
+            .export_private_key(APTOS_ROOT_KEY)?;
+
+        let factory = LocalFactory::from_workspace()?;
+        let mut swarm = factory.swarm_builder().build_with_genesis(
+            ctx.rng(),
+            genesis,
+            waypoint,
+            root_key,
+            validators
+                .into_iter()
+                .enumerate()
+                .map(|(i, (directory, _))| (i.to_string(), directory))
+                .collect(),
+        )?;
+        let runtime = Runtime::new()?;
+        runtime
+            .block_on(swarm.launch())
+            .with_context(|| format!("Swarm logs can be found here: {}", swarm.logs_location()))?;
+        runtime.block_on(swarm.wait_for_all_nodes_to_catchup(Instant::now() + CATCHUP_TIMEOUT))?;
+
+        for (name, client) in swarm.named_rest_clients() {
+            let state = runtime
+                .block_on(client.get_ledger_information())?
+                .into_inner();
+            ensure!(
+                state.chain_id == ChainId::test().id(),
+                "{} runs chain {} instead of {}",
+                name,
+                state.chain_id,
+                ChainId::test()
+            );
+        }
+
+        ctx.report.report_text(format!(
+            "{} : the genesis of a ceremony of {} validators booted a swarm, at waypoint {}",
+            self.name(),
+            self.num_validators,
+            waypoint
+        ));
+        Ok(())
+    }
+}
+
+/// Points the config of the validator at its copy of the ceremony's storage, under the namespace
+/// of its operator, and at the genesis, then saves it in `directory`
+fn write_validator_config(
+    config: &mut NodeConfig,
+    directory: &Path,
+    storage_path: PathBuf,
+    namespace: String,
+    genesis: &Transaction,
+    genesis_path: &Path,
+) -> Result<()> {
+    let mut storage_config = OnDiskStorageConfig::default();
+    storage_config.path = storage_path;
+    storage_config.namespace = Some(namespace);
+    storage_config.set_data_dir(directory.to_path_buf());
+    let backend = SecureBackend::OnDiskStorage(storage_config);
+
+    config.validator_network.as_mut().unwrap().identity = Identity::from_storage(
+        VALIDATOR_NETWORK_KEY.to_owned(),
+        OWNER_ACCOUNT.to_owned(),
+        backend.clone(),
+    );
+    for network in &mut config.full_node_networks {
+        network.identity = Identity::from_storage(
+            FULLNODE_NETWORK_KEY.to_owned(),
+            OWNER_ACCOUNT.to_owned(),
+            backend.clone(),
+        );
+    }
+    config.consensus.safety_rules.service = SafetyRulesService::Thread;
+    config.consensus.safety_rules.backend = backend.clone();
+    config.base.waypoint = WaypointConfig::FromStorage(backend);
+    config.execution.genesis = Some(genesis.clone());
+    config.execution.genesis_file_location = genesis_path.to_path_buf();
+
+    config.save(directory.join("node.yaml"))?;
+    Ok(())
+}
+
+fn owner_ns(index: usize) -> String {
+    format!("owner_{}", index)
+}
+
+fn operator_ns(index: usize) -> String {
+    format!("operator_{}", index)
+}
+
+fn shared_ns(ns: &str) -> String {
+    format!("{}{}", ns, SHARED_NS_SUFFIX)
+}
+
+fn path_str(path: &Path) -> &str {
+    path.to_str().expect("Unable to parse path")
+}
@@ -1,29 +1,50 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod clock_skew_test;
 pub mod compatibility_test;
+pub mod epoch_change_latency_test;
 pub mod fixed_tps_test;
 pub mod gas_price_test;
+pub mod genesis_ceremony_test;
 pub mod partial_nodes_down_test;
+pub mod partition_recovery_test;
 pub mod performance_test;
+pub mod quorum_loss_recovery_test;
+pub mod random_restart_fuzzer;
 pub mod reconfiguration_test;
+pub mod safety_rules_sidecar_test;
+pub mod slow_disk_test;
+pub mod soak_test;
 pub mod state_sync_performance;
+pub mod twin_validator_test;
+pub mod validator_key_rotation_test;
+pub mod validator_rotation_test;
+pub mod validator_set_growth_test;
+pub mod zone_failure_test;
 
 use anyhow::ensure;
+use aptos_rest_client::Client as RestClient;
 use aptos_sdk::{transaction_builder::TransactionFactory, types::PeerId};
 use forge::{NetworkContext, NodeExt, Result, TxnEmitter, TxnStats, Version};
-use rand::SeedableRng;
+use rand::{rngs::StdRng, SeedableRng};
 use std::{
     convert::TryInto,
     time::{Duration, Instant},
 };
-use tokio::runtime::Runtime;
+use tokio::{runtime::Runtime, task::JoinHandle};
 
+/// Coins of the root account of the traffic started by `start_background_traffic`
+const BACKGROUND_ROOT_ACCOUNT_COINS: u64 = 1_000_000_000;
+
+/// Upgrades the validators to `version`, returning how long it took until all of them were
+/// healthy again
 async fn batch_update(
     ctx: &mut NetworkContext<'_>,
     validators_to_update: &[PeerId],
     version: &Version,
-) -> Result<()> {
+) -> Result<Duration> {
+    let start = Instant::now();
     for validator in validators_to_update {
         ctx.swarm().upgrade_validator(*validator, version)?;
     }
@@ -38,7 +59,28 @@ async fn batch_update(
             .await?;
     }
 
-    Ok(())
+    Ok(start.elapsed())
+}
+
+/// Updates the validators which don't run `version` yet to it, returning how long it took until
+/// all of them were healthy again, or `None` when they all ran it already
+async fn update_to_version(
+    ctx: &mut NetworkContext<'_>,
+    validators: &[PeerId],
+    version: &Version,
+) -> Result<Option<Duration>> {
+    let validators_to_update = ctx
+        .swarm()
+        .validators()
+        .filter(|v| validators.contains(&v.peer_id()) && v.version() != *version)
+        .map(|v| v.peer_id())
+        .collect::<Vec<_>>();
+    if validators_to_update.is_empty() {
+        return Ok(None);
+    }
+    batch_update(ctx, &validators_to_update, version)
+        .await
+        .map(Some)
 }
 
 pub fn generate_traffic<'t>(
@@ -51,29 +93,112 @@ pub fn generate_traffic<'t>(
     ensure!(gas_price > 0, "gas_price is required to be non zero");
     let rt = Runtime::new()?;
     let rng = SeedableRng::from_rng(ctx.core().rng())?;
-    let validator_clients = ctx
-        .swarm()
-        .validators()
-        .filter(|v| validators.contains(&v.peer_id()))
-        .map(|n| n.rest_client())
-        .collect::<Vec<_>>();
+    // The accounts of the earlier tests against the swarm, or else those saved by earlier runs
+    // against its chain. Failing to reuse or save accounts only costs minting new ones.
+    let pooled_accounts = ctx.swarm().account_pool().take();
+    let account_keys = if pooled_accounts.is_empty() {
+        rt.block_on(ctx.swarm().emitter_account_keys())
+            .unwrap_or_else(|e| {
+                println!("Failed to load the emitter accounts of earlier runs: {}", e);
+                vec![]
+            })
+    } else {
+        vec![]
+    };
+    let clients = traffic_clients(ctx, validators)?;
     let mut emit_job_request = ctx.global_job.clone();
     let chain_info = ctx.swarm().chain_info();
     let transaction_factory = TransactionFactory::new(chain_info.chain_id).with_gas_unit_price(1);
     let mut emitter = TxnEmitter::new(
         chain_info.root_account,
-        validator_clients[0].clone(),
+        clients[0].clone(),
         transaction_factory,
         rng,
     );
 
-    emit_job_request = emit_job_request
-        .rest_clients(validator_clients)
-        .gas_price(gas_price);
+    emit_job_request = emit_job_request.rest_clients(clients).gas_price(gas_price);
     if let Some(target_tps) = fixed_tps {
         emit_job_request = emit_job_request.fixed_tps(target_tps.try_into().unwrap());
     }
-    let stats = rt.block_on(emitter.emit_txn_for(duration, emit_job_request))?;
+    rt.block_on(emitter.add_accounts(pooled_accounts));
+    rt.block_on(emitter.reuse_accounts(account_keys));
+    let stats = rt.block_on(emitter.emit_txn_for(duration, emit_job_request));
+    let accounts = emitter.into_accounts();
+    if let Err(e) = rt.block_on(ctx.swarm().save_emitter_accounts(&accounts)) {
+        println!("Failed to save the emitter accounts: {}", e);
+    }
+    ctx.swarm().account_pool().put_back(accounts);
+
+    stats
+}
+
+/// Starts `duration` of traffic to the validators in the background, so that the test can change
+/// the swarm meanwhile, e.g. restart nodes, and returns the handle to await its stats with.
+///
+/// The traffic can't borrow the root account of the swarm while the test uses it, so it gets a
+/// root account of its own, delegated the minting of coins, and mints new accounts rather than
+/// taking those of the account pool.
+pub fn start_background_traffic(
+    ctx: &mut NetworkContext<'_>,
+    validators: &[PeerId],
+    duration: Duration,
+    gas_price: u64,
+) -> Result<JoinHandle<Result<TxnStats>>> {
+    ensure!(gas_price > 0, "gas_price is required to be non zero");
+    let rt = Runtime::new()?;
+    let mut rng = StdRng::from_rng(ctx.core().rng())?;
+    let emitter_rng = StdRng::from_rng(ctx.core().rng())?;
+    let clients = traffic_clients(ctx, validators)?;
+    let emit_job_request = ctx
+        .global_job
+        .clone()
+        .rest_clients(clients.clone())
+        .gas_price(gas_price);
+    let chain_info = ctx.swarm().chain_info();
+    let transaction_factory = TransactionFactory::new(chain_info.chain_id).with_gas_unit_price(1);
+    let mut root_account = rt.block_on(
+        chain_info
+            .into_aptos_public_info()
+            .create_delegated_root_account(&mut rng, BACKGROUND_ROOT_ACCOUNT_COINS),
+    )?;
+
+    ctx.spawn_background(async move {
+        let mut emitter = TxnEmitter::new(
+            &mut root_account,
+            clients[0].clone(),
+            transaction_factory,
+            emitter_rng,
+        );
+        emitter.emit_txn_for(duration, emit_job_request).await
+    })
+}
 
-    Ok(stats)
+/// The clients to emit the traffic of the test to the validators with, or to their fullnodes if
+/// the test emits to fullnodes
+fn traffic_clients(ctx: &mut NetworkContext<'_>, validators: &[PeerId]) -> Result<Vec<RestClient>> {
+    let clients = if ctx.emit_to_fullnodes() {
+        // The fullnode of a validator goes by the peer id of the validator
+        ctx.swarm()
+            .full_nodes()
+            .filter(|n| validators.contains(&n.peer_id()))
+            .map(|n| n.rest_client())
+            .collect::<Vec<_>>()
+    } else {
+        ctx.swarm()
+            .validators()
+            .filter(|v| validators.contains(&v.peer_id()))
+            .map(|n| n.rest_client())
+            .collect::<Vec<_>>()
+    };
+    ensure!(
+        !clients.is_empty(),
+        "No {} to emit traffic to among {:?}",
+        if ctx.emit_to_fullnodes() {
+            "fullnode of a validator"
+        } else {
+            "validator"
+        },
+        validators
+    );
+    Ok(clients)
 }
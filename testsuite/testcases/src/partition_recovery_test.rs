@@ -0,0 +1,122 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{anyhow, format_err};
+use forge::{ChaosSpec, NetworkContext, NetworkTest, NodeExt, Result, Test, Validator};
+use std::{
+    collections::HashMap,
+    thread,
+    time::{Duration, Instant},
+};
+use tokio::runtime::Runtime;
+
+const PARTITION_DURATION: Duration = Duration::from_secs(10);
+const RECOVERY_TIMEOUT: Duration = Duration::from_secs(120);
+const CATCH_UP_RESULTS: &[&str] = &["caught_up", "fallback", "state_sync"];
+
+/// Partitions a validator from the others for 10 seconds, and reports how long it takes it to
+/// catch up with the ledger of the others once the partition heals, along with whether consensus
+/// caught up from its peers or fell back to state sync.
+pub struct PartitionRecovery;
+
+impl Test for PartitionRecovery {
+    fn name(&self) -> &'static str {
+        "partition-recovery"
+    }
+}
+
+impl NetworkTest for PartitionRecovery {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        let runtime = Runtime::new()?;
+        let mut validators = ctx
+            .swarm()
+            .validators()
+            .map(|v| v.peer_id())
+            .collect::<Vec<_>>();
+        let partitioned = validators
+            .pop()
+            .ok_or_else(|| anyhow!("No validator in the swarm"))?;
+        let partitioned_validator = ctx.swarm().validator(partitioned).unwrap();
+        let partitioned_client = partitioned_validator.rest_client();
+        let catch_ups_before = runtime.block_on(catch_up_counts(partitioned_validator))?;
+        let client = ctx
+            .swarm()
+            .validator(
+                *validators
+                    .first()
+                    .ok_or_else(|| anyhow!("Partition recovery needs 2 validators"))?,
+            )
+            .unwrap()
+            .rest_client();
+
+        let chaos = ChaosSpec::NetworkPartition {
+            group_a: vec![partitioned],
+            group_b: validators,
+        };
+        runtime.block_on(ctx.swarm().inject_chaos(chaos.clone()))?;
+        thread::sleep(PARTITION_DURATION);
+        runtime.block_on(ctx.swarm().remove_chaos(chaos))?;
+
+        // The partitioned validator has recovered once it reaches the version the others had
+        // when the partition healed
+        let healed = Instant::now();
+        let target_version = runtime
+            .block_on(client.get_ledger_information())?
+            .into_inner()
+            .version;
+        let recovery_time = runtime.block_on(async {
+            loop {
+                if let Ok(resp) = partitioned_client.get_ledger_information().await {
+                    if resp.into_inner().version >= target_version {
+                        return Ok(healed.elapsed());
+                    }
+                }
+                if healed.elapsed() > RECOVERY_TIMEOUT {
+                    return Err(format_err!(
+                        "partitioned validator did not reach version {} within {:?}",
+                        target_version,
+                        RECOVERY_TIMEOUT
+                    ));
+                }
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        })?;
+
+        let catch_ups =
+            runtime.block_on(catch_up_counts(ctx.swarm().validator(partitioned).unwrap()))?;
+        let catch_ups = CATCH_UP_RESULTS
+            .iter()
+            .map(|result| {
+                format!(
+                    "{} {}",
+                    catch_ups[result] - catch_ups_before[result],
+                    result
+                )
+            })
+            .collect::<Vec<_>>();
+        ctx.report.report_recovery_time(self.name(), recovery_time);
+        ctx.report.report_text(format!(
+            "{} : validator recovered {:.1}s after a {}s partition healed, catch ups: {}",
+            self.name(),
+            recovery_time.as_secs_f64(),
+            PARTITION_DURATION.as_secs(),
+            catch_ups.join(", ")
+        ));
+        Ok(())
+    }
+}
+
+/// Number of times consensus of the validator fell behind, by how it caught up
+async fn catch_up_counts(validator: &dyn Validator) -> Result<HashMap<&'static str, i64>> {
+    let mut counts = HashMap::new();
+    for result in CATCH_UP_RESULTS {
+        let mut fields = HashMap::new();
+        fields.insert("result".to_string(), result.to_string());
+        let count = validator
+            .get_metric_with_fields("aptos_consensus_catch_up_count", fields)
+            .await?
+            .unwrap_or_default();
+        counts.insert(*result, count);
+    }
+    Ok(counts)
+}
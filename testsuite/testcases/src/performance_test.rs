@@ -2,8 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::generate_traffic;
-use forge::{NetworkContext, NetworkTest, Result, Test};
-use tokio::time::Duration;
+use anyhow::ensure;
+use forge::{NetworkContext, NetworkTest, Result, StorageMetrics, Test};
+use std::mem;
+use tokio::{runtime::Runtime, time::Duration};
 
 pub struct PerformanceBenchmark;
 
@@ -22,11 +24,153 @@ impl NetworkTest for PerformanceBenchmark {
             .map(|v| v.peer_id())
             .collect::<Vec<_>>();
 
+        let storage_before = StorageSnapshot::take(ctx);
         // Generate some traffic
         let txn_stat = generate_traffic(ctx, &all_validators, duration, 1, None)?;
         ctx.report
             .report_txn_stats(self.name().to_string(), txn_stat, duration);
+        let storage_after = StorageSnapshot::take(ctx);
 
+        // The storage metrics only add to the report, so the benchmark doesn't fail without them
+        let storage_growth = storage_before.and_then(|before| {
+            let after = storage_after?;
+            report_storage_growth(ctx, self.name(), &before, &after)
+        });
+        if let Err(e) = storage_growth {
+            let msg = format!("{} : storage metrics missing: {}", self.name(), e);
+            println!("{}", msg);
+            ctx.report.report_text(msg);
+        }
+
+        Ok(())
+    }
+}
+
+/// The ledger version of the chain along with the disk usage of each validator
+struct StorageSnapshot {
+    version: u64,
+    validators: Vec<StorageMetrics>,
+}
+
+impl StorageSnapshot {
+    fn take(ctx: &mut NetworkContext<'_>) -> Result<Self> {
+        let version = Runtime::new()?
+            .block_on(
+                ctx.swarm()
+                    .chain_info()
+                    .rest_client()
+                    .get_ledger_information(),
+            )?
+            .into_inner()
+            .version;
+        let validators = ctx
+            .swarm()
+            .validators()
+            .map(|v| v.storage_metrics())
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            version,
+            validators,
+        })
+    }
+}
+
+/// Reports how many bytes the storage of the validators grew by per transaction committed between
+/// the two snapshots, on average and for the validator which grew the most, so that storage bloat
+/// shows up in the report
+fn report_storage_growth(
+    ctx: &mut NetworkContext<'_>,
+    test_name: &str,
+    before: &StorageSnapshot,
+    after: &StorageSnapshot,
+) -> Result<()> {
+    let num_txns = after.version.saturating_sub(before.version);
+    ensure!(num_txns > 0, "No transaction was committed");
+    // The DB shrinks when it compacts, so the growth of a validator can be negative
+    let growths = before
+        .validators
+        .iter()
+        .zip(&after.validators)
+        .map(|(before, after)| {
+            (
+                (after.db_bytes as f64 - before.db_bytes as f64) / num_txns as f64,
+                (after.data_dir_bytes as f64 - before.data_dir_bytes as f64) / num_txns as f64,
+            )
+        })
+        .collect::<Vec<_>>();
+    let avg_db = growths.iter().map(|(db, _)| db).sum::<f64>() / growths.len() as f64;
+    let avg_data_dir = growths.iter().map(|(_, dir)| dir).sum::<f64>() / growths.len() as f64;
+    let max_db = growths
+        .iter()
+        .map(|(db, _)| *db)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let avg_db_size =
+        after.validators.iter().map(|m| m.db_bytes).sum::<u64>() / after.validators.len() as u64;
+
+    ctx.report
+        .report_metric(test_name, "avg_db_bytes_per_txn", avg_db);
+    ctx.report
+        .report_metric(test_name, "max_db_bytes_per_txn", max_db);
+    ctx.report
+        .report_metric(test_name, "avg_data_dir_bytes_per_txn", avg_data_dir);
+    ctx.report.report_text(format!(
+        "{} : storage grew by {:.0} bytes per txn in the DB and {:.0} in the data directory on average over {} txns, to a DB of {} MiB",
+        test_name,
+        avg_db,
+        avg_data_dir,
+        num_txns,
+        avg_db_size / (1024 * 1024)
+    ));
+    Ok(())
+}
+
+/// Emits transfers which all go to a single account, so that every transaction of a block writes
+/// the same balance, and reports the throughput next to that of the usual transfers spread over
+/// many accounts. The ratio of the two tells how well execution copes with conflicting
+/// transactions.
+pub struct HotAccountBenchmark {
+    /// How long each of the two kinds of traffic runs
+    pub duration: Duration,
+}
+
+impl Test for HotAccountBenchmark {
+    fn name(&self) -> &'static str {
+        "hot account"
+    }
+}
+
+impl NetworkTest for HotAccountBenchmark {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        let all_validators = ctx
+            .swarm()
+            .validators()
+            .map(|v| v.peer_id())
+            .collect::<Vec<_>>();
+
+        let spread_stats = generate_traffic(ctx, &all_validators, self.duration, 1, None)?;
+        let spread_tps = spread_stats.rate(self.duration).committed;
+        ctx.report.report_txn_stats(
+            format!("{} (spread)", self.name()),
+            spread_stats,
+            self.duration,
+        );
+
+        let hot_job = ctx.global_job.clone().hot_account();
+        let global_job = mem::replace(&mut ctx.global_job, hot_job);
+        let hot_stats = generate_traffic(ctx, &all_validators, self.duration, 1, None);
+        ctx.global_job = global_job;
+        let hot_stats = hot_stats?;
+        let hot_tps = hot_stats.rate(self.duration).committed;
+        ctx.report
+            .report_txn_stats(self.name().to_string(), hot_stats, self.duration);
+
+        if spread_tps > 0 {
+            ctx.report.report_metric(
+                self.name(),
+                "contended_tps_ratio",
+                hot_tps as f64 / spread_tps as f64,
+            );
+        }
         Ok(())
     }
 }
@@ -0,0 +1,161 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::generate_traffic;
+use anyhow::{bail, ensure};
+use aptos_rest_client::{aptos_api_types::HashValue, Client as RestClient};
+use forge::{NetworkContext, NetworkTest, NodeExt, Result, SwarmExt, Test};
+use rand::seq::SliceRandom;
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+use tokio::runtime::Runtime;
+
+/// How long traffic runs before the validators are stopped, so that there are transactions which
+/// could be lost
+const TRAFFIC_DURATION: Duration = Duration::from_secs(30);
+/// How long the blocks in flight when the validators are stopped get to commit
+const SETTLE_TIME: Duration = Duration::from_secs(5);
+/// How long the network gets to commit again once the validators are restarted
+const RECOVERY_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Stops f + 1 validators, one more than the network tolerates, so that it loses liveness, and
+/// restarts them after `down_time`. Checks that nothing was committed while the quorum was lost
+/// and that all nodes still have the transactions committed before, and reports how long the
+/// network took to commit again from the moment the validators were restarted.
+pub struct QuorumLossRecovery {
+    /// How long the stopped validators stay down
+    pub down_time: Duration,
+}
+
+impl Test for QuorumLossRecovery {
+    fn name(&self) -> &'static str {
+        "quorum-loss-recovery"
+    }
+}
+
+impl NetworkTest for QuorumLossRecovery {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        let runtime = Runtime::new()?;
+        let all_validators = ctx
+            .swarm()
+            .validators()
+            .map(|v| v.peer_id())
+            .collect::<Vec<_>>();
+        if all_validators.len() < 4 {
+            bail!(
+                "losing the quorum while some validators stay up needs at least 4 of them, got {}",
+                all_validators.len()
+            );
+        }
+        let num_stopped = ctx.swarm().max_faulty_validators() + 1;
+
+        let txn_stat = generate_traffic(ctx, &all_validators, TRAFFIC_DURATION, 1, None)?;
+        ensure!(
+            txn_stat.committed > 0,
+            "No transaction was committed before the quorum was lost"
+        );
+
+        let stopped = all_validators
+            .choose_multiple(ctx.core().rng(), num_stopped)
+            .cloned()
+            .collect::<Vec<_>>();
+        let client = ctx
+            .swarm()
+            .validators()
+            .find(|v| !stopped.contains(&v.peer_id()))
+            .unwrap()
+            .rest_client();
+        for id in &stopped {
+            let validator = ctx.swarm().validator_mut(*id).unwrap();
+            println!("Node {} is going to stop", validator.name());
+            validator.stop()?;
+        }
+        thread::sleep(SETTLE_TIME);
+        let stalled_version = runtime
+            .block_on(client.get_ledger_information())?
+            .into_inner()
+            .version;
+        let stalled_root_hash = runtime.block_on(root_hash_at_version(&client, stalled_version))?;
+
+        thread::sleep(self.down_time);
+        let version = runtime
+            .block_on(client.get_ledger_information())?
+            .into_inner()
+            .version;
+        if version != stalled_version {
+            bail!(
+                "The chain committed from version {} to {} with {} of {} validators down",
+                stalled_version,
+                version,
+                num_stopped,
+                all_validators.len()
+            );
+        }
+
+        let restarted = Instant::now();
+        for id in &stopped {
+            let validator = ctx.swarm().validator_mut(*id).unwrap();
+            println!("Node {} is going to restart", validator.name());
+            runtime.block_on(validator.start())?;
+        }
+        let recovery_time = runtime.block_on(async {
+            loop {
+                if let Ok(resp) = client.get_ledger_information().await {
+                    if resp.into_inner().version > stalled_version {
+                        return Ok(restarted.elapsed());
+                    }
+                }
+                if restarted.elapsed() > RECOVERY_TIMEOUT {
+                    bail!(
+                        "The chain did not commit past version {} within {:?} of restarting the validators",
+                        stalled_version,
+                        RECOVERY_TIMEOUT
+                    );
+                }
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        })?;
+
+        // None of the transactions committed before the quorum was lost may have been dropped or
+        // replaced by any node
+        runtime.block_on(
+            ctx.swarm()
+                .wait_for_all_nodes_to_catchup(Instant::now() + RECOVERY_TIMEOUT),
+        )?;
+        for (name, client) in ctx.swarm().named_rest_clients() {
+            let root_hash = runtime.block_on(root_hash_at_version(&client, stalled_version))?;
+            if root_hash != stalled_root_hash {
+                bail!(
+                    "{} has root hash {} at version {} after recovering, instead of {}",
+                    name,
+                    root_hash,
+                    stalled_version,
+                    stalled_root_hash
+                );
+            }
+        }
+
+        ctx.report.report_recovery_time(self.name(), recovery_time);
+        ctx.report.report_text(format!(
+            "{} : committed again {:.1}s after restarting {} of {} validators, down for {}s, with all transactions up to version {} kept",
+            self.name(),
+            recovery_time.as_secs_f64(),
+            num_stopped,
+            all_validators.len(),
+            self.down_time.as_secs(),
+            stalled_version
+        ));
+        Ok(())
+    }
+}
+
+async fn root_hash_at_version(client: &RestClient, version: u64) -> Result<HashValue> {
+    Ok(client
+        .get_transaction_by_version(version)
+        .await?
+        .into_inner()
+        .transaction_info()?
+        .accumulator_root_hash)
+}
@@ -0,0 +1,162 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::start_background_traffic;
+use anyhow::{bail, ensure};
+use aptos_rest_client::Client as RestClient;
+use forge::{NetworkContext, NetworkTest, NodeExt, Result, SwarmExt, Test};
+use rand::{seq::SliceRandom, Rng};
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+use tokio::runtime::Runtime;
+
+/// The least time the validators of a round stay down, for the chain to have a chance to move on
+/// without them
+const MIN_DOWN_TIME: Duration = Duration::from_secs(5);
+/// How long the remaining validators get to commit something while the others are down
+const PROGRESS_TIMEOUT: Duration = Duration::from_secs(60);
+/// How long restarted validators, and in the end all nodes, get to catch up
+const RECOVERY_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Kills and restarts random validators at random intervals while traffic runs, asserting that
+/// the chain keeps committing while they are down and that all nodes converge on the same ledger
+/// in the end.
+///
+/// Each round kills at most a third of the validators minus one, the most that can be down
+/// without losing the quorum, so the test needs at least 4 validators.
+pub struct RandomRestartFuzzer {
+    /// How long traffic runs and validators keep being restarted
+    pub duration: Duration,
+    /// The longest the killed validators of a round stay down
+    pub max_down_time: Duration,
+    /// The longest wait between two rounds
+    pub max_restart_interval: Duration,
+}
+
+impl Test for RandomRestartFuzzer {
+    fn name(&self) -> &'static str {
+        "random-restart-fuzzer"
+    }
+}
+
+impl NetworkTest for RandomRestartFuzzer {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        ensure!(
+            self.max_down_time >= MIN_DOWN_TIME,
+            "max_down_time is required to be at least {:?}",
+            MIN_DOWN_TIME
+        );
+        let runtime = Runtime::new()?;
+        let all_validators = ctx
+            .swarm()
+            .validators()
+            .map(|v| v.peer_id())
+            .collect::<Vec<_>>();
+        let max_killed = ctx.swarm().max_faulty_validators();
+        if max_killed == 0 {
+            bail!(
+                "restarting validators without halting the chain needs at least 4 of them, got {}",
+                all_validators.len()
+            );
+        }
+
+        let traffic = start_background_traffic(ctx, &all_validators, self.duration, 1)?;
+        let start = Instant::now();
+        let mut num_rounds = 0;
+        let mut num_restarts = 0;
+        while start.elapsed() < self.duration {
+            let rng = ctx.core().rng();
+            let num_killed = rng.gen_range(1..=max_killed);
+            let killed = all_validators
+                .choose_multiple(rng, num_killed)
+                .cloned()
+                .collect::<Vec<_>>();
+            let down_time = rng.gen_range(MIN_DOWN_TIME..=self.max_down_time);
+            let restart_interval = rng.gen_range(Duration::ZERO..=self.max_restart_interval);
+
+            let client = ctx
+                .swarm()
+                .validators()
+                .find(|v| !killed.contains(&v.peer_id()))
+                .unwrap()
+                .rest_client();
+            let version_before = runtime
+                .block_on(client.get_ledger_information())?
+                .into_inner()
+                .version;
+            for id in &killed {
+                let validator = ctx.swarm().validator_mut(*id).unwrap();
+                println!("Killing {} for {:?}", validator.name(), down_time);
+                validator.stop()?;
+            }
+            thread::sleep(down_time);
+            let progress = runtime.block_on(wait_for_progress(
+                &client,
+                version_before,
+                Instant::now() + PROGRESS_TIMEOUT,
+            ));
+
+            for id in &killed {
+                let validator = ctx.swarm().validator_mut(*id).unwrap();
+                println!("Restarting {}", validator.name());
+                runtime.block_on(validator.start())?;
+            }
+            if let Err(e) = progress {
+                bail!(
+                    "The chain halted with {} of {} validators down: {}",
+                    killed.len(),
+                    all_validators.len(),
+                    e
+                );
+            }
+            let deadline = Instant::now() + RECOVERY_TIMEOUT;
+            for id in &killed {
+                runtime.block_on(
+                    ctx.swarm()
+                        .validator_mut(*id)
+                        .unwrap()
+                        .wait_until_healthy(deadline),
+                )?;
+            }
+            num_rounds += 1;
+            num_restarts += killed.len();
+            thread::sleep(restart_interval);
+        }
+
+        let txn_stat = runtime.block_on(traffic)??;
+        ensure!(
+            txn_stat.committed > 0,
+            "No transaction was committed while validators were restarted"
+        );
+        runtime.block_on(
+            ctx.swarm()
+                .wait_for_all_nodes_to_catchup(Instant::now() + RECOVERY_TIMEOUT),
+        )?;
+
+        let msg = format!(
+            "Restarted validators {} times over {} rounds",
+            num_restarts, num_rounds
+        );
+        println!("{}", msg);
+        ctx.report.report_text(msg);
+        ctx.report
+            .report_txn_stats(self.name().to_string(), txn_stat, self.duration);
+        Ok(())
+    }
+}
+
+/// Waits for the ledger of the node to go past `version`
+async fn wait_for_progress(client: &RestClient, version: u64, deadline: Instant) -> Result<()> {
+    loop {
+        let current = client.get_ledger_information().await?.into_inner().version;
+        if current > version {
+            return Ok(());
+        }
+        if Instant::now() > deadline {
+            bail!("stuck at version {}", current);
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
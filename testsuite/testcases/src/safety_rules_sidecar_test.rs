@@ -0,0 +1,114 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::generate_traffic;
+use anyhow::{anyhow, ensure};
+use aptos_sdk::types::PeerId;
+use forge::{NetworkContext, NetworkTest, NodeExt, Result, Swarm, SwarmExt, Test};
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+use tokio::runtime::Runtime;
+
+/// Blocks a validator proposed, each signed by its safety rules
+const PROPOSALS_METRIC: &str = "aptos_consensus_proposals_count";
+/// Requests served by safety rules, which are only in the metrics of the validator when safety
+/// rules run in its process
+const SAFETY_RULES_QUERIES_METRIC: &str = "aptos_safety_rules_queries";
+
+/// Runs traffic through a network where some validators run safety rules in a process of their
+/// own, e.g. the sidecar of the validator chart, and the others in-process. Checks that the
+/// network stays live and that validators of both kinds keep proposing, which they only do when
+/// their safety rules sign.
+pub struct SafetyRulesSidecarCompat {
+    pub duration: Duration,
+}
+
+impl Test for SafetyRulesSidecarCompat {
+    fn name(&self) -> &'static str {
+        "safety-rules-sidecar-compat"
+    }
+}
+
+impl NetworkTest for SafetyRulesSidecarCompat {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        let runtime = Runtime::new()?;
+        let validators = ctx
+            .swarm()
+            .validators()
+            .map(|v| v.peer_id())
+            .collect::<Vec<_>>();
+
+        let proposals_before = runtime.block_on(proposals(ctx.swarm(), &validators))?;
+        let stats = generate_traffic(ctx, &validators, self.duration, 1, None)?;
+        ctx.report
+            .report_txn_stats(self.name().to_string(), stats, self.duration);
+        runtime.block_on(
+            ctx.swarm()
+                .liveness_check(Instant::now() + Duration::from_secs(60)),
+        )?;
+        let proposals_after = runtime.block_on(proposals(ctx.swarm(), &validators))?;
+
+        let (mut in_process, mut separate) = (vec![], vec![]);
+        for validator in &validators {
+            if runtime.block_on(runs_safety_rules_in_process(ctx.swarm(), *validator))? {
+                in_process.push(*validator);
+            } else {
+                separate.push(*validator);
+            }
+        }
+        ensure!(
+            !in_process.is_empty() && !separate.is_empty(),
+            "Needs validators with safety rules in-process and in a process of their own, found \
+             {} and {}",
+            in_process.len(),
+            separate.len()
+        );
+
+        for (kind, validators) in [("in_process", &in_process), ("separate", &separate)] {
+            let mut total = 0;
+            for validator in validators {
+                let proposed = proposals_after[validator] - proposals_before[validator];
+                ensure!(
+                    proposed > 0,
+                    "Validator {} with {} safety rules didn't propose in {:?}",
+                    validator,
+                    kind,
+                    self.duration
+                );
+                total += proposed;
+            }
+            ctx.report
+                .report_metric(self.name(), &format!("{}_proposals", kind), total as f64);
+        }
+        Ok(())
+    }
+}
+
+async fn proposals(swarm: &dyn Swarm, validators: &[PeerId]) -> Result<HashMap<PeerId, i64>> {
+    let mut proposals = HashMap::new();
+    for validator in validators {
+        let count = swarm
+            .validator(*validator)
+            .ok_or_else(|| anyhow!("No validator {}", validator))?
+            .get_metric(PROPOSALS_METRIC)
+            .await?
+            .unwrap_or_default();
+        proposals.insert(*validator, count);
+    }
+    Ok(proposals)
+}
+
+async fn runs_safety_rules_in_process(swarm: &dyn Swarm, validator: PeerId) -> Result<bool> {
+    let validator = swarm
+        .validator(validator)
+        .ok_or_else(|| anyhow!("No validator {}", validator))?;
+    let mut fields = HashMap::new();
+    fields.insert("result".to_string(), "success".to_string());
+    let served = validator
+        .get_metric_with_fields(SAFETY_RULES_QUERIES_METRIC, fields)
+        .await?
+        .unwrap_or_default();
+    Ok(served > 0)
+}
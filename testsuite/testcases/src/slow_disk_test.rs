@@ -0,0 +1,135 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::generate_traffic;
+use anyhow::{anyhow, ensure, format_err};
+use forge::{NetworkContext, NetworkTest, NodeExt, Result, Test};
+use std::time::{Duration, Instant};
+use tokio::runtime::Runtime;
+
+/// How long the degraded validator gets to catch up with the others once its IO is restored
+const RECOVERY_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Throttles the IO of a validator, as a slow or faulty disk would, and checks that the others
+/// keep committing the traffic sent to them, at no less than `min_tps_pct` percent of the TPS
+/// they committed before. Then restores the IO of the validator and reports how long it takes it
+/// to catch up with the others.
+pub struct SlowDiskValidator {
+    /// IO operations per second the disk of the degraded validator is limited to
+    pub iops: u64,
+    /// Latency each IO operation of the degraded validator is delayed by
+    pub latency: Duration,
+    /// How long the traffic runs, before and while the validator is degraded
+    pub traffic_duration: Duration,
+    pub min_tps_pct: u64,
+}
+
+impl Test for SlowDiskValidator {
+    fn name(&self) -> &'static str {
+        "slow-disk-validator"
+    }
+}
+
+impl NetworkTest for SlowDiskValidator {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        let runtime = Runtime::new()?;
+        let mut validators = ctx
+            .swarm()
+            .validators()
+            .map(|v| v.peer_id())
+            .collect::<Vec<_>>();
+        ensure!(
+            validators.len() >= 4,
+            "The others can only commit without the degraded validator with >= 4 validators"
+        );
+        let degraded = validators
+            .pop()
+            .ok_or_else(|| anyhow!("No validator in the swarm"))?;
+        let degraded_client = ctx.swarm().validator(degraded).unwrap().rest_client();
+        let client = ctx.swarm().validator(validators[0]).unwrap().rest_client();
+
+        let baseline = generate_traffic(ctx, &validators, self.traffic_duration, 1, None)?;
+        let baseline_tps = baseline.committed / self.traffic_duration.as_secs();
+
+        println!(
+            "Throttling the IO of {} to {} IOPS with {}ms of latency",
+            degraded,
+            self.iops,
+            self.latency.as_millis()
+        );
+        ctx.swarm()
+            .validator_mut(degraded)
+            .unwrap()
+            .throttle_io(self.iops, self.latency)?;
+        // The IO is restored even when the traffic fails, so as not to degrade the later tests
+        let degraded_stats = generate_traffic(ctx, &validators, self.traffic_duration, 1, None);
+        let lag = runtime.block_on(async {
+            let version = client.get_ledger_information().await?.into_inner().version;
+            let degraded_version = degraded_client
+                .get_ledger_information()
+                .await?
+                .into_inner()
+                .version;
+            Ok::<_, anyhow::Error>(version.saturating_sub(degraded_version))
+        });
+        ctx.swarm().validator_mut(degraded).unwrap().restore_io()?;
+        let degraded_tps = degraded_stats?.committed / self.traffic_duration.as_secs();
+
+        // The degraded validator has recovered once it reaches the version the others had when
+        // its IO was restored
+        let restored = Instant::now();
+        let target_version = runtime
+            .block_on(client.get_ledger_information())?
+            .into_inner()
+            .version;
+        let recovery_time = runtime.block_on(async {
+            loop {
+                if let Ok(resp) = degraded_client.get_ledger_information().await {
+                    if resp.into_inner().version >= target_version {
+                        return Ok(restored.elapsed());
+                    }
+                }
+                if restored.elapsed() > RECOVERY_TIMEOUT {
+                    return Err(format_err!(
+                        "degraded validator did not reach version {} within {:?}",
+                        target_version,
+                        RECOVERY_TIMEOUT
+                    ));
+                }
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        })?;
+
+        ctx.report
+            .report_metric(self.name(), "baseline_tps", baseline_tps as f64);
+        ctx.report
+            .report_metric(self.name(), "degraded_tps", degraded_tps as f64);
+        // The degraded validator may still answer slowly enough for the query to fail
+        if let Ok(lag) = &lag {
+            ctx.report
+                .report_metric(self.name(), "degraded_validator_lag_versions", *lag as f64);
+        }
+        ctx.report.report_recovery_time(self.name(), recovery_time);
+        ctx.report.report_text(format!(
+            "{} : {} TPS with a validator limited to {} IOPS and {}ms of IO latency, down from {} TPS, the validator {}, and recovered {:.1}s after its IO was restored",
+            self.name(),
+            degraded_tps,
+            self.iops,
+            self.latency.as_millis(),
+            baseline_tps,
+            lag.map_or_else(
+                |e| format!("not answering ({})", e),
+                |lag| format!("{} versions behind", lag)
+            ),
+            recovery_time.as_secs_f64()
+        ));
+        ensure!(
+            degraded_tps * 100 >= baseline_tps * self.min_tps_pct,
+            "The validators committed {} TPS with a degraded validator, less than {}% of the {} TPS before",
+            degraded_tps,
+            self.min_tps_pct,
+            baseline_tps
+        );
+        Ok(())
+    }
+}
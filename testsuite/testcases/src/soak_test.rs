@@ -0,0 +1,280 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::generate_traffic;
+use anyhow::{anyhow, bail};
+use aptos_rest_client::Client as RestClient;
+use aptos_sdk::{
+    move_types::account_address::AccountAddress,
+    transaction_builder::TransactionFactory,
+    types::{LocalAccount, PeerId},
+};
+use forge::{
+    create_account_request, NetworkContext, NetworkTest, NodeExt, Result, SwarmExt, Test, TxnStats,
+};
+use futures::future::try_join_all;
+use move_deps::{
+    move_binary_format::file_format::empty_module, move_core_types::identifier::Identifier,
+};
+use rand::{rngs::StdRng, SeedableRng};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tokio::runtime::Runtime;
+
+/// Coins given to the account creating accounts and publishing modules, enough for days of gas
+const SIDE_TRAFFIC_COINS: u64 = 100_000_000_000;
+
+/// Runs mixed traffic against the validators for hours: transfers from the transaction emitter,
+/// with accounts created and modules published alongside them. At every checkpoint it checks the
+/// health of the validators, reports their memory and CPU usage and the TPS since the previous
+/// checkpoint, and fails if a validator went down or the TPS stays degraded.
+pub struct SoakTest {
+    /// How long the traffic runs
+    pub duration: Duration,
+    /// Time between two checkpoints
+    pub checkpoint_interval: Duration,
+    /// A checkpoint is degraded when its TPS is below this percentage of the first checkpoint's
+    pub min_tps_pct: u64,
+    /// Number of degraded checkpoints in a row after which the TPS degradation is sustained
+    pub max_degraded_checkpoints: usize,
+}
+
+impl Test for SoakTest {
+    fn name(&self) -> &'static str {
+        "soak"
+    }
+}
+
+impl NetworkTest for SoakTest {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        let runtime = Runtime::new()?;
+        let validators = ctx
+            .swarm()
+            .validators()
+            .map(|v| v.peer_id())
+            .collect::<Vec<_>>();
+        let client = ctx
+            .swarm()
+            .validators()
+            .next()
+            .ok_or_else(|| anyhow!("No validator in the swarm"))?
+            .rest_client();
+        let mut rng = StdRng::from_rng(ctx.core().rng())?;
+        let mut side_account = LocalAccount::generate(&mut rng);
+        let txn_factory = {
+            let mut public_info = ctx.swarm().chain_info().into_aptos_public_info();
+            runtime.block_on(public_info.create_user_account(side_account.public_key()))?;
+            runtime.block_on(public_info.mint(side_account.address(), SIDE_TRAFFIC_COINS))?;
+            public_info.transaction_factory()
+        };
+
+        let msg = format!(
+            "Soaking {} validators for {}s, with a checkpoint every {}s",
+            validators.len(),
+            self.duration.as_secs(),
+            self.checkpoint_interval.as_secs()
+        );
+        println!("{}", msg);
+        ctx.report.report_text(msg);
+
+        let start = Instant::now();
+        let mut checkpoint = 0u32;
+        let mut total_stats = TxnStats::default();
+        let mut side_stats = SideTrafficStats::default();
+        let mut baseline_tps = None;
+        let mut min_tps = u64::MAX;
+        let mut degraded_checkpoints = 0;
+        let mut max_memory_kb = 0;
+        let mut max_cpu_pct = 0;
+        while start.elapsed() < self.duration {
+            checkpoint += 1;
+
+            // Transfers block this thread, so accounts and modules come from the runtime's workers
+            let stop = Arc::new(AtomicBool::new(false));
+            let side_traffic = runtime.spawn(run_side_traffic(
+                client.clone(),
+                side_account,
+                txn_factory.clone(),
+                StdRng::from_rng(&mut rng)?,
+                stop.clone(),
+            ));
+            let traffic = generate_traffic(ctx, &validators, self.checkpoint_interval, 1, None);
+            stop.store(true, Ordering::Relaxed);
+            let (account, checkpoint_side_stats) = runtime.block_on(side_traffic)?;
+            side_account = account;
+            let stats = traffic?;
+            side_stats.add(&checkpoint_side_stats);
+
+            runtime
+                .block_on(ctx.swarm().health_check())
+                .map_err(|e| anyhow!("Validators unhealthy at checkpoint {}: {}", checkpoint, e))?;
+            ctx.swarm().fork_check()?;
+
+            let usage = runtime.block_on(resource_usage(ctx))?;
+            let memory = usage.iter().max_by_key(|usage| usage.memory_kb);
+            let cpu = usage.iter().max_by_key(|usage| usage.cpu_pct);
+            max_memory_kb = max_memory_kb.max(memory.map_or(0, |u| u.memory_kb));
+            max_cpu_pct = max_cpu_pct.max(cpu.map_or(0, |u| u.cpu_pct));
+
+            let rate = stats.rate(self.checkpoint_interval);
+            let msg = format!(
+                "Checkpoint {} at {}s: {} TPS, {} ms p99 latency, {} expired, {} accounts created, \
+                {} modules published, {} failed, max memory {}, max CPU {}",
+                checkpoint,
+                start.elapsed().as_secs(),
+                rate.committed,
+                rate.p99_latency,
+                stats.expired,
+                checkpoint_side_stats.accounts_created,
+                checkpoint_side_stats.modules_published,
+                checkpoint_side_stats.failed,
+                memory.map_or("unknown".to_string(), |u| format!(
+                    "{} MB ({})",
+                    u.memory_kb / 1024,
+                    u.peer_id
+                )),
+                cpu.map_or("unknown".to_string(), |u| format!("{}% ({})", u.cpu_pct, u.peer_id)),
+            );
+            println!("{}", msg);
+            ctx.report.report_text(msg);
+
+            let baseline_tps = *baseline_tps.get_or_insert(rate.committed);
+            min_tps = min_tps.min(rate.committed);
+            if rate.committed * 100 < baseline_tps * self.min_tps_pct {
+                degraded_checkpoints += 1;
+                if degraded_checkpoints >= self.max_degraded_checkpoints {
+                    bail!(
+                        "TPS degraded for {} checkpoints in a row, down to {} from {} at the first checkpoint",
+                        degraded_checkpoints,
+                        rate.committed,
+                        baseline_tps
+                    );
+                }
+            } else {
+                degraded_checkpoints = 0;
+            }
+            total_stats = &total_stats + &stats;
+        }
+
+        if checkpoint == 0 {
+            bail!("The soak ran no checkpoint");
+        }
+        ctx.report.report_txn_stats(
+            self.name().to_string(),
+            total_stats,
+            self.checkpoint_interval * checkpoint,
+        );
+        ctx.report
+            .report_metric(self.name(), "min_checkpoint_tps", min_tps as f64);
+        ctx.report
+            .report_metric(self.name(), "max_memory_mb", (max_memory_kb / 1024) as f64);
+        ctx.report
+            .report_metric(self.name(), "max_cpu_pct", max_cpu_pct as f64);
+        ctx.report.report_text(format!(
+            "Soak passed after {} checkpoints, with {} accounts created and {} modules published",
+            checkpoint, side_stats.accounts_created, side_stats.modules_published
+        ));
+
+        Ok(())
+    }
+}
+
+/// Transactions sent alongside the transfers of the transaction emitter
+#[derive(Default)]
+struct SideTrafficStats {
+    accounts_created: u64,
+    modules_published: u64,
+    failed: u64,
+}
+
+impl SideTrafficStats {
+    fn add(&mut self, other: &SideTrafficStats) {
+        self.accounts_created += other.accounts_created;
+        self.modules_published += other.modules_published;
+        self.failed += other.failed;
+    }
+}
+
+/// Creates accounts and publishes modules from `account` until `stop` is set, and hands the
+/// account back for the next checkpoint. Failed transactions are counted rather than failing the
+/// test, which only fails when the validators themselves do.
+async fn run_side_traffic(
+    client: RestClient,
+    mut account: LocalAccount,
+    txn_factory: TransactionFactory,
+    mut rng: StdRng,
+    stop: Arc<AtomicBool>,
+) -> (LocalAccount, SideTrafficStats) {
+    let mut stats = SideTrafficStats::default();
+    while !stop.load(Ordering::Relaxed) {
+        let failed = stats.failed;
+
+        let new_account = LocalAccount::generate(&mut rng);
+        let txn = create_account_request(&mut account, new_account.public_key(), &txn_factory);
+        match client.submit_and_wait(&txn).await {
+            Ok(_) => stats.accounts_created += 1,
+            Err(_) => stats.failed += 1,
+        }
+
+        // A module can't be published twice, so every one is named after its sequence number
+        let code = module_code(account.address(), account.sequence_number());
+        let txn = account.sign_with_transaction_builder(txn_factory.module(code));
+        match client.submit_and_wait(&txn).await {
+            Ok(_) => stats.modules_published += 1,
+            Err(_) => stats.failed += 1,
+        }
+
+        if stats.failed > failed {
+            // The failed transactions may not have used their sequence numbers
+            if let Ok(onchain) = client.get_account(account.address()).await {
+                *account.sequence_number_mut() = onchain.into_inner().sequence_number;
+            }
+        }
+    }
+    (account, stats)
+}
+
+/// Code of an empty module under `address`
+fn module_code(address: AccountAddress, sequence_number: u64) -> Vec<u8> {
+    let mut module = empty_module();
+    module.address_identifiers[0] = address;
+    module.identifiers[0] =
+        Identifier::new(format!("Soak{}", sequence_number)).expect("Valid module name");
+    let mut code = vec![];
+    module
+        .serialize(&mut code)
+        .expect("Empty modules always serialize");
+    code
+}
+
+struct ResourceUsage {
+    peer_id: PeerId,
+    memory_kb: i64,
+    cpu_pct: i64,
+}
+
+/// Memory and CPU usage of every validator, as of their last metrics refresh
+async fn resource_usage(ctx: &mut NetworkContext<'_>) -> Result<Vec<ResourceUsage>> {
+    let validators = ctx.swarm().validators().collect::<Vec<_>>();
+    try_join_all(validators.iter().map(|validator| async move {
+        let memory_kb = validator
+            .get_metric("system_process_memory{}")
+            .await?
+            .unwrap_or_default();
+        let cpu_pct = validator
+            .get_metric("system_process_cpu_usage{}")
+            .await?
+            .unwrap_or_default();
+        Ok::<_, anyhow::Error>(ResourceUsage {
+            peer_id: validator.peer_id(),
+            memory_kb,
+            cpu_pct,
+        })
+    }))
+    .await
+}
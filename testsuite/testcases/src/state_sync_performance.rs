@@ -2,16 +2,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::generate_traffic;
-use forge::{NetworkContext, NetworkTest, Result, Test};
-use rand::{
-    rngs::{OsRng, StdRng},
-    seq::IteratorRandom,
-    Rng, SeedableRng,
-};
-use std::{thread, time::Instant};
+use forge::{NetworkContext, NetworkTest, Result, SwarmExt, Test};
+use rand::{rngs::StdRng, seq::IteratorRandom, SeedableRng};
+use std::time::Instant;
 use tokio::{runtime::Runtime, time::Duration};
 
 const STATE_SYNC_COMMITTED_COUNTER_NAME: &str = "aptos_state_sync_version.synced";
+/// How long the restarted fullnode gets to sync to the version of the validators
+const STATE_SYNC_TIMEOUT: Duration = Duration::from_secs(600);
 
 pub struct StateSyncPerformance;
 
@@ -23,7 +21,7 @@ impl Test for StateSyncPerformance {
 
 impl NetworkTest for StateSyncPerformance {
     fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
-        let mut rng = StdRng::from_seed(OsRng.gen());
+        let mut rng = StdRng::from_rng(ctx.core().rng())?;
         let duration = Duration::from_secs(30);
         let all_validators = ctx
             .swarm()
@@ -72,14 +70,10 @@ impl NetworkTest for StateSyncPerformance {
             validator_synced_version
         );
         let start_instant = Instant::now();
-        let fullnode_metric_port = fullnode.expose_metric()?;
-        while fullnode
-            .counter(STATE_SYNC_COMMITTED_COUNTER_NAME, fullnode_metric_port)
-            .unwrap_or(0.0)
-            < validator_synced_version
-        {
-            thread::sleep(Duration::from_secs(1));
-        }
+        runtime.block_on(ctx.swarm().wait_for_all_nodes_to_catchup_to_version(
+            validator_synced_version as u64,
+            start_instant + STATE_SYNC_TIMEOUT,
+        ))?;
         println!(
             "The fullnode has caught up to version: {}",
             validator_synced_version
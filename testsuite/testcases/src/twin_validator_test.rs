@@ -0,0 +1,78 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::generate_traffic;
+use anyhow::ensure;
+use forge::{NetworkContext, NetworkTest, NodeExt, Result, Swarm, SwarmExt, Test};
+use std::time::{Duration, Instant};
+use tokio::runtime::Runtime;
+
+const FORK_CHECK_SAMPLES: u64 = 20;
+
+/// Launches a twin of a validator, which shares its identity and keys but votes on its own, so
+/// that the two equivocate whenever they vote for different proposals of a round. Checks that the
+/// network stays live and safe: the validators never commit conflicting transactions. Whether the
+/// twins vote differently in a round depends on timing, so the equivocating votes are only
+/// reported.
+pub struct TwinValidator {
+    /// How long the twin runs alongside the validator, under traffic
+    pub duration: Duration,
+}
+
+impl Test for TwinValidator {
+    fn name(&self) -> &'static str {
+        "twin-validator"
+    }
+}
+
+impl NetworkTest for TwinValidator {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        let runtime = Runtime::new()?;
+        let validators = ctx
+            .swarm()
+            .validators()
+            .map(|v| v.peer_id())
+            .collect::<Vec<_>>();
+        // The twin is a byzantine validator, which the others only tolerate if there are 3 more
+        ensure!(
+            validators.len() >= 4,
+            "Twin validator needs 4 validators, the swarm has {}",
+            validators.len()
+        );
+        let twinned = validators[0];
+
+        let equivocations_before = runtime.block_on(equivocating_votes(ctx.swarm()))?;
+        runtime.block_on(ctx.swarm().add_twin_validator(twinned))?;
+        let stats = generate_traffic(ctx, &validators, self.duration, 1, None);
+        ctx.swarm().remove_twin_validator(twinned)?;
+        ctx.report
+            .report_txn_stats(self.name().to_string(), stats?, self.duration);
+
+        runtime.block_on(
+            ctx.swarm()
+                .liveness_check(Instant::now() + Duration::from_secs(60)),
+        )?;
+        ctx.swarm().fork_check()?;
+        // The root hash at a version covers the history up to it, so sampling the history checks
+        // every commit made while the twin voted
+        runtime.block_on(ctx.swarm().sampled_fork_check(FORK_CHECK_SAMPLES))?;
+
+        let equivocations =
+            runtime.block_on(equivocating_votes(ctx.swarm()))? - equivocations_before;
+        ctx.report
+            .report_metric(self.name(), "equivocating_votes", equivocations as f64);
+        Ok(())
+    }
+}
+
+/// Number of equivocating votes all the validators rejected
+async fn equivocating_votes(swarm: &dyn Swarm) -> Result<i64> {
+    let mut count = 0;
+    for validator in swarm.validators() {
+        count += validator
+            .get_metric("aptos_consensus_equivocating_votes_count")
+            .await?
+            .unwrap_or_default();
+    }
+    Ok(count)
+}
@@ -0,0 +1,105 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::generate_traffic;
+use anyhow::{anyhow, ensure};
+use aptos_sdk::crypto::ValidCryptoMaterialStringExt;
+use forge::{NetworkContext, NetworkTest, NodeExt, Result, SwarmExt, Test};
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+use tokio::runtime::Runtime;
+
+/// Rotates the consensus keys of some of the validators, starts a new epoch for the keys to take
+/// effect, and checks that the network stays live and keeps up with a fixed rate of traffic.
+///
+/// The keys are registered with `Stake::rotate_consensus_key` rather than through the operational
+/// tool, whose `set_validator_config_and_reconfigure` is a no-op in aptos-framework so far. For the
+/// same reason, network keys can't be rotated on chain yet and are left as is.
+pub struct ValidatorKeyRotation {
+    /// Number of validators whose consensus keys are rotated, each restarted while the key is
+    /// rotated in its storage
+    pub num_rotated: usize,
+    /// Rate of the traffic the network must keep up with once the keys are rotated
+    pub target_tps: u64,
+    pub traffic_duration: Duration,
+}
+
+impl Test for ValidatorKeyRotation {
+    fn name(&self) -> &'static str {
+        "validator-key-rotation"
+    }
+}
+
+impl NetworkTest for ValidatorKeyRotation {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        let runtime = Runtime::new()?;
+        let validators = ctx
+            .swarm()
+            .validators()
+            .map(|v| v.peer_id())
+            .collect::<Vec<_>>();
+        ensure!(
+            self.num_rotated <= validators.len(),
+            "Can't rotate the keys of {} of {} validators",
+            self.num_rotated,
+            validators.len()
+        );
+
+        let mut rotated_keys = HashMap::new();
+        for validator in &validators[..self.num_rotated] {
+            let consensus_key = runtime.block_on(ctx.rotate_consensus_key(*validator))?;
+            rotated_keys.insert(*validator, consensus_key.to_encoded_string()?);
+        }
+        let start = Instant::now();
+        runtime.block_on(ctx.reconfigure())?;
+
+        let validator_set = runtime.block_on(
+            ctx.swarm()
+                .chain_info()
+                .into_aptos_public_info()
+                .validator_set(),
+        )?;
+        for (validator, consensus_key) in &rotated_keys {
+            let registered_key = validator_set
+                .iter()
+                .find(|v| v.address == *validator)
+                .map(|v| &v.consensus_pubkey)
+                .ok_or_else(|| anyhow!("Validator {} left the validator set", validator))?;
+            ensure!(
+                registered_key == consensus_key,
+                "Validator {} has consensus key {} instead of {} in the new epoch",
+                validator,
+                registered_key,
+                consensus_key
+            );
+        }
+        runtime.block_on(
+            ctx.swarm()
+                .liveness_check(Instant::now() + Duration::from_secs(60)),
+        )?;
+        ctx.report
+            .report_metric(self.name(), "recovery_secs", start.elapsed().as_secs_f64());
+
+        let stats = generate_traffic(
+            ctx,
+            &validators,
+            self.traffic_duration,
+            1,
+            Some(self.target_tps),
+        )?;
+        let rate = stats.rate(self.traffic_duration);
+        ctx.report
+            .report_txn_stats(self.name().to_string(), stats, self.traffic_duration);
+        // The emitter falls a little short of its target even on a healthy network
+        ensure!(
+            rate.committed * 10 >= self.target_tps * 9,
+            "Only {} txn/s committed after rotating {} consensus keys, target is {}",
+            rate.committed,
+            self.num_rotated,
+            self.target_tps
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::generate_traffic;
+use anyhow::{anyhow, ensure};
+use aptos_config::config::NodeConfig;
+use aptos_sdk::types::PeerId;
+use forge::{ActiveValidator, NetworkContext, NetworkTest, NodeExt, Result, Test};
+use futures::future::try_join_all;
+use std::time::{Duration, Instant};
+use tokio::runtime::Runtime;
+
+/// Consensus round of a validator, which stops advancing once it's out of the validator set
+const CURRENT_ROUND_METRIC: &str = "aptos_consensus_current_round";
+/// How far behind the wall clock the ledger of a live validator can be
+const LIVENESS_CHECK_SECS: u64 = 10;
+
+/// Swaps a validator of the validator set for a standby one, i.e. a validator added to the swarm
+/// after the genesis: the standby registers on chain with the stake of the first, then the first
+/// leaves the set and the standby joins it at the next epoch. Checks that consensus carries on
+/// with the new validator set under traffic and that the validator which left stops taking part
+/// in it.
+pub struct ValidatorRotation {
+    /// How long the removed validator is watched to make sure it no longer votes
+    pub observation_duration: Duration,
+    pub traffic_duration: Duration,
+}
+
+impl Test for ValidatorRotation {
+    fn name(&self) -> &'static str {
+        "validator-rotation"
+    }
+}
+
+impl NetworkTest for ValidatorRotation {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        let runtime = Runtime::new()?;
+        let validator_set = runtime.block_on(validator_set(ctx))?;
+        ensure!(
+            validator_set.len() >= 2,
+            "Can't remove the only validator of the validator set"
+        );
+        let (removed, version, stake) = ctx
+            .swarm()
+            .validators()
+            .find_map(|v| {
+                validator_set
+                    .iter()
+                    .find(|active| active.address == v.peer_id())
+                    .map(|active| (v.peer_id(), v.version(), active.voting_power))
+            })
+            .ok_or_else(|| anyhow!("No validator of the validator set in the swarm"))?;
+
+        let start = Instant::now();
+        let standby = ctx
+            .swarm()
+            .add_validator(&version, NodeConfig::default_for_validator())?;
+        runtime.block_on(
+            ctx.swarm()
+                .validator_mut(standby)
+                .ok_or_else(|| anyhow!("No validator {}", standby))?
+                .wait_until_healthy(Instant::now() + Duration::from_secs(60)),
+        )?;
+        runtime.block_on(ctx.register_validator(standby, stake))?;
+        runtime.block_on(ctx.leave_validator_set(removed))?;
+        runtime.block_on(ctx.join_validator_set(standby))?;
+        runtime.block_on(ctx.reconfigure())?;
+
+        let new_validator_set = runtime.block_on(validator_set(ctx))?;
+        ensure!(
+            new_validator_set.iter().all(|v| v.address != removed),
+            "Validator {} is still in the validator set after leaving it",
+            removed
+        );
+        ensure!(
+            new_validator_set.iter().any(|v| v.address == standby),
+            "Validator {} is not in the validator set after joining it",
+            standby
+        );
+        // The standby has to catch up with the chain, which it only syncs from the validators once
+        // it's one of them, and the removed validator stalls, so only the new set has to be live
+        let new_validators = new_validator_set
+            .iter()
+            .map(|v| v.address)
+            .collect::<Vec<_>>();
+        runtime.block_on(liveness_check(
+            ctx,
+            &new_validators,
+            Instant::now() + Duration::from_secs(120),
+        ))?;
+        ctx.report
+            .report_metric(self.name(), "rotation_secs", start.elapsed().as_secs_f64());
+
+        // The removed validator is left running, it just mustn't take part in consensus anymore
+        let removed_round = runtime.block_on(current_round(ctx, removed))?;
+        std::thread::sleep(self.observation_duration);
+        let later_removed_round = runtime.block_on(current_round(ctx, removed))?;
+        ensure!(
+            later_removed_round == removed_round,
+            "Validator {} went from round {} to {} after leaving the validator set",
+            removed,
+            removed_round,
+            later_removed_round
+        );
+
+        let stats = generate_traffic(ctx, &new_validators, self.traffic_duration, 1, None)?;
+        ctx.report
+            .report_txn_stats(self.name().to_string(), stats, self.traffic_duration);
+        Ok(())
+    }
+}
+
+async fn validator_set(ctx: &mut NetworkContext<'_>) -> Result<Vec<ActiveValidator>> {
+    ctx.swarm()
+        .chain_info()
+        .into_aptos_public_info()
+        .validator_set()
+        .await
+}
+
+/// Waits for each of `validators` to be live, i.e. to have a ledger close to the wall clock
+async fn liveness_check(
+    ctx: &mut NetworkContext<'_>,
+    validators: &[PeerId],
+    deadline: Instant,
+) -> Result<()> {
+    let swarm = ctx.swarm();
+    let nodes = validators
+        .iter()
+        .map(|validator| {
+            swarm
+                .validator(*validator)
+                .ok_or_else(|| anyhow!("No validator {}", validator))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    while try_join_all(
+        nodes
+            .iter()
+            .map(|node| node.liveness_check(LIVENESS_CHECK_SECS)),
+    )
+    .await
+    .is_err()
+    {
+        if Instant::now() > deadline {
+            return Err(anyhow!("Liveness check of the new validator set timed out"));
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+    Ok(())
+}
+
+async fn current_round(ctx: &mut NetworkContext<'_>, validator: PeerId) -> Result<i64> {
+    let round = ctx
+        .swarm()
+        .validator(validator)
+        .ok_or_else(|| anyhow!("No validator {}", validator))?
+        .get_metric(CURRENT_ROUND_METRIC)
+        .await?;
+    Ok(round.unwrap_or_default())
+}
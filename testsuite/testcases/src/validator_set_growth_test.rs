@@ -0,0 +1,181 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::generate_traffic;
+use anyhow::{anyhow, ensure};
+use aptos_config::config::NodeConfig;
+use aptos_sdk::types::PeerId;
+use forge::{NetworkContext, NetworkTest, NodeExt, Result, Test};
+use std::{
+    cmp::min,
+    time::{Duration, Instant},
+};
+use tokio::runtime::Runtime;
+
+/// Grows the validator set in batches through on-chain joins, reporting at each step how long the
+/// epoch change took and the latency of the network, to find where consensus stops scaling. The
+/// validators joining are added to the swarm, which needs room for `target_validators` less the
+/// initial validators, see `ForgeConfig::with_standby_validator_count`.
+pub struct ValidatorSetGrowth {
+    /// Size of the validator set at which the test stops
+    pub target_validators: usize,
+    /// Number of validators joining at each step
+    pub batch_size: usize,
+    /// Duration of the traffic measuring the latency at each step
+    pub traffic_duration: Duration,
+}
+
+impl Test for ValidatorSetGrowth {
+    fn name(&self) -> &'static str {
+        "validator-set-growth"
+    }
+}
+
+impl NetworkTest for ValidatorSetGrowth {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        let mut num_validators = ctx.swarm().validators().count();
+        ensure!(
+            num_validators <= self.target_validators,
+            "Starting with {} validators, more than the target of {}",
+            num_validators,
+            self.target_validators
+        );
+        self.measure_latency(ctx, num_validators)?;
+
+        while num_validators < self.target_validators {
+            let batch_size = min(self.batch_size, self.target_validators - num_validators);
+            let joined = join_validators(ctx, batch_size)?;
+            let epoch = current_epoch(ctx)?;
+            let start = Instant::now();
+            Runtime::new()?.block_on(ctx.reconfigure())?;
+            wait_for_epoch(ctx, epoch + 1, Duration::from_secs(600))?;
+            let epoch_change_duration = start.elapsed();
+            num_validators += batch_size;
+
+            let validator_set = Runtime::new()?.block_on(
+                ctx.swarm()
+                    .chain_info()
+                    .into_aptos_public_info()
+                    .validator_set(),
+            )?;
+            for validator in &joined {
+                ensure!(
+                    validator_set.iter().any(|v| v.address == *validator),
+                    "Validator {} is not in the validator set after joining it",
+                    validator
+                );
+            }
+            ensure!(
+                validator_set.len() == num_validators,
+                "{} validators in the validator set, expected {}",
+                validator_set.len(),
+                num_validators
+            );
+
+            ctx.report.report_metric(
+                self.name(),
+                format!("epoch_change_secs_{}_validators", num_validators),
+                epoch_change_duration.as_secs_f64(),
+            );
+            self.measure_latency(ctx, num_validators)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl ValidatorSetGrowth {
+    fn measure_latency(&self, ctx: &mut NetworkContext<'_>, num_validators: usize) -> Result<()> {
+        let validators = ctx
+            .swarm()
+            .validators()
+            .map(|v| v.peer_id())
+            .collect::<Vec<_>>();
+        let stats = generate_traffic(ctx, &validators, self.traffic_duration, 1, None)?;
+        let rate = stats.rate(self.traffic_duration);
+        ctx.report.report_metric(
+            self.name(),
+            format!("avg_latency_ms_{}_validators", num_validators),
+            rate.latency as f64,
+        );
+        ctx.report.report_metric(
+            self.name(),
+            format!("p99_latency_ms_{}_validators", num_validators),
+            rate.p99_latency as f64,
+        );
+        ctx.report.report_text(format!(
+            "{}: {} validators, {} committed txn/s, {} ms average latency",
+            self.name(),
+            num_validators,
+            rate.committed,
+            rate.latency
+        ));
+        Ok(())
+    }
+}
+
+/// Brings up `batch_size` new validators and has them join the validator set, which takes effect
+/// at the next epoch. They run the version of the validators of the swarm and get the stake of a
+/// validator of the validator set, so that they all have the same voting power.
+fn join_validators(ctx: &mut NetworkContext<'_>, batch_size: usize) -> Result<Vec<PeerId>> {
+    let runtime = Runtime::new()?;
+    let version = ctx
+        .swarm()
+        .validators()
+        .next()
+        .ok_or_else(|| anyhow!("No validator in the swarm"))?
+        .version();
+    let stake = runtime
+        .block_on(
+            ctx.swarm()
+                .chain_info()
+                .into_aptos_public_info()
+                .validator_set(),
+        )?
+        .first()
+        .ok_or_else(|| anyhow!("No validator in the validator set"))?
+        .voting_power;
+
+    let mut joined = vec![];
+    for _ in 0..batch_size {
+        let validator = ctx
+            .swarm()
+            .add_validator(&version, NodeConfig::default_for_validator())?;
+        runtime.block_on(
+            ctx.swarm()
+                .validator_mut(validator)
+                .ok_or_else(|| anyhow!("No validator {}", validator))?
+                .wait_until_healthy(Instant::now() + Duration::from_secs(60)),
+        )?;
+        runtime.block_on(ctx.register_validator(validator, stake))?;
+        runtime.block_on(ctx.join_validator_set(validator))?;
+        joined.push(validator);
+    }
+    Ok(joined)
+}
+
+fn current_epoch(ctx: &mut NetworkContext<'_>) -> Result<u64> {
+    let client = ctx
+        .swarm()
+        .validators()
+        .next()
+        .ok_or_else(|| anyhow!("No validator in the swarm"))?
+        .rest_client();
+    let state = Runtime::new()?
+        .block_on(client.get_ledger_information())?
+        .into_inner();
+    Ok(state.epoch)
+}
+
+fn wait_for_epoch(ctx: &mut NetworkContext<'_>, epoch: u64, timeout: Duration) -> Result<()> {
+    let deadline = Instant::now() + timeout;
+    while current_epoch(ctx)? < epoch {
+        ensure!(
+            Instant::now() < deadline,
+            "Timed out waiting for epoch {}",
+            epoch
+        );
+        std::thread::sleep(Duration::from_secs(1));
+    }
+    Ok(())
+}
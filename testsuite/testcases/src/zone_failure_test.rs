@@ -0,0 +1,171 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::generate_traffic;
+use anyhow::{bail, format_err};
+use aptos_rest_client::Client as RestClient;
+use forge::{
+    fail_zone, get_validator_zones, restore_zone, NetworkContext, NetworkTest, NodeExt, Result,
+    Test,
+};
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+use tokio::runtime::Runtime;
+
+const RECOVERY_TIMEOUT: Duration = Duration::from_secs(300);
+/// How long the version has to stay the same for the chain to count as stalled
+const STALL_PERIOD: Duration = Duration::from_secs(2);
+/// How long after the pods of the zone are gone the chain has to be seen stalling
+const STALL_TIMEOUT: Duration = Duration::from_secs(60);
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Simulates the loss of a whole availability zone on k8s and asserts that the validators in the
+/// remaining zones keep committing transactions, reporting how long the chain stalled for once
+/// the pods of the zone were gone. The test fails if no stall is observed, as the recovery time
+/// couldn't be measured then.
+///
+/// Validators should be launched with `ValidatorPlacement::ZoneSpread` so that no single zone
+/// holds more than a third of the voting power.
+pub struct ZoneFailure;
+
+impl Test for ZoneFailure {
+    fn name(&self) -> &'static str {
+        "zone-failure"
+    }
+}
+
+impl NetworkTest for ZoneFailure {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        let runtime = Runtime::new()?;
+        let validator_zones = get_validator_zones()?;
+        let num_validators = ctx.swarm().validators().count();
+
+        let mut zone_sizes: HashMap<&str, usize> = HashMap::new();
+        for zone in validator_zones.values() {
+            *zone_sizes.entry(zone).or_default() += 1;
+        }
+        if zone_sizes.len() < 2 {
+            bail!("zone failure test requires validators spread over at least 2 zones");
+        }
+        let (failed_zone, failed_count) = zone_sizes
+            .iter()
+            .max_by_key(|(_, count)| **count)
+            .map(|(zone, count)| (zone.to_string(), *count))
+            .unwrap();
+        if failed_count * 3 >= num_validators {
+            bail!(
+                "zone {} holds {} of {} validators, losing it would halt the chain",
+                failed_zone,
+                failed_count,
+                num_validators
+            );
+        }
+
+        let surviving_validators = ctx
+            .swarm()
+            .validators()
+            .filter(|v| validator_zones.get(v.name()) != Some(&failed_zone))
+            .map(|v| v.peer_id())
+            .collect::<Vec<_>>();
+        let client = ctx
+            .swarm()
+            .validator(surviving_validators[0])
+            .unwrap()
+            .rest_client();
+
+        let msg = format!(
+            "Failing zone {} with {} of {} validators",
+            failed_zone, failed_count, num_validators
+        );
+        println!("{}", msg);
+        ctx.report.report_text(msg);
+        let cordoned_nodes = fail_zone(&failed_zone)?;
+
+        // The pods of the zone are gone by now, so the chain stalls on the rounds their
+        // validators lead, and has recovered once the surviving validators commit past the
+        // version it stalled at
+        let recovery = runtime.block_on(async {
+            let (stalled_version, stall_start) = wait_for_stall(&client).await?;
+            println!("Chain stalled at version {}", stalled_version);
+            let recovery_time = wait_for_recovery(&client, stalled_version, stall_start).await?;
+            Ok::<_, anyhow::Error>((stalled_version, recovery_time))
+        });
+        let traffic_result = recovery.and_then(|(stalled_version, recovery_time)| {
+            ctx.report.report_metric(
+                self.name(),
+                "recovery_time_ms",
+                recovery_time.as_millis() as f64,
+            );
+            ctx.report.report_text(format!(
+                "{} : chain stalled at version {} after losing zone {}, and recovered {:.1}s later",
+                self.name(),
+                stalled_version,
+                failed_zone,
+                recovery_time.as_secs_f64()
+            ));
+
+            let duration = Duration::from_secs(120);
+            let txn_stat = generate_traffic(ctx, &surviving_validators, duration, 1, None)?;
+            ctx.report
+                .report_txn_stats(self.name().to_string(), txn_stat, duration);
+            Ok(())
+        });
+
+        // Always bring the zone back, even if the chain failed to recover
+        restore_zone(&cordoned_nodes)?;
+        traffic_result
+    }
+}
+
+/// Polls the version of the chain until it stays the same for `STALL_PERIOD`, returning the
+/// version and when it was first seen, and fails if the chain doesn't stall within
+/// `STALL_TIMEOUT`
+async fn wait_for_stall(client: &RestClient) -> Result<(u64, Instant)> {
+    let deadline = Instant::now() + STALL_TIMEOUT;
+    let mut last_seen: Option<(u64, Instant)> = None;
+    while Instant::now() < deadline {
+        // The surviving validators may be slow to answer while the chain stalls
+        if let Ok(resp) = client.get_ledger_information().await {
+            let version = resp.into_inner().version;
+            match last_seen {
+                Some((last_version, since)) if last_version == version => {
+                    if since.elapsed() >= STALL_PERIOD {
+                        return Ok((version, since));
+                    }
+                }
+                _ => last_seen = Some((version, Instant::now())),
+            }
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+    bail!(
+        "no stall of the chain observed within {:?} of losing the zone, last seen at version {:?}",
+        STALL_TIMEOUT,
+        last_seen.map(|(version, _)| version)
+    )
+}
+
+/// Waits for the chain to commit past `stalled_version`, returning how long it stalled for
+async fn wait_for_recovery(
+    client: &RestClient,
+    stalled_version: u64,
+    stall_start: Instant,
+) -> Result<Duration> {
+    loop {
+        if let Ok(resp) = client.get_ledger_information().await {
+            if resp.into_inner().version > stalled_version {
+                return Ok(stall_start.elapsed());
+            }
+        }
+        if stall_start.elapsed() > RECOVERY_TIMEOUT {
+            return Err(format_err!(
+                "chain did not make progress past version {} within {:?}",
+                stalled_version,
+                RECOVERY_TIMEOUT
+            ));
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
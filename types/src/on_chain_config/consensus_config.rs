@@ -4,12 +4,20 @@
 use crate::on_chain_config::OnChainConfig;
 use anyhow::{format_err, Result};
 use serde::{Deserialize, Serialize};
+use std::{ops::RangeInclusive, time::Duration};
+
+/// Bounds of the block budget set on chain, so a bad config can't stall consensus. Values outside
+/// of them are clamped.
+pub const MAX_BLOCK_TXNS_BOUNDS: RangeInclusive<u64> = 1..=10_000;
+pub const MAX_BLOCK_BYTES_BOUNDS: RangeInclusive<u64> = (64 * 1024)..=(16 * 1024 * 1024);
+pub const MAX_PULL_LATENCY_MS_BOUNDS: RangeInclusive<u64> = 0..=1_000;
 
 /// The on-chain consensus config, in order to be able to add fields, we use enum to wrap the actual struct.
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum OnChainConsensusConfig {
     V1(ConsensusConfigV1),
     V2(ConsensusConfigV2),
+    V3(ConsensusConfigV3),
 }
 
 /// The public interface that exposes all values with safe fallback.
@@ -19,6 +27,7 @@ impl OnChainConsensusConfig {
         match &self {
             OnChainConsensusConfig::V1(config) => config.two_chain,
             OnChainConsensusConfig::V2(config) => config.two_chain,
+            OnChainConsensusConfig::V3(config) => config.two_chain,
         }
     }
 
@@ -26,6 +35,7 @@ impl OnChainConsensusConfig {
     pub fn leader_reputation_exclude_round(&self) -> u64 {
         match &self {
             OnChainConsensusConfig::V2(config) => config.exclude_round,
+            OnChainConsensusConfig::V3(config) => config.exclude_round,
             // default value before onchain config
             _ => 4,
         }
@@ -35,6 +45,7 @@ impl OnChainConsensusConfig {
     pub fn decoupled_execution(&self) -> bool {
         match &self {
             OnChainConsensusConfig::V2(config) => config.decoupled_execution,
+            OnChainConsensusConfig::V3(config) => config.decoupled_execution,
             _ => false,
         }
     }
@@ -48,9 +59,47 @@ impl OnChainConsensusConfig {
         }
         match &self {
             OnChainConsensusConfig::V2(config) => config.back_pressure_limit,
+            OnChainConsensusConfig::V3(config) => config.back_pressure_limit,
             _ => 10,
         }
     }
+
+    /// Max number of transactions in a proposed block, `None` to use the local config.
+    pub fn max_block_txns(&self) -> Option<u64> {
+        match &self {
+            OnChainConsensusConfig::V3(config) => {
+                Some(clamp(config.max_block_txns, MAX_BLOCK_TXNS_BOUNDS))
+            }
+            _ => None,
+        }
+    }
+
+    /// Max total size in bytes of the transactions in a proposed block, `None` to use the local
+    /// config.
+    pub fn max_block_bytes(&self) -> Option<u64> {
+        match &self {
+            OnChainConsensusConfig::V3(config) => {
+                Some(clamp(config.max_block_bytes, MAX_BLOCK_BYTES_BOUNDS))
+            }
+            _ => None,
+        }
+    }
+
+    /// How long the proposer keeps polling mempool for transactions before proposing an empty
+    /// block, `None` to use the local config.
+    pub fn max_pull_latency(&self) -> Option<Duration> {
+        match &self {
+            OnChainConsensusConfig::V3(config) => Some(Duration::from_millis(clamp(
+                config.max_pull_latency_ms,
+                MAX_PULL_LATENCY_MS_BOUNDS,
+            ))),
+            _ => None,
+        }
+    }
+}
+
+fn clamp(value: u64, bounds: RangeInclusive<u64>) -> u64 {
+    value.clamp(*bounds.start(), *bounds.end())
 }
 
 /// This is used when on-chain config is not initialized.
@@ -73,6 +122,17 @@ pub struct ConsensusConfigV2 {
     pub exclude_round: u64,
 }
 
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ConsensusConfigV3 {
+    pub two_chain: bool,
+    pub decoupled_execution: bool,
+    pub back_pressure_limit: u64,
+    pub exclude_round: u64,
+    pub max_block_txns: u64,
+    pub max_block_bytes: u64,
+    pub max_pull_latency_ms: u64,
+}
+
 impl OnChainConfig for OnChainConsensusConfig {
     const IDENTIFIER: &'static str = "ConsensusConfig";
 